@@ -0,0 +1,30 @@
+// The service is generated from a description of proto/quant.proto's RPCs
+// rather than by protoc, so building needs no protobuf toolchain. The
+// messages are hand-written in src/proto.rs; keep the three in step.
+
+use tonic_build::manual::{Builder, Method, Service};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=proto/quant.proto");
+
+    let method = |name: &str, route: &str, input: &str, output: &str| {
+        Method::builder()
+            .name(name)
+            .route_name(route)
+            .input_type(format!("crate::proto::{}", input))
+            .output_type(format!("crate::proto::{}", output))
+            .codec_path("tonic::codec::ProstCodec")
+    };
+    let service = Service::builder()
+        .name("QuantService")
+        .package("quant.v1")
+        .method(method("stream_events", "StreamEvents", "StreamRequest", "MatchEvent").server_streaming().build())
+        .method(method("stream_predictions", "StreamPredictions", "StreamRequest", "Prediction").server_streaming().build())
+        .method(method("stream_signals", "StreamSignals", "StreamRequest", "TradingSignal").server_streaming().build())
+        .method(method("get_portfolio", "GetPortfolio", "PortfolioRequest", "Portfolio").build())
+        .method(method("get_odds", "GetOdds", "OddsRequest", "MatchOdds").build())
+        .build();
+
+    Builder::new().compile(&[service]);
+}
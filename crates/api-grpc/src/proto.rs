@@ -0,0 +1,312 @@
+// Messages of proto/quant.proto, and conversions from the engine's types
+
+use chrono::{DateTime, Utc};
+use quant_models::{BestPrice as DomainBestPrice, BettingDecision, SimpleMarketOdds};
+use quant_services::{describe_bet_type, PortfolioSummary};
+
+include!(concat!(env!("OUT_DIR"), "/quant.v1.QuantService.rs"));
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct StreamRequest {
+    #[prost(string, tag = "1")]
+    pub match_id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MatchEvent {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub match_id: String,
+    #[prost(int64, tag = "3")]
+    pub timestamp_ms: i64,
+    #[prost(string, tag = "4")]
+    pub event_type: String,
+    #[prost(string, tag = "5")]
+    pub event_detail_json: String,
+    #[prost(string, tag = "6")]
+    pub team_home: String,
+    #[prost(string, tag = "7")]
+    pub team_away: String,
+    #[prost(string, tag = "8")]
+    pub league: String,
+    #[prost(string, tag = "9")]
+    pub season: String,
+    #[prost(string, tag = "10")]
+    pub match_status: String,
+    #[prost(uint32, optional, tag = "11")]
+    pub home_score: Option<u32>,
+    #[prost(uint32, optional, tag = "12")]
+    pub away_score: Option<u32>,
+    #[prost(uint64, optional, tag = "13")]
+    pub sequence: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Prediction {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub match_id: String,
+    #[prost(string, tag = "3")]
+    pub model_name: String,
+    #[prost(string, tag = "4")]
+    pub model_version: String,
+    #[prost(double, tag = "5")]
+    pub home_win_prob: f64,
+    #[prost(double, optional, tag = "6")]
+    pub draw_prob: Option<f64>,
+    #[prost(double, tag = "7")]
+    pub away_win_prob: f64,
+    #[prost(double, tag = "8")]
+    pub confidence: f64,
+    #[prost(double, optional, tag = "9")]
+    pub expected_goals_home: Option<f64>,
+    #[prost(double, optional, tag = "10")]
+    pub expected_goals_away: Option<f64>,
+    #[prost(int64, tag = "11")]
+    pub timestamp_ms: i64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Bet {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub match_id: String,
+    #[prost(string, tag = "3")]
+    pub bet_type: String,
+    #[prost(string, tag = "4")]
+    pub stake: String,
+    #[prost(string, tag = "5")]
+    pub odds: String,
+    #[prost(double, tag = "6")]
+    pub expected_value: f64,
+    #[prost(double, tag = "7")]
+    pub kelly_fraction: f64,
+    #[prost(string, tag = "8")]
+    pub strategy: String,
+    #[prost(string, tag = "9")]
+    pub status: String,
+    #[prost(string, tag = "10")]
+    pub currency: String,
+    #[prost(int64, tag = "11")]
+    pub timestamp_ms: i64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TradingSignal {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub match_id: String,
+    #[prost(double, tag = "3")]
+    pub signal_strength: f64,
+    #[prost(message, optional, tag = "4")]
+    pub recommended_bet: Option<Bet>,
+    #[prost(double, tag = "5")]
+    pub risk_score: f64,
+    #[prost(string, repeated, tag = "6")]
+    pub risk_warnings: Vec<String>,
+    #[prost(string, tag = "7")]
+    pub reasoning: String,
+    #[prost(int64, tag = "8")]
+    pub generated_at_ms: i64,
+}
+
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct PortfolioRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Portfolio {
+    #[prost(string, tag = "1")]
+    pub currency: String,
+    #[prost(string, tag = "2")]
+    pub total_bankroll: String,
+    #[prost(string, tag = "3")]
+    pub available_bankroll: String,
+    #[prost(string, tag = "4")]
+    pub total_exposure: String,
+    #[prost(uint64, tag = "5")]
+    pub active_bets_count: u64,
+    #[prost(uint64, tag = "6")]
+    pub total_trades: u64,
+    #[prost(double, tag = "7")]
+    pub roi: f64,
+    #[prost(double, tag = "8")]
+    pub win_rate: f64,
+    #[prost(string, tag = "9")]
+    pub profit_loss: String,
+    #[prost(double, tag = "10")]
+    pub max_drawdown: f64,
+    #[prost(double, tag = "11")]
+    pub sharpe_ratio: f64,
+    #[prost(message, repeated, tag = "12")]
+    pub active_bets: Vec<Bet>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OddsRequest {
+    #[prost(string, tag = "1")]
+    pub match_id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MatchWinnerOdds {
+    #[prost(string, tag = "1")]
+    pub home_win: String,
+    #[prost(string, tag = "2")]
+    pub draw: String,
+    #[prost(string, tag = "3")]
+    pub away_win: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BestPrice {
+    #[prost(string, tag = "1")]
+    pub price: String,
+    #[prost(string, tag = "2")]
+    pub bookmaker: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MatchOdds {
+    #[prost(string, tag = "1")]
+    pub match_id: String,
+    #[prost(message, optional, tag = "2")]
+    pub match_winner: Option<MatchWinnerOdds>,
+    #[prost(message, optional, tag = "3")]
+    pub best_home_win: Option<BestPrice>,
+    #[prost(message, optional, tag = "4")]
+    pub best_draw: Option<BestPrice>,
+    #[prost(message, optional, tag = "5")]
+    pub best_away_win: Option<BestPrice>,
+    #[prost(message, optional, tag = "6")]
+    pub first_half: Option<MatchWinnerOdds>,
+    #[prost(message, optional, tag = "7")]
+    pub second_half: Option<MatchWinnerOdds>,
+    #[prost(string, optional, tag = "8")]
+    pub liquidity: Option<String>,
+}
+
+fn millis(at: DateTime<Utc>) -> i64 {
+    at.timestamp_millis()
+}
+
+impl From<&quant_models::MatchEvent> for MatchEvent {
+    fn from(event: &quant_models::MatchEvent) -> Self {
+        // Unit kinds serialize to their name, the rest to `{ name: fields }`
+        let (event_type, event_detail_json) = match serde_json::to_value(&event.event_type) {
+            Ok(serde_json::Value::String(name)) => (name, String::new()),
+            Ok(serde_json::Value::Object(kind)) => kind.into_iter()
+                .next()
+                .map(|(name, detail)| (name, detail.to_string()))
+                .unwrap_or_default(),
+            _ => (format!("{:?}", event.event_type), String::new()),
+        };
+        Self {
+            id: event.id.to_string(),
+            match_id: event.match_id.clone(),
+            timestamp_ms: millis(event.timestamp),
+            event_type,
+            event_detail_json,
+            team_home: event.team_home.clone(),
+            team_away: event.team_away.clone(),
+            league: event.league.clone(),
+            season: event.season.clone(),
+            match_status: format!("{:?}", event.match_status),
+            home_score: event.score.as_ref().map(|score| u32::from(score.home)),
+            away_score: event.score.as_ref().map(|score| u32::from(score.away)),
+            sequence: event.sequence,
+        }
+    }
+}
+
+impl From<&quant_models::Prediction> for Prediction {
+    fn from(prediction: &quant_models::Prediction) -> Self {
+        Self {
+            id: prediction.id.to_string(),
+            match_id: prediction.match_id.clone(),
+            model_name: prediction.model_name.clone(),
+            model_version: prediction.model_version.clone(),
+            home_win_prob: prediction.home_win_prob,
+            draw_prob: prediction.draw_prob,
+            away_win_prob: prediction.away_win_prob,
+            confidence: prediction.confidence,
+            expected_goals_home: prediction.expected_goals_home,
+            expected_goals_away: prediction.expected_goals_away,
+            timestamp_ms: millis(prediction.prediction_timestamp),
+        }
+    }
+}
+
+impl From<&BettingDecision> for Bet {
+    fn from(bet: &BettingDecision) -> Self {
+        Self {
+            id: bet.id.to_string(),
+            match_id: bet.match_id.clone(),
+            bet_type: describe_bet_type(&bet.bet_type),
+            stake: bet.stake.to_string(),
+            odds: bet.odds.to_string(),
+            expected_value: bet.expected_value,
+            kelly_fraction: bet.kelly_fraction,
+            strategy: bet.strategy.clone(),
+            status: format!("{:?}", bet.status),
+            currency: bet.currency.to_string(),
+            timestamp_ms: millis(bet.timestamp),
+        }
+    }
+}
+
+impl From<&quant_services::TradingSignal> for TradingSignal {
+    fn from(signal: &quant_services::TradingSignal) -> Self {
+        Self {
+            id: signal.id.to_string(),
+            match_id: signal.match_id.clone(),
+            signal_strength: signal.signal_strength,
+            recommended_bet: signal.recommended_bet.as_ref().map(Bet::from),
+            risk_score: signal.risk_assessment.risk_score,
+            risk_warnings: signal.risk_assessment.warnings.clone(),
+            reasoning: signal.reasoning.clone(),
+            generated_at_ms: millis(signal.generated_at),
+        }
+    }
+}
+
+impl Portfolio {
+    pub fn new(summary: &PortfolioSummary, active_bets: &[BettingDecision]) -> Self {
+        Self {
+            currency: summary.currency.to_string(),
+            total_bankroll: summary.total_bankroll.to_string(),
+            available_bankroll: summary.available_bankroll.to_string(),
+            total_exposure: summary.total_exposure.to_string(),
+            active_bets_count: summary.active_bets_count as u64,
+            total_trades: summary.total_trades,
+            roi: summary.roi,
+            win_rate: summary.win_rate,
+            profit_loss: summary.profit_loss.to_string(),
+            max_drawdown: summary.max_drawdown,
+            sharpe_ratio: summary.sharpe_ratio,
+            active_bets: active_bets.iter().map(Bet::from).collect(),
+        }
+    }
+}
+
+impl From<&SimpleMarketOdds> for MatchWinnerOdds {
+    fn from(odds: &SimpleMarketOdds) -> Self {
+        Self {
+            home_win: odds.home_win.to_string(),
+            draw: odds.draw.to_string(),
+            away_win: odds.away_win.to_string(),
+        }
+    }
+}
+
+impl From<&DomainBestPrice> for BestPrice {
+    fn from(price: &DomainBestPrice) -> Self {
+        Self {
+            price: price.price.to_string(),
+            bookmaker: price.bookmaker.clone(),
+        }
+    }
+}
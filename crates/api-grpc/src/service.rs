@@ -0,0 +1,207 @@
+// QuantService: streams of the live feeds and queries on the portfolio and
+// odds, for clients that want less overhead than REST and JSON
+
+use crate::proto::{
+    self, quant_service_server::{QuantService, QuantServiceServer}, MatchOdds, OddsRequest, Portfolio,
+    PortfolioRequest, StreamRequest,
+};
+use quant_models::Prediction;
+use quant_services::{MarketSimulator, TradingEngine, TradingSignal};
+use quant_stream::EventBus;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+use tracing::debug;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrpcConfig {
+    pub enabled: bool,
+    /// Served on the REST API's host
+    pub port: u16,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        Self { enabled: false, port: 50051 }
+    }
+}
+
+pub type UpdateStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+#[derive(Clone)]
+pub struct QuantGrpc {
+    trading_engine: Arc<TradingEngine>,
+    market_simulator: Arc<MarketSimulator>,
+    /// Streams are served from its match event, prediction and signal topics
+    events: EventBus,
+}
+
+impl QuantGrpc {
+    pub fn new(trading_engine: Arc<TradingEngine>, market_simulator: Arc<MarketSimulator>, events: EventBus) -> Self {
+        Self { trading_engine, market_simulator, events }
+    }
+
+    pub fn into_server(self) -> QuantServiceServer<Self> {
+        QuantServiceServer::new(self)
+    }
+}
+
+/// Serve until `shutdown` completes
+pub async fn serve_grpc(
+    addr: SocketAddr,
+    service: QuantGrpc,
+    shutdown: impl Future<Output = ()>,
+) -> anyhow::Result<()> {
+    tonic::transport::Server::builder()
+        .add_service(service.into_server())
+        .serve_with_shutdown(addr, shutdown)
+        .await?;
+    Ok(())
+}
+
+/// Updates for the requested match, or every match, converted for the wire.
+/// A subscriber that falls behind skips what it missed.
+fn updates<T, M>(
+    receiver: broadcast::Receiver<T>,
+    request: StreamRequest,
+    match_id: fn(&T) -> &str,
+) -> UpdateStream<M>
+where
+    T: Clone + Send + 'static,
+    M: for<'a> From<&'a T> + Send + 'static,
+{
+    let stream = BroadcastStream::new(receiver).filter_map(move |update| match update {
+        Ok(update) if request.match_id.is_empty() || match_id(&update) == request.match_id => {
+            Some(Ok(M::from(&update)))
+        }
+        Ok(_) => None,
+        Err(BroadcastStreamRecvError::Lagged(missed)) => {
+            debug!("🛰️ gRPC subscriber fell behind, {} updates skipped", missed);
+            None
+        }
+    });
+    Box::pin(stream)
+}
+
+#[tonic::async_trait]
+impl QuantService for QuantGrpc {
+    type StreamEventsStream = UpdateStream<proto::MatchEvent>;
+    type StreamPredictionsStream = UpdateStream<proto::Prediction>;
+    type StreamSignalsStream = UpdateStream<proto::TradingSignal>;
+
+    async fn stream_events(
+        &self,
+        request: Request<StreamRequest>,
+    ) -> Result<Response<Self::StreamEventsStream>, Status> {
+        let receiver = self.events.subscribe::<quant_models::MatchEvent>();
+        Ok(Response::new(updates(receiver, request.into_inner(), |event| &event.match_id)))
+    }
+
+    async fn stream_predictions(
+        &self,
+        request: Request<StreamRequest>,
+    ) -> Result<Response<Self::StreamPredictionsStream>, Status> {
+        let receiver = self.events.subscribe::<Prediction>();
+        Ok(Response::new(updates(receiver, request.into_inner(), |prediction| &prediction.match_id)))
+    }
+
+    async fn stream_signals(
+        &self,
+        request: Request<StreamRequest>,
+    ) -> Result<Response<Self::StreamSignalsStream>, Status> {
+        let receiver = self.events.subscribe::<TradingSignal>();
+        Ok(Response::new(updates(receiver, request.into_inner(), |signal| &signal.match_id)))
+    }
+
+    async fn get_portfolio(&self, _request: Request<PortfolioRequest>) -> Result<Response<Portfolio>, Status> {
+        let summary = self.trading_engine.get_portfolio_summary().await;
+        let active_bets = self.trading_engine.get_active_bets().await;
+        Ok(Response::new(Portfolio::new(&summary, &active_bets)))
+    }
+
+    async fn get_odds(&self, request: Request<OddsRequest>) -> Result<Response<MatchOdds>, Status> {
+        let match_id = request.into_inner().match_id;
+        let match_winner = self.market_simulator.get_current_odds(&match_id).await;
+        let best = self.trading_engine.get_odds_book(&match_id).await.and_then(|book| book.best());
+        if match_winner.is_none() && best.is_none() {
+            return Err(Status::not_found(format!("no odds for match {}", match_id)));
+        }
+        let halves = self.market_simulator.get_half_result_odds(&match_id).await;
+        let liquidity = self.market_simulator.get_liquidity(&match_id).await;
+
+        Ok(Response::new(MatchOdds {
+            match_winner: match_winner.as_ref().map(Into::into),
+            best_home_win: best.as_ref().map(|best| (&best.home_win).into()),
+            best_draw: best.as_ref().and_then(|best| best.draw.as_ref()).map(Into::into),
+            best_away_win: best.as_ref().map(|best| (&best.away_win).into()),
+            first_half: halves.as_ref().and_then(|halves| halves.first_half.as_ref()).map(Into::into),
+            second_half: halves.as_ref().map(|halves| (&halves.second_half).into()),
+            liquidity: liquidity.map(|liquidity| liquidity.to_string()),
+            match_id,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::quant_service_client::QuantServiceClient;
+    use crate::proto::MatchEvent;
+    use quant_models::EventType;
+    use rust_decimal_macros::dec;
+    use tokio_stream::wrappers::TcpListenerStream;
+
+    fn goal(match_id: &str) -> quant_models::MatchEvent {
+        quant_models::MatchEvent::new(
+            match_id.to_string(),
+            EventType::Goal { team: "Arsenal".to_string(), player: None, minute: 12 },
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_streams_and_queries_over_the_wire() {
+        let trading_engine = Arc::new(TradingEngine::new(dec!(1000)));
+        let market_simulator = Arc::new(MarketSimulator::new().with_seed(7));
+        let bus = EventBus::new();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let service = QuantGrpc::new(trading_engine, market_simulator.clone(), bus.clone());
+        tokio::spawn(tonic::transport::Server::builder()
+            .add_service(service.into_server())
+            .serve_with_incoming(TcpListenerStream::new(listener)));
+        let mut client = QuantServiceClient::connect(format!("http://{}", addr)).await.unwrap();
+
+        // Only the requested match's events come through
+        let mut events = client.stream_events(StreamRequest { match_id: "m1".to_string() }).await
+            .unwrap()
+            .into_inner();
+        bus.publish(goal("m2"));
+        bus.publish(goal("m1"));
+        let event: MatchEvent = events.message().await.unwrap().unwrap();
+        assert_eq!((event.match_id.as_str(), event.event_type.as_str()), ("m1", "Goal"));
+        let detail: serde_json::Value = serde_json::from_str(&event.event_detail_json).unwrap();
+        assert_eq!(detail["minute"], 12);
+
+        let portfolio = client.get_portfolio(PortfolioRequest {}).await.unwrap().into_inner();
+        assert_eq!((portfolio.available_bankroll.as_str(), portfolio.active_bets_count), ("1000", 0));
+
+        let missing = client.get_odds(OddsRequest { match_id: "m1".to_string() }).await.unwrap_err();
+        assert_eq!(missing.code(), tonic::Code::NotFound);
+        market_simulator.generate_market_odds(&goal("m1")).await.unwrap();
+        let odds = client.get_odds(OddsRequest { match_id: "m1".to_string() }).await.unwrap().into_inner();
+        assert!(odds.match_winner.is_some() && odds.first_half.is_some() && odds.second_half.is_some());
+        assert!(odds.best_home_win.is_none());
+    }
+}
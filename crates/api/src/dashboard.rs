@@ -0,0 +1,138 @@
+// Embedded operator dashboard: one HTML page fed by Server-Sent Events
+
+use crate::routes::{AppState, PortfolioResponse};
+use axum::{
+    extract::{Query, State},
+    response::{sse::{Event, KeepAlive, Sse}, Html},
+};
+use chrono::{DateTime, Utc};
+use quant_models::{EventType, MatchEvent, MatchStatus, Prediction, Score, SimpleMarketOdds};
+use quant_services::{FeedStatus, SystemMetrics};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+const DASHBOARD_HTML: &str = include_str!("../assets/dashboard.html");
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_PREDICTIONS: usize = 10;
+const MAX_PREDICTIONS: usize = 50;
+
+#[derive(Deserialize)]
+pub struct DashboardParams {
+    /// Most recent predictions to include
+    pub predictions: Option<usize>,
+}
+
+/// A match that has kicked off and not yet finished, as of its latest event
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveMatch {
+    pub match_id: String,
+    pub team_home: String,
+    pub team_away: String,
+    pub league: String,
+    pub status: MatchStatus,
+    pub score: Option<Score>,
+    pub last_event_at: DateTime<Utc>,
+}
+
+/// A prediction with the prices it implies next to those the market offers
+#[derive(Serialize)]
+pub struct PricedPrediction {
+    #[serde(flatten)]
+    pub prediction: Prediction,
+    pub fair_odds: SimpleMarketOdds,
+    pub market_odds: Option<SimpleMarketOdds>,
+}
+
+/// What the dashboard shows, pushed on every tick
+#[derive(Serialize)]
+pub struct DashboardSnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub portfolio: PortfolioResponse,
+    pub predictions: Vec<PricedPrediction>,
+    pub active_matches: Vec<ActiveMatch>,
+    pub metrics: SystemMetrics,
+    pub feed: FeedStatus,
+}
+
+impl DashboardSnapshot {
+    pub async fn capture(state: &AppState, predictions: usize) -> Self {
+        let portfolio = PortfolioResponse::from(state.trading_engine.get_portfolio_summary().await);
+        let recent: Vec<Prediction> = state.recent_predictions.read().await
+            .iter().rev().take(predictions).cloned().collect();
+        let mut predictions = Vec::with_capacity(recent.len());
+        for prediction in recent {
+            predictions.push(PricedPrediction {
+                fair_odds: prediction.to_fair_odds(0.0),
+                market_odds: state.trading_engine.get_market_odds(&prediction.match_id).await,
+                prediction,
+            });
+        }
+        let active_matches = active_matches(&state.recent_events.read().await);
+
+        Self {
+            timestamp: Utc::now(),
+            portfolio,
+            predictions,
+            active_matches,
+            metrics: state.metrics.get_current_metrics().await,
+            feed: state.feed_control.status(),
+        }
+    }
+}
+
+/// Matches whose latest event in `events` has them live or at half time,
+/// most recently active first
+pub fn active_matches(events: &[MatchEvent]) -> Vec<ActiveMatch> {
+    let mut latest: HashMap<&str, &MatchEvent> = HashMap::new();
+    for event in events {
+        latest.insert(&event.match_id, event);
+    }
+
+    let mut active: Vec<ActiveMatch> = latest.into_values()
+        .filter(|event| matches!(event.match_status, MatchStatus::Live | MatchStatus::HalfTime))
+        .filter(|event| !matches!(event.event_type, EventType::FullTime | EventType::MatchEnd))
+        .map(|event| ActiveMatch {
+            match_id: event.match_id.clone(),
+            team_home: event.team_home.clone(),
+            team_away: event.team_away.clone(),
+            league: event.league.clone(),
+            status: event.match_status.clone(),
+            score: event.score.clone(),
+            last_event_at: event.timestamp,
+        })
+        .collect();
+    active.sort_by_key(|event| Reverse(event.last_event_at));
+    active
+}
+
+// The dashboard page itself
+pub async fn dashboard_page() -> Html<&'static str> {
+    Html(DASHBOARD_HTML)
+}
+
+// A snapshot every couple of seconds until the client goes away
+pub async fn dashboard_stream(
+    Query(params): Query<DashboardParams>,
+    State(state): State<AppState>,
+) -> Sse<ReceiverStream<Result<Event, axum::Error>>> {
+    let predictions = params.predictions.unwrap_or(DEFAULT_PREDICTIONS).min(MAX_PREDICTIONS);
+    let (sender, receiver) = mpsc::channel(1);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SNAPSHOT_INTERVAL);
+        loop {
+            interval.tick().await;
+            let snapshot = DashboardSnapshot::capture(&state, predictions).await;
+            let event = Event::default().event("snapshot").json_data(&snapshot);
+            if sender.send(event).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    Sse::new(ReceiverStream::new(receiver)).keep_alive(KeepAlive::default())
+}
@@ -2,7 +2,10 @@ pub mod routes;
 pub mod handlers;
 pub mod websocket;
 pub mod middleware;
+pub mod dashboard;
 
 pub use routes::*;
 pub use handlers::*;
-pub use websocket::*;
\ No newline at end of file
+pub use websocket::*;
+pub use dashboard::*;
+pub use middleware::*;
\ No newline at end of file
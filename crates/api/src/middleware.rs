@@ -1,9 +1,190 @@
 // Middleware for authentication, logging, etc.
 
-pub struct AuthMiddleware;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use quant_services::MetricsCollector;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, debug_span, Instrument};
+use uuid::Uuid;
+
+/// Header a client can identify itself with, so it is limited on its own
+/// rather than with everyone behind the same address once the key checks out
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Buckets kept before full (idle) ones are dropped
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// The API keys clients are known by
+#[derive(Debug, Clone, Default)]
+pub struct AuthMiddleware {
+    keys: HashSet<String>,
+}
+
+/// Set on a request whose `X-API-Key` is one of the configured keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedApiKey(pub String);
 
 impl AuthMiddleware {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    pub fn with_keys(mut self, keys: impl IntoIterator<Item = String>) -> Self {
+        self.keys.extend(keys);
+        self
+    }
+
+    pub fn verify(&self, key: &str) -> bool {
+        self.keys.contains(key)
+    }
+}
+
+/// Mark requests carrying a known API key with [`VerifiedApiKey`]. Requests
+/// without one still go through, treated as anonymous.
+pub async fn authenticate(State(auth): State<Arc<AuthMiddleware>>, mut request: Request, next: Next) -> Response {
+    let key = request.headers().get(API_KEY_HEADER)
+        .and_then(|key| key.to_str().ok())
+        .filter(|key| auth.verify(key))
+        .map(str::to_string);
+    if let Some(key) = key {
+        request.extensions_mut().insert(VerifiedApiKey(key));
     }
-}
\ No newline at end of file
+    next.run(request).await
+}
+
+/// Run each request in its own span, so the database and Redis calls made
+/// while handling it are logged against it, and log how long it took
+pub async fn trace_request(request: Request, next: Next) -> Response {
+    let span = debug_span!(
+        "request",
+        id = %Uuid::new_v4(),
+        method = %request.method(),
+        path = %request.uri().path(),
+    );
+
+    async move {
+        let started = Instant::now();
+        let response = next.run(request).await;
+        debug!(status = response.status().as_u16(),
+               duration_ms = started.elapsed().as_secs_f64() * 1000.0,
+               "🌐 Request handled");
+        response
+    }
+    .instrument(span)
+    .await
+}
+
+/// Per-client request budgets for the API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// Sustained requests per second allowed to each client
+    pub requests_per_second: f64,
+    /// Requests a client that has been idle may make at once
+    pub burst: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self { enabled: true, requests_per_second: 10.0, burst: 40 }
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn refill(&mut self, now: Instant, rate: f64, burst: f64) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(burst);
+        self.updated = now;
+    }
+}
+
+/// Token bucket per client, keyed by verified API key or else by address
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+    metrics: Option<Arc<MetricsCollector>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self { config, buckets: Mutex::new(HashMap::new()), metrics: None }
+    }
+
+    /// Count throttled requests in `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Spend one of `client`'s tokens, or say how long until it has one
+    pub fn acquire(&self, client: &str, now: Instant) -> Result<(), Duration> {
+        let rate = self.config.requests_per_second.max(f64::MIN_POSITIVE);
+        let burst = f64::from(self.config.burst.max(1));
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(client) {
+            buckets.retain(|_, bucket| {
+                bucket.refill(now, rate, burst);
+                bucket.tokens < burst
+            });
+        }
+
+        let bucket = buckets.entry(client.to_string())
+            .or_insert_with(|| TokenBucket { tokens: burst, updated: now });
+        bucket.refill(now, rate, burst);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+}
+
+/// The client's API key if [`authenticate`] verified it, or else its IP
+/// address, so a made-up key can't buy a fresh budget
+fn client_key(request: &Request) -> String {
+    if let Some(VerifiedApiKey(key)) = request.extensions().get::<VerifiedApiKey>() {
+        return format!("key:{}", key);
+    }
+    request.extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map_or_else(|| "unknown".to_string(), |ConnectInfo(address)| format!("ip:{}", address.ip()))
+}
+
+/// Turn away clients over their budget with 429 and a `Retry-After` in
+/// seconds. Health checks are never limited.
+pub async fn rate_limit(State(limiter): State<Arc<RateLimiter>>, request: Request, next: Next) -> Response {
+    if !limiter.config.enabled || request.uri().path() == "/health" {
+        return next.run(request).await;
+    }
+
+    let Err(retry_after) = limiter.acquire(&client_key(&request), Instant::now()) else {
+        return next.run(request).await;
+    };
+    if let Some(metrics) = &limiter.metrics {
+        metrics.increment_throttled_requests().await;
+    }
+    debug!(path = %request.uri().path(), "🚦 Request throttled");
+
+    let seconds = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, seconds.to_string())],
+        "Rate limit exceeded",
+    ).into_response()
+}
@@ -1,16 +1,42 @@
 use axum::{
     Router, 
-    routing::{get, post},
+    middleware,
+    routing::{delete, get, post},
     extract::{Query, Path, State},
     response::Json,
     http::StatusCode,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use quant_services::{TradingEngine, MarketSimulator, PredictorService};
-use quant_models::{MatchEvent, Prediction, SimpleMarketOdds};
+use quant_services::{
+    TradingEngine, MarketSimulator, PredictorService, PredictionLedger, PortfolioConcentration, OddsHistory,
+    HistoryFilter, TradingSignal, MatchSummaryTracker, FeedControl, FeedStatus, SimulatedFixture, FeatureStore,
+    SearchIndex, SearchResult, SearchResultKind, MetricsCollector, PerformanceStats, SystemMetrics,
+    StrategyAttribution, PortfolioSummary, ExposureReport, StateRebuilder, RebuildProgress, RebuildState,
+    EventReplayer, ReplayReport, EventLog, EventPage, OddsSeries,
+    DailyLossStatus, ValueBetFilter, ValueBetOpportunity, score_matrix_for, describe_bet_type, PredictionHistory, PredictionPoint, Provenance,
+    Fixture, FixtureService, SimulatedTrade, TradeSimulationParams, Job, JobQueue, JobStatus,
+};
+use quant_models::{
+    MatchEvent, Prediction, BettingDecision, BetSlip, SimpleMarketOdds, BookmakerPrice, BestOdds, BetType, Promotion, PromotionKind,
+    DerivedMarketOdds, EquityPoint, MatchSummary, AsianHandicapOdds, CorrectScoreOdds, HalfResultOdds, TeamNameMatch,
+    TeamNameNormalizer, PredictionFeatures, SlowQuery, TeamRating, Currency, EventCursor, EventQuery,
+    OddsConversion, OddsStyle, BankrollAdjustment, QuantsError,
+};
+use quant_ml::{EvaluationReport, Evaluator, LeagueTable, XgTimeline};
+use quant_stream::DomainEventBus;
+use crate::dashboard::{dashboard_page, dashboard_stream};
+use crate::middleware::trace_request;
+use crate::websocket::{blotter_updates, domain_events, prediction_updates};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use uuid::Uuid;
+
+const DEFAULT_DRAWDOWN_WINDOW: usize = 50;
+/// Lowest similarity a team search result may have
+const MIN_TEAM_SEARCH_SCORE: f64 = 0.75;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -19,6 +45,21 @@ pub struct AppState {
     pub predictor: Arc<PredictorService>,
     pub recent_events: Arc<RwLock<Vec<MatchEvent>>>,
     pub recent_predictions: Arc<RwLock<Vec<Prediction>>>,
+    pub prediction_history: Arc<PredictionHistory>,
+    pub prediction_ledger: Arc<PredictionLedger>,
+    pub odds_history: Arc<OddsHistory>,
+    pub match_summaries: Arc<MatchSummaryTracker>,
+    pub feature_store: Arc<FeatureStore>,
+    pub search_index: Arc<SearchIndex>,
+    pub metrics: Arc<MetricsCollector>,
+    pub feed_control: FeedControl,
+    pub rebuilder: Arc<StateRebuilder>,
+    pub replayer: Arc<EventReplayer>,
+    pub event_log: Arc<EventLog>,
+    pub domain_events: DomainEventBus,
+    pub provenance: Arc<Provenance>,
+    pub fixtures: Arc<FixtureService>,
+    pub jobs: Arc<JobQueue>,
 }
 
 #[derive(Deserialize)]
@@ -51,23 +92,340 @@ pub struct HealthResponse {
     pub uptime: String,
 }
 
+#[derive(Deserialize)]
+pub struct EvaluationRequest {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct FixturesParams {
+    /// UTC day, e.g. `2025-03-01`; today when left out
+    pub date: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct JobsParams {
+    /// `pending`, `running`, `succeeded` or `failed`; all when left out
+    pub status: Option<JobStatus>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct PromotionRequest {
+    pub bookmaker: String,
+    pub kind: PromotionKind,
+    pub expires_at: DateTime<Utc>,
+    pub match_id: Option<String>,
+    pub bet_type: Option<BetType>,
+    pub min_odds: Option<Decimal>,
+}
+
+/// A bet slip to place as one accumulator
+#[derive(Deserialize)]
+pub struct AccumulatorRequest {
+    pub stake: Decimal,
+    pub legs: Vec<AccumulatorLegRequest>,
+    pub strategy: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AccumulatorLegRequest {
+    pub match_id: String,
+    pub bet_type: BetType,
+    pub odds: Decimal,
+    /// Chance the leg wins; the odds' implied probability if unset
+    pub probability: Option<f64>,
+}
+
+/// A trade to size without placing it, on the match's latest prediction
+/// unless a hypothetical one is given
+#[derive(Deserialize)]
+pub struct TradeSimulationRequest {
+    pub match_id: String,
+    pub prediction: Option<HypotheticalPrediction>,
+    /// Strategy preset to size with; the active strategy if unset
+    pub strategy: Option<String>,
+    /// Only this match winner outcome
+    pub bet_type: Option<BetType>,
+    /// Price to size at instead of the best quoted one
+    pub odds: Option<Decimal>,
+}
+
+/// The draw takes whatever probability is left
+#[derive(Deserialize)]
+pub struct HypotheticalPrediction {
+    pub home_win_prob: f64,
+    pub away_win_prob: f64,
+    /// Fully confident if unset
+    pub confidence: Option<f64>,
+}
+
+/// Body of a deposit or withdrawal; `amount` is always positive
+#[derive(Deserialize)]
+pub struct BankrollAdjustmentRequest {
+    pub amount: Decimal,
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct EventListParams {
+    pub limit: Option<usize>,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+    pub league: Option<String>,
+    /// Either side of the match
+    pub team: Option<String>,
+    /// e.g. `goal`, `card` or `match_start`
+    pub event_type: Option<String>,
+    /// Inclusive lower bound on when the event happened
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound
+    pub to: Option<DateTime<Utc>>,
+    pub match_id: Option<String>,
+}
+
+impl EventListParams {
+    fn query(&self) -> Result<EventQuery, StatusCode> {
+        let after = self.cursor.as_deref()
+            .map(str::parse::<EventCursor>)
+            .transpose()
+            .map_err(|_| StatusCode::BAD_REQUEST)?;
+        Ok(EventQuery {
+            league: self.league.clone(),
+            team: self.team.clone(),
+            event_type: self.event_type.clone(),
+            from: self.from,
+            to: self.to,
+            match_id: self.match_id.clone(),
+            after,
+            limit: self.limit.unwrap_or(50).clamp(1, 100),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+pub struct OddsConvertParams {
+    /// One price, e.g. `2.5`, `+150` or `3/2`
+    pub odds: String,
+    /// How `odds` is written; decimal unless given
+    pub from: Option<OddsStyle>,
+}
+
+#[derive(Deserialize)]
+pub struct SearchParams {
+    pub q: String,
+    /// Only return this kind of entity
+    pub kind: Option<SearchResultKind>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct TeamSearchParams {
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct OddsHistoryParams {
+    /// Point in time to reconstruct; without it the price series is listed
+    pub at: Option<DateTime<Utc>>,
+    pub bookmaker: Option<String>,
+    /// Inclusive lower bound on when the price was quoted
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound; defaults to now
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct HistoryParams {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+    pub match_id: Option<String>,
+    pub strategy: Option<String>,
+    /// Inclusive lower bound on when the trade or signal happened
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl HistoryParams {
+    fn filter(&self) -> HistoryFilter {
+        HistoryFilter {
+            match_id: self.match_id.clone(),
+            strategy: self.strategy.clone(),
+            from: self.from,
+            to: self.to,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ValueBetParams {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+    /// Least the predicted probability must beat the implied one by, e.g. 0.02
+    pub min_edge: Option<f64>,
+    pub league: Option<String>,
+}
+
+impl ValueBetParams {
+    fn filter(&self) -> ValueBetFilter {
+        ValueBetFilter {
+            min_edge: self.min_edge.unwrap_or(0.0),
+            league: self.league.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct SimulationStartRequest {
+    pub speed_multiplier: Option<f64>,
+    /// Replace the simulated matches; they kick off straight away unless given a `kickoff`
+    pub matches: Option<Vec<SimulatedFixture>>,
+    /// Restart the current matches from kick-off
+    #[serde(default)]
+    pub reset: bool,
+}
+
+#[derive(Deserialize)]
+pub struct SimulationSpeedRequest {
+    pub multiplier: f64,
+}
+
+#[derive(Deserialize)]
+pub struct AddMatchesRequest {
+    pub matches: Vec<SimulatedFixture>,
+}
+
+#[derive(Deserialize)]
+pub struct FastForwardRequest {
+    pub minutes: u8,
+}
+
+#[derive(Serialize)]
+pub struct OddsBookResponse {
+    pub match_id: String,
+    pub bookmakers: Vec<BookmakerPrice>,
+    pub best: Option<BestOdds>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum OddsHistoryResponse {
+    /// Every bookmaker's prices at one moment
+    Snapshot(Box<OddsBookResponse>),
+    /// Each bookmaker's price moves over a period
+    Series { match_id: String, bookmakers: Vec<OddsSeries> },
+}
+
 #[derive(Serialize)]
 pub struct PortfolioResponse {
+    pub currency: Currency,
     pub total_bankroll: String,
     pub available_bankroll: String,
     pub total_exposure: String,
     pub active_bets_count: usize,
     pub total_trades: u64,
     pub roi: f64,
+    pub return_on_capital: f64,
     pub win_rate: f64,
     pub profit_loss: String,
+    pub max_drawdown: f64,
+    pub sharpe_ratio: f64,
+    pub concentration: PortfolioConcentration,
+    pub exposure: ExposureReport,
+    /// Open stake per currency, before conversion
+    pub exposure_by_currency: BTreeMap<Currency, String>,
+    /// Each strategy book's own results, by preset name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub strategies: BTreeMap<String, PortfolioResponse>,
+}
+
+impl From<PortfolioSummary> for PortfolioResponse {
+    fn from(summary: PortfolioSummary) -> Self {
+        Self {
+            currency: summary.currency,
+            total_bankroll: summary.total_bankroll.to_string(),
+            available_bankroll: summary.available_bankroll.to_string(),
+            total_exposure: summary.total_exposure.to_string(),
+            active_bets_count: summary.active_bets_count,
+            total_trades: summary.total_trades,
+            roi: summary.roi,
+            return_on_capital: summary.return_on_capital,
+            win_rate: summary.win_rate,
+            profit_loss: summary.profit_loss.to_string(),
+            max_drawdown: summary.max_drawdown,
+            sharpe_ratio: summary.sharpe_ratio,
+            concentration: summary.concentration,
+            exposure: summary.exposure,
+            exposure_by_currency: summary.exposure_by_currency.into_iter()
+                .map(|(currency, stake)| (currency, stake.to_string()))
+                .collect(),
+            strategies: summary.strategies.into_iter()
+                .map(|(preset, book)| (preset, book.into()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct EquityHistoryParams {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Settlements the rolling drawdown looks back over
+    pub window: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct EquityHistoryResponse {
+    pub points: Vec<EquityPoint>,
+    pub max_drawdown: f64,
+    pub current_drawdown: f64,
+    pub rolling_max_drawdown: f64,
+    pub sharpe_ratio: f64,
+    pub sortino_ratio: f64,
+}
+
+#[derive(Serialize)]
+pub struct ScorelineResponse {
+    pub match_id: String,
+    pub model_name: String,
+    pub model_version: String,
+    pub predicted_at: DateTime<Utc>,
+    /// False when the model predicts no goals and the grid was fitted to its
+    /// match winner probabilities
+    pub from_goal_model: bool,
+    pub max_goals: u8,
+    /// Indexed by home goals, then away goals
+    pub probabilities: Vec<Vec<f64>>,
+    /// Every score in the grid, most likely first
+    pub scorelines: Vec<Scoreline>,
+    /// Probability of a score outside the grid
+    pub other: f64,
+}
+
+#[derive(Serialize)]
+pub struct Scoreline {
+    pub home_goals: u8,
+    pub away_goals: u8,
+    pub probability: f64,
+    /// Current correct score price, where the market quotes one
+    pub market_price: Option<Decimal>,
+    /// Expected return per unit staked at that price
+    pub edge: Option<f64>,
 }
 
 pub fn create_routes() -> Router<AppState> {
     Router::new()
         // Health and status
         .route("/health", get(health_check))
+        .route("/dashboard", get(dashboard_page))
+        .route("/dashboard/stream", get(dashboard_stream))
         .route("/api/v1/status", get(get_system_status))
+        .route("/api/v1/metrics", get(get_metrics))
+        .route("/api/v1/metrics/slow-queries", get(get_slow_queries))
         
         // Live data endpoints
         .route("/api/v1/events", get(get_recent_events))
@@ -76,24 +434,69 @@ pub fn create_routes() -> Router<AppState> {
         // Predictions
         .route("/api/v1/predictions", get(get_recent_predictions))
         .route("/api/v1/predictions/:match_id", get(get_prediction_by_match))
+        .route("/api/v1/predictions/:prediction_id/features", get(get_prediction_features))
+        .route("/api/v1/predictions/:match_id/history", get(get_prediction_history))
+        .route("/ws/predictions", get(prediction_updates))
+        .route("/ws/events", get(domain_events))
+        .route("/ws/blotter", get(blotter_updates))
+        .route("/api/v1/fixtures", get(get_fixtures))
+        .route("/api/v1/matches/:match_id/summary", get(get_match_summary))
+        .route("/api/v1/matches/:match_id/scorelines", get(get_scorelines))
+        .route("/api/v1/matches/:match_id/xg", get(get_xg_timeline))
+        .route("/api/v1/teams/search", get(search_teams))
+        .route("/api/v1/teams/ratings", get(get_team_ratings))
+        .route("/api/v1/teams/:team/rating", get(get_team_rating))
+        .route("/api/v1/leagues/:league/table", get(get_league_table))
+        .route("/api/v1/search", get(search))
         
         // Market data
         .route("/api/v1/odds/:match_id", get(get_market_odds))
+        .route("/api/v1/odds/:match_id/book", get(get_odds_book))
+        .route("/api/v1/odds/:match_id/derived", get(get_derived_odds))
+        .route("/api/v1/odds/:match_id/asian-handicap", get(get_asian_handicap_odds))
+        .route("/api/v1/odds/:match_id/correct-score", get(get_correct_score_odds))
+        .route("/api/v1/odds/:match_id/half-result", get(get_half_result_odds))
+        .route("/api/v1/odds/:match_id/history", get(get_odds_history))
         .route("/api/v1/markets", get(get_all_markets))
+        .route("/api/v1/value-bets", get(get_value_bets))
+        .route("/api/v1/tools/odds/convert", get(convert_odds))
         
         // Trading and portfolio
         .route("/api/v1/portfolio", get(get_portfolio))
+        .route("/api/v1/portfolio/history", get(get_portfolio_history))
+        .route("/api/v1/portfolio/daily-loss", get(get_daily_loss))
+        .route("/api/v1/portfolio/deposit", post(deposit_bankroll))
+        .route("/api/v1/portfolio/withdraw", post(withdraw_bankroll))
+        .route("/api/v1/portfolio/adjustments", get(get_bankroll_adjustments))
         .route("/api/v1/trades", get(get_recent_trades))
         .route("/api/v1/trades/signals", get(get_trading_signals))
+        .route("/api/v1/trades/attribution", get(get_strategy_attribution))
+        .route("/api/v1/trades/accumulator", post(place_accumulator))
+        .route("/api/v1/simulate/trade", post(simulate_trade))
+        
+        // Promotions
+        .route("/api/v1/promotions", get(get_promotions).post(register_promotion))
+        .route("/api/v1/promotions/:id", delete(remove_promotion))
         
         // Analytics
         .route("/api/v1/analytics/performance", get(get_performance_analytics))
         .route("/api/v1/analytics/models", get(get_model_performance))
         
+        // Admin
+        .route("/api/v1/admin/models/:name/evaluate", post(evaluate_model))
+        .route("/api/v1/admin/rebuild", get(get_rebuild_progress).post(start_rebuild))
+        .route("/api/v1/replay", post(replay_match))
+        .route("/api/v1/jobs", get(get_jobs))
+        
         // Simulation controls
         .route("/api/v1/simulation/start", post(start_simulation))
         .route("/api/v1/simulation/stop", post(stop_simulation))
         .route("/api/v1/simulation/status", get(get_simulation_status))
+        .route("/api/v1/simulation/speed", post(set_simulation_speed))
+        .route("/api/v1/simulation/matches", post(add_simulated_matches))
+        .route("/api/v1/simulation/matches/:match_id/end", post(end_simulated_match))
+        .route("/api/v1/simulation/matches/:match_id/fast-forward", post(fast_forward_simulated_match))
+        .layer(middleware::from_fn(trace_request))
 }
 
 // Health check endpoint
@@ -118,7 +521,8 @@ async fn get_system_status(State(state): State<AppState>) -> Json<ApiResponse<se
         "available": portfolio.available_bankroll.to_string(),
         "active_bets": portfolio.active_bets_count,
         "total_trades": portfolio.total_trades,
-        "roi": format!("{:.2}%", portfolio.roi * 100.0)
+        "roi": format!("{:.2}%", portfolio.roi * 100.0),
+        "diversification_score": portfolio.concentration.diversification_score
     }));
     status.insert("data_pipeline".to_string(), serde_json::json!({
         "recent_events": events_count,
@@ -130,6 +534,7 @@ async fn get_system_status(State(state): State<AppState>) -> Json<ApiResponse<se
         "predictor": "online", 
         "market_simulator": "online"
     }));
+    status.insert("provenance".to_string(), serde_json::to_value(state.provenance.as_ref()).unwrap_or_default());
     
     Json(ApiResponse {
         success: true,
@@ -139,36 +544,53 @@ async fn get_system_status(State(state): State<AppState>) -> Json<ApiResponse<se
     })
 }
 
+#[derive(Serialize)]
+pub struct MetricsResponse {
+    pub system: SystemMetrics,
+    pub performance: PerformanceStats,
+}
+
+// Counters, throughput and latency percentiles per operation
+async fn get_metrics(State(state): State<AppState>) -> Json<ApiResponse<MetricsResponse>> {
+    let system = state.metrics.get_current_metrics().await;
+    let performance = state.metrics.get_performance_stats().await;
+
+    Json(ApiResponse {
+        success: true,
+        data: Some(MetricsResponse { system, performance }),
+        message: None,
+        pagination: None,
+    })
+}
+
+// Database and Redis calls over the slow query threshold, most recent first
+async fn get_slow_queries(State(state): State<AppState>) -> Json<ApiResponse<Vec<SlowQuery>>> {
+    Json(ApiResponse {
+        success: true,
+        data: Some(state.metrics.get_slow_queries()),
+        message: None,
+        pagination: None,
+    })
+}
+
 // Get recent match events
 async fn get_recent_events(
-    Query(params): Query<PaginationParams>,
+    Query(params): Query<EventListParams>,
     State(state): State<AppState>,
-) -> Json<ApiResponse<Vec<MatchEvent>>> {
-    let page = params.page.unwrap_or(1);
-    let limit = params.limit.unwrap_or(50).min(100); // Max 100 per page
-    
-    let events = state.recent_events.read().await;
-    let total = events.len() as u32;
-    let start = ((page - 1) * limit) as usize;
-    let end = (start + limit as usize).min(events.len());
-    
-    let page_events = if start < events.len() {
-        events[start..end].to_vec()
+) -> Result<Json<ApiResponse<EventPage>>, StatusCode> {
+    let query = params.query()?;
+    let page = if state.event_log.is_persistent() {
+        state.event_log.list(&query, &[]).await
     } else {
-        vec![]
-    };
-    
-    Json(ApiResponse {
+        state.event_log.list(&query, &state.recent_events.read().await).await
+    }.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(ApiResponse {
         success: true,
-        data: Some(page_events),
+        data: Some(page),
         message: None,
-        pagination: Some(PaginationInfo {
-            page,
-            limit,
-            total,
-            pages: (total + limit - 1) / limit,
-        }),
-    })
+        pagination: None,
+    }))
 }
 
 // Get live events (last 10)
@@ -235,6 +657,213 @@ async fn get_prediction_by_match(
     }
 }
 
+// Every prediction made for the match so far, oldest first
+async fn get_prediction_history(
+    Path(match_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<Vec<PredictionPoint>>>, StatusCode> {
+    let points = state.prediction_history.get(&match_id).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(points),
+        message: None,
+        pagination: None,
+    }))
+}
+
+// Features a prediction was made from
+async fn get_prediction_features(
+    Path(prediction_id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<PredictionFeatures>>, StatusCode> {
+    let features = state.feature_store.get(prediction_id).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(features),
+        message: None,
+        pagination: None,
+    }))
+}
+
+// Post-mortem for a finished match
+// Matches yet to kick off on a day, with their pre-match prediction and odds
+// once they come within the pre-match window
+async fn get_fixtures(
+    Query(params): Query<FixturesParams>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<Vec<Fixture>>>, StatusCode> {
+    let date = params.date.unwrap_or_else(|| Utc::now().date_naive());
+    let fixtures = state.fixtures.on_date(date).await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        message: Some(format!("{} fixtures on {}", fixtures.len(), date)),
+        data: Some(fixtures),
+        pagination: None,
+    }))
+}
+
+async fn get_match_summary(
+    Path(match_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<MatchSummary>>, StatusCode> {
+    let summary = state.match_summaries.get_summary(&match_id).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(summary),
+        message: None,
+        pagination: None,
+    }))
+}
+
+// Exact score probabilities from the match's latest prediction, against the
+// correct score market where it is quoted
+async fn get_scorelines(
+    Path(match_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<ScorelineResponse>>, StatusCode> {
+    let prediction = state.recent_predictions.read().await
+        .iter().rev()
+        .find(|prediction| prediction.match_id == match_id)
+        .cloned()
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let matrix = score_matrix_for(&prediction);
+    let market = state.market_simulator.get_correct_score_odds(&match_id).await;
+
+    let mut scorelines: Vec<Scoreline> = matrix.scores()
+        .map(|(home_goals, away_goals, probability)| {
+            let market_price = market.as_ref().and_then(|odds| odds.price(home_goals, away_goals));
+            Scoreline {
+                home_goals,
+                away_goals,
+                probability,
+                market_price,
+                edge: market_price.and_then(|price| price.to_f64()).map(|price| probability * price - 1.0),
+            }
+        })
+        .collect();
+    scorelines.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(ScorelineResponse {
+            match_id,
+            model_name: prediction.model_name,
+            model_version: prediction.model_version,
+            predicted_at: prediction.prediction_timestamp,
+            from_goal_model: prediction.score_matrix.is_some(),
+            max_goals: matrix.max_goals,
+            other: matrix.other(),
+            probabilities: matrix.probabilities,
+            scorelines,
+        }),
+        message: None,
+        pagination: None,
+    }))
+}
+
+// Every shot so far with its expected goals and the running totals per side
+async fn get_xg_timeline(
+    Path(match_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<XgTimeline>>, StatusCode> {
+    let timeline = state.predictor.get_feature_engineer()
+        .xg_timeline(&match_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(timeline),
+        message: None,
+        pagination: None,
+    }))
+}
+
+// Matches, teams, leagues and bets resembling the query, best first
+async fn search(
+    Query(params): Query<SearchParams>,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<Vec<SearchResult>>> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let results = state.search_index.search(&params.q, params.kind, limit).await;
+
+    Json(ApiResponse {
+        success: true,
+        data: Some(results),
+        message: None,
+        pagination: None,
+    })
+}
+
+// Teams seen on the feed whose names resemble the query
+async fn search_teams(
+    Query(params): Query<TeamSearchParams>,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<Vec<TeamNameMatch>>> {
+    let limit = params.limit.unwrap_or(10).min(50);
+    let events = state.recent_events.read().await;
+    let teams: std::collections::HashSet<&str> = events
+        .iter()
+        .flat_map(|event| [event.team_home.as_str(), event.team_away.as_str()])
+        .collect();
+
+    let mut results = TeamNameNormalizer::new().search(&params.q, teams, MIN_TEAM_SEARCH_SCORE);
+    results.truncate(limit);
+
+    Json(ApiResponse {
+        success: true,
+        data: Some(results),
+        message: None,
+        pagination: None,
+    })
+}
+
+// Elo rating of every team that has finished a match, highest first
+async fn get_team_ratings(State(state): State<AppState>) -> Json<ApiResponse<Vec<TeamRating>>> {
+    Json(ApiResponse {
+        success: true,
+        data: Some(state.predictor.get_feature_engineer().elo_ratings().ratings()),
+        message: None,
+        pagination: None,
+    })
+}
+
+async fn get_team_rating(
+    Path(team): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<TeamRating>>, StatusCode> {
+    let rating = state.predictor.get_feature_engineer().elo_ratings()
+        .get(&team)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(rating),
+        message: None,
+        pagination: None,
+    }))
+}
+
+// The league's table for its current season, with home and away records
+async fn get_league_table(
+    Path(league): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<LeagueTable>>, StatusCode> {
+    let table = state.predictor.get_feature_engineer().standings()
+        .table(&league)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(table),
+        message: None,
+        pagination: None,
+    }))
+}
+
 // Get market odds for specific match
 async fn get_market_odds(
     Path(match_id): Path<String>,
@@ -252,6 +881,134 @@ async fn get_market_odds(
     }
 }
 
+// Get double chance and draw no bet quotes for a match
+async fn get_derived_odds(
+    Path(match_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<DerivedMarketOdds>>, StatusCode> {
+    let odds = state.market_simulator.get_derived_odds(&match_id).await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(odds),
+        message: None,
+        pagination: None,
+    }))
+}
+
+// Get the Asian handicap ladder for a match
+async fn get_asian_handicap_odds(
+    Path(match_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<AsianHandicapOdds>>, StatusCode> {
+    let odds = state.market_simulator.get_asian_handicap_odds(&match_id).await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(odds),
+        message: None,
+        pagination: None,
+    }))
+}
+
+// Get exact score prices for a match
+async fn get_correct_score_odds(
+    Path(match_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<CorrectScoreOdds>>, StatusCode> {
+    let odds = state.market_simulator.get_correct_score_odds(&match_id).await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(odds),
+        message: None,
+        pagination: None,
+    }))
+}
+
+// Get first and second half result prices for a match
+async fn get_half_result_odds(
+    Path(match_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<HalfResultOdds>>, StatusCode> {
+    let odds = state.market_simulator.get_half_result_odds(&match_id).await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(odds),
+        message: None,
+        pagination: None,
+    }))
+}
+
+// Each bookmaker's price moves for a match, or with `at` every bookmaker's
+// prices as they stood at that point in time
+async fn get_odds_history(
+    Path(match_id): Path<String>,
+    Query(params): Query<OddsHistoryParams>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<OddsHistoryResponse>>, StatusCode> {
+    if let Some(at) = params.at {
+        let book = state.odds_history.book_at(&match_id, at).await
+            .ok_or(StatusCode::NOT_FOUND)?;
+
+        return Ok(Json(ApiResponse {
+            success: true,
+            data: Some(OddsHistoryResponse::Snapshot(Box::new(OddsBookResponse {
+                match_id,
+                best: book.best(),
+                bookmakers: book.ladder(),
+            }))),
+            message: Some(format!("Prices as of {}", at.to_rfc3339())),
+            pagination: None,
+        }));
+    }
+
+    let from = params.from.unwrap_or(DateTime::<Utc>::UNIX_EPOCH);
+    let to = params.to.unwrap_or_else(Utc::now);
+    let limit = params.limit.unwrap_or(500).clamp(1, 5000);
+    let bookmakers = state.odds_history.series(&match_id, params.bookmaker.as_deref(), from, to, limit).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if bookmakers.is_empty() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let quotes: usize = bookmakers.iter().map(|series| series.quotes.len()).sum();
+    Ok(Json(ApiResponse {
+        success: true,
+        message: Some(format!("{} price changes from {} bookmakers", quotes, bookmakers.len())),
+        data: Some(OddsHistoryResponse::Series { match_id, bookmakers }),
+        pagination: None,
+    }))
+}
+
+// Get the full price ladder across bookmakers for a match
+async fn get_odds_book(
+    Path(match_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<OddsBookResponse>>, StatusCode> {
+    let book = state.trading_engine.get_odds_book(&match_id).await
+        .filter(|book| !book.is_empty())
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let ladder = book.ladder();
+    let bookmakers_count = ladder.len();
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(OddsBookResponse {
+            match_id,
+            best: book.best(),
+            bookmakers: ladder,
+        }),
+        message: Some(format!("Prices from {} bookmakers", bookmakers_count)),
+        pagination: None,
+    }))
+}
+
 // Get all current market odds
 async fn get_all_markets(State(state): State<AppState>) -> Json<ApiResponse<HashMap<String, SimpleMarketOdds>>> {
     // This is a simplified version - in reality we'd store this in the market simulator
@@ -281,20 +1038,36 @@ async fn get_all_markets(State(state): State<AppState>) -> Json<ApiResponse<Hash
     })
 }
 
+// Prices across every book that beat the latest predictions, best expected value first
+async fn get_value_bets(
+    Query(params): Query<ValueBetParams>,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<Vec<ValueBetOpportunity>>> {
+    let predictions = state.recent_predictions.read().await.clone();
+    let value_bets = state.trading_engine.find_value_bets(&predictions, &params.filter()).await;
+    Json(paginate(value_bets, params.page, params.limit))
+}
+
+// One price in decimal, American and fractional form
+async fn convert_odds(
+    Query(params): Query<OddsConvertParams>,
+) -> Result<Json<ApiResponse<OddsConversion>>, StatusCode> {
+    let conversion = params.from.unwrap_or(OddsStyle::Decimal)
+        .parse_price(&params.odds)
+        .and_then(OddsConversion::from_decimal)
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(conversion),
+        message: None,
+        pagination: None,
+    }))
+}
+
 // Get portfolio information
 async fn get_portfolio(State(state): State<AppState>) -> Json<ApiResponse<PortfolioResponse>> {
-    let summary = state.trading_engine.get_portfolio_summary().await;
-    
-    let portfolio = PortfolioResponse {
-        total_bankroll: summary.total_bankroll.to_string(),
-        available_bankroll: summary.available_bankroll.to_string(),
-        total_exposure: summary.total_exposure.to_string(),
-        active_bets_count: summary.active_bets_count,
-        total_trades: summary.total_trades,
-        roi: summary.roi,
-        win_rate: summary.win_rate,
-        profit_loss: summary.profit_loss.to_string(),
-    };
+    let portfolio = PortfolioResponse::from(state.trading_engine.get_portfolio_summary().await);
     
     Json(ApiResponse {
         success: true,
@@ -304,25 +1077,222 @@ async fn get_portfolio(State(state): State<AppState>) -> Json<ApiResponse<Portfo
     })
 }
 
-// Placeholder endpoints (to be implemented)
-async fn get_recent_trades(State(_state): State<AppState>) -> Json<ApiResponse<Vec<serde_json::Value>>> {
+// Losses since the last daily reset and whether the kill switch has halted trading
+async fn get_daily_loss(State(state): State<AppState>) -> Json<ApiResponse<DailyLossStatus>> {
+    let status = state.trading_engine.get_daily_loss_status().await;
+    let message = status.halted
+        .then(|| format!("Trading halted until {}", status.next_reset.format("%Y-%m-%d %H:%M UTC")));
+
+    Json(ApiResponse {
+        success: true,
+        data: Some(status),
+        message,
+        pagination: None,
+    })
+}
+
+// Equity after each settlement, for charting
+async fn get_portfolio_history(
+    Query(params): Query<EquityHistoryParams>,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<EquityHistoryResponse>> {
+    let curve = state.trading_engine.get_equity_curve().await;
+    let history = EquityHistoryResponse {
+        points: curve.between(params.from, params.to).to_vec(),
+        max_drawdown: curve.max_drawdown(),
+        current_drawdown: curve.current_drawdown(),
+        rolling_max_drawdown: curve.rolling_max_drawdown(params.window.unwrap_or(DEFAULT_DRAWDOWN_WINDOW)),
+        sharpe_ratio: curve.sharpe_ratio(),
+        sortino_ratio: curve.sortino_ratio(),
+    };
+
+    Json(ApiResponse {
+        success: true,
+        data: Some(history),
+        message: None,
+        pagination: None,
+    })
+}
+
+// Top up the bankroll without restarting
+async fn deposit_bankroll(
+    State(state): State<AppState>,
+    Json(request): Json<BankrollAdjustmentRequest>,
+) -> Result<Json<ApiResponse<BankrollAdjustment>>, StatusCode> {
+    adjust_bankroll(&state, request, false).await
+}
+
+// Take money out of the available bankroll; open stakes can't be withdrawn
+async fn withdraw_bankroll(
+    State(state): State<AppState>,
+    Json(request): Json<BankrollAdjustmentRequest>,
+) -> Result<Json<ApiResponse<BankrollAdjustment>>, StatusCode> {
+    adjust_bankroll(&state, request, true).await
+}
+
+async fn adjust_bankroll(
+    state: &AppState,
+    request: BankrollAdjustmentRequest,
+    withdraw: bool,
+) -> Result<Json<ApiResponse<BankrollAdjustment>>, StatusCode> {
+    if request.amount <= Decimal::ZERO {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let amount = if withdraw { -request.amount } else { request.amount };
+    let adjustment = state.trading_engine.adjust_bankroll(amount, request.reason).await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        message: Some(format!("Bankroll now {} ({} available)", adjustment.total_bankroll, adjustment.available_bankroll)),
+        data: Some(adjustment),
+        pagination: None,
+    }))
+}
+
+// Every deposit and withdrawal, oldest first
+async fn get_bankroll_adjustments(State(state): State<AppState>) -> Json<ApiResponse<Vec<BankrollAdjustment>>> {
     Json(ApiResponse {
         success: true,
-        data: Some(vec![]),
-        message: Some("Recent trades endpoint - TODO".to_string()),
+        data: Some(state.trading_engine.get_bankroll_adjustments().await),
+        message: None,
         pagination: None,
     })
 }
 
-async fn get_trading_signals(State(_state): State<AppState>) -> Json<ApiResponse<Vec<serde_json::Value>>> {
+// Executed trades, most recent first
+async fn get_recent_trades(
+    Query(params): Query<HistoryParams>,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<Vec<BettingDecision>>> {
+    let trades = state.trading_engine.get_recent_trades(&params.filter()).await;
+    Json(paginate(trades, params.page, params.limit))
+}
+
+// Place a bet slip across several matches as one accumulator. Slips with
+// correlated legs or over the exposure limits are rejected; one the risk
+// checks or venue turn down is a conflict.
+async fn place_accumulator(
+    State(state): State<AppState>,
+    Json(request): Json<AccumulatorRequest>,
+) -> Result<Json<ApiResponse<BettingDecision>>, StatusCode> {
+    let mut slip = BetSlip::new(request.stake);
+    if let Some(strategy) = request.strategy {
+        slip = slip.with_strategy(strategy);
+    }
+    for leg in request.legs {
+        if leg.odds <= Decimal::ONE {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        let probability = leg.probability
+            .unwrap_or_else(|| (Decimal::ONE / leg.odds).to_f64().unwrap_or(0.0));
+        slip = slip.with_leg(leg.match_id, leg.bet_type, leg.odds, probability);
+    }
+
+    let bet = state.trading_engine.place_accumulator(slip).await
+        .map_err(|_| StatusCode::BAD_REQUEST)?
+        .ok_or(StatusCode::CONFLICT)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        message: Some(format!("Placed {} at {} ({})", describe_bet_type(&bet.bet_type), bet.odds, bet.id)),
+        data: Some(bet),
+        pagination: None,
+    }))
+}
+
+// Size a real or hypothetical prediction through the strategy and risk
+// limits without placing anything, reporting which limits bound the stake
+async fn simulate_trade(
+    State(state): State<AppState>,
+    Json(request): Json<TradeSimulationRequest>,
+) -> Result<Json<ApiResponse<Vec<SimulatedTrade>>>, StatusCode> {
+    let prediction = match request.prediction {
+        Some(hypothetical) => Prediction::new(
+            request.match_id.clone(),
+            "what_if".to_string(),
+            "1".to_string(),
+            hypothetical.home_win_prob,
+            hypothetical.away_win_prob,
+            Utc::now(),
+        )
+        .and_then(|prediction| prediction.with_confidence(hypothetical.confidence.unwrap_or(1.0)))
+        .map_err(|_| StatusCode::BAD_REQUEST)?,
+        None => state.recent_predictions.read().await
+            .iter().rev()
+            .find(|prediction| prediction.match_id == request.match_id)
+            .cloned()
+            .ok_or(StatusCode::NOT_FOUND)?,
+    };
+    let params = TradeSimulationParams {
+        strategy: request.strategy,
+        bet_type: request.bet_type,
+        odds: request.odds,
+    };
+
+    let trades = state.trading_engine.simulate_trade(&prediction, &params).await
+        .map_err(|e| match e {
+            QuantsError::MatchNotFound { .. } => StatusCode::NOT_FOUND,
+            _ => StatusCode::BAD_REQUEST,
+        })?;
+    let message = match trades.iter().find(|trade| trade.stake > Decimal::ZERO) {
+        Some(trade) => format!("Would stake {} on {} at {}", trade.stake, describe_bet_type(&trade.bet_type), trade.odds),
+        None => "Would not bet".to_string(),
+    };
+
+    Ok(Json(ApiResponse {
+        success: true,
+        message: Some(message),
+        data: Some(trades),
+        pagination: None,
+    }))
+}
+
+// Generated trading signals, most recent first
+async fn get_trading_signals(
+    Query(params): Query<HistoryParams>,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<Vec<TradingSignal>>> {
+    let signals = state.trading_engine.get_trading_signals(&params.filter()).await;
+    Json(paginate(signals, params.page, params.limit))
+}
+
+// How each ensemble strategy's votes have turned out
+async fn get_strategy_attribution(
+    State(state): State<AppState>,
+) -> Json<ApiResponse<BTreeMap<String, StrategyAttribution>>> {
+    let attribution = state.trading_engine.strategy_attribution().await;
+
     Json(ApiResponse {
         success: true,
-        data: Some(vec![]),
-        message: Some("Trading signals endpoint - TODO".to_string()),
+        data: Some(attribution),
+        message: None,
         pagination: None,
     })
 }
 
+fn paginate<T>(items: Vec<T>, page: Option<u32>, limit: Option<u32>) -> ApiResponse<Vec<T>> {
+    let page = page.unwrap_or(1).max(1);
+    let limit = limit.unwrap_or(50).clamp(1, 100);
+
+    let total = items.len() as u32;
+    let start = ((page - 1) * limit) as usize;
+    let page_items = items.into_iter().skip(start).take(limit as usize).collect();
+
+    ApiResponse {
+        success: true,
+        data: Some(page_items),
+        message: None,
+        pagination: Some(PaginationInfo {
+            page,
+            limit,
+            total,
+            pages: total.div_ceil(limit),
+        }),
+    }
+}
+
+// Placeholder endpoints (to be implemented)
 async fn get_performance_analytics(State(_state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
     Json(ApiResponse {
         success: true,
@@ -334,48 +1304,304 @@ async fn get_performance_analytics(State(_state): State<AppState>) -> Json<ApiRe
     })
 }
 
-async fn get_model_performance(State(_state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
+// Current model and, for the ensemble, how its components are weighted
+async fn get_model_performance(State(state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
+    let weights = state.predictor.ensemble_weights().await;
+    let message = match &weights {
+        Some(weights) => format!("Ensemble weights from {} settled predictions", weights.samples),
+        None => "Model is not an ensemble".to_string(),
+    };
+    // Champion and challenger side by side, when a challenger is shadowing
+    let shadow = match state.predictor.challenger() {
+        Some(challenger) => Some(challenger.compare(&state.prediction_ledger, state.predictor.active_model().await).await),
+        None => None,
+    };
+
     Json(ApiResponse {
         success: true,
         data: Some(serde_json::json!({
-            "message": "Model performance endpoint - TODO"
+            "predictions": state.predictor.get_prediction_count().await,
+            "ensemble_weights": weights,
+            "shadow": shadow,
+            "drift": state.predictor.drift_report().await,
         })),
-        message: Some("Model performance endpoint - TODO".to_string()),
+        message: Some(message),
         pagination: None,
     })
 }
 
-async fn start_simulation(State(_state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
+// List bookmaker promotions that have not expired or been used
+async fn get_promotions(State(state): State<AppState>) -> Json<ApiResponse<Vec<Promotion>>> {
+    let promotions = state.trading_engine.get_promotions().await;
+    let count = promotions.len();
+
     Json(ApiResponse {
         success: true,
-        data: Some(serde_json::json!({
-            "message": "Simulation control - TODO"
-        })),
-        message: Some("Simulation already running".to_string()),
+        data: Some(promotions),
+        message: Some(format!("{} active promotions", count)),
         pagination: None,
     })
 }
 
-async fn stop_simulation(State(_state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
+// Register an odds boost or free bet the trader may use
+async fn register_promotion(
+    State(state): State<AppState>,
+    Json(request): Json<PromotionRequest>,
+) -> Result<Json<ApiResponse<Promotion>>, StatusCode> {
+    let mut promotion = Promotion::new(request.bookmaker, request.kind, request.expires_at)
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    if let Some(match_id) = request.match_id {
+        promotion = promotion.for_match(match_id);
+    }
+    if let Some(bet_type) = request.bet_type {
+        promotion = promotion.for_bet_type(bet_type);
+    }
+    if let Some(min_odds) = request.min_odds {
+        promotion = promotion.with_min_odds(min_odds);
+    }
+
+    state.trading_engine.register_promotion(promotion.clone()).await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        message: Some(format!("Registered promotion {}", promotion.id)),
+        data: Some(promotion),
+        pagination: None,
+    }))
+}
+
+async fn remove_promotion(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<Promotion>>, StatusCode> {
+    let promotion = state.trading_engine.remove_promotion(id).await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(promotion),
+        message: Some(format!("Removed promotion {}", id)),
+        pagination: None,
+    }))
+}
+
+// Evaluate a model over settled predictions made within a date range
+async fn evaluate_model(
+    Path(model_name): Path<String>,
+    State(state): State<AppState>,
+    Json(request): Json<EvaluationRequest>,
+) -> Result<Json<ApiResponse<EvaluationReport>>, StatusCode> {
+    if request.from >= request.to {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let samples = state.prediction_ledger.samples(&model_name, request.from, request.to).await;
+    let report = Evaluator::new().evaluate(&samples);
+
+    Ok(Json(ApiResponse {
+        success: true,
+        message: Some(format!("Evaluated {} settled predictions for {}", report.sample_count, model_name)),
+        data: Some(report),
+        pagination: None,
+    }))
+}
+
+// Rebuild match contexts, team stats and match state by replaying the stored
+// event log; progress is polled with GET
+async fn start_rebuild(State(state): State<AppState>) -> Result<Json<ApiResponse<RebuildProgress>>, StatusCode> {
+    if !state.rebuilder.has_event_log() {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+    let progress = state.rebuilder.start().await.map_err(|_| StatusCode::CONFLICT)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(progress),
+        message: Some("Rebuild started".to_string()),
+        pagination: None,
+    }))
+}
+
+async fn get_rebuild_progress(State(state): State<AppState>) -> Json<ApiResponse<RebuildProgress>> {
+    let progress = state.rebuilder.progress().await;
+    let message = match progress.state {
+        RebuildState::Running => Some(format!("Replayed {} of {} events", progress.events_processed, progress.events_total)),
+        _ => None,
+    };
+
     Json(ApiResponse {
         success: true,
-        data: Some(serde_json::json!({
-            "message": "Simulation control - TODO"
-        })),
-        message: Some("Simulation control - TODO".to_string()),
+        data: Some(progress),
+        message,
         pagination: None,
     })
 }
 
-async fn get_simulation_status(State(_state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
+// Queued settlement retries, retraining and odds backfill, newest first
+async fn get_jobs(
+    Query(params): Query<JobsParams>,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<Vec<Job>>> {
+    let jobs = state.jobs.list(params.status, params.limit.unwrap_or(100).min(500)).await;
+
     Json(ApiResponse {
         success: true,
-        data: Some(serde_json::json!({
-            "status": "running",
-            "uptime": "unknown",
-            "events_processed": 0
-        })),
-        message: Some("Simulation status".to_string()),
+        message: Some(format!("{} jobs", jobs.len())),
+        data: Some(jobs),
         pagination: None,
     })
-}
\ No newline at end of file
+}
+
+#[derive(Deserialize)]
+pub struct ReplayRequest {
+    pub match_id: String,
+}
+
+// Replay a match's stored events through a sandboxed predictor and trading
+// engine, starting from the live bankroll, to see why it did or didn't bet.
+// Without a database the events still held in memory are replayed.
+async fn replay_match(
+    State(state): State<AppState>,
+    Json(request): Json<ReplayRequest>,
+) -> Result<Json<ApiResponse<ReplayReport>>, StatusCode> {
+    let events = if state.replayer.has_event_log() {
+        state.replayer.stored_events(&request.match_id).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    } else {
+        state.recent_events.read().await.iter()
+            .filter(|event| event.match_id == request.match_id)
+            .cloned()
+            .collect()
+    };
+    if events.is_empty() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let bankroll = state.trading_engine.get_portfolio_summary().await.available_bankroll;
+    let report = state.replayer.replay(&request.match_id, &events, bankroll).await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        message: Some(format!("Replayed {} events, {} bets placed", report.events_replayed, report.bets.len())),
+        data: Some(report),
+        pagination: None,
+    }))
+}
+
+// Resume event generation, optionally changing speed or loading new matches
+async fn start_simulation(
+    State(state): State<AppState>,
+    request: Option<Json<SimulationStartRequest>>,
+) -> Result<Json<ApiResponse<FeedStatus>>, StatusCode> {
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let control = &state.feed_control;
+
+    if let Some(multiplier) = request.speed_multiplier {
+        control.set_speed(multiplier).map_err(|_| StatusCode::BAD_REQUEST)?;
+    }
+    let message = match request.matches {
+        Some(fixtures) => {
+            let count = fixtures.len();
+            control.load_matches(fixtures).map_err(|_| StatusCode::BAD_REQUEST)?;
+            format!("Simulation started with {} new matches", count)
+        }
+        None if request.reset => {
+            control.reset();
+            "Simulation restarted from kick-off".to_string()
+        }
+        None => "Simulation running".to_string(),
+    };
+    control.resume();
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(control.status()),
+        message: Some(message),
+        pagination: None,
+    }))
+}
+
+// Pause event generation; match progress is kept
+async fn stop_simulation(State(state): State<AppState>) -> Json<ApiResponse<FeedStatus>> {
+    state.feed_control.pause();
+
+    Json(ApiResponse {
+        success: true,
+        data: Some(state.feed_control.status()),
+        message: Some("Simulation paused".to_string()),
+        pagination: None,
+    })
+}
+
+async fn get_simulation_status(State(state): State<AppState>) -> Json<ApiResponse<FeedStatus>> {
+    Json(ApiResponse {
+        success: true,
+        data: Some(state.feed_control.status()),
+        message: None,
+        pagination: None,
+    })
+}
+
+async fn set_simulation_speed(
+    State(state): State<AppState>,
+    Json(request): Json<SimulationSpeedRequest>,
+) -> Result<Json<ApiResponse<FeedStatus>>, StatusCode> {
+    state.feed_control.set_speed(request.multiplier).map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(state.feed_control.status()),
+        message: None,
+        pagination: None,
+    }))
+}
+
+// Add matches to the running simulation; the others carry on undisturbed
+async fn add_simulated_matches(
+    State(state): State<AppState>,
+    Json(request): Json<AddMatchesRequest>,
+) -> Result<Json<ApiResponse<FeedStatus>>, StatusCode> {
+    let count = request.matches.len();
+    state.feed_control.add_matches(request.matches).map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(state.feed_control.status()),
+        message: Some(format!("Added {} matches", count)),
+        pagination: None,
+    }))
+}
+
+// Finish a simulated match now on its current score
+async fn end_simulated_match(
+    Path(match_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<FeedStatus>>, StatusCode> {
+    state.feed_control.end_match(&match_id).map_err(|_| StatusCode::NOT_FOUND)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(state.feed_control.status()),
+        message: Some(format!("Ending {}", match_id)),
+        pagination: None,
+    }))
+}
+
+async fn fast_forward_simulated_match(
+    Path(match_id): Path<String>,
+    State(state): State<AppState>,
+    Json(request): Json<FastForwardRequest>,
+) -> Result<Json<ApiResponse<FeedStatus>>, StatusCode> {
+    if !state.feed_control.has_match(&match_id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    state.feed_control.fast_forward(&match_id, request.minutes).map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(state.feed_control.status()),
+        message: Some(format!("Fast-forwarding {} by {} minutes", match_id, request.minutes)),
+        pagination: None,
+    }))
+}
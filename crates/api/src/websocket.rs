@@ -1,9 +1,93 @@
 // WebSocket handlers for real-time data
 
-pub struct WebSocketManager;
+use crate::routes::AppState;
+use axum::{
+    extract::{ws::{Message, WebSocket, WebSocketUpgrade}, Query, State},
+    response::Response,
+};
+use quant_stream::DomainEventMessage;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::debug;
 
-impl WebSocketManager {
-    pub fn new() -> Self {
-        Self
+#[derive(Deserialize)]
+pub struct PredictionStreamParams {
+    /// Only this match's predictions; every match when absent
+    pub match_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DomainEventStreamParams {
+    /// Comma-separated event types, e.g. `bet_placed,bet_settled`; every type when absent
+    pub types: Option<String>,
+}
+
+// Each new prediction as a delta on the match's previous one
+pub async fn prediction_updates(
+    ws: WebSocketUpgrade,
+    Query(params): Query<PredictionStreamParams>,
+    State(state): State<AppState>,
+) -> Response {
+    let updates = state.prediction_history.subscribe();
+    ws.on_upgrade(move |socket| forward(socket, updates, move |delta| {
+        params.match_id.as_ref().is_none_or(|id| *id == delta.match_id)
+    }))
+}
+
+// The domain event feed: bets, settlements, risk breaches and model promotions
+pub async fn domain_events(
+    ws: WebSocketUpgrade,
+    Query(params): Query<DomainEventStreamParams>,
+    State(state): State<AppState>,
+) -> Response {
+    let events = state.domain_events.subscribe();
+    let types: Option<Vec<String>> = params.types
+        .map(|types| types.split(',').map(|name| name.trim().to_string()).collect());
+    ws.on_upgrade(move |socket| forward(socket, events, move |message: &DomainEventMessage| {
+        types.as_ref().is_none_or(|types| types.iter().any(|name| name == message.event.name()))
+    }))
+}
+
+// Every step of every order: signals, risk checks, placements, settlements,
+// voids and cash-outs
+pub async fn blotter_updates(
+    ws: WebSocketUpgrade,
+    Query(params): Query<PredictionStreamParams>,
+    State(state): State<AppState>,
+) -> Response {
+    let updates = state.trading_engine.subscribe_blotter();
+    ws.on_upgrade(move |socket| forward(socket, updates, move |message| {
+        params.match_id.as_ref().is_none_or(|id| *id == message.match_id)
+    }))
+}
+
+/// Send each update `keep` accepts to the socket as JSON until either side closes
+async fn forward<T: Clone + Serialize>(
+    mut socket: WebSocket,
+    mut updates: broadcast::Receiver<T>,
+    keep: impl Fn(&T) -> bool,
+) {
+    loop {
+        tokio::select! {
+            update = updates.recv() => match update {
+                Ok(update) => {
+                    if !keep(&update) {
+                        continue;
+                    }
+                    let Ok(text) = serde_json::to_string(&update) else {
+                        continue;
+                    };
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(missed)) => debug!("🔌 Subscriber fell behind, {} updates skipped", missed),
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                _ => {}
+            },
+        }
     }
-}
\ No newline at end of file
+}
@@ -24,7 +24,7 @@ impl DatabaseConnection {
     }
     
     pub async fn run_migrations(&self) -> Result<()> {
-        sqlx::migrate!("./migrations").run(&self.pool).await?;
+        sqlx::migrate!("../../migrations").run(&self.pool).await?;
         Ok(())
     }
 }
\ No newline at end of file
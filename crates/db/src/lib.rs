@@ -2,6 +2,8 @@ pub mod schema;
 pub mod repository;
 pub mod migrations;
 pub mod connection;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 
 pub use schema::*;
 pub use repository::*;
@@ -1,9 +1,55 @@
 // Migration utilities and helpers
 
 pub const INITIAL_SCHEMA: &str = include_str!("../../../migrations/001_initial_schema.sql");
+pub const OUTBOX_SCHEMA: &str = include_str!("../../../migrations/002_outbox.sql");
+pub const TRAINING_EXAMPLES_SCHEMA: &str = include_str!("../../../migrations/003_training_examples.sql");
+pub const TRADING_SIGNALS_SCHEMA: &str = include_str!("../../../migrations/004_trading_signals.sql");
+pub const MATCH_SUMMARIES_SCHEMA: &str = include_str!("../../../migrations/005_match_summaries.sql");
+pub const PREDICTION_FEATURES_SCHEMA: &str = include_str!("../../../migrations/006_prediction_features.sql");
+pub const EVENT_PAYLOAD_SCHEMA: &str = include_str!("../../../migrations/007_event_payload.sql");
+pub const TEAM_RATINGS_SCHEMA: &str = include_str!("../../../migrations/008_team_ratings.sql");
+pub const TRADE_AUDIT_LOG_SCHEMA: &str = include_str!("../../../migrations/009_trade_audit_log.sql");
+pub const BET_CURRENCY_SCHEMA: &str = include_str!("../../../migrations/010_bet_currency.sql");
+pub const EVENT_LISTING_SCHEMA: &str = include_str!("../../../migrations/011_event_listing.sql");
+pub const ODDS_HISTORY_SCHEMA: &str = include_str!("../../../migrations/012_odds_history.sql");
+pub const FIXTURES_SCHEMA: &str = include_str!("../../../migrations/013_fixtures.sql");
+pub const JOBS_SCHEMA: &str = include_str!("../../../migrations/014_jobs.sql");
+pub const PREDICTION_ODDS_SCHEMA: &str = include_str!("../../../migrations/015_prediction_odds.sql");
 
 pub fn get_migrations() -> Vec<(&'static str, &'static str)> {
     vec![
         ("001", INITIAL_SCHEMA),
+        ("002", OUTBOX_SCHEMA),
+        ("003", TRAINING_EXAMPLES_SCHEMA),
+        ("004", TRADING_SIGNALS_SCHEMA),
+        ("005", MATCH_SUMMARIES_SCHEMA),
+        ("006", PREDICTION_FEATURES_SCHEMA),
+        ("007", EVENT_PAYLOAD_SCHEMA),
+        ("008", TEAM_RATINGS_SCHEMA),
+        ("009", TRADE_AUDIT_LOG_SCHEMA),
+        ("010", BET_CURRENCY_SCHEMA),
+        ("011", EVENT_LISTING_SCHEMA),
+        ("012", ODDS_HISTORY_SCHEMA),
+        ("013", FIXTURES_SCHEMA),
+        ("014", JOBS_SCHEMA),
+        ("015", PREDICTION_ODDS_SCHEMA),
     ]
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_migration_listed() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../migrations");
+        let mut versions: Vec<String> = std::fs::read_dir(dir).unwrap()
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| name.ends_with(".sql"))
+            .map(|name| name[..3].to_string())
+            .collect();
+        versions.sort();
+        let listed: Vec<&str> = get_migrations().iter().map(|(version, _)| *version).collect();
+        assert_eq!(versions, listed);
+    }
+}
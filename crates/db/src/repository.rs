@@ -1,14 +1,114 @@
 use crate::schema::*;
-use sqlx::PgPool;
+#[cfg(feature = "sqlite")]
+use crate::sqlite;
+use quant_models::{EventQuery, Outcome1X2, QueryLog};
+use sqlx::{PgExecutor, PgPool};
+#[cfg(feature = "sqlite")]
+use sqlx::SqlitePool;
 use anyhow::Result;
+use std::future::Future;
+use std::sync::Arc;
+
+/// Runs `$body` with `$pool` bound to whichever pool the repository has. The
+/// body is compiled once per backend, so its SQL has to suit both.
+macro_rules! on_pool {
+    ($repository:expr, |$pool:ident| $body:expr) => {
+        match &$repository.pool {
+            DbPool::Postgres($pool) => $body,
+            #[cfg(feature = "sqlite")]
+            DbPool::Sqlite($pool) => $body,
+        }
+    };
+}
+
+/// Where the repository keeps its data
+enum DbPool {
+    Postgres(PgPool),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqlitePool),
+}
 
 pub struct Repository {
-    pool: PgPool,
+    pool: DbPool,
+    query_log: Arc<QueryLog>,
 }
 
 impl Repository {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self { pool: DbPool::Postgres(pool), query_log: Arc::new(QueryLog::default()) }
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite(pool: SqlitePool) -> Self {
+        Self { pool: DbPool::Sqlite(pool), query_log: Arc::new(QueryLog::default()) }
+    }
+
+    /// Connect to the database at `database_url`: Postgres, or SQLite for a
+    /// `sqlite:` URL. A SQLite database is created and migrated if need be.
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        if database_url.starts_with("sqlite:") {
+            #[cfg(feature = "sqlite")]
+            {
+                let repository = Self::sqlite(sqlite::connect(database_url).await?);
+                repository.run_migrations().await?;
+                return Ok(repository);
+            }
+            #[cfg(not(feature = "sqlite"))]
+            anyhow::bail!("{} is a SQLite database; build with the `sqlite` feature to use it", database_url);
+        }
+        Ok(Self::new(PgPool::connect(database_url).await?))
+    }
+
+    /// Record query timings, and slow queries, in a shared log
+    pub fn with_query_log(mut self, query_log: Arc<QueryLog>) -> Self {
+        self.query_log = query_log;
+        self
+    }
+
+    /// `postgres` or `sqlite`
+    pub fn backend(&self) -> &'static str {
+        match self.pool {
+            DbPool::Postgres(_) => "postgres",
+            #[cfg(feature = "sqlite")]
+            DbPool::Sqlite(_) => "sqlite",
+        }
+    }
+
+    /// Apply the migrations for this backend that haven't been yet
+    pub async fn run_migrations(&self) -> Result<()> {
+        match &self.pool {
+            DbPool::Postgres(pool) => sqlx::migrate!("../../migrations").run(pool).await?,
+            #[cfg(feature = "sqlite")]
+            DbPool::Sqlite(pool) => sqlx::migrate!("../../migrations/sqlite").run(pool).await?,
+        }
+        Ok(())
+    }
+
+    /// Latest migration applied to the database, if any have been
+    pub async fn migration_level(&self) -> Result<Option<i64>> {
+        self.timed("migration_level", async {
+            let level = on_pool!(self, |pool| {
+                sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+                    .fetch_one(pool)
+                    .await?
+            });
+
+            Ok(level)
+        }).await
+    }
+
+    /// The Postgres pool, for operations written for Postgres only
+    #[cfg_attr(not(feature = "sqlite"), allow(unused_variables))]
+    fn postgres(&self, operation: &str) -> Result<&PgPool> {
+        match &self.pool {
+            DbPool::Postgres(pool) => Ok(pool),
+            #[cfg(feature = "sqlite")]
+            DbPool::Sqlite(_) => anyhow::bail!("{} needs Postgres and is not available on SQLite", operation),
+        }
+    }
+
+    async fn timed<T>(&self, operation: &'static str, query: impl Future<Output = Result<T>>) -> Result<T> {
+        self.query_log.time(self.backend(), operation, query).await
     }
 }
 
@@ -17,11 +117,48 @@ pub trait MatchRepository {
     async fn create_match(&self, match_record: &MatchRecord) -> Result<MatchRecord>;
     async fn get_match(&self, match_id: &str) -> Result<Option<MatchRecord>>;
     async fn update_match(&self, match_record: &MatchRecord) -> Result<MatchRecord>;
+    /// Mark a match finished on its final score
+    async fn record_result(&self, match_id: &str, home_score: i32, away_score: i32) -> Result<()>;
+    /// Those of `match_ids` that are finished with a final score stored
+    async fn get_finished_matches(&self, match_ids: &[String]) -> Result<Vec<MatchRecord>>;
+    /// Every match finished with a final score since `since`, oldest first
+    async fn get_results_since(&self, since: chrono::DateTime<chrono::Utc>) -> Result<Vec<MatchRecord>>;
+    /// Store a match ahead of kick-off. Its teams and kick-off are updated
+    /// until its first event arrives.
+    async fn save_fixture(&self, fixture: &MatchRecord) -> Result<()>;
+    /// Matches yet to kick off between `from` and `to`, soonest first
+    async fn get_fixtures(
+        &self,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<MatchRecord>>;
+}
+
+/// The log of every accepted match event, replayed to rebuild derived state
+pub trait EventRepository {
+    /// Append an event, storing its match first if this is the match's first
+    async fn record_event(&self, match_record: &MatchRecord, event: &EventRecord) -> Result<()>;
+    /// Events that can be replayed
+    async fn count_events(&self) -> Result<i64>;
+    /// Replayable events in the order they happened
+    async fn get_events(&self, offset: i64, limit: i64) -> Result<Vec<EventRecord>>;
+    /// One match's replayable events in the order they happened
+    async fn get_match_events(&self, match_id: &str) -> Result<Vec<EventRecord>>;
+    /// A page of replayable events matching `query`, newest first
+    async fn query_events(&self, query: &EventQuery) -> Result<Vec<EventRecord>>;
 }
 
 pub trait PredictionRepository {
     async fn create_prediction(&self, prediction: &PredictionRecord) -> Result<PredictionRecord>;
     async fn get_predictions_for_match(&self, match_id: &str) -> Result<Vec<PredictionRecord>>;
+    /// `model_name`'s predictions made within `[from, to)` on matches that
+    /// have finished, oldest first
+    async fn get_settled_predictions(
+        &self,
+        model_name: &str,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<SettledPredictionRecord>>;
 }
 
 pub trait BetRepository {
@@ -30,4 +167,1531 @@ pub trait BetRepository {
     async fn get_active_bets(&self) -> Result<Vec<BetRecord>>;
 }
 
-// TODO: Implement these traits for Repository
\ No newline at end of file
+/// Transactional outbox: state changes and the messages announcing them are
+/// committed together, and a relay publishes pending messages afterwards.
+pub trait OutboxRepository {
+    async fn create_bet_with_outbox(
+        &self,
+        match_record: Option<&MatchRecord>,
+        bet: &BetRecord,
+        message: &OutboxRecord,
+    ) -> Result<BetRecord>;
+    async fn get_pending_outbox(&self, limit: i64) -> Result<Vec<OutboxRecord>>;
+    async fn mark_outbox_published(&self, id: uuid::Uuid) -> Result<()>;
+    async fn record_outbox_failure(&self, id: uuid::Uuid, error: &str) -> Result<()>;
+}
+
+/// Background jobs, written through on every change of status
+pub trait JobRepository {
+    /// Insert the job, or update its status, attempts, due time and error
+    async fn save_job(&self, job: &JobRecord) -> Result<()>;
+    /// Jobs still pending or running, soonest due first
+    async fn get_unfinished_jobs(&self) -> Result<Vec<JobRecord>>;
+}
+
+/// Bulk loading of finished matches and their closing odds
+pub trait HistoricalRepository {
+    /// Store a finished match and replace its closing odds from the same
+    /// bookmakers, so re-importing a file is idempotent
+    async fn import_historical_match(&self, match_record: &MatchRecord, odds: &[OddsRecord]) -> Result<()>;
+    /// Closing 1X2 prices for a match, preferring Pinnacle's sharp line
+    async fn get_closing_odds(&self, match_id: &str) -> Result<Option<OddsRecord>>;
+}
+
+/// Live prices as they were quoted, for line movement and closing line value
+pub trait OddsRepository {
+    async fn record_odds(&self, odds: &[OddsRecord]) -> Result<()>;
+    /// A match's 1X2 quotes within `[from, to)`, oldest first, optionally
+    /// from one bookmaker only
+    async fn get_odds_history(
+        &self,
+        match_id: &str,
+        bookmaker: Option<&str>,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+        limit: i64,
+    ) -> Result<Vec<OddsRecord>>;
+}
+
+pub trait ModelPerformanceRepository {
+    /// Insert an evaluation, replacing any earlier one for the same model
+    /// version and period start
+    async fn save_model_performance(&self, record: &ModelPerformanceRecord) -> Result<ModelPerformanceRecord>;
+    async fn get_model_performance(&self, model_name: &str) -> Result<Vec<ModelPerformanceRecord>>;
+}
+
+pub trait TradingSignalRepository {
+    async fn create_trading_signal(&self, signal: &TradingSignalRecord) -> Result<()>;
+    /// Most recent first
+    async fn get_trading_signals_for_match(&self, match_id: &str) -> Result<Vec<TradingSignalRecord>>;
+}
+
+pub trait MatchSummaryRepository {
+    /// Insert or replace the summary for a match
+    async fn save_match_summary(&self, summary: &MatchSummaryRecord) -> Result<()>;
+    async fn get_match_summary(&self, match_id: &str) -> Result<Option<MatchSummaryRecord>>;
+}
+
+/// Elo ratings, so they survive restarts
+pub trait TeamRatingRepository {
+    /// Insert or replace each team's rating
+    async fn save_team_ratings(&self, ratings: &[TeamRatingRecord]) -> Result<()>;
+    async fn get_team_ratings(&self) -> Result<Vec<TeamRatingRecord>>;
+}
+
+/// Append-only trail of signals, risk decisions and executions
+pub trait TradeAuditRepository {
+    async fn append_audit_entry(&self, entry: &TradeAuditRecord) -> Result<()>;
+    /// Append an execution attempt unless one with the same idempotency key
+    /// exists; returns whether it was appended
+    async fn claim_execution(&self, entry: &TradeAuditRecord) -> Result<bool>;
+    /// Oldest first
+    async fn get_audit_entries_for_match(&self, match_id: &str) -> Result<Vec<TradeAuditRecord>>;
+}
+
+/// Feature snapshots and their eventual results, for offline training
+pub trait TrainingDataRepository {
+    async fn record_training_example(&self, example: &TrainingExampleRecord) -> Result<()>;
+    /// Label every snapshot of a finished match; returns how many were labeled
+    async fn label_training_examples(&self, match_id: &str, outcome: &Outcome1X2) -> Result<u64>;
+    /// Most recent labeled snapshots first
+    async fn get_labeled_examples(&self, limit: i64) -> Result<Vec<TrainingExampleRecord>>;
+    /// Most recent labeled snapshots first, from matches in the given leagues
+    async fn get_labeled_examples_for_leagues(&self, leagues: &[String], limit: i64) -> Result<Vec<TrainingExampleRecord>>;
+}
+
+/// Feature store: the inputs behind each prediction
+pub trait FeatureStoreRepository {
+    /// Insert or replace the features for a prediction
+    async fn save_prediction_features(&self, record: &PredictionFeaturesRecord) -> Result<()>;
+    async fn get_prediction_features(&self, prediction_id: uuid::Uuid) -> Result<Option<PredictionFeaturesRecord>>;
+    /// Oldest first
+    async fn get_features_for_match(&self, match_id: &str) -> Result<Vec<PredictionFeaturesRecord>>;
+    /// Everything a model version predicted from within `[from, to)`, oldest
+    /// first, for re-running an evaluation on the same inputs
+    async fn get_features_for_model(
+        &self,
+        model_name: &str,
+        model_version: &str,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<PredictionFeaturesRecord>>;
+}
+
+/// Age-based pruning of raw and high-volume tables
+pub trait RetentionRepository {
+    /// Delete up to `limit` of the target's oldest rows from before `cutoff`.
+    /// The rows are handed to `archive` before the deletion commits; if it
+    /// fails, nothing is deleted.
+    async fn prune_batch<F>(
+        &self,
+        target: RetentionTarget,
+        cutoff: chrono::DateTime<chrono::Utc>,
+        limit: i64,
+        archive: F,
+    ) -> Result<Vec<PrunedRow>>
+    where
+        F: FnOnce(&[PrunedRow]) -> Result<()> + Send;
+}
+
+/// Moving a whole season out of the live tables and back. `league` narrows
+/// the season to one competition.
+pub trait SeasonArchiveRepository {
+    async fn get_season_status(&self, season: &str, league: Option<&str>) -> Result<SeasonStatus>;
+    /// The season's rows of `table`, as JSON objects keyed by column
+    async fn export_season_table(&self, table: ArchiveTable, season: &str, league: Option<&str>) -> Result<Vec<serde_json::Value>>;
+    /// Delete the season's matches and everything referencing them, in one
+    /// transaction. Returns the number of rows deleted.
+    async fn delete_season(&self, season: &str, league: Option<&str>) -> Result<u64>;
+    /// Insert archived rows in one transaction, skipping rows already present.
+    /// Returns the number of rows inserted.
+    async fn restore_season(&self, tables: &[(ArchiveTable, Vec<serde_json::Value>)]) -> Result<u64>;
+}
+
+impl MatchRepository for Repository {
+    async fn create_match(&self, match_record: &MatchRecord) -> Result<MatchRecord> {
+        self.timed("create_match", async {
+            match &self.pool {
+                DbPool::Postgres(pool) => upsert_match(pool, match_record).await,
+                #[cfg(feature = "sqlite")]
+                DbPool::Sqlite(pool) => sqlite::upsert_match(pool, match_record).await,
+            }
+        }).await
+    }
+
+    async fn get_match(&self, match_id: &str) -> Result<Option<MatchRecord>> {
+        self.timed("get_match", async {
+            let record = on_pool!(self, |pool| {
+                sqlx::query_as::<_, MatchRecord>("SELECT * FROM matches WHERE match_id = $1")
+                    .bind(match_id)
+                    .fetch_optional(pool)
+                    .await?
+            });
+
+            Ok(record)
+        }).await
+    }
+
+    async fn update_match(&self, match_record: &MatchRecord) -> Result<MatchRecord> {
+        self.timed("update_match", async {
+            let record = on_pool!(self, |pool| {
+                sqlx::query_as::<_, MatchRecord>(
+                    r#"
+                    UPDATE matches
+                    SET status = $2, home_score = $3, away_score = $4, updated_at = CURRENT_TIMESTAMP
+                    WHERE match_id = $1
+                    RETURNING *
+                    "#,
+                )
+                .bind(&match_record.match_id)
+                .bind(&match_record.status)
+                .bind(match_record.home_score)
+                .bind(match_record.away_score)
+                .fetch_one(pool)
+                .await?
+            });
+
+            Ok(record)
+        }).await
+    }
+
+    async fn record_result(&self, match_id: &str, home_score: i32, away_score: i32) -> Result<()> {
+        self.timed("record_result", async {
+            on_pool!(self, |pool| {
+                sqlx::query(
+                    r#"
+                    UPDATE matches
+                    SET status = 'finished', home_score = $2, away_score = $3, updated_at = CURRENT_TIMESTAMP
+                    WHERE match_id = $1
+                    "#,
+                )
+                .bind(match_id)
+                .bind(home_score)
+                .bind(away_score)
+                .execute(pool)
+                .await?;
+            });
+
+            Ok(())
+        }).await
+    }
+
+    async fn get_finished_matches(&self, match_ids: &[String]) -> Result<Vec<MatchRecord>> {
+        self.timed("get_finished_matches", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::get_finished_matches(pool, match_ids).await;
+            }
+            let pool = self.postgres("get_finished_matches")?;
+
+            let records = sqlx::query_as::<_, MatchRecord>(
+                r#"
+                SELECT * FROM matches
+                WHERE match_id = ANY($1)
+                  AND status = 'finished'
+                  AND home_score IS NOT NULL
+                  AND away_score IS NOT NULL
+                "#,
+            )
+            .bind(match_ids)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(records)
+        }).await
+    }
+
+    async fn get_results_since(&self, since: chrono::DateTime<chrono::Utc>) -> Result<Vec<MatchRecord>> {
+        self.timed("get_results_since", async {
+            let records = on_pool!(self, |pool| {
+                sqlx::query_as::<_, MatchRecord>(
+                    r#"
+                    SELECT * FROM matches
+                    WHERE status = 'finished'
+                      AND home_score IS NOT NULL
+                      AND away_score IS NOT NULL
+                      AND match_date >= $1
+                    ORDER BY match_date
+                    "#,
+                )
+                .bind(since)
+                .fetch_all(pool)
+                .await?
+            });
+
+            Ok(records)
+        }).await
+    }
+
+    async fn save_fixture(&self, fixture: &MatchRecord) -> Result<()> {
+        self.timed("save_fixture", async {
+            on_pool!(self, |pool| {
+                sqlx::query(
+                    r#"
+                    INSERT INTO matches (id, match_id, team_home, team_away, league, season, match_date, status)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, 'scheduled')
+                    ON CONFLICT (match_id) DO UPDATE SET
+                        team_home = EXCLUDED.team_home,
+                        team_away = EXCLUDED.team_away,
+                        league = EXCLUDED.league,
+                        season = EXCLUDED.season,
+                        match_date = EXCLUDED.match_date,
+                        updated_at = CURRENT_TIMESTAMP
+                    WHERE matches.status = 'scheduled'
+                    "#,
+                )
+                .bind(fixture.id)
+                .bind(&fixture.match_id)
+                .bind(&fixture.team_home)
+                .bind(&fixture.team_away)
+                .bind(&fixture.league)
+                .bind(&fixture.season)
+                .bind(fixture.match_date)
+                .execute(pool)
+                .await?;
+            });
+
+            Ok(())
+        }).await
+    }
+
+    async fn get_fixtures(
+        &self,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<MatchRecord>> {
+        self.timed("get_fixtures", async {
+            let records = on_pool!(self, |pool| {
+                sqlx::query_as::<_, MatchRecord>(
+                    r#"
+                    SELECT * FROM matches
+                    WHERE status = 'scheduled'
+                      AND match_date >= $1
+                      AND match_date < $2
+                    ORDER BY match_date
+                    "#,
+                )
+                .bind(from)
+                .bind(to)
+                .fetch_all(pool)
+                .await?
+            });
+
+            Ok(records)
+        }).await
+    }
+}
+
+impl EventRepository for Repository {
+    async fn record_event(&self, match_record: &MatchRecord, event: &EventRecord) -> Result<()> {
+        self.timed("record_event", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::record_event(pool, match_record, event).await;
+            }
+            let pool = self.postgres("record_event")?;
+            let mut tx = pool.begin().await?;
+
+            upsert_match(&mut *tx, match_record).await?;
+            sqlx::query(
+                r#"
+                INSERT INTO events (id, match_id, event_type, timestamp, minute, team, player, metadata, payload)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT (id) DO NOTHING
+                "#,
+            )
+            .bind(event.id)
+            .bind(&event.match_id)
+            .bind(&event.event_type)
+            .bind(event.timestamp)
+            .bind(event.minute)
+            .bind(&event.team)
+            .bind(&event.player)
+            .bind(&event.metadata)
+            .bind(&event.payload)
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(())
+        }).await
+    }
+
+    async fn count_events(&self) -> Result<i64> {
+        self.timed("count_events", async {
+            let count = on_pool!(self, |pool| {
+                sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM events WHERE payload IS NOT NULL")
+                    .fetch_one(pool)
+                    .await?
+            });
+
+            Ok(count)
+        }).await
+    }
+
+    async fn get_events(&self, offset: i64, limit: i64) -> Result<Vec<EventRecord>> {
+        self.timed("get_events", async {
+            let records = on_pool!(self, |pool| {
+                sqlx::query_as::<_, EventRecord>(
+                    r#"
+                    SELECT * FROM events
+                    WHERE payload IS NOT NULL
+                    ORDER BY timestamp, created_at, id
+                    LIMIT $1 OFFSET $2
+                    "#,
+                )
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(pool)
+                .await?
+            });
+
+            Ok(records)
+        }).await
+    }
+
+    async fn get_match_events(&self, match_id: &str) -> Result<Vec<EventRecord>> {
+        self.timed("get_match_events", async {
+            let records = on_pool!(self, |pool| {
+                sqlx::query_as::<_, EventRecord>(
+                    r#"
+                    SELECT * FROM events
+                    WHERE match_id = $1 AND payload IS NOT NULL
+                    ORDER BY timestamp, created_at, id
+                    "#,
+                )
+                .bind(match_id)
+                .fetch_all(pool)
+                .await?
+            });
+
+            Ok(records)
+        }).await
+    }
+
+    async fn query_events(&self, query: &EventQuery) -> Result<Vec<EventRecord>> {
+        self.timed("query_events", async {
+            let records = on_pool!(self, |pool| {
+                sqlx::query_as::<_, EventRecord>(
+                    r#"
+                    SELECT e.* FROM events e
+                    JOIN matches m ON m.match_id = e.match_id
+                    WHERE e.payload IS NOT NULL
+                      AND ($1 IS NULL OR LOWER(m.league) = LOWER($1))
+                      AND ($2 IS NULL OR LOWER(m.team_home) = LOWER($2) OR LOWER(m.team_away) = LOWER($2))
+                      AND ($3 IS NULL OR e.event_type = LOWER($3))
+                      AND ($4 IS NULL OR e.timestamp >= $4)
+                      AND ($5 IS NULL OR e.timestamp < $5)
+                      AND ($6 IS NULL OR e.match_id = $6)
+                      AND ($7 IS NULL OR (e.timestamp, e.id) < ($7, $8))
+                    ORDER BY e.timestamp DESC, e.id DESC
+                    LIMIT $9
+                    "#,
+                )
+                .bind(&query.league)
+                .bind(&query.team)
+                .bind(&query.event_type)
+                .bind(query.from)
+                .bind(query.to)
+                .bind(&query.match_id)
+                .bind(query.after.map(|cursor| cursor.timestamp))
+                .bind(query.after.map(|cursor| cursor.id))
+                .bind(query.limit as i64)
+                .fetch_all(pool)
+                .await?
+            });
+
+            Ok(records)
+        }).await
+    }
+}
+
+impl PredictionRepository for Repository {
+    async fn create_prediction(&self, prediction: &PredictionRecord) -> Result<PredictionRecord> {
+        self.timed("create_prediction", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::create_prediction(pool, prediction).await;
+            }
+            let pool = self.postgres("create_prediction")?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO predictions (id, match_id, model_name, model_version, home_win_prob, draw_prob,
+                                         away_win_prob, confidence, expected_goals_home, expected_goals_away,
+                                         features_used, prediction_timestamp, match_timestamp,
+                                         home_odds, draw_odds, away_odds)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+                ON CONFLICT (id) DO NOTHING
+                "#,
+            )
+            .bind(prediction.id)
+            .bind(&prediction.match_id)
+            .bind(&prediction.model_name)
+            .bind(&prediction.model_version)
+            .bind(prediction.home_win_prob)
+            .bind(prediction.draw_prob)
+            .bind(prediction.away_win_prob)
+            .bind(prediction.confidence)
+            .bind(prediction.expected_goals_home)
+            .bind(prediction.expected_goals_away)
+            .bind(&prediction.features_used)
+            .bind(prediction.prediction_timestamp)
+            .bind(prediction.match_timestamp)
+            .bind(prediction.home_odds)
+            .bind(prediction.draw_odds)
+            .bind(prediction.away_odds)
+            .execute(pool)
+            .await?;
+
+            Ok(prediction.clone())
+        }).await
+    }
+
+    async fn get_predictions_for_match(&self, match_id: &str) -> Result<Vec<PredictionRecord>> {
+        self.timed("get_predictions_for_match", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::get_predictions_for_match(pool, match_id).await;
+            }
+            let pool = self.postgres("get_predictions_for_match")?;
+
+            let records = sqlx::query_as::<_, PredictionRecord>(
+                "SELECT * FROM predictions WHERE match_id = $1 ORDER BY prediction_timestamp",
+            )
+            .bind(match_id)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(records)
+        }).await
+    }
+
+    async fn get_settled_predictions(
+        &self,
+        model_name: &str,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<SettledPredictionRecord>> {
+        self.timed("get_settled_predictions", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::get_settled_predictions(pool, model_name, from, to).await;
+            }
+            let pool = self.postgres("get_settled_predictions")?;
+
+            let records = sqlx::query_as::<_, SettledPredictionRecord>(
+                r#"
+                SELECT p.*, m.home_score, m.away_score
+                FROM predictions p
+                JOIN matches m ON m.match_id = p.match_id
+                WHERE p.model_name = $1
+                  AND p.prediction_timestamp >= $2 AND p.prediction_timestamp < $3
+                  AND m.status = 'finished' AND m.home_score IS NOT NULL AND m.away_score IS NOT NULL
+                ORDER BY p.prediction_timestamp
+                "#,
+            )
+            .bind(model_name)
+            .bind(from)
+            .bind(to)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(records)
+        }).await
+    }
+}
+
+impl BetRepository for Repository {
+    async fn create_bet(&self, bet: &BetRecord) -> Result<BetRecord> {
+        self.timed("create_bet", async {
+            match &self.pool {
+                DbPool::Postgres(pool) => upsert_bet(pool, bet).await,
+                #[cfg(feature = "sqlite")]
+                DbPool::Sqlite(pool) => sqlite::upsert_bet(pool, bet).await,
+            }
+        }).await
+    }
+
+    async fn update_bet_status(&self, bet_id: uuid::Uuid, status: &str) -> Result<()> {
+        self.timed("update_bet_status", async {
+            on_pool!(self, |pool| {
+                sqlx::query("UPDATE bets SET status = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1")
+                    .bind(bet_id)
+                    .bind(status)
+                    .execute(pool)
+                    .await?;
+            });
+
+            Ok(())
+        }).await
+    }
+
+    async fn get_active_bets(&self) -> Result<Vec<BetRecord>> {
+        self.timed("get_active_bets", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::get_active_bets(pool).await;
+            }
+            let pool = self.postgres("get_active_bets")?;
+
+            let records = sqlx::query_as::<_, BetRecord>(
+                "SELECT * FROM bets WHERE status IN ('pending', 'placed') ORDER BY placed_at",
+            )
+            .fetch_all(pool)
+            .await?;
+
+            Ok(records)
+        }).await
+    }
+}
+
+impl OutboxRepository for Repository {
+    async fn create_bet_with_outbox(
+        &self,
+        match_record: Option<&MatchRecord>,
+        bet: &BetRecord,
+        message: &OutboxRecord,
+    ) -> Result<BetRecord> {
+        self.timed("create_bet_with_outbox", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::create_bet_with_outbox(pool, match_record, bet, message).await;
+            }
+            let pool = self.postgres("create_bet_with_outbox")?;
+            let mut tx = pool.begin().await?;
+
+            if let Some(match_record) = match_record {
+                upsert_match(&mut *tx, match_record).await?;
+            }
+            let record = upsert_bet(&mut *tx, bet).await?;
+            sqlx::query("INSERT INTO outbox (id, stream_key, payload, created_at) VALUES ($1, $2, $3, $4)")
+                .bind(message.id)
+                .bind(&message.stream_key)
+                .bind(&message.payload)
+                .bind(message.created_at)
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+            Ok(record)
+        }).await
+    }
+
+    async fn get_pending_outbox(&self, limit: i64) -> Result<Vec<OutboxRecord>> {
+        self.timed("get_pending_outbox", async {
+            let records = on_pool!(self, |pool| {
+                sqlx::query_as::<_, OutboxRecord>(
+                    "SELECT * FROM outbox WHERE published_at IS NULL ORDER BY created_at LIMIT $1",
+                )
+                .bind(limit)
+                .fetch_all(pool)
+                .await?
+            });
+
+            Ok(records)
+        }).await
+    }
+
+    async fn mark_outbox_published(&self, id: uuid::Uuid) -> Result<()> {
+        self.timed("mark_outbox_published", async {
+            on_pool!(self, |pool| {
+                sqlx::query("UPDATE outbox SET published_at = CURRENT_TIMESTAMP, attempts = attempts + 1 WHERE id = $1")
+                    .bind(id)
+                    .execute(pool)
+                    .await?;
+            });
+
+            Ok(())
+        }).await
+    }
+
+    async fn record_outbox_failure(&self, id: uuid::Uuid, error: &str) -> Result<()> {
+        self.timed("record_outbox_failure", async {
+            on_pool!(self, |pool| {
+                sqlx::query("UPDATE outbox SET attempts = attempts + 1, last_error = $2 WHERE id = $1")
+                    .bind(id)
+                    .bind(error)
+                    .execute(pool)
+                    .await?;
+            });
+
+            Ok(())
+        }).await
+    }
+}
+
+impl JobRepository for Repository {
+    async fn save_job(&self, job: &JobRecord) -> Result<()> {
+        self.timed("save_job", async {
+            on_pool!(self, |pool| {
+                sqlx::query(
+                    r#"
+                    INSERT INTO jobs (id, kind, payload, status, attempts, max_attempts, run_at, last_error, created_at, updated_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                    ON CONFLICT (id) DO UPDATE SET
+                        status = EXCLUDED.status,
+                        attempts = EXCLUDED.attempts,
+                        run_at = EXCLUDED.run_at,
+                        last_error = EXCLUDED.last_error,
+                        updated_at = EXCLUDED.updated_at
+                    "#,
+                )
+                .bind(job.id)
+                .bind(&job.kind)
+                .bind(&job.payload)
+                .bind(&job.status)
+                .bind(job.attempts)
+                .bind(job.max_attempts)
+                .bind(job.run_at)
+                .bind(&job.last_error)
+                .bind(job.created_at)
+                .bind(job.updated_at)
+                .execute(pool)
+                .await?;
+            });
+
+            Ok(())
+        }).await
+    }
+
+    async fn get_unfinished_jobs(&self) -> Result<Vec<JobRecord>> {
+        self.timed("get_unfinished_jobs", async {
+            let records = on_pool!(self, |pool| {
+                sqlx::query_as::<_, JobRecord>(
+                    "SELECT * FROM jobs WHERE status IN ('pending', 'running') ORDER BY run_at",
+                )
+                .fetch_all(pool)
+                .await?
+            });
+
+            Ok(records)
+        }).await
+    }
+}
+
+impl HistoricalRepository for Repository {
+    async fn import_historical_match(&self, match_record: &MatchRecord, odds: &[OddsRecord]) -> Result<()> {
+        self.timed("import_historical_match", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::import_historical_match(pool, match_record, odds).await;
+            }
+            let pool = self.postgres("import_historical_match")?;
+            let mut tx = pool.begin().await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO matches (id, match_id, team_home, team_away, league, season,
+                                     match_date, status, home_score, away_score)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                ON CONFLICT (match_id) DO UPDATE SET
+                    match_date = EXCLUDED.match_date,
+                    status = EXCLUDED.status,
+                    home_score = EXCLUDED.home_score,
+                    away_score = EXCLUDED.away_score,
+                    updated_at = NOW()
+                "#,
+            )
+            .bind(match_record.id)
+            .bind(&match_record.match_id)
+            .bind(&match_record.team_home)
+            .bind(&match_record.team_away)
+            .bind(&match_record.league)
+            .bind(&match_record.season)
+            .bind(match_record.match_date)
+            .bind(&match_record.status)
+            .bind(match_record.home_score)
+            .bind(match_record.away_score)
+            .execute(&mut *tx)
+            .await?;
+
+            for record in odds {
+                sqlx::query("DELETE FROM odds WHERE match_id = $1 AND bookmaker = $2 AND market_type = $3")
+                    .bind(&record.match_id)
+                    .bind(&record.bookmaker)
+                    .bind(&record.market_type)
+                    .execute(&mut *tx)
+                    .await?;
+
+                sqlx::query(
+                    r#"
+                    INSERT INTO odds (id, match_id, bookmaker, market_type, home_odds, draw_odds,
+                                      away_odds, timestamp, is_active)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                    "#,
+                )
+                .bind(record.id)
+                .bind(&record.match_id)
+                .bind(&record.bookmaker)
+                .bind(&record.market_type)
+                .bind(record.home_odds)
+                .bind(record.draw_odds)
+                .bind(record.away_odds)
+                .bind(record.timestamp)
+                .bind(record.is_active)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            tx.commit().await?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_closing_odds(&self, match_id: &str) -> Result<Option<OddsRecord>> {
+        self.timed("get_closing_odds", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::get_closing_odds(pool, match_id).await;
+            }
+            let pool = self.postgres("get_closing_odds")?;
+
+            let record = sqlx::query_as::<_, OddsRecord>(
+                r#"
+                SELECT * FROM odds
+                WHERE match_id = $1 AND market_type = 'match_winner' AND is_active = FALSE
+                ORDER BY (bookmaker = 'Pinnacle') DESC, timestamp DESC
+                LIMIT 1
+                "#,
+            )
+            .bind(match_id)
+            .fetch_optional(pool)
+            .await?;
+
+            Ok(record)
+        }).await
+    }
+}
+
+impl OddsRepository for Repository {
+    async fn record_odds(&self, odds: &[OddsRecord]) -> Result<()> {
+        self.timed("record_odds", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::record_odds(pool, odds).await;
+            }
+            let pool = self.postgres("record_odds")?;
+            let mut tx = pool.begin().await?;
+
+            for record in odds {
+                sqlx::query(
+                    r#"
+                    INSERT INTO odds (id, match_id, bookmaker, market_type, home_odds, draw_odds,
+                                      away_odds, timestamp, is_active)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                    "#,
+                )
+                .bind(record.id)
+                .bind(&record.match_id)
+                .bind(&record.bookmaker)
+                .bind(&record.market_type)
+                .bind(record.home_odds)
+                .bind(record.draw_odds)
+                .bind(record.away_odds)
+                .bind(record.timestamp)
+                .bind(record.is_active)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            tx.commit().await?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_odds_history(
+        &self,
+        match_id: &str,
+        bookmaker: Option<&str>,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+        limit: i64,
+    ) -> Result<Vec<OddsRecord>> {
+        self.timed("get_odds_history", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::get_odds_history(pool, match_id, bookmaker, from, to, limit).await;
+            }
+            let pool = self.postgres("get_odds_history")?;
+
+            let records = sqlx::query_as::<_, OddsRecord>(
+                r#"
+                SELECT * FROM odds
+                WHERE match_id = $1 AND market_type = 'match_winner'
+                  AND ($2::TEXT IS NULL OR bookmaker = $2)
+                  AND timestamp >= $3 AND timestamp < $4
+                ORDER BY timestamp, bookmaker
+                LIMIT $5
+                "#,
+            )
+            .bind(match_id)
+            .bind(bookmaker)
+            .bind(from)
+            .bind(to)
+            .bind(limit)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(records)
+        }).await
+    }
+}
+
+impl ModelPerformanceRepository for Repository {
+    async fn save_model_performance(&self, record: &ModelPerformanceRecord) -> Result<ModelPerformanceRecord> {
+        self.timed("save_model_performance", async {
+            let record = on_pool!(self, |pool| {
+                sqlx::query_as::<_, ModelPerformanceRecord>(
+                    r#"
+                    INSERT INTO model_performance (id, model_name, model_version, total_predictions,
+                                                   correct_predictions, accuracy, log_loss, brier_score, roi,
+                                                   sharpe_ratio, max_drawdown, calibration_slope,
+                                                   calibration_intercept, evaluation_period_start,
+                                                   evaluation_period_end)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+                    ON CONFLICT (model_name, model_version, evaluation_period_start) DO UPDATE SET
+                        total_predictions = EXCLUDED.total_predictions,
+                        correct_predictions = EXCLUDED.correct_predictions,
+                        accuracy = EXCLUDED.accuracy,
+                        log_loss = EXCLUDED.log_loss,
+                        brier_score = EXCLUDED.brier_score,
+                        roi = EXCLUDED.roi,
+                        sharpe_ratio = EXCLUDED.sharpe_ratio,
+                        max_drawdown = EXCLUDED.max_drawdown,
+                        calibration_slope = EXCLUDED.calibration_slope,
+                        calibration_intercept = EXCLUDED.calibration_intercept,
+                        evaluation_period_end = EXCLUDED.evaluation_period_end,
+                        updated_at = CURRENT_TIMESTAMP
+                    RETURNING *
+                    "#,
+                )
+                .bind(record.id)
+                .bind(&record.model_name)
+                .bind(&record.model_version)
+                .bind(record.total_predictions)
+                .bind(record.correct_predictions)
+                .bind(record.accuracy)
+                .bind(record.log_loss)
+                .bind(record.brier_score)
+                .bind(record.roi)
+                .bind(record.sharpe_ratio)
+                .bind(record.max_drawdown)
+                .bind(record.calibration_slope)
+                .bind(record.calibration_intercept)
+                .bind(record.evaluation_period_start)
+                .bind(record.evaluation_period_end)
+                .fetch_one(pool)
+                .await?
+            });
+
+            Ok(record)
+        }).await
+    }
+
+    async fn get_model_performance(&self, model_name: &str) -> Result<Vec<ModelPerformanceRecord>> {
+        self.timed("get_model_performance", async {
+            let records = on_pool!(self, |pool| {
+                sqlx::query_as::<_, ModelPerformanceRecord>(
+                    "SELECT * FROM model_performance WHERE model_name = $1 ORDER BY evaluation_period_start DESC",
+                )
+                .bind(model_name)
+                .fetch_all(pool)
+                .await?
+            });
+
+            Ok(records)
+        }).await
+    }
+}
+
+impl TradingSignalRepository for Repository {
+    async fn create_trading_signal(&self, signal: &TradingSignalRecord) -> Result<()> {
+        self.timed("create_trading_signal", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::create_trading_signal(pool, signal).await;
+            }
+            let pool = self.postgres("create_trading_signal")?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO trading_signals (id, match_id, signal_strength, bet_type, stake, odds, strategy,
+                                             risk_score, warnings, reasoning, generated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                ON CONFLICT (id) DO NOTHING
+                "#,
+            )
+            .bind(signal.id)
+            .bind(&signal.match_id)
+            .bind(signal.signal_strength)
+            .bind(&signal.bet_type)
+            .bind(signal.stake)
+            .bind(signal.odds)
+            .bind(&signal.strategy)
+            .bind(signal.risk_score)
+            .bind(&signal.warnings)
+            .bind(&signal.reasoning)
+            .bind(signal.generated_at)
+            .execute(pool)
+            .await?;
+
+            Ok(())
+        }).await
+    }
+
+    async fn get_trading_signals_for_match(&self, match_id: &str) -> Result<Vec<TradingSignalRecord>> {
+        self.timed("get_trading_signals_for_match", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::get_trading_signals_for_match(pool, match_id).await;
+            }
+            let pool = self.postgres("get_trading_signals_for_match")?;
+
+            let records = sqlx::query_as::<_, TradingSignalRecord>(
+                "SELECT * FROM trading_signals WHERE match_id = $1 ORDER BY generated_at DESC",
+            )
+            .bind(match_id)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(records)
+        }).await
+    }
+}
+
+impl MatchSummaryRepository for Repository {
+    async fn save_match_summary(&self, summary: &MatchSummaryRecord) -> Result<()> {
+        self.timed("save_match_summary", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::save_match_summary(pool, summary).await;
+            }
+            let pool = self.postgres("save_match_summary")?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO match_summaries (match_id, home_score, away_score, profit_loss, summary)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (match_id) DO UPDATE SET
+                    home_score = EXCLUDED.home_score,
+                    away_score = EXCLUDED.away_score,
+                    profit_loss = EXCLUDED.profit_loss,
+                    summary = EXCLUDED.summary,
+                    created_at = NOW()
+                "#,
+            )
+            .bind(&summary.match_id)
+            .bind(summary.home_score)
+            .bind(summary.away_score)
+            .bind(summary.profit_loss)
+            .bind(&summary.summary)
+            .execute(pool)
+            .await?;
+
+            Ok(())
+        }).await
+    }
+
+    async fn get_match_summary(&self, match_id: &str) -> Result<Option<MatchSummaryRecord>> {
+        self.timed("get_match_summary", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::get_match_summary(pool, match_id).await;
+            }
+            let pool = self.postgres("get_match_summary")?;
+
+            let record = sqlx::query_as::<_, MatchSummaryRecord>("SELECT * FROM match_summaries WHERE match_id = $1")
+                .bind(match_id)
+                .fetch_optional(pool)
+                .await?;
+
+            Ok(record)
+        }).await
+    }
+}
+
+impl TradeAuditRepository for Repository {
+    async fn append_audit_entry(&self, entry: &TradeAuditRecord) -> Result<()> {
+        self.timed("append_audit_entry", async {
+            on_pool!(self, |pool| {
+                sqlx::query(
+                    r#"
+                    INSERT INTO trade_audit_log (id, kind, match_id, signal_id, bet_id, idempotency_key, entry, recorded_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                    "#,
+                )
+                .bind(entry.id)
+                .bind(&entry.kind)
+                .bind(&entry.match_id)
+                .bind(entry.signal_id)
+                .bind(entry.bet_id)
+                .bind(&entry.idempotency_key)
+                .bind(&entry.entry)
+                .bind(entry.recorded_at)
+                .execute(pool)
+                .await?;
+            });
+
+            Ok(())
+        }).await
+    }
+
+    async fn claim_execution(&self, entry: &TradeAuditRecord) -> Result<bool> {
+        self.timed("claim_execution", async {
+            let rows_affected = on_pool!(self, |pool| {
+                sqlx::query(
+                    r#"
+                    INSERT INTO trade_audit_log (id, kind, match_id, signal_id, bet_id, idempotency_key, entry, recorded_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                    ON CONFLICT (idempotency_key) WHERE kind = 'execution_attempt' DO NOTHING
+                    "#,
+                )
+                .bind(entry.id)
+                .bind(&entry.kind)
+                .bind(&entry.match_id)
+                .bind(entry.signal_id)
+                .bind(entry.bet_id)
+                .bind(&entry.idempotency_key)
+                .bind(&entry.entry)
+                .bind(entry.recorded_at)
+                .execute(pool)
+                .await?
+                .rows_affected()
+            });
+
+            Ok(rows_affected == 1)
+        }).await
+    }
+
+    async fn get_audit_entries_for_match(&self, match_id: &str) -> Result<Vec<TradeAuditRecord>> {
+        self.timed("get_audit_entries_for_match", async {
+            let records = on_pool!(self, |pool| {
+                sqlx::query_as::<_, TradeAuditRecord>(
+                    "SELECT * FROM trade_audit_log WHERE match_id = $1 ORDER BY recorded_at, id",
+                )
+                .bind(match_id)
+                .fetch_all(pool)
+                .await?
+            });
+
+            Ok(records)
+        }).await
+    }
+}
+
+impl TeamRatingRepository for Repository {
+    async fn save_team_ratings(&self, ratings: &[TeamRatingRecord]) -> Result<()> {
+        self.timed("save_team_ratings", async {
+            on_pool!(self, |pool| {
+                let mut tx = pool.begin().await?;
+                for rating in ratings {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO team_ratings (team, rating, matches_played, season, updated_at)
+                        VALUES ($1, $2, $3, $4, $5)
+                        ON CONFLICT (team) DO UPDATE SET
+                            rating = EXCLUDED.rating,
+                            matches_played = EXCLUDED.matches_played,
+                            season = EXCLUDED.season,
+                            updated_at = EXCLUDED.updated_at
+                        "#,
+                    )
+                    .bind(&rating.team)
+                    .bind(rating.rating)
+                    .bind(rating.matches_played)
+                    .bind(&rating.season)
+                    .bind(rating.updated_at)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                tx.commit().await?;
+            });
+
+            Ok(())
+        }).await
+    }
+
+    async fn get_team_ratings(&self) -> Result<Vec<TeamRatingRecord>> {
+        self.timed("get_team_ratings", async {
+            let records = on_pool!(self, |pool| {
+                sqlx::query_as::<_, TeamRatingRecord>("SELECT * FROM team_ratings ORDER BY rating DESC")
+                    .fetch_all(pool)
+                    .await?
+            });
+
+            Ok(records)
+        }).await
+    }
+}
+
+impl TrainingDataRepository for Repository {
+    async fn record_training_example(&self, example: &TrainingExampleRecord) -> Result<()> {
+        self.timed("record_training_example", async {
+            on_pool!(self, |pool| {
+                sqlx::query(
+                    r#"
+                    INSERT INTO training_examples (id, match_id, features, outcome, feature_timestamp)
+                    VALUES ($1, $2, $3, $4, $5)
+                    "#,
+                )
+                .bind(example.id)
+                .bind(&example.match_id)
+                .bind(&example.features)
+                .bind(&example.outcome)
+                .bind(example.feature_timestamp)
+                .execute(pool)
+                .await?;
+            });
+
+            Ok(())
+        }).await
+    }
+
+    async fn label_training_examples(&self, match_id: &str, outcome: &Outcome1X2) -> Result<u64> {
+        self.timed("label_training_examples", async {
+            let rows_affected = on_pool!(self, |pool| {
+                sqlx::query("UPDATE training_examples SET outcome = $2 WHERE match_id = $1")
+                    .bind(match_id)
+                    .bind(TrainingExampleRecord::outcome_label(outcome))
+                    .execute(pool)
+                    .await?
+                    .rows_affected()
+            });
+
+            Ok(rows_affected)
+        }).await
+    }
+
+    async fn get_labeled_examples(&self, limit: i64) -> Result<Vec<TrainingExampleRecord>> {
+        self.timed("get_labeled_examples", async {
+            let records = on_pool!(self, |pool| {
+                sqlx::query_as::<_, TrainingExampleRecord>(
+                    r#"
+                    SELECT * FROM training_examples
+                    WHERE outcome IS NOT NULL
+                    ORDER BY feature_timestamp DESC
+                    LIMIT $1
+                    "#,
+                )
+                .bind(limit)
+                .fetch_all(pool)
+                .await?
+            });
+
+            Ok(records)
+        }).await
+    }
+
+    async fn get_labeled_examples_for_leagues(&self, leagues: &[String], limit: i64) -> Result<Vec<TrainingExampleRecord>> {
+        self.timed("get_labeled_examples_for_leagues", async {
+            #[cfg(feature = "sqlite")]
+            if let DbPool::Sqlite(pool) = &self.pool {
+                return sqlite::get_labeled_examples_for_leagues(pool, leagues, limit).await;
+            }
+            let pool = self.postgres("get_labeled_examples_for_leagues")?;
+
+            let records = sqlx::query_as::<_, TrainingExampleRecord>(
+                r#"
+                SELECT t.* FROM training_examples t
+                JOIN matches m ON m.match_id = t.match_id
+                WHERE t.outcome IS NOT NULL AND m.league = ANY($1)
+                ORDER BY t.feature_timestamp DESC
+                LIMIT $2
+                "#,
+            )
+            .bind(leagues)
+            .bind(limit)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(records)
+        }).await
+    }
+}
+
+impl FeatureStoreRepository for Repository {
+    async fn save_prediction_features(&self, record: &PredictionFeaturesRecord) -> Result<()> {
+        self.timed("save_prediction_features", async {
+            on_pool!(self, |pool| {
+                sqlx::query(
+                    r#"
+                    INSERT INTO prediction_features (prediction_id, match_id, model_name, model_version,
+                                                     features, feature_timestamp)
+                    VALUES ($1, $2, $3, $4, $5, $6)
+                    ON CONFLICT (prediction_id) DO UPDATE SET
+                        features = EXCLUDED.features,
+                        feature_timestamp = EXCLUDED.feature_timestamp
+                    "#,
+                )
+                .bind(record.prediction_id)
+                .bind(&record.match_id)
+                .bind(&record.model_name)
+                .bind(&record.model_version)
+                .bind(&record.features)
+                .bind(record.feature_timestamp)
+                .execute(pool)
+                .await?;
+            });
+
+            Ok(())
+        }).await
+    }
+
+    async fn get_prediction_features(&self, prediction_id: uuid::Uuid) -> Result<Option<PredictionFeaturesRecord>> {
+        self.timed("get_prediction_features", async {
+            let record = on_pool!(self, |pool| {
+                sqlx::query_as::<_, PredictionFeaturesRecord>(
+                    "SELECT * FROM prediction_features WHERE prediction_id = $1",
+                )
+                .bind(prediction_id)
+                .fetch_optional(pool)
+                .await?
+            });
+
+            Ok(record)
+        }).await
+    }
+
+    async fn get_features_for_match(&self, match_id: &str) -> Result<Vec<PredictionFeaturesRecord>> {
+        self.timed("get_features_for_match", async {
+            let records = on_pool!(self, |pool| {
+                sqlx::query_as::<_, PredictionFeaturesRecord>(
+                    "SELECT * FROM prediction_features WHERE match_id = $1 ORDER BY feature_timestamp",
+                )
+                .bind(match_id)
+                .fetch_all(pool)
+                .await?
+            });
+
+            Ok(records)
+        }).await
+    }
+
+    async fn get_features_for_model(
+        &self,
+        model_name: &str,
+        model_version: &str,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<PredictionFeaturesRecord>> {
+        self.timed("get_features_for_model", async {
+            let records = on_pool!(self, |pool| {
+                sqlx::query_as::<_, PredictionFeaturesRecord>(
+                    r#"
+                    SELECT * FROM prediction_features
+                    WHERE model_name = $1 AND model_version = $2
+                      AND feature_timestamp >= $3 AND feature_timestamp < $4
+                    ORDER BY feature_timestamp
+                    "#,
+                )
+                .bind(model_name)
+                .bind(model_version)
+                .bind(from)
+                .bind(to)
+                .fetch_all(pool)
+                .await?
+            });
+
+            Ok(records)
+        }).await
+    }
+}
+
+impl RetentionRepository for Repository {
+    async fn prune_batch<F>(
+        &self,
+        target: RetentionTarget,
+        cutoff: chrono::DateTime<chrono::Utc>,
+        limit: i64,
+        archive: F,
+    ) -> Result<Vec<PrunedRow>>
+    where
+        F: FnOnce(&[PrunedRow]) -> Result<()> + Send,
+    {
+        self.timed("prune_batch", async {
+            let pool = self.postgres("Retention pruning")?;
+            // Table and column names come from `RetentionTarget`, never from input
+            let query = format!(
+                r#"
+                DELETE FROM {table} t
+                WHERE t.ctid IN (
+                    SELECT ctid FROM {table}
+                    WHERE {column} < $1 AND {condition}
+                    ORDER BY {column}
+                    LIMIT $2
+                )
+                RETURNING to_jsonb(t.*) AS data, pg_column_size(t.*) AS bytes
+                "#,
+                table = target.table(),
+                column = target.timestamp_column(),
+                condition = target.condition(),
+            );
+
+            let mut tx = pool.begin().await?;
+            let rows = sqlx::query_as::<_, PrunedRow>(&query)
+                .bind(cutoff)
+                .bind(limit)
+                .fetch_all(&mut *tx)
+                .await?;
+            archive(&rows)?;
+            tx.commit().await?;
+
+            Ok(rows)
+        }).await
+    }
+}
+
+/// Selects the season's match ids for `$1` = season, `$2` = optional league
+const SEASON_MATCH_IDS: &str = "SELECT match_id FROM matches WHERE season = $1 AND ($2::text IS NULL OR league = $2)";
+
+impl SeasonArchiveRepository for Repository {
+    async fn get_season_status(&self, season: &str, league: Option<&str>) -> Result<SeasonStatus> {
+        self.timed("get_season_status", async {
+            let pool = self.postgres("Season archiving")?;
+
+            let status = sqlx::query_as::<_, SeasonStatus>(
+                r#"
+                SELECT COUNT(*) AS matches,
+                       COUNT(*) FILTER (WHERE status <> 'finished') AS unfinished
+                FROM matches
+                WHERE season = $1 AND ($2::text IS NULL OR league = $2)
+                "#,
+            )
+            .bind(season)
+            .bind(league)
+            .fetch_one(pool)
+            .await?;
+
+            Ok(status)
+        }).await
+    }
+
+    async fn export_season_table(&self, table: ArchiveTable, season: &str, league: Option<&str>) -> Result<Vec<serde_json::Value>> {
+        self.timed("export_season_table", async {
+            let pool = self.postgres("Season archiving")?;
+            // Table names come from `ArchiveTable`, never from input
+            let query = format!(
+                "SELECT to_jsonb(t.*) FROM {table} t WHERE t.match_id IN ({ids}) ORDER BY t.created_at, t.id",
+                table = table.table(),
+                ids = SEASON_MATCH_IDS,
+            );
+            let rows = sqlx::query_scalar::<_, serde_json::Value>(&query)
+                .bind(season)
+                .bind(league)
+                .fetch_all(pool)
+                .await?;
+
+            Ok(rows)
+        }).await
+    }
+
+    async fn delete_season(&self, season: &str, league: Option<&str>) -> Result<u64> {
+        self.timed("delete_season", async {
+            let pool = self.postgres("Season archiving")?;
+            let mut tx = pool.begin().await?;
+            let mut deleted = 0;
+            for table in ArchiveTable::ALL.iter().rev() {
+                let query = format!("DELETE FROM {} WHERE match_id IN ({})", table.table(), SEASON_MATCH_IDS);
+                deleted += sqlx::query(&query)
+                    .bind(season)
+                    .bind(league)
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+            }
+            tx.commit().await?;
+
+            Ok(deleted)
+        }).await
+    }
+
+    async fn restore_season(&self, tables: &[(ArchiveTable, Vec<serde_json::Value>)]) -> Result<u64> {
+        self.timed("restore_season", async {
+            let pool = self.postgres("Season restoring")?;
+            let mut tx = pool.begin().await?;
+            let mut restored = 0;
+            for (table, rows) in tables {
+                let query = format!(
+                    "INSERT INTO {table} SELECT * FROM jsonb_populate_recordset(NULL::{table}, $1) ON CONFLICT DO NOTHING",
+                    table = table.table(),
+                );
+                restored += sqlx::query(&query)
+                    .bind(serde_json::Value::Array(rows.clone()))
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+            }
+            tx.commit().await?;
+
+            Ok(restored)
+        }).await
+    }
+}
+
+async fn upsert_match<'e, E: PgExecutor<'e>>(executor: E, match_record: &MatchRecord) -> Result<MatchRecord> {
+    let record = sqlx::query_as::<_, MatchRecord>(
+        r#"
+        INSERT INTO matches (id, match_id, team_home, team_away, league, season,
+                             match_date, status, home_score, away_score)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (match_id) DO UPDATE SET
+            status = CASE WHEN matches.status = 'scheduled' THEN EXCLUDED.status ELSE matches.status END,
+            updated_at = NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(match_record.id)
+    .bind(&match_record.match_id)
+    .bind(&match_record.team_home)
+    .bind(&match_record.team_away)
+    .bind(&match_record.league)
+    .bind(&match_record.season)
+    .bind(match_record.match_date)
+    .bind(&match_record.status)
+    .bind(match_record.home_score)
+    .bind(match_record.away_score)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(record)
+}
+
+/// Inserts the bet, or refreshes its settlement fields if it was already stored
+async fn upsert_bet<'e, E: PgExecutor<'e>>(executor: E, bet: &BetRecord) -> Result<BetRecord> {
+    let record = sqlx::query_as::<_, BetRecord>(
+        r#"
+        INSERT INTO bets (id, match_id, bet_type, stake, odds, expected_value, kelly_fraction,
+                          confidence, strategy, status, placed_at, settled_at, payout, profit_loss,
+                          currency, fx_rate)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+        ON CONFLICT (id) DO UPDATE SET
+            status = EXCLUDED.status,
+            settled_at = EXCLUDED.settled_at,
+            payout = EXCLUDED.payout,
+            profit_loss = EXCLUDED.profit_loss,
+            updated_at = NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(bet.id)
+    .bind(&bet.match_id)
+    .bind(&bet.bet_type)
+    .bind(bet.stake)
+    .bind(bet.odds)
+    .bind(bet.expected_value)
+    .bind(bet.kelly_fraction)
+    .bind(bet.confidence)
+    .bind(&bet.strategy)
+    .bind(&bet.status)
+    .bind(bet.placed_at)
+    .bind(bet.settled_at)
+    .bind(bet.payout)
+    .bind(bet.profit_loss)
+    .bind(&bet.currency)
+    .bind(bet.fx_rate)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(record)
+}
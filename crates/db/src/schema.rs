@@ -3,6 +3,10 @@ use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use quant_models::{
+    AuditEntry, BetStatus, BetType, BettingDecision, FeatureVector, MatchEvent, MatchSummary, ModelPerformance,
+    Outcome1X2, Prediction, PredictionFeatures, SimpleMarketOdds, TeamRating,
+};
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct MatchRecord {
@@ -20,6 +24,42 @@ pub struct MatchRecord {
     pub updated_at: DateTime<Utc>,
 }
 
+impl MatchRecord {
+    pub fn new(match_id: String, team_home: String, team_away: String, league: String, season: String) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            match_id,
+            team_home,
+            team_away,
+            league,
+            season,
+            match_date: now,
+            status: "live".to_string(),
+            home_score: None,
+            away_score: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// A match yet to kick off
+    pub fn fixture(
+        match_id: String,
+        team_home: String,
+        team_away: String,
+        league: String,
+        season: String,
+        kickoff: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            match_date: kickoff,
+            status: "scheduled".to_string(),
+            ..Self::new(match_id, team_home, team_away, league, season)
+        }
+    }
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct EventRecord {
     pub id: Uuid,
@@ -30,9 +70,35 @@ pub struct EventRecord {
     pub team: Option<String>,
     pub player: Option<String>,
     pub metadata: serde_json::Value,
+    /// The whole event, for replaying the log
+    pub payload: Option<serde_json::Value>,
     pub created_at: DateTime<Utc>,
 }
 
+impl From<&MatchEvent> for EventRecord {
+    fn from(event: &MatchEvent) -> Self {
+        Self {
+            id: event.id,
+            match_id: event.match_id.clone(),
+            event_type: event.event_type.kind().to_string(),
+            timestamp: event.timestamp,
+            minute: event.minute().map(i32::from),
+            team: event.event_type.team().map(str::to_string),
+            player: event.event_type.player().map(str::to_string),
+            metadata: event.metadata.clone(),
+            payload: serde_json::to_value(event).ok(),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+impl EventRecord {
+    /// The event as it was received; None for rows stored without one
+    pub fn to_match_event(&self) -> Option<MatchEvent> {
+        serde_json::from_value(self.payload.clone()?).ok()
+    }
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct PredictionRecord {
     pub id: Uuid,
@@ -49,6 +115,75 @@ pub struct PredictionRecord {
     pub prediction_timestamp: DateTime<Utc>,
     pub match_timestamp: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    /// The 1X2 prices on offer when the prediction was made
+    pub home_odds: Option<Decimal>,
+    pub draw_odds: Option<Decimal>,
+    pub away_odds: Option<Decimal>,
+}
+
+impl PredictionRecord {
+    pub fn new(prediction: &Prediction, odds: Option<&SimpleMarketOdds>) -> Self {
+        Self {
+            id: prediction.id,
+            match_id: prediction.match_id.clone(),
+            model_name: prediction.model_name.clone(),
+            model_version: prediction.model_version.clone(),
+            home_win_prob: prediction.home_win_prob,
+            draw_prob: prediction.draw_prob,
+            away_win_prob: prediction.away_win_prob,
+            confidence: prediction.confidence,
+            expected_goals_home: prediction.expected_goals_home,
+            expected_goals_away: prediction.expected_goals_away,
+            features_used: prediction.features_used.clone(),
+            prediction_timestamp: prediction.prediction_timestamp,
+            match_timestamp: prediction.match_timestamp,
+            created_at: Utc::now(),
+            home_odds: odds.map(|odds| odds.home_win),
+            draw_odds: odds.map(|odds| odds.draw),
+            away_odds: odds.map(|odds| odds.away_win),
+        }
+    }
+
+    /// The prediction as made, less its score matrix and metadata
+    pub fn to_prediction(&self) -> Prediction {
+        Prediction {
+            id: self.id,
+            match_id: self.match_id.clone(),
+            model_name: self.model_name.clone(),
+            model_version: self.model_version.clone(),
+            home_win_prob: self.home_win_prob,
+            draw_prob: self.draw_prob,
+            away_win_prob: self.away_win_prob,
+            confidence: self.confidence,
+            expected_goals_home: self.expected_goals_home,
+            expected_goals_away: self.expected_goals_away,
+            score_matrix: None,
+            features_used: self.features_used.clone(),
+            prediction_timestamp: self.prediction_timestamp,
+            match_timestamp: self.match_timestamp,
+            metadata: serde_json::Value::Null,
+        }
+    }
+
+    /// The 1X2 prices, if all three were quoted
+    pub fn to_simple_odds(&self) -> Option<SimpleMarketOdds> {
+        Some(SimpleMarketOdds::new(self.home_odds?, self.draw_odds?, self.away_odds?))
+    }
+}
+
+/// A stored prediction for a match that has since finished
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct SettledPredictionRecord {
+    #[sqlx(flatten)]
+    pub prediction: PredictionRecord,
+    pub home_score: i32,
+    pub away_score: i32,
+}
+
+impl SettledPredictionRecord {
+    pub fn outcome(&self) -> Outcome1X2 {
+        Outcome1X2::from_score(self.home_score, self.away_score)
+    }
 }
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
@@ -69,6 +204,72 @@ pub struct BetRecord {
     pub profit_loss: Option<Decimal>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Stake, payout and profit/loss are in this currency
+    pub currency: String,
+    pub fx_rate: Decimal,
+}
+
+/// Column value for a bet type, e.g. `home_win` or `over_2.5`
+pub fn bet_type_label(bet_type: &BetType) -> String {
+    match bet_type {
+        BetType::HomeWin => "home_win".to_string(),
+        BetType::Draw => "draw".to_string(),
+        BetType::AwayWin => "away_win".to_string(),
+        BetType::OverUnder { line, over } => {
+            format!("{}_{}", if *over { "over" } else { "under" }, line)
+        }
+        BetType::AsianHandicap { line, team } => format!("asian_handicap_{}_{}", team, line),
+        BetType::BothTeamsToScore { yes } => format!("btts_{}", if *yes { "yes" } else { "no" }),
+        BetType::CorrectScore { home_goals, away_goals } => {
+            format!("correct_score_{}_{}", home_goals, away_goals)
+        }
+        BetType::DoubleChance { selection } => format!("double_chance_{}", selection.as_str()),
+        BetType::DrawNoBet { home } => format!("draw_no_bet_{}", if *home { "home" } else { "away" }),
+        BetType::HalfResult { half, outcome } => {
+            format!("{}_{}", half.as_str(), outcome.as_str())
+        }
+        BetType::Accumulator { legs } => format!("accumulator_{}", legs.len()),
+    }
+}
+
+impl From<&BettingDecision> for BetRecord {
+    fn from(bet: &BettingDecision) -> Self {
+        let bet_type = bet_type_label(&bet.bet_type);
+
+        let (status, payout) = match &bet.status {
+            BetStatus::Pending => ("pending", None),
+            BetStatus::Placed => ("placed", None),
+            BetStatus::Won => ("won", Some(bet.potential_payout())),
+            BetStatus::Lost => ("lost", Some(Decimal::ZERO)),
+            BetStatus::Void => ("void", Some(bet.cash_stake())),
+            BetStatus::HalfWon => ("half_won", bet.settled_payout()),
+            BetStatus::HalfLost => ("half_lost", bet.settled_payout()),
+            BetStatus::CashedOut { amount } => ("cashed_out", Some(*amount)),
+        };
+        let settled = payout.is_some();
+        let now = Utc::now();
+
+        Self {
+            id: bet.id,
+            match_id: bet.match_id.clone(),
+            bet_type,
+            stake: bet.stake,
+            odds: bet.odds,
+            expected_value: bet.expected_value,
+            kelly_fraction: bet.kelly_fraction,
+            confidence: bet.confidence,
+            strategy: bet.strategy.clone(),
+            status: status.to_string(),
+            placed_at: bet.timestamp,
+            settled_at: if settled { Some(now) } else { None },
+            payout,
+            profit_loss: payout.map(|payout| payout - bet.cash_stake()),
+            created_at: now,
+            updated_at: now,
+            currency: bet.currency.to_string(),
+            fx_rate: bet.fx_rate,
+        }
+    }
 }
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
@@ -85,6 +286,44 @@ pub struct OddsRecord {
     pub created_at: DateTime<Utc>,
 }
 
+impl OddsRecord {
+    /// Closing 1X2 prices from a historical results file
+    pub fn closing(
+        match_id: String,
+        bookmaker: String,
+        home_odds: Decimal,
+        draw_odds: Decimal,
+        away_odds: Decimal,
+        timestamp: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            match_id,
+            bookmaker,
+            market_type: "match_winner".to_string(),
+            home_odds: Some(home_odds),
+            draw_odds: Some(draw_odds),
+            away_odds: Some(away_odds),
+            timestamp,
+            is_active: false,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// A live 1X2 quote, pruned with the other odds ticks
+    pub fn tick(match_id: String, bookmaker: String, odds: &SimpleMarketOdds, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            is_active: true,
+            ..Self::closing(match_id, bookmaker, odds.home_win, odds.draw, odds.away_win, timestamp)
+        }
+    }
+
+    /// The 1X2 prices, if all three were quoted
+    pub fn to_simple_odds(&self) -> Option<SimpleMarketOdds> {
+        Some(SimpleMarketOdds::new(self.home_odds?, self.draw_odds?, self.away_odds?))
+    }
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct ModelPerformanceRecord {
     pub id: Uuid,
@@ -104,4 +343,401 @@ pub struct ModelPerformanceRecord {
     pub evaluation_period_end: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
-}
\ No newline at end of file
+}
+
+impl ModelPerformanceRecord {
+    pub fn new(
+        performance: &ModelPerformance,
+        evaluation_period_start: DateTime<Utc>,
+        evaluation_period_end: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            model_name: performance.model_name.clone(),
+            model_version: performance.model_version.clone(),
+            total_predictions: performance.total_predictions as i32,
+            correct_predictions: performance.correct_predictions as i32,
+            accuracy: performance.accuracy,
+            log_loss: performance.log_loss,
+            brier_score: performance.brier_score,
+            roi: performance.roi,
+            sharpe_ratio: performance.sharpe_ratio,
+            max_drawdown: performance.max_drawdown,
+            calibration_slope: performance.calibration_slope,
+            calibration_intercept: performance.calibration_intercept,
+            evaluation_period_start,
+            evaluation_period_end,
+            created_at: performance.last_updated,
+            updated_at: performance.last_updated,
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TrainingExampleRecord {
+    pub id: Uuid,
+    pub match_id: String,
+    pub features: serde_json::Value,
+    pub outcome: Option<String>,
+    pub feature_timestamp: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TrainingExampleRecord {
+    /// An unlabeled snapshot; the outcome is filled in at full time
+    pub fn new(features: &FeatureVector) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            match_id: features.match_id.clone(),
+            features: serde_json::to_value(&features.features).unwrap_or_default(),
+            outcome: None,
+            feature_timestamp: features.timestamp,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn outcome_label(outcome: &Outcome1X2) -> &'static str {
+        outcome.as_str()
+    }
+
+    pub fn to_feature_vector(&self) -> Option<FeatureVector> {
+        Some(FeatureVector {
+            match_id: self.match_id.clone(),
+            features: serde_json::from_value(self.features.clone()).ok()?,
+            timestamp: self.feature_timestamp,
+            schema: None,
+        })
+    }
+
+    pub fn parsed_outcome(&self) -> Option<Outcome1X2> {
+        match self.outcome.as_deref()? {
+            "home_win" => Some(Outcome1X2::HomeWin),
+            "draw" => Some(Outcome1X2::Draw),
+            "away_win" => Some(Outcome1X2::AwayWin),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct PredictionFeaturesRecord {
+    pub prediction_id: Uuid,
+    pub match_id: String,
+    pub model_name: String,
+    pub model_version: String,
+    pub features: serde_json::Value,
+    pub feature_timestamp: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&PredictionFeatures> for PredictionFeaturesRecord {
+    fn from(stored: &PredictionFeatures) -> Self {
+        Self {
+            prediction_id: stored.prediction_id,
+            match_id: stored.features.match_id.clone(),
+            model_name: stored.model_name.clone(),
+            model_version: stored.model_version.clone(),
+            features: serde_json::to_value(&stored.features.features).unwrap_or_default(),
+            feature_timestamp: stored.features.timestamp,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+impl PredictionFeaturesRecord {
+    pub fn to_prediction_features(&self) -> Option<PredictionFeatures> {
+        Some(PredictionFeatures {
+            prediction_id: self.prediction_id,
+            model_name: self.model_name.clone(),
+            model_version: self.model_version.clone(),
+            features: FeatureVector {
+                match_id: self.match_id.clone(),
+                features: serde_json::from_value(self.features.clone()).ok()?,
+                timestamp: self.feature_timestamp,
+                schema: None,
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TradingSignalRecord {
+    pub id: Uuid,
+    pub match_id: String,
+    pub signal_strength: f64,
+    pub bet_type: Option<String>,
+    pub stake: Option<Decimal>,
+    pub odds: Option<Decimal>,
+    pub strategy: Option<String>,
+    pub risk_score: f64,
+    pub warnings: Vec<String>,
+    pub reasoning: String,
+    pub generated_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct MatchSummaryRecord {
+    pub match_id: String,
+    pub home_score: i32,
+    pub away_score: i32,
+    pub profit_loss: Decimal,
+    pub summary: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&MatchSummary> for MatchSummaryRecord {
+    fn from(summary: &MatchSummary) -> Self {
+        Self {
+            match_id: summary.match_id.clone(),
+            home_score: summary.final_score.home as i32,
+            away_score: summary.final_score.away as i32,
+            profit_loss: summary.profit_loss,
+            summary: serde_json::to_value(summary).unwrap_or(serde_json::Value::Null),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+impl MatchSummaryRecord {
+    pub fn to_summary(&self) -> Option<MatchSummary> {
+        serde_json::from_value(self.summary.clone()).ok()
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TeamRatingRecord {
+    pub team: String,
+    pub rating: f64,
+    pub matches_played: i32,
+    pub season: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<&TeamRating> for TeamRatingRecord {
+    fn from(rating: &TeamRating) -> Self {
+        Self {
+            team: rating.team.clone(),
+            rating: rating.rating,
+            matches_played: rating.matches_played as i32,
+            season: rating.season.clone(),
+            updated_at: rating.updated_at,
+        }
+    }
+}
+
+impl TeamRatingRecord {
+    pub fn to_team_rating(&self) -> TeamRating {
+        TeamRating {
+            team: self.team.clone(),
+            rating: self.rating,
+            matches_played: self.matches_played.max(0) as u32,
+            season: self.season.clone(),
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TradeAuditRecord {
+    pub id: Uuid,
+    pub kind: String,
+    pub match_id: String,
+    pub signal_id: Option<Uuid>,
+    pub bet_id: Option<Uuid>,
+    pub idempotency_key: Option<String>,
+    pub entry: serde_json::Value,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl TryFrom<&AuditEntry> for TradeAuditRecord {
+    type Error = serde_json::Error;
+
+    fn try_from(entry: &AuditEntry) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: entry.id,
+            kind: entry.kind().to_string(),
+            match_id: entry.match_id.clone(),
+            signal_id: entry.signal_id,
+            bet_id: entry.bet_id,
+            idempotency_key: entry.idempotency_key().map(str::to_string),
+            entry: serde_json::to_value(entry)?,
+            recorded_at: entry.recorded_at,
+        })
+    }
+}
+
+impl TradeAuditRecord {
+    pub fn to_audit_entry(&self) -> Result<AuditEntry, serde_json::Error> {
+        serde_json::from_value(self.entry.clone())
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct OutboxRecord {
+    pub id: Uuid,
+    pub stream_key: String,
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+}
+
+impl OutboxRecord {
+    pub fn new(stream_key: impl Into<String>, payload: serde_json::Value) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            stream_key: stream_key.into(),
+            payload,
+            created_at: Utc::now(),
+            published_at: None,
+            attempts: 0,
+            last_error: None,
+        }
+    }
+}
+
+/// A background job and how far it has got; `status` is `pending`,
+/// `running`, `succeeded` or `failed`
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub id: Uuid,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    /// When a pending job is next due
+    pub run_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Rows that may be pruned once they are older than a retention window.
+/// Aggregates (matches, bets, summaries, model performance) are never pruned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionTarget {
+    /// Raw match events
+    Events,
+    /// Live prices; closing lines are kept for training and evaluation
+    OddsTicks,
+    TradingSignals,
+    /// Outbox messages already relayed to Redis
+    PublishedOutbox,
+    PredictionFeatures,
+    TrainingExamples,
+}
+
+impl RetentionTarget {
+    pub const ALL: [RetentionTarget; 6] = [
+        RetentionTarget::Events,
+        RetentionTarget::OddsTicks,
+        RetentionTarget::TradingSignals,
+        RetentionTarget::PublishedOutbox,
+        RetentionTarget::PredictionFeatures,
+        RetentionTarget::TrainingExamples,
+    ];
+
+    /// Name used in configuration and archive file names
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetentionTarget::Events => "events",
+            RetentionTarget::OddsTicks => "odds_ticks",
+            RetentionTarget::TradingSignals => "trading_signals",
+            RetentionTarget::PublishedOutbox => "published_outbox",
+            RetentionTarget::PredictionFeatures => "prediction_features",
+            RetentionTarget::TrainingExamples => "training_examples",
+        }
+    }
+
+    pub fn table(&self) -> &'static str {
+        match self {
+            RetentionTarget::Events => "events",
+            RetentionTarget::OddsTicks => "odds",
+            RetentionTarget::TradingSignals => "trading_signals",
+            RetentionTarget::PublishedOutbox => "outbox",
+            RetentionTarget::PredictionFeatures => "prediction_features",
+            RetentionTarget::TrainingExamples => "training_examples",
+        }
+    }
+
+    /// Column the row's age is measured from
+    pub fn timestamp_column(&self) -> &'static str {
+        match self {
+            RetentionTarget::Events | RetentionTarget::OddsTicks => "timestamp",
+            RetentionTarget::TradingSignals => "generated_at",
+            RetentionTarget::PublishedOutbox => "published_at",
+            RetentionTarget::PredictionFeatures | RetentionTarget::TrainingExamples => "feature_timestamp",
+        }
+    }
+
+    /// Extra condition a row must meet to be pruned
+    pub fn condition(&self) -> &'static str {
+        match self {
+            RetentionTarget::OddsTicks => "is_active = TRUE",
+            RetentionTarget::PublishedOutbox => "published_at IS NOT NULL",
+            _ => "TRUE",
+        }
+    }
+}
+
+impl std::str::FromStr for RetentionTarget {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        RetentionTarget::ALL
+            .into_iter()
+            .find(|target| target.as_str() == name)
+            .ok_or_else(|| anyhow::anyhow!("unknown retention target '{}'", name))
+    }
+}
+
+/// A pruned row, as JSON for archiving
+#[derive(Debug, Clone, FromRow)]
+pub struct PrunedRow {
+    pub data: serde_json::Value,
+    /// On-disk size of the row
+    pub bytes: i32,
+}
+
+/// Tables a season archive holds, parents first so a restore satisfies the
+/// foreign keys on `matches(match_id)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveTable {
+    Matches,
+    Events,
+    Odds,
+    Predictions,
+    Bets,
+}
+
+impl ArchiveTable {
+    pub const ALL: [ArchiveTable; 5] = [
+        ArchiveTable::Matches,
+        ArchiveTable::Events,
+        ArchiveTable::Odds,
+        ArchiveTable::Predictions,
+        ArchiveTable::Bets,
+    ];
+
+    pub fn table(&self) -> &'static str {
+        match self {
+            ArchiveTable::Matches => "matches",
+            ArchiveTable::Events => "events",
+            ArchiveTable::Odds => "odds",
+            ArchiveTable::Predictions => "predictions",
+            ArchiveTable::Bets => "bets",
+        }
+    }
+}
+
+/// How many of a season's matches are stored and how many are not finished
+#[derive(Debug, Clone, FromRow)]
+pub struct SeasonStatus {
+    pub matches: i64,
+    pub unfinished: i64,
+}
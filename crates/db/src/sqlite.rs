@@ -0,0 +1,734 @@
+// SQLite versions of the queries whose Postgres form doesn't carry over:
+// decimals are stored as text, string arrays as JSON, and `= ANY($1)`
+// becomes a `json_each` lookup
+
+use crate::schema::*;
+use anyhow::Result;
+use rust_decimal::Decimal;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteRow};
+use sqlx::types::Json;
+use sqlx::{Row, SqliteExecutor, SqlitePool};
+use std::str::FromStr;
+
+/// Open, and create if missing, the database at a `sqlite:` URL
+pub async fn connect(database_url: &str) -> Result<SqlitePool> {
+    let options = SqliteConnectOptions::from_str(database_url)?
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Wal);
+    // Every connection to an in-memory database would get a database of its own
+    let max_connections = if database_url.contains(":memory:") { 1 } else { 8 };
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(max_connections)
+        .connect_with(options)
+        .await?;
+    Ok(pool)
+}
+
+pub async fn upsert_match<'e, E: SqliteExecutor<'e>>(executor: E, match_record: &MatchRecord) -> Result<MatchRecord> {
+    let record = sqlx::query_as::<_, MatchRecord>(
+        r#"
+        INSERT INTO matches (id, match_id, team_home, team_away, league, season,
+                             match_date, status, home_score, away_score)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (match_id) DO UPDATE SET
+            status = CASE WHEN matches.status = 'scheduled' THEN excluded.status ELSE matches.status END,
+            updated_at = CURRENT_TIMESTAMP
+        RETURNING *
+        "#,
+    )
+    .bind(match_record.id)
+    .bind(&match_record.match_id)
+    .bind(&match_record.team_home)
+    .bind(&match_record.team_away)
+    .bind(&match_record.league)
+    .bind(&match_record.season)
+    .bind(match_record.match_date)
+    .bind(&match_record.status)
+    .bind(match_record.home_score)
+    .bind(match_record.away_score)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(record)
+}
+
+pub async fn get_finished_matches(pool: &SqlitePool, match_ids: &[String]) -> Result<Vec<MatchRecord>> {
+    let records = sqlx::query_as::<_, MatchRecord>(
+        r#"
+        SELECT * FROM matches
+        WHERE match_id IN (SELECT value FROM json_each($1))
+          AND status = 'finished'
+          AND home_score IS NOT NULL
+          AND away_score IS NOT NULL
+        "#,
+    )
+    .bind(Json(match_ids))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(records)
+}
+
+pub async fn record_event(pool: &SqlitePool, match_record: &MatchRecord, event: &EventRecord) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    upsert_match(&mut *tx, match_record).await?;
+    sqlx::query(
+        r#"
+        INSERT INTO events (id, match_id, event_type, timestamp, minute, team, player, metadata, payload)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        ON CONFLICT (id) DO NOTHING
+        "#,
+    )
+    .bind(event.id)
+    .bind(&event.match_id)
+    .bind(&event.event_type)
+    .bind(event.timestamp)
+    .bind(event.minute)
+    .bind(&event.team)
+    .bind(&event.player)
+    .bind(&event.metadata)
+    .bind(&event.payload)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Inserts the bet, or refreshes its settlement fields if it was already stored
+pub async fn upsert_bet<'e, E: SqliteExecutor<'e>>(executor: E, bet: &BetRecord) -> Result<BetRecord> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO bets (id, match_id, bet_type, stake, odds, expected_value, kelly_fraction,
+                          confidence, strategy, status, placed_at, settled_at, payout, profit_loss,
+                          currency, fx_rate)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+        ON CONFLICT (id) DO UPDATE SET
+            status = EXCLUDED.status,
+            settled_at = EXCLUDED.settled_at,
+            payout = EXCLUDED.payout,
+            profit_loss = EXCLUDED.profit_loss,
+            updated_at = CURRENT_TIMESTAMP
+        RETURNING *
+        "#,
+    )
+    .bind(bet.id)
+    .bind(&bet.match_id)
+    .bind(&bet.bet_type)
+    .bind(bet.stake.to_string())
+    .bind(bet.odds.to_string())
+    .bind(bet.expected_value)
+    .bind(bet.kelly_fraction)
+    .bind(bet.confidence)
+    .bind(&bet.strategy)
+    .bind(&bet.status)
+    .bind(bet.placed_at)
+    .bind(bet.settled_at)
+    .bind(bet.payout.map(|payout| payout.to_string()))
+    .bind(bet.profit_loss.map(|profit_loss| profit_loss.to_string()))
+    .bind(&bet.currency)
+    .bind(bet.fx_rate.to_string())
+    .fetch_one(executor)
+    .await?;
+
+    bet_from_row(&row)
+}
+
+pub async fn get_active_bets(pool: &SqlitePool) -> Result<Vec<BetRecord>> {
+    let rows = sqlx::query("SELECT * FROM bets WHERE status IN ('pending', 'placed') ORDER BY placed_at")
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(bet_from_row).collect()
+}
+
+pub async fn create_bet_with_outbox(
+    pool: &SqlitePool,
+    match_record: Option<&MatchRecord>,
+    bet: &BetRecord,
+    message: &OutboxRecord,
+) -> Result<BetRecord> {
+    let mut tx = pool.begin().await?;
+
+    if let Some(match_record) = match_record {
+        upsert_match(&mut *tx, match_record).await?;
+    }
+    let record = upsert_bet(&mut *tx, bet).await?;
+    sqlx::query("INSERT INTO outbox (id, stream_key, payload, created_at) VALUES ($1, $2, $3, $4)")
+        .bind(message.id)
+        .bind(&message.stream_key)
+        .bind(&message.payload)
+        .bind(message.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(record)
+}
+
+pub async fn import_historical_match(pool: &SqlitePool, match_record: &MatchRecord, odds: &[OddsRecord]) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO matches (id, match_id, team_home, team_away, league, season,
+                             match_date, status, home_score, away_score)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (match_id) DO UPDATE SET
+            match_date = EXCLUDED.match_date,
+            status = EXCLUDED.status,
+            home_score = EXCLUDED.home_score,
+            away_score = EXCLUDED.away_score,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(match_record.id)
+    .bind(&match_record.match_id)
+    .bind(&match_record.team_home)
+    .bind(&match_record.team_away)
+    .bind(&match_record.league)
+    .bind(&match_record.season)
+    .bind(match_record.match_date)
+    .bind(&match_record.status)
+    .bind(match_record.home_score)
+    .bind(match_record.away_score)
+    .execute(&mut *tx)
+    .await?;
+
+    for record in odds {
+        sqlx::query("DELETE FROM odds WHERE match_id = $1 AND bookmaker = $2 AND market_type = $3")
+            .bind(&record.match_id)
+            .bind(&record.bookmaker)
+            .bind(&record.market_type)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO odds (id, match_id, bookmaker, market_type, home_odds, draw_odds,
+                              away_odds, timestamp, is_active)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(record.id)
+        .bind(&record.match_id)
+        .bind(&record.bookmaker)
+        .bind(&record.market_type)
+        .bind(record.home_odds.map(|odds| odds.to_string()))
+        .bind(record.draw_odds.map(|odds| odds.to_string()))
+        .bind(record.away_odds.map(|odds| odds.to_string()))
+        .bind(record.timestamp)
+        .bind(record.is_active)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn get_closing_odds(pool: &SqlitePool, match_id: &str) -> Result<Option<OddsRecord>> {
+    let row = sqlx::query(
+        r#"
+        SELECT * FROM odds
+        WHERE match_id = $1 AND market_type = 'match_winner' AND is_active = FALSE
+        ORDER BY (bookmaker = 'Pinnacle') DESC, timestamp DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(match_id)
+    .fetch_optional(pool)
+    .await?;
+
+    row.as_ref().map(odds_from_row).transpose()
+}
+
+pub async fn record_odds(pool: &SqlitePool, odds: &[OddsRecord]) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    for record in odds {
+        sqlx::query(
+            r#"
+            INSERT INTO odds (id, match_id, bookmaker, market_type, home_odds, draw_odds,
+                              away_odds, timestamp, is_active)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(record.id)
+        .bind(&record.match_id)
+        .bind(&record.bookmaker)
+        .bind(&record.market_type)
+        .bind(record.home_odds.map(|odds| odds.to_string()))
+        .bind(record.draw_odds.map(|odds| odds.to_string()))
+        .bind(record.away_odds.map(|odds| odds.to_string()))
+        .bind(record.timestamp)
+        .bind(record.is_active)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn get_odds_history(
+    pool: &SqlitePool,
+    match_id: &str,
+    bookmaker: Option<&str>,
+    from: chrono::DateTime<chrono::Utc>,
+    to: chrono::DateTime<chrono::Utc>,
+    limit: i64,
+) -> Result<Vec<OddsRecord>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM odds
+        WHERE match_id = $1 AND market_type = 'match_winner'
+          AND ($2 IS NULL OR bookmaker = $2)
+          AND timestamp >= $3 AND timestamp < $4
+        ORDER BY timestamp, bookmaker
+        LIMIT $5
+        "#,
+    )
+    .bind(match_id)
+    .bind(bookmaker)
+    .bind(from)
+    .bind(to)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(odds_from_row).collect()
+}
+
+pub async fn create_prediction(pool: &SqlitePool, prediction: &PredictionRecord) -> Result<PredictionRecord> {
+    sqlx::query(
+        r#"
+        INSERT INTO predictions (id, match_id, model_name, model_version, home_win_prob, draw_prob,
+                                 away_win_prob, confidence, expected_goals_home, expected_goals_away,
+                                 features_used, prediction_timestamp, match_timestamp,
+                                 home_odds, draw_odds, away_odds)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+        ON CONFLICT (id) DO NOTHING
+        "#,
+    )
+    .bind(prediction.id)
+    .bind(&prediction.match_id)
+    .bind(&prediction.model_name)
+    .bind(&prediction.model_version)
+    .bind(prediction.home_win_prob)
+    .bind(prediction.draw_prob)
+    .bind(prediction.away_win_prob)
+    .bind(prediction.confidence)
+    .bind(prediction.expected_goals_home)
+    .bind(prediction.expected_goals_away)
+    .bind(Json(&prediction.features_used))
+    .bind(prediction.prediction_timestamp)
+    .bind(prediction.match_timestamp)
+    .bind(prediction.home_odds.map(|odds| odds.to_string()))
+    .bind(prediction.draw_odds.map(|odds| odds.to_string()))
+    .bind(prediction.away_odds.map(|odds| odds.to_string()))
+    .execute(pool)
+    .await?;
+
+    Ok(prediction.clone())
+}
+
+pub async fn get_predictions_for_match(pool: &SqlitePool, match_id: &str) -> Result<Vec<PredictionRecord>> {
+    let rows = sqlx::query("SELECT * FROM predictions WHERE match_id = $1 ORDER BY prediction_timestamp")
+        .bind(match_id)
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(prediction_from_row).collect()
+}
+
+pub async fn get_settled_predictions(
+    pool: &SqlitePool,
+    model_name: &str,
+    from: chrono::DateTime<chrono::Utc>,
+    to: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<SettledPredictionRecord>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT p.*, m.home_score, m.away_score
+        FROM predictions p
+        JOIN matches m ON m.match_id = p.match_id
+        WHERE p.model_name = $1
+          AND p.prediction_timestamp >= $2 AND p.prediction_timestamp < $3
+          AND m.status = 'finished' AND m.home_score IS NOT NULL AND m.away_score IS NOT NULL
+        ORDER BY p.prediction_timestamp
+        "#,
+    )
+    .bind(model_name)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| Ok(SettledPredictionRecord {
+            prediction: prediction_from_row(row)?,
+            home_score: row.try_get("home_score")?,
+            away_score: row.try_get("away_score")?,
+        }))
+        .collect()
+}
+
+pub async fn create_trading_signal(pool: &SqlitePool, signal: &TradingSignalRecord) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO trading_signals (id, match_id, signal_strength, bet_type, stake, odds, strategy,
+                                     risk_score, warnings, reasoning, generated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT (id) DO NOTHING
+        "#,
+    )
+    .bind(signal.id)
+    .bind(&signal.match_id)
+    .bind(signal.signal_strength)
+    .bind(&signal.bet_type)
+    .bind(signal.stake.map(|stake| stake.to_string()))
+    .bind(signal.odds.map(|odds| odds.to_string()))
+    .bind(&signal.strategy)
+    .bind(signal.risk_score)
+    .bind(Json(&signal.warnings))
+    .bind(&signal.reasoning)
+    .bind(signal.generated_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_trading_signals_for_match(pool: &SqlitePool, match_id: &str) -> Result<Vec<TradingSignalRecord>> {
+    let rows = sqlx::query("SELECT * FROM trading_signals WHERE match_id = $1 ORDER BY generated_at DESC")
+        .bind(match_id)
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(signal_from_row).collect()
+}
+
+pub async fn save_match_summary(pool: &SqlitePool, summary: &MatchSummaryRecord) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO match_summaries (match_id, home_score, away_score, profit_loss, summary)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (match_id) DO UPDATE SET
+            home_score = EXCLUDED.home_score,
+            away_score = EXCLUDED.away_score,
+            profit_loss = EXCLUDED.profit_loss,
+            summary = EXCLUDED.summary,
+            created_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(&summary.match_id)
+    .bind(summary.home_score)
+    .bind(summary.away_score)
+    .bind(summary.profit_loss.to_string())
+    .bind(&summary.summary)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_match_summary(pool: &SqlitePool, match_id: &str) -> Result<Option<MatchSummaryRecord>> {
+    let row = sqlx::query("SELECT * FROM match_summaries WHERE match_id = $1")
+        .bind(match_id)
+        .fetch_optional(pool)
+        .await?;
+
+    row.map(|row| {
+        Ok(MatchSummaryRecord {
+            match_id: row.try_get("match_id")?,
+            home_score: row.try_get("home_score")?,
+            away_score: row.try_get("away_score")?,
+            profit_loss: decimal(&row, "profit_loss")?,
+            summary: row.try_get("summary")?,
+            created_at: row.try_get("created_at")?,
+        })
+    })
+    .transpose()
+}
+
+pub async fn get_labeled_examples_for_leagues(
+    pool: &SqlitePool,
+    leagues: &[String],
+    limit: i64,
+) -> Result<Vec<TrainingExampleRecord>> {
+    let records = sqlx::query_as::<_, TrainingExampleRecord>(
+        r#"
+        SELECT t.* FROM training_examples t
+        JOIN matches m ON m.match_id = t.match_id
+        WHERE t.outcome IS NOT NULL AND m.league IN (SELECT value FROM json_each($1))
+        ORDER BY t.feature_timestamp DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(Json(leagues))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(records)
+}
+
+fn decimal(row: &SqliteRow, column: &str) -> Result<Decimal> {
+    Ok(row.try_get::<String, _>(column)?.parse()?)
+}
+
+fn optional_decimal(row: &SqliteRow, column: &str) -> Result<Option<Decimal>> {
+    Ok(row.try_get::<Option<String>, _>(column)?.map(|value| value.parse()).transpose()?)
+}
+
+fn bet_from_row(row: &SqliteRow) -> Result<BetRecord> {
+    Ok(BetRecord {
+        id: row.try_get("id")?,
+        match_id: row.try_get("match_id")?,
+        bet_type: row.try_get("bet_type")?,
+        stake: decimal(row, "stake")?,
+        odds: decimal(row, "odds")?,
+        expected_value: row.try_get("expected_value")?,
+        kelly_fraction: row.try_get("kelly_fraction")?,
+        confidence: row.try_get("confidence")?,
+        strategy: row.try_get("strategy")?,
+        status: row.try_get("status")?,
+        placed_at: row.try_get("placed_at")?,
+        settled_at: row.try_get("settled_at")?,
+        payout: optional_decimal(row, "payout")?,
+        profit_loss: optional_decimal(row, "profit_loss")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        currency: row.try_get("currency")?,
+        fx_rate: decimal(row, "fx_rate")?,
+    })
+}
+
+fn odds_from_row(row: &SqliteRow) -> Result<OddsRecord> {
+    Ok(OddsRecord {
+        id: row.try_get("id")?,
+        match_id: row.try_get("match_id")?,
+        bookmaker: row.try_get("bookmaker")?,
+        market_type: row.try_get("market_type")?,
+        home_odds: optional_decimal(row, "home_odds")?,
+        draw_odds: optional_decimal(row, "draw_odds")?,
+        away_odds: optional_decimal(row, "away_odds")?,
+        timestamp: row.try_get("timestamp")?,
+        is_active: row.try_get("is_active")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn prediction_from_row(row: &SqliteRow) -> Result<PredictionRecord> {
+    Ok(PredictionRecord {
+        id: row.try_get("id")?,
+        match_id: row.try_get("match_id")?,
+        model_name: row.try_get("model_name")?,
+        model_version: row.try_get("model_version")?,
+        home_win_prob: row.try_get("home_win_prob")?,
+        draw_prob: row.try_get("draw_prob")?,
+        away_win_prob: row.try_get("away_win_prob")?,
+        confidence: row.try_get("confidence")?,
+        expected_goals_home: row.try_get("expected_goals_home")?,
+        expected_goals_away: row.try_get("expected_goals_away")?,
+        features_used: row.try_get::<Json<Vec<String>>, _>("features_used")?.0,
+        prediction_timestamp: row.try_get("prediction_timestamp")?,
+        match_timestamp: row.try_get("match_timestamp")?,
+        created_at: row.try_get("created_at")?,
+        home_odds: optional_decimal(row, "home_odds")?,
+        draw_odds: optional_decimal(row, "draw_odds")?,
+        away_odds: optional_decimal(row, "away_odds")?,
+    })
+}
+
+fn signal_from_row(row: &SqliteRow) -> Result<TradingSignalRecord> {
+    Ok(TradingSignalRecord {
+        id: row.try_get("id")?,
+        match_id: row.try_get("match_id")?,
+        signal_strength: row.try_get("signal_strength")?,
+        bet_type: row.try_get("bet_type")?,
+        stake: optional_decimal(row, "stake")?,
+        odds: optional_decimal(row, "odds")?,
+        strategy: row.try_get("strategy")?,
+        risk_score: row.try_get("risk_score")?,
+        warnings: row.try_get::<Json<Vec<String>>, _>("warnings")?.0,
+        reasoning: row.try_get("reasoning")?,
+        generated_at: row.try_get("generated_at")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use chrono::{Duration, Utc};
+    use quant_models::{EventQuery, Outcome1X2};
+    use rust_decimal_macros::dec;
+
+    fn finished(match_id: &str, league: &str) -> MatchRecord {
+        let mut record = MatchRecord::new(
+            match_id.to_string(),
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            league.to_string(),
+            "2024-25".to_string(),
+        );
+        record.status = "finished".to_string();
+        record.home_score = Some(2);
+        record.away_score = Some(1);
+        record
+    }
+
+    #[tokio::test]
+    async fn test_repository_round_trips_on_sqlite() {
+        let repository = Repository::connect("sqlite::memory:").await.unwrap();
+        assert_eq!(repository.backend(), "sqlite");
+        assert_eq!(repository.migration_level().await.unwrap(), Some(15));
+
+        let kickoff = Utc::now() - Duration::days(1);
+        let mut epl = finished("m1", "Premier League");
+        epl.match_date = kickoff;
+        let closing = OddsRecord::closing("m1".to_string(), "Pinnacle".to_string(), dec!(1.95), dec!(3.60), dec!(4.20), kickoff);
+        repository.import_historical_match(&epl, &[closing]).await.unwrap();
+        repository.create_match(&finished("m2", "La Liga")).await.unwrap();
+
+        let odds = repository.get_closing_odds("m1").await.unwrap().unwrap();
+        assert_eq!((odds.home_odds, odds.draw_odds), (Some(dec!(1.95)), Some(dec!(3.60))));
+        let results = repository.get_results_since(kickoff - Duration::hours(1)).await.unwrap();
+        assert_eq!(results.len(), 2);
+        let ids = vec!["m1".to_string(), "m3".to_string()];
+        assert_eq!(repository.get_finished_matches(&ids).await.unwrap().len(), 1);
+
+        let mut bet = BetRecord {
+            id: uuid::Uuid::new_v4(),
+            match_id: "m1".to_string(),
+            bet_type: "home_win".to_string(),
+            stake: dec!(25.50),
+            odds: dec!(1.95),
+            expected_value: 0.04,
+            kelly_fraction: 0.02,
+            confidence: 0.7,
+            strategy: "value".to_string(),
+            status: "placed".to_string(),
+            placed_at: kickoff,
+            settled_at: None,
+            payout: None,
+            profit_loss: None,
+            created_at: kickoff,
+            updated_at: kickoff,
+            currency: "GBP".to_string(),
+            fx_rate: dec!(1.27),
+        };
+        let stored = repository.create_bet(&bet).await.unwrap();
+        assert_eq!((stored.stake, stored.fx_rate), (dec!(25.50), dec!(1.27)));
+        assert_eq!(repository.get_active_bets().await.unwrap().len(), 1);
+
+        bet.status = "won".to_string();
+        bet.profit_loss = Some(dec!(24.23));
+        assert_eq!(repository.create_bet(&bet).await.unwrap().profit_loss, Some(dec!(24.23)));
+        assert!(repository.get_active_bets().await.unwrap().is_empty());
+
+        let example = TrainingExampleRecord {
+            id: uuid::Uuid::new_v4(),
+            match_id: "m1".to_string(),
+            features: serde_json::json!({ "home_elo": 1600.0 }),
+            outcome: None,
+            feature_timestamp: kickoff,
+            created_at: kickoff,
+        };
+        repository.record_training_example(&example).await.unwrap();
+        assert_eq!(repository.label_training_examples("m1", &Outcome1X2::HomeWin).await.unwrap(), 1);
+        let leagues = vec!["Premier League".to_string()];
+        assert_eq!(repository.get_labeled_examples_for_leagues(&leagues, 10).await.unwrap().len(), 1);
+
+        let goal = quant_models::MatchEvent::new(
+            "m1".to_string(),
+            quant_models::EventType::Goal { team: "Arsenal".to_string(), player: None, minute: 30 },
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        );
+        repository.record_event(&epl, &EventRecord::from(&goal)).await.unwrap();
+        let query = EventQuery { league: Some("premier league".to_string()), event_type: Some("goal".to_string()), limit: 10, ..EventQuery::default() };
+        let events = repository.query_events(&query).await.unwrap();
+        assert_eq!(events[0].to_match_event().unwrap().id, goal.id);
+        let after = EventQuery { after: Some(quant_models::EventCursor::from(&goal)), ..query };
+        assert!(repository.query_events(&after).await.unwrap().is_empty());
+
+        let quote = |minutes, home| OddsRecord::tick(
+            "m1".to_string(),
+            "Bet365".to_string(),
+            &quant_models::SimpleMarketOdds::new(home, dec!(3.40), dec!(4.10)),
+            kickoff + Duration::minutes(minutes),
+        );
+        repository.record_odds(&[quote(10, dec!(1.90)), quote(0, dec!(2.05))]).await.unwrap();
+        let history = repository.get_odds_history("m1", Some("Bet365"), kickoff, kickoff + Duration::hours(1), 10).await.unwrap();
+        assert_eq!(history.iter().map(|tick| tick.home_odds.unwrap()).collect::<Vec<_>>(), [dec!(2.05), dec!(1.90)]);
+        // Imported closing lines are part of the history too
+        assert_eq!(repository.get_odds_history("m1", None, kickoff, kickoff + Duration::hours(1), 10).await.unwrap().len(), 3);
+
+        // A fixture can be moved until its first event, which starts it
+        let fixture = |kickoff| MatchRecord::fixture(
+            "m4".to_string(), "Everton".to_string(), "Fulham".to_string(),
+            "Premier League".to_string(), "2024-25".to_string(), kickoff,
+        );
+        let saturday = Utc::now() + Duration::days(2);
+        repository.save_fixture(&fixture(saturday)).await.unwrap();
+        repository.save_fixture(&fixture(saturday + Duration::hours(3))).await.unwrap();
+        let fixtures = repository.get_fixtures(saturday, saturday + Duration::days(1)).await.unwrap();
+        assert_eq!(fixtures.len(), 1);
+        assert_eq!(fixtures[0].match_date.timestamp(), (saturday + Duration::hours(3)).timestamp());
+        repository.create_match(&MatchRecord::new(
+            "m4".to_string(), "Everton".to_string(), "Fulham".to_string(), "Premier League".to_string(), "2024-25".to_string(),
+        )).await.unwrap();
+        assert!(repository.get_fixtures(saturday, saturday + Duration::days(1)).await.unwrap().is_empty());
+        assert_eq!(repository.get_match("m4").await.unwrap().unwrap().status, "live");
+
+        // Jobs are updated in place and drop out once finished
+        let now = Utc::now();
+        let mut job = JobRecord {
+            id: uuid::Uuid::new_v4(),
+            kind: "settle_match".to_string(),
+            payload: serde_json::json!({"match_id": "m1"}),
+            status: "pending".to_string(),
+            attempts: 0,
+            max_attempts: 3,
+            run_at: now,
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        repository.save_job(&job).await.unwrap();
+        job.attempts = 1;
+        job.last_error = Some("timed out".to_string());
+        repository.save_job(&job).await.unwrap();
+        let unfinished = repository.get_unfinished_jobs().await.unwrap();
+        assert_eq!((unfinished.len(), unfinished[0].attempts), (1, 1));
+        assert_eq!(unfinished[0].payload["match_id"], "m1");
+        job.status = "succeeded".to_string();
+        repository.save_job(&job).await.unwrap();
+        assert!(repository.get_unfinished_jobs().await.unwrap().is_empty());
+
+        // Only predictions on finished matches are settled
+        let predict = |match_id: &str| {
+            let mut prediction = quant_models::Prediction::new(
+                match_id.to_string(), "poisson".to_string(), "1.0".to_string(), 0.5, 0.2, kickoff,
+            ).unwrap();
+            prediction.features_used = vec!["home_elo".to_string()];
+            prediction
+        };
+        let odds = quant_models::SimpleMarketOdds::new(dec!(1.95), dec!(3.60), dec!(4.20));
+        repository.create_prediction(&PredictionRecord::new(&predict("m1"), Some(&odds))).await.unwrap();
+        repository.create_prediction(&PredictionRecord::new(&predict("m4"), None)).await.unwrap();
+        assert_eq!(repository.get_predictions_for_match("m4").await.unwrap()[0].features_used, ["home_elo"]);
+        let settled = repository.get_settled_predictions("poisson", kickoff, Utc::now() + Duration::minutes(1)).await.unwrap();
+        assert_eq!(settled.len(), 1);
+        assert_eq!((settled[0].outcome(), settled[0].prediction.to_simple_odds()), (Outcome1X2::HomeWin, Some(odds)));
+
+        // Postgres-only operations say so rather than fail on the SQL
+        let error = repository.get_season_status("2024-25", None).await.unwrap_err();
+        assert!(error.to_string().contains("Postgres"));
+    }
+}
@@ -0,0 +1,185 @@
+// Elo team ratings, updated on every result
+
+use chrono::Utc;
+use dashmap::DashMap;
+use quant_models::TeamRating;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EloConfig {
+    /// Rating of a team seen for the first time, and the mean ratings regress to
+    pub initial_rating: f64,
+    /// Most points a one-goal result can move
+    pub k_factor: f64,
+    /// Rating points added to the home side when predicting its result
+    pub home_advantage: f64,
+    /// Share of its distance from the mean a rating loses at the start of a
+    /// new season, as squads change over the summer
+    pub season_regression: f64,
+}
+
+impl Default for EloConfig {
+    fn default() -> Self {
+        Self {
+            initial_rating: 1500.0,
+            k_factor: 20.0,
+            home_advantage: 65.0,
+            season_regression: 0.33,
+        }
+    }
+}
+
+/// Ratings for every team that has played a rated match
+pub struct EloRatings {
+    config: EloConfig,
+    ratings: DashMap<String, TeamRating>,
+}
+
+impl EloRatings {
+    pub fn new() -> Self {
+        Self::with_config(EloConfig::default())
+    }
+
+    pub fn with_config(config: EloConfig) -> Self {
+        Self { config, ratings: DashMap::new() }
+    }
+
+    pub fn config(&self) -> &EloConfig {
+        &self.config
+    }
+
+    /// Replace ratings with stored ones, e.g. at startup
+    pub fn load(&self, ratings: impl IntoIterator<Item = TeamRating>) {
+        for rating in ratings {
+            self.ratings.insert(rating.team.clone(), rating);
+        }
+    }
+
+    pub fn rating(&self, team: &str) -> f64 {
+        self.ratings.get(team).map_or(self.config.initial_rating, |rating| rating.rating)
+    }
+
+    pub fn get(&self, team: &str) -> Option<TeamRating> {
+        self.ratings.get(team).map(|rating| rating.clone())
+    }
+
+    /// Every rated team, highest first
+    pub fn ratings(&self) -> Vec<TeamRating> {
+        let mut ratings: Vec<TeamRating> = self.ratings.iter().map(|rating| rating.clone()).collect();
+        ratings.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+        ratings
+    }
+
+    pub fn len(&self) -> usize {
+        self.ratings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ratings.is_empty()
+    }
+
+    /// The home side's expected score, a win counting 1 and a draw 0.5
+    pub fn expected_home_score(&self, home: &str, away: &str) -> f64 {
+        self.expected(self.rating(home) + self.config.home_advantage, self.rating(away))
+    }
+
+    /// Rate a finished match, returning the points the home side gained
+    /// (the away side loses as many). The first match of a new season
+    /// regresses a team toward the mean before it is rated.
+    pub fn record_result(&self, home: &str, away: &str, home_goals: u8, away_goals: u8, season: &str) -> f64 {
+        let home_rating = self.season_start(home, season);
+        let away_rating = self.season_start(away, season);
+
+        let expected = self.expected(home_rating + self.config.home_advantage, away_rating);
+        let actual = match home_goals.cmp(&away_goals) {
+            std::cmp::Ordering::Greater => 1.0,
+            std::cmp::Ordering::Equal => 0.5,
+            std::cmp::Ordering::Less => 0.0,
+        };
+        let change = self.config.k_factor * margin_multiplier(home_goals.abs_diff(away_goals)) * (actual - expected);
+
+        self.set(home, home_rating + change, season);
+        self.set(away, away_rating - change, season);
+        change
+    }
+
+    fn expected(&self, rating: f64, opponent: f64) -> f64 {
+        1.0 / (1.0 + 10_f64.powf((opponent - rating) / 400.0))
+    }
+
+    /// A team's rating going into a match of `season`
+    fn season_start(&self, team: &str, season: &str) -> f64 {
+        match self.ratings.get(team) {
+            Some(rating) if rating.season.as_deref().is_some_and(|last| last != season) => {
+                let mean = self.config.initial_rating;
+                rating.rating - (rating.rating - mean) * self.config.season_regression
+            }
+            Some(rating) => rating.rating,
+            None => self.config.initial_rating,
+        }
+    }
+
+    fn set(&self, team: &str, rating: f64, season: &str) {
+        let mut entry = self.ratings.entry(team.to_string()).or_insert_with(|| TeamRating {
+            team: team.to_string(),
+            rating,
+            matches_played: 0,
+            season: None,
+            updated_at: Utc::now(),
+        });
+        entry.rating = rating;
+        entry.matches_played += 1;
+        entry.season = Some(season.to_string());
+        entry.updated_at = Utc::now();
+    }
+}
+
+impl Default for EloRatings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Wins by more goals move ratings further, with diminishing returns
+fn margin_multiplier(goal_difference: u8) -> f64 {
+    match goal_difference {
+        0 | 1 => 1.0,
+        2 => 1.5,
+        n => (11.0 + n as f64) / 8.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_results_move_ratings_and_new_seasons_regress_to_the_mean() {
+        let elo = EloRatings::new();
+        assert!(elo.expected_home_score("Arsenal", "Chelsea") > 0.5);
+
+        let change = elo.record_result("Arsenal", "Chelsea", 1, 0, "2024-25");
+        assert!(change > 0.0 && change < elo.config().k_factor);
+        assert_eq!(elo.rating("Arsenal"), 1500.0 + change);
+        assert_eq!(elo.rating("Chelsea"), 1500.0 - change);
+
+        // A bigger win moves more
+        let rout = EloRatings::new().record_result("Arsenal", "Chelsea", 4, 0, "2024-25");
+        assert!(rout > change);
+
+        // A home draw between equals costs the home side its advantage
+        let before = elo.rating("Liverpool");
+        assert!(elo.record_result("Liverpool", "Everton", 1, 1, "2024-25") < 0.0);
+        assert!(elo.rating("Liverpool") < before);
+
+        // Next season Arsenal starts a third of the way back to 1500
+        let arsenal = elo.rating("Arsenal");
+        let chelsea = elo.rating("Chelsea");
+        elo.record_result("Arsenal", "Chelsea", 0, 0, "2025-26");
+        let regressed_sum = (arsenal - (arsenal - 1500.0) * 0.33) + (chelsea - (chelsea - 1500.0) * 0.33);
+        assert!((elo.rating("Arsenal") + elo.rating("Chelsea") - regressed_sum).abs() < 1e-9);
+        assert_eq!(elo.get("Arsenal").unwrap().matches_played, 2);
+        assert_eq!(elo.ratings()[0].team, "Arsenal");
+    }
+}
@@ -1,9 +1,570 @@
-// Model evaluation metrics
+// Model evaluation metrics, and drift of live inputs and outputs away from
+// what the model was trained on
 
-pub struct ModelEvaluator;
+use crate::models::Model;
+use quant_models::{FeatureVector, ModelPerformance, Outcome1X2, Prediction, SimpleMarketOdds};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
 
-impl ModelEvaluator {
+const CALIBRATION_BUCKETS: usize = 10;
+const PROB_EPSILON: f64 = 1e-15;
+/// Quantile bins a baseline distribution is split into
+const DRIFT_BINS: usize = 10;
+/// Share given to empty bins so PSI and KL divergence stay finite
+const DRIFT_EPSILON: f64 = 1e-4;
+/// Names the outcome probabilities are tracked under
+const PREDICTION_DISTRIBUTIONS: [&str; 3] = ["home_win_prob", "draw_prob", "away_win_prob"];
+
+/// A prediction paired with what actually happened and the odds on offer
+/// when it was made
+#[derive(Debug, Clone)]
+pub struct EvaluationSample {
+    pub prediction: Prediction,
+    pub outcome: Outcome1X2,
+    pub odds: Option<SimpleMarketOdds>,
+}
+
+/// A row of an offline dataset: what the model would have seen, what
+/// happened, and the closing price if one is known
+#[derive(Debug, Clone)]
+pub struct EvaluationExample {
+    pub features: FeatureVector,
+    pub outcome: Outcome1X2,
+    pub closing_odds: Option<SimpleMarketOdds>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationBucket {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: usize,
+    pub mean_predicted: f64,
+    pub observed_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluationReport {
+    pub sample_count: usize,
+    pub accuracy: f64,
+    pub log_loss: f64,
+    pub brier_score: f64,
+    pub calibration_slope: f64,
+    pub calibration_intercept: f64,
+    pub calibration: Vec<CalibrationBucket>,
+    /// Return from a flat 1-unit stake on the most likely outcome of every
+    /// prediction that had odds available
+    pub roi_if_followed: f64,
+    pub bets_followed: usize,
+    /// Mean over standard deviation of the per-bet returns behind `roi_if_followed`
+    pub sharpe_ratio: f64,
+    /// Largest peak-to-trough fall of the cumulative flat-stake profit, in stake units
+    pub max_drawdown: f64,
+}
+
+impl EvaluationReport {
+    pub fn to_model_performance(&self, model_name: &str, model_version: &str) -> ModelPerformance {
+        ModelPerformance {
+            model_name: model_name.to_string(),
+            model_version: model_version.to_string(),
+            total_predictions: self.sample_count as u32,
+            correct_predictions: (self.accuracy * self.sample_count as f64).round() as u32,
+            accuracy: self.accuracy,
+            log_loss: self.log_loss,
+            brier_score: self.brier_score,
+            roi: self.roi_if_followed,
+            sharpe_ratio: self.sharpe_ratio,
+            max_drawdown: self.max_drawdown,
+            calibration_slope: self.calibration_slope,
+            calibration_intercept: self.calibration_intercept,
+            last_updated: Utc::now(),
+        }
+    }
+}
+
+pub struct Evaluator;
+
+impl Evaluator {
     pub fn new() -> Self {
         Self
     }
-}
\ No newline at end of file
+
+    /// Run `model` over a dataset and score it, pricing the followed bets at
+    /// each example's closing odds
+    pub async fn evaluate_model(&self, model: &Model, examples: &[EvaluationExample]) -> Result<EvaluationReport> {
+        let samples = self.predict_samples(model, examples).await?;
+        Ok(self.evaluate(&samples))
+    }
+
+    /// `model`'s prediction for each example, alongside what happened and
+    /// the closing odds
+    pub async fn predict_samples(&self, model: &Model, examples: &[EvaluationExample]) -> Result<Vec<EvaluationSample>> {
+        let mut samples = Vec::with_capacity(examples.len());
+        for example in examples {
+            samples.push(EvaluationSample {
+                prediction: model.predict(&example.features).await?,
+                outcome: example.outcome,
+                odds: example.closing_odds.clone(),
+            });
+        }
+        Ok(samples)
+    }
+
+    pub fn evaluate(&self, samples: &[EvaluationSample]) -> EvaluationReport {
+        let mut correct = 0;
+        let mut log_loss = 0.0;
+        let mut brier = 0.0;
+        let mut points: Vec<(f64, f64)> = Vec::with_capacity(samples.len() * 3);
+        let mut bet_returns = Vec::new();
+
+        for sample in samples {
+            let probs = outcome_probabilities(&sample.prediction);
+            let actual = sample.outcome.index();
+            let picked = sample.prediction.most_likely_outcome();
+
+            if picked == sample.outcome {
+                correct += 1;
+            }
+
+            log_loss -= probs[actual].clamp(PROB_EPSILON, 1.0).ln();
+            for (i, p) in probs.iter().enumerate() {
+                let observed = if i == actual { 1.0 } else { 0.0 };
+                brier += (p - observed).powi(2);
+                points.push((*p, observed));
+            }
+
+            if let Some(odds) = &sample.odds {
+                let price = picked.select([odds.home_win, odds.draw, odds.away_win]).to_f64().unwrap_or(0.0);
+
+                bet_returns.push(if picked == sample.outcome { price - 1.0 } else { -1.0 });
+            }
+        }
+
+        let n = samples.len() as f64;
+        let staked = bet_returns.len() as f64;
+        let (calibration_slope, calibration_intercept) = calibration_fit(&points);
+
+        EvaluationReport {
+            sample_count: samples.len(),
+            accuracy: if n > 0.0 { correct as f64 / n } else { 0.0 },
+            log_loss: if n > 0.0 { log_loss / n } else { 0.0 },
+            brier_score: if n > 0.0 { brier / n } else { 0.0 },
+            calibration_slope,
+            calibration_intercept,
+            calibration: calibration_buckets(&points),
+            roi_if_followed: if staked > 0.0 { bet_returns.iter().sum::<f64>() / staked } else { 0.0 },
+            bets_followed: bet_returns.len(),
+            sharpe_ratio: sharpe_ratio(&bet_returns),
+            max_drawdown: max_drawdown(&bet_returns),
+        }
+    }
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub(crate) fn outcome_probabilities(prediction: &Prediction) -> [f64; 3] {
+    [
+        prediction.home_win_prob,
+        prediction.draw_prob.unwrap_or(0.0),
+        prediction.away_win_prob,
+    ]
+}
+
+/// Least-squares fit of observed frequency against predicted probability.
+/// A perfectly calibrated model has slope 1 and intercept 0.
+fn calibration_fit(points: &[(f64, f64)]) -> (f64, f64) {
+    if points.is_empty() {
+        return (1.0, 0.0);
+    }
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let cov = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f64>();
+    let var = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>();
+
+    if var <= f64::EPSILON {
+        return (1.0, 0.0);
+    }
+
+    let slope = cov / var;
+    (slope, mean_y - slope * mean_x)
+}
+
+fn sharpe_ratio(returns: &[f64]) -> f64 {
+    if returns.len() < 2 {
+        return 0.0;
+    }
+
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let std_dev = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+    if std_dev > f64::EPSILON { mean / std_dev } else { 0.0 }
+}
+
+fn max_drawdown(returns: &[f64]) -> f64 {
+    let mut cumulative: f64 = 0.0;
+    let mut peak: f64 = 0.0;
+    let mut drawdown: f64 = 0.0;
+    for r in returns {
+        cumulative += r;
+        peak = peak.max(cumulative);
+        drawdown = drawdown.max(peak - cumulative);
+    }
+    drawdown
+}
+
+fn calibration_buckets(points: &[(f64, f64)]) -> Vec<CalibrationBucket> {
+    let mut sums = vec![(0usize, 0.0, 0.0); CALIBRATION_BUCKETS];
+    for (predicted, observed) in points {
+        let bucket = ((predicted * CALIBRATION_BUCKETS as f64) as usize).min(CALIBRATION_BUCKETS - 1);
+        sums[bucket].0 += 1;
+        sums[bucket].1 += predicted;
+        sums[bucket].2 += observed;
+    }
+
+    sums.into_iter()
+        .enumerate()
+        .filter(|(_, (count, _, _))| *count > 0)
+        .map(|(i, (count, predicted, observed))| CalibrationBucket {
+            lower: i as f64 / CALIBRATION_BUCKETS as f64,
+            upper: (i + 1) as f64 / CALIBRATION_BUCKETS as f64,
+            count,
+            mean_predicted: predicted / count as f64,
+            observed_rate: observed / count as f64,
+        })
+        .collect()
+}
+
+/// A baseline distribution split at its deciles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinnedDistribution {
+    /// Upper edge of each bin but the last, which is open-ended
+    pub edges: Vec<f64>,
+    /// Share of the baseline in each bin
+    pub proportions: Vec<f64>,
+}
+
+impl BinnedDistribution {
+    pub fn fit(values: &[f64]) -> Self {
+        let mut sorted: Vec<f64> = values.iter().copied().filter(|value| value.is_finite()).collect();
+        sorted.sort_by(f64::total_cmp);
+        let mut edges: Vec<f64> = (1..DRIFT_BINS)
+            .filter_map(|i| sorted.get(i * sorted.len() / DRIFT_BINS).copied())
+            .collect();
+        edges.dedup();
+        let mut distribution = Self { edges, proportions: Vec::new() };
+        distribution.proportions = distribution.proportions_of(sorted.iter().copied());
+        distribution
+    }
+
+    /// Share of `values` falling in each of this distribution's bins
+    pub fn proportions_of(&self, values: impl IntoIterator<Item = f64>) -> Vec<f64> {
+        let mut counts = vec![0usize; self.edges.len() + 1];
+        for value in values {
+            counts[self.edges.partition_point(|edge| value > *edge)] += 1;
+        }
+        let total = counts.iter().sum::<usize>().max(1) as f64;
+        counts.into_iter().map(|count| count as f64 / total).collect()
+    }
+}
+
+/// Population stability index of `actual` against `expected` bin shares.
+/// Under 0.1 is usually read as stable and over 0.25 as a real shift.
+pub fn population_stability_index(expected: &[f64], actual: &[f64]) -> f64 {
+    expected.iter().zip(actual)
+        .map(|(e, a)| {
+            let (e, a) = (e.max(DRIFT_EPSILON), a.max(DRIFT_EPSILON));
+            (a - e) * (a / e).ln()
+        })
+        .sum()
+}
+
+/// KL divergence of `actual` from `expected` bin shares
+pub fn kl_divergence(expected: &[f64], actual: &[f64]) -> f64 {
+    expected.iter().zip(actual)
+        .map(|(e, a)| {
+            let (e, a) = (e.max(DRIFT_EPSILON), a.max(DRIFT_EPSILON));
+            a * (a / e).ln()
+        })
+        .sum()
+}
+
+/// Feature and outcome probability distributions over a model's training
+/// set, for spotting when live data stops looking like it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DriftBaseline {
+    pub features: BTreeMap<String, BinnedDistribution>,
+    /// Home, draw and away probabilities the model gave the training set
+    pub predictions: BTreeMap<String, BinnedDistribution>,
+}
+
+impl DriftBaseline {
+    /// From training `rows` of the features in `feature_names`, and the
+    /// probabilities the model predicts for them
+    pub fn fit(feature_names: &[String], rows: &[Vec<f64>], probabilities: &[[f64; 3]]) -> Self {
+        let features = feature_names.iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let values: Vec<f64> = rows.iter().filter_map(|row| row.get(i).copied()).collect();
+                (name.clone(), BinnedDistribution::fit(&values))
+            })
+            .collect();
+        let predictions = PREDICTION_DISTRIBUTIONS.iter()
+            .enumerate()
+            .map(|(k, name)| {
+                let values: Vec<f64> = probabilities.iter().map(|probs| probs[k]).collect();
+                (name.to_string(), BinnedDistribution::fit(&values))
+            })
+            .collect();
+        Self { features, predictions }
+    }
+}
+
+/// How far a window may drift from the baseline before it is flagged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DriftThresholds {
+    pub max_psi: f64,
+    pub max_kl_divergence: f64,
+    /// Most recent observations compared with the baseline
+    pub window: usize,
+    /// Observations needed before a distribution is scored at all
+    pub min_samples: usize,
+}
+
+impl Default for DriftThresholds {
+    fn default() -> Self {
+        Self {
+            max_psi: 0.25,
+            max_kl_divergence: 0.1,
+            window: 500,
+            min_samples: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftScore {
+    pub name: String,
+    pub psi: f64,
+    pub kl_divergence: f64,
+    pub samples: usize,
+    pub drifted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftReport {
+    /// Only distributions with enough recent observations are scored
+    pub features: Vec<DriftScore>,
+    pub predictions: Vec<DriftScore>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl DriftReport {
+    pub fn drifted(&self) -> impl Iterator<Item = &DriftScore> {
+        self.features.iter().chain(&self.predictions).filter(|score| score.drifted)
+    }
+}
+
+/// Keeps sliding windows of live feature values and predicted
+/// probabilities and scores them against a training baseline
+#[derive(Debug, Clone)]
+pub struct DriftMonitor {
+    baseline: DriftBaseline,
+    thresholds: DriftThresholds,
+    features: BTreeMap<String, VecDeque<f64>>,
+    predictions: BTreeMap<String, VecDeque<f64>>,
+}
+
+impl DriftMonitor {
+    pub fn new(baseline: DriftBaseline, thresholds: DriftThresholds) -> Self {
+        Self {
+            baseline,
+            thresholds,
+            features: BTreeMap::new(),
+            predictions: BTreeMap::new(),
+        }
+    }
+
+    /// Record the baseline's features from a vector the model predicted from
+    pub fn observe_features(&mut self, features: &FeatureVector) {
+        let window = self.thresholds.window.max(1);
+        for name in self.baseline.features.keys() {
+            if let Some(value) = features.features.get(name).filter(|value| value.is_finite()) {
+                push_window(self.features.entry(name.clone()).or_default(), *value, window);
+            }
+        }
+    }
+
+    pub fn observe_prediction(&mut self, prediction: &Prediction) {
+        let window = self.thresholds.window.max(1);
+        for (name, value) in PREDICTION_DISTRIBUTIONS.iter().zip(outcome_probabilities(prediction)) {
+            push_window(self.predictions.entry(name.to_string()).or_default(), value, window);
+        }
+    }
+
+    pub fn report(&self) -> DriftReport {
+        DriftReport {
+            features: self.scores(&self.baseline.features, &self.features),
+            predictions: self.scores(&self.baseline.predictions, &self.predictions),
+            checked_at: Utc::now(),
+        }
+    }
+
+    fn scores(
+        &self,
+        baseline: &BTreeMap<String, BinnedDistribution>,
+        windows: &BTreeMap<String, VecDeque<f64>>,
+    ) -> Vec<DriftScore> {
+        windows.iter()
+            .filter(|(_, values)| values.len() >= self.thresholds.min_samples)
+            .filter_map(|(name, values)| {
+                let expected = baseline.get(name)?;
+                let actual = expected.proportions_of(values.iter().copied());
+                let psi = population_stability_index(&expected.proportions, &actual);
+                let kl_divergence = kl_divergence(&expected.proportions, &actual);
+                Some(DriftScore {
+                    name: name.clone(),
+                    psi,
+                    kl_divergence,
+                    samples: values.len(),
+                    drifted: psi > self.thresholds.max_psi || kl_divergence > self.thresholds.max_kl_divergence,
+                })
+            })
+            .collect()
+    }
+}
+
+fn push_window(values: &mut VecDeque<f64>, value: f64, window: usize) {
+    if values.len() >= window {
+        values.pop_front();
+    }
+    values.push_back(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+    use std::collections::HashMap;
+
+    fn sample(home: f64, draw: f64, away: f64, outcome: Outcome1X2) -> EvaluationSample {
+        let prediction = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), home, away, Utc::now(),
+        ).unwrap().with_draw_prob(draw).unwrap();
+
+        EvaluationSample {
+            prediction,
+            outcome,
+            odds: Some(SimpleMarketOdds::new(dec!(2.0), dec!(3.5), dec!(4.0))),
+        }
+    }
+
+    #[test]
+    fn test_evaluation_metrics() {
+        let samples = vec![
+            sample(0.6, 0.25, 0.15, Outcome1X2::HomeWin),
+            sample(0.6, 0.25, 0.15, Outcome1X2::AwayWin),
+        ];
+
+        let report = Evaluator::new().evaluate(&samples);
+
+        assert_eq!(report.sample_count, 2);
+        assert!((report.accuracy - 0.5).abs() < 1e-9);
+        assert!((report.log_loss - (-(0.6f64.ln() + 0.15f64.ln()) / 2.0)).abs() < 1e-9);
+        // One winner at 2.0 and one loser on two 1-unit stakes breaks even
+        assert_eq!(report.bets_followed, 2);
+        assert!(report.roi_if_followed.abs() < 1e-9);
+        assert!((report.max_drawdown - 1.0).abs() < 1e-9);
+
+        let performance = report.to_model_performance("test", "1.0");
+        assert_eq!(performance.total_predictions, 2);
+        assert_eq!(performance.correct_predictions, 1);
+        assert_eq!(report.calibration.iter().map(|b| b.count).sum::<usize>(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_model_evaluated_at_closing_odds() {
+        let model = Model::Poisson(crate::PoissonModel::new());
+        let features = FeatureVector {
+            match_id: "m1".to_string(),
+            features: HashMap::new(),
+            timestamp: Utc::now(),
+            schema: None,
+        };
+        let picked = model.predict(&features).await.unwrap().most_likely_outcome();
+
+        let example = |closing_odds| EvaluationExample {
+            features: features.clone(),
+            outcome: picked,
+            closing_odds,
+        };
+        let examples = vec![
+            example(Some(SimpleMarketOdds::new(dec!(2.5), dec!(2.5), dec!(2.5)))),
+            example(None),
+        ];
+
+        let report = Evaluator::new().evaluate_model(&model, &examples).await.unwrap();
+        assert_eq!(report.sample_count, 2);
+        assert_eq!(report.bets_followed, 1);
+        assert!((report.roi_if_followed - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_drift_flagged_once_live_features_shift() {
+        let names = vec!["elo_difference".to_string()];
+        let rows: Vec<Vec<f64>> = (0..1000).map(|i| vec![(i % 200) as f64 - 100.0]).collect();
+        let probabilities: Vec<[f64; 3]> = (0..1000).map(|i| {
+            let home = 0.3 + (i % 40) as f64 / 100.0;
+            [home, 0.25, 0.75 - home]
+        }).collect();
+        let baseline = DriftBaseline::fit(&names, &rows, &probabilities);
+        assert_eq!(baseline.features["elo_difference"].proportions.len(), DRIFT_BINS);
+
+        let thresholds = DriftThresholds { window: 200, min_samples: 50, ..Default::default() };
+        let mut monitor = DriftMonitor::new(baseline, thresholds);
+        let observe = |monitor: &mut DriftMonitor, elo: f64, home: f64| {
+            let features = FeatureVector {
+                match_id: "m1".to_string(),
+                features: HashMap::from([("elo_difference".to_string(), elo)]),
+                timestamp: Utc::now(),
+                schema: None,
+            };
+            let prediction = Prediction::new(
+                "m1".to_string(), "test".to_string(), "1.0".to_string(), home, 0.75 - home, Utc::now(),
+            ).unwrap().with_draw_prob(0.25).unwrap();
+            monitor.observe_features(&features);
+            monitor.observe_prediction(&prediction);
+        };
+
+        // Too few observations to judge yet
+        observe(&mut monitor, 500.0, 0.7);
+        assert!(monitor.report().features.is_empty());
+
+        // Live data drawn like the training set doesn't drift
+        for i in 0..200 {
+            observe(&mut monitor, (i % 200) as f64 - 100.0, 0.3 + (i % 40) as f64 / 100.0);
+        }
+        let report = monitor.report();
+        assert_eq!(report.features[0].samples, 200);
+        assert_eq!(report.drifted().count(), 0, "{:?}", report);
+
+        // Every team suddenly far stronger at home, and the model agreeing
+        for _ in 0..200 {
+            observe(&mut monitor, 250.0, 0.72);
+        }
+        let report = monitor.report();
+        let drifted: Vec<&str> = report.drifted().map(|score| score.name.as_str()).collect();
+        assert!(drifted.contains(&"elo_difference"));
+        assert!(drifted.contains(&"home_win_prob"));
+        assert!(report.features[0].psi > 1.0);
+    }
+}
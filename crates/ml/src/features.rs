@@ -1,11 +1,21 @@
-use quant_models::{MatchEvent, FeatureVector, EventType, MatchStatus};
+use crate::schema::{FeatureGroup, FeaturePipeline};
+use crate::elo::EloRatings;
+use crate::standings::{LeagueTable, LeagueTables};
+use crate::models::{FITTED_AWAY_GOALS_FEATURE, FITTED_HOME_GOALS_FEATURE};
+use crate::training::{DixonColesFit, MatchResult};
+use crate::xg::{XgTimeline, XgTracker};
+use quant_models::{Absentee, MatchEvent, FeatureVector, EventType, MatchStatus, CardType, TeamRating};
 use anyhow::Result;
 use std::collections::HashMap;
 use chrono::{DateTime, Utc, Timelike, Datelike};
 use std::sync::{Arc, RwLock};
 use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+/// An absentee carrying at least this share of the team counts as a key player
+const KEY_PLAYER_IMPORTANCE: f64 = 0.05;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TeamStats {
     pub goals_for: u32,
     pub goals_against: u32,
@@ -17,9 +27,11 @@ pub struct TeamStats {
     pub fouls: u32,
     pub offsides: u32,
     pub recent_form: Vec<bool>, // Win = true, Loss/Draw = false
-    pub elo_rating: f64,
     pub attack_strength: f64,
     pub defense_strength: f64,
+    /// The formation of the team's latest line-up
+    #[serde(default)]
+    pub formation: Option<String>,
 }
 
 impl Default for TeamStats {
@@ -35,14 +47,14 @@ impl Default for TeamStats {
             fouls: 0,
             offsides: 0,
             recent_form: Vec::new(),
-            elo_rating: 1500.0, // Standard Elo starting rating
             attack_strength: 1.0,
             defense_strength: 1.0,
+            formation: None,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MatchContext {
     pub minute: u8,
     pub home_score: u8,
@@ -51,12 +63,65 @@ pub struct MatchContext {
     pub intensity: f64, // 0.0 to 1.0
     pub last_goal_minute: Option<u8>,
     pub last_goal_team: Option<String>,
+    /// Players sent off so far
+    pub home_red_cards: u8,
+    pub away_red_cards: u8,
+    /// Each side's line-up, once named
+    #[serde(default)]
+    pub home_lineup: Option<LineupContext>,
+    #[serde(default)]
+    pub away_lineup: Option<LineupContext>,
+    pub last_updated: DateTime<Utc>,
+}
+
+/// What a side's team news means for the match
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineupContext {
+    pub formation: String,
+    /// The formation the team lined up in for its previous match, if known
+    pub previous_formation: Option<String>,
+    /// Summed importance of the players missing, capped at 1
+    pub missing_impact: f64,
+    pub key_players_missing: u32,
+}
+
+impl LineupContext {
+    fn new(formation: &str, previous_formation: Option<String>, absentees: &[Absentee]) -> Self {
+        Self {
+            formation: formation.to_string(),
+            previous_formation,
+            missing_impact: absentees.iter().map(|absentee| absentee.importance.max(0.0)).sum::<f64>().min(1.0),
+            key_players_missing: absentees.iter()
+                .filter(|absentee| absentee.importance >= KEY_PLAYER_IMPORTANCE)
+                .count() as u32,
+        }
+    }
+
+    pub fn formation_changed(&self) -> bool {
+        self.previous_formation.as_ref().is_some_and(|previous| *previous != self.formation)
+    }
+}
+
+/// What the feature engineer has built up about teams and matches, for
+/// carrying it across a restart. Shot timelines are not kept.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeatureState {
+    pub team_stats: HashMap<String, TeamStats>,
+    pub match_contexts: HashMap<String, MatchContext>,
+    pub elo_ratings: Vec<TeamRating>,
+    #[serde(default)]
+    pub league_tables: Vec<LeagueTable>,
 }
 
 pub struct FeatureEngineer {
     team_stats: Arc<DashMap<String, TeamStats>>,
     match_contexts: Arc<DashMap<String, MatchContext>>,
     league_averages: Arc<RwLock<HashMap<String, LeagueAverages>>>,
+    xg: Arc<XgTracker>,
+    elo: Arc<EloRatings>,
+    standings: Arc<LeagueTables>,
+    dixon_coles: Arc<RwLock<Option<Arc<DixonColesFit>>>>,
+    pipeline: FeaturePipeline,
 }
 
 #[derive(Debug, Clone)]
@@ -82,33 +147,73 @@ impl FeatureEngineer {
             team_stats: Arc::new(DashMap::new()),
             match_contexts: Arc::new(DashMap::new()),
             league_averages: Arc::new(RwLock::new(HashMap::new())),
+            xg: Arc::new(XgTracker::new()),
+            elo: Arc::new(EloRatings::new()),
+            standings: Arc::new(LeagueTables::new()),
+            dixon_coles: Arc::new(RwLock::new(None)),
+            pipeline: FeaturePipeline::default(),
         }
     }
     
+    /// Read team ratings from these instead of a fresh set
+    pub fn with_elo_ratings(mut self, elo: Arc<EloRatings>) -> Self {
+        self.elo = elo;
+        self
+    }
+    
+    pub fn elo_ratings(&self) -> Arc<EloRatings> {
+        self.elo.clone()
+    }
+    
+    /// Read league positions from these instead of a fresh set
+    pub fn with_standings(mut self, standings: Arc<LeagueTables>) -> Self {
+        self.standings = standings;
+        self
+    }
+    
+    pub fn standings(&self) -> Arc<LeagueTables> {
+        self.standings.clone()
+    }
+    
+    /// Team strengths the Poisson model prices from; replaces any earlier fit
+    pub fn set_dixon_coles(&self, fit: impl Into<Arc<DixonColesFit>>) {
+        *self.dixon_coles.write().unwrap() = Some(fit.into());
+    }
+    
+    pub fn dixon_coles(&self) -> Option<Arc<DixonColesFit>> {
+        self.dixon_coles.read().unwrap().clone()
+    }
+    
+    /// Run these stages and tag vectors with this feature set instead of the in-play one
+    pub fn with_pipeline(mut self, pipeline: FeaturePipeline) -> Self {
+        self.pipeline = pipeline;
+        self
+    }
+    
+    pub fn pipeline(&self) -> &FeaturePipeline {
+        &self.pipeline
+    }
+    
     pub async fn extract_features(&self, event: &MatchEvent) -> Result<FeatureVector> {
         self.update_context(event).await?;
         
         let mut features = HashMap::new();
         
-        // Basic match state features
-        self.add_match_state_features(&mut features, event);
-        
-        // Team performance features
-        self.add_team_features(&mut features, event);
-        
-        // Situational features
-        self.add_situational_features(&mut features, event);
-        
-        // Time-based features
-        self.add_temporal_features(&mut features, event);
-        
-        // League context features
-        self.add_league_features(&mut features, event);
+        for group in FeatureGroup::ALL.into_iter().filter(|group| self.pipeline.runs(*group)) {
+            match group {
+                FeatureGroup::MatchState => self.add_match_state_features(&mut features, event),
+                FeatureGroup::Team => self.add_team_features(&mut features, event),
+                FeatureGroup::Situational => self.add_situational_features(&mut features, event),
+                FeatureGroup::Temporal => self.add_temporal_features(&mut features, event),
+                FeatureGroup::League => self.add_league_features(&mut features, event),
+            }
+        }
         
         Ok(FeatureVector {
             match_id: event.match_id.clone(),
             features,
             timestamp: Utc::now(),
+            schema: Some(self.pipeline.schema().id()),
         })
     }
     
@@ -123,7 +228,13 @@ impl FeatureEngineer {
                 intensity: 0.5,
                 last_goal_minute: None,
                 last_goal_team: None,
+                home_red_cards: 0,
+                away_red_cards: 0,
+                home_lineup: None,
+                away_lineup: None,
+                last_updated: Utc::now(),
             });
+        context.last_updated = Utc::now();
         
         // Update based on event type
         match &event.event_type {
@@ -135,13 +246,44 @@ impl FeatureEngineer {
                     context.away_score += 1;
                     context.momentum = (context.momentum - 0.3).max(-1.0);
                 }
+                // The feed's running score beats counting, which a missed goal throws off
+                if let Some(score) = &event.score {
+                    context.home_score = score.home;
+                    context.away_score = score.away;
+                }
+                context.minute = *minute;
                 context.last_goal_minute = Some(*minute);
                 context.last_goal_team = Some(team.clone());
                 context.intensity = (context.intensity + 0.2).min(1.0);
             }
-            EventType::Card { minute, .. } => {
+            EventType::Card { team, card_type, minute, .. } => {
                 context.minute = *minute;
                 context.intensity = (context.intensity + 0.1).min(1.0);
+                if matches!(card_type, CardType::Red) {
+                    if team == &event.team_home {
+                        context.home_red_cards += 1;
+                    } else {
+                        context.away_red_cards += 1;
+                    }
+                }
+            }
+            EventType::Substitution { minute, .. } => {
+                context.minute = *minute;
+            }
+            EventType::Shot { minute, .. } => {
+                context.minute = *minute;
+                self.xg.record(event);
+            }
+            EventType::Lineup { team, formation, absentees, .. } => {
+                let lineup = if team == &event.team_home { &mut context.home_lineup } else { &mut context.away_lineup };
+                let mut stats = self.team_stats.entry(team.clone()).or_default();
+                // A revised line-up is still compared with the previous match's
+                let previous_formation = match lineup {
+                    Some(named) => named.previous_formation.clone(),
+                    None => stats.formation.clone(),
+                };
+                *lineup = Some(LineupContext::new(formation, previous_formation, absentees));
+                stats.formation = Some(formation.clone());
             }
             _ => {}
         }
@@ -164,6 +306,8 @@ impl FeatureEngineer {
                            (ctx.home_score as i8 - ctx.away_score as i8) as f64);
             features.insert("total_goals".to_string(), 
                            (ctx.home_score + ctx.away_score) as f64);
+            features.insert("home_red_cards".to_string(), ctx.home_red_cards as f64);
+            features.insert("away_red_cards".to_string(), ctx.away_red_cards as f64);
             features.insert("momentum".to_string(), ctx.momentum);
             features.insert("intensity".to_string(), ctx.intensity);
             
@@ -185,19 +329,29 @@ impl FeatureEngineer {
             let time_pressure = if ctx.minute > 80 { 1.0 } else { 0.0 };
             features.insert("time_pressure".to_string(), time_pressure);
         }
+        
+        // Chances created so far, from the shots seen
+        let (home_xg, away_xg) = self.xg.totals(&event.match_id);
+        features.insert("home_xg".to_string(), home_xg);
+        features.insert("away_xg".to_string(), away_xg);
     }
     
     fn add_team_features(&self, features: &mut HashMap<String, f64>, event: &MatchEvent) {
+        // Clone out of the map: holding two entry guards at once deadlocks
+        // when both teams hash to the same shard
         let home_stats = self.team_stats.entry(event.team_home.clone())
-            .or_insert_with(TeamStats::default);
+            .or_insert_with(TeamStats::default)
+            .clone();
         let away_stats = self.team_stats.entry(event.team_away.clone())
-            .or_insert_with(TeamStats::default);
+            .or_insert_with(TeamStats::default)
+            .clone();
         
         // Elo ratings
-        features.insert("home_elo".to_string(), home_stats.elo_rating);
-        features.insert("away_elo".to_string(), away_stats.elo_rating);
-        features.insert("elo_difference".to_string(), 
-                       home_stats.elo_rating - away_stats.elo_rating);
+        let home_elo = self.elo.rating(&event.team_home);
+        let away_elo = self.elo.rating(&event.team_away);
+        features.insert("home_elo".to_string(), home_elo);
+        features.insert("away_elo".to_string(), away_elo);
+        features.insert("elo_difference".to_string(), home_elo - away_elo);
         
         // Attack/Defense strength
         features.insert("home_attack".to_string(), home_stats.attack_strength);
@@ -205,11 +359,13 @@ impl FeatureEngineer {
         features.insert("away_attack".to_string(), away_stats.attack_strength);
         features.insert("away_defense".to_string(), away_stats.defense_strength);
         
-        // Expected goals based on strength
-        let home_xg = home_stats.attack_strength * away_stats.defense_strength;
-        let away_xg = away_stats.attack_strength * home_stats.defense_strength;
-        features.insert("home_expected_goals".to_string(), home_xg);
-        features.insert("away_expected_goals".to_string(), away_xg);
+        // Fitted strengths, once both teams have some
+        let fitted = self.dixon_coles()
+            .and_then(|fit| fit.expected_goals(&event.team_home, &event.team_away));
+        if let Some((home_goals, away_goals)) = fitted {
+            features.insert(FITTED_HOME_GOALS_FEATURE.to_string(), home_goals);
+            features.insert(FITTED_AWAY_GOALS_FEATURE.to_string(), away_goals);
+        }
         
         // Form features
         let home_form = self.calculate_form_score(&home_stats.recent_form);
@@ -223,6 +379,19 @@ impl FeatureEngineer {
         let away_discipline = (away_stats.yellow_cards + away_stats.red_cards * 2) as f64;
         features.insert("home_discipline".to_string(), home_discipline);
         features.insert("away_discipline".to_string(), away_discipline);
+        
+        // Team news: who is missing and whether the shape has changed
+        let (home_lineup, away_lineup) = self.match_contexts.get(&event.match_id)
+            .map(|ctx| (ctx.home_lineup.clone(), ctx.away_lineup.clone()))
+            .unwrap_or_default();
+        for (side, lineup) in [("home", home_lineup), ("away", away_lineup)] {
+            let (impact, key_players, changed) = lineup.map_or((0.0, 0.0, false), |lineup| {
+                (lineup.missing_impact, lineup.key_players_missing as f64, lineup.formation_changed())
+            });
+            features.insert(format!("{}_missing_impact", side), impact);
+            features.insert(format!("{}_key_players_missing", side), key_players);
+            features.insert(format!("{}_formation_change", side), if changed { 1.0 } else { 0.0 });
+        }
     }
     
     fn add_situational_features(&self, features: &mut HashMap<String, f64>, event: &MatchEvent) {
@@ -282,6 +451,22 @@ impl FeatureEngineer {
             _ => 0.70,
         };
         features.insert("league_competitiveness".to_string(), competitiveness);
+        
+        // Table position and what is at stake, once the team has a result this season
+        let home = self.standings.pressure(&event.league, &event.season, &event.team_home);
+        let away = self.standings.pressure(&event.league, &event.season, &event.team_away);
+        for (side, pressure) in [("home", home), ("away", away)] {
+            if let Some(pressure) = pressure {
+                features.insert(format!("{}_table_position", side), pressure.position as f64);
+                features.insert(format!("{}_points_per_game", side), pressure.points_per_game);
+            }
+            features.insert(format!("{}_relegation_pressure", side), pressure.map_or(0.0, |pressure| pressure.relegation));
+            features.insert(format!("{}_title_pressure", side), pressure.map_or(0.0, |pressure| pressure.title));
+        }
+        if let (Some(home), Some(away)) = (home, away) {
+            // Positive when the home side is higher
+            features.insert("table_position_difference".to_string(), away.position as f64 - home.position as f64);
+        }
     }
     
     fn calculate_form_score(&self, recent_form: &[bool]) -> f64 {
@@ -316,27 +501,223 @@ impl FeatureEngineer {
         stats.goals_for += goals_for;
         stats.goals_against += goals_against;
         
-        // Update Elo rating (simplified)
-        let expected_score = 1.0 / (1.0 + 10_f64.powf((1500.0 - stats.elo_rating) / 400.0));
-        let actual_score = if goals_for > goals_against { 1.0 } 
-                          else if goals_for == goals_against { 0.5 } 
-                          else { 0.0 };
-        
-        let k_factor = 32.0;
-        stats.elo_rating += k_factor * (actual_score - expected_score);
-        
         // Update attack/defense strength
         stats.attack_strength = (stats.goals_for as f64 / 10.0).max(0.1).min(3.0);
         stats.defense_strength = (10.0 / (stats.goals_against as f64 + 1.0)).max(0.1).min(3.0);
         
         // Update form
-        stats.recent_form.push(actual_score > 0.5);
+        stats.recent_form.push(goals_for > goals_against);
         if stats.recent_form.len() > 10 {
             stats.recent_form.remove(0);
         }
     }
     
+    /// Seed each team's stats with its last `per_team` of `results`, so
+    /// form and strengths mean something from its first match. Elo ratings
+    /// are replayed from the same matches only while none are loaded, since
+    /// stored ratings already count them. League tables are rebuilt from
+    /// every result of each league's latest season while none are held.
+    /// Returns the teams seeded.
+    pub fn warm_start(&self, results: &[MatchResult], per_team: usize) -> usize {
+        let mut results: Vec<&MatchResult> = results.iter().collect();
+        results.sort_by_key(|result| result.played_at);
+
+        let mut by_team: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, result) in results.iter().enumerate() {
+            by_team.entry(&result.home_team).or_default().push(index);
+            by_team.entry(&result.away_team).or_default().push(index);
+        }
+
+        let mut replayed = vec![false; results.len()];
+        for (team, matches) in &by_team {
+            for &index in &matches[matches.len().saturating_sub(per_team)..] {
+                let result = results[index];
+                let (goals_for, goals_against) = if result.home_team == *team {
+                    (result.home_goals, result.away_goals)
+                } else {
+                    (result.away_goals, result.home_goals)
+                };
+                self.update_team_stats(team, goals_for.into(), goals_against.into());
+                replayed[index] = true;
+            }
+        }
+
+        if self.elo.is_empty() {
+            for (result, _) in results.iter().zip(&replayed).filter(|(_, replayed)| **replayed) {
+                self.elo.record_result(&result.home_team, &result.away_team, result.home_goals, result.away_goals, &result.season);
+            }
+        }
+        if self.standings.is_empty() {
+            for result in results.iter().filter(|result| !result.league.is_empty()) {
+                self.standings.record_result(&result.league, &result.season, &result.home_team, &result.away_team,
+                                             result.home_goals, result.away_goals);
+            }
+        }
+        by_team.len()
+    }
+
     pub fn get_team_stats(&self, team: &str) -> Option<TeamStats> {
         self.team_stats.get(team).map(|entry| entry.clone())
     }
-}
\ No newline at end of file
+
+    pub fn get_match_context(&self, match_id: &str) -> Option<MatchContext> {
+        self.match_contexts.get(match_id).map(|entry| entry.clone())
+    }
+
+    /// Every shot in a match so far with its xG and the running totals
+    pub fn xg_timeline(&self, match_id: &str) -> Option<XgTimeline> {
+        self.xg.timeline(match_id)
+    }
+
+    /// Overwrite a match's score, e.g. when the feed's own score shows a
+    /// goal event was missed
+    pub fn correct_score(&self, match_id: &str, home_score: u8, away_score: u8) {
+        if let Some(mut context) = self.match_contexts.get_mut(match_id) {
+            context.home_score = home_score;
+            context.away_score = away_score;
+            context.last_updated = Utc::now();
+        }
+    }
+
+    pub fn context_count(&self) -> usize {
+        self.match_contexts.len()
+    }
+
+    /// Approximate bytes held per match context entry (key + value + last goal team)
+    pub fn context_entry_size(&self) -> usize {
+        std::mem::size_of::<String>() * 2 + std::mem::size_of::<MatchContext>() + 32
+    }
+
+    pub fn state(&self) -> FeatureState {
+        FeatureState {
+            team_stats: self.team_stats.iter().map(|entry| (entry.key().clone(), entry.value().clone())).collect(),
+            match_contexts: self.match_contexts.iter().map(|entry| (entry.key().clone(), entry.value().clone())).collect(),
+            elo_ratings: self.elo.ratings(),
+            league_tables: self.standings.tables(),
+        }
+    }
+
+    /// Take up teams, matches, ratings and tables from an earlier run, replacing
+    /// any of the same name
+    pub fn restore_state(&self, state: FeatureState) {
+        for (team, stats) in state.team_stats {
+            self.team_stats.insert(team, stats);
+        }
+        for (match_id, context) in state.match_contexts {
+            self.match_contexts.insert(match_id, context);
+        }
+        self.elo.load(state.elo_ratings);
+        self.standings.load(state.league_tables);
+    }
+
+    /// Forget every team and match, ahead of rebuilding them from the event log
+    pub fn reset(&self) {
+        self.team_stats.clear();
+        self.match_contexts.clear();
+        self.xg.clear();
+        self.league_averages.write().unwrap().clear();
+    }
+
+    /// Drop the `count` least recently updated match contexts
+    pub fn evict_oldest_contexts(&self, count: usize) {
+        if count == 0 {
+            return;
+        }
+
+        let mut by_age: Vec<(String, DateTime<Utc>)> = self.match_contexts
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().last_updated))
+            .collect();
+        by_age.sort_by_key(|(_, updated)| *updated);
+
+        for (match_id, _) in by_age.into_iter().take(count) {
+            self.match_contexts.remove(&match_id);
+            self.xg.remove(&match_id);
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_models::AbsenceReason;
+
+    fn lineup(match_id: &str, team: &str, formation: &str, absentees: Vec<Absentee>) -> MatchEvent {
+        MatchEvent::new(
+            match_id.to_string(),
+            EventType::Lineup { team: team.to_string(), formation: formation.to_string(), starters: Vec::new(), absentees },
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_lineup_features_from_team_news() {
+        let engineer = FeatureEngineer::new();
+        engineer.extract_features(&lineup("m1", "Arsenal", "4-3-3", Vec::new())).await.unwrap();
+
+        let absentee = |player: &str, importance| Absentee {
+            player: player.to_string(),
+            reason: AbsenceReason::Injury,
+            importance,
+        };
+        let features = engineer.extract_features(&lineup(
+            "m2", "Arsenal", "3-4-3", vec![absentee("Saka", 0.12), absentee("Tierney", 0.02)],
+        )).await.unwrap().features;
+        assert!((features["home_missing_impact"] - 0.14).abs() < 1e-9);
+        assert_eq!(features["home_key_players_missing"], 1.0);
+        assert_eq!(features["home_formation_change"], 1.0);
+        assert_eq!(features["away_missing_impact"], 0.0);
+
+        // A late change to the same match is still against the previous match's shape
+        let features = engineer.extract_features(&lineup("m2", "Arsenal", "4-3-3", Vec::new())).await.unwrap().features;
+        assert_eq!(features["home_formation_change"], 0.0);
+        assert_eq!(features["home_missing_impact"], 0.0);
+    }
+
+    #[test]
+    fn test_warm_start_seeds_each_teams_last_results() {
+        let start = Utc::now() - chrono::Duration::days(30);
+        let result = |day, home: &str, away: &str, home_goals, away_goals| MatchResult {
+            home_team: home.to_string(),
+            away_team: away.to_string(),
+            home_goals,
+            away_goals,
+            played_at: start + chrono::Duration::days(day),
+            season: "2024-25".to_string(),
+            league: "Premier League".to_string(),
+        };
+        // Given newest first; Arsenal lost the oldest, which falls outside its last 3
+        let results = [
+            result(4, "Chelsea", "Arsenal", 0, 2),
+            result(3, "Arsenal", "Everton", 1, 1),
+            result(2, "Arsenal", "Fulham", 3, 0),
+            result(1, "Fulham", "Arsenal", 1, 0),
+        ];
+
+        let engineer = FeatureEngineer::new();
+        assert_eq!(engineer.warm_start(&results, 3), 4);
+        let arsenal = engineer.get_team_stats("Arsenal").unwrap();
+        assert_eq!(arsenal.recent_form, [true, false, true]);
+        assert_eq!((arsenal.goals_for, arsenal.goals_against), (6, 1));
+        assert_eq!(engineer.get_team_stats("Fulham").unwrap().recent_form, [true, false]);
+        assert!(engineer.elo_ratings().rating("Arsenal") > engineer.elo_ratings().rating("Chelsea"));
+        assert_eq!(engineer.elo_ratings().get("Fulham").unwrap().matches_played, 2);
+        // The table counts every result, Arsenal's oldest too
+        let table = engineer.standings().table("Premier League").unwrap();
+        assert_eq!(table.standing("Arsenal").unwrap().overall.played, 4);
+
+        // Stored ratings already count these results
+        let engineer = FeatureEngineer::new();
+        engineer.elo_ratings().load([TeamRating {
+            team: "Arsenal".to_string(),
+            rating: 1600.0,
+            matches_played: 10,
+            season: Some("2024-25".to_string()),
+            updated_at: Utc::now(),
+        }]);
+        engineer.warm_start(&results, 3);
+        assert_eq!(engineer.elo_ratings().rating("Arsenal"), 1600.0);
+    }
+}
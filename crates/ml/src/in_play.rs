@@ -0,0 +1,163 @@
+// In-play match outcome model: the goals still to come in the time left,
+// on top of the score as it stands
+
+use crate::models::{PoissonModel, SCORE_MATRIX_MAX_GOALS};
+use anyhow::Result;
+use quant_models::{FeatureVector, Prediction, ScoreMatrix};
+
+/// Regulation time plus the stoppage time usually added across both halves
+const MATCH_MINUTES: f64 = 94.0;
+/// Even in stoppage time there is a little football left
+const MIN_REMAINING_MINUTES: f64 = 1.0;
+/// Goals per side covered when adding up the rest of the match
+const MAX_REMAINING_GOALS: u32 = 10;
+/// Scoring rate multipliers per player sent off: the side a man down scores
+/// less and concedes more
+const SENT_OFF_OWN_RATE: f64 = 0.7;
+const SENT_OFF_OPPONENT_RATE: f64 = 1.2;
+
+/// Remaining-time Poisson: each side's pre-match expected goals, scaled to
+/// the minutes left and for red cards, are added to the current score
+#[derive(Debug)]
+pub struct InPlayModel {
+    name: String,
+    version: String,
+    /// Where pre-match goal expectations come from
+    pre_match: PoissonModel,
+}
+
+impl InPlayModel {
+    pub fn new() -> Self {
+        Self {
+            name: "InPlayPoisson".to_string(),
+            version: "v1.0".to_string(),
+            pre_match: PoissonModel::new(),
+        }
+    }
+
+    pub fn model_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn model_version(&self) -> &str {
+        &self.version
+    }
+
+    /// Goals each side is expected to add from here
+    pub fn remaining_goals(&self, features: &FeatureVector) -> (f64, f64) {
+        let feature = |name: &str| features.features.get(name).copied().unwrap_or(0.0);
+        let minute = feature("minute");
+        let (home_reds, away_reds) = (feature("home_red_cards"), feature("away_red_cards"));
+
+        let remaining = (MATCH_MINUTES - minute).max(MIN_REMAINING_MINUTES) / MATCH_MINUTES;
+        let (full_home, full_away) = self.pre_match.expected_goals(features);
+        (
+            full_home * remaining * SENT_OFF_OWN_RATE.powf(home_reds) * SENT_OFF_OPPONENT_RATE.powf(away_reds),
+            full_away * remaining * SENT_OFF_OWN_RATE.powf(away_reds) * SENT_OFF_OPPONENT_RATE.powf(home_reds),
+        )
+    }
+
+    pub async fn predict(&self, features: &FeatureVector) -> Result<Prediction> {
+        let feature = |name: &str| features.features.get(name).copied().unwrap_or(0.0);
+        let (home_score, away_score) = (feature("home_score").max(0.0) as u32, feature("away_score").max(0.0) as u32);
+        let (remaining_home, remaining_away) = self.remaining_goals(features);
+
+        let mut outcomes = [0.0; 3];
+        for home_goals in 0..=MAX_REMAINING_GOALS {
+            for away_goals in 0..=MAX_REMAINING_GOALS {
+                let prob = PoissonModel::poisson_probability(remaining_home, home_goals)
+                    * PoissonModel::poisson_probability(remaining_away, away_goals);
+                let outcome = match (home_score + home_goals).cmp(&(away_score + away_goals)) {
+                    std::cmp::Ordering::Greater => 0,
+                    std::cmp::Ordering::Equal => 1,
+                    std::cmp::Ordering::Less => 2,
+                };
+                outcomes[outcome] += prob;
+            }
+        }
+
+        // Never quite certain: a settled-looking match can still turn
+        let outcomes = outcomes.map(|prob| prob.clamp(0.001, 0.998));
+        let total: f64 = outcomes.iter().sum();
+        let [home_win_prob, draw_prob, away_win_prob] = outcomes.map(|prob| prob / total);
+
+        // Less is left to happen the later it gets
+        let elapsed = (feature("minute") / MATCH_MINUTES).clamp(0.0, 1.0);
+        let matrix = ScoreMatrix::from_fn(SCORE_MATRIX_MAX_GOALS, |home_goals, away_goals| {
+            match (u32::from(home_goals).checked_sub(home_score), u32::from(away_goals).checked_sub(away_score)) {
+                (Some(home), Some(away)) => {
+                    PoissonModel::poisson_probability(remaining_home, home)
+                        * PoissonModel::poisson_probability(remaining_away, away)
+                }
+                _ => 0.0,
+            }
+        });
+
+        let prediction = Prediction::new(
+            features.match_id.clone(),
+            self.model_name().to_string(),
+            self.model_version().to_string(),
+            home_win_prob,
+            away_win_prob,
+            features.timestamp,
+        )?
+        .with_draw_prob(draw_prob)?
+        .with_confidence(0.5 + 0.5 * elapsed)?
+        .with_expected_goals(home_score as f64 + remaining_home, away_score as f64 + remaining_away)
+        .with_score_matrix(matrix);
+
+        Ok(prediction)
+    }
+}
+
+impl Default for InPlayModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    fn features(minute: f64, home_score: f64, away_score: f64) -> FeatureVector {
+        FeatureVector {
+            match_id: "m1".to_string(),
+            features: HashMap::from([
+                ("minute".to_string(), minute),
+                ("home_score".to_string(), home_score),
+                ("away_score".to_string(), away_score),
+            ]),
+            timestamp: Utc::now(),
+            schema: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_late_lead_is_nearly_decided() {
+        let model = InPlayModel::new();
+
+        let kickoff = model.predict(&features(0.0, 0.0, 0.0)).await.unwrap();
+        let late_lead = model.predict(&features(85.0, 2.0, 0.0)).await.unwrap();
+        assert!(kickoff.home_win_prob < 0.6);
+        assert!(late_lead.home_win_prob > 0.95, "home win {}", late_lead.home_win_prob);
+        assert!(late_lead.confidence > kickoff.confidence);
+        assert!(late_lead.expected_goals_home.unwrap() > 2.0);
+        // A score already behind the current one can't be the final score
+        assert_eq!(late_lead.score_matrix.as_ref().unwrap().probability(1, 0), 0.0);
+
+        // Level late on, a draw is the likeliest result
+        let late_level = model.predict(&features(85.0, 1.0, 1.0)).await.unwrap();
+        assert!(late_level.draw_prob.unwrap() > 0.7);
+
+        // A red card swings the rest of the match to the other side
+        let mut sent_off = features(30.0, 0.0, 0.0);
+        let level = model.remaining_goals(&sent_off);
+        sent_off.features.insert("home_red_cards".to_string(), 1.0);
+        let (home, away) = model.remaining_goals(&sent_off);
+        assert!(home < level.0 && away > level.1);
+        assert!(model.predict(&sent_off).await.unwrap().away_win_prob > kickoff.away_win_prob);
+    }
+}
@@ -0,0 +1,262 @@
+// Batched inference executor running models on a dedicated thread pool
+
+use crate::models::Model;
+use quant_models::{FeatureVector, Prediction};
+use anyhow::{anyhow, Result};
+use crossbeam::channel::{self, Receiver, RecvTimeoutError, Sender, TrySendError};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, RwLock};
+
+#[derive(Debug, Clone)]
+pub struct BatchInferenceConfig {
+    pub worker_threads: usize,
+    pub max_batch_size: usize,
+    pub max_batch_wait_ms: u64,
+    pub queue_capacity: usize,
+}
+
+impl Default for BatchInferenceConfig {
+    fn default() -> Self {
+        Self {
+            worker_threads: 2,
+            max_batch_size: 32,
+            max_batch_wait_ms: 5,
+            queue_capacity: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceQueueStats {
+    pub queue_depth: usize,
+    pub in_flight: usize,
+    pub submitted: u64,
+    pub completed: u64,
+    pub rejected: u64,
+    pub batches: u64,
+    pub avg_batch_size: f64,
+    pub avg_latency_ms: f64,
+}
+
+#[derive(Debug, Default)]
+struct InferenceQueueMetrics {
+    queue_depth: AtomicUsize,
+    in_flight: AtomicUsize,
+    submitted: AtomicU64,
+    completed: AtomicU64,
+    rejected: AtomicU64,
+    batches: AtomicU64,
+    total_latency_us: AtomicU64,
+}
+
+struct InferenceJob {
+    features: FeatureVector,
+    enqueued_at: Instant,
+    respond_to: oneshot::Sender<Result<Prediction>>,
+}
+
+/// Runs model inference off the async runtime so heavy models (GBMs, ONNX nets)
+/// cannot stall the event pipeline. Jobs are queued and grouped into batches so
+/// each worker takes the model lock once per batch rather than once per event.
+pub struct InferenceExecutor {
+    sender: Option<Sender<InferenceJob>>,
+    metrics: Arc<InferenceQueueMetrics>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl InferenceExecutor {
+    pub fn new(model: Arc<RwLock<Model>>, config: BatchInferenceConfig) -> Result<Self> {
+        let (sender, receiver) = channel::bounded(config.queue_capacity.max(1));
+        let metrics = Arc::new(InferenceQueueMetrics::default());
+
+        let mut workers = Vec::with_capacity(config.worker_threads.max(1));
+        for worker_id in 0..config.worker_threads.max(1) {
+            let receiver = receiver.clone();
+            let model = model.clone();
+            let metrics = metrics.clone();
+            let config = config.clone();
+
+            let handle = std::thread::Builder::new()
+                .name(format!("inference-{}", worker_id))
+                .spawn(move || run_worker(receiver, model, metrics, config))?;
+            workers.push(handle);
+        }
+
+        tracing::info!("🧵 Batched inference executor started with {} workers (batch size {})",
+                      workers.len(), config.max_batch_size);
+
+        Ok(Self {
+            sender: Some(sender),
+            metrics,
+            workers,
+        })
+    }
+
+    pub async fn predict(&self, features: FeatureVector) -> Result<Prediction> {
+        let sender = self.sender.as_ref()
+            .ok_or_else(|| anyhow!("Inference executor has been shut down"))?;
+
+        let (respond_to, response) = oneshot::channel();
+        let job = InferenceJob {
+            features,
+            enqueued_at: Instant::now(),
+            respond_to,
+        };
+
+        // Count the job as queued before sending so a fast worker never sees a
+        // depth lower than what it is about to dequeue
+        self.metrics.queue_depth.fetch_add(1, Ordering::Relaxed);
+        match sender.try_send(job) {
+            Ok(()) => {
+                self.metrics.submitted.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(TrySendError::Full(_)) => {
+                self.metrics.queue_depth.fetch_sub(1, Ordering::Relaxed);
+                self.metrics.rejected.fetch_add(1, Ordering::Relaxed);
+                return Err(anyhow!("Inference queue is full"));
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                self.metrics.queue_depth.fetch_sub(1, Ordering::Relaxed);
+                return Err(anyhow!("Inference workers have stopped"));
+            }
+        }
+
+        response.await.map_err(|_| anyhow!("Inference worker dropped the request"))?
+    }
+
+    pub fn stats(&self) -> InferenceQueueStats {
+        let completed = self.metrics.completed.load(Ordering::Relaxed);
+        let batches = self.metrics.batches.load(Ordering::Relaxed);
+        let total_latency_us = self.metrics.total_latency_us.load(Ordering::Relaxed);
+
+        InferenceQueueStats {
+            queue_depth: self.metrics.queue_depth.load(Ordering::Relaxed),
+            in_flight: self.metrics.in_flight.load(Ordering::Relaxed),
+            submitted: self.metrics.submitted.load(Ordering::Relaxed),
+            completed,
+            rejected: self.metrics.rejected.load(Ordering::Relaxed),
+            batches,
+            avg_batch_size: if batches > 0 { completed as f64 / batches as f64 } else { 0.0 },
+            avg_latency_ms: if completed > 0 {
+                total_latency_us as f64 / completed as f64 / 1000.0
+            } else {
+                0.0
+            },
+        }
+    }
+
+    pub fn shutdown(&mut self) {
+        // Dropping the sender lets workers drain the queue and exit
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            if worker.join().is_err() {
+                tracing::warn!("Inference worker panicked during shutdown");
+            }
+        }
+    }
+}
+
+impl Drop for InferenceExecutor {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn run_worker(
+    receiver: Receiver<InferenceJob>,
+    model: Arc<RwLock<Model>>,
+    metrics: Arc<InferenceQueueMetrics>,
+    config: BatchInferenceConfig,
+) {
+    let runtime = match tokio::runtime::Builder::new_current_thread().build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            tracing::error!("❌ Failed to start inference worker runtime: {}", e);
+            return;
+        }
+    };
+    let max_wait = Duration::from_millis(config.max_batch_wait_ms);
+
+    while let Ok(first) = receiver.recv() {
+        let mut batch = vec![first];
+        let deadline = Instant::now() + max_wait;
+
+        while batch.len() < config.max_batch_size.max(1) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match receiver.recv_timeout(remaining) {
+                Ok(job) => batch.push(job),
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        metrics.queue_depth.fetch_sub(batch.len(), Ordering::Relaxed);
+        metrics.in_flight.fetch_add(batch.len(), Ordering::Relaxed);
+        metrics.batches.fetch_add(1, Ordering::Relaxed);
+
+        {
+            let model = model.blocking_read();
+            for job in batch.drain(..) {
+                let result = runtime.block_on(model.predict(&job.features));
+                let latency_us = job.enqueued_at.elapsed().as_micros() as u64;
+
+                metrics.total_latency_us.fetch_add(latency_us, Ordering::Relaxed);
+                metrics.completed.fetch_add(1, Ordering::Relaxed);
+                metrics.in_flight.fetch_sub(1, Ordering::Relaxed);
+
+                // Caller may have given up waiting; nothing to do in that case
+                let _ = job.respond_to.send(result);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PoissonModel;
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    fn test_features(match_id: &str) -> FeatureVector {
+        FeatureVector {
+            match_id: match_id.to_string(),
+            features: HashMap::new(),
+            timestamp: Utc::now(),
+            schema: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batched_predictions_complete() {
+        let model = Arc::new(RwLock::new(Model::Poisson(PoissonModel::new())));
+        let executor = Arc::new(InferenceExecutor::new(model, BatchInferenceConfig {
+            worker_threads: 2,
+            max_batch_size: 4,
+            max_batch_wait_ms: 2,
+            queue_capacity: 64,
+        }).unwrap());
+
+        let mut handles = Vec::new();
+        for i in 0..10 {
+            let executor = executor.clone();
+            handles.push(tokio::spawn(async move {
+                executor.predict(test_features(&format!("match_{}", i))).await
+            }));
+        }
+
+        for handle in handles {
+            let prediction = handle.await.unwrap().unwrap();
+            assert_eq!(prediction.model_name, "PoissonGoals");
+        }
+
+        let stats = executor.stats();
+        assert_eq!(stats.submitted, 10);
+        assert_eq!(stats.completed, 10);
+        assert_eq!(stats.queue_depth, 0);
+        assert!(stats.batches >= 3); // max 4 per batch
+    }
+}
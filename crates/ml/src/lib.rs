@@ -1,9 +1,25 @@
 pub mod models;
 pub mod features;
+pub mod schema;
 pub mod training;
 pub mod evaluation;
+pub mod tracker;
+pub mod xg;
+pub mod elo;
+pub mod standings;
+pub mod in_play;
+#[cfg(feature = "batched-inference")]
+pub mod inference;
 
 pub use models::*;
 pub use features::*;
+pub use schema::*;
 pub use training::*;
-pub use evaluation::*;
\ No newline at end of file
+pub use evaluation::*;
+pub use tracker::*;
+pub use xg::*;
+pub use elo::*;
+pub use standings::*;
+pub use in_play::*;
+#[cfg(feature = "batched-inference")]
+pub use inference::*;
\ No newline at end of file
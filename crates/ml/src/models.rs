@@ -1,22 +1,38 @@
-use quant_models::{Prediction, FeatureVector};
+use crate::in_play::InPlayModel;
+use crate::schema::FeatureSchema;
+use crate::tracker::ModelTracker;
+use crate::training::ModelArtifact;
+use quant_models::{Prediction, FeatureVector, Outcome1X2, HandicapProbabilities, HandicapResult, MatchHalf, ScoreMatrix};
 use anyhow::Result;
 use chrono::Utc;
+use dashmap::DashMap;
 use nalgebra::DVector;
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-use rand::Rng;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 
 pub struct ModelFeedback {
     pub prediction_id: uuid::Uuid,
     pub actual_outcome: bool,
     pub reward: f64,
+    /// The realised match result, when known. Without it the model can only
+    /// learn whether its most likely outcome was right or wrong.
+    pub outcome: Option<Outcome1X2>,
 }
 
+/// Maximum number of prediction feature vectors kept around for training
+const MAX_STORED_FEATURES: usize = 10_000;
+
 #[derive(Debug)]
 pub enum Model {
     LogisticRegression(LogisticRegressionModel),
     Poisson(PoissonModel),
     Ensemble(EnsembleModel),
+    /// Conditions on the score, minute and red cards of a match under way
+    InPlay(InPlayModel),
 }
 
 impl Model {
@@ -25,6 +41,7 @@ impl Model {
             Model::LogisticRegression(m) => m.model_name(),
             Model::Poisson(m) => m.model_name(),
             Model::Ensemble(m) => m.model_name(),
+            Model::InPlay(m) => m.model_name(),
         }
     }
     
@@ -33,6 +50,7 @@ impl Model {
             Model::LogisticRegression(m) => m.model_version(),
             Model::Poisson(m) => m.model_version(),
             Model::Ensemble(m) => m.model_version(),
+            Model::InPlay(m) => m.model_version(),
         }
     }
     
@@ -41,100 +59,339 @@ impl Model {
             Model::LogisticRegression(m) => m.predict(features).await,
             Model::Poisson(m) => m.predict(features).await,
             Model::Ensemble(m) => m.predict(features).await,
+            Model::InPlay(m) => m.predict(features).await,
+        }
+    }
+    
+    /// Load offline-trained logistic weights into this model. An ensemble
+    /// keeps its Poisson component; a Poisson model is replaced outright,
+    /// and an in-play model has no logistic weights to replace.
+    pub fn install_artifact(&mut self, artifact: &ModelArtifact) {
+        let trained = LogisticRegressionModel::from_artifact(artifact);
+        *self = match std::mem::replace(self, Model::LogisticRegression(LogisticRegressionModel::new())) {
+            Model::Ensemble(ensemble) => Model::Ensemble(ensemble.with_logistic_model(trained)),
+            Model::LogisticRegression(_) | Model::Poisson(_) => Model::LogisticRegression(trained),
+            Model::InPlay(in_play) => Model::InPlay(in_play),
+        };
+    }
+    
+    /// The Poisson model, or an ensemble's Poisson component
+    pub fn poisson_model(&self) -> Option<&PoissonModel> {
+        match self {
+            Model::Poisson(m) => Some(m),
+            Model::Ensemble(m) => Some(m.poisson_model()),
+            Model::LogisticRegression(_) | Model::InPlay(_) => None,
+        }
+    }
+    
+    /// Current component weights, if this is an ensemble
+    pub fn ensemble_weights(&self) -> Option<EnsembleWeights> {
+        match self {
+            Model::Ensemble(m) => Some(m.weights()),
+            Model::LogisticRegression(_) | Model::Poisson(_) | Model::InPlay(_) => None,
+        }
+    }
+    
+    /// What the model has learned online so far
+    pub fn state(&self) -> ModelState {
+        let (logistic, ensemble_weights) = match self {
+            Model::LogisticRegression(m) => (Some(m.logistic_weights()), None),
+            Model::Ensemble(m) => (Some(m.logistic_model.logistic_weights()), Some((m.logistic_weight, m.poisson_weight))),
+            Model::Poisson(_) | Model::InPlay(_) => (None, None),
+        };
+        ModelState {
+            name: self.model_name().to_string(),
+            version: self.model_version().to_string(),
+            logistic,
+            poisson_lambdas: self.poisson_model().map(PoissonModel::lambdas),
+            ensemble_weights,
         }
     }
     
+    /// Pick up where an earlier run of the same model left off. Fails
+    /// without changing anything if the state is of another model or
+    /// version, so a newly deployed artifact isn't overwritten.
+    pub fn restore_state(&mut self, state: &ModelState) -> Result<()> {
+        if state.name != self.model_name() || state.version != self.model_version() {
+            anyhow::bail!("state is of {} {}, but the model is {} {}",
+                          state.name, state.version, self.model_name(), self.model_version());
+        }
+        let logistic_model = match self {
+            Model::LogisticRegression(m) => Some(&*m),
+            Model::Ensemble(m) => Some(&m.logistic_model),
+            Model::Poisson(_) | Model::InPlay(_) => None,
+        };
+        if let (Some(model), Some(weights)) = (logistic_model, &state.logistic) {
+            model.set_logistic_weights(weights)?;
+        }
+        if let (Some(poisson), Some(lambdas)) = (self.poisson_model(), state.poisson_lambdas) {
+            poisson.set_lambdas(lambdas);
+        }
+        if let (Model::Ensemble(m), Some((logistic_weight, poisson_weight))) = (self, state.ensemble_weights) {
+            m.logistic_weight = logistic_weight;
+            m.poisson_weight = poisson_weight;
+        }
+        Ok(())
+    }
+    
     pub async fn update_weights(&mut self, feedback: &ModelFeedback) -> Result<()> {
         match self {
             Model::LogisticRegression(m) => m.update_weights(feedback).await,
             Model::Poisson(m) => m.update_weights(feedback).await,
             Model::Ensemble(m) => m.update_weights(feedback).await,
+            // Nothing learned online; the score and clock carry it
+            Model::InPlay(_) => Ok(()),
         }
     }
 }
 
+const MIN_PROBABILITY: f64 = 0.01;
+
+/// Normalise home/draw/away probabilities, keeping each at least
+/// `MIN_PROBABILITY` (so at most 0.98). Whatever is lifted to the floor is
+/// taken from the others in proportion, so the floor survives the renormalising.
+fn bounded_probabilities(probabilities: [f64; 3]) -> [f64; 3] {
+    let total: f64 = probabilities.iter().sum();
+    let mut probabilities = probabilities.map(|p| p / total);
+    
+    // Each pass floors at least one more outcome, and two at most can be
+    while probabilities.iter().any(|&p| p < MIN_PROBABILITY) {
+        let floored = probabilities.iter().filter(|&&p| p <= MIN_PROBABILITY).count();
+        let free: f64 = probabilities.iter().filter(|&&p| p > MIN_PROBABILITY).sum();
+        let scale = (1.0 - floored as f64 * MIN_PROBABILITY) / free;
+        probabilities = probabilities.map(|p| if p <= MIN_PROBABILITY { MIN_PROBABILITY } else { p * scale });
+    }
+    probabilities
+}
+
+/// A model's online-learned parameters, for carrying them across a restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelState {
+    pub name: String,
+    pub version: String,
+    /// The logistic model's weights, or the ensemble's logistic component's
+    pub logistic: Option<LogisticWeights>,
+    /// Home and away goal rates of the Poisson model or component
+    pub poisson_lambdas: Option<(f64, f64)>,
+    /// The ensemble's logistic and Poisson weighting
+    pub ensemble_weights: Option<(f64, f64)>,
+}
+
+/// Logistic weights over raw features, per outcome (home, draw, away)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogisticWeights {
+    pub home_win: Vec<f64>,
+    pub draw: Vec<f64>,
+    pub away_win: Vec<f64>,
+    pub bias: [f64; 3],
+}
+
 #[derive(Debug, Clone)]
 pub struct ModelWeights {
     pub home_win: DVector<f64>,
     pub draw: DVector<f64>,
     pub away_win: DVector<f64>,
+    /// Intercept per outcome (home, draw, away)
+    pub bias: [f64; 3],
     pub learning_rate: f64,
     pub regularization: f64,
+    pub max_gradient_norm: f64,
 }
 
-impl Default for ModelWeights {
-    fn default() -> Self {
-        let size = 30; // Number of features we expect
+impl ModelWeights {
+    /// Small random weights over `size` features
+    pub fn for_features(size: usize) -> Self {
+        Self::from_rng(size, &mut rand::thread_rng())
+    }
+    
+    /// Small random weights over `size` features, the same ones for the same seed
+    pub fn seeded(size: usize, seed: u64) -> Self {
+        Self::from_rng(size, &mut StdRng::seed_from_u64(seed))
+    }
+    
+    fn from_rng(size: usize, rng: &mut impl Rng) -> Self {
         Self {
-            home_win: DVector::from_fn(size, |_, _| rand::thread_rng().gen_range(-0.01..0.01)),
-            draw: DVector::from_fn(size, |_, _| rand::thread_rng().gen_range(-0.01..0.01)),
-            away_win: DVector::from_fn(size, |_, _| rand::thread_rng().gen_range(-0.01..0.01)),
+            home_win: DVector::from_fn(size, |_, _| rng.gen_range(-0.01..0.01)),
+            draw: DVector::from_fn(size, |_, _| rng.gen_range(-0.01..0.01)),
+            away_win: DVector::from_fn(size, |_, _| rng.gen_range(-0.01..0.01)),
+            bias: [0.0; 3],
             learning_rate: 0.001,
             regularization: 0.01,
+            max_gradient_norm: 5.0, // Raw features (e.g. Elo) are unscaled
         }
     }
 }
 
+impl Default for ModelWeights {
+    fn default() -> Self {
+        Self::for_features(FeatureSchema::in_play().len())
+    }
+}
+
 #[derive(Debug)]
 pub struct LogisticRegressionModel {
     name: String,
     version: String,
     weights: Arc<RwLock<ModelWeights>>,
-    feature_names: Vec<String>,
+    /// The feature set it was trained on, and the only one it accepts
+    schema: FeatureSchema,
+    feature_store: Arc<FeatureStore>,
+}
+
+/// Feature vectors used for recent predictions, keyed by prediction id, so
+/// feedback can be turned into a proper gradient step later on.
+#[derive(Debug, Default)]
+struct FeatureStore {
+    entries: DashMap<uuid::Uuid, StoredFeatures>,
+    insertion_order: Mutex<VecDeque<uuid::Uuid>>,
+}
+
+#[derive(Debug, Clone)]
+struct StoredFeatures {
+    features: FeatureVector,
+    predicted_outcome: Outcome1X2,
+}
+
+impl FeatureStore {
+    fn insert(&self, prediction_id: uuid::Uuid, stored: StoredFeatures) {
+        self.entries.insert(prediction_id, stored);
+        
+        let mut order = self.insertion_order.lock().unwrap();
+        order.push_back(prediction_id);
+        while order.len() > MAX_STORED_FEATURES {
+            if let Some(oldest) = order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+    
+    fn take(&self, prediction_id: &uuid::Uuid) -> Option<StoredFeatures> {
+        self.entries.remove(prediction_id).map(|(_, stored)| stored)
+    }
+    
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
 }
 
 impl LogisticRegressionModel {
     pub fn new() -> Self {
-        let feature_names = vec![
-            "minute".to_string(),
-            "home_score".to_string(),
-            "away_score".to_string(),
-            "score_difference".to_string(),
-            "total_goals".to_string(),
-            "momentum".to_string(),
-            "intensity".to_string(),
-            "game_phase".to_string(),
-            "time_pressure".to_string(),
-            "home_elo".to_string(),
-            "away_elo".to_string(),
-            "elo_difference".to_string(),
-            "home_attack".to_string(),
-            "home_defense".to_string(),
-            "away_attack".to_string(),
-            "away_defense".to_string(),
-            "home_expected_goals".to_string(),
-            "away_expected_goals".to_string(),
-            "home_form".to_string(),
-            "away_form".to_string(),
-            "form_difference".to_string(),
-            "home_discipline".to_string(),
-            "away_discipline".to_string(),
-            "match_status".to_string(),
-            "event_influence".to_string(),
-            "home_advantage".to_string(),
-            "hour_of_day".to_string(),
-            "is_evening".to_string(),
-            "day_of_week".to_string(),
-            "league_competitiveness".to_string(),
-        ];
-        
         Self {
             name: "LogisticRegression".to_string(),
             version: "v1.0".to_string(),
             weights: Arc::new(RwLock::new(ModelWeights::default())),
-            feature_names,
+            schema: FeatureSchema::in_play(),
+            feature_store: Arc::new(FeatureStore::default()),
         }
     }
     
-    fn extract_feature_vector(&self, features: &FeatureVector) -> DVector<f64> {
-        let mut feature_vec = Vec::with_capacity(self.feature_names.len());
+    /// Read this feature set instead of the in-play one, starting from fresh weights
+    pub fn with_schema(mut self, schema: FeatureSchema) -> Self {
+        self.weights = Arc::new(RwLock::new(ModelWeights::for_features(schema.len())));
+        self.schema = schema;
+        self
+    }
+    
+    /// Start from the random weights drawn from `seed`, so runs can be reproduced
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.weights = Arc::new(RwLock::new(ModelWeights::seeded(self.schema.len(), seed)));
+        self
+    }
+    
+    /// Build a model from offline-trained weights. The artifact's feature
+    /// standardization is folded into the weights and bias so raw features
+    /// can be fed straight in.
+    pub fn from_artifact(artifact: &ModelArtifact) -> Self {
+        let schema = artifact.schema();
+        let fold = |k: usize| -> (DVector<f64>, f64) {
+            let weights = DVector::from_iterator(
+                schema.len(),
+                artifact.weights[k].iter().zip(&artifact.feature_scales).map(|(w, scale)| w / scale),
+            );
+            let offset: f64 = weights.iter().zip(&artifact.feature_means).map(|(w, mean)| w * mean).sum();
+            (weights, artifact.bias[k] - offset)
+        };
+        let (home_win, home_bias) = fold(0);
+        let (draw, draw_bias) = fold(1);
+        let (away_win, away_bias) = fold(2);
         
-        for feature_name in &self.feature_names {
-            let value = features.features.get(feature_name).copied().unwrap_or(0.0);
-            feature_vec.push(value);
+        Self {
+            name: artifact.name.clone(),
+            version: artifact.version.clone(),
+            weights: Arc::new(RwLock::new(ModelWeights {
+                home_win,
+                draw,
+                away_win,
+                bias: [home_bias, draw_bias, away_bias],
+                ..ModelWeights::default()
+            })),
+            schema,
+            feature_store: Arc::new(FeatureStore::default()),
         }
-        
-        DVector::from_vec(feature_vec)
+    }
+    
+    pub fn schema(&self) -> &FeatureSchema {
+        &self.schema
+    }
+    
+    pub fn logistic_weights(&self) -> LogisticWeights {
+        let weights = self.weights.read().unwrap();
+        LogisticWeights {
+            home_win: weights.home_win.iter().copied().collect(),
+            draw: weights.draw.iter().copied().collect(),
+            away_win: weights.away_win.iter().copied().collect(),
+            bias: weights.bias,
+        }
+    }
+    
+    /// Replace the weights, keeping the learning rate and regularization.
+    /// Fails if they don't cover this model's feature set.
+    pub fn set_logistic_weights(&self, logistic: &LogisticWeights) -> Result<()> {
+        let size = self.schema.len();
+        if [&logistic.home_win, &logistic.draw, &logistic.away_win].iter().any(|w| w.len() != size) {
+            anyhow::bail!("weights don't cover the {} features of {}", size, self.schema.id());
+        }
+        let mut weights = self.weights.write().unwrap();
+        weights.home_win = DVector::from_column_slice(&logistic.home_win);
+        weights.draw = DVector::from_column_slice(&logistic.draw);
+        weights.away_win = DVector::from_column_slice(&logistic.away_win);
+        weights.bias = logistic.bias;
+        Ok(())
+    }
+    
+    /// Number of predictions whose features are still available for training
+    pub fn stored_feature_count(&self) -> usize {
+        self.feature_store.len()
+    }
+    
+    fn record_features(&self, prediction: &Prediction, features: &FeatureVector) {
+        self.feature_store.insert(prediction.id, StoredFeatures {
+            features: features.clone(),
+            predicted_outcome: prediction.most_likely_outcome(),
+        });
+    }
+    
+    /// Target distribution over (home, draw, away) for a feedback signal
+    fn target_distribution(feedback: &ModelFeedback, predicted: &Outcome1X2) -> [f64; 3] {
+        let mut target = [0.0; 3];
+        match (&feedback.outcome, feedback.actual_outcome) {
+            (Some(outcome), _) => target[outcome.index()] = 1.0,
+            (None, true) => target[predicted.index()] = 1.0,
+            (None, false) => {
+                // Only know the predicted outcome was wrong: spread the mass
+                // evenly over the remaining outcomes
+                for (i, value) in target.iter_mut().enumerate() {
+                    if i != predicted.index() {
+                        *value = 0.5;
+                    }
+                }
+            }
+        }
+        target
+    }
+    
+    fn extract_feature_vector(&self, features: &FeatureVector) -> Result<DVector<f64>> {
+        Ok(DVector::from_vec(self.schema.values(features)?))
     }
     
     fn sigmoid(&self, x: f64) -> f64 {
@@ -150,36 +407,35 @@ impl LogisticRegressionModel {
 }
 
 impl LogisticRegressionModel {
-    fn model_name(&self) -> &str {
+    pub fn model_name(&self) -> &str {
         &self.name
     }
     
-    fn model_version(&self) -> &str {
+    pub fn model_version(&self) -> &str {
         &self.version
     }
     
-    async fn predict(&self, features: &FeatureVector) -> Result<Prediction> {
-        let feature_vec = self.extract_feature_vector(features);
+    pub async fn predict(&self, features: &FeatureVector) -> Result<Prediction> {
+        let prediction = self.compute_prediction(features)?;
+        self.record_features(&prediction, features);
+        Ok(prediction)
+    }
+    
+    fn compute_prediction(&self, features: &FeatureVector) -> Result<Prediction> {
+        let feature_vec = self.extract_feature_vector(features)?;
         let weights = self.weights.read().unwrap();
         
         // Calculate logits for each outcome
-        let home_logit = weights.home_win.dot(&feature_vec);
-        let draw_logit = weights.draw.dot(&feature_vec);
-        let away_logit = weights.away_win.dot(&feature_vec);
+        let home_logit = weights.home_win.dot(&feature_vec) + weights.bias[0];
+        let draw_logit = weights.draw.dot(&feature_vec) + weights.bias[1];
+        let away_logit = weights.away_win.dot(&feature_vec) + weights.bias[2];
         
         // Apply softmax to get probabilities
         let logits = vec![home_logit, draw_logit, away_logit];
         let probabilities = self.softmax(&logits);
         
-        let home_win_prob = probabilities[0].max(0.01).min(0.98);
-        let draw_prob = probabilities[1].max(0.01).min(0.98);
-        let away_win_prob = probabilities[2].max(0.01).min(0.98);
-        
-        // Normalize to ensure they sum to ~1.0
-        let total = home_win_prob + draw_prob + away_win_prob;
-        let home_win_prob = home_win_prob / total;
-        let draw_prob = draw_prob / total;
-        let away_win_prob = away_win_prob / total;
+        let [home_win_prob, draw_prob, away_win_prob] =
+            bounded_probabilities([probabilities[0], probabilities[1], probabilities[2]]);
         
         // Calculate confidence based on entropy
         let entropy = -probabilities.iter()
@@ -199,55 +455,109 @@ impl LogisticRegressionModel {
         )?
         .with_draw_prob(draw_prob)?
         .with_confidence(confidence)?
-        .with_features(self.feature_names.clone());
+        .with_features(self.schema.features.clone());
         
         Ok(prediction)
     }
     
-    async fn update_weights(&mut self, feedback: &ModelFeedback) -> Result<()> {
-        // Simplified weight update using gradient descent
-        // In a real implementation, you'd store the features used for each prediction
-        // and use them here for proper gradient calculation
+    pub async fn update_weights(&mut self, feedback: &ModelFeedback) -> Result<()> {
+        let Some(stored) = self.feature_store.take(&feedback.prediction_id) else {
+            tracing::debug!("No stored features for prediction {}, skipping update", feedback.prediction_id);
+            return Ok(());
+        };
         
-        let mut weights = self.weights.write().unwrap();
-        let adjustment = feedback.reward * weights.learning_rate;
+        let x = self.extract_feature_vector(&stored.features)?;
+        let target = Self::target_distribution(feedback, &stored.predicted_outcome);
         
-        // Apply small adjustments to weights based on feedback
-        if feedback.actual_outcome {
-            // Positive outcome - slightly increase all weights
-            weights.home_win *= 1.0 + adjustment * 0.1;
-            weights.draw *= 1.0 + adjustment * 0.05;
-            weights.away_win *= 1.0 + adjustment * 0.1;
-        } else {
-            // Negative outcome - slightly decrease weights
-            weights.home_win *= 1.0 - adjustment * 0.1;
-            weights.draw *= 1.0 - adjustment * 0.05;
-            weights.away_win *= 1.0 - adjustment * 0.1;
+        let mut guard = self.weights.write().unwrap();
+        let weights = &mut *guard;
+        let logits = [
+            weights.home_win.dot(&x) + weights.bias[0],
+            weights.draw.dot(&x) + weights.bias[1],
+            weights.away_win.dot(&x) + weights.bias[2],
+        ];
+        let probabilities = self.softmax(&logits);
+        
+        // Softmax cross-entropy gradient per class: (p_k - y_k) * x + lambda * w_k
+        let learning_rate = weights.learning_rate;
+        let regularization = weights.regularization;
+        let max_norm = weights.max_gradient_norm;
+        
+        for (k, bias) in weights.bias.iter_mut().enumerate() {
+            *bias -= learning_rate * (probabilities[k] - target[k]);
+        }
+        
+        let class_weights = [&mut weights.home_win, &mut weights.draw, &mut weights.away_win];
+        for (k, w) in class_weights.into_iter().enumerate() {
+            let mut gradient = &x * (probabilities[k] - target[k]) + &*w * regularization;
+            
+            let norm = gradient.norm();
+            if norm > max_norm {
+                gradient *= max_norm / norm;
+            }
+            
+            *w -= gradient * learning_rate;
         }
         
         Ok(())
     }
 }
 
+/// League-average goals per match, used when a prediction carries no
+/// expected goals of its own
+pub const DEFAULT_TOTAL_GOALS: f64 = 2.7;
+
+/// Share of a match's goals scored before half time; legs tire and
+/// trailing sides push, so more come after it
+pub const FIRST_HALF_GOAL_SHARE: f64 = 0.45;
+
+/// Goals per side covered by the score grid used for handicap pricing
+const MAX_HANDICAP_GOALS: u32 = 10;
+
+/// Goals per side in the exact score grid; correct score markets quote up to here
+pub const SCORE_MATRIX_MAX_GOALS: u8 = 5;
+
+/// Low-score correlation used until one is fitted: slightly more 0-0 and
+/// 1-1 draws than independent goal counts give, as in most leagues
+pub const DEFAULT_DIXON_COLES_RHO: f64 = -0.08;
+
+/// Expected goals per side the feature engineer adds when both teams have
+/// fitted Dixon-Coles strengths
+pub const FITTED_HOME_GOALS_FEATURE: &str = "fitted_home_goals";
+pub const FITTED_AWAY_GOALS_FEATURE: &str = "fitted_away_goals";
+
+/// Goal counts that are Poisson but for a Dixon-Coles correction to the four
+/// lowest scores, which independent counts get wrong
 #[derive(Debug)]
 pub struct PoissonModel {
     name: String,
     version: String,
     lambda_home: Arc<RwLock<f64>>,
     lambda_away: Arc<RwLock<f64>>,
+    rho: Arc<RwLock<f64>>,
 }
 
 impl PoissonModel {
     pub fn new() -> Self {
         Self {
             name: "PoissonGoals".to_string(),
-            version: "v1.0".to_string(),
+            version: "v2.0".to_string(),
             lambda_home: Arc::new(RwLock::new(1.4)), // Average goals per team
             lambda_away: Arc::new(RwLock::new(1.3)),
+            rho: Arc::new(RwLock::new(DEFAULT_DIXON_COLES_RHO)),
         }
     }
     
-    fn poisson_probability(&self, lambda: f64, k: u32) -> f64 {
+    pub fn rho(&self) -> f64 {
+        *self.rho.read().unwrap()
+    }
+    
+    /// Use a fitted low-score correlation
+    pub fn set_rho(&self, rho: f64) {
+        *self.rho.write().unwrap() = rho;
+    }
+    
+    pub(crate) fn poisson_probability(lambda: f64, k: u32) -> f64 {
         let e_neg_lambda = (-lambda).exp();
         let lambda_k = lambda.powi(k as i32);
         let k_factorial = (1..=k).fold(1.0, |acc, x| acc * x as f64);
@@ -255,7 +565,31 @@ impl PoissonModel {
         (e_neg_lambda * lambda_k) / k_factorial
     }
     
-    fn calculate_match_probabilities(&self, lambda_home: f64, lambda_away: f64) -> (f64, f64, f64) {
+    /// Dixon-Coles adjustment to the independent probability of a score.
+    /// Negative `rho` makes 0-0 and 1-1 likelier and 1-0 and 0-1 less so;
+    /// other scores are left alone, and the total stays one.
+    pub fn dixon_coles_tau(home_goals: u32, away_goals: u32, lambda_home: f64, lambda_away: f64, rho: f64) -> f64 {
+        match (home_goals, away_goals) {
+            (0, 0) => 1.0 - lambda_home * lambda_away * rho,
+            (0, 1) => 1.0 + lambda_home * rho,
+            (1, 0) => 1.0 + lambda_away * rho,
+            (1, 1) => 1.0 - rho,
+            _ => 1.0,
+        }
+    }
+    
+    pub fn dixon_coles_probability(lambda_home: f64, lambda_away: f64, rho: f64, home_goals: u32, away_goals: u32) -> f64 {
+        Self::dixon_coles_tau(home_goals, away_goals, lambda_home, lambda_away, rho).max(0.0)
+            * Self::poisson_probability(lambda_home, home_goals)
+            * Self::poisson_probability(lambda_away, away_goals)
+    }
+    
+    /// Match winner probabilities for independent goal counts
+    fn calculate_match_probabilities(lambda_home: f64, lambda_away: f64) -> (f64, f64, f64) {
+        Self::dixon_coles_match_probabilities(lambda_home, lambda_away, 0.0)
+    }
+    
+    fn dixon_coles_match_probabilities(lambda_home: f64, lambda_away: f64, rho: f64) -> (f64, f64, f64) {
         let mut home_win = 0.0;
         let mut draw = 0.0;
         let mut away_win = 0.0;
@@ -263,8 +597,7 @@ impl PoissonModel {
         // Calculate probabilities for scores up to 6 goals each (covers ~99% of matches)
         for home_goals in 0..=6 {
             for away_goals in 0..=6 {
-                let prob = self.poisson_probability(lambda_home, home_goals) 
-                         * self.poisson_probability(lambda_away, away_goals);
+                let prob = Self::dixon_coles_probability(lambda_home, lambda_away, rho, home_goals, away_goals);
                 
                 if home_goals > away_goals {
                     home_win += prob;
@@ -278,44 +611,139 @@ impl PoissonModel {
         
         (home_win, draw, away_win)
     }
+
+    /// Settlement probabilities for the home side of an Asian handicap of
+    /// `line` goals. Swap the lambdas and negate the line for the away side.
+    pub fn handicap_probabilities(lambda_home: f64, lambda_away: f64, line: Decimal) -> HandicapProbabilities {
+        let mut probabilities = HandicapProbabilities::default();
+        let mut total = 0.0;
+        for home_goals in 0..=MAX_HANDICAP_GOALS {
+            for away_goals in 0..=MAX_HANDICAP_GOALS {
+                let prob = Self::poisson_probability(lambda_home, home_goals)
+                         * Self::poisson_probability(lambda_away, away_goals);
+                let margin = home_goals as i32 - away_goals as i32;
+                probabilities.add(HandicapResult::settle(line, margin), prob);
+                total += prob;
+            }
+        }
+
+        // Spread the truncated tail proportionally
+        if total > 0.0 {
+            probabilities.win /= total;
+            probabilities.half_win /= total;
+            probabilities.push /= total;
+            probabilities.half_loss /= total;
+            probabilities.loss /= total;
+        }
+        probabilities
+    }
+
+    /// Exact score probabilities for independent Poisson goal counts
+    pub fn score_matrix(lambda_home: f64, lambda_away: f64) -> ScoreMatrix {
+        Self::dixon_coles_score_matrix(lambda_home, lambda_away, 0.0)
+    }
+    
+    pub fn dixon_coles_score_matrix(lambda_home: f64, lambda_away: f64, rho: f64) -> ScoreMatrix {
+        ScoreMatrix::from_fn(SCORE_MATRIX_MAX_GOALS, |home_goals, away_goals| {
+            Self::dixon_coles_probability(lambda_home, lambda_away, rho, home_goals as u32, away_goals as u32)
+        })
+    }
+
+    /// Home win, draw and away win probabilities for the goals of one half,
+    /// with each side's goals in it Poisson at its share of the match rate
+    pub fn half_result_probabilities(lambda_home: f64, lambda_away: f64, half: MatchHalf) -> (f64, f64, f64) {
+        let share = match half {
+            MatchHalf::First => FIRST_HALF_GOAL_SHARE,
+            MatchHalf::Second => 1.0 - FIRST_HALF_GOAL_SHARE,
+        };
+        Self::calculate_match_probabilities(lambda_home * share, lambda_away * share)
+    }
+
+    /// Goal expectations that reproduce a home/away win split with
+    /// `total_goals` expected in the match, for pricing goal-based markets
+    /// from a prediction that only carries outcome probabilities
+    pub fn implied_lambdas(home_win_prob: f64, away_win_prob: f64, total_goals: f64) -> (f64, f64) {
+        let total_goals = total_goals.max(0.2);
+        let target = home_win_prob - away_win_prob;
+        let lambdas = |supremacy: f64| {
+            (((total_goals + supremacy) / 2.0).max(0.05), ((total_goals - supremacy) / 2.0).max(0.05))
+        };
+
+        // Home minus away win probability rises with supremacy, so bisect
+        let (mut low, mut high) = (-total_goals, total_goals);
+        for _ in 0..50 {
+            let mid = (low + high) / 2.0;
+            let (lambda_home, lambda_away) = lambdas(mid);
+            let (home, _, away) = Self::calculate_match_probabilities(lambda_home, lambda_away);
+            if home - away < target {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        lambdas((low + high) / 2.0)
+    }
 }
 
 impl PoissonModel {
-    fn model_name(&self) -> &str {
+    /// Home and away goal rates
+    pub fn lambdas(&self) -> (f64, f64) {
+        (*self.lambda_home.read().unwrap(), *self.lambda_away.read().unwrap())
+    }
+    
+    pub fn set_lambdas(&self, (home, away): (f64, f64)) {
+        *self.lambda_home.write().unwrap() = home.clamp(0.5, 3.0);
+        *self.lambda_away.write().unwrap() = away.clamp(0.5, 3.0);
+    }
+    
+    pub fn model_name(&self) -> &str {
         &self.name
     }
     
-    fn model_version(&self) -> &str {
+    pub fn model_version(&self) -> &str {
         &self.version
     }
     
-    async fn predict(&self, features: &FeatureVector) -> Result<Prediction> {
-        // Adjust lambda based on features
-        let base_lambda_home = *self.lambda_home.read().unwrap();
-        let base_lambda_away = *self.lambda_away.read().unwrap();
-        
-        let home_attack = features.features.get("home_attack").copied().unwrap_or(1.0);
-        let away_attack = features.features.get("away_attack").copied().unwrap_or(1.0);
-        let home_defense = features.features.get("home_defense").copied().unwrap_or(1.0);
-        let away_defense = features.features.get("away_defense").copied().unwrap_or(1.0);
-        let home_advantage = features.features.get("home_advantage").copied().unwrap_or(1.0);
-        
-        let adjusted_lambda_home = base_lambda_home * home_attack * away_defense * home_advantage;
-        let adjusted_lambda_away = base_lambda_away * away_attack * home_defense;
-        
-        let (mut home_win_prob, mut draw_prob, mut away_win_prob) = 
-            self.calculate_match_probabilities(adjusted_lambda_home, adjusted_lambda_away);
+    /// Goals each side is expected to score over a whole match
+    pub fn expected_goals(&self, features: &FeatureVector) -> (f64, f64) {
+        let fitted = features.features.get(FITTED_HOME_GOALS_FEATURE)
+            .zip(features.features.get(FITTED_AWAY_GOALS_FEATURE));
+        let (home_goals, away_goals) = match fitted {
+            // Fitted strengths already carry both teams and home advantage
+            Some((home, away)) => (*home, *away),
+            None => {
+                // Adjust lambda based on features
+                let base_lambda_home = *self.lambda_home.read().unwrap();
+                let base_lambda_away = *self.lambda_away.read().unwrap();
+                
+                let home_attack = features.features.get("home_attack").copied().unwrap_or(1.0);
+                let away_attack = features.features.get("away_attack").copied().unwrap_or(1.0);
+                let home_defense = features.features.get("home_defense").copied().unwrap_or(1.0);
+                let away_defense = features.features.get("away_defense").copied().unwrap_or(1.0);
+                let home_advantage = features.features.get("home_advantage").copied().unwrap_or(1.0);
+                
+                (base_lambda_home * home_attack * away_defense * home_advantage,
+                 base_lambda_away * away_attack * home_defense)
+            }
+        };
         
-        // Ensure probabilities are in valid range
-        home_win_prob = home_win_prob.max(0.01).min(0.98);
-        draw_prob = draw_prob.max(0.01).min(0.98);
-        away_win_prob = away_win_prob.max(0.01).min(0.98);
+        // Missing players take their share of the side's goals with them, up
+        // to half of them
+        let missing = |side: &str| features.features.get(&format!("{}_missing_impact", side))
+            .copied()
+            .unwrap_or(0.0)
+            .clamp(0.0, 0.5);
+        (home_goals * (1.0 - missing("home")), away_goals * (1.0 - missing("away")))
+    }
+    
+    pub async fn predict(&self, features: &FeatureVector) -> Result<Prediction> {
+        let (adjusted_lambda_home, adjusted_lambda_away) = self.expected_goals(features);
+        let rho = self.rho();
         
-        // Normalize
-        let total = home_win_prob + draw_prob + away_win_prob;
-        home_win_prob /= total;
-        draw_prob /= total;
-        away_win_prob /= total;
+        let (home_win_prob, draw_prob, away_win_prob) = 
+            Self::dixon_coles_match_probabilities(adjusted_lambda_home, adjusted_lambda_away, rho);
+        let [home_win_prob, draw_prob, away_win_prob] =
+            bounded_probabilities([home_win_prob, draw_prob, away_win_prob]);
         
         // Confidence based on how different the lambdas are (more different = more confident)
         let lambda_diff = (adjusted_lambda_home - adjusted_lambda_away).abs();
@@ -331,12 +759,13 @@ impl PoissonModel {
         )?
         .with_draw_prob(draw_prob)?
         .with_confidence(confidence)?
-        .with_expected_goals(adjusted_lambda_home, adjusted_lambda_away);
+        .with_expected_goals(adjusted_lambda_home, adjusted_lambda_away)
+        .with_score_matrix(Self::dixon_coles_score_matrix(adjusted_lambda_home, adjusted_lambda_away, rho));
         
         Ok(prediction)
     }
     
-    async fn update_weights(&mut self, feedback: &ModelFeedback) -> Result<()> {
+    pub async fn update_weights(&mut self, feedback: &ModelFeedback) -> Result<()> {
         // Update lambda values based on feedback
         let adjustment = feedback.reward * 0.01; // Small learning rate
         
@@ -359,6 +788,48 @@ impl PoissonModel {
     }
 }
 
+/// Component names used to track the ensemble's members
+const LOGISTIC_COMPONENT: &str = "logistic";
+const POISSON_COMPONENT: &str = "poisson";
+/// No component's weight drops below this, so it can earn its way back
+const MIN_COMPONENT_WEIGHT: f64 = 0.1;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnsembleWeights {
+    pub logistic_weight: f64,
+    pub poisson_weight: f64,
+    /// Rolling Brier score, once the component has enough settled predictions
+    pub logistic_brier: Option<f64>,
+    pub poisson_brier: Option<f64>,
+    pub samples: usize,
+}
+
+/// Each component's prediction behind an ensemble prediction, keyed by the
+/// ensemble prediction id, so settled results can be scored per component
+#[derive(Debug, Default)]
+struct ComponentPredictions {
+    entries: DashMap<uuid::Uuid, (Prediction, Prediction)>,
+    insertion_order: Mutex<VecDeque<uuid::Uuid>>,
+}
+
+impl ComponentPredictions {
+    fn insert(&self, prediction_id: uuid::Uuid, logistic: Prediction, poisson: Prediction) {
+        self.entries.insert(prediction_id, (logistic, poisson));
+        
+        let mut order = self.insertion_order.lock().unwrap();
+        order.push_back(prediction_id);
+        while order.len() > MAX_STORED_FEATURES {
+            if let Some(oldest) = order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+    
+    fn take(&self, prediction_id: &uuid::Uuid) -> Option<(Prediction, Prediction)> {
+        self.entries.remove(prediction_id).map(|(_, predictions)| predictions)
+    }
+}
+
 #[derive(Debug)]
 pub struct EnsembleModel {
     name: String,
@@ -367,6 +838,8 @@ pub struct EnsembleModel {
     poisson_model: PoissonModel,
     logistic_weight: f64,
     poisson_weight: f64,
+    components: ComponentPredictions,
+    tracker: ModelTracker,
 }
 
 impl EnsembleModel {
@@ -378,11 +851,26 @@ impl EnsembleModel {
             poisson_model: PoissonModel::new(),
             logistic_weight: 0.6,
             poisson_weight: 0.4,
+            components: ComponentPredictions::default(),
+            tracker: ModelTracker::new(),
         }
     }
+    
+    /// Score components with this tracker instead of the default window
+    pub fn with_tracker(mut self, tracker: ModelTracker) -> Self {
+        self.tracker = tracker;
+        self
+    }
 }
 
 impl EnsembleModel {
+    /// Swap in an offline-trained logistic component, keeping the Poisson one
+    pub fn with_logistic_model(mut self, logistic_model: LogisticRegressionModel) -> Self {
+        self.version = format!("v1.0+lr-{}", logistic_model.model_version());
+        self.logistic_model = logistic_model;
+        self
+    }
+    
     pub fn model_name(&self) -> &str {
         &self.name
     }
@@ -391,32 +879,30 @@ impl EnsembleModel {
         &self.version
     }
     
+    pub fn poisson_model(&self) -> &PoissonModel {
+        &self.poisson_model
+    }
+    
     pub async fn predict(&self, features: &FeatureVector) -> Result<Prediction> {
         // Get predictions from both models
-        let logistic_pred = self.logistic_model.predict(features).await?;
+        let logistic_pred = self.logistic_model.compute_prediction(features)?;
         let poisson_pred = self.poisson_model.predict(features).await?;
         
         // Weighted average of predictions
         let total_weight = self.logistic_weight + self.poisson_weight;
         
-        let mut home_win_prob = (logistic_pred.home_win_prob * self.logistic_weight + 
-                                poisson_pred.home_win_prob * self.poisson_weight) / total_weight;
+        let home_win_prob = (logistic_pred.home_win_prob * self.logistic_weight + 
+                            poisson_pred.home_win_prob * self.poisson_weight) / total_weight;
         
-        let mut draw_prob = (logistic_pred.draw_prob.unwrap_or(0.0) * self.logistic_weight + 
-                            poisson_pred.draw_prob.unwrap_or(0.0) * self.poisson_weight) / total_weight;
+        let draw_prob = (logistic_pred.draw_prob.unwrap_or(0.0) * self.logistic_weight + 
+                        poisson_pred.draw_prob.unwrap_or(0.0) * self.poisson_weight) / total_weight;
         
-        let mut away_win_prob = (logistic_pred.away_win_prob * self.logistic_weight + 
-                                poisson_pred.away_win_prob * self.poisson_weight) / total_weight;
+        let away_win_prob = (logistic_pred.away_win_prob * self.logistic_weight + 
+                            poisson_pred.away_win_prob * self.poisson_weight) / total_weight;
         
         // Ensure probabilities are valid and sum to 1
-        home_win_prob = home_win_prob.max(0.01).min(0.98);
-        draw_prob = draw_prob.max(0.01).min(0.98);
-        away_win_prob = away_win_prob.max(0.01).min(0.98);
-        
-        let total = home_win_prob + draw_prob + away_win_prob;
-        home_win_prob /= total;
-        draw_prob /= total;
-        away_win_prob /= total;
+        let [home_win_prob, draw_prob, away_win_prob] =
+            bounded_probabilities([home_win_prob, draw_prob, away_win_prob]);
         
         // Ensemble confidence is the average of individual confidences
         let avg_confidence = (logistic_pred.confidence + poisson_pred.confidence) / 2.0;
@@ -431,6 +917,15 @@ impl EnsembleModel {
         )?
         .with_draw_prob(draw_prob)?
         .with_confidence(avg_confidence)?;
+        // Only the Poisson component models goals
+        let prediction = match (poisson_pred.expected_goals_home, poisson_pred.expected_goals_away) {
+            (Some(home), Some(away)) => prediction.with_expected_goals(home, away),
+            _ => prediction,
+        };
+        
+        // Feedback arrives keyed by the ensemble prediction id
+        self.logistic_model.record_features(&prediction, features);
+        self.components.insert(prediction.id, logistic_pred, poisson_pred);
         
         Ok(prediction)
     }
@@ -445,7 +940,183 @@ impl EnsembleModel {
             tracing::warn!("Failed to update poisson model: {}", e);
         }
         
-        // TODO: Implement dynamic weight adjustment based on individual model performance
+        // Shift weight toward whichever component has scored better lately
+        if let (Some(outcome), Some((logistic, poisson))) = (&feedback.outcome, self.components.take(&feedback.prediction_id)) {
+            self.tracker.record(LOGISTIC_COMPONENT, &logistic, outcome);
+            self.tracker.record(POISSON_COMPONENT, &poisson, outcome);
+            if let Some(weights) = self.tracker.weights(&[LOGISTIC_COMPONENT, POISSON_COMPONENT]) {
+                self.logistic_weight = weights[0].clamp(MIN_COMPONENT_WEIGHT, 1.0 - MIN_COMPONENT_WEIGHT);
+                self.poisson_weight = 1.0 - self.logistic_weight;
+            }
+        }
         Ok(())
     }
-}
\ No newline at end of file
+    
+    pub fn weights(&self) -> EnsembleWeights {
+        EnsembleWeights {
+            logistic_weight: self.logistic_weight,
+            poisson_weight: self.poisson_weight,
+            logistic_brier: self.tracker.brier_score(LOGISTIC_COMPONENT),
+            poisson_brier: self.tracker.brier_score(POISSON_COMPONENT),
+            samples: self.tracker.samples(LOGISTIC_COMPONENT),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounded_probabilities_keep_the_floor() {
+        for probabilities in [[0.999, 0.0005, 0.0005], [0.995, 0.004, 0.001], [0.6, 0.395, 0.005], [0.0, 0.0, 2.0]] {
+            let bounded = bounded_probabilities(probabilities);
+            assert!(bounded.iter().all(|&p| (MIN_PROBABILITY..=0.98).contains(&p)), "{:?}", bounded);
+            assert!((bounded.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        }
+        assert_eq!(bounded_probabilities([2.0, 1.0, 1.0]), [0.5, 0.25, 0.25]);
+    }
+
+    fn test_features() -> FeatureVector {
+        let mut features = HashMap::new();
+        features.insert("home_form".to_string(), 0.8);
+        features.insert("away_form".to_string(), 0.3);
+        features.insert("form_difference".to_string(), 0.5);
+        features.insert("home_advantage".to_string(), 1.0);
+        
+        FeatureVector {
+            match_id: "sgd_match".to_string(),
+            features,
+            timestamp: Utc::now(),
+            schema: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sgd_moves_probability_towards_outcome() {
+        let mut model = LogisticRegressionModel::new();
+        let features = test_features();
+        let before = model.predict(&features).await.unwrap();
+        
+        for _ in 0..200 {
+            let prediction = model.predict(&features).await.unwrap();
+            let feedback = ModelFeedback {
+                prediction_id: prediction.id,
+                actual_outcome: true,
+                reward: 1.0,
+                outcome: Some(Outcome1X2::AwayWin),
+            };
+            model.update_weights(&feedback).await.unwrap();
+        }
+        
+        let after = model.predict(&features).await.unwrap();
+        assert!(after.away_win_prob > before.away_win_prob);
+    }
+
+    #[tokio::test]
+    async fn test_feedback_consumes_stored_features() {
+        let mut model = LogisticRegressionModel::new();
+        let prediction = model.predict(&test_features()).await.unwrap();
+        assert_eq!(model.stored_feature_count(), 1);
+        
+        let feedback = ModelFeedback {
+            prediction_id: prediction.id,
+            actual_outcome: false,
+            reward: -1.0,
+            outcome: None,
+        };
+        model.update_weights(&feedback).await.unwrap();
+        assert_eq!(model.stored_feature_count(), 0);
+        
+        // Unknown prediction ids are ignored rather than treated as errors
+        model.update_weights(&feedback).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_ensemble_reweights_on_settled_results() {
+        let mut model = EnsembleModel::new().with_tracker(ModelTracker::new().with_window(20).with_min_samples(3));
+        let features = test_features();
+        
+        for _ in 0..3 {
+            let weights = model.weights();
+            assert_eq!((weights.logistic_weight, weights.poisson_weight), (0.6, 0.4));
+            let prediction = model.predict(&features).await.unwrap();
+            model.update_weights(&ModelFeedback {
+                prediction_id: prediction.id,
+                actual_outcome: true,
+                reward: 1.0,
+                outcome: Some(Outcome1X2::HomeWin),
+            }).await.unwrap();
+        }
+        
+        let weights = model.weights();
+        assert_eq!(weights.samples, 3);
+        assert!(weights.logistic_brier.is_some() && weights.poisson_brier.is_some());
+        assert_ne!(weights.logistic_weight, 0.6);
+        assert!((weights.logistic_weight + weights.poisson_weight - 1.0).abs() < 1e-9);
+        assert!(weights.logistic_weight >= MIN_COMPONENT_WEIGHT && weights.poisson_weight >= MIN_COMPONENT_WEIGHT);
+    }
+
+    #[test]
+    fn test_asian_handicap_probabilities() {
+        use rust_decimal_macros::dec;
+
+        let (lambda_home, lambda_away) = (1.6, 1.1);
+        let (home_win, draw, _) = PoissonModel::calculate_match_probabilities(lambda_home, lambda_away);
+
+        // Level ball refunds the draw; -0.5 is a plain home win
+        let level = PoissonModel::handicap_probabilities(lambda_home, lambda_away, dec!(0));
+        assert!((level.push - draw).abs() < 0.01);
+        let half = PoissonModel::handicap_probabilities(lambda_home, lambda_away, dec!(-0.5));
+        assert!((half.win - home_win).abs() < 0.01);
+        assert_eq!(half.push, 0.0);
+
+        // -0.25 half loses on a draw and sits between the two lines
+        let quarter = PoissonModel::handicap_probabilities(lambda_home, lambda_away, dec!(-0.25));
+        assert!((quarter.half_loss - level.push).abs() < 1e-9);
+        assert!(quarter.win_given_no_push() < level.win_given_no_push());
+        assert!(quarter.win_given_no_push() > half.win_given_no_push());
+
+        // The away side of a line mirrors the home side
+        let away = PoissonModel::handicap_probabilities(lambda_away, lambda_home, dec!(0.25));
+        assert!((away.half_win - quarter.half_loss).abs() < 1e-9);
+        assert!((away.win_given_no_push() + quarter.win_given_no_push() - 1.0).abs() < 1e-9);
+
+        let (home, away) = PoissonModel::implied_lambdas(0.5, 0.25, 2.7);
+        assert!((home + away - 2.7).abs() < 1e-6);
+        let (implied_home, _, implied_away) = PoissonModel::calculate_match_probabilities(home, away);
+        assert!(((implied_home - implied_away) - 0.25).abs() < 1e-3);
+
+        // The exact score grid agrees with the match winner split
+        let matrix = PoissonModel::score_matrix(lambda_home, lambda_away);
+        let grid_home: f64 = matrix.scores().filter(|(h, a, _)| h > a).map(|(_, _, p)| p).sum();
+        assert!((grid_home - home_win).abs() < 0.01);
+        assert_eq!(matrix.most_likely().map(|(h, a, _)| (h, a)), Some((1, 1)));
+    }
+
+    #[tokio::test]
+    async fn test_dixon_coles_moves_probability_into_low_draws() {
+        let (lambda_home, lambda_away) = (1.4, 1.1);
+        let independent = PoissonModel::score_matrix(lambda_home, lambda_away);
+        let adjusted = PoissonModel::dixon_coles_score_matrix(lambda_home, lambda_away, -0.1);
+        let total = |matrix: &ScoreMatrix| matrix.scores().map(|(_, _, p)| p).sum::<f64>();
+        assert!((total(&adjusted) - total(&independent)).abs() < 1e-9);
+        assert!(adjusted.probability(0, 0) > independent.probability(0, 0));
+        assert!(adjusted.probability(1, 1) > independent.probability(1, 1));
+        assert!(adjusted.probability(1, 0) < independent.probability(1, 0));
+        assert_eq!(adjusted.probability(2, 1), independent.probability(2, 1));
+
+        let (_, draw, _) = PoissonModel::calculate_match_probabilities(lambda_home, lambda_away);
+        let (_, adjusted_draw, _) = PoissonModel::dixon_coles_match_probabilities(lambda_home, lambda_away, -0.1);
+        assert!(adjusted_draw > draw);
+
+        // Fitted expected goals take over from the in-play strengths
+        let model = PoissonModel::new();
+        let mut features = test_features();
+        features.features.insert(FITTED_HOME_GOALS_FEATURE.to_string(), 2.4);
+        features.features.insert(FITTED_AWAY_GOALS_FEATURE.to_string(), 0.6);
+        let prediction = model.predict(&features).await.unwrap();
+        assert_eq!(prediction.expected_goals_home, Some(2.4));
+        assert!(prediction.home_win_prob > 0.7);
+    }
+}
@@ -0,0 +1,189 @@
+// Named, versioned feature sets shared by the feature engineer and the models
+
+use quant_models::{FeatureSchemaId, FeatureVector};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// The ordered features a model reads. Bump the version whenever the list
+/// or the meaning of a feature changes, so models trained on the old set
+/// refuse vectors built for the new one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureSchema {
+    pub name: String,
+    pub version: u32,
+    pub features: Vec<String>,
+}
+
+impl FeatureSchema {
+    pub fn new(name: impl Into<String>, version: u32, features: Vec<String>) -> Self {
+        Self { name: name.into(), version, features }
+    }
+
+    /// Match state, team strength, situation, time and league: what the
+    /// live logistic model reads. Version 3 adds each side's league table
+    /// standing; version 2 has in-play xG from shots where version 1
+    /// estimated expected goals from attack and defense strength.
+    pub fn in_play() -> Self {
+        let features = [
+            "minute", "home_score", "away_score", "score_difference", "total_goals",
+            "momentum", "intensity", "game_phase", "time_pressure",
+            "home_elo", "away_elo", "elo_difference",
+            "home_attack", "home_defense", "away_attack", "away_defense",
+            "home_xg", "away_xg",
+            "home_form", "away_form", "form_difference",
+            "home_discipline", "away_discipline",
+            "match_status", "event_influence", "home_advantage",
+            "hour_of_day", "is_evening", "day_of_week", "league_competitiveness",
+            "home_table_position", "away_table_position", "table_position_difference",
+            "home_points_per_game", "away_points_per_game",
+            "home_relegation_pressure", "away_relegation_pressure",
+            "home_title_pressure", "away_title_pressure",
+        ];
+        Self::new("in_play", 3, features.iter().map(|name| name.to_string()).collect())
+    }
+
+    /// The in-play set before league table features
+    pub fn in_play_v2() -> Self {
+        let features = [
+            "minute", "home_score", "away_score", "score_difference", "total_goals",
+            "momentum", "intensity", "game_phase", "time_pressure",
+            "home_elo", "away_elo", "elo_difference",
+            "home_attack", "home_defense", "away_attack", "away_defense",
+            "home_xg", "away_xg",
+            "home_form", "away_form", "form_difference",
+            "home_discipline", "away_discipline",
+            "match_status", "event_influence", "home_advantage",
+            "hour_of_day", "is_evening", "day_of_week", "league_competitiveness",
+        ];
+        Self::new("in_play", 2, features.iter().map(|name| name.to_string()).collect())
+    }
+
+    /// The in-play set before shot-based xG, which older artifacts were trained on
+    pub fn in_play_v1() -> Self {
+        let features = [
+            "minute", "home_score", "away_score", "score_difference", "total_goals",
+            "momentum", "intensity", "game_phase", "time_pressure",
+            "home_elo", "away_elo", "elo_difference",
+            "home_attack", "home_defense", "away_attack", "away_defense",
+            "home_expected_goals", "away_expected_goals",
+            "home_form", "away_form", "form_difference",
+            "home_discipline", "away_discipline",
+            "match_status", "event_influence", "home_advantage",
+            "hour_of_day", "is_evening", "day_of_week", "league_competitiveness",
+        ];
+        Self::new("in_play", 1, features.iter().map(|name| name.to_string()).collect())
+    }
+
+    pub fn id(&self) -> FeatureSchemaId {
+        FeatureSchemaId { name: self.name.clone(), version: self.version }
+    }
+
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    /// Fails if the vector was built for another feature set. Untagged
+    /// vectors are read by feature name.
+    pub fn validate(&self, features: &FeatureVector) -> Result<()> {
+        match &features.schema {
+            Some(schema) if *schema != self.id() => Err(anyhow!(
+                "features for {} were built for {}, not {}", features.match_id, schema, self.id()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The vector's values in schema order, missing features as zero
+    pub fn values(&self, features: &FeatureVector) -> Result<Vec<f64>> {
+        self.validate(features)?;
+        Ok(self.features.iter()
+            .map(|name| features.features.get(name).copied().unwrap_or(0.0))
+            .collect())
+    }
+}
+
+impl Default for FeatureSchema {
+    fn default() -> Self {
+        Self::in_play()
+    }
+}
+
+/// A stage of the feature engineer, each adding a group of related features
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureGroup {
+    MatchState,
+    Team,
+    Situational,
+    Temporal,
+    League,
+}
+
+impl FeatureGroup {
+    pub const ALL: [FeatureGroup; 5] = [
+        Self::MatchState, Self::Team, Self::Situational, Self::Temporal, Self::League,
+    ];
+}
+
+/// Which stages the feature engineer runs, and the feature set the vectors
+/// it produces are tagged with
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeaturePipeline {
+    schema: FeatureSchema,
+    groups: Vec<FeatureGroup>,
+}
+
+impl FeaturePipeline {
+    /// Runs every stage
+    pub fn new(schema: FeatureSchema) -> Self {
+        Self { schema, groups: FeatureGroup::ALL.to_vec() }
+    }
+
+    pub fn with_groups(mut self, groups: Vec<FeatureGroup>) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    pub fn schema(&self) -> &FeatureSchema {
+        &self.schema
+    }
+
+    pub fn runs(&self, group: FeatureGroup) -> bool {
+        self.groups.contains(&group)
+    }
+}
+
+impl Default for FeaturePipeline {
+    fn default() -> Self {
+        Self::new(FeatureSchema::in_play())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_values_follow_schema_and_reject_other_versions() {
+        let schema = FeatureSchema::new("in_play", 2, vec!["momentum".to_string(), "minute".to_string()]);
+        let mut features = FeatureVector {
+            match_id: "m1".to_string(),
+            features: HashMap::from([("minute".to_string(), 30.0), ("extra".to_string(), 1.0)]),
+            timestamp: Utc::now(),
+            schema: None,
+        };
+        assert_eq!(schema.values(&features).unwrap(), [0.0, 30.0]);
+
+        features.schema = Some(schema.id());
+        assert!(schema.values(&features).is_ok());
+        features.schema = Some(FeatureSchema::in_play_v1().id());
+        let error = schema.values(&features).unwrap_err().to_string();
+        assert!(error.contains("in_play@v1"), "{}", error);
+    }
+}
@@ -0,0 +1,335 @@
+// League tables from finished matches, and what is at stake for each team
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StandingsConfig {
+    /// Matches each team plays in a season
+    pub matches_per_season: u32,
+    /// Places at the bottom of the table that go down
+    pub relegation_places: usize,
+}
+
+impl Default for StandingsConfig {
+    fn default() -> Self {
+        Self {
+            matches_per_season: 38,
+            relegation_places: 3,
+        }
+    }
+}
+
+/// Results over some set of matches: all of a team's, or its home or away ones
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableRecord {
+    pub played: u32,
+    pub won: u32,
+    pub drawn: u32,
+    pub lost: u32,
+    pub goals_for: u32,
+    pub goals_against: u32,
+    pub goal_difference: i32,
+    pub points: u32,
+}
+
+impl TableRecord {
+    fn record(&mut self, goals_for: u8, goals_against: u8) {
+        self.played += 1;
+        match goals_for.cmp(&goals_against) {
+            Ordering::Greater => self.won += 1,
+            Ordering::Equal => self.drawn += 1,
+            Ordering::Less => self.lost += 1,
+        }
+        self.goals_for += u32::from(goals_for);
+        self.goals_against += u32::from(goals_against);
+        self.goal_difference = self.goals_for as i32 - self.goals_against as i32;
+        self.points = self.won * 3 + self.drawn;
+    }
+
+    fn combine(&self, other: &TableRecord) -> TableRecord {
+        let won = self.won + other.won;
+        let drawn = self.drawn + other.drawn;
+        let goals_for = self.goals_for + other.goals_for;
+        let goals_against = self.goals_against + other.goals_against;
+        TableRecord {
+            played: self.played + other.played,
+            won,
+            drawn,
+            lost: self.lost + other.lost,
+            goals_for,
+            goals_against,
+            goal_difference: goals_for as i32 - goals_against as i32,
+            points: won * 3 + drawn,
+        }
+    }
+}
+
+/// One team's row of a league table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Standing {
+    /// Counting from 1 at the top
+    pub position: u32,
+    pub team: String,
+    #[serde(flatten)]
+    pub overall: TableRecord,
+    pub home: TableRecord,
+    pub away: TableRecord,
+}
+
+/// A league's table for its current season, ordered by points, then goal
+/// difference, then goals scored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeagueTable {
+    pub league: String,
+    pub season: String,
+    pub standings: Vec<Standing>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl LeagueTable {
+    pub fn standing(&self, team: &str) -> Option<&Standing> {
+        self.standings.iter().find(|standing| standing.team == team)
+    }
+}
+
+/// Where a team stands and how much its remaining matches matter to it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TablePressure {
+    pub position: u32,
+    pub points_per_game: f64,
+    /// 0 when safe or in the early weeks, up to 1 for a side in the drop
+    /// zone late in the season
+    pub relegation: f64,
+    /// 0 when out of the running or in the early weeks, up to 1 for a side
+    /// on or near the leader's points late in the season
+    pub title: f64,
+}
+
+struct LeagueEntry {
+    league: String,
+    season: String,
+    /// Home and away record per team
+    teams: HashMap<String, (TableRecord, TableRecord)>,
+    updated_at: DateTime<Utc>,
+}
+
+impl LeagueEntry {
+    fn new(league: &str, season: &str) -> Self {
+        Self {
+            league: league.to_string(),
+            season: season.to_string(),
+            teams: HashMap::new(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn table(&self) -> LeagueTable {
+        let mut standings: Vec<Standing> = self.teams.iter()
+            .map(|(team, (home, away))| Standing {
+                position: 0,
+                team: team.clone(),
+                overall: home.combine(away),
+                home: *home,
+                away: *away,
+            })
+            .collect();
+        standings.sort_by(|a, b| {
+            b.overall.points.cmp(&a.overall.points)
+                .then(b.overall.goal_difference.cmp(&a.overall.goal_difference))
+                .then(b.overall.goals_for.cmp(&a.overall.goals_for))
+                .then(a.team.cmp(&b.team))
+        });
+        for (index, standing) in standings.iter_mut().enumerate() {
+            standing.position = index as u32 + 1;
+        }
+        LeagueTable {
+            league: self.league.clone(),
+            season: self.season.clone(),
+            standings,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+/// The current season's table of every league that has finished a match
+pub struct LeagueTables {
+    config: StandingsConfig,
+    /// Keyed by lowercase league name
+    leagues: DashMap<String, LeagueEntry>,
+}
+
+impl LeagueTables {
+    pub fn new() -> Self {
+        Self::with_config(StandingsConfig::default())
+    }
+
+    pub fn with_config(config: StandingsConfig) -> Self {
+        Self { config, leagues: DashMap::new() }
+    }
+
+    pub fn config(&self) -> &StandingsConfig {
+        &self.config
+    }
+
+    /// Count a finished match. A result from another season than the
+    /// league's table starts a new table.
+    pub fn record_result(&self, league: &str, season: &str, home: &str, away: &str, home_goals: u8, away_goals: u8) {
+        let mut entry = self.leagues.entry(league.to_lowercase())
+            .or_insert_with(|| LeagueEntry::new(league, season));
+        if entry.season != season {
+            *entry = LeagueEntry::new(league, season);
+        }
+        entry.teams.entry(home.to_string()).or_default().0.record(home_goals, away_goals);
+        entry.teams.entry(away.to_string()).or_default().1.record(away_goals, home_goals);
+        entry.updated_at = Utc::now();
+    }
+
+    /// Any capitalisation of the league's name finds it
+    pub fn table(&self, league: &str) -> Option<LeagueTable> {
+        self.leagues.get(&league.to_lowercase()).map(|entry| entry.table())
+    }
+
+    pub fn tables(&self) -> Vec<LeagueTable> {
+        let mut tables: Vec<LeagueTable> = self.leagues.iter().map(|entry| entry.table()).collect();
+        tables.sort_by(|a, b| a.league.cmp(&b.league));
+        tables
+    }
+
+    /// Replace tables with saved ones, e.g. from a snapshot
+    pub fn load(&self, tables: impl IntoIterator<Item = LeagueTable>) {
+        for table in tables {
+            let mut entry = LeagueEntry::new(&table.league, &table.season);
+            entry.teams = table.standings.into_iter()
+                .map(|standing| (standing.team, (standing.home, standing.away)))
+                .collect();
+            entry.updated_at = table.updated_at;
+            self.leagues.insert(table.league.to_lowercase(), entry);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.leagues.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leagues.is_empty()
+    }
+
+    /// `team`'s place in `league`'s `season` table, or `None` if it has no
+    /// result in it yet. Pressure is how little of the points still to play
+    /// for the team's gap to the drop zone or the top covers, scaled by how
+    /// far through the season it is.
+    pub fn pressure(&self, league: &str, season: &str, team: &str) -> Option<TablePressure> {
+        let table = self.table(league).filter(|table| table.season == season)?;
+        let index = table.standings.iter().position(|standing| standing.team == team)?;
+        let standing = &table.standings[index];
+        let played = standing.overall.played;
+        let points = standing.overall.points as f64;
+
+        let season_length = self.config.matches_per_season.max(1);
+        let progress = (played as f64 / season_length as f64).min(1.0);
+        let available = 3.0 * season_length.saturating_sub(played) as f64;
+        let closeness = |gap: f64| if available > 0.0 { (1.0 - gap / available).clamp(0.0, 1.0) } else { 0.0 };
+
+        let teams = table.standings.len();
+        let relegation = if teams <= self.config.relegation_places || available == 0.0 {
+            0.0
+        } else {
+            let first_relegated = teams - self.config.relegation_places;
+            if index >= first_relegated {
+                progress
+            } else {
+                let gap = points - table.standings[first_relegated].overall.points as f64;
+                closeness(gap) * progress
+            }
+        };
+
+        // The leader is chased by second place
+        let rival = if index == 0 { table.standings.get(1) } else { table.standings.first() };
+        let title = rival.map_or(0.0, |rival| {
+            let gap = (points - rival.overall.points as f64).abs();
+            closeness(gap) * progress
+        });
+
+        Some(TablePressure {
+            position: standing.position,
+            points_per_game: if played > 0 { points / played as f64 } else { 0.0 },
+            relegation,
+            title,
+        })
+    }
+}
+
+impl Default for LeagueTables {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_orders_teams_and_splits_home_and_away() {
+        let tables = LeagueTables::new();
+        tables.record_result("Premier League", "2024-25", "Arsenal", "Chelsea", 2, 0);
+        tables.record_result("Premier League", "2024-25", "Chelsea", "Everton", 1, 1);
+        tables.record_result("Premier League", "2024-25", "Everton", "Arsenal", 3, 1);
+        tables.record_result("Premier League", "2024-25", "Fulham", "Chelsea", 2, 0);
+
+        let table = tables.table("premier league").unwrap();
+        assert_eq!(table.league, "Premier League");
+        let order: Vec<&str> = table.standings.iter().map(|standing| standing.team.as_str()).collect();
+        // Fulham and Arsenal both have 3 points; Fulham on goal difference
+        assert_eq!(order, ["Everton", "Fulham", "Arsenal", "Chelsea"]);
+
+        let arsenal = table.standing("Arsenal").unwrap();
+        assert_eq!(arsenal.position, 3);
+        assert_eq!((arsenal.overall.played, arsenal.overall.points, arsenal.overall.goal_difference), (2, 3, 0));
+        assert_eq!((arsenal.home.won, arsenal.away.lost), (1, 1));
+        let chelsea = table.standing("Chelsea").unwrap();
+        assert_eq!((chelsea.overall.drawn, chelsea.home.points, chelsea.away.points), (1, 1, 0));
+
+        // The next season starts from nothing
+        tables.record_result("Premier League", "2025-26", "Fulham", "Arsenal", 0, 0);
+        let table = tables.table("Premier League").unwrap();
+        assert_eq!((table.season.as_str(), table.standings.len()), ("2025-26", 2));
+        assert!(tables.table("La Liga").is_none());
+    }
+
+    #[test]
+    fn test_pressure_rises_for_sides_near_the_drop_zone_and_the_top_late_on() {
+        let tables = LeagueTables::with_config(StandingsConfig { matches_per_season: 6, relegation_places: 1 });
+        let teams = ["A", "B", "C", "D"];
+        // A wins everything and D loses everything; B and C finish level,
+        // B above on name
+        for _ in 0..2 {
+            for (home, away, home_goals, away_goals) in [
+                ("A", "B", 1, 0), ("C", "D", 1, 0), ("A", "C", 1, 0),
+                ("B", "D", 1, 0), ("A", "D", 3, 0), ("B", "C", 1, 1),
+            ] {
+                tables.record_result("Test League", "2024-25", home, away, home_goals, away_goals);
+            }
+        }
+        let pressure = |team| tables.pressure("Test League", "2024-25", team).unwrap();
+        assert_eq!(teams.map(|team| pressure(team).position), [1, 2, 3, 4]);
+
+        // The season is over: nothing left to play for
+        assert_eq!((pressure("D").relegation, pressure("A").title), (0.0, 0.0));
+
+        let tables = LeagueTables::with_config(StandingsConfig { matches_per_season: 38, relegation_places: 1 });
+        for (home, away, home_goals, away_goals) in [("A", "B", 2, 0), ("C", "D", 1, 0)] {
+            tables.record_result("Test League", "2024-25", home, away, home_goals, away_goals);
+        }
+        let early = tables.pressure("Test League", "2024-25", "D").unwrap();
+        assert!(early.relegation > 0.0 && early.relegation < 0.05, "{:?}", early);
+        assert!(tables.pressure("Test League", "2023-24", "D").is_none());
+        assert!(tables.pressure("Test League", "2024-25", "E").is_none());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::evaluation::outcome_probabilities;
+use quant_models::{Outcome1X2, Prediction};
+use std::collections::{HashMap, VecDeque};
+
+const DEFAULT_WINDOW: usize = 200;
+const DEFAULT_MIN_SAMPLES: usize = 30;
+
+/// Rolling Brier score per model over its most recent settled predictions
+#[derive(Debug, Clone)]
+pub struct ModelTracker {
+    window: usize,
+    min_samples: usize,
+    scores: HashMap<String, VecDeque<f64>>,
+}
+
+impl ModelTracker {
+    pub fn new() -> Self {
+        Self {
+            window: DEFAULT_WINDOW,
+            min_samples: DEFAULT_MIN_SAMPLES,
+            scores: HashMap::new(),
+        }
+    }
+
+    /// Score each model on at most this many recent predictions
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self.min_samples = self.min_samples.min(self.window);
+        self
+    }
+
+    /// Settled predictions a model needs before its score is trusted
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples.clamp(1, self.window);
+        self
+    }
+
+    pub fn record(&mut self, model: &str, prediction: &Prediction, outcome: &Outcome1X2) {
+        let actual = outcome.index();
+        let brier: f64 = outcome_probabilities(prediction).iter().enumerate()
+            .map(|(i, p)| (p - if i == actual { 1.0 } else { 0.0 }).powi(2))
+            .sum();
+
+        let scores = self.scores.entry(model.to_string()).or_default();
+        scores.push_back(brier);
+        while scores.len() > self.window {
+            scores.pop_front();
+        }
+    }
+
+    pub fn samples(&self, model: &str) -> usize {
+        self.scores.get(model).map_or(0, VecDeque::len)
+    }
+
+    /// Mean Brier score over the window, once there are enough samples
+    pub fn brier_score(&self, model: &str) -> Option<f64> {
+        let scores = self.scores.get(model).filter(|scores| scores.len() >= self.min_samples)?;
+        Some(scores.iter().sum::<f64>() / scores.len() as f64)
+    }
+
+    /// Softmax over each model's inverse recent Brier score, so the better
+    /// model gets more weight without the other being switched off. `None`
+    /// until every model has enough samples.
+    pub fn weights(&self, models: &[&str]) -> Option<Vec<f64>> {
+        let inverse: Vec<f64> = models.iter()
+            .map(|model| self.brier_score(model).map(|brier| 1.0 / brier.max(1e-6)))
+            .collect::<Option<_>>()?;
+        // Subtracting the max keeps exp() finite for near-perfect scores
+        let max = inverse.iter().cloned().fold(f64::MIN, f64::max);
+        let exp: Vec<f64> = inverse.iter().map(|x| (x - max).exp()).collect();
+        let total: f64 = exp.iter().sum();
+        Some(exp.iter().map(|x| x / total).collect())
+    }
+}
+
+impl Default for ModelTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn prediction(home: f64, away: f64) -> Prediction {
+        Prediction::new("m1".to_string(), "test".to_string(), "1.0".to_string(), home, away, Utc::now())
+            .unwrap()
+            .with_draw_prob(1.0 - home - away)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_better_model_gets_more_weight() {
+        let mut tracker = ModelTracker::new().with_window(10).with_min_samples(5);
+        for _ in 0..4 {
+            tracker.record("sharp", &prediction(0.7, 0.1), &Outcome1X2::HomeWin);
+            tracker.record("flat", &prediction(0.34, 0.33), &Outcome1X2::HomeWin);
+        }
+        assert!(tracker.weights(&["sharp", "flat"]).is_none());
+
+        tracker.record("sharp", &prediction(0.7, 0.1), &Outcome1X2::HomeWin);
+        tracker.record("flat", &prediction(0.34, 0.33), &Outcome1X2::HomeWin);
+        assert!((tracker.brier_score("sharp").unwrap() - 0.14).abs() < 1e-9);
+
+        let weights = tracker.weights(&["sharp", "flat"]).unwrap();
+        assert!(weights[0] > weights[1]);
+        assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+        // Old scores roll out of the window
+        for _ in 0..10 {
+            tracker.record("sharp", &prediction(0.1, 0.7), &Outcome1X2::HomeWin);
+        }
+        assert_eq!(tracker.samples("sharp"), 10);
+        let weights = tracker.weights(&["sharp", "flat"]).unwrap();
+        assert!(weights[0] < weights[1]);
+    }
+}
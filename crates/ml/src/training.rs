@@ -1,9 +1,769 @@
-// Model training utilities
+// Offline batch training for the logistic regression model, and fitting
+// Dixon-Coles team strengths for the Poisson model from past results
 
-pub struct ModelTrainer;
+use crate::evaluation::DriftBaseline;
+use crate::models::PoissonModel;
+use crate::schema::FeatureSchema;
+use quant_models::{FeatureSchemaId, FeatureVector, Outcome1X2};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-impl ModelTrainer {
+const PROB_EPSILON: f64 = 1e-15;
+
+/// A feature vector paired with the match result it should predict
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabeledExample {
+    pub features: FeatureVector,
+    pub outcome: Outcome1X2,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrainingConfig {
+    pub schema: FeatureSchema,
+    pub learning_rate: f64,
+    pub l2_regularization: f64,
+    pub batch_size: usize,
+    pub max_epochs: usize,
+    /// Epochs without validation improvement before stopping
+    pub patience: usize,
+    pub min_improvement: f64,
+    pub validation_fraction: f64,
+    pub min_examples: usize,
+    pub seed: u64,
+}
+
+impl Default for TrainingConfig {
+    fn default() -> Self {
+        Self {
+            schema: FeatureSchema::in_play(),
+            learning_rate: 0.1,
+            l2_regularization: 1e-3,
+            batch_size: 64,
+            max_epochs: 200,
+            patience: 10,
+            min_improvement: 1e-4,
+            validation_fraction: 0.2,
+            min_examples: 20,
+            seed: 42,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainingMetrics {
+    pub train_examples: usize,
+    pub validation_examples: usize,
+    pub epochs_run: usize,
+    pub best_epoch: usize,
+    pub train_log_loss: f64,
+    pub validation_log_loss: f64,
+    pub validation_accuracy: f64,
+}
+
+/// Trained multinomial logistic regression weights plus the feature scaling
+/// they were fitted on, as written to disk and loaded by the predictor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelArtifact {
+    pub name: String,
+    pub version: String,
+    pub trained_at: DateTime<Utc>,
+    pub feature_names: Vec<String>,
+    /// The feature set `feature_names` is; absent from artifacts trained
+    /// before feature sets were versioned
+    #[serde(default)]
+    pub feature_schema: Option<FeatureSchemaId>,
+    pub feature_means: Vec<f64>,
+    pub feature_scales: Vec<f64>,
+    /// Per outcome (home, draw, away), over standardized features
+    pub weights: [Vec<f64>; 3],
+    pub bias: [f64; 3],
+    pub metrics: TrainingMetrics,
+    /// Feature and prediction distributions over the training split, for
+    /// drift monitoring; absent from artifacts trained before it was kept
+    #[serde(default)]
+    pub drift_baseline: Option<DriftBaseline>,
+}
+
+impl ModelArtifact {
+    /// Write the artifact as `<name>-<version>.json` inside `dir`
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<PathBuf> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("creating {}", dir.display()))?;
+
+        let path = dir.join(format!("{}-{}.json", self.name, self.version));
+        std::fs::write(&path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("writing {}", path.display()))?;
+        Ok(path)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        let artifact: Self = serde_json::from_slice(&bytes)
+            .with_context(|| format!("parsing {}", path.display()))?;
+
+        let n = artifact.feature_names.len();
+        if artifact.feature_means.len() != n
+            || artifact.feature_scales.len() != n
+            || artifact.weights.iter().any(|w| w.len() != n)
+        {
+            return Err(anyhow!("model artifact {} has inconsistent dimensions", path.display()));
+        }
+        Ok(artifact)
+    }
+
+    /// The feature set the weights were fitted on. Unversioned artifacts
+    /// trained on the in-play features are taken to be its first version.
+    pub fn schema(&self) -> FeatureSchema {
+        match &self.feature_schema {
+            Some(id) => FeatureSchema::new(id.name.clone(), id.version, self.feature_names.clone()),
+            None if self.feature_names == FeatureSchema::in_play_v1().features => FeatureSchema::in_play_v1(),
+            None => FeatureSchema::new("unversioned", 0, self.feature_names.clone()),
+        }
+    }
+
+    pub fn predict_probabilities(&self, features: &FeatureVector) -> [f64; 3] {
+        let x = standardize(&raw_features(features, &self.feature_names), &self.feature_means, &self.feature_scales);
+        softmax(&logits(&self.weights, &self.bias, &x))
+    }
+}
+
+/// Batch trainer with a held-out validation split and early stopping
+pub struct Trainer {
+    config: TrainingConfig,
+}
+
+impl Trainer {
+    pub fn new() -> Self {
+        Self::with_config(TrainingConfig::default())
+    }
+
+    pub fn with_config(config: TrainingConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn train(&self, examples: &[LabeledExample]) -> Result<ModelArtifact> {
+        let config = &self.config;
+        if examples.len() < config.min_examples {
+            return Err(anyhow!(
+                "need at least {} labeled examples to train, got {}",
+                config.min_examples, examples.len()
+            ));
+        }
+
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let mut order: Vec<usize> = (0..examples.len()).collect();
+        order.shuffle(&mut rng);
+
+        let validation_len = ((examples.len() as f64 * config.validation_fraction).round() as usize)
+            .clamp(1, examples.len() - 1);
+        let (validation_idx, train_idx) = order.split_at(validation_len);
+
+        let rows = |indices: &[usize]| -> Result<(Vec<Vec<f64>>, Vec<usize>)> {
+            indices.iter()
+                .map(|&i| Ok((config.schema.values(&examples[i].features)?, examples[i].outcome.index())))
+                .collect::<Result<Vec<_>>>()
+                .map(|rows| rows.into_iter().unzip())
+        };
+        let (train_raw, train_y) = rows(train_idx)?;
+        let (validation_raw, validation_y) = rows(validation_idx)?;
+
+        // Scale on the training split only so validation stays unseen
+        let (means, scales) = fit_scaler(&train_raw);
+        let train_x: Vec<_> = train_raw.iter().map(|x| standardize(x, &means, &scales)).collect();
+        let validation_x: Vec<_> = validation_raw.iter().map(|x| standardize(x, &means, &scales)).collect();
+
+        let n_features = config.schema.len();
+        let mut weights: [Vec<f64>; 3] = std::array::from_fn(|_| vec![0.0; n_features]);
+        let mut bias = [0.0; 3];
+        let mut best = (weights.clone(), bias, f64::INFINITY, 0);
+        let mut epochs_without_improvement = 0;
+        let mut epochs_run = 0;
+        let mut batch_order: Vec<usize> = (0..train_x.len()).collect();
+
+        for epoch in 1..=config.max_epochs {
+            epochs_run = epoch;
+            batch_order.shuffle(&mut rng);
+
+            for batch in batch_order.chunks(config.batch_size.max(1)) {
+                let mut weight_grad: [Vec<f64>; 3] = std::array::from_fn(|_| vec![0.0; n_features]);
+                let mut bias_grad = [0.0; 3];
+
+                for &i in batch {
+                    let probs = softmax(&logits(&weights, &bias, &train_x[i]));
+                    for k in 0..3 {
+                        let error = probs[k] - if k == train_y[i] { 1.0 } else { 0.0 };
+                        bias_grad[k] += error;
+                        for (grad, x) in weight_grad[k].iter_mut().zip(&train_x[i]) {
+                            *grad += error * x;
+                        }
+                    }
+                }
+
+                let scale = config.learning_rate / batch.len() as f64;
+                for k in 0..3 {
+                    bias[k] -= scale * bias_grad[k];
+                    for (w, grad) in weights[k].iter_mut().zip(&weight_grad[k]) {
+                        *w -= scale * grad + config.learning_rate * config.l2_regularization * *w;
+                    }
+                }
+            }
+
+            let (validation_loss, _) = evaluate(&weights, &bias, &validation_x, &validation_y);
+            if validation_loss < best.2 - config.min_improvement {
+                best = (weights.clone(), bias, validation_loss, epoch);
+                epochs_without_improvement = 0;
+            } else {
+                epochs_without_improvement += 1;
+                if epochs_without_improvement >= config.patience {
+                    tracing::debug!("🧠 Early stopping at epoch {} (best {})", epoch, best.3);
+                    break;
+                }
+            }
+        }
+
+        let (weights, bias, validation_log_loss, best_epoch) = best;
+        let (train_log_loss, _) = evaluate(&weights, &bias, &train_x, &train_y);
+        let (_, validation_accuracy) = evaluate(&weights, &bias, &validation_x, &validation_y);
+        let trained_at = Utc::now();
+        let train_probabilities: Vec<[f64; 3]> = train_x.iter().map(|x| softmax(&logits(&weights, &bias, x))).collect();
+        let drift_baseline = DriftBaseline::fit(&config.schema.features, &train_raw, &train_probabilities);
+
+        tracing::info!("🧠 Trained on {} examples in {} epochs: validation log loss {:.4}, accuracy {:.1}%",
+                       train_x.len(), epochs_run, validation_log_loss, validation_accuracy * 100.0);
+
+        Ok(ModelArtifact {
+            name: "LogisticRegression".to_string(),
+            version: format!("v{}", trained_at.format("%Y%m%d%H%M%S")),
+            trained_at,
+            feature_names: config.schema.features.clone(),
+            feature_schema: Some(config.schema.id()),
+            feature_means: means,
+            feature_scales: scales,
+            weights,
+            bias,
+            metrics: TrainingMetrics {
+                train_examples: train_x.len(),
+                validation_examples: validation_x.len(),
+                epochs_run,
+                best_epoch,
+                train_log_loss,
+                validation_log_loss,
+                validation_accuracy,
+            },
+            drift_baseline: Some(drift_baseline),
+        })
+    }
+}
+
+impl Default for Trainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A finished match, as the Dixon-Coles fit reads it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchResult {
+    pub home_team: String,
+    pub away_team: String,
+    pub home_goals: u8,
+    pub away_goals: u8,
+    pub played_at: DateTime<Utc>,
+    /// Elo pulls ratings back toward the mean between seasons
+    #[serde(default)]
+    pub season: String,
+    /// The table it counts toward; empty for results stored without one
+    #[serde(default)]
+    pub league: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DixonColesConfig {
+    /// Exponential decay of a result's weight per day of age; 0.0065 halves
+    /// it in about 107 days
+    pub decay_per_day: f64,
+    pub max_iterations: usize,
+    /// Stop once no parameter moves by more than this in an iteration
+    pub tolerance: f64,
+    pub min_matches: usize,
+}
+
+impl Default for DixonColesConfig {
+    fn default() -> Self {
+        Self {
+            decay_per_day: 0.0065,
+            max_iterations: 200,
+            tolerance: 1e-6,
+            min_matches: 20,
+        }
+    }
+}
+
+/// Multipliers on a team's expected goals, 1.0 being average
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TeamStrength {
+    /// Scales the goals it scores
+    pub attack: f64,
+    /// Scales the goals it concedes; below 1 is a better than average defence
+    pub defense: f64,
+}
+
+/// Team attack and defence strengths, home advantage and the low-score
+/// correlation, fitted by weighted maximum likelihood
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DixonColesFit {
+    pub teams: HashMap<String, TeamStrength>,
+    /// Multiplier on the home side's expected goals
+    pub home_advantage: f64,
+    /// Goals expected of an average side away to an average side
+    pub base_goals: f64,
+    pub rho: f64,
+    pub matches: usize,
+    pub iterations: usize,
+    /// Time-weighted log likelihood of the results at the fitted parameters
+    pub log_likelihood: f64,
+    pub fitted_at: DateTime<Utc>,
+}
+
+impl DixonColesFit {
+    /// Expected goals for each side; `None` unless both teams were fitted
+    pub fn expected_goals(&self, home: &str, away: &str) -> Option<(f64, f64)> {
+        let (home, away) = (self.teams.get(home)?, self.teams.get(away)?);
+        Some((
+            self.base_goals * self.home_advantage * home.attack * away.defense,
+            self.base_goals * away.attack * home.defense,
+        ))
+    }
+}
+
+/// Fits Dixon-Coles parameters to past results, recent ones counting most
+pub struct DixonColesFitter {
+    config: DixonColesConfig,
+}
+
+/// Keeps every low-score adjustment positive for realistic goal expectations
+const MAX_RHO: f64 = 0.3;
+
+impl DixonColesFitter {
     pub fn new() -> Self {
-        Self
+        Self::with_config(DixonColesConfig::default())
+    }
+
+    pub fn with_config(config: DixonColesConfig) -> Self {
+        Self { config }
+    }
+
+    /// Weights decay with age relative to the most recent result. Each
+    /// iteration takes a diagonal Newton step on every log strength, then
+    /// on rho, with attack strengths re-centred on the average side.
+    pub fn fit(&self, results: &[MatchResult]) -> Result<DixonColesFit> {
+        let config = &self.config;
+        if results.len() < config.min_matches.max(1) {
+            return Err(anyhow!(
+                "need at least {} results to fit team strengths, got {}",
+                config.min_matches, results.len()
+            ));
+        }
+
+        let latest = results.iter().map(|result| result.played_at).max().unwrap_or_else(Utc::now);
+        let mut teams: HashMap<&str, usize> = HashMap::new();
+        let mut games = Vec::with_capacity(results.len());
+        for result in results {
+            let next = teams.len();
+            let home = *teams.entry(result.home_team.as_str()).or_insert(next);
+            let next = teams.len();
+            let away = *teams.entry(result.away_team.as_str()).or_insert(next);
+            let age_days = (latest - result.played_at).num_seconds().max(0) as f64 / 86_400.0;
+            games.push(Game {
+                home,
+                away,
+                home_goals: result.home_goals,
+                away_goals: result.away_goals,
+                weight: (-config.decay_per_day * age_days).exp(),
+            });
+        }
+
+        // Everything in logs: expected home goals are exp(base + home +
+        // attack[home] + defense[away]), away goals exp(base + attack[away] +
+        // defense[home]), and attack and defence both average zero
+        let total_weight: f64 = games.iter().map(|game| game.weight).sum();
+        let mean_goals = games.iter()
+            .map(|game| game.weight * f64::from(game.home_goals + game.away_goals))
+            .sum::<f64>() / (2.0 * total_weight);
+        let mut params = LogStrengths {
+            base: mean_goals.max(0.1).ln(),
+            home: 0.0,
+            attack: vec![0.0; teams.len()],
+            defense: vec![0.0; teams.len()],
+        };
+        let mut rho = 0.0;
+
+        let mut iterations = 0;
+        for iteration in 1..=config.max_iterations {
+            iterations = iteration;
+            let mut largest_step: f64 = 0.0;
+
+            // Attack, then defence, then home advantage, each a Newton step
+            // on the weighted Poisson likelihood plus the low-score adjustment
+            for block in [Block::Attack, Block::Defense, Block::Home] {
+                let mut gradient = vec![0.0; teams.len().max(1)];
+                let mut curvature = vec![0.0; teams.len().max(1)];
+                for game in &games {
+                    let (lambda_home, lambda_away) = params.expected_goals(game);
+                    let (tau_home, tau_away, _) = tau_gradients(game, lambda_home, lambda_away, rho);
+                    let home_gradient = game.weight * (f64::from(game.home_goals) - lambda_home + tau_home);
+                    let away_gradient = game.weight * (f64::from(game.away_goals) - lambda_away + tau_away);
+                    let (home_curvature, away_curvature) = (game.weight * lambda_home, game.weight * lambda_away);
+                    let (home_goals_param, away_goals_param) = match block {
+                        Block::Attack => (game.home, game.away),
+                        Block::Defense => (game.away, game.home),
+                        Block::Home => {
+                            gradient[0] += home_gradient;
+                            curvature[0] += home_curvature;
+                            continue;
+                        }
+                    };
+                    gradient[home_goals_param] += home_gradient;
+                    curvature[home_goals_param] += home_curvature;
+                    gradient[away_goals_param] += away_gradient;
+                    curvature[away_goals_param] += away_curvature;
+                }
+
+                let values = match block {
+                    Block::Attack => &mut params.attack,
+                    Block::Defense => &mut params.defense,
+                    Block::Home => std::slice::from_mut(&mut params.home),
+                };
+                for (value, (gradient, curvature)) in values.iter_mut().zip(gradient.iter().zip(&curvature)) {
+                    if *curvature > PROB_EPSILON {
+                        let step = gradient / curvature;
+                        *value += step;
+                        largest_step = largest_step.max(step.abs());
+                    }
+                }
+            }
+            params.centre();
+
+            let (gradient, curvature) = games.iter().fold((0.0, 0.0), |(gradient, curvature), game| {
+                let (lambda_home, lambda_away) = params.expected_goals(game);
+                let (_, _, tau_rho) = tau_gradients(game, lambda_home, lambda_away, rho);
+                (gradient + game.weight * tau_rho, curvature + game.weight * tau_rho * tau_rho)
+            });
+            if curvature > PROB_EPSILON {
+                let updated = (rho + gradient / curvature).clamp(-MAX_RHO, MAX_RHO);
+                largest_step = largest_step.max((updated - rho).abs());
+                rho = updated;
+            }
+
+            if largest_step < config.tolerance {
+                break;
+            }
+        }
+
+        let log_likelihood = games.iter()
+            .map(|game| {
+                let (lambda_home, lambda_away) = params.expected_goals(game);
+                let probability = PoissonModel::dixon_coles_probability(
+                    lambda_home, lambda_away, rho, game.home_goals.into(), game.away_goals.into(),
+                );
+                game.weight * probability.max(PROB_EPSILON).ln()
+            })
+            .sum();
+
+        let fit = DixonColesFit {
+            teams: teams.into_iter()
+                .map(|(name, i)| (name.to_string(), TeamStrength {
+                    attack: params.attack[i].exp(),
+                    defense: params.defense[i].exp(),
+                }))
+                .collect(),
+            home_advantage: params.home.exp(),
+            base_goals: params.base.exp(),
+            rho,
+            matches: games.len(),
+            iterations,
+            log_likelihood,
+            fitted_at: Utc::now(),
+        };
+        tracing::info!("🧠 Fitted Dixon-Coles strengths for {} teams from {} results in {} iterations: home advantage {:.2}, rho {:.3}",
+                       fit.teams.len(), fit.matches, fit.iterations, fit.home_advantage, fit.rho);
+        Ok(fit)
+    }
+}
+
+struct Game {
+    home: usize,
+    away: usize,
+    home_goals: u8,
+    away_goals: u8,
+    weight: f64,
+}
+
+#[derive(Clone, Copy)]
+enum Block {
+    Attack,
+    Defense,
+    Home,
+}
+
+struct LogStrengths {
+    base: f64,
+    home: f64,
+    attack: Vec<f64>,
+    defense: Vec<f64>,
+}
+
+impl LogStrengths {
+    fn expected_goals(&self, game: &Game) -> (f64, f64) {
+        (
+            (self.base + self.home + self.attack[game.home] + self.defense[game.away]).exp(),
+            (self.base + self.attack[game.away] + self.defense[game.home]).exp(),
+        )
+    }
+
+    /// Move the average attack and defence into the base rate, which leaves
+    /// every expectation unchanged
+    fn centre(&mut self) {
+        for values in [&mut self.attack, &mut self.defense] {
+            let mean = values.iter().sum::<f64>() / values.len().max(1) as f64;
+            values.iter_mut().for_each(|value| *value -= mean);
+            self.base += mean;
+        }
+    }
+}
+
+/// Derivatives of the log low-score adjustment by log home goals, log away
+/// goals and rho
+fn tau_gradients(game: &Game, lambda_home: f64, lambda_away: f64, rho: f64) -> (f64, f64, f64) {
+    let tau = PoissonModel::dixon_coles_tau(game.home_goals.into(), game.away_goals.into(), lambda_home, lambda_away, rho)
+        .max(PROB_EPSILON);
+    match (game.home_goals, game.away_goals) {
+        (0, 0) => {
+            let both = lambda_home * lambda_away;
+            (-both * rho / tau, -both * rho / tau, -both / tau)
+        }
+        (0, 1) => (lambda_home * rho / tau, 0.0, lambda_home / tau),
+        (1, 0) => (0.0, lambda_away * rho / tau, lambda_away / tau),
+        (1, 1) => (0.0, 0.0, -1.0 / tau),
+        _ => (0.0, 0.0, 0.0),
+    }
+}
+
+impl Default for DixonColesFitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn raw_features(features: &FeatureVector, names: &[String]) -> Vec<f64> {
+    names.iter()
+        .map(|name| features.features.get(name).copied().unwrap_or(0.0))
+        .collect()
+}
+
+fn fit_scaler(rows: &[Vec<f64>]) -> (Vec<f64>, Vec<f64>) {
+    let n = rows.len() as f64;
+    let width = rows.first().map(Vec::len).unwrap_or(0);
+
+    (0..width)
+        .map(|j| {
+            let mean = rows.iter().map(|row| row[j]).sum::<f64>() / n;
+            let variance = rows.iter().map(|row| (row[j] - mean).powi(2)).sum::<f64>() / n;
+            // Constant features carry no signal; leave them unscaled
+            let scale = if variance.sqrt() > 1e-12 { variance.sqrt() } else { 1.0 };
+            (mean, scale)
+        })
+        .unzip()
+}
+
+fn standardize(x: &[f64], means: &[f64], scales: &[f64]) -> Vec<f64> {
+    x.iter().zip(means).zip(scales)
+        .map(|((value, mean), scale)| (value - mean) / scale)
+        .collect()
+}
+
+fn logits(weights: &[Vec<f64>; 3], bias: &[f64; 3], x: &[f64]) -> [f64; 3] {
+    std::array::from_fn(|k| bias[k] + weights[k].iter().zip(x).map(|(w, v)| w * v).sum::<f64>())
+}
+
+fn softmax(logits: &[f64; 3]) -> [f64; 3] {
+    let max = logits.iter().fold(f64::NEG_INFINITY, |a, &b| a.max(b));
+    let exp = logits.map(|l| (l - max).exp());
+    let sum: f64 = exp.iter().sum();
+    exp.map(|e| e / sum)
+}
+
+/// Mean log loss and accuracy over a dataset
+fn evaluate(weights: &[Vec<f64>; 3], bias: &[f64; 3], x: &[Vec<f64>], y: &[usize]) -> (f64, f64) {
+    if x.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    let mut loss = 0.0;
+    let mut correct = 0;
+    for (row, &label) in x.iter().zip(y) {
+        let probs = softmax(&logits(weights, bias, row));
+        loss -= probs[label].clamp(PROB_EPSILON, 1.0).ln();
+        let predicted = (0..3).max_by(|&a, &b| probs[a].total_cmp(&probs[b])).unwrap_or(0);
+        if predicted == label {
+            correct += 1;
+        }
+    }
+    (loss / x.len() as f64, correct as f64 / x.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Home wins when the Elo gap favours the home side, away wins otherwise
+    fn synthetic_examples(count: usize) -> Vec<LabeledExample> {
+        (0..count)
+            .map(|i| {
+                let elo_difference = (i as f64 / count as f64 - 0.5) * 400.0;
+                let outcome = if elo_difference > 40.0 {
+                    Outcome1X2::HomeWin
+                } else if elo_difference < -40.0 {
+                    Outcome1X2::AwayWin
+                } else {
+                    Outcome1X2::Draw
+                };
+                LabeledExample {
+                    features: FeatureVector {
+                        match_id: format!("m{}", i),
+                        features: HashMap::from([("elo_difference".to_string(), elo_difference)]),
+                        timestamp: Utc::now(),
+                        schema: None,
+                    },
+                    outcome,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_training_learns_and_round_trips() {
+        let artifact = Trainer::new().train(&synthetic_examples(300)).unwrap();
+
+        assert!(artifact.metrics.validation_accuracy > 0.8);
+        assert!(artifact.metrics.best_epoch <= artifact.metrics.epochs_run);
+
+        let strong_home = FeatureVector {
+            match_id: "x".to_string(),
+            features: HashMap::from([("elo_difference".to_string(), 180.0)]),
+            timestamp: Utc::now(),
+            schema: None,
+        };
+        assert!(artifact.predict_probabilities(&strong_home)[0] > 0.6);
+
+        let dir = std::env::temp_dir().join(format!("artifacts-{}", uuid::Uuid::new_v4()));
+        let path = artifact.save(&dir).unwrap();
+        let loaded = ModelArtifact::load(&path).unwrap();
+        assert_eq!(loaded.version, artifact.version);
+        assert_eq!(loaded.predict_probabilities(&strong_home), artifact.predict_probabilities(&strong_home));
+        assert_eq!(loaded.schema(), FeatureSchema::in_play());
+        std::fs::remove_dir_all(&dir).ok();
+
+        // Artifacts from before feature sets were versioned
+        let unversioned = ModelArtifact { feature_schema: None, feature_names: FeatureSchema::in_play_v1().features, ..artifact };
+        assert_eq!(unversioned.schema().id(), FeatureSchema::in_play_v1().id());
+
+        // Examples built for another feature set are refused
+        let mut examples = synthetic_examples(300);
+        examples[0].features.schema = Some(FeatureSchemaId { name: "pre_match".to_string(), version: 1 });
+        assert!(Trainer::new().train(&examples).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_installed_artifact_matches_artifact_probabilities() {
+        let artifact = Trainer::new().train(&synthetic_examples(300)).unwrap();
+        let mut model = crate::Model::Ensemble(crate::EnsembleModel::new());
+        model.install_artifact(&artifact);
+        assert_eq!(model.model_version(), format!("v1.0+lr-{}", artifact.version));
+
+        let mut model = crate::Model::Poisson(crate::PoissonModel::new());
+        model.install_artifact(&artifact);
+        assert_eq!(model.model_name(), "LogisticRegression");
+
+        let features = FeatureVector {
+            match_id: "x".to_string(),
+            features: HashMap::from([("elo_difference".to_string(), -120.0)]),
+            timestamp: Utc::now(),
+            schema: None,
+        };
+        let expected = artifact.predict_probabilities(&features);
+        let prediction = model.predict(&features).await.unwrap();
+        assert!((prediction.away_win_prob - expected[2]).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_training_requires_enough_examples() {
+        assert!(Trainer::new().train(&synthetic_examples(5)).is_err());
+    }
+
+    /// Knuth's method, fine for football-sized means
+    fn sample_goals(rng: &mut StdRng, lambda: f64) -> u8 {
+        use rand::Rng;
+        let limit = (-lambda).exp();
+        let (mut goals, mut product) = (0, rng.gen::<f64>());
+        while product > limit {
+            goals += 1;
+            product *= rng.gen::<f64>();
+        }
+        goals
+    }
+
+    #[test]
+    fn test_dixon_coles_fit_recovers_team_strengths() {
+        let attack = [("Strong", 1.6), ("Average", 1.0), ("Weak", 0.6), ("Other", 1.0)];
+        let mut rng = StdRng::seed_from_u64(7);
+        let start = Utc::now() - chrono::Duration::days(400);
+        let mut results = Vec::new();
+        for round in 0..150 {
+            for (home, home_attack) in attack {
+                for (away, away_attack) in attack.iter().filter(|(away, _)| *away != home) {
+                    results.push(MatchResult {
+                        home_team: home.to_string(),
+                        away_team: away.to_string(),
+                        home_goals: sample_goals(&mut rng, 1.3 * 1.25 * home_attack),
+                        away_goals: sample_goals(&mut rng, 1.3 * away_attack),
+                        played_at: start + chrono::Duration::days(round * 2),
+                        season: String::new(),
+                        league: String::new(),
+                    });
+                }
+            }
+        }
+
+        let fit = DixonColesFitter::with_config(DixonColesConfig { decay_per_day: 0.001, ..Default::default() })
+            .fit(&results)
+            .unwrap();
+        assert_eq!(fit.teams.len(), 4);
+        assert!(fit.iterations < 200);
+        assert!((fit.home_advantage - 1.25).abs() < 0.1, "home advantage {}", fit.home_advantage);
+        // Goals were independent, so no low-score correlation to find
+        assert!(fit.rho.abs() < 0.1, "rho {}", fit.rho);
+
+        let strong = fit.teams["Strong"].attack / fit.teams["Average"].attack;
+        let weak = fit.teams["Weak"].attack / fit.teams["Average"].attack;
+        assert!((strong - 1.6).abs() < 0.15, "strong attack {}", strong);
+        assert!((weak - 0.6).abs() < 0.1, "weak attack {}", weak);
+
+        let (home, away) = fit.expected_goals("Strong", "Weak").unwrap();
+        assert!(home > 2.0 && away < 1.0);
+        assert!(fit.expected_goals("Strong", "Unknown").is_none());
+
+        assert!(DixonColesFitter::new().fit(&results[..5]).is_err());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,183 @@
+// Expected goals: the chance each shot is scored, summed per team as a
+// match is played
+
+use dashmap::DashMap;
+use quant_models::{BodyPart, EventType, MatchEvent, ShotSituation, XgInputs};
+use serde::Serialize;
+
+/// Logistic model of a shot being scored, on distance and angle to goal
+/// with adjustments for the body part and how the chance was made
+#[derive(Debug, Clone)]
+pub struct XgModel {
+    pub intercept: f64,
+    /// Per metre from goal
+    pub distance: f64,
+    /// Per radian of goal mouth in view
+    pub angle: f64,
+    pub header: f64,
+    pub other_body_part: f64,
+    pub counter: f64,
+    pub set_piece: f64,
+    /// Penalties are scored at much the same rate whoever takes them
+    pub penalty_xg: f64,
+}
+
+impl Default for XgModel {
+    fn default() -> Self {
+        Self {
+            intercept: -1.0,
+            distance: -0.09,
+            angle: 1.6,
+            header: -0.9,
+            other_body_part: -0.5,
+            counter: 0.3,
+            set_piece: -0.2,
+            penalty_xg: 0.76,
+        }
+    }
+}
+
+impl XgModel {
+    pub fn shot_xg(&self, inputs: &XgInputs) -> f64 {
+        if inputs.situation == ShotSituation::Penalty {
+            return self.penalty_xg;
+        }
+        let body_part = match inputs.body_part {
+            BodyPart::Foot => 0.0,
+            BodyPart::Head => self.header,
+            BodyPart::Other => self.other_body_part,
+        };
+        let situation = match inputs.situation {
+            ShotSituation::Counter => self.counter,
+            ShotSituation::SetPiece => self.set_piece,
+            ShotSituation::OpenPlay | ShotSituation::Penalty => 0.0,
+        };
+        let logit = self.intercept
+            + self.distance * inputs.distance.max(0.0)
+            + self.angle * inputs.angle.clamp(0.0, 180.0).to_radians()
+            + body_part
+            + situation;
+        1.0 / (1.0 + (-logit).exp())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct XgShot {
+    pub minute: u8,
+    pub team: String,
+    pub player: Option<String>,
+    pub xg: f64,
+    /// Running totals including this shot
+    pub home_xg: f64,
+    pub away_xg: f64,
+}
+
+/// A match's shots in the order they were taken, with running totals
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct XgTimeline {
+    pub match_id: String,
+    pub home_xg: f64,
+    pub away_xg: f64,
+    pub shots: Vec<XgShot>,
+}
+
+/// In-play expected goals per match, from the shots seen so far
+pub struct XgTracker {
+    model: XgModel,
+    matches: DashMap<String, XgTimeline>,
+}
+
+impl XgTracker {
+    pub fn new() -> Self {
+        Self { model: XgModel::default(), matches: DashMap::new() }
+    }
+
+    pub fn with_model(mut self, model: XgModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// Add a shot to its match, returning its xG; other events are ignored
+    pub fn record(&self, event: &MatchEvent) -> Option<f64> {
+        let EventType::Shot { team, player, minute, xg_inputs } = &event.event_type else {
+            return None;
+        };
+        let xg = self.model.shot_xg(xg_inputs);
+        let mut timeline = self.matches.entry(event.match_id.clone())
+            .or_insert_with(|| XgTimeline { match_id: event.match_id.clone(), ..XgTimeline::default() });
+        if team == &event.team_home {
+            timeline.home_xg += xg;
+        } else {
+            timeline.away_xg += xg;
+        }
+        let shot = XgShot {
+            minute: *minute,
+            team: team.clone(),
+            player: player.clone(),
+            xg,
+            home_xg: timeline.home_xg,
+            away_xg: timeline.away_xg,
+        };
+        timeline.shots.push(shot);
+        Some(xg)
+    }
+
+    /// Home and away xG so far; nothing yet before the first shot
+    pub fn totals(&self, match_id: &str) -> (f64, f64) {
+        self.matches.get(match_id).map_or((0.0, 0.0), |timeline| (timeline.home_xg, timeline.away_xg))
+    }
+
+    pub fn timeline(&self, match_id: &str) -> Option<XgTimeline> {
+        self.matches.get(match_id).map(|timeline| timeline.clone())
+    }
+
+    pub fn remove(&self, match_id: &str) {
+        self.matches.remove(match_id);
+    }
+
+    pub fn clear(&self) {
+        self.matches.clear();
+    }
+}
+
+impl Default for XgTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shot(team: &str, distance: f64, angle: f64, situation: ShotSituation) -> MatchEvent {
+        let xg_inputs = XgInputs { distance, angle, body_part: BodyPart::Foot, situation };
+        MatchEvent::new(
+            "m1".to_string(),
+            EventType::Shot { team: team.to_string(), player: None, minute: 20, xg_inputs },
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_shots_accumulate_per_team_and_closer_shots_are_worth_more() {
+        let tracker = XgTracker::new();
+        let close = tracker.record(&shot("Arsenal", 6.0, 60.0, ShotSituation::OpenPlay)).unwrap();
+        let long = tracker.record(&shot("Arsenal", 25.0, 15.0, ShotSituation::OpenPlay)).unwrap();
+        let penalty = tracker.record(&shot("Chelsea", 11.0, 36.0, ShotSituation::Penalty)).unwrap();
+        assert!(close > 0.4 && long < 0.1, "{} {}", close, long);
+        assert_eq!(penalty, 0.76);
+
+        let (home, away) = tracker.totals("m1");
+        assert!((home - (close + long)).abs() < 1e-12);
+        assert_eq!(away, penalty);
+
+        let timeline = tracker.timeline("m1").unwrap();
+        assert_eq!(timeline.shots.len(), 3);
+        assert_eq!(timeline.shots[1].home_xg, close + long);
+        assert_eq!(tracker.totals("other"), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,131 @@
+// Append-only trail of what the trader decided and did: every signal, every
+// risk decision and every execution, so any bet (or missing bet) can be
+// explained after the fact
+
+use crate::BettingDecision;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub match_id: String,
+    pub signal_id: Option<Uuid>,
+    pub bet_id: Option<Uuid>,
+    #[serde(flatten)]
+    pub event: AuditEvent,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// A trading signal was generated
+    Signal {
+        signal_strength: f64,
+        risk_score: f64,
+        reasoning: String,
+        bet: Option<Box<BettingDecision>>,
+    },
+    /// The final checks before execution passed, or the one that failed
+    RiskDecision {
+        approved: bool,
+        constraint: Option<RiskConstraint>,
+        detail: Option<String>,
+    },
+    /// An order is about to go to the venue. Only one attempt per
+    /// idempotency key is ever allowed.
+    ExecutionAttempt { idempotency_key: String, venue: String },
+    /// What came of the attempt with the same key
+    Execution {
+        idempotency_key: String,
+        outcome: ExecutionOutcome,
+        detail: Option<String>,
+    },
+}
+
+/// The pre-execution check that stopped a trade
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskConstraint {
+    RiskScore,
+    DailyLoss,
+    FreeBetUnavailable,
+    ConcurrentBets,
+    InsufficientFunds,
+    /// The same bet was already sent to the venue, e.g. before a crash
+    DuplicateExecution,
+    /// The price moved too far from the signal's, or no longer has the edge
+    PriceMoved,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionOutcome {
+    /// Matched at the venue and booked in the portfolio
+    Booked,
+    /// Taken by the venue but nothing was filled
+    Unmatched,
+    /// The venue refused or could not be reached
+    Failed,
+    /// Matched at the venue, but a limit stopped it being booked
+    NotBooked,
+}
+
+impl AuditEntry {
+    pub fn new(match_id: impl Into<String>, event: AuditEvent) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            match_id: match_id.into(),
+            signal_id: None,
+            bet_id: None,
+            event,
+            recorded_at: Utc::now(),
+        }
+    }
+
+    pub fn with_signal(mut self, signal_id: Uuid) -> Self {
+        self.signal_id = Some(signal_id);
+        self
+    }
+
+    pub fn with_bet(mut self, bet_id: Uuid) -> Self {
+        self.bet_id = Some(bet_id);
+        self
+    }
+
+    /// Short name, matching the serialized `kind`
+    pub fn kind(&self) -> &'static str {
+        match self.event {
+            AuditEvent::Signal { .. } => "signal",
+            AuditEvent::RiskDecision { .. } => "risk_decision",
+            AuditEvent::ExecutionAttempt { .. } => "execution_attempt",
+            AuditEvent::Execution { .. } => "execution",
+        }
+    }
+
+    pub fn idempotency_key(&self) -> Option<&str> {
+        match &self.event {
+            AuditEvent::ExecutionAttempt { idempotency_key, .. } | AuditEvent::Execution { idempotency_key, .. } => {
+                Some(idempotency_key)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The key an execution of `bet` is claimed under, from what the decision
+/// was made on rather than the bet's own id: `source` is the prediction (or
+/// else the signal) it came from, then its match, market and selection, and
+/// strategy. Re-sizing the same prediction after a crash gives the same key.
+pub fn execution_key(source: Uuid, bet: &BettingDecision) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(source.as_bytes());
+    for part in [bet.match_id.as_str(), &serde_json::to_string(&bet.bet_type).unwrap_or_default(), &bet.strategy] {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hasher.finalize()[..16].iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -3,8 +3,15 @@ use rust_decimal::Decimal;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
 use uuid::Uuid;
+use crate::currency::Currency;
+use crate::equity::EquityCurve;
 use crate::error::{QuantsError, Result};
+use crate::outcome::Outcome1X2;
+use crate::promotion::free_bet_expected_value;
+use crate::staking::{FractionalKelly, StakeContext, StakingPlan};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct BettingDecision {
@@ -20,6 +27,23 @@ pub struct BettingDecision {
     pub timestamp: DateTime<Utc>,
     pub status: BetStatus,
     pub metadata: serde_json::Value,
+    /// Fraction of net winnings the executing venue keeps (exchanges only)
+    #[serde(default)]
+    pub commission: Decimal,
+    /// Stake-not-returned free bet: costs nothing and a win pays only the profit
+    #[serde(default)]
+    pub free_bet: bool,
+    /// The bookmaker's currency, which the stake and payouts are in
+    #[serde(default)]
+    pub currency: Currency,
+    /// Value of one unit of `currency` in the portfolio's base currency,
+    /// fixed when the bet is priced
+    #[serde(default = "unit_rate")]
+    pub fx_rate: Decimal,
+}
+
+fn unit_rate() -> Decimal {
+    Decimal::ONE
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -31,6 +55,100 @@ pub enum BetType {
     AsianHandicap { line: Decimal, team: String },
     BothTeamsToScore { yes: bool },
     CorrectScore { home_goals: u8, away_goals: u8 },
+    DoubleChance { selection: DoubleChanceSelection },
+    /// Stake is returned if the match is drawn
+    DrawNoBet { home: bool },
+    /// Result of the goals scored in one half alone
+    HalfResult { half: MatchHalf, outcome: Outcome1X2 },
+    /// Selections on several matches that must all win, at the product of
+    /// their odds. Each leg is settled as its own match finishes.
+    Accumulator { legs: Vec<AccumulatorLeg> },
+}
+
+impl BetType {
+    /// The half a bet is settled on, or `None` for the whole match
+    pub fn half(&self) -> Option<MatchHalf> {
+        match self {
+            BetType::HalfResult { half, .. } => Some(*half),
+            _ => None,
+        }
+    }
+
+    /// An accumulator's legs; empty for a single bet
+    pub fn legs(&self) -> &[AccumulatorLeg] {
+        match self {
+            BetType::Accumulator { legs } => legs,
+            _ => &[],
+        }
+    }
+}
+
+/// One selection of an accumulator
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AccumulatorLeg {
+    pub match_id: String,
+    pub bet_type: BetType,
+    pub odds: Decimal,
+    /// `Pending` until the leg's match settles it
+    pub status: BetStatus,
+}
+
+impl AccumulatorLeg {
+    pub fn new(match_id: String, bet_type: BetType, odds: Decimal) -> Self {
+        Self { match_id, bet_type, odds, status: BetStatus::Pending }
+    }
+
+    pub fn is_settled(&self) -> bool {
+        !matches!(self.status, BetStatus::Pending | BetStatus::Placed)
+    }
+
+    /// What the leg multiplies the accumulator's odds by: its odds until it
+    /// loses, 1 once void, and halfway between for a half won or lost leg
+    pub fn settled_odds(&self) -> Decimal {
+        match self.status {
+            BetStatus::Lost => Decimal::ZERO,
+            BetStatus::Void => Decimal::ONE,
+            BetStatus::HalfWon => (self.odds + Decimal::ONE) / dec!(2),
+            BetStatus::HalfLost => dec!(0.5),
+            _ => self.odds,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchHalf {
+    First,
+    Second,
+}
+
+impl MatchHalf {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::First => "first_half",
+            Self::Second => "second_half",
+        }
+    }
+}
+
+/// The two match winner outcomes a double chance bet covers
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DoubleChanceSelection {
+    HomeOrDraw,
+    DrawOrAway,
+    HomeOrAway,
+}
+
+impl DoubleChanceSelection {
+    pub const ALL: [DoubleChanceSelection; 3] = [Self::HomeOrDraw, Self::DrawOrAway, Self::HomeOrAway];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::HomeOrDraw => "1X",
+            Self::DrawOrAway => "X2",
+            Self::HomeOrAway => "12",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -40,9 +158,98 @@ pub enum BetStatus {
     Won,
     Lost,
     Void,
+    /// Quarter-line Asian handicap: half the stake won, half refunded
+    HalfWon,
+    /// Quarter-line Asian handicap: half the stake lost, half refunded
+    HalfLost,
     CashedOut { amount: Decimal },
 }
 
+/// How an Asian handicap bet settles against the final goal margin
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HandicapResult {
+    Win,
+    HalfWin,
+    Push,
+    HalfLoss,
+    Loss,
+}
+
+impl HandicapResult {
+    /// Settle a handicap of `line` goals given to a side that finished
+    /// `goal_margin` goals ahead (negative when behind). Quarter lines such
+    /// as -0.25 split the stake over the two neighbouring lines, -0.5 and 0.
+    /// The line is rounded to the nearest quarter goal.
+    pub fn settle(line: Decimal, goal_margin: i32) -> Self {
+        let quarters = (line * dec!(4)).round().to_i64().unwrap_or(0);
+        let adjusted = i64::from(goal_margin) * 4 + quarters;
+
+        if quarters % 2 == 0 {
+            match adjusted.signum() {
+                1 => Self::Win,
+                0 => Self::Push,
+                _ => Self::Loss,
+            }
+        } else {
+            match (adjusted - 1).signum() + (adjusted + 1).signum() {
+                2 => Self::Win,
+                1 => Self::HalfWin,
+                -1 => Self::HalfLoss,
+                _ => Self::Loss,
+            }
+        }
+    }
+
+    pub fn bet_status(self) -> BetStatus {
+        match self {
+            Self::Win => BetStatus::Won,
+            Self::HalfWin => BetStatus::HalfWon,
+            Self::Push => BetStatus::Void,
+            Self::HalfLoss => BetStatus::HalfLost,
+            Self::Loss => BetStatus::Lost,
+        }
+    }
+}
+
+/// Chance of each Asian handicap settlement for one side of a line
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct HandicapProbabilities {
+    pub win: f64,
+    pub half_win: f64,
+    pub push: f64,
+    pub half_loss: f64,
+    pub loss: f64,
+}
+
+impl HandicapProbabilities {
+    pub fn add(&mut self, result: HandicapResult, probability: f64) {
+        match result {
+            HandicapResult::Win => self.win += probability,
+            HandicapResult::HalfWin => self.half_win += probability,
+            HandicapResult::Push => self.push += probability,
+            HandicapResult::HalfLoss => self.half_loss += probability,
+            HandicapResult::Loss => self.loss += probability,
+        }
+    }
+
+    /// Share of the stake expected back untouched; each half result
+    /// refunds half the stake
+    pub fn push_probability(&self) -> f64 {
+        self.push + (self.half_win + self.half_loss) / 2.0
+    }
+
+    /// Probability the rest of the stake wins, which is what prices and
+    /// Kelly stakes for the line are built on
+    pub fn win_given_no_push(&self) -> f64 {
+        let decided = 1.0 - self.push_probability();
+        if decided > f64::EPSILON {
+            ((self.win + self.half_win / 2.0) / decided).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BettingStrategy {
     pub name: String,
@@ -55,6 +262,39 @@ pub struct BettingStrategy {
     pub min_confidence: f64,
     pub max_correlation: f64,
     pub risk_tolerance: RiskTolerance,
+    #[serde(default)]
+    pub market_filter: MarketFilter,
+    #[serde(default)]
+    pub odds_tolerance: OddsTolerance,
+    /// How bets are sized; fractional Kelly at `kelly_multiplier` when unset
+    #[serde(skip)]
+    pub staking: Option<Arc<dyn StakingPlan>>,
+}
+
+/// Markets a strategy stays out of whatever the edge
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarketFilter {
+    /// Highest bookmaker margin accepted, e.g. 0.08 for 8%
+    #[serde(default)]
+    pub max_overround: Option<f64>,
+    /// Least money that must be available in the match's markets. Matches
+    /// with no liquidity figure are skipped as well.
+    #[serde(default)]
+    pub min_liquidity: Option<Decimal>,
+}
+
+/// How far the price may have moved between a signal and its execution
+/// before the trade is called off
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct OddsTolerance {
+    /// Most ticks on the exchange price ladder the current best price may
+    /// be from the price the bet was signalled at, in either direction
+    #[serde(default)]
+    pub max_ticks: Option<u32>,
+    /// Re-price the edge at the current best price and call the trade off
+    /// if it has fallen below the strategy's `min_edge`
+    #[serde(default)]
+    pub recheck_edge: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,8 +304,14 @@ pub enum RiskTolerance {
     Aggressive,
 }
 
+/// Bankroll and bets, with every total in `currency`. Bets placed in
+/// another currency are converted at the rate they were priced at.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Portfolio {
+    /// The base currency
+    #[serde(default)]
+    pub currency: Currency,
+    /// Net contributions: the starting bankroll plus deposits less withdrawals
     pub total_bankroll: Decimal,
     pub available_bankroll: Decimal,
     pub active_bets: Vec<BettingDecision>,
@@ -76,6 +322,25 @@ pub struct Portfolio {
     pub sharpe_ratio: f64,
     pub max_drawdown: f64,
     pub last_updated: DateTime<Utc>,
+    /// Equity after each settlement
+    #[serde(default)]
+    pub equity_curve: EquityCurve,
+    /// Every deposit and withdrawal, oldest first
+    #[serde(default)]
+    pub adjustments: Vec<BankrollAdjustment>,
+}
+
+/// Money put into or taken out of the bankroll
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BankrollAdjustment {
+    pub id: Uuid,
+    /// Positive for a deposit, negative for a withdrawal
+    pub amount: Decimal,
+    pub reason: Option<String>,
+    /// Net contributions and available bankroll once it was applied
+    pub total_bankroll: Decimal,
+    pub available_bankroll: Decimal,
+    pub timestamp: DateTime<Utc>,
 }
 
 impl BettingDecision {
@@ -87,6 +352,26 @@ impl BettingDecision {
         true_probability: f64,
         strategy: String,
     ) -> Result<Self> {
+        Self::with_commission(match_id, bet_type, stake, odds, true_probability, strategy, Decimal::ZERO)
+    }
+
+    /// Like `new`, but prices EV, edge and Kelly off the odds net of the
+    /// venue's commission on winnings
+    pub fn with_commission(
+        match_id: String,
+        bet_type: BetType,
+        stake: Decimal,
+        odds: Decimal,
+        true_probability: f64,
+        strategy: String,
+        commission: Decimal,
+    ) -> Result<Self> {
+        if commission < Decimal::ZERO || commission >= Decimal::ONE {
+            return Err(QuantsError::InvalidOdds(
+                format!("Commission must be in [0, 1), got {}", commission)
+            ));
+        }
+
         if stake <= Decimal::ZERO {
             return Err(QuantsError::InvalidStake { 
                 amount: stake.to_string() 
@@ -99,19 +384,10 @@ impl BettingDecision {
             ));
         }
         
-        let implied_probability = 1.0 / odds.to_f64().unwrap();
-        let expected_value = (true_probability * odds.to_f64().unwrap()) - 1.0;
-        let edge = true_probability - implied_probability;
-        
-        // Kelly criterion: f = (bp - q) / b
-        // where b = odds - 1, p = true probability, q = 1 - p
-        let b = odds.to_f64().unwrap() - 1.0;
-        let q = 1.0 - true_probability;
-        let kelly_fraction = if b > 0.0 {
-            (b * true_probability - q) / b
-        } else {
-            0.0
-        }.max(0.0); // Don't bet if Kelly is negative
+        let net = net_odds(odds, commission);
+        let expected_value = expected_value(true_probability, net);
+        let edge = edge(true_probability, net);
+        let kelly_fraction = kelly_fraction(true_probability, net);
         
         Ok(Self {
             id: Uuid::new_v4(),
@@ -126,21 +402,129 @@ impl BettingDecision {
             timestamp: Utc::now(),
             status: BetStatus::Pending,
             metadata: serde_json::Value::Null,
+            commission,
+            free_bet: false,
+            currency: Currency::default(),
+            fx_rate: Decimal::ONE,
         })
     }
+
+    /// Use a stake-not-returned free bet. EV is the expected profit per unit
+    /// of free stake; Kelly sizing does not apply since nothing is risked.
+    pub fn free_bet(
+        match_id: String,
+        bet_type: BetType,
+        stake: Decimal,
+        odds: Decimal,
+        true_probability: f64,
+        strategy: String,
+    ) -> Result<Self> {
+        let mut bet = Self::new(match_id, bet_type, stake, odds, true_probability, strategy)?;
+        bet.expected_value = free_bet_expected_value(true_probability, odds, Decimal::ONE);
+        bet.kelly_fraction = 0.0;
+        bet.free_bet = true;
+        Ok(bet)
+    }
+    
+    /// Take the stake and odds a venue actually filled, repricing EV, edge
+    /// and Kelly at the same probability. An accumulator's odds stay its legs'.
+    pub fn fill(&mut self, stake: Decimal, odds: Decimal) {
+        self.stake = stake;
+        let legs_priced = matches!(self.bet_type, BetType::Accumulator { .. });
+        if legs_priced || odds <= Decimal::ONE || odds == self.odds {
+            return;
+        }
+        let true_probability = self.true_probability();
+        let net = net_odds(odds, self.commission);
+        self.odds = odds;
+        self.confidence = edge(true_probability, net);
+        if self.free_bet {
+            self.expected_value = free_bet_expected_value(true_probability, odds, Decimal::ONE);
+        } else {
+            self.expected_value = expected_value(true_probability, net);
+            self.kelly_fraction = kelly_fraction(true_probability, net);
+        }
+    }
     
     pub fn has_positive_ev(&self) -> bool {
         self.expected_value > 0.0
     }
     
+    /// Whether the bet, or one of its accumulator legs, is on `match_id`
+    pub fn covers_match(&self, match_id: &str) -> bool {
+        self.match_id == match_id || self.bet_type.legs().iter().any(|leg| leg.match_id == match_id)
+    }
+
+    /// The odds the bet pays at: for an accumulator, the product of its
+    /// legs' settled odds, so void legs drop out as they settle
+    pub fn settled_odds(&self) -> Decimal {
+        match &self.bet_type {
+            BetType::Accumulator { legs } => legs.iter().map(AccumulatorLeg::settled_odds).product(),
+            _ => self.odds,
+        }
+    }
+
+    /// Stake plus winnings after commission; free bets return winnings only
     pub fn potential_payout(&self) -> Decimal {
-        self.stake * self.odds
+        let winnings = self.stake * (self.settled_odds() - Decimal::ONE) * (Decimal::ONE - self.commission);
+        if self.free_bet {
+            winnings
+        } else {
+            self.stake + winnings
+        }
+    }
+
+    /// Bankroll actually put at risk
+    pub fn cash_stake(&self) -> Decimal {
+        if self.free_bet {
+            Decimal::ZERO
+        } else {
+            self.stake
+        }
     }
     
+    /// `amount` in the bet's currency, in the portfolio's base currency
+    pub fn to_base(&self, amount: Decimal) -> Decimal {
+        amount * self.fx_rate
+    }
+
+    pub fn base_cash_stake(&self) -> Decimal {
+        self.to_base(self.cash_stake())
+    }
+
+    /// The probability the bet was priced with, recovered from its edge
+    pub fn true_probability(&self) -> f64 {
+        self.confidence + 1.0 / net_odds(self.odds, self.commission)
+    }
+
     pub fn potential_profit(&self) -> Decimal {
-        self.potential_payout() - self.stake
+        self.potential_payout() - self.cash_stake()
     }
     
+    /// What the bet returns once settled; `None` while it is still open
+    pub fn settled_payout(&self) -> Option<Decimal> {
+        match &self.status {
+            BetStatus::Pending | BetStatus::Placed => None,
+            BetStatus::Won => Some(self.potential_payout()),
+            BetStatus::Lost => Some(Decimal::ZERO),
+            BetStatus::Void => Some(self.cash_stake()),
+            BetStatus::HalfWon => Some((self.potential_payout() + self.cash_stake()) / dec!(2)),
+            BetStatus::HalfLost => Some(self.cash_stake() / dec!(2)),
+            BetStatus::CashedOut { amount } => Some(*amount),
+        }
+    }
+
+    /// Profit or loss once settled; `None` while the bet is still open
+    pub fn settled_profit_loss(&self) -> Option<Decimal> {
+        self.settled_payout().map(|payout| payout - self.cash_stake())
+    }
+
+    /// Like `settled_profit_loss`, in the base currency at the rate the bet
+    /// was priced at
+    pub fn base_settled_profit_loss(&self) -> Option<Decimal> {
+        self.settled_profit_loss().map(|profit_loss| self.to_base(profit_loss))
+    }
+
     pub fn risk_reward_ratio(&self) -> f64 {
         let potential_profit = self.potential_profit().to_f64().unwrap();
         let stake = self.stake.to_f64().unwrap();
@@ -154,9 +538,90 @@ impl BettingDecision {
     pub fn is_active(&self) -> bool {
         matches!(self.status, BetStatus::Pending | BetStatus::Placed)
     }
+
+    /// Scale EV for markets that refund the stake with probability
+    /// `push_probability`. Build the bet from the probability of winning
+    /// given that it does not push, so edge and Kelly are already correct.
+    pub fn with_push_probability(mut self, push_probability: f64) -> Self {
+        self.expected_value *= 1.0 - push_probability.clamp(0.0, 1.0);
+        self
+    }
+}
+
+/// Selections gathered into one bet: a single with one leg, or an
+/// accumulator at the product of the legs' odds with more
+#[derive(Debug, Clone)]
+pub struct BetSlip {
+    stake: Decimal,
+    strategy: String,
+    legs: Vec<(AccumulatorLeg, f64)>,
+}
+
+impl BetSlip {
+    pub fn new(stake: Decimal) -> Self {
+        Self { stake, strategy: "manual".to_string(), legs: Vec::new() }
+    }
+
+    pub fn with_strategy(mut self, strategy: impl Into<String>) -> Self {
+        self.strategy = strategy.into();
+        self
+    }
+
+    /// Add a selection at `odds`, which wins with `true_probability`
+    pub fn with_leg(mut self, match_id: impl Into<String>, bet_type: BetType, odds: Decimal, true_probability: f64) -> Self {
+        self.legs.push((AccumulatorLeg::new(match_id.into(), bet_type, odds), true_probability));
+        self
+    }
+
+    pub fn legs(&self) -> impl Iterator<Item = &AccumulatorLeg> {
+        self.legs.iter().map(|(leg, _)| leg)
+    }
+
+    /// The bet the slip makes. Legs are priced as independent, so the
+    /// accumulator's probability is the product of theirs; correlated legs
+    /// are for the risk checks to turn away. Its match id joins the legs'.
+    pub fn build(mut self) -> Result<BettingDecision> {
+        for (leg, probability) in &self.legs {
+            if matches!(leg.bet_type, BetType::Accumulator { .. }) {
+                return Err(QuantsError::InvalidAccumulator("legs cannot be accumulators".to_string()));
+            }
+            if !(0.0..=1.0).contains(probability) {
+                return Err(QuantsError::InvalidProbability { prob: *probability });
+            }
+        }
+        if self.legs.is_empty() {
+            return Err(QuantsError::InvalidAccumulator("the bet slip is empty".to_string()));
+        }
+        if self.legs.len() == 1 {
+            let (leg, probability) = self.legs.remove(0);
+            return BettingDecision::new(leg.match_id, leg.bet_type, self.stake, leg.odds, probability, self.strategy);
+        }
+
+        let legs = self.legs;
+        if let Some((leg, _)) = legs.iter().find(|(leg, _)| leg.odds <= Decimal::ONE) {
+            return Err(QuantsError::InvalidOdds(
+                format!("Odds must be greater than 1.0, got {} on {}", leg.odds, leg.match_id)
+            ));
+        }
+        let odds = legs.iter().map(|(leg, _)| leg.odds).product();
+        let probability = legs.iter().map(|(_, probability)| probability).product();
+        let match_id = legs.iter().map(|(leg, _)| leg.match_id.as_str()).collect::<Vec<_>>().join("+");
+        let legs = legs.into_iter().map(|(leg, _)| leg).collect();
+        BettingDecision::new(match_id, BetType::Accumulator { legs }, self.stake, odds, probability, self.strategy)
+    }
 }
 
 impl BettingStrategy {
+    /// A built-in strategy by its short name: `conservative`, `moderate` or `aggressive`
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "conservative" => Some(Self::conservative()),
+            "moderate" => Some(Self::moderate()),
+            "aggressive" => Some(Self::aggressive()),
+            _ => None,
+        }
+    }
+
     pub fn conservative() -> Self {
         Self {
             name: "Conservative Value".to_string(),
@@ -169,6 +634,9 @@ impl BettingStrategy {
             min_confidence: 0.8,
             max_correlation: 0.3,
             risk_tolerance: RiskTolerance::Conservative,
+            market_filter: MarketFilter::default(),
+            odds_tolerance: OddsTolerance::default(),
+            staking: None,
         }
     }
     
@@ -184,6 +652,9 @@ impl BettingStrategy {
             min_confidence: 0.6,
             max_correlation: 0.5,
             risk_tolerance: RiskTolerance::Moderate,
+            market_filter: MarketFilter::default(),
+            odds_tolerance: OddsTolerance::default(),
+            staking: None,
         }
     }
     
@@ -199,17 +670,51 @@ impl BettingStrategy {
             min_confidence: 0.4,
             max_correlation: 0.7,
             risk_tolerance: RiskTolerance::Aggressive,
+            market_filter: MarketFilter::default(),
+            odds_tolerance: OddsTolerance::default(),
+            staking: None,
         }
     }
     
+    pub fn with_market_filter(mut self, market_filter: MarketFilter) -> Self {
+        self.market_filter = market_filter;
+        self
+    }
+
+    pub fn with_odds_tolerance(mut self, odds_tolerance: OddsTolerance) -> Self {
+        self.odds_tolerance = odds_tolerance;
+        self
+    }
+
+    pub fn with_staking_plan(mut self, plan: Arc<dyn StakingPlan>) -> Self {
+        self.staking = Some(plan);
+        self
+    }
+
+    pub fn staking_plan(&self) -> Arc<dyn StakingPlan> {
+        self.staking.clone()
+            .unwrap_or_else(|| Arc::new(FractionalKelly { multiplier: self.kelly_multiplier }))
+    }
+
     pub fn should_bet(
         &self,
         odds: Decimal,
         true_probability: f64,
         confidence: f64,
     ) -> bool {
-        let implied_probability = 1.0 / odds.to_f64().unwrap();
-        let edge = true_probability - implied_probability;
+        self.should_bet_with_commission(odds, true_probability, confidence, Decimal::ZERO)
+    }
+
+    /// Odds limits apply to the quoted price; the edge requirement applies
+    /// to the price net of commission
+    pub fn should_bet_with_commission(
+        &self,
+        odds: Decimal,
+        true_probability: f64,
+        confidence: f64,
+        commission: Decimal,
+    ) -> bool {
+        let edge = edge(true_probability, net_odds(odds, commission));
         
         odds >= self.min_odds
             && odds <= self.max_odds
@@ -222,18 +727,58 @@ impl BettingStrategy {
         bankroll: Decimal,
         kelly_fraction: f64,
     ) -> Decimal {
-        let kelly_stake = bankroll.to_f64().unwrap() * kelly_fraction * self.kelly_multiplier;
-        let max_stake = bankroll.to_f64().unwrap() * self.max_stake_percent;
-        
-        Decimal::from_f64_retain(kelly_stake.min(max_stake))
-            .unwrap_or(Decimal::ZERO)
+        self.size_stake(&StakeContext::new(bankroll, kelly_fraction))
+    }
+
+    /// The staking plan's stake held to `max_stake_percent` of the bankroll
+    pub fn size_stake(&self, context: &StakeContext) -> Decimal {
+        let max_stake = Decimal::from_f64_retain(context.bankroll.to_f64().unwrap_or(0.0) * self.max_stake_percent)
+            .unwrap_or(Decimal::ZERO);
+
+        self.planned_stake(context)
+            .min(max_stake)
             .max(Decimal::ZERO)
     }
+
+    /// What the staking plan asks for before the strategy's cap
+    pub fn planned_stake(&self, context: &StakeContext) -> Decimal {
+        self.staking_plan().stake(context).max(Decimal::ZERO)
+    }
+}
+
+/// Effective decimal odds once the venue takes `commission` of net winnings
+pub fn net_odds(odds: Decimal, commission: Decimal) -> f64 {
+    let odds = odds.to_f64().unwrap_or(1.0);
+    let commission = commission.to_f64().unwrap_or(0.0);
+    1.0 + (odds - 1.0) * (1.0 - commission)
+}
+
+/// Expected return per unit staked at decimal odds `odds`
+pub fn expected_value(true_probability: f64, odds: f64) -> f64 {
+    true_probability * odds - 1.0
+}
+
+/// True probability minus the probability implied by `odds`
+pub fn edge(true_probability: f64, odds: f64) -> f64 {
+    true_probability - 1.0 / odds
+}
+
+/// Kelly criterion: f = (bp - q) / b
+/// where b = odds - 1, p = true probability, q = 1 - p
+pub fn kelly_fraction(true_probability: f64, odds: f64) -> f64 {
+    let b = odds - 1.0;
+    let q = 1.0 - true_probability;
+    if b > 0.0 {
+        (b * true_probability - q) / b
+    } else {
+        0.0
+    }.max(0.0) // Don't bet if Kelly is negative
 }
 
 impl Portfolio {
     pub fn new(initial_bankroll: Decimal) -> Self {
         Self {
+            currency: Currency::default(),
             total_bankroll: initial_bankroll,
             available_bankroll: initial_bankroll,
             active_bets: Vec::new(),
@@ -244,17 +789,20 @@ impl Portfolio {
             sharpe_ratio: 0.0,
             max_drawdown: 0.0,
             last_updated: Utc::now(),
+            equity_curve: EquityCurve::new(initial_bankroll),
+            adjustments: Vec::new(),
         }
     }
     
+    pub fn with_currency(mut self, currency: Currency) -> Self {
+        self.currency = currency;
+        self
+    }
+
     pub fn place_bet(&mut self, mut bet: BettingDecision) -> Result<()> {
-        if bet.stake > self.available_bankroll {
-            return Err(QuantsError::InvalidStake { 
-                amount: format!("Insufficient funds: {} > {}", bet.stake, self.available_bankroll)
-            });
-        }
+        self.check_bet(&bet)?;
         
-        self.available_bankroll -= bet.stake;
+        self.available_bankroll -= bet.base_cash_stake();
         bet.update_status(BetStatus::Placed);
         self.active_bets.push(bet);
         self.last_updated = Utc::now();
@@ -262,7 +810,79 @@ impl Portfolio {
         Ok(())
     }
     
+    /// Refund a bet whose market pushed, e.g. draw no bet on a draw
+    pub fn void_bet(&mut self, bet_id: Uuid) -> Result<()> {
+        self.void_bet_at(bet_id, Utc::now())
+    }
+
+    /// Like `void_bet`, recording equity at `at`, e.g. when replaying a journal
+    pub fn void_bet_at(&mut self, bet_id: Uuid, at: DateTime<Utc>) -> Result<()> {
+        self.close_bet_at(bet_id, BetStatus::Void, at)
+    }
+    
     pub fn settle_bet(&mut self, bet_id: Uuid, won: bool) -> Result<()> {
+        self.settle_bet_at(bet_id, won, Utc::now())
+    }
+
+    /// Like `settle_bet`, recording equity at `at`, e.g. when replaying a journal
+    pub fn settle_bet_at(&mut self, bet_id: Uuid, won: bool, at: DateTime<Utc>) -> Result<()> {
+        let status = if won { BetStatus::Won } else { BetStatus::Lost };
+        self.close_bet_at(bet_id, status, at)
+    }
+
+    /// Settle a quarter-line Asian handicap bet where half the stake was
+    /// refunded and the other half `won` or lost
+    pub fn settle_half_bet(&mut self, bet_id: Uuid, won: bool) -> Result<()> {
+        self.settle_half_bet_at(bet_id, won, Utc::now())
+    }
+
+    pub fn settle_half_bet_at(&mut self, bet_id: Uuid, won: bool, at: DateTime<Utc>) -> Result<()> {
+        let status = if won { BetStatus::HalfWon } else { BetStatus::HalfLost };
+        self.close_bet_at(bet_id, status, at)
+    }
+
+    /// Settle an accumulator's open leg on `match_id` as `status`. The
+    /// accumulator is lost with its first losing leg; once every leg is
+    /// settled it pays at the product of their settled odds, or is void if
+    /// they all were. Returns how it closed, if it did.
+    pub fn settle_leg(&mut self, bet_id: Uuid, match_id: &str, status: BetStatus) -> Result<Option<BetStatus>> {
+        self.settle_leg_at(bet_id, match_id, status, Utc::now())
+    }
+
+    pub fn settle_leg_at(
+        &mut self,
+        bet_id: Uuid,
+        match_id: &str,
+        status: BetStatus,
+        at: DateTime<Utc>,
+    ) -> Result<Option<BetStatus>> {
+        let bet = self.active_bets
+            .iter_mut()
+            .find(|bet| bet.id == bet_id)
+            .ok_or_else(|| QuantsError::MatchNotFound { match_id: bet_id.to_string() })?;
+        let BetType::Accumulator { legs } = &mut bet.bet_type else {
+            return Err(QuantsError::InvalidAccumulator(format!("bet {} is not an accumulator", bet_id)));
+        };
+        let leg = legs.iter_mut()
+            .find(|leg| leg.match_id == match_id && !leg.is_settled())
+            .ok_or_else(|| QuantsError::InvalidAccumulator(format!("bet {} has no open leg on {}", bet_id, match_id)))?;
+        leg.status = status;
+
+        let closed = if legs.iter().any(|leg| leg.status == BetStatus::Lost) {
+            Some(BetStatus::Lost)
+        } else if legs.iter().all(AccumulatorLeg::is_settled) {
+            Some(if legs.iter().all(|leg| leg.status == BetStatus::Void) { BetStatus::Void } else { BetStatus::Won })
+        } else {
+            None
+        };
+        match &closed {
+            Some(status) => self.close_bet_at(bet_id, status.clone(), at)?,
+            None => self.last_updated = at,
+        }
+        Ok(closed)
+    }
+
+    fn close_bet_at(&mut self, bet_id: Uuid, status: BetStatus, at: DateTime<Utc>) -> Result<()> {
         let bet_index = self.active_bets
             .iter()
             .position(|bet| bet.id == bet_id)
@@ -271,31 +891,123 @@ impl Portfolio {
             })?;
         
         let mut bet = self.active_bets.remove(bet_index);
-        
-        let payout = if won {
-            bet.update_status(BetStatus::Won);
-            bet.potential_payout()
-        } else {
-            bet.update_status(BetStatus::Lost);
-            Decimal::ZERO
-        };
+        bet.update_status(status);
+        let payout = bet.to_base(bet.settled_payout().unwrap_or(Decimal::ZERO));
         
         self.available_bankroll += payout;
-        let profit_loss = payout - bet.stake;
+        let profit_loss = payout - bet.base_cash_stake();
         self.total_profit_loss += profit_loss;
         
         self.historical_bets.push(bet);
         self.update_metrics();
+        self.record_equity(at);
         
         Ok(())
     }
+
+    /// Deposit (positive `amount`) or withdraw (negative) money. Only the
+    /// available bankroll can be withdrawn; stakes at risk stay put.
+    pub fn adjust_bankroll(&mut self, amount: Decimal, reason: Option<String>) -> Result<BankrollAdjustment> {
+        self.adjust_bankroll_at(amount, reason, Utc::now())
+    }
+
+    /// Like `adjust_bankroll`, dated `at`, e.g. when replaying a journal
+    pub fn adjust_bankroll_at(&mut self, amount: Decimal, reason: Option<String>, at: DateTime<Utc>) -> Result<BankrollAdjustment> {
+        self.check_adjustment(amount)?;
+        self.total_bankroll += amount;
+        self.available_bankroll += amount;
+        self.equity_curve.add_contribution(amount);
+
+        let adjustment = BankrollAdjustment {
+            id: Uuid::new_v4(),
+            amount,
+            reason,
+            total_bankroll: self.total_bankroll,
+            available_bankroll: self.available_bankroll,
+            timestamp: at,
+        };
+        self.adjustments.push(adjustment.clone());
+        self.last_updated = at;
+        Ok(adjustment)
+    }
+
+    /// Whether `place_bet` would accept `bet`, without placing it
+    pub fn check_bet(&self, bet: &BettingDecision) -> Result<()> {
+        if self.active_bets.iter().any(|active| active.id == bet.id) {
+            return Err(QuantsError::InvalidStake { amount: format!("bet {} is already placed", bet.id) });
+        }
+        if bet.base_cash_stake() > self.available_bankroll {
+            return Err(QuantsError::InvalidStake {
+                amount: format!("Insufficient funds: {} > {}", bet.base_cash_stake(), self.available_bankroll)
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether `adjust_bankroll` would accept `amount`, without applying it
+    pub fn check_adjustment(&self, amount: Decimal) -> Result<()> {
+        if amount.is_zero() {
+            return Err(QuantsError::InvalidAdjustment("amount must not be zero".to_string()));
+        }
+        if -amount > self.available_bankroll {
+            return Err(QuantsError::InvalidAdjustment(
+                format!("cannot withdraw {} with {} available", -amount, self.available_bankroll)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Settled losses `strategy` has had in a row since its last win. Voids,
+    /// pushes and cash-outs neither extend nor end a streak.
+    pub fn losing_streak(&self, strategy: &str) -> usize {
+        self.historical_bets.iter()
+            .rev()
+            .filter(|bet| bet.strategy == strategy)
+            .filter_map(|bet| match bet.status {
+                BetStatus::Lost | BetStatus::HalfLost => Some(true),
+                BetStatus::Won | BetStatus::HalfWon => Some(false),
+                _ => None,
+            })
+            .take_while(|lost| *lost)
+            .count()
+    }
+
+    /// Profit and loss as a fraction of net contributions, so deposits and
+    /// withdrawals don't count as returns. `roi` is the return on stakes.
+    pub fn return_on_capital(&self) -> f64 {
+        if self.total_bankroll > Decimal::ZERO {
+            (self.total_profit_loss / self.total_bankroll).to_f64().unwrap_or(0.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Available bankroll plus stakes still at risk
+    pub fn equity(&self) -> Decimal {
+        self.available_bankroll + self.total_exposure()
+    }
+
+    fn record_equity(&mut self, at: DateTime<Utc>) {
+        self.equity_curve.record(at, self.equity());
+        self.max_drawdown = self.equity_curve.max_drawdown();
+        self.sharpe_ratio = self.equity_curve.sharpe_ratio();
+    }
     
     pub fn total_exposure(&self) -> Decimal {
-        self.active_bets.iter().map(|bet| bet.stake).sum()
+        self.active_bets.iter().map(|bet| bet.base_cash_stake()).sum()
+    }
+
+    /// Open stake in each currency bets were placed in, unconverted
+    pub fn exposure_by_currency(&self) -> BTreeMap<Currency, Decimal> {
+        let mut exposure = BTreeMap::new();
+        for bet in &self.active_bets {
+            *exposure.entry(bet.currency).or_insert(Decimal::ZERO) += bet.cash_stake();
+        }
+        exposure
     }
     
     pub fn potential_total_payout(&self) -> Decimal {
-        self.active_bets.iter().map(|bet| bet.potential_payout()).sum()
+        self.active_bets.iter().map(|bet| bet.to_base(bet.potential_payout())).sum()
     }
     
     fn update_metrics(&mut self) {
@@ -303,17 +1015,26 @@ impl Portfolio {
             return;
         }
         
-        let total_bets = self.historical_bets.len();
-        let won_bets = self.historical_bets
+        // Voided bets were refunded, so they count towards neither
+        let resolved: Vec<_> = self.historical_bets
             .iter()
-            .filter(|bet| matches!(bet.status, BetStatus::Won))
+            .filter(|bet| bet.status != BetStatus::Void)
+            .collect();
+        if resolved.is_empty() {
+            return;
+        }
+        
+        let total_bets = resolved.len();
+        let won_bets = resolved
+            .iter()
+            .filter(|bet| matches!(bet.status, BetStatus::Won | BetStatus::HalfWon))
             .count();
         
         self.win_rate = won_bets as f64 / total_bets as f64;
         
-        let total_staked: Decimal = self.historical_bets
+        let total_staked: Decimal = resolved
             .iter()
-            .map(|bet| bet.stake)
+            .map(|bet| bet.base_cash_stake())
             .sum();
         
         if total_staked > Decimal::ZERO {
@@ -327,6 +1048,7 @@ impl Portfolio {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::staking::FibonacciProgression;
     use rust_decimal_macros::dec;
 
     #[test]
@@ -364,6 +1086,29 @@ mod tests {
         assert!((decision.kelly_fraction - 0.2).abs() < 0.001);
     }
     
+    #[test]
+    fn test_commission_reduces_ev_and_kelly() {
+        // 5% commission turns 2.0 into an effective 1.95
+        let decision = BettingDecision::with_commission(
+            "match_123".to_string(),
+            BetType::HomeWin,
+            dec!(100),
+            dec!(2.0),
+            0.6,
+            "TestStrategy".to_string(),
+            dec!(0.05),
+        ).unwrap();
+
+        assert!((decision.expected_value - 0.17).abs() < 1e-9);
+        assert!((decision.kelly_fraction - (0.95 * 0.6 - 0.4) / 0.95).abs() < 1e-9);
+        assert_eq!(decision.potential_payout(), dec!(195));
+
+        // A 5.5% edge at the quoted price falls below the 5% minimum after commission
+        let strategy = BettingStrategy::conservative();
+        assert!(strategy.should_bet(dec!(2.0), 0.555, 0.9));
+        assert!(!strategy.should_bet_with_commission(dec!(2.0), 0.555, 0.9, dec!(0.05)));
+    }
+    
     #[test]
     fn test_betting_strategy() {
         let strategy = BettingStrategy::conservative();
@@ -378,6 +1123,28 @@ mod tests {
         assert!(!strategy.should_bet(dec!(2.0), 0.5, 0.9));
     }
     
+    #[test]
+    fn test_staking_plan_is_capped_by_the_strategy() {
+        // Quarter Kelly of a 20% edge, under the 2% cap
+        let strategy = BettingStrategy::conservative();
+        assert_eq!(strategy.calculate_stake(dec!(1000), 0.06), dec!(15));
+
+        let strategy = strategy.with_staking_plan(Arc::new(FibonacciProgression { unit: dec!(5), max_steps: 10 }));
+        let context = StakeContext::new(dec!(1000), 0.06);
+        assert_eq!(strategy.size_stake(&context.with_losing_streak(2)), dec!(10));
+        assert_eq!(strategy.planned_stake(&context.with_losing_streak(5)), dec!(40));
+        assert_eq!(strategy.size_stake(&context.with_losing_streak(5)), dec!(20));
+
+        let mut portfolio = Portfolio::new(dec!(1000));
+        for (strategy, won) in [("A", true), ("A", false), ("B", true), ("A", false)] {
+            let bet = BettingDecision::new("m1".to_string(), BetType::HomeWin, dec!(10), dec!(2.0), 0.5, strategy.to_string()).unwrap();
+            let bet_id = bet.id;
+            portfolio.place_bet(bet).unwrap();
+            portfolio.settle_bet(bet_id, won).unwrap();
+        }
+        assert_eq!((portfolio.losing_streak("A"), portfolio.losing_streak("B")), (2, 0));
+    }
+
     #[test]
     fn test_portfolio_management() {
         let mut portfolio = Portfolio::new(dec!(1000));
@@ -404,5 +1171,139 @@ mod tests {
         assert_eq!(portfolio.active_bets.len(), 0);
         assert_eq!(portfolio.historical_bets.len(), 1);
         assert_eq!(portfolio.total_profit_loss, dec!(100));
+
+        // A winning free bet pays only the profit and never touches the bankroll
+        let free_bet = BettingDecision::free_bet(
+            "match_456".to_string(),
+            BetType::Draw,
+            dec!(10),
+            dec!(3.0),
+            0.3,
+            "promotion".to_string(),
+        ).unwrap();
+        let free_bet_id = free_bet.id;
+        assert!((free_bet.expected_value - 0.6).abs() < 1e-9);
+
+        portfolio.place_bet(free_bet).unwrap();
+        assert_eq!(portfolio.available_bankroll, dec!(1100));
+        assert_eq!(portfolio.total_exposure(), Decimal::ZERO);
+        portfolio.settle_bet(free_bet_id, true).unwrap();
+        assert_eq!(portfolio.available_bankroll, dec!(1120));
+        assert_eq!(portfolio.total_profit_loss, dec!(120));
+
+        // A pushed draw no bet refunds the stake
+        let dnb = BettingDecision::new(
+            "match_789".to_string(),
+            BetType::DrawNoBet { home: true },
+            dec!(50),
+            dec!(1.6),
+            0.7,
+            "TestStrategy".to_string(),
+        ).unwrap();
+        let dnb_id = dnb.id;
+        portfolio.place_bet(dnb).unwrap();
+        portfolio.void_bet(dnb_id).unwrap();
+        assert_eq!(portfolio.available_bankroll, dec!(1120));
+        assert_eq!(portfolio.total_profit_loss, dec!(120));
+    }
+
+    #[test]
+    fn test_bankroll_adjustments_are_not_returns() {
+        let mut portfolio = Portfolio::new(dec!(1000));
+        let bet = BettingDecision::new(
+            "match_123".to_string(),
+            BetType::HomeWin,
+            dec!(100),
+            dec!(2.0),
+            0.6,
+            "TestStrategy".to_string(),
+        ).unwrap();
+        let bet_id = bet.id;
+        portfolio.place_bet(bet).unwrap();
+
+        let deposit = portfolio.adjust_bankroll(dec!(1000), Some("top-up".to_string())).unwrap();
+        assert_eq!((deposit.total_bankroll, deposit.available_bankroll), (dec!(2000), dec!(1900)));
+        // The stake at risk can't be withdrawn
+        assert!(portfolio.adjust_bankroll(dec!(-1901), None).is_err());
+        assert!(portfolio.adjust_bankroll(Decimal::ZERO, None).is_err());
+
+        portfolio.settle_bet(bet_id, true).unwrap();
+        portfolio.adjust_bankroll(dec!(-500), None).unwrap();
+        assert_eq!(portfolio.available_bankroll, dec!(1600));
+        assert_eq!(portfolio.total_bankroll, dec!(1500));
+        assert_eq!(portfolio.adjustments.len(), 2);
+
+        // 100 profit on 1500 put in, and the deposit isn't a gain on the curve
+        assert!((portfolio.return_on_capital() - 100.0 / 1500.0).abs() < 1e-9);
+        assert!((portfolio.roi - 1.0).abs() < 1e-9);
+        let point = &portfolio.equity_curve.points()[0];
+        assert_eq!((point.equity, point.contribution), (dec!(2100), dec!(1000)));
+        assert_eq!(point.drawdown, 0.0);
+    }
+
+    #[test]
+    fn test_asian_handicap_settlement() {
+        // Full, half and quarter lines for a side that won by one goal
+        assert_eq!(HandicapResult::settle(dec!(-1), 1), HandicapResult::Push);
+        assert_eq!(HandicapResult::settle(dec!(-1.5), 1), HandicapResult::Loss);
+        assert_eq!(HandicapResult::settle(dec!(-0.75), 1), HandicapResult::HalfWin);
+        assert_eq!(HandicapResult::settle(dec!(-1.25), 1), HandicapResult::HalfLoss);
+        assert_eq!(HandicapResult::settle(dec!(0.25), 0), HandicapResult::HalfWin);
+        assert_eq!(HandicapResult::settle(dec!(-0.25), 0), HandicapResult::HalfLoss);
+        assert_eq!(HandicapResult::settle(dec!(0.5), -1), HandicapResult::Loss);
+
+        // Half won at 1.90: 50 refunded plus 50 at 1.90
+        let mut portfolio = Portfolio::new(dec!(1000));
+        let bet = BettingDecision::new(
+            "match_1".to_string(),
+            BetType::AsianHandicap { line: dec!(-0.75), team: "Arsenal".to_string() },
+            dec!(100),
+            dec!(1.90),
+            0.55,
+            "TestStrategy".to_string(),
+        ).unwrap();
+        let bet_id = bet.id;
+        portfolio.place_bet(bet).unwrap();
+        portfolio.settle_half_bet(bet_id, true).unwrap();
+        assert_eq!(portfolio.available_bankroll, dec!(1045));
+        assert_eq!(portfolio.historical_bets[0].settled_profit_loss(), Some(dec!(45)));
+
+        let probabilities = HandicapProbabilities { win: 0.4, half_win: 0.2, push: 0.0, half_loss: 0.0, loss: 0.4 };
+        assert!((probabilities.push_probability() - 0.1).abs() < 1e-9);
+        assert!((probabilities.win_given_no_push() - 0.5 / 0.9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_accumulator_settles_leg_by_leg() {
+        assert!(BetSlip::new(dec!(10)).build().is_err());
+        let single = BetSlip::new(dec!(10)).with_leg("m1", BetType::HomeWin, dec!(2.0), 0.55).build().unwrap();
+        assert_eq!((single.bet_type, single.match_id.as_str()), (BetType::HomeWin, "m1"));
+
+        let slip = BetSlip::new(dec!(10))
+            .with_leg("m1", BetType::HomeWin, dec!(2.0), 0.55)
+            .with_leg("m2", BetType::Draw, dec!(3.0), 0.3)
+            .with_leg("m3", BetType::AwayWin, dec!(1.5), 0.7);
+        let accumulator = slip.clone().build().unwrap();
+        assert_eq!((accumulator.odds, accumulator.match_id.as_str()), (dec!(9.0), "m1+m2+m3"));
+        assert!((accumulator.expected_value - (0.55 * 0.3 * 0.7 * 9.0 - 1.0)).abs() < 1e-9);
+        assert!(accumulator.covers_match("m2") && !accumulator.covers_match("m4"));
+
+        // A void leg drops out of the odds; the rest pay 2.0 x 1.5
+        let mut portfolio = Portfolio::new(dec!(1000));
+        let bet_id = accumulator.id;
+        portfolio.place_bet(accumulator).unwrap();
+        assert_eq!(portfolio.settle_leg(bet_id, "m2", BetStatus::Void).unwrap(), None);
+        assert_eq!(portfolio.settle_leg(bet_id, "m1", BetStatus::Won).unwrap(), None);
+        assert_eq!(portfolio.total_exposure(), dec!(10));
+        assert!(portfolio.settle_leg(bet_id, "m1", BetStatus::Won).is_err());
+        assert_eq!(portfolio.settle_leg(bet_id, "m3", BetStatus::Won).unwrap(), Some(BetStatus::Won));
+        assert_eq!(portfolio.available_bankroll, dec!(1020));
+
+        // The first losing leg loses the lot
+        let accumulator = slip.build().unwrap();
+        let bet_id = accumulator.id;
+        portfolio.place_bet(accumulator).unwrap();
+        assert_eq!(portfolio.settle_leg(bet_id, "m3", BetStatus::Lost).unwrap(), Some(BetStatus::Lost));
+        assert_eq!((portfolio.available_bankroll, portfolio.active_bets.len()), (dec!(1010), 0));
     }
 }
\ No newline at end of file
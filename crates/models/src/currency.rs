@@ -0,0 +1,128 @@
+// Currencies bookmakers quote in, and converting between them
+
+use crate::error::{QuantsError, Result};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// ISO 4217 currency; read case-insensitively, written as the upper-case code
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Currency {
+    #[default]
+    Usd,
+    Eur,
+    Gbp,
+}
+
+impl Currency {
+    pub const ALL: [Currency; 3] = [Self::Usd, Self::Eur, Self::Gbp];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Usd => "USD",
+            Self::Eur => "EUR",
+            Self::Gbp => "GBP",
+        }
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Currency {
+    type Err = QuantsError;
+
+    fn from_str(code: &str) -> Result<Self> {
+        Self::ALL.into_iter()
+            .find(|currency| currency.code().eq_ignore_ascii_case(code.trim()))
+            .ok_or_else(|| QuantsError::Config(format!("unknown currency '{}'", code)))
+    }
+}
+
+impl TryFrom<String> for Currency {
+    type Error = QuantsError;
+
+    fn try_from(code: String) -> Result<Self> {
+        code.parse()
+    }
+}
+
+impl From<Currency> for String {
+    fn from(currency: Currency) -> Self {
+        currency.code().to_string()
+    }
+}
+
+/// Exchange rates between currencies
+pub trait RateProvider: Send + Sync {
+    /// Units of `to` that one unit of `from` buys; `None` when unknown
+    fn rate(&self, from: Currency, to: Currency) -> Option<Decimal>;
+
+    fn convert(&self, amount: Decimal, from: Currency, to: Currency) -> Result<Decimal> {
+        self.rate(from, to)
+            .map(|rate| amount * rate)
+            .ok_or(QuantsError::MissingRate { from, to })
+    }
+}
+
+/// Fixed rates, each the value of one unit of a currency in `base`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FixedRates {
+    pub base: Currency,
+    pub rates: HashMap<Currency, Decimal>,
+}
+
+impl FixedRates {
+    pub fn new(base: Currency) -> Self {
+        Self { base, rates: HashMap::new() }
+    }
+
+    pub fn with_rate(mut self, currency: Currency, value_in_base: Decimal) -> Self {
+        self.rates.insert(currency, value_in_base);
+        self
+    }
+
+    fn value_in_base(&self, currency: Currency) -> Option<Decimal> {
+        if currency == self.base {
+            return Some(Decimal::ONE);
+        }
+        self.rates.get(&currency).copied().filter(|rate| *rate > Decimal::ZERO)
+    }
+}
+
+impl RateProvider for FixedRates {
+    fn rate(&self, from: Currency, to: Currency) -> Option<Decimal> {
+        if from == to {
+            return Some(Decimal::ONE);
+        }
+        Some(self.value_in_base(from)? / self.value_in_base(to)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_rates_cross_through_the_base_currency() {
+        let rates = FixedRates::new(Currency::Usd)
+            .with_rate(Currency::Eur, dec!(1.08))
+            .with_rate(Currency::Gbp, dec!(1.35));
+
+        assert_eq!(rates.convert(dec!(100), Currency::Eur, Currency::Usd).unwrap(), dec!(108));
+        assert_eq!(rates.convert(dec!(135), Currency::Usd, Currency::Gbp).unwrap().round_dp(2), dec!(100));
+        assert_eq!(rates.rate(Currency::Gbp, Currency::Eur).unwrap().round_dp(4), dec!(1.25));
+        assert!(FixedRates::new(Currency::Usd).convert(dec!(1), Currency::Eur, Currency::Usd).is_err());
+
+        assert_eq!("gbp".parse::<Currency>().unwrap(), Currency::Gbp);
+        assert_eq!(serde_json::to_string(&Currency::Eur).unwrap(), "\"EUR\"");
+        assert!("JPY".parse::<Currency>().is_err());
+    }
+}
@@ -0,0 +1,47 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::betting::BettingDecision;
+
+/// A change to the portfolio, its risk or the live model, published once on
+/// the event bus for every consumer to read instead of each keeping its own
+/// view
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DomainEvent {
+    BetPlaced { bet: BettingDecision },
+    /// `bet` carries its settled status
+    BetSettled { bet: BettingDecision, profit_loss: Decimal },
+    RiskLimitBreached {
+        limit: RiskLimit,
+        /// The match whose trade hit the limit, if one did
+        match_id: Option<String>,
+    },
+    /// Equity fell through `threshold` below its peak
+    DrawdownThresholdCrossed { threshold: f64, drawdown: f64 },
+    /// A trained model was installed for live predictions
+    ModelPromoted { name: String, version: String, validation_log_loss: f64 },
+}
+
+/// Which risk limit was breached, and by how much
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RiskLimit {
+    /// Trading halts until the next daily reset
+    DailyLoss { limit: Decimal, realized_loss: Decimal },
+    /// A matched order was not booked
+    ConcurrentBets { limit: usize },
+    Concentration { dimension: String, hhi: f64, threshold: f64, largest_group: String },
+}
+
+impl DomainEvent {
+    /// Short name, matching the serialized `type`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::BetPlaced { .. } => "bet_placed",
+            Self::BetSettled { .. } => "bet_settled",
+            Self::RiskLimitBreached { .. } => "risk_limit_breached",
+            Self::DrawdownThresholdCrossed { .. } => "drawdown_threshold_crossed",
+            Self::ModelPromoted { .. } => "model_promoted",
+        }
+    }
+}
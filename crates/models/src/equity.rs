@@ -0,0 +1,150 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+/// Portfolio value after a settlement
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EquityPoint {
+    pub timestamp: DateTime<Utc>,
+    /// Available bankroll plus stakes still at risk
+    pub equity: Decimal,
+    /// Fall from the highest equity seen so far, as a fraction of that peak
+    pub drawdown: f64,
+    /// Money deposited (or, if negative, withdrawn) since the previous point,
+    /// which is not a return
+    #[serde(default, skip_serializing_if = "Decimal::is_zero")]
+    pub contribution: Decimal,
+}
+
+/// Equity after each settlement, with the risk-adjusted return statistics
+/// derived from it. Returns are per settlement, not annualised.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EquityCurve {
+    points: Vec<EquityPoint>,
+    peak: Decimal,
+    max_drawdown: f64,
+    /// Deposits and withdrawals not yet folded into a point
+    #[serde(default)]
+    pending_contribution: Decimal,
+}
+
+impl EquityCurve {
+    pub fn new(initial_equity: Decimal) -> Self {
+        Self {
+            points: Vec::new(),
+            peak: initial_equity,
+            max_drawdown: 0.0,
+            pending_contribution: Decimal::ZERO,
+        }
+    }
+
+    /// Account for money put in or taken out, so it moves the peak rather
+    /// than showing up as a gain or a drawdown
+    pub fn add_contribution(&mut self, amount: Decimal) {
+        self.peak = (self.peak + amount).max(Decimal::ZERO);
+        self.pending_contribution += amount;
+    }
+
+    pub fn record(&mut self, timestamp: DateTime<Utc>, equity: Decimal) {
+        self.peak = self.peak.max(equity);
+        let drawdown = if self.peak > Decimal::ZERO {
+            ((self.peak - equity) / self.peak).to_f64().unwrap_or(0.0)
+        } else {
+            0.0
+        };
+        self.max_drawdown = self.max_drawdown.max(drawdown);
+        let contribution = std::mem::take(&mut self.pending_contribution);
+        self.points.push(EquityPoint { timestamp, equity, drawdown, contribution });
+    }
+
+    pub fn points(&self) -> &[EquityPoint] {
+        &self.points
+    }
+
+    /// Points recorded within `[from, to)`
+    pub fn between(&self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> &[EquityPoint] {
+        let start = from.map_or(0, |from| self.points.partition_point(|p| p.timestamp < from));
+        let end = to.map_or(self.points.len(), |to| self.points.partition_point(|p| p.timestamp < to));
+        &self.points[start..end.max(start)]
+    }
+
+    pub fn max_drawdown(&self) -> f64 {
+        self.max_drawdown
+    }
+
+    pub fn current_drawdown(&self) -> f64 {
+        self.points.last().map_or(0.0, |point| point.drawdown)
+    }
+
+    /// Largest drawdown among the last `window` points, each measured from
+    /// the all-time peak
+    pub fn rolling_max_drawdown(&self, window: usize) -> f64 {
+        self.points.iter()
+            .rev()
+            .take(window)
+            .map(|point| point.drawdown)
+            .fold(0.0, f64::max)
+    }
+
+    pub fn sharpe_ratio(&self) -> f64 {
+        let returns = self.returns();
+        if returns.len() < 2 {
+            return 0.0;
+        }
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let std_dev = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+        if std_dev > f64::EPSILON { mean / std_dev } else { 0.0 }
+    }
+
+    /// Like Sharpe, but only losing settlements count as risk
+    pub fn sortino_ratio(&self) -> f64 {
+        let returns = self.returns();
+        if returns.len() < 2 {
+            return 0.0;
+        }
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let downside = (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n).sqrt();
+        if downside > f64::EPSILON { mean / downside } else { 0.0 }
+    }
+
+    /// Fractional change in equity from one settlement to the next, net of
+    /// deposits and withdrawals in between
+    fn returns(&self) -> Vec<f64> {
+        self.points
+            .windows(2)
+            .filter(|pair| pair[0].equity > Decimal::ZERO)
+            .filter_map(|pair| ((pair[1].equity - pair[1].contribution - pair[0].equity) / pair[0].equity).to_f64())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_drawdown_and_ratios() {
+        let start = Utc::now();
+        let mut curve = EquityCurve::new(dec!(1000));
+        for (i, equity) in [dec!(1100), dec!(990), dec!(1045), dec!(1210)].into_iter().enumerate() {
+            curve.record(start + Duration::hours(i as i64), equity);
+        }
+
+        // 1100 -> 990 is the worst fall: 10% of the peak
+        assert!((curve.max_drawdown() - 0.1).abs() < 1e-9);
+        assert!((curve.points()[1].drawdown - 0.1).abs() < 1e-9);
+        assert!((curve.rolling_max_drawdown(2) - 0.05).abs() < 1e-9);
+        assert_eq!(curve.current_drawdown(), 0.0);
+
+        // Returns of -10%, +5.6% and +15.8%; only the loss counts against Sortino
+        assert!(curve.sharpe_ratio() > 0.0);
+        assert!(curve.sortino_ratio() > curve.sharpe_ratio());
+
+        assert_eq!(curve.between(Some(start + Duration::minutes(30)), Some(start + Duration::hours(3))).len(), 2);
+    }
+}
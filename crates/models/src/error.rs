@@ -11,6 +11,21 @@ pub enum QuantsError {
     #[error("Invalid stake amount: {amount}")]
     InvalidStake { amount: String },
     
+    #[error("Invalid promotion: {0}")]
+    InvalidPromotion(String),
+    
+    #[error("Invalid accumulator: {0}")]
+    InvalidAccumulator(String),
+    
+    #[error("Invalid bankroll adjustment: {0}")]
+    InvalidAdjustment(String),
+    
+    #[error("Invalid cursor: {cursor}")]
+    InvalidCursor { cursor: String },
+    
+    #[error("Not a match result selection: {0}")]
+    NotMatchResult(String),
+    
     #[error("Match not found: {match_id}")]
     MatchNotFound { match_id: String },
     
@@ -23,6 +38,12 @@ pub enum QuantsError {
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
     
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    
+    #[error("No exchange rate from {from} to {to}")]
+    MissingRate { from: crate::Currency, to: crate::Currency },
+    
     #[error("Configuration error: {0}")]
     Config(String),
 }
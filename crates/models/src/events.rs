@@ -1,8 +1,14 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 use uuid::Uuid;
+use crate::error::{QuantsError, Result};
 
+/// Also reads the flat shape older producers wrote (`score_home`,
+/// `minute`, `player` and a bare `"Goal"` event type)
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(from = "crate::legacy::MatchEventWire")]
 pub struct MatchEvent {
     pub id: Uuid,
     pub match_id: String,
@@ -15,6 +21,9 @@ pub struct MatchEvent {
     pub match_status: MatchStatus,
     pub score: Option<Score>,
     pub metadata: serde_json::Value,
+    /// Position of the event within its match, counting from 1, as numbered
+    /// by the source. Lets consumers drop duplicates and restore order.
+    pub sequence: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -23,10 +32,108 @@ pub enum EventType {
     Goal { team: String, player: Option<String>, minute: u8 },
     Card { team: String, player: String, card_type: CardType, minute: u8 },
     Substitution { team: String, player_in: String, player_out: String, minute: u8 },
+    /// An attempt on goal. Feeds report a shot that is scored before its goal.
+    Shot { team: String, player: Option<String>, minute: u8, xg_inputs: XgInputs },
     HalfTime,
     FullTime,
     MatchEnd,
     OddsUpdate,
+    /// A team's starting line-up, or a change to it, and who is missing
+    Lineup { team: String, formation: String, starters: Vec<String>, absentees: Vec<Absentee> },
+}
+
+impl EventType {
+    /// Snake-case name of the kind of event, as stored and filtered on
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EventType::MatchStart => "match_start",
+            EventType::Goal { .. } => "goal",
+            EventType::Card { .. } => "card",
+            EventType::Substitution { .. } => "substitution",
+            EventType::Shot { .. } => "shot",
+            EventType::HalfTime => "half_time",
+            EventType::FullTime => "full_time",
+            EventType::MatchEnd => "match_end",
+            EventType::OddsUpdate => "odds_update",
+            EventType::Lineup { .. } => "lineup",
+        }
+    }
+
+    /// Match minute of a goal, card or substitution
+    pub fn minute(&self) -> Option<u8> {
+        match self {
+            EventType::Goal { minute, .. }
+            | EventType::Card { minute, .. }
+            | EventType::Substitution { minute, .. }
+            | EventType::Shot { minute, .. } => Some(*minute),
+            _ => None,
+        }
+    }
+
+    /// The team a goal, card, substitution, shot or line-up was for
+    pub fn team(&self) -> Option<&str> {
+        match self {
+            EventType::Goal { team, .. }
+            | EventType::Card { team, .. }
+            | EventType::Substitution { team, .. }
+            | EventType::Shot { team, .. }
+            | EventType::Lineup { team, .. } => Some(team),
+            _ => None,
+        }
+    }
+
+    /// The scorer, the player booked, the player brought on or the shooter
+    pub fn player(&self) -> Option<&str> {
+        match self {
+            EventType::Goal { player, .. } | EventType::Shot { player, .. } => player.as_deref(),
+            EventType::Card { player, .. } => Some(player),
+            EventType::Substitution { player_in, .. } => Some(player_in),
+            _ => None,
+        }
+    }
+}
+
+/// A player left out of a line-up
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Absentee {
+    pub player: String,
+    pub reason: AbsenceReason,
+    /// Share of the team's strength the player carries, 0 for a squad
+    /// player up to around 0.15 for a talisman
+    pub importance: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum AbsenceReason {
+    Injury,
+    Suspension,
+    Rested,
+}
+
+/// Where and how a shot was taken, for the expected goals model
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct XgInputs {
+    /// Metres from the centre of the goal line
+    pub distance: f64,
+    /// Angle in degrees the goal mouth spans from where the shot was taken
+    pub angle: f64,
+    pub body_part: BodyPart,
+    pub situation: ShotSituation,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum BodyPart {
+    Foot,
+    Head,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ShotSituation {
+    OpenPlay,
+    Counter,
+    SetPiece,
+    Penalty,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -74,6 +181,7 @@ impl MatchEvent {
             match_status: MatchStatus::Scheduled,
             score: None,
             metadata: serde_json::Value::Null,
+            sequence: None,
         }
     }
     
@@ -82,11 +190,30 @@ impl MatchEvent {
         self
     }
     
+    pub fn with_sequence(mut self, sequence: u64) -> Self {
+        self.sequence = Some(sequence);
+        self
+    }
+    
     pub fn with_status(mut self, status: MatchStatus) -> Self {
         self.match_status = status;
         self
     }
     
+    pub fn with_timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+    
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = metadata;
+        self
+    }
+    
+    pub fn minute(&self) -> Option<u8> {
+        self.event_type.minute()
+    }
+    
     pub fn is_live(&self) -> bool {
         matches!(self.match_status, MatchStatus::Live | MatchStatus::HalfTime)
     }
@@ -96,6 +223,73 @@ impl MatchEvent {
     }
 }
 
+/// Narrows an event listing, newest first; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct EventQuery {
+    pub league: Option<String>,
+    /// Either side of the match
+    pub team: Option<String>,
+    /// An `EventType::kind`, e.g. `goal`
+    pub event_type: Option<String>,
+    /// Inclusive lower bound on when the event happened
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound
+    pub to: Option<DateTime<Utc>>,
+    pub match_id: Option<String>,
+    /// Only events listed after this one, i.e. older
+    pub after: Option<EventCursor>,
+    pub limit: usize,
+}
+
+impl EventQuery {
+    /// Whether `event` passes the filters and comes after the cursor.
+    /// Names are compared case-insensitively.
+    pub fn accepts(&self, event: &MatchEvent) -> bool {
+        let same = |wanted: &Option<String>, value: &str| wanted.as_deref().is_none_or(|w| w.eq_ignore_ascii_case(value));
+        same(&self.league, &event.league)
+            && self.team.as_deref().is_none_or(|team| {
+                event.team_home.eq_ignore_ascii_case(team) || event.team_away.eq_ignore_ascii_case(team)
+            })
+            && same(&self.event_type, event.event_type.kind())
+            && self.match_id.as_deref().is_none_or(|id| id == event.match_id)
+            && self.from.is_none_or(|from| event.timestamp >= from)
+            && self.to.is_none_or(|to| event.timestamp < to)
+            && self.after.as_ref().is_none_or(|cursor| (event.timestamp, event.id) < (cursor.timestamp, cursor.id))
+    }
+}
+
+/// Keyset position in an event listing: the last event of a page. Written
+/// as `<unix microseconds>_<event id>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventCursor {
+    pub timestamp: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl From<&MatchEvent> for EventCursor {
+    fn from(event: &MatchEvent) -> Self {
+        Self { timestamp: event.timestamp, id: event.id }
+    }
+}
+
+impl fmt::Display for EventCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_{}", self.timestamp.timestamp_micros(), self.id.simple())
+    }
+}
+
+impl FromStr for EventCursor {
+    type Err = QuantsError;
+
+    fn from_str(cursor: &str) -> Result<Self> {
+        let invalid = || QuantsError::InvalidCursor { cursor: cursor.to_string() };
+        let (micros, id) = cursor.split_once('_').ok_or_else(invalid)?;
+        let timestamp = micros.parse().ok().and_then(DateTime::from_timestamp_micros).ok_or_else(invalid)?;
+        let id = Uuid::parse_str(id).map_err(|_| invalid())?;
+        Ok(Self { timestamp, id })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
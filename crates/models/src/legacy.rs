@@ -0,0 +1,272 @@
+// Deserialization shims for the flat event and prediction shapes written
+// before the current models. Both shapes are accepted on the way in; only
+// the current one is ever written.
+
+use crate::events::{CardType, EventType, MatchEvent, MatchStatus, Score};
+use crate::outcome::Outcome1X2;
+use crate::predictions::{Prediction, ScoreMatrix};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A match event in either shape. The old one kept the score, minute and
+/// player at the top level and named the event type without its details.
+#[derive(Deserialize)]
+pub(crate) struct MatchEventWire {
+    #[serde(default = "Uuid::new_v4")]
+    id: Uuid,
+    match_id: String,
+    timestamp: DateTime<Utc>,
+    event_type: EventTypeWire,
+    team_home: String,
+    team_away: String,
+    league: String,
+    #[serde(default)]
+    season: String,
+    #[serde(default)]
+    match_status: Option<MatchStatus>,
+    #[serde(default)]
+    score: Option<Score>,
+    #[serde(default)]
+    metadata: serde_json::Value,
+    #[serde(default)]
+    sequence: Option<u64>,
+    #[serde(default)]
+    score_home: Option<u8>,
+    #[serde(default)]
+    score_away: Option<u8>,
+    #[serde(default)]
+    minute: Option<u8>,
+    #[serde(default)]
+    player: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EventTypeWire {
+    Current(EventType),
+    Legacy(LegacyEventType),
+}
+
+/// Old event types that now carry their details
+#[derive(Deserialize)]
+enum LegacyEventType {
+    Goal,
+    YellowCard,
+    RedCard,
+    Substitution,
+}
+
+impl From<MatchEventWire> for MatchEvent {
+    fn from(wire: MatchEventWire) -> Self {
+        let event_type = match wire.event_type {
+            EventTypeWire::Current(event_type) => event_type,
+            EventTypeWire::Legacy(legacy) => {
+                // The old shape had no team field; producers that knew it put it in metadata
+                let team = metadata_str(&wire.metadata, "team").unwrap_or_default();
+                let minute = wire.minute.unwrap_or(0);
+                let player = wire.player.clone();
+                match legacy {
+                    LegacyEventType::Goal => EventType::Goal { team, player, minute },
+                    LegacyEventType::YellowCard | LegacyEventType::RedCard => EventType::Card {
+                        team,
+                        player: player.unwrap_or_default(),
+                        card_type: if matches!(legacy, LegacyEventType::RedCard) { CardType::Red } else { CardType::Yellow },
+                        minute,
+                    },
+                    LegacyEventType::Substitution => EventType::Substitution {
+                        team,
+                        player_in: player.unwrap_or_default(),
+                        player_out: metadata_str(&wire.metadata, "player_out").unwrap_or_default(),
+                        minute,
+                    },
+                }
+            }
+        };
+
+        let flat_score = match (wire.score_home, wire.score_away) {
+            (Some(home), Some(away)) => Some(Score { home, away, half_time_home: None, half_time_away: None }),
+            _ => None,
+        };
+        let score = wire.score.or(flat_score);
+        let match_status = wire.match_status.unwrap_or_else(|| status_implied_by(&event_type));
+
+        Self {
+            id: wire.id,
+            match_id: wire.match_id,
+            timestamp: wire.timestamp,
+            event_type,
+            team_home: wire.team_home,
+            team_away: wire.team_away,
+            league: wire.league,
+            season: wire.season,
+            match_status,
+            score,
+            metadata: wire.metadata,
+            sequence: wire.sequence,
+        }
+    }
+}
+
+/// The old shape had no status; work it out from what happened
+fn status_implied_by(event_type: &EventType) -> MatchStatus {
+    match event_type {
+        EventType::OddsUpdate | EventType::Lineup { .. } => MatchStatus::Scheduled,
+        EventType::HalfTime => MatchStatus::HalfTime,
+        EventType::FullTime | EventType::MatchEnd => MatchStatus::Finished,
+        _ => MatchStatus::Live,
+    }
+}
+
+fn metadata_str(metadata: &serde_json::Value, key: &str) -> Option<String> {
+    metadata.get(key)?.as_str().map(str::to_string)
+}
+
+/// A prediction in either shape. The old one held the outcome probabilities
+/// in a map, had one timestamp and no model name, and carried staking advice
+/// that is now the trading engine's job.
+#[derive(Deserialize)]
+pub(crate) struct PredictionWire {
+    #[serde(default = "Uuid::new_v4")]
+    id: Uuid,
+    match_id: String,
+    #[serde(default)]
+    model_name: Option<String>,
+    model_version: String,
+    #[serde(default)]
+    home_win_prob: Option<f64>,
+    #[serde(default)]
+    draw_prob: Option<f64>,
+    #[serde(default)]
+    away_win_prob: Option<f64>,
+    #[serde(default)]
+    confidence: f64,
+    #[serde(default)]
+    expected_goals_home: Option<f64>,
+    #[serde(default)]
+    expected_goals_away: Option<f64>,
+    #[serde(default)]
+    score_matrix: Option<ScoreMatrix>,
+    #[serde(default)]
+    features_used: Vec<String>,
+    #[serde(default)]
+    prediction_timestamp: Option<DateTime<Utc>>,
+    #[serde(default)]
+    match_timestamp: Option<DateTime<Utc>>,
+    #[serde(default)]
+    metadata: serde_json::Value,
+    #[serde(default)]
+    timestamp: Option<DateTime<Utc>>,
+    #[serde(default)]
+    probabilities: Option<HashMap<Outcome1X2, f64>>,
+    #[serde(default)]
+    expected_value: Option<f64>,
+    #[serde(default)]
+    recommended_bet: Option<Outcome1X2>,
+    #[serde(default)]
+    stake_percentage: Option<f64>,
+}
+
+impl TryFrom<PredictionWire> for Prediction {
+    type Error = String;
+
+    fn try_from(wire: PredictionWire) -> Result<Self, Self::Error> {
+        let probabilities = wire.probabilities.unwrap_or_default();
+        let home_win_prob = wire.home_win_prob
+            .or_else(|| probabilities.get(&Outcome1X2::HomeWin).copied())
+            .ok_or("prediction has no home win probability")?;
+        let away_win_prob = wire.away_win_prob
+            .or_else(|| probabilities.get(&Outcome1X2::AwayWin).copied())
+            .ok_or("prediction has no away win probability")?;
+        let draw_prob = wire.draw_prob.or_else(|| probabilities.get(&Outcome1X2::Draw).copied());
+        let prediction_timestamp = wire.prediction_timestamp.or(wire.timestamp)
+            .ok_or("prediction has no timestamp")?;
+
+        // Keep the old staking advice rather than drop it
+        let mut metadata = wire.metadata;
+        let advice = [
+            ("expected_value", wire.expected_value.map(serde_json::Value::from)),
+            ("recommended_bet", wire.recommended_bet.and_then(|bet| serde_json::to_value(bet).ok())),
+            ("stake_percentage", wire.stake_percentage.map(serde_json::Value::from)),
+        ];
+        for (key, value) in advice {
+            let Some(value) = value else { continue };
+            if metadata.is_null() {
+                metadata = serde_json::Value::Object(Default::default());
+            }
+            if let Some(fields) = metadata.as_object_mut() {
+                fields.entry(key).or_insert(value);
+            }
+        }
+
+        Ok(Self {
+            id: wire.id,
+            match_id: wire.match_id,
+            model_name: wire.model_name.unwrap_or_else(|| "unknown".to_string()),
+            model_version: wire.model_version,
+            home_win_prob,
+            draw_prob,
+            away_win_prob,
+            confidence: wire.confidence,
+            expected_goals_home: wire.expected_goals_home,
+            expected_goals_away: wire.expected_goals_away,
+            score_matrix: wire.score_matrix,
+            features_used: wire.features_used,
+            prediction_timestamp,
+            match_timestamp: wire.match_timestamp.unwrap_or(prediction_timestamp),
+            metadata,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_legacy_shapes_deserialize_into_current_models() {
+        let event: MatchEvent = serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "match_id": "m1",
+            "event_type": "Goal",
+            "timestamp": "2024-03-10T15:45:00Z",
+            "sport": "Football",
+            "league": "Premier League",
+            "team_home": "Arsenal",
+            "team_away": "Chelsea",
+            "score_home": 1,
+            "score_away": 0,
+            "minute": 45,
+            "player": "Saka",
+            "metadata": {"team": "Arsenal"},
+        })).unwrap();
+        assert_eq!(event.event_type, EventType::Goal { team: "Arsenal".to_string(), player: Some("Saka".to_string()), minute: 45 });
+        assert_eq!(event.score.as_ref().map(|score| (score.home, score.away)), Some((1, 0)));
+        assert!(event.is_live());
+
+        // The current shape still round trips
+        let restored: MatchEvent = serde_json::from_str(&serde_json::to_string(&event).unwrap()).unwrap();
+        assert_eq!(restored, event);
+
+        let prediction: Prediction = serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "match_id": "m1",
+            "model_version": "v1.0",
+            "timestamp": "2024-03-10T15:45:00Z",
+            "probabilities": {"HomeWin": 0.5, "Draw": 0.3, "AwayWin": 0.2},
+            "confidence": 0.75,
+            "expected_value": 0.15,
+            "recommended_bet": "HomeWin",
+            "stake_percentage": 0.02,
+            "metadata": {},
+        })).unwrap();
+        assert_eq!((prediction.home_win_prob, prediction.draw_prob, prediction.away_win_prob), (0.5, Some(0.3), 0.2));
+        assert_eq!(prediction.metadata["recommended_bet"], "HomeWin");
+        assert_eq!(prediction.match_timestamp, prediction.prediction_timestamp);
+
+        let restored: Prediction = serde_json::from_str(&serde_json::to_string(&prediction).unwrap()).unwrap();
+        assert_eq!(restored, prediction);
+    }
+}
@@ -1,11 +1,34 @@
 pub mod events;
 pub mod predictions;
 pub mod betting;
+pub mod currency;
+pub mod domain;
+pub mod audit;
+pub mod equity;
 pub mod market;
+pub mod odds_history;
+pub mod outcome;
+pub mod promotion;
+pub mod staking;
+pub mod summary;
+pub mod teams;
+pub mod query_log;
 pub mod error;
+mod legacy;
 
 pub use events::*;
 pub use predictions::*;
 pub use betting::*;
+pub use currency::*;
+pub use domain::*;
+pub use audit::*;
+pub use equity::*;
 pub use market::*;
+pub use odds_history::*;
+pub use outcome::*;
+pub use promotion::*;
+pub use staking::*;
+pub use summary::*;
+pub use teams::*;
+pub use query_log::*;
 pub use error::*;
\ No newline at end of file
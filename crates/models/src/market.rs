@@ -2,7 +2,11 @@ use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
 use uuid::Uuid;
+use crate::betting::{net_odds, BetType, DoubleChanceSelection, MatchHalf};
+use crate::outcome::Outcome1X2;
 use crate::error::{QuantsError, Result};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -32,6 +36,149 @@ impl SimpleMarketOdds {
             away_win: Decimal::from_f64_retain(1.0 / adjusted_away).unwrap_or(Decimal::from(2)),
         }
     }
+
+    /// The book's margin: implied probabilities' total above 1
+    pub fn overround(&self) -> f64 {
+        overround([self.home_win, self.draw, self.away_win])
+    }
+}
+
+/// Total implied probability of a set of prices covering every outcome, less 1
+pub fn overround(prices: impl IntoIterator<Item = Decimal>) -> f64 {
+    prices.into_iter()
+        .filter_map(|price| price.to_f64())
+        .filter(|price| *price > 0.0)
+        .map(|price| 1.0 / price)
+        .sum::<f64>() - 1.0
+}
+
+/// Double chance and draw no bet prices implied by a match winner quote.
+/// With q the quote's implied probabilities (margin included), double
+/// chance is 1 / (q1 + q2) and draw no bet is (1 - q_draw) / q_side, so the
+/// derived prices carry the same margin as the quote they come from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DerivedMarketOdds {
+    pub home_or_draw: Decimal,
+    pub draw_or_away: Decimal,
+    pub home_or_away: Decimal,
+    pub draw_no_bet_home: Decimal,
+    pub draw_no_bet_away: Decimal,
+}
+
+impl DerivedMarketOdds {
+    pub fn from_match_odds(odds: &SimpleMarketOdds) -> Self {
+        let implied = |price: Decimal| 1.0 / price.to_f64().unwrap_or(f64::INFINITY).max(1.0);
+        let (home, draw, away) = (implied(odds.home_win), implied(odds.draw), implied(odds.away_win));
+        let price = |value: f64| Decimal::from_f64_retain(value.max(1.01))
+            .unwrap_or(Decimal::ONE)
+            .round_dp(2);
+
+        Self {
+            home_or_draw: price(1.0 / (home + draw)),
+            draw_or_away: price(1.0 / (draw + away)),
+            home_or_away: price(1.0 / (home + away)),
+            draw_no_bet_home: price((1.0 - draw) / home),
+            draw_no_bet_away: price((1.0 - draw) / away),
+        }
+    }
+
+    pub fn price_for(&self, bet_type: &BetType) -> Option<Decimal> {
+        match bet_type {
+            BetType::DoubleChance { selection } => Some(match selection {
+                DoubleChanceSelection::HomeOrDraw => self.home_or_draw,
+                DoubleChanceSelection::DrawOrAway => self.draw_or_away,
+                DoubleChanceSelection::HomeOrAway => self.home_or_away,
+            }),
+            BetType::DrawNoBet { home: true } => Some(self.draw_no_bet_home),
+            BetType::DrawNoBet { home: false } => Some(self.draw_no_bet_away),
+            _ => None,
+        }
+    }
+}
+
+/// One two-way Asian handicap line. `line` is the home side's handicap;
+/// the away side gets the opposite.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AsianHandicapQuote {
+    pub line: Decimal,
+    pub home: Decimal,
+    pub away: Decimal,
+}
+
+impl AsianHandicapQuote {
+    pub fn overround(&self) -> f64 {
+        overround([self.home, self.away])
+    }
+}
+
+/// Asian handicap ladder for a match, around the line that splits the
+/// market most evenly
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AsianHandicapOdds {
+    pub main_line: Decimal,
+    pub quotes: Vec<AsianHandicapQuote>,
+}
+
+impl AsianHandicapOdds {
+    /// Price for one side, with `line` given from that side's point of view
+    pub fn price(&self, line: Decimal, home: bool) -> Option<Decimal> {
+        let home_line = if home { line } else { -line };
+        self.quotes.iter()
+            .find(|quote| quote.line == home_line)
+            .map(|quote| if home { quote.home } else { quote.away })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CorrectScoreQuote {
+    pub home_goals: u8,
+    pub away_goals: u8,
+    pub price: Decimal,
+}
+
+/// Exact score prices for a match
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CorrectScoreOdds {
+    pub quotes: Vec<CorrectScoreQuote>,
+}
+
+impl CorrectScoreOdds {
+    pub fn price(&self, home_goals: u8, away_goals: u8) -> Option<Decimal> {
+        self.quotes.iter()
+            .find(|quote| quote.home_goals == home_goals && quote.away_goals == away_goals)
+            .map(|quote| quote.price)
+    }
+
+    /// Margin across the quoted scores; scores not quoted count as nothing,
+    /// so this understates a book that leaves some out
+    pub fn overround(&self) -> f64 {
+        overround(self.quotes.iter().map(|quote| quote.price))
+    }
+}
+
+/// Match winner prices for the goals of each half alone. The first half
+/// stops being quoted once it is over.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HalfResultOdds {
+    pub first_half: Option<SimpleMarketOdds>,
+    pub second_half: SimpleMarketOdds,
+}
+
+impl HalfResultOdds {
+    pub fn odds_for(&self, half: MatchHalf) -> Option<&SimpleMarketOdds> {
+        match half {
+            MatchHalf::First => self.first_half.as_ref(),
+            MatchHalf::Second => Some(&self.second_half),
+        }
+    }
+
+    pub fn price_for(&self, bet_type: &BetType) -> Option<Decimal> {
+        let BetType::HalfResult { half, outcome } = bet_type else {
+            return None;
+        };
+        let odds = self.odds_for(*half)?;
+        Some(outcome.select([odds.home_win, odds.draw, odds.away_win]))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -53,6 +200,7 @@ pub enum MarketType {
     BothTeamsToScore,
     CorrectScore,
     FirstGoalscorer,
+    HalfResult { half: MatchHalf },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -63,6 +211,32 @@ pub enum OddsFormat {
 }
 
 impl OddsFormat {
+    pub fn style(&self) -> OddsStyle {
+        match self {
+            OddsFormat::Decimal { .. } => OddsStyle::Decimal,
+            OddsFormat::American { .. } => OddsStyle::American,
+            OddsFormat::Fractional { .. } => OddsStyle::Fractional,
+        }
+    }
+
+    /// The same prices written in `style`
+    pub fn convert(&self, style: OddsStyle) -> Result<OddsFormat> {
+        let (home, draw, away) = self.to_decimal()?;
+        Ok(match style {
+            OddsStyle::Decimal => OddsFormat::Decimal { home, draw, away },
+            OddsStyle::American => OddsFormat::American {
+                home: decimal_to_american(home)?,
+                draw: draw.map(decimal_to_american).transpose()?,
+                away: decimal_to_american(away)?,
+            },
+            OddsStyle::Fractional => OddsFormat::Fractional {
+                home: decimal_to_fractional(home)?,
+                draw: draw.map(decimal_to_fractional).transpose()?,
+                away: decimal_to_fractional(away)?,
+            },
+        })
+    }
+
     pub fn to_decimal(&self) -> Result<(Decimal, Option<Decimal>, Decimal)> {
         match self {
             OddsFormat::Decimal { home, draw, away } => Ok((*home, *draw, *away)),
@@ -147,6 +321,53 @@ impl OddsFormat {
     }
 }
 
+/// The ways odds are written: 2.50, +150 or 3/2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OddsStyle {
+    Decimal,
+    American,
+    Fractional,
+}
+
+impl OddsStyle {
+    /// Read one price written in this style, as decimal odds
+    pub fn parse_price(&self, price: &str) -> Result<Decimal> {
+        let price = price.trim();
+        let decimal = match self {
+            OddsStyle::Decimal => Decimal::from_str(price)
+                .map_err(|_| QuantsError::InvalidOdds(format!("Invalid decimal odds: {}", price)))?,
+            OddsStyle::American => american_to_decimal(price.parse()
+                .map_err(|_| QuantsError::InvalidOdds(format!("Invalid American odds: {}", price)))?)?,
+            OddsStyle::Fractional => fractional_to_decimal(price)?,
+        };
+        if decimal <= Decimal::ONE {
+            return Err(QuantsError::InvalidOdds(format!("Odds must pay more than the stake: {}", price)));
+        }
+        Ok(decimal)
+    }
+}
+
+/// One price written every way, with the probability it implies
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OddsConversion {
+    pub decimal: Decimal,
+    pub american: i32,
+    pub fractional: String,
+    pub implied_probability: f64,
+}
+
+impl OddsConversion {
+    pub fn from_decimal(decimal: Decimal) -> Result<Self> {
+        Ok(Self {
+            decimal,
+            american: decimal_to_american(decimal)?,
+            fractional: decimal_to_fractional(decimal)?,
+            implied_probability: 1.0 / decimal.to_f64().unwrap_or(f64::INFINITY),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValueBet {
     pub match_id: String,
@@ -177,9 +398,183 @@ impl ValueBetType {
             ValueBetType::Draw { odds, .. } => *odds,
         }
     }
+
+    pub fn bet_type(&self) -> BetType {
+        match self {
+            ValueBetType::Home { .. } => BetType::HomeWin,
+            ValueBetType::Away { .. } => BetType::AwayWin,
+            ValueBetType::Draw { .. } => BetType::Draw,
+        }
+    }
+
+    pub fn true_prob(&self) -> f64 {
+        match self {
+            ValueBetType::Home { true_prob, .. }
+            | ValueBetType::Away { true_prob, .. }
+            | ValueBetType::Draw { true_prob, .. } => *true_prob,
+        }
+    }
+
+    pub fn implied_prob(&self) -> f64 {
+        match self {
+            ValueBetType::Home { implied_prob, .. }
+            | ValueBetType::Away { implied_prob, .. }
+            | ValueBetType::Draw { implied_prob, .. } => *implied_prob,
+        }
+    }
+
+    /// How far the true probability is above the one the odds imply
+    pub fn edge(&self) -> f64 {
+        self.true_prob() - self.implied_prob()
+    }
+}
+
+/// Latest match winner prices quoted by one bookmaker
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BookmakerPrice {
+    pub bookmaker: String,
+    pub home_win: Decimal,
+    pub draw: Option<Decimal>,
+    pub away_win: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl BookmakerPrice {
+    pub fn price_for(&self, bet_type: &BetType) -> Option<Decimal> {
+        Outcome1X2::try_from(bet_type).ok()?.select([Some(self.home_win), self.draw, Some(self.away_win)])
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BestPrice {
+    pub price: Decimal,
+    pub bookmaker: String,
+    /// Commission the bookmaker takes from net winnings
+    pub commission: Decimal,
+    /// Promotion that produced this price, if it is a boosted one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub promotion: Option<Uuid>,
+}
+
+/// Highest price on offer for each outcome, possibly from different bookmakers
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BestOdds {
+    pub home_win: BestPrice,
+    pub draw: Option<BestPrice>,
+    pub away_win: BestPrice,
+}
+
+/// Match winner prices for one match across every bookmaker quoting it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OddsBook {
+    pub match_id: String,
+    prices: HashMap<String, BookmakerPrice>,
+}
+
+impl OddsBook {
+    pub fn new(match_id: String) -> Self {
+        Self {
+            match_id,
+            prices: HashMap::new(),
+        }
+    }
+
+    /// Replace the bookmaker's quote with `odds`. Only match winner markets
+    /// are tracked; stale quotes (older than the stored one) are ignored.
+    pub fn upsert(&mut self, odds: &MarketOdds) -> Result<()> {
+        if odds.match_id != self.match_id {
+            return Err(QuantsError::MatchNotFound { match_id: odds.match_id.clone() });
+        }
+        if odds.market_type != MarketType::MatchWinner {
+            return Err(QuantsError::InvalidOdds(
+                format!("Unsupported market for odds book: {:?}", odds.market_type)
+            ));
+        }
+
+        if let Some(existing) = self.prices.get(&odds.bookmaker) {
+            if existing.timestamp > odds.timestamp {
+                return Ok(());
+            }
+        }
+
+        if !odds.is_active {
+            self.prices.remove(&odds.bookmaker);
+            return Ok(());
+        }
+
+        let (home_win, draw, away_win) = odds.odds.to_decimal()?;
+        self.prices.insert(odds.bookmaker.clone(), BookmakerPrice {
+            bookmaker: odds.bookmaker.clone(),
+            home_win,
+            draw,
+            away_win,
+            timestamp: odds.timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Add a quote already in decimal form, e.g. from a single aggregated feed
+    pub fn insert_simple(&mut self, bookmaker: &str, odds: &SimpleMarketOdds) {
+        self.insert_simple_at(bookmaker, odds, Utc::now());
+    }
+
+    pub fn insert_simple_at(&mut self, bookmaker: &str, odds: &SimpleMarketOdds, timestamp: DateTime<Utc>) {
+        self.prices.insert(bookmaker.to_string(), BookmakerPrice {
+            bookmaker: bookmaker.to_string(),
+            home_win: odds.home_win,
+            draw: Some(odds.draw),
+            away_win: odds.away_win,
+            timestamp,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+
+    /// Latest quote from `bookmaker`, matched case-insensitively
+    pub fn quote(&self, bookmaker: &str) -> Option<&BookmakerPrice> {
+        self.prices.get(bookmaker).or_else(|| {
+            self.prices.values().find(|quote| quote.bookmaker.eq_ignore_ascii_case(bookmaker))
+        })
+    }
+
+    /// Every bookmaker's quote, ordered by bookmaker name
+    pub fn ladder(&self) -> Vec<BookmakerPrice> {
+        let mut ladder: Vec<_> = self.prices.values().cloned().collect();
+        ladder.sort_by(|a, b| a.bookmaker.cmp(&b.bookmaker));
+        ladder
+    }
+
+    pub fn best(&self) -> Option<BestOdds> {
+        self.best_with_commission(|_| Decimal::ZERO)
+    }
+
+    /// Best price per outcome ranked by odds net of each bookmaker's commission
+    pub fn best_with_commission(&self, commission_for: impl Fn(&str) -> Decimal) -> Option<BestOdds> {
+        let ladder = self.ladder();
+        let best_of = |price: fn(&BookmakerPrice) -> Option<Decimal>| {
+            ladder.iter()
+                .filter_map(|quote| price(quote).map(|p| (p, &quote.bookmaker)))
+                .map(|(price, bookmaker)| BestPrice {
+                    price,
+                    bookmaker: bookmaker.clone(),
+                    commission: commission_for(bookmaker),
+                    promotion: None,
+                })
+                .max_by(|a, b| net_odds(a.price, a.commission).total_cmp(&net_odds(b.price, b.commission)))
+        };
+
+        Some(BestOdds {
+            home_win: best_of(|quote| Some(quote.home_win))?,
+            draw: best_of(|quote| quote.draw),
+            away_win: best_of(|quote| Some(quote.away_win))?,
+        })
+    }
 }
 
-fn american_to_decimal(american: i32) -> Result<Decimal> {
+pub fn american_to_decimal(american: i32) -> Result<Decimal> {
     if american == 0 {
         return Err(QuantsError::InvalidOdds("American odds cannot be zero".to_string()));
     }
@@ -193,7 +588,7 @@ fn american_to_decimal(american: i32) -> Result<Decimal> {
     Ok(decimal)
 }
 
-fn fractional_to_decimal(fractional: &str) -> Result<Decimal> {
+pub fn fractional_to_decimal(fractional: &str) -> Result<Decimal> {
     let parts: Vec<&str> = fractional.split('/').collect();
     if parts.len() != 2 {
         return Err(QuantsError::InvalidOdds(format!("Invalid fractional odds format: {}", fractional)));
@@ -211,6 +606,43 @@ fn fractional_to_decimal(fractional: &str) -> Result<Decimal> {
     Ok(Decimal::from(numerator) / Decimal::from(denominator) + Decimal::ONE)
 }
 
+/// Evens and longer are written +(profit per 100 staked), shorter prices
+/// -(stake needed to win 100); rounded to the nearest whole number
+pub fn decimal_to_american(decimal: Decimal) -> Result<i32> {
+    let profit = decimal - Decimal::ONE;
+    if profit <= Decimal::ZERO {
+        return Err(QuantsError::InvalidOdds(format!("No American odds for {}", decimal)));
+    }
+
+    let american = if profit >= Decimal::ONE {
+        profit * Decimal::from(100)
+    } else {
+        -(Decimal::from(100) / profit)
+    };
+    american.round().to_i32()
+        .ok_or_else(|| QuantsError::InvalidOdds(format!("No American odds for {}", decimal)))
+}
+
+/// Profit to stake as a fraction in lowest terms, e.g. 2.5 is 3/2
+pub fn decimal_to_fractional(decimal: Decimal) -> Result<String> {
+    let profit = (decimal - Decimal::ONE).normalize();
+    if profit <= Decimal::ZERO {
+        return Err(QuantsError::InvalidOdds(format!("No fractional odds for {}", decimal)));
+    }
+
+    let numerator = profit.mantissa();
+    let denominator = 10i128.pow(profit.scale());
+    let gcd = greatest_common_divisor(numerator, denominator);
+    Ok(format!("{}/{}", numerator / gcd, denominator / gcd))
+}
+
+fn greatest_common_divisor(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -230,6 +662,27 @@ mod tests {
         assert_eq!(away, dec!(2.8));
     }
     
+    #[test]
+    fn test_odds_styles_round_trip() {
+        let odds = OddsFormat::Decimal { home: dec!(2.50), draw: Some(dec!(3.20)), away: dec!(1.50) };
+
+        let american = odds.convert(OddsStyle::American).unwrap();
+        assert_eq!(american, OddsFormat::American { home: 150, draw: Some(220), away: -200 });
+        let fractional = odds.convert(OddsStyle::Fractional).unwrap();
+        assert_eq!(fractional, OddsFormat::Fractional {
+            home: "3/2".to_string(),
+            draw: Some("11/5".to_string()),
+            away: "1/2".to_string(),
+        });
+        assert_eq!(fractional.convert(OddsStyle::Decimal).unwrap().to_decimal().unwrap(), odds.to_decimal().unwrap());
+
+        assert_eq!(OddsStyle::American.parse_price("+150").unwrap(), dec!(2.5));
+        assert_eq!(OddsStyle::Fractional.parse_price("1/2").unwrap(), dec!(1.5));
+        assert!(OddsStyle::Decimal.parse_price("1.0").is_err());
+        let conversion = OddsConversion::from_decimal(dec!(1.91)).unwrap();
+        assert_eq!((conversion.american, conversion.fractional.as_str()), (-110, "91/100"));
+    }
+
     #[test]
     fn test_implied_probabilities() {
         let odds = OddsFormat::Decimal {
@@ -244,6 +697,56 @@ mod tests {
         assert!((away_prob - 0.25).abs() < 0.001);
     }
     
+    #[test]
+    fn test_derived_markets_from_match_odds() {
+        // A fair 2.0 / 4.0 / 4.0 book: 1X is 1 / 0.75 and DNB home is 0.75 / 0.5
+        let derived = DerivedMarketOdds::from_match_odds(
+            &SimpleMarketOdds::new(dec!(2.0), dec!(4.0), dec!(4.0)),
+        );
+        assert_eq!(derived.home_or_draw, dec!(1.33));
+        assert_eq!(derived.home_or_away, dec!(1.33));
+        assert_eq!(derived.draw_or_away, dec!(2.0));
+        assert_eq!(derived.draw_no_bet_home, dec!(1.5));
+        assert_eq!(derived.draw_no_bet_away, dec!(3.0));
+        assert_eq!(derived.price_for(&BetType::DrawNoBet { home: false }), Some(dec!(3.0)));
+    }
+
+    #[test]
+    fn test_odds_book_best_price_per_outcome() {
+        let quote = |bookmaker: &str, home, draw, away| MarketOdds {
+            id: Uuid::new_v4(),
+            match_id: "m1".to_string(),
+            market_type: MarketType::MatchWinner,
+            bookmaker: bookmaker.to_string(),
+            odds: OddsFormat::Decimal { home, draw: Some(draw), away },
+            timestamp: Utc::now(),
+            is_active: true,
+        };
+
+        let mut book = OddsBook::new("m1".to_string());
+        book.upsert(&quote("Pinnacle", dec!(2.10), dec!(3.30), dec!(3.60))).unwrap();
+        book.upsert(&quote("Bet365", dec!(2.05), dec!(3.50), dec!(3.75))).unwrap();
+
+        let best = book.best().unwrap();
+        assert_eq!(best.home_win, BestPrice {
+            price: dec!(2.10),
+            bookmaker: "Pinnacle".to_string(),
+            commission: Decimal::ZERO,
+            promotion: None,
+        });
+        assert_eq!(best.draw.unwrap().bookmaker, "Bet365");
+        assert_eq!(best.away_win.price, dec!(3.75));
+        assert_eq!(book.ladder().len(), 2);
+
+        // An exchange's higher quote loses once its 5% commission is netted off
+        book.upsert(&quote("Exchange", dec!(2.15), dec!(3.40), dec!(3.70))).unwrap();
+        assert_eq!(book.best().unwrap().home_win.bookmaker, "Exchange");
+        let net = book.best_with_commission(|bookmaker| {
+            if bookmaker == "Exchange" { dec!(0.05) } else { Decimal::ZERO }
+        }).unwrap();
+        assert_eq!(net.home_win.bookmaker, "Pinnacle");
+    }
+
     #[test]
     fn test_american_to_decimal() {
         assert_eq!(american_to_decimal(100).unwrap(), dec!(2.0));
@@ -0,0 +1,240 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::market::SimpleMarketOdds;
+
+/// A full snapshot is written after this many deltas so reconstruction never
+/// has to replay more than that
+pub const DEFAULT_KEYFRAME_INTERVAL: usize = 32;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum OddsOutcome {
+    Home,
+    Draw,
+    Away,
+}
+
+impl OddsOutcome {
+    pub const ALL: [OddsOutcome; 3] = [OddsOutcome::Home, OddsOutcome::Draw, OddsOutcome::Away];
+
+    fn price(&self, odds: &SimpleMarketOdds) -> Decimal {
+        match self {
+            OddsOutcome::Home => odds.home_win,
+            OddsOutcome::Draw => odds.draw,
+            OddsOutcome::Away => odds.away_win,
+        }
+    }
+
+    fn set_price(&self, odds: &mut SimpleMarketOdds, price: Decimal) {
+        match self {
+            OddsOutcome::Home => odds.home_win = price,
+            OddsOutcome::Draw => odds.draw = price,
+            OddsOutcome::Away => odds.away_win = price,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OddsEntry {
+    Keyframe { timestamp: DateTime<Utc>, odds: SimpleMarketOdds },
+    /// Only the outcomes whose price moved since the previous entry
+    Delta { timestamp: DateTime<Utc>, changes: Vec<(OddsOutcome, Decimal)> },
+}
+
+impl OddsEntry {
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            OddsEntry::Keyframe { timestamp, .. } | OddsEntry::Delta { timestamp, .. } => *timestamp,
+        }
+    }
+}
+
+/// Price history for one bookmaker's quote on one match, stored as periodic
+/// keyframes with deltas in between
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OddsTimeline {
+    keyframe_interval: usize,
+    entries: Vec<OddsEntry>,
+    /// Indexes into `entries` of every keyframe, in order
+    keyframes: Vec<usize>,
+    #[serde(skip)]
+    latest: Option<SimpleMarketOdds>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct TimelineStats {
+    pub keyframes: usize,
+    pub deltas: usize,
+    /// Individual prices stored across keyframes and deltas
+    pub prices_stored: usize,
+    /// Prices full snapshots would have needed for the same updates
+    pub prices_uncompressed: usize,
+}
+
+impl OddsTimeline {
+    pub fn new() -> Self {
+        Self::with_keyframe_interval(DEFAULT_KEYFRAME_INTERVAL)
+    }
+
+    pub fn with_keyframe_interval(keyframe_interval: usize) -> Self {
+        Self {
+            keyframe_interval: keyframe_interval.max(1),
+            entries: Vec::new(),
+            keyframes: Vec::new(),
+            latest: None,
+        }
+    }
+
+    /// Record a quote. Returns false if nothing was stored: either no price
+    /// moved, or the quote is older than the last one recorded.
+    pub fn push(&mut self, timestamp: DateTime<Utc>, odds: &SimpleMarketOdds) -> bool {
+        if self.entries.last().is_some_and(|last| timestamp < last.timestamp()) {
+            return false;
+        }
+        if self.latest.is_none() && !self.entries.is_empty() {
+            // Deserialized timelines rebuild their cursor lazily
+            self.latest = self.at(self.entries[self.entries.len() - 1].timestamp());
+        }
+
+        let since_keyframe = self.keyframes.last().map(|&k| self.entries.len() - k - 1);
+        let entry = match (&self.latest, since_keyframe) {
+            (Some(previous), Some(since)) if since < self.keyframe_interval => {
+                let changes: Vec<_> = OddsOutcome::ALL
+                    .into_iter()
+                    .filter(|outcome| outcome.price(previous) != outcome.price(odds))
+                    .map(|outcome| (outcome, outcome.price(odds)))
+                    .collect();
+                if changes.is_empty() {
+                    return false;
+                }
+                OddsEntry::Delta { timestamp, changes }
+            }
+            (Some(previous), _) if previous == odds => return false,
+            _ => {
+                self.keyframes.push(self.entries.len());
+                OddsEntry::Keyframe { timestamp, odds: odds.clone() }
+            }
+        };
+
+        self.entries.push(entry);
+        self.latest = Some(odds.clone());
+        true
+    }
+
+    /// The quote in force at `timestamp`: the last one recorded at or before it
+    pub fn at(&self, timestamp: DateTime<Utc>) -> Option<SimpleMarketOdds> {
+        self.quote_at(timestamp).map(|(_, odds)| odds)
+    }
+
+    /// Like `at`, also returning when that quote was recorded
+    pub fn quote_at(&self, timestamp: DateTime<Utc>) -> Option<(DateTime<Utc>, SimpleMarketOdds)> {
+        let end = self.entries.partition_point(|entry| entry.timestamp() <= timestamp);
+        if end == 0 {
+            return None;
+        }
+
+        let keyframe_slot = self.keyframes.partition_point(|&k| k < end).checked_sub(1)?;
+        let start = self.keyframes[keyframe_slot];
+
+        let mut odds = match &self.entries[start] {
+            OddsEntry::Keyframe { odds, .. } => odds.clone(),
+            OddsEntry::Delta { .. } => return None,
+        };
+        for entry in &self.entries[start + 1..end] {
+            if let OddsEntry::Delta { changes, .. } = entry {
+                for (outcome, price) in changes {
+                    outcome.set_price(&mut odds, *price);
+                }
+            }
+        }
+        Some((self.entries[end - 1].timestamp(), odds))
+    }
+
+    pub fn latest(&self) -> Option<SimpleMarketOdds> {
+        self.latest.clone().or_else(|| self.entries.last().and_then(|last| self.at(last.timestamp())))
+    }
+
+    pub fn first_timestamp(&self) -> Option<DateTime<Utc>> {
+        self.entries.first().map(OddsEntry::timestamp)
+    }
+
+    pub fn entries(&self) -> &[OddsEntry] {
+        &self.entries
+    }
+
+    pub fn stats(&self) -> TimelineStats {
+        let mut stats = TimelineStats::default();
+        for entry in &self.entries {
+            match entry {
+                OddsEntry::Keyframe { .. } => {
+                    stats.keyframes += 1;
+                    stats.prices_stored += OddsOutcome::ALL.len();
+                }
+                OddsEntry::Delta { changes, .. } => {
+                    stats.deltas += 1;
+                    stats.prices_stored += changes.len();
+                }
+            }
+        }
+        stats.prices_uncompressed = self.entries.len() * OddsOutcome::ALL.len();
+        stats
+    }
+}
+
+impl Default for OddsTimeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_reconstructs_point_in_time_snapshots() {
+        let start = Utc::now();
+        let at = |seconds: i64| start + Duration::seconds(seconds);
+        let mut timeline = OddsTimeline::with_keyframe_interval(2);
+
+        let quotes = [
+            SimpleMarketOdds::new(dec!(2.10), dec!(3.40), dec!(3.60)),
+            SimpleMarketOdds::new(dec!(2.05), dec!(3.40), dec!(3.70)),
+            SimpleMarketOdds::new(dec!(2.05), dec!(3.40), dec!(3.70)),
+            SimpleMarketOdds::new(dec!(2.00), dec!(3.50), dec!(3.80)),
+            SimpleMarketOdds::new(dec!(1.95), dec!(3.50), dec!(3.90)),
+        ];
+        let stored: Vec<bool> = quotes.iter()
+            .enumerate()
+            .map(|(i, odds)| timeline.push(at(i as i64 * 10), odds))
+            .collect();
+        // The unchanged third quote is not stored
+        assert_eq!(stored, [true, true, false, true, true]);
+
+        // Keyframe, delta, delta, then a fresh keyframe
+        let stats = timeline.stats();
+        assert_eq!(stats.keyframes, 2);
+        assert_eq!(stats.deltas, 2);
+        assert_eq!(stats.prices_stored, 3 + 2 + 3 + 3);
+        assert_eq!(stats.prices_uncompressed, 12);
+
+        assert_eq!(timeline.at(at(-1)), None);
+        assert_eq!(timeline.at(at(0)), Some(quotes[0].clone()));
+        assert_eq!(timeline.quote_at(at(25)), Some((at(10), quotes[1].clone())));
+        assert_eq!(timeline.at(at(30)), Some(quotes[3].clone()));
+        assert_eq!(timeline.at(at(1000)), Some(quotes[4].clone()));
+
+        // Out-of-order quotes are rejected
+        assert!(!timeline.push(at(5), &quotes[0]));
+
+        // Round-trips through serde and keeps accepting updates
+        let mut restored: OddsTimeline = serde_json::from_str(&serde_json::to_string(&timeline).unwrap()).unwrap();
+        assert_eq!(restored.latest(), Some(quotes[4].clone()));
+        assert!(!restored.push(at(50), &quotes[4]));
+        assert!(restored.push(at(60), &quotes[0]));
+        assert_eq!(restored.at(at(45)), Some(quotes[4].clone()));
+    }
+}
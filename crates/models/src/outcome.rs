@@ -0,0 +1,96 @@
+// The three results of a match, shared by predictions, bets, settlement and
+// the models, so none of them keeps its own home/draw/away enum
+
+use crate::betting::BetType;
+use crate::error::QuantsError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Outcome1X2 {
+    HomeWin,
+    Draw,
+    AwayWin,
+}
+
+impl Outcome1X2 {
+    /// In the order probability and price triples are kept
+    pub const ALL: [Outcome1X2; 3] = [Self::HomeWin, Self::Draw, Self::AwayWin];
+
+    pub fn from_score<T: Ord>(home_goals: T, away_goals: T) -> Self {
+        match home_goals.cmp(&away_goals) {
+            std::cmp::Ordering::Greater => Self::HomeWin,
+            std::cmp::Ordering::Equal => Self::Draw,
+            std::cmp::Ordering::Less => Self::AwayWin,
+        }
+    }
+
+    /// Position in a `[home, draw, away]` triple
+    pub fn index(&self) -> usize {
+        match self {
+            Self::HomeWin => 0,
+            Self::Draw => 1,
+            Self::AwayWin => 2,
+        }
+    }
+
+    /// This outcome's entry of a `[home, draw, away]` triple
+    pub fn select<T>(&self, [home, draw, away]: [T; 3]) -> T {
+        match self {
+            Self::HomeWin => home,
+            Self::Draw => draw,
+            Self::AwayWin => away,
+        }
+    }
+
+    /// Snake-case name, as stored
+    pub fn as_str(&self) -> &'static str {
+        self.select(["home_win", "draw", "away_win"])
+    }
+}
+
+impl fmt::Display for Outcome1X2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.select(["Home Win", "Draw", "Away Win"]))
+    }
+}
+
+impl From<Outcome1X2> for BetType {
+    fn from(outcome: Outcome1X2) -> Self {
+        outcome.select([BetType::HomeWin, BetType::Draw, BetType::AwayWin])
+    }
+}
+
+/// Only the match winner selections are a result of the match
+impl TryFrom<&BetType> for Outcome1X2 {
+    type Error = QuantsError;
+
+    fn try_from(bet_type: &BetType) -> Result<Self, Self::Error> {
+        match bet_type {
+            BetType::HomeWin => Ok(Self::HomeWin),
+            BetType::Draw => Ok(Self::Draw),
+            BetType::AwayWin => Ok(Self::AwayWin),
+            other => Err(QuantsError::NotMatchResult(format!("{:?}", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome_conversions_round_trip() {
+        assert_eq!(Outcome1X2::from_score(2u8, 1u8), Outcome1X2::HomeWin);
+        assert_eq!(Outcome1X2::from_score(1i32, 1i32), Outcome1X2::Draw);
+        for outcome in Outcome1X2::ALL {
+            assert_eq!(Outcome1X2::try_from(&BetType::from(outcome)).unwrap(), outcome);
+            assert_eq!(outcome.select([0, 1, 2]), outcome.index());
+        }
+        assert_eq!(Outcome1X2::AwayWin.as_str(), "away_win");
+        assert_eq!(Outcome1X2::HomeWin.to_string(), "Home Win");
+        assert!(Outcome1X2::try_from(&BetType::BothTeamsToScore { yes: true }).is_err());
+        // Stored under the names the separate prediction outcome had
+        assert_eq!(serde_json::to_string(&Outcome1X2::Draw).unwrap(), "\"Draw\"");
+    }
+}
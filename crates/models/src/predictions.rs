@@ -2,8 +2,13 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use crate::error::{QuantsError, Result};
+use crate::market::SimpleMarketOdds;
+use crate::outcome::Outcome1X2;
 
+/// Also reads the older shape that held the outcome probabilities in a
+/// `probabilities` map under a single `timestamp`
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(try_from = "crate::legacy::PredictionWire")]
 pub struct Prediction {
     pub id: Uuid,
     pub match_id: String,
@@ -15,6 +20,8 @@ pub struct Prediction {
     pub confidence: f64,
     pub expected_goals_home: Option<f64>,
     pub expected_goals_away: Option<f64>,
+    /// Exact score probabilities, from models that predict goals
+    pub score_matrix: Option<ScoreMatrix>,
     pub features_used: Vec<String>,
     pub prediction_timestamp: DateTime<Utc>,
     pub match_timestamp: DateTime<Utc>,
@@ -43,6 +50,43 @@ pub struct FeatureVector {
     pub match_id: String,
     pub features: std::collections::HashMap<String, f64>,
     pub timestamp: DateTime<Utc>,
+    /// The feature set it was built for; `None` for vectors stored before
+    /// feature sets were versioned
+    #[serde(default)]
+    pub schema: Option<FeatureSchemaId>,
+}
+
+/// A named feature set at one version, e.g. `in_play@v1`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FeatureSchemaId {
+    pub name: String,
+    pub version: u32,
+}
+
+impl std::fmt::Display for FeatureSchemaId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@v{}", self.name, self.version)
+    }
+}
+
+/// The features a prediction was made from, as kept by the feature store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PredictionFeatures {
+    pub prediction_id: Uuid,
+    pub model_name: String,
+    pub model_version: String,
+    pub features: FeatureVector,
+}
+
+impl PredictionFeatures {
+    pub fn new(prediction: &Prediction, features: FeatureVector) -> Self {
+        Self {
+            prediction_id: prediction.id,
+            model_name: prediction.model_name.clone(),
+            model_version: prediction.model_version.clone(),
+            features,
+        }
+    }
 }
 
 impl Prediction {
@@ -77,6 +121,7 @@ impl Prediction {
             confidence: 0.0,
             expected_goals_home: None,
             expected_goals_away: None,
+            score_matrix: None,
             features_used: Vec::new(),
             prediction_timestamp: Utc::now(),
             match_timestamp,
@@ -112,26 +157,52 @@ impl Prediction {
         self
     }
     
+    pub fn with_score_matrix(mut self, score_matrix: ScoreMatrix) -> Self {
+        self.score_matrix = Some(score_matrix);
+        self
+    }
+    
     pub fn with_features(mut self, features: Vec<String>) -> Self {
         self.features_used = features;
         self
     }
     
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = metadata;
+        self
+    }
+    
+    /// Prices implied by the probabilities with a bookmaker's `margin` added
+    /// (0.0 for fair odds). A two-way prediction's draw price is a placeholder.
+    pub fn to_fair_odds(&self, margin: f64) -> SimpleMarketOdds {
+        SimpleMarketOdds::from_probabilities(
+            self.home_win_prob,
+            self.draw_prob.unwrap_or(0.0),
+            self.away_win_prob,
+            margin,
+        )
+    }
+
+    /// Probability of an outcome; a two-way prediction gives a draw none
+    pub fn probability(&self, outcome: &Outcome1X2) -> f64 {
+        outcome.select([self.home_win_prob, self.draw_prob.unwrap_or(0.0), self.away_win_prob])
+    }
+    
     pub fn is_confident(&self, threshold: f64) -> bool {
         self.confidence >= threshold
     }
     
-    pub fn most_likely_outcome(&self) -> PredictedOutcome {
+    pub fn most_likely_outcome(&self) -> Outcome1X2 {
         let home_prob = self.home_win_prob;
         let away_prob = self.away_win_prob;
         let draw_prob = self.draw_prob.unwrap_or(0.0);
         
         if home_prob >= away_prob && home_prob >= draw_prob {
-            PredictedOutcome::HomeWin
+            Outcome1X2::HomeWin
         } else if away_prob >= draw_prob {
-            PredictedOutcome::AwayWin
+            Outcome1X2::AwayWin
         } else {
-            PredictedOutcome::Draw
+            Outcome1X2::Draw
         }
     }
     
@@ -154,11 +225,46 @@ impl Prediction {
     }
 }
 
+/// Probability of each exact score up to `max_goals` a side. Whatever is
+/// left over belongs to scores outside the grid.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum PredictedOutcome {
-    HomeWin,
-    Draw,
-    AwayWin,
+pub struct ScoreMatrix {
+    pub max_goals: u8,
+    /// Indexed by home goals, then away goals
+    pub probabilities: Vec<Vec<f64>>,
+}
+
+impl ScoreMatrix {
+    pub fn from_fn(max_goals: u8, probability: impl Fn(u8, u8) -> f64) -> Self {
+        let probabilities = (0..=max_goals)
+            .map(|home| (0..=max_goals).map(|away| probability(home, away)).collect())
+            .collect();
+        Self { max_goals, probabilities }
+    }
+
+    pub fn probability(&self, home_goals: u8, away_goals: u8) -> f64 {
+        self.probabilities
+            .get(home_goals as usize)
+            .and_then(|row| row.get(away_goals as usize))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Every score in the grid with its probability
+    pub fn scores(&self) -> impl Iterator<Item = (u8, u8, f64)> + '_ {
+        self.probabilities.iter().enumerate().flat_map(|(home, row)| {
+            row.iter().enumerate().map(move |(away, p)| (home as u8, away as u8, *p))
+        })
+    }
+
+    /// Probability of a score outside the grid
+    pub fn other(&self) -> f64 {
+        (1.0 - self.scores().map(|(_, _, p)| p).sum::<f64>()).max(0.0)
+    }
+
+    pub fn most_likely(&self) -> Option<(u8, u8, f64)> {
+        self.scores().max_by(|a, b| a.2.total_cmp(&b.2))
+    }
 }
 
 impl ModelPerformance {
@@ -208,6 +314,7 @@ impl ModelPerformance {
 mod tests {
     use super::*;
     use chrono::{Duration, Utc};
+    use rust_decimal_macros::dec;
 
     #[test]
     fn test_prediction_creation() {
@@ -225,7 +332,17 @@ mod tests {
         assert_eq!(prediction.home_win_prob, 0.6);
         assert_eq!(prediction.away_win_prob, 0.3);
         assert_eq!(prediction.draw_prob, Some(0.1));
-        assert_eq!(prediction.most_likely_outcome(), PredictedOutcome::HomeWin);
+        assert_eq!(prediction.most_likely_outcome(), Outcome1X2::HomeWin);
+    }
+    
+    #[test]
+    fn test_score_matrix() {
+        let matrix = ScoreMatrix::from_fn(2, |home, away| if home == 1 && away == 0 { 0.4 } else { 0.05 });
+        assert_eq!(matrix.probability(1, 0), 0.4);
+        assert_eq!(matrix.probability(3, 0), 0.0);
+        assert_eq!(matrix.scores().count(), 9);
+        assert_eq!(matrix.most_likely(), Some((1, 0, 0.4)));
+        assert!((matrix.other() - 0.2).abs() < 1e-9);
     }
     
     #[test]
@@ -282,6 +399,22 @@ mod tests {
         assert!(certain_prediction.entropy() < uncertain_prediction.entropy());
     }
     
+    #[test]
+    fn test_fair_odds() {
+        let prediction = Prediction::new(
+            "match_123".to_string(),
+            "LogisticRegression".to_string(),
+            "v1.0".to_string(),
+            0.5,
+            0.25,
+            Utc::now(),
+        ).unwrap().with_draw_prob(0.25).unwrap();
+        
+        let fair = prediction.to_fair_odds(0.0);
+        assert_eq!((fair.home_win, fair.draw, fair.away_win), (dec!(2), dec!(4), dec!(4)));
+        assert!((prediction.to_fair_odds(0.05).overround() - 0.05).abs() < 1e-9);
+    }
+    
     #[test]
     fn test_model_performance() {
         let mut performance = ModelPerformance::new(
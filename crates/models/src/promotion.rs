@@ -0,0 +1,154 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::betting::BetType;
+use crate::error::{QuantsError, Result};
+
+/// A bookmaker offer that changes the economics of a bet
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Promotion {
+    pub id: Uuid,
+    pub bookmaker: String,
+    /// Restrict the offer to one match; `None` means any match
+    pub match_id: Option<String>,
+    /// Restrict the offer to one outcome; `None` means any outcome
+    pub bet_type: Option<BetType>,
+    pub kind: PromotionKind,
+    pub min_odds: Option<Decimal>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PromotionKind {
+    /// Winnings are increased by `boost_percent` (e.g. 0.25 pays 25% more
+    /// profit), up to `max_stake`
+    OddsBoost { boost_percent: f64, max_stake: Option<Decimal> },
+    /// A stake-not-returned free bet: a win pays only the profit
+    FreeBet { stake: Decimal },
+}
+
+impl Promotion {
+    pub fn new(
+        bookmaker: String,
+        kind: PromotionKind,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Self> {
+        match &kind {
+            PromotionKind::OddsBoost { boost_percent, .. } if *boost_percent <= 0.0 => {
+                return Err(QuantsError::InvalidPromotion(
+                    format!("Boost must be positive, got {}", boost_percent)
+                ));
+            }
+            PromotionKind::FreeBet { stake } if *stake <= Decimal::ZERO => {
+                return Err(QuantsError::InvalidStake { amount: stake.to_string() });
+            }
+            _ => {}
+        }
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            bookmaker,
+            match_id: None,
+            bet_type: None,
+            kind,
+            min_odds: None,
+            expires_at,
+            created_at: Utc::now(),
+        })
+    }
+
+    pub fn for_match(mut self, match_id: String) -> Self {
+        self.match_id = Some(match_id);
+        self
+    }
+
+    pub fn for_bet_type(mut self, bet_type: BetType) -> Self {
+        self.bet_type = Some(bet_type);
+        self
+    }
+
+    pub fn with_min_odds(mut self, min_odds: Decimal) -> Self {
+        self.min_odds = Some(min_odds);
+        self
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Whether the offer can be used on this bet at `odds` right now
+    pub fn applies_to(&self, match_id: &str, bet_type: &BetType, bookmaker: &str, odds: Decimal) -> bool {
+        !self.is_expired(Utc::now())
+            && self.bookmaker.eq_ignore_ascii_case(bookmaker)
+            && self.match_id.as_deref().is_none_or(|id| id == match_id)
+            && self.bet_type.as_ref().is_none_or(|bt| bt == bet_type)
+            && self.min_odds.is_none_or(|min| odds >= min)
+    }
+
+    pub fn is_free_bet(&self) -> bool {
+        matches!(self.kind, PromotionKind::FreeBet { .. })
+    }
+
+    /// Price after an odds boost; unchanged for other promotions
+    pub fn boosted_odds(&self, odds: Decimal) -> Decimal {
+        match &self.kind {
+            PromotionKind::OddsBoost { boost_percent, .. } => {
+                let boost = Decimal::from_f64(*boost_percent).unwrap_or(Decimal::ZERO);
+                (Decimal::ONE + (odds - Decimal::ONE) * (Decimal::ONE + boost)).round_dp(2)
+            }
+            PromotionKind::FreeBet { .. } => odds,
+        }
+    }
+
+    /// Largest stake the promotion covers, if it is capped
+    pub fn max_stake(&self) -> Option<Decimal> {
+        match &self.kind {
+            PromotionKind::OddsBoost { max_stake, .. } => *max_stake,
+            PromotionKind::FreeBet { stake } => Some(*stake),
+        }
+    }
+}
+
+/// Expected value of a stake-not-returned free bet: only the profit is paid
+/// out, and nothing is lost if it loses, so EV = p * (odds - 1) * stake.
+pub fn free_bet_expected_value(true_probability: f64, odds: Decimal, stake: Decimal) -> f64 {
+    let odds = odds.to_f64().unwrap_or(1.0);
+    let stake = stake.to_f64().unwrap_or(0.0);
+    true_probability * (odds - 1.0) * stake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_boost_and_free_bet_math() {
+        let boost = Promotion::new(
+            "Bet365".to_string(),
+            PromotionKind::OddsBoost { boost_percent: 0.25, max_stake: Some(dec!(25)) },
+            Utc::now() + Duration::hours(1),
+        ).unwrap().for_bet_type(BetType::HomeWin);
+
+        // 25% more profit: 2.00 pays like 2.25
+        assert_eq!(boost.boosted_odds(dec!(2.00)), dec!(2.25));
+        assert!(boost.applies_to("m1", &BetType::HomeWin, "bet365", dec!(2.00)));
+        assert!(!boost.applies_to("m1", &BetType::Draw, "bet365", dec!(3.00)));
+        assert!(!boost.applies_to("m1", &BetType::HomeWin, "Pinnacle", dec!(2.00)));
+
+        // A 10 unit SNR free bet at 3.0 with a 40% chance is worth 8, not 12
+        assert!((free_bet_expected_value(0.4, dec!(3.0), dec!(10)) - 8.0).abs() < 1e-9);
+
+        let expired = Promotion::new(
+            "Bet365".to_string(),
+            PromotionKind::FreeBet { stake: dec!(10) },
+            Utc::now() - Duration::minutes(1),
+        ).unwrap();
+        assert!(!expired.applies_to("m1", &BetType::HomeWin, "Bet365", dec!(2.0)));
+    }
+}
@@ -0,0 +1,118 @@
+// Timings of calls out to the database and Redis. Every call is logged at
+// debug, inside whatever request span made it; slow ones are also kept for
+// the monitor.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+pub const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(100);
+/// Slow calls kept for the monitor; older ones are only counted
+const DEFAULT_CAPACITY: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlowQuery {
+    /// `postgres` or `redis`
+    pub backend: String,
+    pub operation: String,
+    pub duration_ms: f64,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct QueryLog {
+    threshold: Duration,
+    capacity: usize,
+    slow: Mutex<VecDeque<SlowQuery>>,
+    slow_total: AtomicU64,
+}
+
+impl QueryLog {
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            capacity: DEFAULT_CAPACITY,
+            slow: Mutex::new(VecDeque::new()),
+            slow_total: AtomicU64::new(0),
+        }
+    }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    /// Await `call`, recording how long it took under `operation`
+    pub async fn time<F: Future>(&self, backend: &'static str, operation: &'static str, call: F) -> F::Output {
+        let started = Instant::now();
+        let output = call.await;
+        self.record(backend, operation, started.elapsed());
+        output
+    }
+
+    pub fn record(&self, backend: &str, operation: &str, elapsed: Duration) {
+        let duration_ms = elapsed.as_secs_f64() * 1000.0;
+        debug!(backend, operation, duration_ms, "⏱️ Downstream call");
+        if elapsed < self.threshold {
+            return;
+        }
+
+        warn!("🐢 Slow {} call {}: {:.1}ms (threshold {}ms)",
+              backend, operation, duration_ms, self.threshold.as_millis());
+        self.slow_total.fetch_add(1, Ordering::Relaxed);
+        let mut slow = self.slow.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        slow.push_back(SlowQuery {
+            backend: backend.to_string(),
+            operation: operation.to_string(),
+            duration_ms,
+            at: Utc::now(),
+        });
+        while slow.len() > self.capacity {
+            slow.pop_front();
+        }
+    }
+
+    /// Slow calls still held, most recent first
+    pub fn slow_queries(&self) -> Vec<SlowQuery> {
+        let slow = self.slow.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        slow.iter().rev().cloned().collect()
+    }
+
+    /// Slow calls since startup, including those no longer held
+    pub fn slow_query_count(&self) -> u64 {
+        self.slow_total.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for QueryLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_SLOW_QUERY_THRESHOLD)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_slow_calls_kept() {
+        let log = QueryLog::new(Duration::from_millis(50)).with_capacity(2);
+        log.record("postgres", "get_match", Duration::from_millis(5));
+        for operation in ["create_bet", "get_active_bets", "prune_batch"] {
+            log.record("postgres", operation, Duration::from_millis(80));
+        }
+
+        assert_eq!(log.slow_query_count(), 3);
+        let operations: Vec<_> = log.slow_queries().into_iter().map(|query| query.operation).collect();
+        assert_eq!(operations, ["prune_batch", "get_active_bets"]);
+    }
+}
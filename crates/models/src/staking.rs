@@ -0,0 +1,178 @@
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// What a staking plan sizes a bet from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StakeContext {
+    pub bankroll: Decimal,
+    /// Full Kelly fraction of the bet at the price net of commission
+    pub kelly_fraction: f64,
+    /// Losses the strategy has had in a row since its last win
+    pub losing_streak: usize,
+}
+
+impl StakeContext {
+    pub fn new(bankroll: Decimal, kelly_fraction: f64) -> Self {
+        Self { bankroll, kelly_fraction, losing_streak: 0 }
+    }
+
+    pub fn with_losing_streak(mut self, losing_streak: usize) -> Self {
+        self.losing_streak = losing_streak;
+        self
+    }
+}
+
+/// How much a strategy stakes on a bet it has decided to make. The
+/// strategy's `max_stake_percent` still caps whatever the plan asks for.
+pub trait StakingPlan: fmt::Debug + Send + Sync {
+    /// Short name shown alongside the strategy, e.g. `flat`
+    fn name(&self) -> &str;
+
+    fn stake(&self, context: &StakeContext) -> Decimal;
+}
+
+/// A multiple of the full Kelly stake
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FractionalKelly {
+    pub multiplier: f64,
+}
+
+impl StakingPlan for FractionalKelly {
+    fn name(&self) -> &str {
+        "kelly"
+    }
+
+    fn stake(&self, context: &StakeContext) -> Decimal {
+        let stake = context.bankroll.to_f64().unwrap_or(0.0) * context.kelly_fraction * self.multiplier;
+        Decimal::from_f64_retain(stake).unwrap_or(Decimal::ZERO)
+    }
+}
+
+/// The same amount on every bet, or the whole bankroll if that is less
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlatStake {
+    pub amount: Decimal,
+}
+
+impl StakingPlan for FlatStake {
+    fn name(&self) -> &str {
+        "flat"
+    }
+
+    fn stake(&self, context: &StakeContext) -> Decimal {
+        self.amount.min(context.bankroll)
+    }
+}
+
+/// A fixed share of the bankroll on every bet, e.g. 0.01 for 1%
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PercentageOfBankroll {
+    pub fraction: f64,
+}
+
+impl StakingPlan for PercentageOfBankroll {
+    fn name(&self) -> &str {
+        "percentage"
+    }
+
+    fn stake(&self, context: &StakeContext) -> Decimal {
+        context.bankroll * Decimal::from_f64(self.fraction).unwrap_or(Decimal::ZERO)
+    }
+}
+
+/// `unit` times the Fibonacci number for the losing streak: one step up
+/// the sequence (1, 1, 2, 3, 5, ...) after each loss, back to the start
+/// after a win
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FibonacciProgression {
+    pub unit: Decimal,
+    /// Steps the progression climbs at most, so a long streak can't run the stake away
+    pub max_steps: usize,
+}
+
+impl StakingPlan for FibonacciProgression {
+    fn name(&self) -> &str {
+        "fibonacci"
+    }
+
+    fn stake(&self, context: &StakeContext) -> Decimal {
+        let (mut current, mut next) = (Decimal::ONE, Decimal::ONE);
+        for _ in 0..context.losing_streak.min(self.max_steps) {
+            (current, next) = (next, current + next);
+        }
+        (self.unit * current).min(context.bankroll)
+    }
+}
+
+fn default_max_steps() -> usize {
+    10
+}
+
+/// A staking plan as written in config, e.g.
+/// `trading.staking.moderate = { plan = "flat", amount = "25" }`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "plan", rename_all = "snake_case")]
+pub enum StakingConfig {
+    /// Fractional Kelly; the strategy's own `kelly_multiplier` when unset
+    Kelly {
+        #[serde(default)]
+        multiplier: Option<f64>,
+    },
+    Flat { amount: Decimal },
+    Percentage { fraction: f64 },
+    Fibonacci {
+        unit: Decimal,
+        #[serde(default = "default_max_steps")]
+        max_steps: usize,
+    },
+}
+
+impl StakingConfig {
+    /// The plan, with `kelly_multiplier` filling in an unset Kelly multiplier
+    pub fn plan(&self, kelly_multiplier: f64) -> Arc<dyn StakingPlan> {
+        match self {
+            Self::Kelly { multiplier } => Arc::new(FractionalKelly { multiplier: multiplier.unwrap_or(kelly_multiplier) }),
+            Self::Flat { amount } => Arc::new(FlatStake { amount: *amount }),
+            Self::Percentage { fraction } => Arc::new(PercentageOfBankroll { fraction: *fraction }),
+            Self::Fibonacci { unit, max_steps } => Arc::new(FibonacciProgression { unit: *unit, max_steps: *max_steps }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_fibonacci_climbs_with_the_losing_streak() {
+        let plan = FibonacciProgression { unit: dec!(10), max_steps: 4 };
+        let stakes: Vec<Decimal> = (0..7)
+            .map(|streak| plan.stake(&StakeContext::new(dec!(1000), 0.1).with_losing_streak(streak)))
+            .collect();
+        assert_eq!(stakes, vec![dec!(10), dec!(10), dec!(20), dec!(30), dec!(50), dec!(50), dec!(50)]);
+
+        // Never more than the bankroll
+        assert_eq!(plan.stake(&StakeContext::new(dec!(25), 0.1).with_losing_streak(4)), dec!(25));
+    }
+
+    #[test]
+    fn test_plans_from_config() {
+        let context = StakeContext::new(dec!(1000), 0.2);
+        let kelly: StakingConfig = serde_json::from_str(r#"{"plan":"kelly"}"#).unwrap();
+        assert_eq!(kelly.plan(0.5).stake(&context), dec!(100));
+
+        let flat: StakingConfig = serde_json::from_str(r#"{"plan":"flat","amount":"25"}"#).unwrap();
+        assert_eq!(flat.plan(0.5).name(), "flat");
+        assert_eq!(flat.plan(0.5).stake(&context), dec!(25));
+
+        let percentage: StakingConfig = serde_json::from_str(r#"{"plan":"percentage","fraction":0.01}"#).unwrap();
+        assert_eq!(percentage.plan(0.5).stake(&context), dec!(10));
+
+        let fibonacci: StakingConfig = serde_json::from_str(r#"{"plan":"fibonacci","unit":"5"}"#).unwrap();
+        assert_eq!(fibonacci, StakingConfig::Fibonacci { unit: dec!(5), max_steps: 10 });
+    }
+}
@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::betting::BettingDecision;
+use crate::events::{CardType, Score};
+use crate::market::SimpleMarketOdds;
+use crate::outcome::Outcome1X2;
+use crate::predictions::Prediction;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GoalRecord {
+    pub minute: u8,
+    pub team: String,
+    pub player: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CardRecord {
+    pub minute: u8,
+    pub team: String,
+    pub player: String,
+    pub card_type: CardType,
+}
+
+/// How the kick-off prediction held up against the result
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PredictionReview {
+    pub model_name: String,
+    pub model_version: String,
+    pub home_win_prob: f64,
+    pub draw_prob: Option<f64>,
+    pub away_win_prob: f64,
+    pub predicted: Outcome1X2,
+    pub correct: bool,
+    pub brier_score: f64,
+    pub log_loss: f64,
+}
+
+impl PredictionReview {
+    pub fn new(prediction: &Prediction, outcome: &Outcome1X2) -> Self {
+        let probabilities = [
+            (Outcome1X2::HomeWin, prediction.home_win_prob),
+            (Outcome1X2::Draw, prediction.draw_prob.unwrap_or(0.0)),
+            (Outcome1X2::AwayWin, prediction.away_win_prob),
+        ];
+        let brier_score = probabilities.iter()
+            .map(|(o, p)| {
+                let observed = if o == outcome { 1.0 } else { 0.0 };
+                (p - observed).powi(2)
+            })
+            .sum();
+        let actual = probabilities.iter()
+            .find(|(o, _)| o == outcome)
+            .map_or(0.0, |(_, p)| *p);
+        let predicted = prediction.most_likely_outcome();
+
+        Self {
+            model_name: prediction.model_name.clone(),
+            model_version: prediction.model_version.clone(),
+            home_win_prob: prediction.home_win_prob,
+            draw_prob: prediction.draw_prob,
+            away_win_prob: prediction.away_win_prob,
+            correct: &predicted == outcome,
+            predicted,
+            brier_score,
+            log_loss: -actual.max(1e-15).ln(),
+        }
+    }
+}
+
+/// Everything worth reviewing about a finished match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchSummary {
+    pub match_id: String,
+    pub team_home: String,
+    pub team_away: String,
+    pub league: String,
+    pub season: String,
+    pub kickoff: Option<DateTime<Utc>>,
+    pub final_score: Score,
+    pub outcome: Outcome1X2,
+    pub goals: Vec<GoalRecord>,
+    pub cards: Vec<CardRecord>,
+    /// The model's expected goals at kick-off
+    pub expected_goals_home: Option<f64>,
+    pub expected_goals_away: Option<f64>,
+    /// Feed prices at kick-off
+    pub closing_odds: Option<SimpleMarketOdds>,
+    pub prediction: Option<PredictionReview>,
+    pub bets: Vec<BettingDecision>,
+    /// Across settled bets on the match
+    pub profit_loss: Decimal,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl MatchSummary {
+    pub fn outcome_of(score: &Score) -> Outcome1X2 {
+        Outcome1X2::from_score(score.home, score.away)
+    }
+}
@@ -0,0 +1,298 @@
+// Team name canonicalization and fuzzy matching
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Short and alternative names mapped to the names the live feed uses
+const TEAM_ALIASES: [(&str, &str); 24] = [
+    ("Man United", "Manchester United"),
+    ("Man Utd", "Manchester United"),
+    ("Man City", "Manchester City"),
+    ("Nott'm Forest", "Nottingham Forest"),
+    ("Newcastle", "Newcastle United"),
+    ("Wolves", "Wolverhampton Wanderers"),
+    ("Tottenham", "Tottenham Hotspur"),
+    ("Spurs", "Tottenham Hotspur"),
+    ("West Ham", "West Ham United"),
+    ("Brighton", "Brighton & Hove Albion"),
+    ("Leicester", "Leicester City"),
+    ("Leeds", "Leeds United"),
+    ("Norwich", "Norwich City"),
+    ("West Brom", "West Bromwich Albion"),
+    ("Sheffield Weds", "Sheffield Wednesday"),
+    ("QPR", "Queens Park Rangers"),
+    ("Ath Madrid", "Atletico Madrid"),
+    ("Atletico de Madrid", "Atletico Madrid"),
+    ("Ath Bilbao", "Athletic Bilbao"),
+    ("Sociedad", "Real Sociedad"),
+    ("Bayern Munich", "Bayern Munchen"),
+    ("FC Bayern", "Bayern Munchen"),
+    ("Inter", "Inter Milan"),
+    ("PSG", "Paris Saint-Germain"),
+];
+
+/// Club-type affixes that say nothing about which club is meant
+const NOISE_WORDS: [&str; 8] = ["fc", "afc", "cf", "sc", "sv", "ssc", "club", "calcio"];
+
+/// Minimum similarity for `resolve` to accept a fuzzy match by default
+pub const DEFAULT_MATCH_THRESHOLD: f64 = 0.88;
+
+/// A candidate name scored against a query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeamNameMatch {
+    pub name: String,
+    /// 0 (nothing alike) to 1 (same team)
+    pub score: f64,
+}
+
+/// Maps team name variants onto one canonical spelling. Lookups ignore
+/// case, spacing and accents, so "Atlético  Madrid" finds "Atletico Madrid".
+#[derive(Debug, Clone)]
+pub struct TeamNameNormalizer {
+    aliases: HashMap<String, String>,
+}
+
+impl TeamNameNormalizer {
+    pub fn new() -> Self {
+        let mut normalizer = Self { aliases: HashMap::new() };
+        for (alias, canonical) in TEAM_ALIASES {
+            normalizer = normalizer.with_alias(alias, canonical);
+        }
+        normalizer
+    }
+
+    /// Also registers the canonical name, so accented or oddly cased
+    /// spellings of it resolve to this exact spelling
+    pub fn with_alias(mut self, alias: &str, canonical: &str) -> Self {
+        self.aliases.insert(alias_key(alias), canonical.to_string());
+        self.aliases.entry(alias_key(canonical)).or_insert_with(|| canonical.to_string());
+        self
+    }
+
+    /// The canonical name for a known alias, otherwise the name with its
+    /// whitespace tidied
+    pub fn normalize(&self, name: &str) -> String {
+        let cleaned = collapse_whitespace(name);
+        self.aliases
+            .get(&alias_key(&cleaned))
+            .cloned()
+            .unwrap_or(cleaned)
+    }
+
+    /// Similarity of two names after resolving aliases
+    pub fn similarity(&self, a: &str, b: &str) -> f64 {
+        similarity(&self.normalize(a), &self.normalize(b))
+    }
+
+    /// The known name `name` most likely refers to, if any scores at least
+    /// `threshold`
+    pub fn resolve<'a, I>(&self, name: &str, known: I, threshold: f64) -> Option<TeamNameMatch>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let name = self.normalize(name);
+        known.into_iter()
+            .map(|candidate| TeamNameMatch {
+                name: candidate.to_string(),
+                score: similarity(&name, &self.normalize(candidate)),
+            })
+            .filter(|candidate| candidate.score >= threshold)
+            .max_by(|a, b| a.score.total_cmp(&b.score))
+    }
+
+    /// Candidates ranked for a search box: names containing the query come
+    /// first, then the closest spellings. Scores below `min_score` are dropped.
+    pub fn search<'a, I>(&self, query: &str, candidates: I, min_score: f64) -> Vec<TeamNameMatch>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let query_key = match_key(&self.normalize(query));
+        if query_key.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<TeamNameMatch> = candidates.into_iter()
+            .map(|candidate| {
+                let key = match_key(&self.normalize(candidate));
+                let score = if key.contains(&query_key) {
+                    // Between 0.9 and 1, higher the more of the name the query covers
+                    0.9 + 0.1 * query_key.chars().count() as f64 / key.chars().count() as f64
+                } else {
+                    jaro_winkler(&query_key, &key)
+                };
+                TeamNameMatch { name: candidate.to_string(), score }
+            })
+            .filter(|candidate| candidate.score >= min_score)
+            .collect();
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+        matches.dedup_by(|a, b| a.name == b.name);
+        matches
+    }
+}
+
+impl Default for TeamNameNormalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Replace accented Latin letters with their unaccented form, e.g.
+/// "Müller" becomes "Muller" and "Ørn" becomes "Orn"
+pub fn strip_diacritics(name: &str) -> String {
+    let mut stripped = String::with_capacity(name.len());
+    for c in name.chars() {
+        match fold_char(c) {
+            Some(folded) => stripped.push_str(folded),
+            None => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+/// Key two spellings of the same team share: accents, case, punctuation
+/// and affixes like "FC" removed
+pub fn match_key(name: &str) -> String {
+    let folded: String = strip_diacritics(name)
+        .to_lowercase()
+        .chars()
+        // "F.C." and "Nott'm" stay one word
+        .filter(|c| !matches!(c, '.' | '\''))
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+    folded.split_whitespace()
+        .filter(|word| !NOISE_WORDS.contains(word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Jaro-Winkler similarity of two names' match keys
+pub fn similarity(a: &str, b: &str) -> f64 {
+    jaro_winkler(&match_key(a), &match_key(b))
+}
+
+fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut b_matched = vec![false; b.len()];
+    let mut a_matches = Vec::new();
+    for (i, &ca) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        if let Some(j) = (start..end).find(|&j| !b_matched[j] && b[j] == ca) {
+            b_matched[j] = true;
+            a_matches.push(ca);
+        }
+    }
+    if a_matches.is_empty() {
+        return 0.0;
+    }
+
+    let b_matches = b.iter().zip(&b_matched).filter(|(_, matched)| **matched).map(|(c, _)| *c);
+    let transpositions = a_matches.iter().zip(b_matches).filter(|(x, y)| **x != *y).count() / 2;
+    let m = a_matches.len() as f64;
+    let jaro = (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0;
+
+    let prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count() as f64;
+    jaro + prefix * 0.1 * (1.0 - jaro)
+}
+
+fn alias_key(name: &str) -> String {
+    strip_diacritics(&collapse_whitespace(name)).to_lowercase()
+}
+
+fn collapse_whitespace(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn fold_char(c: char) -> Option<&'static str> {
+    let folded = match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'æ' => "ae",
+        'Æ' => "AE",
+        'ç' | 'ć' | 'č' => "c",
+        'Ç' | 'Ć' | 'Č' => "C",
+        'ď' | 'đ' => "d",
+        'Ď' | 'Đ' => "D",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'ğ' => "g",
+        'Ğ' => "G",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'ı' => "i",
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'Ī' | 'İ' => "I",
+        'ł' | 'ľ' => "l",
+        'Ł' | 'Ľ' => "L",
+        'ñ' | 'ń' | 'ň' => "n",
+        'Ñ' | 'Ń' | 'Ň' => "N",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ő' => "O",
+        'œ' => "oe",
+        'Œ' => "OE",
+        'ř' => "r",
+        'Ř' => "R",
+        'ś' | 'š' | 'ş' | 'ș' => "s",
+        'Ś' | 'Š' | 'Ş' | 'Ș' => "S",
+        'ß' => "ss",
+        'ť' | 'ţ' | 'ț' => "t",
+        'Ť' | 'Ţ' | 'Ț' => "T",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => "u",
+        'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ū' | 'Ů' | 'Ű' => "U",
+        'ý' | 'ÿ' => "y",
+        'Ý' | 'Ÿ' => "Y",
+        'ź' | 'ż' | 'ž' => "z",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        _ => return None,
+    };
+    Some(folded)
+}
+
+/// A team's current Elo rating
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeamRating {
+    pub team: String,
+    pub rating: f64,
+    pub matches_played: u32,
+    /// Season of the team's latest rated match
+    pub season: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonicalization_and_fuzzy_matching() {
+        let normalizer = TeamNameNormalizer::new();
+        assert_eq!(normalizer.normalize("Man  City"), "Manchester City");
+        assert_eq!(normalizer.normalize("atlético madrid"), "Atletico Madrid");
+        assert_eq!(normalizer.normalize("Bayern München"), "Bayern Munchen");
+        assert_eq!(normalizer.normalize("Arsenal"), "Arsenal");
+
+        assert_eq!(strip_diacritics("Beşiktaş Øster"), "Besiktas Oster");
+        assert_eq!(match_key("Borussia Mönchengladbach F.C."), "borussia monchengladbach");
+        assert_eq!(similarity("Arsenal FC", "arsenal"), 1.0);
+        assert!(similarity("Arsenal", "Chelsea") < 0.7);
+
+        let known = ["Manchester City", "Manchester United", "Tottenham Hotspur"];
+        let resolved = normalizer.resolve("Man Utd", known, DEFAULT_MATCH_THRESHOLD).unwrap();
+        assert_eq!(resolved.name, "Manchester United");
+        let resolved = normalizer.resolve("Tottenham Hotspurs", known, DEFAULT_MATCH_THRESHOLD).unwrap();
+        assert_eq!(resolved.name, "Tottenham Hotspur");
+        assert!(normalizer.resolve("Everton", known, DEFAULT_MATCH_THRESHOLD).is_none());
+
+        let results = normalizer.search("manchester", known, 0.75);
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.name.starts_with("Manchester")));
+        assert_eq!(normalizer.search("Spurs", known, 0.75)[0].name, "Tottenham Hotspur");
+    }
+}
@@ -0,0 +1,368 @@
+// Alert rules over system health, and the channels alerts are sent through
+
+use crate::http::{self, HttpTransport};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+
+const SMTP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    ErrorRate,
+    Drawdown,
+    FeedSilent,
+    ModelDegraded,
+    /// Live features or predictions no longer look like the training set
+    Drift,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertSeverity {
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub severity: AlertSeverity,
+    pub message: String,
+    pub raised_at: DateTime<Utc>,
+}
+
+impl Alert {
+    fn new(kind: AlertKind, severity: AlertSeverity, message: String) -> Self {
+        Self { kind, severity, message, raised_at: Utc::now() }
+    }
+}
+
+/// When to alert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertRules {
+    /// Errors per event processed between two health checks
+    pub max_error_rate: f64,
+    /// Errors between two checks before their rate counts, so one error
+    /// in a quiet spell doesn't page anyone
+    pub min_errors: u64,
+    /// Drawdown from peak equity
+    pub max_drawdown: f64,
+    /// Minutes without a feed event
+    pub silence_minutes: i64,
+    /// Rolling Brier score of the model's best component. Always guessing
+    /// a third each way scores 0.667.
+    pub max_brier: f64,
+}
+
+impl Default for AlertRules {
+    fn default() -> Self {
+        Self {
+            max_error_rate: 0.05,
+            min_errors: 5,
+            max_drawdown: 0.1,
+            silence_minutes: 10,
+            max_brier: 0.65,
+        }
+    }
+}
+
+/// What a health check saw
+#[derive(Debug, Clone)]
+pub struct HealthSnapshot {
+    pub events_processed: u64,
+    pub error_count: u64,
+    /// When the feed last delivered an event, or monitoring started if it never has
+    pub last_event_at: DateTime<Utc>,
+    pub model_brier: Option<f64>,
+    /// Features and predicted probabilities past their drift thresholds
+    pub drifted: Vec<String>,
+    pub taken_at: DateTime<Utc>,
+}
+
+impl AlertRules {
+    /// Rules broken as of `current`, with rates measured since `previous`
+    pub fn evaluate(&self, previous: &HealthSnapshot, current: &HealthSnapshot) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+
+        let errors = current.error_count.saturating_sub(previous.error_count);
+        let events = current.events_processed.saturating_sub(previous.events_processed);
+        let error_rate = errors as f64 / events.max(1) as f64;
+        if errors >= self.min_errors && error_rate > self.max_error_rate {
+            alerts.push(Alert::new(AlertKind::ErrorRate, AlertSeverity::Critical, format!(
+                "{} errors over {} events ({:.1}%) since the last check", errors, events, error_rate * 100.0
+            )));
+        }
+
+        let silence = current.taken_at - current.last_event_at;
+        if silence >= Duration::minutes(self.silence_minutes) {
+            alerts.push(Alert::new(AlertKind::FeedSilent, AlertSeverity::Warning, format!(
+                "no feed events for {} minutes", silence.num_minutes()
+            )));
+        }
+
+        if let Some(brier) = current.model_brier.filter(|brier| *brier > self.max_brier) {
+            alerts.push(Alert::new(AlertKind::ModelDegraded, AlertSeverity::Warning, format!(
+                "model Brier score {:.3} is above {:.3}", brier, self.max_brier
+            )));
+        }
+
+        if !current.drifted.is_empty() {
+            alerts.push(Alert::new(AlertKind::Drift, AlertSeverity::Warning, format!(
+                "drifted from the training set: {}", current.drifted.join(", ")
+            )));
+        }
+
+        alerts
+    }
+
+    pub fn drawdown_alert(&self, drawdown: f64) -> Option<Alert> {
+        (drawdown >= self.max_drawdown).then(|| Alert::new(AlertKind::Drawdown, AlertSeverity::Critical, format!(
+            "equity is {:.1}% below its peak", drawdown * 100.0
+        )))
+    }
+}
+
+/// Somewhere alerts are delivered
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn send(&self, alert: &Alert) -> Result<()>;
+}
+
+/// POSTs each alert as JSON to an HTTPS endpoint
+pub struct WebhookChannel {
+    url: String,
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl WebhookChannel {
+    pub fn new(url: impl Into<String>, transport: Arc<dyn HttpTransport>) -> Self {
+        Self { url: url.into(), transport }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for WebhookChannel {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        post_json(self.transport.as_ref(), &self.url, serde_json::to_string(alert)?).await
+    }
+}
+
+/// Posts each alert to a Slack channel through an incoming webhook
+pub struct SlackChannel {
+    webhook_url: String,
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl SlackChannel {
+    pub fn new(webhook_url: impl Into<String>, transport: Arc<dyn HttpTransport>) -> Self {
+        Self { webhook_url: webhook_url.into(), transport }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for SlackChannel {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        let icon = match alert.severity {
+            AlertSeverity::Warning => ":warning:",
+            AlertSeverity::Critical => ":rotating_light:",
+        };
+        let text = format!("{} *{:?}*: {}", icon, alert.kind, alert.message);
+        post_json(self.transport.as_ref(), &self.webhook_url, serde_json::json!({ "text": text }).to_string()).await
+    }
+}
+
+async fn post_json(transport: &dyn HttpTransport, url: &str, body: String) -> Result<()> {
+    let response = transport.post(url, &[("Content-Type", "application/json")], body).await?;
+    if !(200..300).contains(&response.status) {
+        bail!("{} answered {}: {}", url, response.status, response.body);
+    }
+    Ok(())
+}
+
+/// Mails each alert through an SMTP relay that accepts mail without
+/// authentication or TLS, such as a local MTA
+pub struct EmailChannel {
+    smtp_host: String,
+    smtp_port: u16,
+    from: String,
+    to: Vec<String>,
+}
+
+impl EmailChannel {
+    pub fn new(smtp_host: impl Into<String>, smtp_port: u16, from: impl Into<String>, to: Vec<String>) -> Self {
+        Self { smtp_host: smtp_host.into(), smtp_port, from: from.into(), to }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for EmailChannel {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        let (host, port, from, to) = (self.smtp_host.clone(), self.smtp_port, self.from.clone(), self.to.clone());
+        let subject = format!("[{:?}] {:?}", alert.severity, alert.kind);
+        let body = format!("{}\r\n\r\nRaised at {}", alert.message, alert.raised_at.to_rfc3339());
+        tokio::task::spawn_blocking(move || send_mail(&host, port, &from, &to, &subject, &body)).await?
+    }
+}
+
+fn send_mail(host: &str, port: u16, from: &str, to: &[String], subject: &str, body: &str) -> Result<()> {
+    let stream = TcpStream::connect((host, port))?;
+    stream.set_read_timeout(Some(SMTP_TIMEOUT))?;
+    stream.set_write_timeout(Some(SMTP_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    smtp_reply(&mut reader, 220)?;
+    smtp_command(&mut writer, &mut reader, "EHLO quant-rs", 250)?;
+    smtp_command(&mut writer, &mut reader, &format!("MAIL FROM:<{}>", from), 250)?;
+    for recipient in to {
+        smtp_command(&mut writer, &mut reader, &format!("RCPT TO:<{}>", recipient), 250)?;
+    }
+    smtp_command(&mut writer, &mut reader, "DATA", 354)?;
+    // A line starting with a dot would end the message early, so double it
+    let body = body.lines()
+        .map(|line| if line.starts_with('.') { format!(".{}", line) } else { line.to_string() })
+        .collect::<Vec<_>>()
+        .join("\r\n");
+    let message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\n\r\n{}\r\n.",
+        from, to.join(", "), subject, Utc::now().to_rfc2822(), body
+    );
+    smtp_command(&mut writer, &mut reader, &message, 250)?;
+    smtp_command(&mut writer, &mut reader, "QUIT", 221)
+}
+
+fn smtp_command(writer: &mut TcpStream, reader: &mut BufReader<TcpStream>, command: &str, expected: u16) -> Result<()> {
+    writer.write_all(format!("{}\r\n", command).as_bytes())?;
+    writer.flush()?;
+    smtp_reply(reader, expected)
+}
+
+/// Read a possibly multi-line reply and check its code
+fn smtp_reply(reader: &mut BufReader<TcpStream>, expected: u16) -> Result<()> {
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            bail!("SMTP server closed the connection");
+        }
+        let code: u16 = line.get(..3).and_then(|code| code.parse().ok())
+            .ok_or_else(|| anyhow!("malformed SMTP reply: {}", line.trim_end()))?;
+        // `250-...` continues, `250 ...` is the last line
+        if line.as_bytes().get(3) == Some(&b'-') {
+            continue;
+        }
+        if code != expected {
+            bail!("SMTP server answered {}", line.trim_end());
+        }
+        return Ok(());
+    }
+}
+
+/// A notification channel as configured under `[[monitoring.channels]]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChannelConfig {
+    Webhook { url: String },
+    Slack { webhook_url: String },
+    Email {
+        smtp_host: String,
+        #[serde(default = "default_smtp_port")]
+        smtp_port: u16,
+        from: String,
+        to: Vec<String>,
+    },
+}
+
+fn default_smtp_port() -> u16 {
+    25
+}
+
+impl ChannelConfig {
+    pub fn build(&self) -> Box<dyn NotificationChannel> {
+        match self {
+            Self::Webhook { url } => Box::new(WebhookChannel::new(url, Arc::new(http::client()))),
+            Self::Slack { webhook_url } => Box::new(SlackChannel::new(webhook_url, Arc::new(http::client()))),
+            Self::Email { smtp_host, smtp_port, from, to } => {
+                Box::new(EmailChannel::new(smtp_host, *smtp_port, from, to.clone()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HttpResponse;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingTransport {
+        posts: Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl HttpTransport for RecordingTransport {
+        async fn post(&self, url: &str, _headers: &[(&str, &str)], body: String) -> Result<HttpResponse> {
+            self.posts.lock().unwrap().push((url.to_string(), body));
+            Ok(HttpResponse { status: 200, body: "ok".to_string() })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rules_raise_alerts_and_channels_deliver_them() {
+        let rules = AlertRules::default();
+        let now = Utc::now();
+        let previous = HealthSnapshot {
+            events_processed: 100,
+            error_count: 2,
+            last_event_at: now,
+            model_brier: None,
+            drifted: Vec::new(),
+            taken_at: now,
+        };
+        let healthy = HealthSnapshot { events_processed: 200, error_count: 4, ..previous.clone() };
+        assert!(rules.evaluate(&previous, &healthy).is_empty());
+
+        let failing = HealthSnapshot {
+            events_processed: 120,
+            error_count: 12,
+            last_event_at: now - Duration::minutes(15),
+            model_brier: Some(0.7),
+            drifted: vec!["elo_difference".to_string()],
+            taken_at: now,
+        };
+        let kinds: Vec<AlertKind> = rules.evaluate(&previous, &failing).iter().map(|alert| alert.kind).collect();
+        assert_eq!(kinds, [AlertKind::ErrorRate, AlertKind::FeedSilent, AlertKind::ModelDegraded, AlertKind::Drift]);
+        assert!(rules.drawdown_alert(0.05).is_none());
+        let drawdown = rules.drawdown_alert(0.12).unwrap();
+
+        let transport = Arc::new(RecordingTransport::default());
+        WebhookChannel::new("https://hooks.example.com/alerts", transport.clone()).send(&drawdown).await.unwrap();
+        SlackChannel::new("https://hooks.slack.com/services/T/B/X", transport.clone()).send(&drawdown).await.unwrap();
+        let posts = transport.posts.lock().unwrap();
+        let webhook: serde_json::Value = serde_json::from_str(&posts[0].1).unwrap();
+        assert_eq!(webhook["kind"], "drawdown");
+        assert_eq!(webhook["severity"], "critical");
+        assert!(posts[1].1.contains(":rotating_light: *Drawdown*: equity is 12.0% below its peak"), "{}", posts[1].1);
+    }
+}
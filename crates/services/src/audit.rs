@@ -0,0 +1,166 @@
+// Where the trader's audit trail is written: kept in memory by default, or
+// in the database's append-only trade_audit_log
+
+use anyhow::Result;
+use async_trait::async_trait;
+use quant_db::{Repository, TradeAuditRecord, TradeAuditRepository};
+use quant_models::AuditEntry;
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Entries kept by the in-memory log
+const DEFAULT_AUDIT_CAPACITY: usize = 10_000;
+
+#[async_trait]
+pub trait TradeAuditLog: Send + Sync {
+    async fn append(&self, entry: &AuditEntry) -> Result<()>;
+
+    /// Append an execution attempt, unless one with the same idempotency
+    /// key was already claimed; returns whether the caller may execute
+    async fn claim_execution(&self, entry: &AuditEntry) -> Result<bool>;
+}
+
+/// Keeps the most recent entries, and every claimed key for the life of
+/// the process
+pub struct MemoryAuditLog {
+    inner: Mutex<MemoryAudit>,
+    capacity: usize,
+}
+
+#[derive(Default)]
+struct MemoryAudit {
+    entries: VecDeque<AuditEntry>,
+    claimed: HashSet<String>,
+}
+
+impl MemoryAuditLog {
+    pub fn new() -> Self {
+        Self { inner: Mutex::new(MemoryAudit::default()), capacity: DEFAULT_AUDIT_CAPACITY }
+    }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Oldest first
+    pub async fn entries(&self) -> Vec<AuditEntry> {
+        self.inner.lock().await.entries.iter().cloned().collect()
+    }
+
+    fn push(&self, audit: &mut MemoryAudit, entry: &AuditEntry) {
+        if audit.entries.len() >= self.capacity {
+            audit.entries.pop_front();
+        }
+        audit.entries.push_back(entry.clone());
+    }
+}
+
+impl Default for MemoryAuditLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl TradeAuditLog for MemoryAuditLog {
+    async fn append(&self, entry: &AuditEntry) -> Result<()> {
+        let mut audit = self.inner.lock().await;
+        self.push(&mut audit, entry);
+        Ok(())
+    }
+
+    async fn claim_execution(&self, entry: &AuditEntry) -> Result<bool> {
+        let mut audit = self.inner.lock().await;
+        if let Some(key) = entry.idempotency_key() {
+            if !audit.claimed.insert(key.to_string()) {
+                return Ok(false);
+            }
+        }
+        self.push(&mut audit, entry);
+        Ok(true)
+    }
+}
+
+/// Writes to the database, whose unique key on execution attempts holds
+/// across restarts
+pub struct RepositoryAuditLog {
+    repository: Arc<Repository>,
+}
+
+impl RepositoryAuditLog {
+    pub fn new(repository: Arc<Repository>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl TradeAuditLog for RepositoryAuditLog {
+    async fn append(&self, entry: &AuditEntry) -> Result<()> {
+        self.repository.append_audit_entry(&TradeAuditRecord::try_from(entry)?).await
+    }
+
+    async fn claim_execution(&self, entry: &AuditEntry) -> Result<bool> {
+        self.repository.claim_execution(&TradeAuditRecord::try_from(entry)?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trader::{RiskAssessment, TradingEngine, TradingSignal};
+    use quant_models::{AuditEvent, BetType, BettingDecision, ExecutionOutcome, RiskConstraint};
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_retried_execution_is_refused_and_every_decision_audited() {
+        let audit = Arc::new(MemoryAuditLog::new());
+        let engine = TradingEngine::new(dec!(1000.0)).with_audit_log(audit.clone());
+        let bet = BettingDecision::new(
+            "m1".to_string(), BetType::HomeWin, dec!(50), dec!(2.0), 0.6, "test".to_string(),
+        ).unwrap();
+        let signal = TradingSignal::new("m1".to_string(), 0.5, Some(bet.clone()), RiskAssessment::default(), String::new());
+
+        assert!(engine.execute_trade(&signal).await.unwrap());
+        // A retry of the same bet, e.g. replayed after a crash
+        assert!(!engine.execute_trade(&signal).await.unwrap());
+        assert_eq!(engine.get_portfolio_summary().await.active_bets_count, 1);
+
+        let mut risky = TradingSignal::new("m2".to_string(), 0.5, Some(bet.clone()), RiskAssessment::default(), String::new());
+        risky.risk_assessment.risk_score = 0.9;
+        assert!(!engine.execute_trade(&risky).await.unwrap());
+
+        let entries = audit.entries().await;
+        assert_eq!(entries.iter().map(AuditEntry::kind).collect::<Vec<_>>(),
+                   ["risk_decision", "execution_attempt", "execution", "risk_decision", "risk_decision", "risk_decision"]);
+        assert!(entries.iter().all(|entry| entry.bet_id == Some(bet.id)));
+        assert!(matches!(entries[2].event, AuditEvent::Execution { outcome: ExecutionOutcome::Booked, .. }));
+        assert!(matches!(entries[4].event, AuditEvent::RiskDecision {
+            approved: false, constraint: Some(RiskConstraint::DuplicateExecution), ..
+        }));
+        assert!(matches!(entries[5].event, AuditEvent::RiskDecision {
+            approved: false, constraint: Some(RiskConstraint::RiskScore), ..
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_same_prediction_sized_again_is_not_executed_twice() {
+        let engine = TradingEngine::new(dec!(1000.0));
+        let prediction_id = uuid::Uuid::new_v4();
+        // Each sizing of the prediction makes a bet with a fresh id
+        let signal = |match_id: &str, prediction_id| {
+            let bet = BettingDecision::new(
+                match_id.to_string(), BetType::HomeWin, dec!(50), dec!(2.0), 0.6, "test".to_string(),
+            ).unwrap();
+            TradingSignal::new(match_id.to_string(), 0.5, Some(bet), RiskAssessment::default(), String::new())
+                .with_prediction(prediction_id)
+        };
+
+        assert!(engine.execute_trade(&signal("m1", prediction_id)).await.unwrap());
+        assert!(!engine.execute_trade(&signal("m1", prediction_id)).await.unwrap());
+        // A new prediction is a new decision
+        assert!(engine.execute_trade(&signal("m1", uuid::Uuid::new_v4())).await.unwrap());
+        assert_eq!(engine.get_portfolio_summary().await.active_bets_count, 2);
+    }
+}
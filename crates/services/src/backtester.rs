@@ -1,11 +1,361 @@
-// Backtesting service
+// Backtesting: a betting strategy replayed over historical predictions at
+// their closing prices, parameter sweeps ranking many variants of it, and
+// walk-forward runs trading each retrained model's out-of-sample window
 
-pub struct BacktestService {
-    name: String,
+use crate::model_training::WalkForwardWindow;
+use anyhow::Result;
+use quant_ml::EvaluationSample;
+use quant_models::{edge, BetType, BettingDecision, BettingStrategy, Outcome1X2, Portfolio, StakeContext};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::debug;
+
+/// How one strategy fared over the historical matches
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestReport {
+    pub strategy: String,
+    /// Matches with a prediction and closing prices to trade on
+    pub matches: usize,
+    pub bets: usize,
+    pub win_rate: f64,
+    pub staked: Decimal,
+    pub profit_loss: Decimal,
+    /// Profit or loss per unit staked
+    pub roi: f64,
+    pub final_bankroll: Decimal,
+    pub max_drawdown: f64,
+    pub sharpe_ratio: f64,
+}
+
+/// Values a sweep tries for each strategy parameter; an empty list keeps
+/// the base strategy's value
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParameterGrid {
+    pub min_edge: Vec<f64>,
+    pub kelly_multiplier: Vec<f64>,
+    pub min_confidence: Vec<f64>,
+}
+
+/// One combination of swept parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SweepParameters {
+    pub min_edge: f64,
+    pub kelly_multiplier: f64,
+    pub min_confidence: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepResult {
+    pub rank: usize,
+    pub parameters: SweepParameters,
+    pub report: BacktestReport,
+}
+
+/// A strategy traded on each walk-forward window's out-of-sample predictions
+#[derive(Debug, Clone, Serialize)]
+pub struct WalkForwardReport {
+    pub windows: Vec<WalkForwardResult>,
+    /// Every window's predictions together, in one bankroll
+    pub combined: BacktestReport,
+    pub log_loss_mean: f64,
+    /// Spread of the model's log loss from window to window; a large one
+    /// means what it learned does not hold for long
+    pub log_loss_std_dev: f64,
+    pub roi_mean: f64,
+    pub roi_std_dev: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WalkForwardResult {
+    pub window: WalkForwardWindow,
+    /// Starting from a fresh bankroll
+    pub backtest: BacktestReport,
+}
+
+/// What sweep results are ranked by, best first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SweepRanking {
+    #[default]
+    Roi,
+    ProfitLoss,
+    Sharpe,
+}
+
+impl ParameterGrid {
+    /// Every combination of the grid's values, with `base` filling in
+    /// parameters the grid leaves empty
+    pub fn combinations(&self, base: &BettingStrategy) -> Vec<SweepParameters> {
+        let values = |values: &[f64], default: f64| if values.is_empty() { vec![default] } else { values.to_vec() };
+        let mut combinations = Vec::new();
+        for min_edge in values(&self.min_edge, base.min_edge) {
+            for kelly_multiplier in values(&self.kelly_multiplier, base.kelly_multiplier) {
+                for min_confidence in values(&self.min_confidence, base.min_confidence) {
+                    combinations.push(SweepParameters { min_edge, kelly_multiplier, min_confidence });
+                }
+            }
+        }
+        combinations
+    }
+}
+
+impl SweepParameters {
+    /// `base` with these parameters
+    pub fn apply(&self, base: &BettingStrategy) -> BettingStrategy {
+        let mut strategy = base.clone();
+        strategy.min_edge = self.min_edge;
+        strategy.kelly_multiplier = self.kelly_multiplier;
+        strategy.min_confidence = self.min_confidence;
+        strategy
+    }
+}
+
+/// Each match is traded and settled before the next one, in the order the
+/// matches were played, so stakes follow the bankroll as it grows or
+/// shrinks. Risk limits beyond the strategy's own are not applied.
+#[derive(Debug, Clone)]
+pub struct Backtester {
+    bankroll: Decimal,
+    /// Largest number of combinations a sweep runs at once
+    max_parallel: usize,
+}
+
+impl Backtester {
+    pub fn new(bankroll: Decimal) -> Self {
+        let max_parallel = std::thread::available_parallelism().map_or(4, |n| n.get());
+        Self { bankroll, max_parallel }
+    }
+
+    pub fn with_max_parallel(mut self, max_parallel: usize) -> Self {
+        self.max_parallel = max_parallel.max(1);
+        self
+    }
+
+    /// Trade `strategy` on every sample that has closing odds, backing the
+    /// match winner outcome with the largest edge it accepts
+    pub fn run(&self, strategy: &BettingStrategy, samples: &[EvaluationSample]) -> Result<BacktestReport> {
+        let mut samples: Vec<&EvaluationSample> = samples.iter().filter(|sample| sample.odds.is_some()).collect();
+        samples.sort_by_key(|sample| sample.prediction.match_timestamp);
+
+        let mut portfolio = Portfolio::new(self.bankroll);
+        for sample in &samples {
+            let Some(bet) = self.pick_bet(strategy, sample, &portfolio)? else {
+                continue;
+            };
+            let (bet_id, won) = (bet.id, bet.bet_type == BetType::from(sample.outcome));
+            portfolio.place_bet(bet)?;
+            portfolio.settle_bet_at(bet_id, won, sample.prediction.match_timestamp)?;
+        }
+
+        let staked = portfolio.historical_bets.iter().map(|bet| bet.stake).sum();
+        Ok(BacktestReport {
+            strategy: strategy.name.clone(),
+            matches: samples.len(),
+            bets: portfolio.historical_bets.len(),
+            win_rate: portfolio.win_rate,
+            staked,
+            profit_loss: portfolio.total_profit_loss,
+            roi: portfolio.roi,
+            final_bankroll: portfolio.available_bankroll,
+            max_drawdown: portfolio.equity_curve.max_drawdown(),
+            sharpe_ratio: portfolio.equity_curve.sharpe_ratio(),
+        })
+    }
+
+    fn pick_bet(&self, strategy: &BettingStrategy, sample: &EvaluationSample, portfolio: &Portfolio) -> Result<Option<BettingDecision>> {
+        let Some(odds) = &sample.odds else {
+            return Ok(None);
+        };
+        let prediction = &sample.prediction;
+        let probabilities = [Some(prediction.home_win_prob), prediction.draw_prob, Some(prediction.away_win_prob)];
+        let prices = [odds.home_win, odds.draw, odds.away_win];
+
+        let best = Outcome1X2::ALL.into_iter()
+            .filter_map(|outcome| Some((outcome, outcome.select(probabilities)?, outcome.select(prices))))
+            .filter(|(_, probability, price)| strategy.should_bet(*price, *probability, prediction.confidence))
+            .max_by(|(_, pa, a), (_, pb, b)| {
+                let edge_at = |probability: f64, price: Decimal| edge(probability, price.to_f64().unwrap_or(f64::MAX));
+                edge_at(*pa, *a).total_cmp(&edge_at(*pb, *b))
+            });
+        let Some((outcome, probability, price)) = best else {
+            return Ok(None);
+        };
+
+        let mut bet = BettingDecision::new(
+            prediction.match_id.clone(),
+            BetType::from(outcome),
+            Decimal::ONE,
+            price,
+            probability,
+            strategy.name.clone(),
+        )?;
+        let context = StakeContext::new(portfolio.available_bankroll, bet.kelly_fraction)
+            .with_losing_streak(portfolio.losing_streak(&strategy.name));
+        bet.stake = strategy.size_stake(&context).round_dp(2);
+        if bet.stake <= Decimal::ZERO {
+            return Ok(None);
+        }
+        Ok(Some(bet))
+    }
+
+    /// Trade `strategy` on each window's predictions separately, and on all
+    /// of them in sequence
+    pub fn walk_forward(&self, strategy: &BettingStrategy, windows: Vec<WalkForwardWindow>) -> Result<WalkForwardReport> {
+        let all: Vec<EvaluationSample> = windows.iter().flat_map(|window| window.samples.iter().cloned()).collect();
+        let combined = self.run(strategy, &all)?;
+        let windows = windows.into_iter()
+            .map(|window| Ok(WalkForwardResult { backtest: self.run(strategy, &window.samples)?, window }))
+            .collect::<Result<Vec<_>>>()?;
+
+        let (log_loss_mean, log_loss_std_dev) = mean_and_std_dev(windows.iter().map(|result| result.window.evaluation.log_loss));
+        let (roi_mean, roi_std_dev) = mean_and_std_dev(windows.iter().map(|result| result.backtest.roi));
+        Ok(WalkForwardReport { windows, combined, log_loss_mean, log_loss_std_dev, roi_mean, roi_std_dev })
+    }
+
+    /// Backtest `base` with every combination in `grid`, spread over up to
+    /// `max_parallel` blocking tasks, best first by `ranking`
+    pub async fn sweep(
+        &self,
+        base: &BettingStrategy,
+        grid: &ParameterGrid,
+        samples: Arc<Vec<EvaluationSample>>,
+        ranking: SweepRanking,
+    ) -> Result<Vec<SweepResult>> {
+        let combinations = grid.combinations(base);
+        let chunk_size = combinations.len().div_ceil(self.max_parallel).max(1);
+        debug!("📈 Sweeping {} parameter combinations in chunks of {}", combinations.len(), chunk_size);
+
+        let mut tasks = Vec::new();
+        for chunk in combinations.chunks(chunk_size) {
+            let (chunk, base, samples, backtester) = (chunk.to_vec(), base.clone(), samples.clone(), self.clone());
+            tasks.push(tokio::task::spawn_blocking(move || {
+                chunk.into_iter()
+                    .map(|parameters| Ok((parameters, backtester.run(&parameters.apply(&base), &samples)?)))
+                    .collect::<Result<Vec<_>>>()
+            }));
+        }
+        let mut results = Vec::with_capacity(combinations.len());
+        for task in tasks {
+            results.extend(task.await??);
+        }
+
+        let score = |report: &BacktestReport| match ranking {
+            SweepRanking::Roi => report.roi,
+            SweepRanking::ProfitLoss => report.profit_loss.to_f64().unwrap_or(0.0),
+            SweepRanking::Sharpe => report.sharpe_ratio,
+        };
+        results.sort_by(|(_, a), (_, b)| score(b).total_cmp(&score(a)));
+        Ok(results.into_iter()
+            .enumerate()
+            .map(|(index, (parameters, report))| SweepResult { rank: index + 1, parameters, report })
+            .collect())
+    }
+}
+
+/// Sample standard deviation; zero for fewer than two values
+fn mean_and_std_dev(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let values: Vec<f64> = values.collect();
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    if values.len() < 2 {
+        return (mean, 0.0);
+    }
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, variance.sqrt())
 }
 
-impl BacktestService {
-    pub fn new(name: String) -> Self {
-        Self { name }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use quant_ml::Evaluator;
+    use quant_models::{Prediction, SimpleMarketOdds};
+    use rust_decimal_macros::dec;
+
+    fn sample(minutes: i64, home_win_prob: f64, outcome: Outcome1X2) -> EvaluationSample {
+        let prediction = Prediction::new(
+            format!("m{}", minutes), "test".to_string(), "1.0".to_string(),
+            home_win_prob, (1.0 - home_win_prob) / 2.0, Utc::now() + Duration::minutes(minutes),
+        ).unwrap().with_confidence(0.9).unwrap();
+        EvaluationSample {
+            prediction,
+            outcome,
+            odds: Some(SimpleMarketOdds::new(dec!(2.0), dec!(4.0), dec!(4.0))),
+        }
+    }
+
+    #[test]
+    fn test_backtest_trades_and_settles_in_order() {
+        let samples = vec![
+            sample(2, 0.6, Outcome1X2::AwayWin),
+            sample(1, 0.6, Outcome1X2::HomeWin),
+            // No edge at 2.0
+            sample(3, 0.45, Outcome1X2::HomeWin),
+        ];
+        let report = Backtester::new(dec!(1000)).run(&BettingStrategy::conservative(), &samples).unwrap();
+
+        // Quarter Kelly of 20% is 5%, held to the 2% cap: 20 won, then 20.40 lost
+        assert_eq!((report.matches, report.bets), (3, 2));
+        assert_eq!(report.staked, dec!(40.40));
+        assert_eq!(report.profit_loss, dec!(-0.40));
+        assert_eq!(report.final_bankroll, dec!(999.60));
+        assert!((report.win_rate - 0.5).abs() < 1e-9);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_walk_forward_scores_each_window() {
+        let window = |index: usize, outcome: Outcome1X2| {
+            let samples: Vec<EvaluationSample> = (0..4).map(|i| sample(index as i64 * 10 + i, 0.6, outcome)).collect();
+            WalkForwardWindow {
+                index,
+                train_examples: 100,
+                test_from: samples[0].prediction.match_timestamp,
+                test_to: samples[3].prediction.match_timestamp,
+                evaluation: Evaluator::new().evaluate(&samples),
+                samples,
+            }
+        };
+        let windows = vec![window(1, Outcome1X2::HomeWin), window(2, Outcome1X2::AwayWin)];
+        let report = Backtester::new(dec!(1000)).walk_forward(&BettingStrategy::conservative(), windows).unwrap();
+
+        assert_eq!(report.windows.len(), 2);
+        assert!(report.windows[0].backtest.roi > 0.0 && report.windows[1].backtest.roi < 0.0);
+        assert_eq!(report.combined.bets, 8);
+        // The model did well in one window and badly in the next
+        assert!(report.log_loss_std_dev > 0.5 && report.roi_std_dev > 1.0);
+        assert!((report.roi_mean - (report.windows[0].backtest.roi + report.windows[1].backtest.roi) / 2.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_sweep_ranks_every_combination() {
+        let samples = Arc::new((0..20)
+            .map(|i| sample(i, 0.6, if i % 3 == 0 { Outcome1X2::Draw } else { Outcome1X2::HomeWin }))
+            .collect::<Vec<_>>());
+        let grid = ParameterGrid {
+            min_edge: vec![0.05, 0.15],
+            kelly_multiplier: vec![0.1, 0.25],
+            min_confidence: vec![],
+        };
+        let base = BettingStrategy::moderate();
+        let results = Backtester::new(dec!(1000)).with_max_parallel(3)
+            .sweep(&base, &grid, samples, SweepRanking::ProfitLoss)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 4);
+        assert!(results.iter().all(|result| result.parameters.min_confidence == base.min_confidence));
+        assert!(results.windows(2).all(|pair| pair[0].report.profit_loss >= pair[1].report.profit_loss));
+        assert_eq!(results.iter().map(|result| result.rank).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        // A 15% edge requirement rules out every 10% edge bet
+        let strict = results.iter().find(|result| result.parameters.min_edge == 0.15).unwrap();
+        assert_eq!(strict.report.bets, 0);
+        // The larger profitable stake comes first
+        assert_eq!(results[0].parameters, SweepParameters { min_edge: 0.05, kelly_multiplier: 0.25, min_confidence: 0.6 });
+    }
+}
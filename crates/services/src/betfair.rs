@@ -0,0 +1,493 @@
+// Betfair Exchange: session handling, order placement and market books
+// over the API-NG REST endpoints
+
+use crate::execution::{ExecutionVenue, OrderReceipt};
+use crate::http::{self, HttpTransport};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use quant_models::{BettingDecision, Outcome1X2};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+const LOGIN_URL: &str = "https://identitysso.betfair.com/api/login";
+const KEEP_ALIVE_URL: &str = "https://identitysso.betfair.com/api/keepAlive";
+const BETTING_URL: &str = "https://api.betfair.com/exchange/betting/rest/v1.0";
+/// API errors that mean the session token is no longer any good
+const SESSION_ERRORS: [&str; 2] = ["INVALID_SESSION_INFORMATION", "NO_SESSION"];
+
+/// Betfair's price ladder: from each price up, the tick size
+const PRICE_LADDER: [(Decimal, Decimal); 10] = [
+    (dec!(1.01), dec!(0.01)),
+    (dec!(2), dec!(0.02)),
+    (dec!(3), dec!(0.05)),
+    (dec!(4), dec!(0.1)),
+    (dec!(6), dec!(0.2)),
+    (dec!(10), dec!(0.5)),
+    (dec!(20), dec!(1)),
+    (dec!(30), dec!(2)),
+    (dec!(50), dec!(5)),
+    (dec!(100), dec!(10)),
+];
+const MAX_PRICE: Decimal = dec!(1000);
+
+/// The lowest price on Betfair's ladder at or above `odds`, so an order never
+/// takes worse odds than the bet was sized for. `None` off the ladder.
+pub fn ladder_price(odds: Decimal) -> Option<Decimal> {
+    if odds < PRICE_LADDER[0].0 || odds > MAX_PRICE {
+        return None;
+    }
+    let (from, tick) = PRICE_LADDER.iter().rev().find(|(from, _)| odds >= *from)?;
+    let steps = ((odds - from) / tick).ceil();
+    Some((from + steps * tick).normalize())
+}
+
+/// Ticks on Betfair's ladder between two prices, each taken at the ladder
+/// price at or above it. `None` if either is off the ladder.
+pub fn ladder_ticks(from: Decimal, to: Decimal) -> Option<u32> {
+    let position = |odds: Decimal| -> Option<Decimal> {
+        let odds = ladder_price(odds)?;
+        let mut ticks = Decimal::ZERO;
+        for (i, (start, tick)) in PRICE_LADDER.iter().enumerate() {
+            let end = PRICE_LADDER.get(i + 1).map_or(MAX_PRICE, |(next, _)| *next);
+            ticks += (odds.min(end) - start) / tick;
+            if odds < end {
+                break;
+            }
+        }
+        Some(ticks)
+    };
+    (position(from)? - position(to)?).abs().to_u32()
+}
+
+#[derive(Debug, Clone)]
+pub struct BetfairCredentials {
+    pub app_key: String,
+    pub username: String,
+    pub password: String,
+}
+
+/// The Betfair match odds market for one of our matches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BetfairMarket {
+    pub market_id: String,
+    /// Selection ids of the three runners
+    pub home: u64,
+    pub draw: u64,
+    pub away: u64,
+}
+
+/// Match odds markets by match id, from a TOML file:
+///
+/// ```toml
+/// [epl_2024_arsenal_chelsea]
+/// market_id = "1.234567890"
+/// home = 1096
+/// draw = 58805
+/// away = 56323
+/// ```
+pub fn load_betfair_markets(path: impl AsRef<Path>) -> Result<HashMap<String, BetfairMarket>> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("cannot read Betfair markets {}", path.display()))?;
+    toml::from_str(&source).with_context(|| format!("invalid Betfair markets {}", path.display()))
+}
+
+#[derive(Debug, Deserialize)]
+struct SessionResponse {
+    #[serde(default)]
+    token: String,
+    status: String,
+    #[serde(default)]
+    error: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaceExecutionReport {
+    status: String,
+    #[serde(default)]
+    error_code: Option<String>,
+    #[serde(default)]
+    instruction_reports: Vec<PlaceInstructionReport>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaceInstructionReport {
+    #[serde(default)]
+    error_code: Option<String>,
+    #[serde(default)]
+    bet_id: Option<String>,
+    #[serde(default)]
+    average_price_matched: Decimal,
+    #[serde(default)]
+    size_matched: Decimal,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelExecutionReport {
+    status: String,
+    #[serde(default)]
+    error_code: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiError {
+    #[serde(default)]
+    detail: Option<ApiErrorDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiErrorDetail {
+    #[serde(rename = "APINGException", default)]
+    exception: Option<ApiException>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiException {
+    error_code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceSize {
+    pub price: Decimal,
+    pub size: Decimal,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangePrices {
+    #[serde(default)]
+    pub available_to_back: Vec<PriceSize>,
+    #[serde(default)]
+    pub available_to_lay: Vec<PriceSize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunnerBook {
+    pub selection_id: u64,
+    pub status: String,
+    #[serde(default)]
+    pub last_price_traded: Option<Decimal>,
+    #[serde(default)]
+    pub ex: ExchangePrices,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketBook {
+    pub market_id: String,
+    pub status: String,
+    #[serde(default)]
+    pub inplay: bool,
+    #[serde(default)]
+    pub total_matched: Decimal,
+    #[serde(default)]
+    pub runners: Vec<RunnerBook>,
+}
+
+/// Places the engine's match odds bets on the Betfair Exchange as
+/// fill-or-kill back orders. Only matches with a mapped market can be
+/// traded; the session is opened on first use and renewed when it expires.
+pub struct BetfairExchange {
+    credentials: BetfairCredentials,
+    transport: Arc<dyn HttpTransport>,
+    session: RwLock<Option<String>>,
+    markets: RwLock<HashMap<String, BetfairMarket>>,
+}
+
+impl BetfairExchange {
+    pub fn new(credentials: BetfairCredentials) -> Self {
+        Self {
+            credentials,
+            transport: Arc::new(http::client()),
+            session: RwLock::new(None),
+            markets: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    pub fn with_markets(mut self, markets: HashMap<String, BetfairMarket>) -> Self {
+        self.markets = RwLock::new(markets);
+        self
+    }
+
+    pub async fn map_market(&self, match_id: &str, market: BetfairMarket) {
+        self.markets.write().await.insert(match_id.to_string(), market);
+    }
+
+    pub async fn login(&self) -> Result<()> {
+        let body = format!(
+            "username={}&password={}",
+            form_encode(&self.credentials.username),
+            form_encode(&self.credentials.password),
+        );
+        let response = self.transport.post(LOGIN_URL, &[
+            ("X-Application", &self.credentials.app_key),
+            ("Accept", "application/json"),
+            ("Content-Type", "application/x-www-form-urlencoded"),
+        ], body).await?;
+        let session: SessionResponse = serde_json::from_str(&response.body)
+            .with_context(|| format!("unexpected Betfair login response ({})", response.status))?;
+        if session.status != "SUCCESS" {
+            bail!("Betfair login failed: {}", session.error);
+        }
+        *self.session.write().await = Some(session.token);
+        info!("🔑 Logged in to Betfair as {}", self.credentials.username);
+        Ok(())
+    }
+
+    /// Best prices for each runner in `market_ids`
+    pub async fn list_market_books(&self, market_ids: &[String]) -> Result<Vec<MarketBook>> {
+        self.call("listMarketBook", serde_json::json!({
+            "marketIds": market_ids,
+            "priceProjection": { "priceData": ["EX_BEST_OFFERS"] },
+        })).await
+    }
+
+    async fn session_token(&self) -> Result<String> {
+        if let Some(token) = self.session.read().await.clone() {
+            return Ok(token);
+        }
+        self.login().await?;
+        self.session.read().await.clone().ok_or_else(|| anyhow!("no Betfair session"))
+    }
+
+    /// Call a betting operation, logging in again once if the session has
+    /// expired
+    async fn call<T: DeserializeOwned>(&self, operation: &str, params: serde_json::Value) -> Result<T> {
+        let url = format!("{}/{}/", BETTING_URL, operation);
+        let body = params.to_string();
+        let mut retried = false;
+        loop {
+            let token = self.session_token().await?;
+            let response = self.transport.post(&url, &[
+                ("X-Application", &self.credentials.app_key),
+                ("X-Authentication", &token),
+                ("Accept", "application/json"),
+                ("Content-Type", "application/json"),
+            ], body.clone()).await?;
+            if response.status == 200 {
+                return serde_json::from_str(&response.body)
+                    .with_context(|| format!("unexpected Betfair {} response", operation));
+            }
+
+            let error_code = serde_json::from_str::<ApiError>(&response.body).ok()
+                .and_then(|error| error.detail)
+                .and_then(|detail| detail.exception)
+                .map(|exception| exception.error_code)
+                .unwrap_or_else(|| format!("HTTP {}", response.status));
+            if !retried && SESSION_ERRORS.contains(&error_code.as_str()) {
+                warn!("🔑 Betfair session expired, logging in again");
+                *self.session.write().await = None;
+                retried = true;
+                continue;
+            }
+            bail!("Betfair {} failed: {}", operation, error_code);
+        }
+    }
+
+    async fn selection(&self, bet: &BettingDecision) -> Result<(String, u64)> {
+        let markets = self.markets.read().await;
+        let market = markets.get(&bet.match_id)
+            .ok_or_else(|| anyhow!("no Betfair market mapped for match {}", bet.match_id))?;
+        let Ok(outcome) = Outcome1X2::try_from(&bet.bet_type) else {
+            bail!("only match odds bets can go to Betfair, not {:?}", bet.bet_type);
+        };
+        let selection = outcome.select([market.home, market.draw, market.away]);
+        Ok((market.market_id.clone(), selection))
+    }
+}
+
+#[async_trait]
+impl ExecutionVenue for BetfairExchange {
+    fn name(&self) -> &str {
+        "betfair"
+    }
+
+    async fn place_order(&self, bet: &BettingDecision) -> Result<OrderReceipt> {
+        if bet.free_bet {
+            bail!("free bets can't be placed on Betfair");
+        }
+        let (market_id, selection_id) = self.selection(bet).await?;
+        let price = ladder_price(bet.odds)
+            .ok_or_else(|| anyhow!("odds {} are off the Betfair price ladder", bet.odds))?;
+        let size = bet.stake.round_dp(2);
+
+        let report: PlaceExecutionReport = self.call("placeOrders", serde_json::json!({
+            "marketId": market_id,
+            "customerRef": bet.id.simple().to_string(),
+            "instructions": [{
+                "selectionId": selection_id,
+                "handicap": 0,
+                "side": "BACK",
+                "orderType": "LIMIT",
+                "limitOrder": {
+                    "size": size,
+                    "price": price,
+                    "persistenceType": "LAPSE",
+                    "timeInForce": "FILL_OR_KILL",
+                    "minFillSize": size,
+                },
+            }],
+        })).await?;
+
+        let instruction = report.instruction_reports.into_iter().next();
+        if report.status != "SUCCESS" {
+            let code = instruction.and_then(|report| report.error_code).or(report.error_code);
+            bail!("Betfair rejected the order: {}", code.unwrap_or(report.status));
+        }
+        let instruction = instruction.ok_or_else(|| anyhow!("Betfair returned no instruction report"))?;
+        Ok(OrderReceipt {
+            venue: self.name().to_string(),
+            order_id: instruction.bet_id.unwrap_or_default(),
+            market_id: Some(market_id),
+            size_matched: instruction.size_matched,
+            average_price: instruction.average_price_matched,
+        })
+    }
+
+    async fn cancel_order(&self, receipt: &OrderReceipt) -> Result<()> {
+        let market_id = receipt.market_id.as_ref()
+            .ok_or_else(|| anyhow!("order {} has no Betfair market", receipt.order_id))?;
+        let report: CancelExecutionReport = self.call("cancelOrders", serde_json::json!({
+            "marketId": market_id,
+            "instructions": [{ "betId": receipt.order_id }],
+        })).await?;
+        if report.status != "SUCCESS" {
+            bail!("Betfair could not cancel order {}: {}", receipt.order_id,
+                  report.error_code.unwrap_or(report.status));
+        }
+        Ok(())
+    }
+
+    /// Extend the session, or open a new one if it has already gone
+    async fn keep_alive(&self) -> Result<()> {
+        let Some(token) = self.session.read().await.clone() else {
+            return self.login().await;
+        };
+        let response = self.transport.post(KEEP_ALIVE_URL, &[
+            ("X-Application", &self.credentials.app_key),
+            ("X-Authentication", &token),
+            ("Accept", "application/json"),
+        ], String::new()).await?;
+        let session: SessionResponse = serde_json::from_str(&response.body)
+            .with_context(|| format!("unexpected Betfair keep-alive response ({})", response.status))?;
+        if session.status != "SUCCESS" {
+            warn!("🔑 Betfair keep-alive failed ({}), logging in again", session.error);
+            return self.login().await;
+        }
+        Ok(())
+    }
+}
+
+/// `application/x-www-form-urlencoded` value encoding
+fn form_encode(value: &str) -> String {
+    value.bytes().map(|byte| match byte {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+        _ => format!("%{:02X}", byte),
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HttpResponse;
+    use quant_models::BetType;
+    use std::sync::Mutex;
+
+    /// Answers each request with the next canned response and keeps what
+    /// was sent
+    struct ScriptedTransport {
+        responses: Mutex<Vec<HttpResponse>>,
+        requests: Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl HttpTransport for ScriptedTransport {
+        async fn post(&self, url: &str, _headers: &[(&str, &str)], body: String) -> Result<HttpResponse> {
+            self.requests.lock().unwrap().push((url.to_string(), body));
+            Ok(self.responses.lock().unwrap().remove(0))
+        }
+    }
+
+    fn response(status: u16, body: serde_json::Value) -> HttpResponse {
+        HttpResponse { status, body: body.to_string() }
+    }
+
+    #[tokio::test]
+    async fn test_order_placed_after_session_renewed() {
+        let login = || response(200, serde_json::json!({ "token": "abc", "status": "SUCCESS", "error": "" }));
+        let transport = Arc::new(ScriptedTransport {
+            responses: Mutex::new(vec![
+                login(),
+                response(400, serde_json::json!({ "detail": { "APINGException": { "errorCode": "INVALID_SESSION_INFORMATION" } } })),
+                login(),
+                response(200, serde_json::json!({
+                    "status": "SUCCESS",
+                    "instructionReports": [{ "status": "SUCCESS", "betId": "31242", "averagePriceMatched": 2.52, "sizeMatched": 25.0 }],
+                })),
+            ]),
+            requests: Mutex::new(Vec::new()),
+        });
+        let exchange = BetfairExchange::new(BetfairCredentials {
+            app_key: "key".to_string(),
+            username: "trader".to_string(),
+            password: "p@ss word".to_string(),
+        }).with_transport(transport.clone());
+        exchange.map_market("m1", BetfairMarket { market_id: "1.23".to_string(), home: 1, draw: 2, away: 3 }).await;
+
+        let bet = BettingDecision::new(
+            "m1".to_string(), BetType::AwayWin, dec!(25), dec!(2.51), 0.45, "test".to_string(),
+        ).unwrap();
+        let receipt = exchange.place_order(&bet).await.unwrap();
+        assert_eq!((receipt.order_id.as_str(), receipt.size_matched), ("31242", dec!(25)));
+        assert_eq!(receipt.market_id.as_deref(), Some("1.23"));
+
+        let requests = transport.requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 4);
+        assert!(requests[0].1.contains("password=p%40ss%20word"));
+        let order: serde_json::Value = serde_json::from_str(&requests[3].1).unwrap();
+        let instruction = &order["instructions"][0];
+        assert_eq!(instruction["selectionId"], 3);
+        // 2.51 is between ticks; the order asks for the next price up
+        assert_eq!(instruction["limitOrder"]["price"], 2.52);
+        assert_eq!(instruction["limitOrder"]["timeInForce"], "FILL_OR_KILL");
+
+        // Bets on unmapped matches never reach the exchange
+        let unmapped = BettingDecision::new(
+            "m2".to_string(), BetType::HomeWin, dec!(10), dec!(2.0), 0.6, "test".to_string(),
+        ).unwrap();
+        assert!(exchange.place_order(&unmapped).await.is_err());
+        assert_eq!(ladder_price(dec!(1001)), None);
+    }
+
+    #[test]
+    fn test_ladder_ticks_span_tick_sizes() {
+        assert_eq!(ladder_ticks(dec!(2.0), dec!(2.0)), Some(0));
+        assert_eq!(ladder_ticks(dec!(1.98), dec!(2.04)), Some(4));
+        // 2.96 to 3 in 0.02s, then 3 to 3.2 in 0.05s
+        assert_eq!(ladder_ticks(dec!(3.2), dec!(2.96)), Some(6));
+        // Between ticks counts from the next price up
+        assert_eq!(ladder_ticks(dec!(2.51), dec!(2.5)), Some(1));
+        assert_eq!(ladder_ticks(dec!(1.0), dec!(2.0)), None);
+    }
+}
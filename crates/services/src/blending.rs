@@ -0,0 +1,237 @@
+// Shrinks model probabilities toward the market before edges are computed
+
+use quant_ml::EvaluationSample;
+use quant_models::{Prediction, SimpleMarketOdds};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const DEFAULT_MODEL_WEIGHT: f64 = 0.5;
+const DEFAULT_MIN_SAMPLES: usize = 50;
+/// Candidate weights tried when calibrating: 0.00, 0.05, ..., 1.00
+const WEIGHT_STEPS: usize = 20;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlendMethod {
+    /// Trade on the model's probabilities as they are
+    #[default]
+    None,
+    /// `w * model + (1 - w) * market`
+    Linear,
+    /// Weighted geometric mean, renormalised. Disagreement on longshots is
+    /// pulled toward the market harder than with a linear blend.
+    Logarithmic,
+}
+
+/// How much the model has earned the right to disagree with the market in a league
+#[derive(Debug, Clone, Serialize)]
+pub struct LeagueSkill {
+    /// Weight on the model; the market gets the rest
+    pub model_weight: f64,
+    pub samples: usize,
+    pub model_log_loss: f64,
+    pub market_log_loss: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProbabilityBlender {
+    method: BlendMethod,
+    default_weight: f64,
+    min_samples: usize,
+    league_skill: HashMap<String, LeagueSkill>,
+}
+
+impl ProbabilityBlender {
+    pub fn new() -> Self {
+        Self {
+            method: BlendMethod::None,
+            default_weight: DEFAULT_MODEL_WEIGHT,
+            min_samples: DEFAULT_MIN_SAMPLES,
+            league_skill: HashMap::new(),
+        }
+    }
+
+    pub fn with_method(mut self, method: BlendMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Weight on the model in leagues without enough settled predictions
+    pub fn with_model_weight(mut self, weight: f64) -> Self {
+        self.default_weight = weight.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Settled predictions a league needs before its weight is calibrated
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples.max(1);
+        self
+    }
+
+    pub fn method(&self) -> BlendMethod {
+        self.method
+    }
+
+    pub fn model_weight(&self, league: Option<&str>) -> f64 {
+        league
+            .and_then(|league| self.league_skill.get(league))
+            .map_or(self.default_weight, |skill| skill.model_weight)
+    }
+
+    pub fn league_skill(&self) -> &HashMap<String, LeagueSkill> {
+        &self.league_skill
+    }
+
+    /// The prediction with its probabilities blended toward the margin-free
+    /// market prices. Predictions without a draw are blended over the two
+    /// remaining outcomes.
+    pub fn blend(&self, prediction: &Prediction, market: &SimpleMarketOdds, league: Option<&str>) -> Prediction {
+        let mut blended = prediction.clone();
+        let Some(market) = fair_probabilities(market, prediction.draw_prob.is_some()) else {
+            return blended;
+        };
+
+        let model = [prediction.home_win_prob, prediction.draw_prob.unwrap_or(0.0), prediction.away_win_prob];
+        let [home, draw, away] = blend_with(self.method, &model, &market, self.model_weight(league));
+        blended.home_win_prob = home;
+        blended.draw_prob = prediction.draw_prob.map(|_| draw);
+        blended.away_win_prob = away;
+        if self.method != BlendMethod::None {
+            // The model's score grid no longer matches; markets refit it
+            blended.score_matrix = None;
+        }
+        blended
+    }
+
+    /// Pick, per league, the weight that would have given the lowest log
+    /// loss on these settled predictions. Returns how many leagues had
+    /// enough samples to be calibrated.
+    pub fn calibrate<'a>(&mut self, samples: impl IntoIterator<Item = (&'a str, &'a EvaluationSample)>) -> usize {
+        if self.method == BlendMethod::None {
+            return 0;
+        }
+
+        // (model, market, index of the outcome that happened)
+        type Row = ([f64; 3], [f64; 3], usize);
+        let mut by_league: HashMap<&str, Vec<Row>> = HashMap::new();
+        for (league, sample) in samples {
+            let Some(market) = sample.odds.as_ref().and_then(|odds| fair_probabilities(odds, true)) else {
+                continue;
+            };
+            let prediction = &sample.prediction;
+            let model = [prediction.home_win_prob, prediction.draw_prob.unwrap_or(0.0), prediction.away_win_prob];
+            by_league.entry(league).or_default().push((model, market, sample.outcome.index()));
+        }
+
+        let mut calibrated = 0;
+        for (league, rows) in by_league {
+            if rows.len() < self.min_samples {
+                continue;
+            }
+            let log_loss = |weight: f64| {
+                rows.iter()
+                    .map(|(model, market, outcome)| {
+                        let blended = blend_with(self.method, model, market, weight);
+                        -blended[*outcome].max(1e-15).ln()
+                    })
+                    .sum::<f64>() / rows.len() as f64
+            };
+
+            let (model_weight, _) = (0..=WEIGHT_STEPS)
+                .map(|step| step as f64 / WEIGHT_STEPS as f64)
+                .map(|weight| (weight, log_loss(weight)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .expect("weight grid is never empty");
+
+            self.league_skill.insert(league.to_string(), LeagueSkill {
+                model_weight,
+                samples: rows.len(),
+                model_log_loss: log_loss(1.0),
+                market_log_loss: log_loss(0.0),
+            });
+            calibrated += 1;
+        }
+        calibrated
+    }
+}
+
+impl Default for ProbabilityBlender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn blend_with(method: BlendMethod, model: &[f64; 3], market: &[f64; 3], weight: f64) -> [f64; 3] {
+    let blended = match method {
+        BlendMethod::None => return *model,
+        BlendMethod::Linear => {
+            std::array::from_fn(|i| weight * model[i] + (1.0 - weight) * market[i])
+        }
+        BlendMethod::Logarithmic => std::array::from_fn(|i| {
+            if model[i] == 0.0 && market[i] == 0.0 {
+                0.0
+            } else {
+                model[i].max(1e-12).powf(weight) * market[i].max(1e-12).powf(1.0 - weight)
+            }
+        }),
+    };
+    let total: f64 = blended.iter().sum();
+    blended.map(|p| p / total)
+}
+
+/// Implied probabilities with the bookmaker margin removed proportionally
+fn fair_probabilities(odds: &SimpleMarketOdds, with_draw: bool) -> Option<[f64; 3]> {
+    let implied = |price: rust_decimal::Decimal| price.to_f64().filter(|p| *p > 1.0).map(|p| 1.0 / p);
+    let home = implied(odds.home_win)?;
+    let draw = if with_draw { implied(odds.draw)? } else { 0.0 };
+    let away = implied(odds.away_win)?;
+    let total = home + draw + away;
+    Some([home / total, draw / total, away / total])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use quant_models::Outcome1X2;
+    use rust_decimal_macros::dec;
+
+    fn prediction(home: f64, draw: f64, away: f64) -> Prediction {
+        Prediction::new("m1".to_string(), "test".to_string(), "1.0".to_string(), home, away, Utc::now())
+            .unwrap()
+            .with_draw_prob(draw)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_linear_blend_and_calibration() {
+        // 2.50 / 3.33 / 3.33 is a 0.4 / 0.3 / 0.3 market with no margin to speak of
+        let market = SimpleMarketOdds::new(dec!(2.50), dec!(3.333), dec!(3.333));
+        let blender = ProbabilityBlender::new().with_method(BlendMethod::Linear).with_model_weight(0.5);
+
+        let blended = blender.blend(&prediction(0.6, 0.2, 0.2), &market, None);
+        assert!((blended.home_win_prob - 0.5).abs() < 1e-3);
+        assert!((blended.draw_prob.unwrap() - 0.25).abs() < 1e-3);
+
+        // No blending leaves the model alone
+        let untouched = ProbabilityBlender::new().blend(&prediction(0.6, 0.2, 0.2), &market, None);
+        assert_eq!(untouched.home_win_prob, 0.6);
+
+        // A model that is confidently wrong every time earns no weight
+        let samples: Vec<_> = (0..60)
+            .map(|_| EvaluationSample {
+                prediction: prediction(0.8, 0.1, 0.1),
+                outcome: Outcome1X2::AwayWin,
+                odds: Some(market.clone()),
+            })
+            .collect();
+        let mut blender = blender.with_min_samples(50);
+        assert_eq!(blender.calibrate(samples.iter().map(|s| ("Premier League", s))), 1);
+        assert_eq!(blender.model_weight(Some("Premier League")), 0.0);
+        assert_eq!(blender.model_weight(Some("La Liga")), 0.5);
+
+        let skill = &blender.league_skill()["Premier League"];
+        assert!(skill.model_log_loss > skill.market_log_loss);
+    }
+}
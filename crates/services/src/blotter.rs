@@ -0,0 +1,132 @@
+// Live blotter: every step of an order's life, from the signal to the
+// settlement, broadcast in order for dashboards mirroring the engine
+
+use chrono::{DateTime, Utc};
+use quant_models::{AuditEntry, AuditEvent, BetStatus, BettingDecision, DomainEvent, ExecutionOutcome, RiskConstraint};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// One step of an order's life
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BlotterEvent {
+    /// Only signals that recommend a bet or record a rejected market
+    SignalGenerated {
+        signal_strength: f64,
+        risk_score: f64,
+        reasoning: String,
+        bet: Option<Box<BettingDecision>>,
+    },
+    /// The pre-execution checks passed, or the one that stopped the trade
+    RiskChecked {
+        approved: bool,
+        constraint: Option<RiskConstraint>,
+        detail: Option<String>,
+    },
+    /// The venue did not fill the order, or a limit stopped it being booked
+    NotExecuted { outcome: ExecutionOutcome, detail: Option<String> },
+    Placed { bet: BettingDecision },
+    /// Won, lost or half settled; `bet` carries its status
+    Settled { bet: BettingDecision, profit_loss: Decimal },
+    /// The stake was returned
+    Voided { bet: BettingDecision },
+    CashedOut { bet: BettingDecision, amount: Decimal, profit_loss: Decimal },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlotterMessage {
+    /// Increases by one per message, so a subscriber can tell it missed some
+    pub sequence: u64,
+    pub timestamp: DateTime<Utc>,
+    pub match_id: String,
+    pub signal_id: Option<Uuid>,
+    pub bet_id: Option<Uuid>,
+    #[serde(flatten)]
+    pub event: BlotterEvent,
+}
+
+/// Fed by the trading engine's audit trail and domain events. Messages
+/// nobody is subscribed to are dropped.
+pub struct Blotter {
+    updates: broadcast::Sender<BlotterMessage>,
+    sequence: AtomicU64,
+}
+
+impl Blotter {
+    pub fn new() -> Self {
+        let (updates, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { updates, sequence: AtomicU64::new(0) }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<BlotterMessage> {
+        self.updates.subscribe()
+    }
+
+    /// Signals, risk checks and unfilled orders from the audit trail.
+    /// Execution attempts and booked fills are left to `record_domain_event`.
+    pub fn record_audit(&self, entry: &AuditEntry) {
+        let event = match &entry.event {
+            AuditEvent::Signal { signal_strength, risk_score, reasoning, bet } => BlotterEvent::SignalGenerated {
+                signal_strength: *signal_strength,
+                risk_score: *risk_score,
+                reasoning: reasoning.clone(),
+                bet: bet.clone(),
+            },
+            AuditEvent::RiskDecision { approved, constraint, detail } => BlotterEvent::RiskChecked {
+                approved: *approved,
+                constraint: *constraint,
+                detail: detail.clone(),
+            },
+            AuditEvent::Execution { outcome, detail, .. } if *outcome != ExecutionOutcome::Booked => {
+                BlotterEvent::NotExecuted { outcome: *outcome, detail: detail.clone() }
+            }
+            AuditEvent::Execution { .. } | AuditEvent::ExecutionAttempt { .. } => return,
+        };
+        self.send(entry.match_id.clone(), entry.signal_id, entry.bet_id, event);
+    }
+
+    /// Placements and settlements; other domain events are not order steps
+    pub fn record_domain_event(&self, event: &DomainEvent) {
+        let (bet, event) = match event {
+            DomainEvent::BetPlaced { bet } => (bet, BlotterEvent::Placed { bet: bet.clone() }),
+            DomainEvent::BetSettled { bet, profit_loss } => {
+                let event = match &bet.status {
+                    BetStatus::Void => BlotterEvent::Voided { bet: bet.clone() },
+                    BetStatus::CashedOut { amount } => BlotterEvent::CashedOut {
+                        bet: bet.clone(),
+                        amount: *amount,
+                        profit_loss: *profit_loss,
+                    },
+                    _ => BlotterEvent::Settled { bet: bet.clone(), profit_loss: *profit_loss },
+                };
+                (bet, event)
+            }
+            _ => return,
+        };
+        self.send(bet.match_id.clone(), None, Some(bet.id), event);
+    }
+
+    fn send(&self, match_id: String, signal_id: Option<Uuid>, bet_id: Option<Uuid>, event: BlotterEvent) {
+        let message = BlotterMessage {
+            sequence: self.sequence.fetch_add(1, Ordering::Relaxed) + 1,
+            timestamp: Utc::now(),
+            match_id,
+            signal_id,
+            bet_id,
+            event,
+        };
+        // No subscribers is fine
+        let _ = self.updates.send(message);
+    }
+}
+
+impl Default for Blotter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
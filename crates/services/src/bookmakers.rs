@@ -0,0 +1,152 @@
+// Simulated bookmakers: how much margin each takes, how quickly it reacts to
+// the match and which way it leans
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BookmakerProfile {
+    pub name: String,
+    /// Typical match winner overround on a top-tier league at kick-off
+    pub margin: f64,
+    /// Each quote's margin is drawn uniformly from `margin ± margin_spread`
+    pub margin_spread: f64,
+    /// A book only reprices once this long has passed since its last quote;
+    /// events in between leave the old price standing
+    pub update_latency_ms: u64,
+    /// Probability moved onto a home favourite before pricing, so the side
+    /// the public backs is priced shorter than it should be
+    pub home_favourite_shading: f64,
+    /// Random variation in each outcome's probability
+    pub noise: f64,
+}
+
+impl Default for BookmakerProfile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            margin: 0.05,
+            margin_spread: 0.0,
+            update_latency_ms: 0,
+            home_favourite_shading: 0.0,
+            noise: 0.015,
+        }
+    }
+}
+
+impl BookmakerProfile {
+    pub fn new(name: impl Into<String>, margin: f64) -> Self {
+        Self { name: name.into(), margin, ..Default::default() }
+    }
+
+    pub fn with_margin_spread(mut self, spread: f64) -> Self {
+        self.margin_spread = spread.max(0.0);
+        self
+    }
+
+    pub fn with_update_latency(mut self, latency: Duration) -> Self {
+        self.update_latency_ms = latency.as_millis() as u64;
+        self
+    }
+
+    pub fn with_home_favourite_shading(mut self, shading: f64) -> Self {
+        self.home_favourite_shading = shading.max(0.0);
+        self
+    }
+
+    pub fn with_noise(mut self, noise: f64) -> Self {
+        self.noise = noise.max(0.0);
+        self
+    }
+
+    /// The books simulated unless configured otherwise. The exchange prices
+    /// close to fair and earns its money through commission instead; the
+    /// high street books are slower to move and shade home favourites.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new("Pinnacle", 0.025).with_margin_spread(0.003).with_noise(0.01),
+            Self::new("Bet365", 0.05)
+                .with_margin_spread(0.008)
+                .with_update_latency(Duration::from_secs(2))
+                .with_home_favourite_shading(0.01),
+            Self::new("WilliamHill", 0.065)
+                .with_margin_spread(0.01)
+                .with_update_latency(Duration::from_secs(5))
+                .with_home_favourite_shading(0.02),
+            Self::new("BetfairExchange", 0.005).with_margin_spread(0.002).with_noise(0.02),
+        ]
+    }
+
+    pub fn update_latency(&self) -> chrono::Duration {
+        chrono::Duration::milliseconds(self.update_latency_ms.min(i64::MAX as u64) as i64)
+    }
+
+    /// This quote's margin at kick-off on a top-tier league
+    pub fn draw_margin(&self, rng: &mut impl Rng) -> f64 {
+        let margin = if self.margin_spread > 0.0 {
+            rng.gen_range(self.margin - self.margin_spread..=self.margin + self.margin_spread)
+        } else {
+            self.margin
+        };
+        margin.max(0.0)
+    }
+
+    /// Home, draw and away probabilities as the book sees them: noisy, and
+    /// leaning toward the home side when it is the favourite. Normalized.
+    pub fn view(&self, [home, draw, away]: [f64; 3], rng: &mut impl Rng) -> [f64; 3] {
+        let mut jitter = |p: f64| {
+            let noise = if self.noise > 0.0 { rng.gen_range(-self.noise..self.noise) } else { 0.0 };
+            (p + noise).max(0.01)
+        };
+        let [mut home, mut draw, mut away] = [jitter(home), jitter(draw), jitter(away)];
+
+        if home > draw && home > away && self.home_favourite_shading > 0.0 {
+            let shading = self.home_favourite_shading.min((draw + away - 0.02).max(0.0));
+            let rest = draw + away;
+            home += shading;
+            draw -= shading * draw / rest;
+            away -= shading * away / rest;
+        }
+
+        let total = home + draw + away;
+        [home / total, draw / total, away / total]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_profile_margin_and_home_favourite_shading() {
+        let mut rng = SmallRng::seed_from_u64(3);
+        let book = BookmakerProfile::new("Book", 0.05)
+            .with_margin_spread(0.01)
+            .with_home_favourite_shading(0.03)
+            .with_noise(0.0);
+
+        for _ in 0..100 {
+            let margin = book.draw_margin(&mut rng);
+            assert!((0.04..=0.06).contains(&margin));
+        }
+
+        // A home favourite is shortened, taking from draw and away alike
+        let [home, draw, away] = book.view([0.5, 0.3, 0.2], &mut rng);
+        assert!((home - 0.53).abs() < 1e-9);
+        assert!((draw - 0.282).abs() < 1e-9);
+        assert!((away - 0.188).abs() < 1e-9);
+
+        // An away favourite is left alone
+        let [home, _, away] = book.view([0.25, 0.3, 0.45], &mut rng);
+        assert!((home - 0.25).abs() < 1e-9);
+        assert!((away - 0.45).abs() < 1e-9);
+
+        let defaults = BookmakerProfile::defaults();
+        assert_eq!(defaults.len(), 4);
+        assert!(defaults.iter().any(|book| book.update_latency() > chrono::Duration::zero()));
+    }
+}
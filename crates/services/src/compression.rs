@@ -0,0 +1,120 @@
+// Minimal gzip (RFC 1952) encoding and decoding for archive files
+
+use anyhow::{anyhow, bail, Result};
+use miniz_oxide::inflate::core::{decompress, inflate_flags, DecompressorOxide};
+use miniz_oxide::inflate::TINFLStatus;
+use quant_stream::crc32;
+
+/// zlib's default trade-off between speed and size
+const COMPRESSION_LEVEL: u8 = 6;
+const HEADER_LEN: usize = 10;
+const TRAILER_LEN: usize = 8;
+
+// Header flags
+const FHCRC: u8 = 0x02;
+const FEXTRA: u8 = 0x04;
+const FNAME: u8 = 0x08;
+const FCOMMENT: u8 = 0x10;
+
+/// A single gzip member holding `data`. Members can be appended to the same
+/// file; gzip readers treat them as one stream.
+pub(crate) fn gzip(data: &[u8]) -> Vec<u8> {
+    // Magic, deflate, no flags, no mtime, no extra flags, unknown OS
+    let mut out = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 255];
+    out.extend(miniz_oxide::deflate::compress_to_vec(data, COMPRESSION_LEVEL));
+    out.extend(crc32(data).to_le_bytes());
+    out.extend((data.len() as u32).to_le_bytes());
+    out
+}
+
+/// Every member of a gzip stream, concatenated
+pub(crate) fn gunzip(mut input: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    while !input.is_empty() {
+        let start = member_data_offset(input)?;
+        let (data, read) = inflate(&input[start..])?;
+        let trailer = input
+            .get(start + read..start + read + TRAILER_LEN)
+            .ok_or_else(|| anyhow!("truncated gzip trailer"))?;
+        let expected_crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+        if crc32(&data) != expected_crc {
+            bail!("gzip checksum mismatch");
+        }
+        out.extend(data);
+        input = &input[start + read + TRAILER_LEN..];
+    }
+    Ok(out)
+}
+
+/// Length of the member's header, including optional fields
+fn member_data_offset(input: &[u8]) -> Result<usize> {
+    if input.len() < HEADER_LEN || input[..3] != [0x1f, 0x8b, 8] {
+        bail!("not a gzip stream");
+    }
+    let flags = input[3];
+    let mut offset = HEADER_LEN;
+    if flags & FEXTRA != 0 {
+        let len = input.get(offset..offset + 2).ok_or_else(|| anyhow!("truncated gzip header"))?;
+        offset += 2 + u16::from_le_bytes([len[0], len[1]]) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let end = input.get(offset..)
+                .and_then(|rest| rest.iter().position(|b| *b == 0))
+                .ok_or_else(|| anyhow!("truncated gzip header"))?;
+            offset += end + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        offset += 2;
+    }
+    Ok(offset)
+}
+
+/// Inflate one raw deflate stream; returns the data and the bytes consumed
+fn inflate(input: &[u8]) -> Result<(Vec<u8>, usize)> {
+    let mut decompressor = DecompressorOxide::new();
+    let mut output = vec![0u8; (input.len() * 4).max(1024)];
+    let (mut read, mut written) = (0, 0);
+    loop {
+        let (status, in_bytes, out_bytes) = decompress(
+            &mut decompressor,
+            &input[read..],
+            &mut output,
+            written,
+            inflate_flags::TINFL_FLAG_USING_NON_WRAPPING_OUTPUT_BUF,
+        );
+        read += in_bytes;
+        written += out_bytes;
+        match status {
+            TINFLStatus::Done => {
+                output.truncate(written);
+                return Ok((output, read));
+            }
+            TINFLStatus::HasMoreOutput => output.resize(output.len() * 2, 0),
+            status => bail!("corrupt gzip data: {:?}", status),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_across_members() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let first = b"{\"id\":1}\n".repeat(500);
+        let mut stream = gzip(&first);
+        stream.extend(gzip(b"{\"id\":2}\n"));
+
+        let mut expected = first.clone();
+        expected.extend(b"{\"id\":2}\n");
+        assert_eq!(gunzip(&stream).unwrap(), expected);
+
+        let last = stream.len() - 5;
+        stream[last] ^= 0xff;
+        assert!(gunzip(&stream).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+// Realized losses since the last daily reset, and the kill switch that
+// stops new trades once they reach the daily loss limit
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, FixedOffset, NaiveTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// When the trading day rolls over. A fixed UTC offset, so a reset meant
+/// for local midnight moves by an hour across daylight saving changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyResetSchedule {
+    time: NaiveTime,
+    utc_offset: FixedOffset,
+}
+
+impl DailyResetSchedule {
+    pub fn new(time: NaiveTime, utc_offset: FixedOffset) -> Self {
+        Self { time, utc_offset }
+    }
+
+    /// Parse e.g. `"06:00"` and `"+01:00"`
+    pub fn parse(time: &str, utc_offset: &str) -> Result<Self> {
+        let time = NaiveTime::parse_from_str(time, "%H:%M")
+            .with_context(|| format!("invalid daily reset time '{}', expected HH:MM", time))?;
+        let utc_offset = utc_offset.parse::<FixedOffset>()
+            .map_err(|_| anyhow::anyhow!("invalid UTC offset '{}', expected e.g. +01:00", utc_offset))?;
+        Ok(Self::new(time, utc_offset))
+    }
+
+    /// The most recent reset at or before `at`
+    pub fn period_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let local = at.with_timezone(&self.utc_offset);
+        let mut start = local.date_naive().and_time(self.time);
+        if start > local.naive_local() {
+            start -= Duration::days(1);
+        }
+        self.utc_offset.from_local_datetime(&start)
+            .single()
+            .expect("a fixed offset has no ambiguous local times")
+            .with_timezone(&Utc)
+    }
+
+    pub fn next_reset(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        self.period_start(at) + Duration::days(1)
+    }
+}
+
+impl Default for DailyResetSchedule {
+    /// Midnight UTC
+    fn default() -> Self {
+        Self::new(NaiveTime::MIN, FixedOffset::east_opt(0).expect("zero offset is valid"))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyLossStatus {
+    pub max_daily_loss: Decimal,
+    /// Net realized loss since the last reset; winnings offset losses
+    pub realized_loss: Decimal,
+    pub remaining: Decimal,
+    /// Whether the kill switch has stopped new trades until the next reset
+    pub halted: bool,
+    pub halted_at: Option<DateTime<Utc>>,
+    pub period_start: DateTime<Utc>,
+    pub next_reset: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DailyLossTracker {
+    /// Comes from configuration, not from a saved tracker
+    #[serde(skip)]
+    schedule: DailyResetSchedule,
+    /// Realized profit/loss of each settlement over the last day, oldest
+    /// first. A day's worth covers the current period whatever the schedule.
+    settlements: VecDeque<(DateTime<Utc>, Decimal)>,
+    halted_at: Option<DateTime<Utc>>,
+}
+
+impl DailyLossTracker {
+    pub fn new(schedule: DailyResetSchedule) -> Self {
+        Self { schedule, ..Default::default() }
+    }
+
+    pub fn schedule(&self) -> DailyResetSchedule {
+        self.schedule
+    }
+
+    pub fn set_schedule(&mut self, schedule: DailyResetSchedule) {
+        self.schedule = schedule;
+    }
+
+    /// Record a settlement's realized profit, negative for a loss
+    pub fn record(&mut self, at: DateTime<Utc>, profit_loss: Decimal, now: DateTime<Utc>) {
+        if at >= now - Duration::days(1) {
+            self.settlements.push_back((at, profit_loss));
+        }
+    }
+
+    pub fn realized_loss(&self, now: DateTime<Utc>) -> Decimal {
+        let start = self.schedule.period_start(now);
+        let profit_loss: Decimal = self.settlements.iter()
+            .filter(|(at, _)| *at >= start)
+            .map(|(_, profit_loss)| *profit_loss)
+            .sum();
+        (-profit_loss).max(Decimal::ZERO)
+    }
+
+    /// Trip the kill switch if the day's losses have reached
+    /// `max_daily_loss`. Returns true only when it trips.
+    pub fn check(&mut self, max_daily_loss: Decimal, now: DateTime<Utc>) -> bool {
+        if self.is_halted(now) || self.realized_loss(now) < max_daily_loss {
+            return false;
+        }
+        self.halted_at = Some(now);
+        true
+    }
+
+    /// Halted until the next reset once tripped, even if later winnings
+    /// bring the day's loss back under the limit
+    pub fn is_halted(&self, now: DateTime<Utc>) -> bool {
+        self.halted_at.is_some_and(|at| at >= self.schedule.period_start(now))
+    }
+
+    /// Start a new trading day: forget earlier settlements and lift the kill
+    /// switch. Returns the loss the previous day closed on.
+    pub fn reset(&mut self, now: DateTime<Utc>) -> Decimal {
+        let start = self.schedule.period_start(now);
+        let closing_loss = self.realized_loss(start - Duration::nanoseconds(1));
+        while self.settlements.front().is_some_and(|(at, _)| *at < start) {
+            self.settlements.pop_front();
+        }
+        if !self.is_halted(now) {
+            self.halted_at = None;
+        }
+        closing_loss
+    }
+
+    pub fn status(&self, max_daily_loss: Decimal, now: DateTime<Utc>) -> DailyLossStatus {
+        let realized_loss = self.realized_loss(now);
+        let halted = self.is_halted(now);
+        DailyLossStatus {
+            max_daily_loss,
+            realized_loss,
+            remaining: (max_daily_loss - realized_loss).max(Decimal::ZERO),
+            halted,
+            halted_at: self.halted_at.filter(|_| halted),
+            period_start: self.schedule.period_start(now),
+            next_reset: self.schedule.next_reset(now),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 10, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_kill_switch_latches_until_reset() {
+        // 06:00 at UTC+1 is 05:00 UTC
+        let schedule = DailyResetSchedule::parse("06:00", "+01:00").unwrap();
+        assert_eq!(schedule.period_start(at(4, 59)), at(5, 0) - Duration::days(1));
+        assert_eq!(schedule.next_reset(at(5, 0)), at(5, 0) + Duration::days(1));
+
+        let mut tracker = DailyLossTracker::new(schedule);
+        let now = at(12, 0);
+        tracker.record(at(4, 0), dec!(-80), now);
+        tracker.record(at(9, 0), dec!(-30), now);
+        tracker.record(at(10, 0), dec!(20), now);
+        tracker.record(at(11, 0), dec!(-40), now);
+        assert_eq!(tracker.realized_loss(now), dec!(50));
+
+        assert!(tracker.check(dec!(50), now));
+        assert!(!tracker.check(dec!(50), now));
+        tracker.record(at(13, 0), dec!(100), now);
+        assert!(tracker.is_halted(at(14, 0)));
+
+        let next_day = at(5, 0) + Duration::days(1);
+        assert_eq!(tracker.reset(next_day), Decimal::ZERO);
+        assert!(!tracker.is_halted(next_day));
+        assert_eq!(tracker.status(dec!(50), next_day).remaining, dec!(50));
+    }
+}
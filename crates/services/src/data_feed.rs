@@ -1,10 +1,32 @@
-use quant_models::{MatchEvent, EventType, MatchStatus, Score};
-use tokio::sync::mpsc;
-use tokio::time::{interval, Duration};
-use anyhow::Result;
+use crate::generator::ScheduleSpec;
+use crate::scenario::{MatchScript, Scenario, DEFAULT_FULL_TIME_MINUTE};
+use quant_models::{Absentee, BodyPart, MatchEvent, EventType, MatchStatus, Score, ShotSituation, TeamNameNormalizer, XgInputs};
+use quant_stream::EventQueueSender;
+use tokio::sync::Notify;
+use tokio::time::Duration;
+use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
-use std::sync::{Arc, RwLock};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, RwLock};
 use dashmap::DashMap;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use tokio_util::sync::CancellationToken;
+
+/// Fastest the simulation may be run relative to `feed_interval_ms`
+pub const MAX_SPEED_MULTIPLIER: f64 = 100.0;
+/// Strongest a simulated side may be relative to an average one
+pub(crate) const MAX_TEAM_STRENGTH: f64 = 5.0;
+/// Chance per cycle of a goal in a random match between average sides
+pub(crate) const BASE_GOAL_PROBABILITY: f64 = 0.02;
+const YELLOW_CARD_PROBABILITY: f64 = 0.024;
+pub(crate) const RED_CARD_PROBABILITY: f64 = 0.006;
+/// Chance per cycle of a shot that isn't scored; simulated goals come without theirs
+const SHOT_PROBABILITY: f64 = 0.12;
+/// Half the width of the goal mouth, in metres
+const HALF_GOAL_WIDTH: f64 = 3.66;
 
 #[derive(Debug, Clone)]
 pub struct DataFeedConfig {
@@ -27,10 +49,348 @@ impl Default for DataFeedConfig {
 
 #[derive(Clone)]
 pub struct DataFeedService {
-    event_sender: mpsc::UnboundedSender<MatchEvent>,
+    event_sender: EventQueueSender,
     config: DataFeedConfig,
     active_matches: Arc<DashMap<String, MatchState>>,
+    /// Last sequence number sent per match, kept across match restarts so
+    /// numbers never repeat
+    sequences: Arc<DashMap<String, u64>>,
     simulation_data: Arc<RwLock<SimulationData>>,
+    shutdown: CancellationToken,
+    control: FeedControl,
+    /// Decides what happens in simulated matches
+    rng: Arc<Mutex<SmallRng>>,
+}
+
+/// A match to simulate, as supplied by an operator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedFixture {
+    pub match_id: String,
+    pub team_home: String,
+    pub team_away: String,
+    pub league: String,
+    /// Hold the match back until then; otherwise it kicks off straight away
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kickoff: Option<DateTime<Utc>>,
+    /// Attacking strength relative to an average side (1.0); sets how often
+    /// each team scores in a random match
+    #[serde(default = "average_strength")]
+    pub home_strength: f64,
+    #[serde(default = "average_strength")]
+    pub away_strength: f64,
+    /// Chance per cycle of a goal between average sides in a random match,
+    /// if not the feed's default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goal_rate: Option<f64>,
+    /// Chance per cycle of a red card in a random match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub red_card_rate: Option<f64>,
+    /// Play these events back exactly instead of simulating the match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<MatchScript>,
+    /// Team news announced just before kick-off. Absentees also weaken a
+    /// random match's side by their importance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub home_lineup: Option<SimulatedLineup>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub away_lineup: Option<SimulatedLineup>,
+}
+
+/// A side's line-up and who is missing from it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedLineup {
+    pub formation: String,
+    #[serde(default)]
+    pub starters: Vec<String>,
+    #[serde(default)]
+    pub absentees: Vec<Absentee>,
+}
+
+impl SimulatedLineup {
+    /// Share of the side's strength still available
+    fn strength_remaining(&self) -> f64 {
+        1.0 - self.absentees.iter().map(|absentee| absentee.importance).sum::<f64>().min(0.5)
+    }
+}
+
+fn average_strength() -> f64 {
+    1.0
+}
+
+impl SimulatedFixture {
+    pub fn new(match_id: String, team_home: String, team_away: String, league: String) -> Self {
+        Self {
+            match_id,
+            team_home,
+            team_away,
+            league,
+            kickoff: None,
+            home_strength: average_strength(),
+            away_strength: average_strength(),
+            goal_rate: None,
+            red_card_rate: None,
+            script: None,
+            home_lineup: None,
+            away_lineup: None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for strength in [self.home_strength, self.away_strength] {
+            if !(strength > 0.0 && strength <= MAX_TEAM_STRENGTH) {
+                return Err(anyhow!("team strength must be in (0, {}], got {}", MAX_TEAM_STRENGTH, strength));
+            }
+        }
+        for rate in [self.goal_rate, self.red_card_rate].into_iter().flatten() {
+            if !(0.0..=0.5).contains(&rate) {
+                return Err(anyhow!("goal and red card rates must be in [0, 0.5], got {}", rate));
+            }
+        }
+        if let Some(script) = &self.script {
+            script.validate()?;
+        }
+        for lineup in [&self.home_lineup, &self.away_lineup].into_iter().flatten() {
+            if let Some(absentee) = lineup.absentees.iter().find(|absentee| !(0.0..=1.0).contains(&absentee.importance)) {
+                return Err(anyhow!("absentee importance must be in [0, 1], got {} for {}", absentee.importance, absentee.player));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedStatus {
+    pub paused: bool,
+    pub speed_multiplier: f64,
+    pub events_sent: u64,
+    pub scheduled_matches: usize,
+    pub live_matches: usize,
+    pub finished_matches: usize,
+    pub started_at: DateTime<Utc>,
+    pub uptime_seconds: i64,
+}
+
+/// Shared handle for steering a running feed, e.g. from the API. Changes
+/// take effect on the feed's next cycle.
+#[derive(Clone)]
+pub struct FeedControl {
+    inner: Arc<FeedControlState>,
+}
+
+struct FeedControlState {
+    paused: AtomicBool,
+    speed_multiplier: RwLock<f64>,
+    /// Set by `reset`/`load_matches`; `Some(None)` restarts the current fixtures
+    pending_reset: Mutex<Option<Option<Vec<SimulatedFixture>>>>,
+    /// Changes to individual matches, applied in order after any reset
+    pending_commands: Mutex<Vec<MatchCommand>>,
+    /// Every fixture the feed has or is about to have, for rejecting
+    /// commands about unknown matches up front
+    fixture_ids: RwLock<HashSet<String>>,
+    events_sent: AtomicU64,
+    scheduled_matches: AtomicUsize,
+    live_matches: AtomicUsize,
+    finished_matches: AtomicUsize,
+    started_at: DateTime<Utc>,
+    changed: Notify,
+}
+
+impl FeedControl {
+    pub fn new(speed_multiplier: f64) -> Self {
+        Self {
+            inner: Arc::new(FeedControlState {
+                paused: AtomicBool::new(false),
+                speed_multiplier: RwLock::new(speed_multiplier.clamp(f64::EPSILON, MAX_SPEED_MULTIPLIER)),
+                pending_reset: Mutex::new(None),
+                pending_commands: Mutex::new(Vec::new()),
+                fixture_ids: RwLock::new(HashSet::new()),
+                events_sent: AtomicU64::new(0),
+                scheduled_matches: AtomicUsize::new(0),
+                live_matches: AtomicUsize::new(0),
+                finished_matches: AtomicUsize::new(0),
+                started_at: Utc::now(),
+                changed: Notify::new(),
+            }),
+        }
+    }
+
+    pub fn pause(&self) {
+        self.inner.paused.store(true, Ordering::SeqCst);
+        self.inner.changed.notify_one();
+    }
+
+    pub fn resume(&self) {
+        self.inner.paused.store(false, Ordering::SeqCst);
+        self.inner.changed.notify_one();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner.paused.load(Ordering::SeqCst)
+    }
+
+    /// Run the feed this many times faster than its configured interval
+    pub fn set_speed(&self, multiplier: f64) -> Result<()> {
+        if !(multiplier > 0.0 && multiplier <= MAX_SPEED_MULTIPLIER) {
+            return Err(anyhow!("speed multiplier must be in (0, {}], got {}", MAX_SPEED_MULTIPLIER, multiplier));
+        }
+        *self.inner.speed_multiplier.write().unwrap() = multiplier;
+        self.inner.changed.notify_one();
+        Ok(())
+    }
+
+    pub fn speed(&self) -> f64 {
+        *self.inner.speed_multiplier.read().unwrap()
+    }
+
+    /// Restart every simulated match from kick-off
+    pub fn reset(&self) {
+        self.inner.pending_reset.lock().unwrap().get_or_insert(None);
+        self.inner.changed.notify_one();
+    }
+
+    /// Replace the simulated matches; they kick off on the next cycle. Team
+    /// names are canonicalized so they line up with other sources.
+    pub fn load_matches(&self, fixtures: Vec<SimulatedFixture>) -> Result<()> {
+        let fixtures = Self::prepare_fixtures(fixtures, &HashSet::new())?;
+        // Commands queued against the old fixtures no longer apply
+        self.inner.pending_commands.lock().unwrap().clear();
+        self.set_fixture_ids(fixtures.iter().map(|fixture| fixture.match_id.clone()));
+        *self.inner.pending_reset.lock().unwrap() = Some(Some(fixtures));
+        self.inner.changed.notify_one();
+        Ok(())
+    }
+
+    /// Add matches alongside the running ones without restarting anything
+    pub fn add_matches(&self, fixtures: Vec<SimulatedFixture>) -> Result<()> {
+        let mut known = self.inner.fixture_ids.write().unwrap();
+        let fixtures = Self::prepare_fixtures(fixtures, &known)?;
+        known.extend(fixtures.iter().map(|fixture| fixture.match_id.clone()));
+        self.queue(MatchCommand::Add(fixtures));
+        Ok(())
+    }
+
+    /// Blow the final whistle now, on the current score. A match that hasn't
+    /// kicked off is cancelled instead.
+    pub fn end_match(&self, match_id: &str) -> Result<()> {
+        self.require_match(match_id)?;
+        self.queue(MatchCommand::End(match_id.to_string()));
+        Ok(())
+    }
+
+    /// Move a match's clock on, kicking it off first if need be. Scripted
+    /// events in the skipped minutes are all sent; a random match just skips
+    /// ahead, at most to full time.
+    pub fn fast_forward(&self, match_id: &str, minutes: u8) -> Result<()> {
+        if minutes == 0 {
+            return Err(anyhow!("fast forward needs at least one minute"));
+        }
+        self.require_match(match_id)?;
+        self.queue(MatchCommand::FastForward { match_id: match_id.to_string(), minutes });
+        Ok(())
+    }
+
+    pub fn has_match(&self, match_id: &str) -> bool {
+        self.inner.fixture_ids.read().unwrap().contains(match_id)
+    }
+
+    /// Replace the simulated matches with a generated schedule; the same seed
+    /// draws the same fixtures. Returns the fixtures generated.
+    pub fn load_schedule(&self, spec: &ScheduleSpec, seed: u64) -> Result<Vec<SimulatedFixture>> {
+        spec.validate()?;
+        let fixtures = spec.generate(seed);
+        self.load_matches(fixtures.clone())?;
+        tracing::info!("🗓️  Generated schedule '{}' with {} matches", spec.name, fixtures.len());
+        Ok(fixtures)
+    }
+
+    /// Replace the simulated matches with a scenario's scripted fixtures
+    pub fn load_scenario(&self, scenario: &Scenario) -> Result<()> {
+        scenario.validate()?;
+        self.load_matches(scenario.matches.clone())?;
+        tracing::info!("🎬 Loaded scenario '{}' with {} matches", scenario.name, scenario.matches.len());
+        Ok(())
+    }
+
+    pub fn status(&self) -> FeedStatus {
+        FeedStatus {
+            paused: self.is_paused(),
+            speed_multiplier: self.speed(),
+            events_sent: self.inner.events_sent.load(Ordering::Relaxed),
+            scheduled_matches: self.inner.scheduled_matches.load(Ordering::Relaxed),
+            live_matches: self.inner.live_matches.load(Ordering::Relaxed),
+            finished_matches: self.inner.finished_matches.load(Ordering::Relaxed),
+            started_at: self.inner.started_at,
+            uptime_seconds: (Utc::now() - self.inner.started_at).num_seconds(),
+        }
+    }
+
+    fn take_pending_reset(&self) -> Option<Option<Vec<SimulatedFixture>>> {
+        self.inner.pending_reset.lock().unwrap().take()
+    }
+
+    fn take_pending_commands(&self) -> Vec<MatchCommand> {
+        std::mem::take(&mut *self.inner.pending_commands.lock().unwrap())
+    }
+
+    fn queue(&self, command: MatchCommand) {
+        self.inner.pending_commands.lock().unwrap().push(command);
+        self.inner.changed.notify_one();
+    }
+
+    fn require_match(&self, match_id: &str) -> Result<()> {
+        if !self.has_match(match_id) {
+            return Err(anyhow!("no simulated match {}", match_id));
+        }
+        Ok(())
+    }
+
+    fn set_fixture_ids(&self, ids: impl IntoIterator<Item = String>) {
+        *self.inner.fixture_ids.write().unwrap() = ids.into_iter().collect();
+    }
+
+    /// Validate fixtures and canonicalize team names so they line up with
+    /// other sources. Match ids must be new and unique.
+    fn prepare_fixtures(mut fixtures: Vec<SimulatedFixture>, known: &HashSet<String>) -> Result<Vec<SimulatedFixture>> {
+        if fixtures.is_empty() {
+            return Err(anyhow!("at least one fixture is required"));
+        }
+        let mut ids = HashSet::new();
+        for fixture in &fixtures {
+            fixture.validate()?;
+            if known.contains(&fixture.match_id) || !ids.insert(fixture.match_id.as_str()) {
+                return Err(anyhow!("duplicate match id {}", fixture.match_id));
+            }
+        }
+        let normalizer = TeamNameNormalizer::new();
+        for fixture in &mut fixtures {
+            fixture.team_home = normalizer.normalize(&fixture.team_home);
+            fixture.team_away = normalizer.normalize(&fixture.team_away);
+        }
+        Ok(fixtures)
+    }
+}
+
+/// A change to one part of a running simulation
+#[derive(Debug, Clone)]
+enum MatchCommand {
+    Add(Vec<SimulatedFixture>),
+    End(String),
+    FastForward { match_id: String, minutes: u8 },
+}
+
+impl Default for FeedControl {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+fn nil_nil() -> Score {
+    Score {
+        home: 0,
+        away: 0,
+        half_time_home: None,
+        half_time_away: None,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -58,35 +418,110 @@ struct SimulatedMatch {
     pub team_home: String,
     pub team_away: String,
     pub league: String,
-    pub events: Vec<SimulatedEvent>,
+    pub kickoff: Option<DateTime<Utc>>,
+    pub home_strength: f64,
+    pub away_strength: f64,
+    pub goal_rate: f64,
+    pub red_card_rate: f64,
+    /// Scripted matches advance a minute per cycle and only do what the
+    /// script says; the rest are random
+    pub script: Option<MatchScript>,
+    pub home_lineup: Option<SimulatedLineup>,
+    pub away_lineup: Option<SimulatedLineup>,
 }
 
-#[derive(Debug, Clone)]
-struct SimulatedEvent {
-    pub minute: u8,
-    pub event_type: EventType,
-    pub team: Option<String>,
-    pub player: Option<String>,
+impl From<SimulatedFixture> for SimulatedMatch {
+    fn from(fixture: SimulatedFixture) -> Self {
+        let remaining = |lineup: &Option<SimulatedLineup>| lineup.as_ref().map_or(1.0, SimulatedLineup::strength_remaining);
+        Self {
+            home_strength: fixture.home_strength * remaining(&fixture.home_lineup),
+            away_strength: fixture.away_strength * remaining(&fixture.away_lineup),
+            match_id: fixture.match_id,
+            team_home: fixture.team_home,
+            team_away: fixture.team_away,
+            league: fixture.league,
+            kickoff: fixture.kickoff,
+            goal_rate: fixture.goal_rate.unwrap_or(BASE_GOAL_PROBABILITY),
+            red_card_rate: fixture.red_card_rate.unwrap_or(RED_CARD_PROBABILITY),
+            script: fixture.script,
+            home_lineup: fixture.home_lineup,
+            away_lineup: fixture.away_lineup,
+        }
+    }
+}
+
+impl SimulatedMatch {
+    fn event(&self, event_type: EventType, season: &str) -> MatchEvent {
+        MatchEvent::new(
+            self.match_id.clone(),
+            event_type,
+            self.team_home.clone(),
+            self.team_away.clone(),
+            self.league.clone(),
+            season.to_string(),
+        )
+    }
+
+    /// Each side's team news, for announcing ahead of kick-off
+    fn lineup_events(&self, season: &str) -> Vec<MatchEvent> {
+        [(&self.team_home, &self.home_lineup), (&self.team_away, &self.away_lineup)]
+            .into_iter()
+            .filter_map(|(team, lineup)| {
+                let lineup = lineup.as_ref()?;
+                Some(self.event(EventType::Lineup {
+                    team: team.clone(),
+                    formation: lineup.formation.clone(),
+                    starters: lineup.starters.clone(),
+                    absentees: lineup.absentees.clone(),
+                }, season))
+            })
+            .collect()
+    }
 }
 
 impl DataFeedService {
     pub fn new(
-        event_sender: mpsc::UnboundedSender<MatchEvent>,
+        event_sender: EventQueueSender,
         config: Option<DataFeedConfig>,
     ) -> Self {
         let config = config.unwrap_or_default();
+        let matches = Self::generate_sample_matches();
+        let control = FeedControl::new(config.simulation_speed_multiplier);
+        control.set_fixture_ids(matches.iter().map(|fixture| fixture.match_id.clone()));
         let simulation_data = Arc::new(RwLock::new(SimulationData {
-            matches: Self::generate_sample_matches(),
+            matches,
             current_index: 0,
         }));
         
         Self {
             event_sender,
+            control,
             config,
             active_matches: Arc::new(DashMap::new()),
+            sequences: Arc::new(DashMap::new()),
             simulation_data,
+            shutdown: CancellationToken::new(),
+            rng: Arc::new(Mutex::new(SmallRng::from_entropy())),
         }
     }
+
+    /// Simulate matches from a generator seeded with `seed`, so runs can be repeated
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Arc::new(Mutex::new(SmallRng::seed_from_u64(seed)));
+        self
+    }
+
+    /// Handle for pausing, speeding up or reloading the feed while it runs
+    pub fn control(&self) -> FeedControl {
+        self.control.clone()
+    }
+
+    /// Stop producing events once the token is cancelled. `start` returns and,
+    /// once every clone is dropped, the event channel closes so consumers drain.
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
     
     pub async fn start(&self) -> Result<()> {
         tracing::info!("🎯 Starting DataFeedService");
@@ -94,10 +529,29 @@ impl DataFeedService {
         tracing::info!("📊 Max events per batch: {}", self.config.max_events_per_batch);
         tracing::info!("🎮 Simulation mode: {}", self.config.enable_simulation);
         
-        let mut ticker = interval(Duration::from_millis(self.config.feed_interval_ms));
-        
         loop {
-            ticker.tick().await;
+            // Re-read the speed every cycle so changes apply straight away
+            let cycle = Duration::from_secs_f64(self.config.feed_interval_ms as f64 / 1000.0 / self.control.speed());
+            tokio::select! {
+                _ = tokio::time::sleep(cycle) => {}
+                _ = self.control.inner.changed.notified() => continue,
+                _ = self.shutdown.cancelled() => {
+                    tracing::info!("🛑 DataFeedService stopped producing events");
+                    return Ok(());
+                }
+            }
+
+            if let Some(fixtures) = self.control.take_pending_reset() {
+                self.reset_simulation(fixtures);
+            }
+            for command in self.control.take_pending_commands() {
+                if let Err(e) = self.apply_command(command).await {
+                    tracing::error!("❌ Error applying simulation command: {}", e);
+                }
+            }
+            if self.control.is_paused() {
+                continue;
+            }
             
             if let Err(e) = self.process_feed_cycle().await {
                 tracing::error!("❌ Error in feed cycle: {}", e);
@@ -117,6 +571,33 @@ impl DataFeedService {
         Ok(())
     }
     
+    /// Drop all match progress, optionally swapping in new fixtures
+    fn reset_simulation(&self, fixtures: Option<Vec<SimulatedFixture>>) {
+        let mut simulation_data = self.simulation_data.write().unwrap();
+        if let Some(fixtures) = fixtures {
+            simulation_data.matches = fixtures.into_iter().map(SimulatedMatch::from).collect();
+        }
+        simulation_data.current_index = 0;
+        self.active_matches.clear();
+
+        tracing::info!("🔄 Simulation reset with {} matches", simulation_data.matches.len());
+    }
+
+    fn update_status_counts(&self, total_matches: usize) {
+        let (mut live, mut finished) = (0, 0);
+        for entry in self.active_matches.iter() {
+            match entry.value().status {
+                MatchStatus::Finished | MatchStatus::Cancelled => finished += 1,
+                MatchStatus::Scheduled => {}
+                _ => live += 1,
+            }
+        }
+        let inner = &self.control.inner;
+        inner.live_matches.store(live, Ordering::Relaxed);
+        inner.finished_matches.store(finished, Ordering::Relaxed);
+        inner.scheduled_matches.store(total_matches.saturating_sub(live + finished), Ordering::Relaxed);
+    }
+
     async fn process_simulation_events(&self) -> Result<()> {
         let mut events_sent = 0;
         let max_events = self.config.max_events_per_batch;
@@ -136,35 +617,28 @@ impl DataFeedService {
                 break;
             }
             
-            // Check if match is already active
-            let mut match_state = self.active_matches.entry(match_data.match_id.clone())
-                .or_insert_with(|| MatchState {
-                    match_id: match_data.match_id.clone(),
-                    team_home: match_data.team_home.clone(),
-                    team_away: match_data.team_away.clone(),
-                    league: match_data.league.clone(),
-                    season: "2024-25".to_string(),
-                    status: MatchStatus::Scheduled,
-                    score: None,
-                    minute: 0,
-                    last_event_time: Utc::now(),
-                });
+            let mut match_state = self.match_state_entry(match_data);
+            // Matches with a kick-off time wait for it
+            if matches!(match_state.status, MatchStatus::Scheduled)
+                && match_data.kickoff.is_some_and(|kickoff| kickoff > Utc::now()) {
+                continue;
+            }
             
+            // Team news goes out with kick-off
+            let mut events = if matches!(match_state.status, MatchStatus::Scheduled) {
+                match_data.lineup_events(&match_state.season)
+            } else {
+                Vec::new()
+            };
             // Generate events based on match progression
-            if let Some(event) = self.generate_next_event(&match_data, &match_state).await? {
-                self.send_event(event).await?;
-                events_sent += 1;
-                
-                // Update match state
-                match_state.last_event_time = Utc::now();
-                match_state.minute = match_state.minute.saturating_add(1);
-                
-                if match_state.minute >= 90 {
-                    match_state.status = MatchStatus::Finished;
-                }
-            }
+            events.extend(match &match_data.script {
+                Some(script) => Self::scripted_events(match_data, script, &mut match_state),
+                None => self.generate_next_event(match_data, &match_state).await?.into_iter().collect(),
+            });
+            events_sent += self.dispatch(match_data, &mut match_state, events).await?;
         }
         
+        self.update_status_counts(matches.len());
         if events_sent > 0 {
             tracing::debug!("📡 Sent {} simulated events", events_sent);
         }
@@ -172,13 +646,191 @@ impl DataFeedService {
         Ok(())
     }
     
+    fn match_state_entry(&self, match_data: &SimulatedMatch) -> dashmap::mapref::one::RefMut<'_, String, MatchState> {
+        self.active_matches.entry(match_data.match_id.clone())
+            .or_insert_with(|| MatchState {
+                match_id: match_data.match_id.clone(),
+                team_home: match_data.team_home.clone(),
+                team_away: match_data.team_away.clone(),
+                league: match_data.league.clone(),
+                season: "2024-25".to_string(),
+                status: MatchStatus::Scheduled,
+                score: None,
+                minute: 0,
+                last_event_time: Utc::now(),
+            })
+    }
+
+    /// Send a match's events and move its state on; returns how many were
+    /// sent, counting the MatchEnd that follows full time
+    async fn dispatch(&self, match_data: &SimulatedMatch, match_state: &mut MatchState, events: Vec<MatchEvent>) -> Result<usize> {
+        let mut events_sent = 0;
+        for event in events {
+            let event_type = event.event_type.clone();
+            if event.score.is_some() {
+                match_state.score = event.score.clone();
+            }
+            self.send_event(event).await?;
+            events_sent += 1;
+            
+            // Update match state; scripted matches keep their own clock
+            match_state.last_event_time = Utc::now();
+            if match_data.script.is_none() && !matches!(event_type, EventType::Lineup { .. }) {
+                match_state.minute = match_state.minute.saturating_add(1);
+            }
+            
+            match event_type {
+                EventType::MatchStart => match_state.status = MatchStatus::Live,
+                EventType::FullTime => {
+                    match_state.status = MatchStatus::Finished;
+                    // Close the match once the result is out
+                    let match_end = match_data.event(EventType::MatchEnd, &match_state.season)
+                        .with_status(MatchStatus::Finished);
+                    self.send_event(match_end).await?;
+                    events_sent += 1;
+                }
+                _ => {}
+            }
+        }
+        Ok(events_sent)
+    }
+
+    async fn apply_command(&self, command: MatchCommand) -> Result<()> {
+        match command {
+            MatchCommand::Add(fixtures) => {
+                let mut simulation_data = self.simulation_data.write().unwrap();
+                simulation_data.matches.extend(fixtures.into_iter().map(SimulatedMatch::from));
+                tracing::info!("➕ Simulation now has {} matches", simulation_data.matches.len());
+                Ok(())
+            }
+            MatchCommand::End(match_id) => self.end_match(&match_id).await,
+            MatchCommand::FastForward { match_id, minutes } => self.fast_forward(&match_id, minutes).await,
+        }
+    }
+
+    fn simulated_match(&self, match_id: &str) -> Result<SimulatedMatch> {
+        self.simulation_data.read().unwrap().matches.iter()
+            .find(|match_data| match_data.match_id == match_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("no simulated match {}", match_id))
+    }
+
+    async fn end_match(&self, match_id: &str) -> Result<()> {
+        let match_data = self.simulated_match(match_id)?;
+        let mut match_state = self.match_state_entry(&match_data);
+        match match_state.status {
+            MatchStatus::Scheduled => {
+                match_state.status = MatchStatus::Cancelled;
+                // Bets placed before kick-off are voided on this
+                let cancelled = match_data.event(EventType::MatchEnd, &match_state.season)
+                    .with_status(MatchStatus::Cancelled);
+                self.send_event(cancelled).await?;
+                tracing::info!("🚫 Cancelled {} before kick-off", match_id);
+            }
+            MatchStatus::Finished | MatchStatus::Cancelled => {}
+            _ => {
+                let full_time = match_data.event(EventType::FullTime, &match_state.season)
+                    .with_status(MatchStatus::Finished)
+                    .with_score(match_state.score.clone().unwrap_or_else(nil_nil));
+                self.dispatch(&match_data, &mut match_state, vec![full_time]).await?;
+                tracing::info!("🏁 Ended {} early at minute {}", match_id, match_state.minute);
+            }
+        }
+        Ok(())
+    }
+
+    async fn fast_forward(&self, match_id: &str, minutes: u8) -> Result<()> {
+        let match_data = self.simulated_match(match_id)?;
+        let mut match_state = self.match_state_entry(&match_data);
+        if matches!(match_state.status, MatchStatus::Finished | MatchStatus::Cancelled) {
+            return Err(anyhow!("match {} is already over", match_id));
+        }
+        if matches!(match_state.status, MatchStatus::Scheduled) {
+            let mut events = match_data.lineup_events(&match_state.season);
+            events.push(match_data.event(EventType::MatchStart, &match_state.season).with_status(MatchStatus::Live));
+            self.dispatch(&match_data, &mut match_state, events).await?;
+        }
+
+        match &match_data.script {
+            Some(script) => {
+                for _ in 0..minutes {
+                    if matches!(match_state.status, MatchStatus::Finished) {
+                        break;
+                    }
+                    let events = Self::scripted_events(&match_data, script, &mut match_state);
+                    self.dispatch(&match_data, &mut match_state, events).await?;
+                }
+            }
+            None => {
+                // Full time follows on the next cycle
+                let from = match_state.minute;
+                match_state.minute = from.saturating_add(minutes).min(DEFAULT_FULL_TIME_MINUTE);
+                if from < 45 && match_state.minute >= 45 {
+                    let half_time = match_data.event(EventType::HalfTime, &match_state.season)
+                        .with_status(MatchStatus::HalfTime);
+                    self.send_event(half_time).await?;
+                }
+            }
+        }
+        tracing::info!("⏩ Fast-forwarded {} to minute {}", match_id, match_state.minute);
+        Ok(())
+    }
+
+    /// Advance a scripted match by one minute and return what happens in it:
+    /// kick-off first, then the minute's scripted events, half time at 45 and
+    /// full time at the script's final minute
+    fn scripted_events(match_data: &SimulatedMatch, script: &MatchScript, match_state: &mut MatchState) -> Vec<MatchEvent> {
+        let new_event = |event_type: EventType, status: MatchStatus| MatchEvent::new(
+            match_data.match_id.clone(),
+            event_type,
+            match_data.team_home.clone(),
+            match_data.team_away.clone(),
+            match_data.league.clone(),
+            match_state.season.clone(),
+        ).with_status(status);
+
+        match match_state.status {
+            MatchStatus::Finished => return vec![],
+            MatchStatus::Scheduled => return vec![new_event(EventType::MatchStart, MatchStatus::Live)],
+            _ => {}
+        }
+
+        let minute = match_state.minute.saturating_add(1);
+        let mut score = match_state.score.clone().unwrap_or_else(nil_nil);
+        let mut events = Vec::new();
+        for scripted in script.events_at(minute) {
+            let event_type = scripted.event_type(&match_data.team_home, &match_data.team_away);
+            let mut event = new_event(event_type, MatchStatus::Live);
+            if let EventType::Goal { ref team, .. } = event.event_type {
+                if team == &match_data.team_home {
+                    score.home += 1;
+                } else {
+                    score.away += 1;
+                }
+                event = event.with_score(score.clone());
+            }
+            if let Some(metadata) = scripted.metadata() {
+                event.metadata = metadata;
+            }
+            events.push(event);
+        }
+        if minute == 45 {
+            events.push(new_event(EventType::HalfTime, MatchStatus::HalfTime));
+        }
+        if minute >= script.full_time {
+            events.push(new_event(EventType::FullTime, MatchStatus::Finished).with_score(score));
+        }
+
+        match_state.minute = minute;
+        events
+    }
+    
     async fn generate_next_event(
         &self,
         match_data: &SimulatedMatch,
         match_state: &MatchState,
     ) -> Result<Option<MatchEvent>> {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+        let mut rng = self.rng.lock().unwrap_or_else(|e| e.into_inner());
         
         // Skip if match is finished
         if matches!(match_state.status, MatchStatus::Finished) {
@@ -203,9 +855,13 @@ impl DataFeedService {
         if matches!(match_state.status, MatchStatus::Live) {
             let event_probability = rng.gen::<f64>();
             
-            let event_type = if event_probability < 0.02 {
-                // 2% chance of goal
-                let scoring_team = if rng.gen_bool(0.5) {
+            // Goals are split between the sides by strength, and stronger
+            // pairings score more often overall
+            let total_strength = match_data.home_strength + match_data.away_strength;
+            let goal_probability = match_data.goal_rate * total_strength / 2.0;
+            let card_probability = YELLOW_CARD_PROBABILITY + match_data.red_card_rate;
+            let event_type = if event_probability < goal_probability {
+                let scoring_team = if rng.gen_bool(match_data.home_strength / total_strength) {
                     match_data.team_home.clone()
                 } else {
                     match_data.team_away.clone()
@@ -215,8 +871,7 @@ impl DataFeedService {
                     player: Some(format!("Player{}", rng.gen_range(1..=23))),
                     minute: match_state.minute,
                 }
-            } else if event_probability < 0.05 {
-                // 3% chance of card
+            } else if event_probability < goal_probability + card_probability {
                 let team = if rng.gen_bool(0.5) {
                     match_data.team_home.clone()
                 } else {
@@ -225,13 +880,35 @@ impl DataFeedService {
                 EventType::Card {
                     team,
                     player: format!("Player{}", rng.gen_range(1..=23)),
-                    card_type: if rng.gen_bool(0.8) {
+                    card_type: if rng.gen_bool(YELLOW_CARD_PROBABILITY / card_probability) {
                         quant_models::CardType::Yellow
                     } else {
                         quant_models::CardType::Red
                     },
                     minute: match_state.minute,
                 }
+            } else if event_probability < goal_probability + card_probability + SHOT_PROBABILITY {
+                let team = if rng.gen_bool(match_data.home_strength / total_strength) {
+                    match_data.team_home.clone()
+                } else {
+                    match_data.team_away.clone()
+                };
+                let situation = match rng.gen_range(0..100) {
+                    0 => ShotSituation::Penalty,
+                    1..=10 => ShotSituation::Counter,
+                    11..=30 => ShotSituation::SetPiece,
+                    _ => ShotSituation::OpenPlay,
+                };
+                let distance = if situation == ShotSituation::Penalty { 11.0 } else { rng.gen_range(5.0..30.0) };
+                // Shots from wide of the posts see less of the goal than central ones
+                let angle = 2.0 * (HALF_GOAL_WIDTH / distance).atan().to_degrees() * rng.gen_range(0.4..=1.0);
+                let body_part = if distance < 14.0 && rng.gen_bool(0.3) { BodyPart::Head } else { BodyPart::Foot };
+                EventType::Shot {
+                    team,
+                    player: Some(format!("Player{}", rng.gen_range(1..=23))),
+                    minute: match_state.minute,
+                    xg_inputs: XgInputs { distance, angle, body_part, situation },
+                }
             } else if match_state.minute == 45 {
                 EventType::HalfTime
             } else if match_state.minute >= 90 {
@@ -292,39 +969,33 @@ impl DataFeedService {
     }
     
     async fn send_event(&self, event: MatchEvent) -> Result<()> {
-        if let Err(_) = self.event_sender.send(event.clone()) {
+        let sequence = {
+            let mut last = self.sequences.entry(event.match_id.clone()).or_insert(0);
+            *last += 1;
+            *last
+        };
+        let event = event.with_sequence(sequence);
+        if let Err(_) = self.event_sender.send(event.clone()).await {
             tracing::error!("❌ Failed to send event - receiver dropped");
             return Err(anyhow::anyhow!("Event receiver has been dropped"));
         }
         
+        self.control.inner.events_sent.fetch_add(1, Ordering::Relaxed);
         tracing::debug!("📤 Sent event: {} - {:?}", event.match_id, event.event_type);
         Ok(())
     }
     
     fn generate_sample_matches() -> Vec<SimulatedMatch> {
-        vec![
-            SimulatedMatch {
-                match_id: "epl_match_001".to_string(),
-                team_home: "Arsenal".to_string(),
-                team_away: "Chelsea".to_string(),
-                league: "Premier League".to_string(),
-                events: vec![],
-            },
-            SimulatedMatch {
-                match_id: "epl_match_002".to_string(),
-                team_home: "Manchester City".to_string(),
-                team_away: "Liverpool".to_string(),
-                league: "Premier League".to_string(),
-                events: vec![],
-            },
-            SimulatedMatch {
-                match_id: "laliga_match_001".to_string(),
-                team_home: "Real Madrid".to_string(),
-                team_away: "Barcelona".to_string(),
-                league: "La Liga".to_string(),
-                events: vec![],
-            },
+        [
+            ("epl_match_001", "Arsenal", "Chelsea", "Premier League"),
+            ("epl_match_002", "Manchester City", "Liverpool", "Premier League"),
+            ("laliga_match_001", "Real Madrid", "Barcelona", "La Liga"),
         ]
+        .into_iter()
+        .map(|(match_id, team_home, team_away, league)| {
+            SimulatedFixture::new(match_id.to_string(), team_home.to_string(), team_away.to_string(), league.to_string()).into()
+        })
+        .collect()
     }
     
     pub fn get_active_matches(&self) -> Vec<String> {
@@ -337,4 +1008,138 @@ impl DataFeedService {
     pub fn get_match_state(&self, match_id: &str) -> Option<MatchState> {
         self.active_matches.get(match_id).map(|entry| entry.value().clone())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::{ScenarioSide, ScriptedAction, ScriptedEvent};
+    use quant_models::AbsenceReason;
+    use quant_stream::{event_queue, QueueConfig};
+
+    #[tokio::test]
+    async fn test_control_loads_fixtures_and_pauses() {
+        let (sender, mut receiver) = event_queue(&QueueConfig::default(), None);
+        let config = DataFeedConfig { feed_interval_ms: 5, ..Default::default() };
+        let shutdown = CancellationToken::new();
+        let feed = DataFeedService::new(sender, Some(config)).with_shutdown(shutdown.clone());
+        let control = feed.control();
+
+        control.load_matches(vec![SimulatedFixture::new(
+            "cup_final".to_string(),
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "FA Cup".to_string(),
+        )]).unwrap();
+        assert!(control.set_speed(0.0).is_err());
+        let handle = tokio::spawn(async move { feed.start().await });
+
+        let first = tokio::time::timeout(Duration::from_secs(1), receiver.recv()).await.unwrap().unwrap();
+        assert_eq!(first.match_id, "cup_final");
+        assert_eq!(first.event_type, EventType::MatchStart);
+
+        control.pause();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        while receiver.try_recv().is_some() {}
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(receiver.try_recv().is_none());
+
+        let status = control.status();
+        assert!(status.paused);
+        assert!(status.events_sent >= 1);
+        assert_eq!(status.live_matches, 1);
+
+        shutdown.cancel();
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_matches_injected_ended_and_fast_forwarded_live() {
+        let (sender, mut receiver) = event_queue(&QueueConfig::default(), None);
+        let config = DataFeedConfig { feed_interval_ms: 20, ..Default::default() };
+        let shutdown = CancellationToken::new();
+        let feed = DataFeedService::new(sender, Some(config)).with_shutdown(shutdown.clone());
+        let control = feed.control();
+        let fixture = |match_id: &str| SimulatedFixture::new(
+            match_id.to_string(), "Arsenal".to_string(), "Chelsea".to_string(), "FA Cup".to_string(),
+        );
+        control.load_matches(vec![fixture("random")]).unwrap();
+        let handle = tokio::spawn(async move { feed.start().await });
+        let first = tokio::time::timeout(Duration::from_secs(1), receiver.recv()).await.unwrap().unwrap();
+        assert_eq!((first.match_id.as_str(), &first.event_type), ("random", &EventType::MatchStart));
+
+        let mut scripted = fixture("scripted");
+        scripted.script = Some(MatchScript {
+            full_time: 90,
+            events: vec![ScriptedEvent {
+                minute: 30,
+                action: ScriptedAction::Goal { team: ScenarioSide::Away, player: None },
+            }],
+        });
+        scripted.home_lineup = Some(SimulatedLineup {
+            formation: "4-4-2".to_string(),
+            starters: Vec::new(),
+            absentees: vec![Absentee { player: "Saka".to_string(), reason: AbsenceReason::Injury, importance: 0.1 }],
+        });
+        let mut later = fixture("later");
+        later.kickoff = Some(Utc::now() + chrono::Duration::hours(1));
+        assert!(control.add_matches(vec![fixture("random")]).is_err());
+        assert!(control.add_matches(vec![SimulatedFixture { home_strength: 0.0, ..fixture("weak") }]).is_err());
+        control.add_matches(vec![scripted, later]).unwrap();
+        control.fast_forward("scripted", 40).unwrap();
+        control.end_match("random").unwrap();
+        assert!(control.end_match("unknown").is_err());
+
+        let mut events = Vec::new();
+        while !events.iter().any(|event: &MatchEvent| event.match_id == "random" && event.event_type == EventType::MatchEnd) {
+            events.push(tokio::time::timeout(Duration::from_secs(1), receiver.recv()).await.unwrap().unwrap());
+        }
+        shutdown.cancel();
+        handle.await.unwrap().unwrap();
+
+        let scripted: Vec<&MatchEvent> = events.iter().filter(|event| event.match_id == "scripted").collect();
+        assert!(matches!(&scripted[0].event_type, EventType::Lineup { team, formation, .. } if team == "Arsenal" && formation == "4-4-2"));
+        assert_eq!(scripted[1].event_type, EventType::MatchStart);
+        let goal = events.iter().find(|event| event.match_id == "scripted" && matches!(event.event_type, EventType::Goal { .. }));
+        assert_eq!(goal.and_then(|event| event.score.as_ref()).map(|s| (s.home, s.away)), Some((0, 1)));
+        assert!(events.iter().any(|event| event.match_id == "random" && event.event_type == EventType::FullTime));
+        assert!(events.iter().all(|event| event.match_id != "later"));
+    }
+
+    #[tokio::test]
+    async fn test_scenario_played_back_exactly() {
+        let scenario = Scenario::from_toml_str(include_str!("../../../examples/scenarios/late_equalizer.toml")).unwrap();
+        let (sender, mut receiver) = event_queue(&QueueConfig::default(), None);
+        let config = DataFeedConfig { feed_interval_ms: 1, ..Default::default() };
+        let shutdown = CancellationToken::new();
+        let feed = DataFeedService::new(sender, Some(config)).with_shutdown(shutdown.clone());
+        feed.control().load_scenario(&scenario).unwrap();
+        let handle = tokio::spawn(async move { feed.start().await });
+
+        let mut events = Vec::new();
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(5), receiver.recv()).await.unwrap().unwrap();
+            let done = event.event_type == EventType::MatchEnd;
+            events.push(event);
+            if done {
+                break;
+            }
+        }
+        shutdown.cancel();
+        handle.await.unwrap().unwrap();
+
+        let kinds: Vec<String> = events.iter()
+            .map(|event| match &event.event_type {
+                EventType::Goal { team, minute, .. } => format!("goal {} {}", team, minute),
+                EventType::Card { team, minute, .. } => format!("card {} {}", team, minute),
+                EventType::Substitution { player_in, minute, .. } => format!("sub {} {}", player_in, minute),
+                other => format!("{:?}", other),
+            })
+            .collect();
+        assert_eq!(kinds, [
+            "MatchStart", "goal Arsenal 12", "HalfTime", "card Chelsea 58", "sub Nkunku 75",
+            "goal Chelsea 93", "FullTime", "MatchEnd",
+        ]);
+        let full_time = &events[events.len() - 2];
+        assert_eq!(full_time.score.as_ref().map(|s| (s.home, s.away)), Some((1, 1)));
+    }
+}
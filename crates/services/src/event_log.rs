@@ -0,0 +1,115 @@
+// Lists match events for the API: from the stored event log once events are
+// persisted, otherwise from the recent events held in memory
+
+use quant_db::{EventRepository, Repository};
+use quant_models::{EventCursor, EventQuery, MatchEvent};
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// One page of an event listing, newest first
+#[derive(Debug, Clone, Serialize)]
+pub struct EventPage {
+    pub events: Vec<MatchEvent>,
+    /// Pass back as the next query's cursor; absent on the last page
+    pub next_cursor: Option<String>,
+}
+
+pub struct EventLog {
+    repository: Option<Arc<Repository>>,
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        Self { repository: None }
+    }
+
+    pub fn with_repository(mut self, repository: Arc<Repository>) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    pub fn is_persistent(&self) -> bool {
+        self.repository.is_some()
+    }
+
+    /// Events matching `query`, from the database when there is one and
+    /// from `recent` otherwise
+    pub async fn list(&self, query: &EventQuery, recent: &[MatchEvent]) -> Result<EventPage> {
+        // One more than asked for tells whether there is another page
+        let peek = EventQuery { limit: query.limit + 1, ..query.clone() };
+        let mut events = match &self.repository {
+            Some(repository) => repository.query_events(&peek).await?
+                .iter()
+                .filter_map(|record| record.to_match_event())
+                .collect(),
+            None => {
+                let mut matching: Vec<MatchEvent> = recent.iter().filter(|event| peek.accepts(event)).cloned().collect();
+                matching.sort_by_key(|event| std::cmp::Reverse((event.timestamp, event.id)));
+                matching.truncate(peek.limit);
+                matching
+            }
+        };
+
+        let next_cursor = (events.len() > query.limit).then(|| {
+            events.truncate(query.limit);
+            events.last().map(|event| EventCursor::from(event).to_string())
+        }).flatten();
+        Ok(EventPage { events, next_cursor })
+    }
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use quant_models::EventType;
+
+    fn event(match_id: &str, league: &str, event_type: EventType, minutes_ago: i64) -> MatchEvent {
+        MatchEvent::new(
+            match_id.to_string(),
+            event_type,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            league.to_string(),
+            "2024-25".to_string(),
+        ).with_timestamp(Utc::now() - Duration::minutes(minutes_ago))
+    }
+
+    #[tokio::test]
+    async fn test_recent_events_filtered_and_paged_newest_first() {
+        let goal = |minute| EventType::Goal { team: "Arsenal".to_string(), player: None, minute };
+        let recent = vec![
+            event("m1", "Premier League", EventType::MatchStart, 50),
+            event("m1", "Premier League", goal(10), 40),
+            event("m2", "La Liga", goal(12), 30),
+            event("m1", "Premier League", goal(30), 20),
+            event("m1", "Premier League", goal(45), 10),
+        ];
+        let log = EventLog::new();
+
+        let mut query = EventQuery {
+            league: Some("premier league".to_string()),
+            event_type: Some("goal".to_string()),
+            limit: 2,
+            ..EventQuery::default()
+        };
+        let first = log.list(&query, &recent).await.unwrap();
+        assert_eq!(first.events.iter().map(|e| e.id).collect::<Vec<_>>(), [recent[4].id, recent[3].id]);
+
+        query.after = Some(first.next_cursor.unwrap().parse().unwrap());
+        let second = log.list(&query, &recent).await.unwrap();
+        assert_eq!(second.events.iter().map(|e| e.id).collect::<Vec<_>>(), [recent[1].id]);
+        assert!(second.next_cursor.is_none());
+
+        let team = EventQuery { team: Some("CHELSEA".to_string()), from: Some(recent[2].timestamp), limit: 10, ..EventQuery::default() };
+        assert_eq!(log.list(&team, &recent).await.unwrap().events.len(), 3);
+        assert!("not-a-cursor".parse::<EventCursor>().is_err());
+    }
+}
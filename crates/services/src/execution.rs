@@ -0,0 +1,148 @@
+// Where executed trades are sent: paper trading by default, or a real
+// exchange
+
+use anyhow::Result;
+use async_trait::async_trait;
+use quant_models::BettingDecision;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// How much of an order the venue filled, and at what price
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderReceipt {
+    pub venue: String,
+    /// The venue's id for the order, for cancelling it later
+    pub order_id: String,
+    /// The venue's market the order was placed in, if it has them
+    pub market_id: Option<String>,
+    pub size_matched: Decimal,
+    pub average_price: Decimal,
+}
+
+impl OrderReceipt {
+    pub fn is_matched(&self) -> bool {
+        self.size_matched > Decimal::ZERO
+    }
+}
+
+/// How a stake that falls between increments is brought onto one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StakeRounding {
+    /// Never more than the risk checks allowed
+    #[default]
+    Down,
+    /// The closest increment, which can be up to half an increment more
+    Nearest,
+}
+
+/// The stakes a venue accepts. Unset limits don't apply.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StakeLimits {
+    pub min_stake: Option<Decimal>,
+    pub max_stake: Option<Decimal>,
+    /// Stakes have to be a multiple of this, e.g. 0.50
+    pub increment: Option<Decimal>,
+    pub rounding: StakeRounding,
+}
+
+impl StakeLimits {
+    /// `stake` capped at the maximum and rounded to the increment, or zero
+    /// if that leaves it under the minimum
+    pub fn fit(&self, stake: Decimal) -> Decimal {
+        let mut fitted = self.max_stake.map_or(stake, |max_stake| stake.min(max_stake));
+        if let Some(increment) = self.increment.filter(|increment| *increment > Decimal::ZERO) {
+            let steps = fitted / increment;
+            let steps = match self.rounding {
+                StakeRounding::Down => steps.floor(),
+                StakeRounding::Nearest => steps.round(),
+            };
+            fitted = steps * increment;
+            // A maximum off the increment grid is still a maximum
+            if self.max_stake.is_some_and(|max_stake| fitted > max_stake) {
+                fitted -= increment;
+            }
+        }
+
+        if fitted <= Decimal::ZERO || self.min_stake.is_some_and(|min_stake| fitted < min_stake) {
+            return Decimal::ZERO;
+        }
+        fitted.normalize()
+    }
+}
+
+/// A venue that takes the engine's bets as orders
+#[async_trait]
+pub trait ExecutionVenue: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Place `bet` as an order. An unmatched receipt means the venue took
+    /// the order but nothing was filled.
+    async fn place_order(&self, bet: &BettingDecision) -> Result<OrderReceipt>;
+
+    async fn cancel_order(&self, receipt: &OrderReceipt) -> Result<()>;
+
+    /// Keep the venue session from expiring; called periodically
+    async fn keep_alive(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Fills every order in full at the requested price, without sending it
+/// anywhere
+#[derive(Debug, Clone, Default)]
+pub struct PaperVenue;
+
+impl PaperVenue {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl ExecutionVenue for PaperVenue {
+    fn name(&self) -> &str {
+        "paper"
+    }
+
+    async fn place_order(&self, bet: &BettingDecision) -> Result<OrderReceipt> {
+        Ok(OrderReceipt {
+            venue: self.name().to_string(),
+            order_id: bet.id.to_string(),
+            market_id: None,
+            size_matched: bet.stake,
+            average_price: bet.odds,
+        })
+    }
+
+    async fn cancel_order(&self, _receipt: &OrderReceipt) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_stakes_fit_venue_limits() {
+        let limits = StakeLimits {
+            min_stake: Some(dec!(2)),
+            max_stake: Some(dec!(100.25)),
+            increment: Some(dec!(0.50)),
+            rounding: StakeRounding::Down,
+        };
+        assert_eq!(limits.fit(dec!(13.476217)), dec!(13));
+        assert_eq!(limits.fit(dec!(250)), dec!(100));
+        assert_eq!(limits.fit(dec!(2.40)), dec!(2));
+        assert_eq!(limits.fit(dec!(1.99)), Decimal::ZERO);
+
+        let nearest = StakeLimits { rounding: StakeRounding::Nearest, ..limits };
+        assert_eq!(nearest.fit(dec!(13.76)), dec!(14));
+        assert_eq!(nearest.fit(dec!(1.80)), dec!(2));
+
+        assert_eq!(StakeLimits::default().fit(dec!(13.476217)), dec!(13.476217));
+    }
+}
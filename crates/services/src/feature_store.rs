@@ -0,0 +1,98 @@
+use quant_models::{FeatureVector, Prediction, PredictionFeatures};
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+/// Recent predictions' feature vectors, keyed by prediction id. The database
+/// keeps the full history; this serves debugging lookups without it.
+pub struct FeatureStore {
+    entries: RwLock<HashMap<Uuid, PredictionFeatures>>,
+    /// Prediction ids, oldest first, for eviction
+    order: RwLock<VecDeque<Uuid>>,
+    max_entries: usize,
+}
+
+impl FeatureStore {
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+            order: RwLock::new(VecDeque::new()),
+            max_entries: DEFAULT_MAX_ENTRIES,
+        }
+    }
+
+    /// Keep at most this many feature vectors in memory, dropping the oldest
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    pub async fn record(&self, prediction: &Prediction, features: FeatureVector) -> PredictionFeatures {
+        let stored = PredictionFeatures::new(prediction, features);
+
+        let mut entries = self.entries.write().await;
+        let mut order = self.order.write().await;
+        if entries.insert(stored.prediction_id, stored.clone()).is_none() {
+            order.push_back(stored.prediction_id);
+        }
+        while order.len() > self.max_entries {
+            if let Some(oldest) = order.pop_front() {
+                entries.remove(&oldest);
+            }
+        }
+
+        stored
+    }
+
+    pub async fn get(&self, prediction_id: Uuid) -> Option<PredictionFeatures> {
+        self.entries.read().await.get(&prediction_id).cloned()
+    }
+
+    pub async fn len(&self) -> usize {
+        self.entries.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.entries.read().await.is_empty()
+    }
+}
+
+impl Default for FeatureStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn prediction() -> Prediction {
+        Prediction::new("m1".to_string(), "test".to_string(), "1.0".to_string(), 0.5, 0.3, Utc::now()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_features_kept_per_prediction_and_evicted_oldest_first() {
+        let store = FeatureStore::new().with_max_entries(2);
+        let features = FeatureVector {
+            match_id: "m1".to_string(),
+            features: HashMap::from([("home_form".to_string(), 0.7)]),
+            timestamp: Utc::now(),
+            schema: None,
+        };
+
+        let first = prediction();
+        store.record(&first, features.clone()).await;
+        let stored = store.get(first.id).await.unwrap();
+        assert_eq!(stored.model_name, "test");
+        assert_eq!(stored.features.features["home_form"], 0.7);
+
+        store.record(&prediction(), features.clone()).await;
+        store.record(&prediction(), features).await;
+        assert_eq!(store.len().await, 2);
+        assert!(store.get(first.id).await.is_none());
+    }
+}
@@ -0,0 +1,282 @@
+// Upcoming matches and their kick-off times, priced and predicted ahead of
+// the first feed event
+
+use crate::data_feed::SimulatedFixture;
+use crate::ingest::season_for;
+use crate::market_simulator::MarketSimulator;
+use crate::predictor::PredictorService;
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use quant_db::{MatchRecord, MatchRepository, Repository};
+use quant_models::{EventType, MatchEvent, Prediction, SimpleMarketOdds};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// Stored fixtures further ahead than this are left in the database at startup
+const FIXTURE_HORIZON_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FixturesConfig {
+    /// Fixtures kicking off within this many hours are priced and predicted
+    pub pre_match_hours: u64,
+    /// How often fixtures are checked for coming into that window
+    pub check_interval_seconds: u64,
+}
+
+impl Default for FixturesConfig {
+    fn default() -> Self {
+        Self { pre_match_hours: 24, check_interval_seconds: 60 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    pub match_id: String,
+    pub team_home: String,
+    pub team_away: String,
+    pub league: String,
+    pub season: String,
+    pub kickoff: DateTime<Utc>,
+    /// Set once the fixture comes within the pre-match window
+    pub prediction: Option<Prediction>,
+    pub odds: Option<SimpleMarketOdds>,
+}
+
+impl Fixture {
+    pub fn new(match_id: String, team_home: String, team_away: String, league: String, kickoff: DateTime<Utc>) -> Self {
+        Self {
+            match_id,
+            team_home,
+            team_away,
+            league,
+            season: season_for(kickoff),
+            kickoff,
+            prediction: None,
+            odds: None,
+        }
+    }
+
+    /// None for a simulated match that kicks off straight away
+    pub fn from_simulated(fixture: &SimulatedFixture) -> Option<Self> {
+        Some(Self::new(
+            fixture.match_id.clone(),
+            fixture.team_home.clone(),
+            fixture.team_away.clone(),
+            fixture.league.clone(),
+            fixture.kickoff?,
+        ))
+    }
+
+    pub fn to_record(&self) -> MatchRecord {
+        MatchRecord::fixture(
+            self.match_id.clone(),
+            self.team_home.clone(),
+            self.team_away.clone(),
+            self.league.clone(),
+            self.season.clone(),
+            self.kickoff,
+        )
+    }
+
+    /// The market and prediction as they stand at `at`, before kick-off
+    fn pre_match_event(&self, at: DateTime<Utc>) -> MatchEvent {
+        let mut event = MatchEvent::new(
+            self.match_id.clone(),
+            EventType::OddsUpdate,
+            self.team_home.clone(),
+            self.team_away.clone(),
+            self.league.clone(),
+            self.season.clone(),
+        );
+        event.timestamp = at;
+        event
+    }
+}
+
+impl From<&MatchRecord> for Fixture {
+    fn from(record: &MatchRecord) -> Self {
+        Self {
+            season: record.season.clone(),
+            ..Self::new(
+                record.match_id.clone(),
+                record.team_home.clone(),
+                record.team_away.clone(),
+                record.league.clone(),
+                record.match_date,
+            )
+        }
+    }
+}
+
+/// Fixtures yet to kick off, kept in the database when there is one
+pub struct FixtureService {
+    fixtures: RwLock<HashMap<String, Fixture>>,
+    repository: Option<Arc<Repository>>,
+    predictor: Arc<PredictorService>,
+    market_simulator: Arc<MarketSimulator>,
+    pre_match_window: Duration,
+}
+
+impl FixtureService {
+    pub fn new(predictor: Arc<PredictorService>, market_simulator: Arc<MarketSimulator>) -> Self {
+        Self {
+            fixtures: RwLock::new(HashMap::new()),
+            repository: None,
+            predictor,
+            market_simulator,
+            pre_match_window: Duration::hours(FixturesConfig::default().pre_match_hours as i64),
+        }
+    }
+
+    pub fn with_repository(mut self, repository: Arc<Repository>) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    pub fn with_pre_match_window(mut self, window: Duration) -> Self {
+        self.pre_match_window = window;
+        self
+    }
+
+    /// Pick up the fixtures stored for the coming weeks
+    pub async fn load_stored(&self, now: DateTime<Utc>) -> Result<usize> {
+        let Some(repository) = &self.repository else {
+            return Ok(0);
+        };
+        let records = repository.get_fixtures(now, now + Duration::days(FIXTURE_HORIZON_DAYS)).await?;
+        let mut fixtures = self.fixtures.write().await;
+        for record in &records {
+            fixtures.entry(record.match_id.clone()).or_insert_with(|| Fixture::from(record));
+        }
+        Ok(records.len())
+    }
+
+    /// Add or reschedule fixtures, storing them when connected to the database
+    pub async fn add(&self, added: Vec<Fixture>) {
+        for fixture in added {
+            if let Some(repository) = &self.repository {
+                if let Err(e) = repository.save_fixture(&fixture.to_record()).await {
+                    warn!("🗓️ Failed to store fixture {}: {}", fixture.match_id, e);
+                }
+            }
+            self.fixtures.write().await.insert(fixture.match_id.clone(), fixture);
+        }
+    }
+
+    /// The match's first event has arrived, so it's no longer a fixture
+    pub async fn kicked_off(&self, match_id: &str) {
+        self.fixtures.write().await.remove(match_id);
+    }
+
+    pub async fn get(&self, match_id: &str) -> Option<Fixture> {
+        self.fixtures.read().await.get(match_id).cloned()
+    }
+
+    /// Fixtures kicking off on `date` (UTC), soonest first
+    pub async fn on_date(&self, date: NaiveDate) -> Vec<Fixture> {
+        let mut fixtures: Vec<Fixture> = self.fixtures.read().await
+            .values()
+            .filter(|fixture| fixture.kickoff.date_naive() == date)
+            .cloned()
+            .collect();
+        fixtures.sort_by(|a, b| a.kickoff.cmp(&b.kickoff).then_with(|| a.match_id.cmp(&b.match_id)));
+        fixtures
+    }
+
+    /// Open the market and predict every fixture that has come within the
+    /// pre-match window since the last call. Returns how many were.
+    pub async fn prepare_due(&self, now: DateTime<Utc>) -> usize {
+        let due: Vec<Fixture> = self.fixtures.read().await
+            .values()
+            .filter(|fixture| fixture.prediction.is_none() && fixture.kickoff - now <= self.pre_match_window)
+            .cloned()
+            .collect();
+
+        let mut prepared = 0;
+        for mut fixture in due {
+            let event = fixture.pre_match_event(now);
+            self.market_simulator.set_kickoff(&fixture.match_id, &fixture.league, fixture.kickoff).await;
+            match self.market_simulator.generate_market_odds(&event).await {
+                Ok(odds) => fixture.odds = Some(odds),
+                Err(e) => warn!("🗓️ Failed to open the market for {}: {}", fixture.match_id, e),
+            }
+            // A sandbox, so the live match context starts from the first real event
+            match self.predictor.sandbox().predict(&event).await {
+                Ok(prediction) => fixture.prediction = Some(prediction),
+                Err(e) => {
+                    warn!("🗓️ Failed to predict {} before kick-off: {}", fixture.match_id, e);
+                    continue;
+                }
+            }
+            debug!("🗓️ Priced {} ahead of its {} kick-off", fixture.match_id, fixture.kickoff);
+            // Unless it kicked off in the meantime
+            if let Some(stored) = self.fixtures.write().await.get_mut(&fixture.match_id) {
+                *stored = fixture;
+                prepared += 1;
+            }
+        }
+        prepared
+    }
+
+    /// Prepare fixtures as they come due, every `interval` until cancelled
+    pub async fn run(&self, interval: std::time::Duration, shutdown: CancellationToken) {
+        info!("🗓️ Pricing fixtures {} hours before kick-off", self.pre_match_window.num_hours());
+        let mut ticker = tokio::time::interval(interval.max(std::time::Duration::from_secs(1)));
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
+            let prepared = self.prepare_due(Utc::now()).await;
+            if prepared > 0 {
+                info!("🗓️ Priced and predicted {} fixtures ahead of kick-off", prepared);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fixtures_priced_within_pre_match_window() {
+        let predictor = Arc::new(PredictorService::new());
+        let market_simulator = Arc::new(MarketSimulator::new().with_seed(5));
+        let fixtures = FixtureService::new(predictor, market_simulator.clone())
+            .with_pre_match_window(Duration::hours(24));
+
+        let now = Utc::now();
+        let fixture = |match_id: &str, hours| Fixture::new(
+            match_id.to_string(), "Arsenal".to_string(), "Chelsea".to_string(),
+            "Premier League".to_string(), now + Duration::hours(hours),
+        );
+        let later_today = fixture("soon", 2);
+        let mut simulated = SimulatedFixture::new(
+            "next_week".to_string(), "Everton".to_string(), "Fulham".to_string(), "Premier League".to_string(),
+        );
+        assert!(Fixture::from_simulated(&simulated).is_none());
+        simulated.kickoff = Some(now + Duration::days(7));
+        fixtures.add(vec![later_today.clone(), Fixture::from_simulated(&simulated).unwrap()]).await;
+
+        assert_eq!(fixtures.prepare_due(now).await, 1);
+        let priced = fixtures.get("soon").await.unwrap();
+        assert_eq!(priced.prediction.unwrap().match_id, "soon");
+        assert_eq!(priced.odds, market_simulator.get_current_odds("soon").await);
+        assert!(fixtures.get("next_week").await.unwrap().prediction.is_none());
+        // Already priced, so not again
+        assert_eq!(fixtures.prepare_due(now).await, 0);
+
+        let on_date = fixtures.on_date(later_today.kickoff.date_naive()).await;
+        assert!(on_date.iter().any(|fixture| fixture.match_id == "soon"));
+        assert!(on_date.iter().all(|fixture| fixture.match_id != "next_week"));
+
+        fixtures.kicked_off("soon").await;
+        assert!(fixtures.get("soon").await.is_none());
+    }
+}
@@ -0,0 +1,260 @@
+// Generated simulation schedules: many random matches across leagues, with
+// team strengths and goal and red-card rates drawn from a spec, for stress
+// tests and demos
+
+use crate::data_feed::{SimulatedFixture, BASE_GOAL_PROBABILITY, MAX_TEAM_STRENGTH, RED_CARD_PROBABILITY};
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, Utc};
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Weakest a generated side may be relative to an average one
+const MIN_TEAM_STRENGTH: f64 = 0.2;
+
+/// How strong generated teams are, relative to an average side (1.0)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrengthDistribution {
+    pub mean: f64,
+    /// Strengths are drawn uniformly from `mean ± spread`
+    pub spread: f64,
+    /// Multiplies the home side's strength
+    pub home_advantage: f64,
+}
+
+impl Default for StrengthDistribution {
+    fn default() -> Self {
+        Self {
+            mean: 1.0,
+            spread: 0.3,
+            home_advantage: 1.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeagueSpec {
+    pub name: String,
+    pub teams: Vec<String>,
+    /// Override the schedule's rates for this league
+    #[serde(default)]
+    pub goal_rate: Option<f64>,
+    #[serde(default)]
+    pub red_card_rate: Option<f64>,
+}
+
+/// A schedule of random matches, usually loaded from a TOML or JSON file:
+///
+/// ```toml
+/// name = "Saturday afternoon"
+/// matches = 6
+/// goal_rate = 0.025
+///
+/// [strength]
+/// spread = 0.5
+///
+/// [[leagues]]
+/// name = "Premier League"
+/// teams = ["Arsenal", "Chelsea", "Liverpool", "Everton"]
+/// ```
+///
+/// Matches are shared out over the leagues in turn and each team plays at
+/// most once, so the leagues need enough teams between them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleSpec {
+    pub name: String,
+    /// Matches running at once
+    pub matches: usize,
+    pub leagues: Vec<LeagueSpec>,
+    #[serde(default)]
+    pub strength: StrengthDistribution,
+    /// Chance per cycle of a goal between average sides
+    #[serde(default = "default_goal_rate")]
+    pub goal_rate: f64,
+    /// Chance per cycle of a red card
+    #[serde(default = "default_red_card_rate")]
+    pub red_card_rate: f64,
+    /// Seconds between successive kick-offs; everything kicks off at once when 0
+    #[serde(default)]
+    pub kickoff_interval_seconds: u64,
+}
+
+fn default_goal_rate() -> f64 {
+    BASE_GOAL_PROBABILITY
+}
+
+fn default_red_card_rate() -> f64 {
+    RED_CARD_PROBABILITY
+}
+
+impl ScheduleSpec {
+    pub fn from_toml_str(source: &str) -> Result<Self> {
+        let spec: ScheduleSpec = toml::from_str(source)?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    pub fn from_json_str(source: &str) -> Result<Self> {
+        let spec: ScheduleSpec = serde_json::from_str(source)?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Read a spec from a `.json` file, or TOML otherwise
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read schedule {}", path.display()))?;
+        let spec = if path.extension().is_some_and(|extension| extension == "json") {
+            Self::from_json_str(&source)
+        } else {
+            Self::from_toml_str(&source)
+        };
+        spec.with_context(|| format!("invalid schedule {}", path.display()))
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.matches == 0 {
+            bail!("schedule '{}' has no matches", self.name);
+        }
+        let capacity: usize = self.leagues.iter().map(|league| league.teams.len() / 2).sum();
+        if self.matches > capacity {
+            bail!("schedule '{}' needs {} matches but its leagues have teams for {}", self.name, self.matches, capacity);
+        }
+        let strength = &self.strength;
+        if strength.mean <= 0.0 || strength.mean > MAX_TEAM_STRENGTH || strength.mean.is_nan() {
+            bail!("strength mean must be in (0, {}], got {}", MAX_TEAM_STRENGTH, strength.mean);
+        }
+        if !(0.0..=MAX_TEAM_STRENGTH).contains(&strength.spread) {
+            bail!("strength spread must be in [0, {}], got {}", MAX_TEAM_STRENGTH, strength.spread);
+        }
+        if strength.home_advantage <= 0.0 || strength.home_advantage.is_nan() {
+            bail!("home advantage must be positive, got {}", strength.home_advantage);
+        }
+        let rates = self.leagues.iter()
+            .flat_map(|league| [league.goal_rate, league.red_card_rate])
+            .flatten()
+            .chain([self.goal_rate, self.red_card_rate]);
+        for rate in rates {
+            if !(0.0..=0.5).contains(&rate) {
+                bail!("goal and red card rates are chances per cycle in [0, 0.5], got {}", rate);
+            }
+        }
+        Ok(())
+    }
+
+    /// Draw the fixtures; the same seed gives the same schedule
+    pub fn generate(&self, seed: u64) -> Vec<SimulatedFixture> {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let strength = &self.strength;
+        let draw_strength = |rng: &mut SmallRng| {
+            let offset = if strength.spread > 0.0 { rng.gen_range(-strength.spread..=strength.spread) } else { 0.0 };
+            (strength.mean + offset).clamp(MIN_TEAM_STRENGTH, MAX_TEAM_STRENGTH)
+        };
+
+        // Each league's teams in a random order, paired off two at a time
+        let mut pools: Vec<Vec<&String>> = self.leagues.iter()
+            .map(|league| {
+                let mut teams: Vec<&String> = league.teams.iter().collect();
+                teams.shuffle(&mut rng);
+                teams
+            })
+            .collect();
+
+        let now = Utc::now();
+        let mut fixtures = Vec::with_capacity(self.matches);
+        let mut league_index = 0;
+        while fixtures.len() < self.matches {
+            let league = &self.leagues[league_index];
+            let pool = &mut pools[league_index];
+            league_index = (league_index + 1) % self.leagues.len();
+            if pool.len() < 2 {
+                continue;
+            }
+            let (team_home, team_away) = (pool.pop().unwrap(), pool.pop().unwrap());
+
+            let number = fixtures.len();
+            let mut fixture = SimulatedFixture::new(
+                format!("gen_{}_{:03}", slug(&league.name), number + 1),
+                team_home.clone(),
+                team_away.clone(),
+                league.name.clone(),
+            );
+            fixture.home_strength = (draw_strength(&mut rng) * strength.home_advantage).clamp(MIN_TEAM_STRENGTH, MAX_TEAM_STRENGTH);
+            fixture.away_strength = draw_strength(&mut rng);
+            fixture.goal_rate = Some(league.goal_rate.unwrap_or(self.goal_rate));
+            fixture.red_card_rate = Some(league.red_card_rate.unwrap_or(self.red_card_rate));
+            if self.kickoff_interval_seconds > 0 {
+                fixture.kickoff = Some(now + Duration::seconds((self.kickoff_interval_seconds * number as u64) as i64));
+            }
+            fixtures.push(fixture);
+        }
+        fixtures
+    }
+}
+
+fn slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_schedule_generated_from_spec() {
+        let spec = ScheduleSpec::from_toml_str(r#"
+            name = "Weekend"
+            matches = 3
+            red_card_rate = 0.01
+
+            [strength]
+            mean = 1.2
+            spread = 0.4
+
+            [[leagues]]
+            name = "Premier League"
+            teams = ["Arsenal", "Chelsea", "Liverpool", "Everton"]
+
+            [[leagues]]
+            name = "La Liga"
+            teams = ["Real Madrid", "Barcelona", "Sevilla"]
+            goal_rate = 0.03
+        "#).unwrap();
+
+        let fixtures = spec.generate(7);
+        assert_eq!(fixtures.len(), 3);
+        let teams: HashSet<&str> = fixtures.iter()
+            .flat_map(|fixture| [fixture.team_home.as_str(), fixture.team_away.as_str()])
+            .collect();
+        assert_eq!(teams.len(), 6, "no team plays twice");
+        assert_eq!(fixtures.iter().filter(|fixture| fixture.league == "La Liga").count(), 1);
+        for fixture in &fixtures {
+            fixture.validate().unwrap();
+            assert!((0.2..=1.6 * 1.1).contains(&fixture.home_strength));
+            assert_eq!(fixture.red_card_rate, Some(0.01));
+            let goal_rate = if fixture.league == "La Liga" { 0.03 } else { BASE_GOAL_PROBABILITY };
+            assert_eq!(fixture.goal_rate, Some(goal_rate));
+        }
+        assert_eq!(spec.generate(7)[0].home_strength, fixtures[0].home_strength);
+
+        // La Liga's odd team out can't make a fourth match
+        let mut too_many = spec.clone();
+        too_many.matches = 4;
+        assert!(too_many.validate().is_err());
+    }
+
+    #[test]
+    fn test_example_schedule_is_valid() {
+        let spec = ScheduleSpec::from_toml_str(include_str!("../../../examples/schedules/weekend.toml")).unwrap();
+        assert_eq!(spec.generate(1).len(), spec.matches);
+    }
+}
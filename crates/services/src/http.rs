@@ -0,0 +1,69 @@
+// Outbound HTTP for the exchange and alert channels, swappable in tests
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::time::Duration;
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// How requests reach an outside service
+#[async_trait]
+pub trait HttpTransport: Send + Sync {
+    async fn post(&self, url: &str, headers: &[(&str, &str)], body: String) -> Result<HttpResponse>;
+}
+
+/// A pooled client that gives up on a request after ten seconds
+pub fn client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+#[async_trait]
+impl HttpTransport for reqwest::Client {
+    async fn post(&self, url: &str, headers: &[(&str, &str)], body: String) -> Result<HttpResponse> {
+        let mut request = reqwest::Client::post(self, url).body(body);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let response = request.send().await?;
+        Ok(HttpResponse {
+            status: response.status().as_u16(),
+            body: response.text().await?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_posts_to_a_url_with_a_port() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            let read = socket.read(&mut request).await.unwrap();
+            socket.write_all(
+                b"HTTP/1.1 202 Accepted\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nWiki\r\n5\r\npedia\r\n0\r\n\r\n",
+            ).await.unwrap();
+            String::from_utf8_lossy(&request[..read]).to_string()
+        });
+
+        let response = HttpTransport::post(&client(), &url, &[("X-Token", "t1")], "{}".to_string()).await.unwrap();
+        assert_eq!((response.status, response.body.as_str()), (202, "Wikipedia"));
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /hook "));
+        assert!(request.to_lowercase().contains("x-token: t1"));
+    }
+}
@@ -0,0 +1,352 @@
+// Historical results and closing odds import (football-data.co.uk CSV format)
+
+use quant_db::{HistoricalRepository, MatchRecord, OddsRecord, Repository};
+use quant_models::{Outcome1X2, TeamNameNormalizer};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::Path;
+use std::str::FromStr;
+use tracing::{info, warn};
+
+/// Closing price columns per bookmaker, with the pre-match columns older
+/// files carry instead as a fallback
+const ODDS_COLUMNS: [(&str, &str, &str); 3] = [
+    ("Pinnacle", "PSC", "PS"),
+    ("Bet365", "B365C", "B365"),
+    ("MarketAverage", "AvgC", "Avg"),
+];
+
+/// Kick-off time assumed when a file has no `Time` column
+const DEFAULT_KICKOFF: (u32, u32) = (15, 0);
+
+/// football-data.co.uk division codes
+const LEAGUES: [(&str, &str); 9] = [
+    ("E0", "Premier League"),
+    ("E1", "Championship"),
+    ("SP1", "La Liga"),
+    ("D1", "Bundesliga"),
+    ("I1", "Serie A"),
+    ("F1", "Ligue 1"),
+    ("N1", "Eredivisie"),
+    ("P1", "Primeira Liga"),
+    ("SC0", "Scottish Premiership"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosingOdds {
+    pub bookmaker: String,
+    pub home: Decimal,
+    pub draw: Decimal,
+    pub away: Decimal,
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoricalMatch {
+    pub match_id: String,
+    pub league: String,
+    pub season: String,
+    pub match_date: DateTime<Utc>,
+    pub team_home: String,
+    pub team_away: String,
+    pub home_goals: i32,
+    pub away_goals: i32,
+    pub closing_odds: Vec<ClosingOdds>,
+}
+
+impl HistoricalMatch {
+    pub fn result(&self) -> Outcome1X2 {
+        Outcome1X2::from_score(self.home_goals, self.away_goals)
+    }
+
+    pub fn to_records(&self) -> (MatchRecord, Vec<OddsRecord>) {
+        let mut match_record = MatchRecord::new(
+            self.match_id.clone(),
+            self.team_home.clone(),
+            self.team_away.clone(),
+            self.league.clone(),
+            self.season.clone(),
+        );
+        match_record.match_date = self.match_date;
+        match_record.status = "finished".to_string();
+        match_record.home_score = Some(self.home_goals);
+        match_record.away_score = Some(self.away_goals);
+
+        let odds = self.closing_odds
+            .iter()
+            .map(|odds| OddsRecord::closing(
+                self.match_id.clone(),
+                odds.bookmaker.clone(),
+                odds.home,
+                odds.draw,
+                odds.away,
+                self.match_date,
+            ))
+            .collect();
+
+        (match_record, odds)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ParseSummary {
+    pub matches: Vec<HistoricalMatch>,
+    pub skipped: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Loads football-data.co.uk style result files into the database
+pub struct HistoricalImporter {
+    normalizer: TeamNameNormalizer,
+}
+
+impl HistoricalImporter {
+    pub fn new() -> Self {
+        Self {
+            normalizer: TeamNameNormalizer::new(),
+        }
+    }
+
+    pub fn with_normalizer(mut self, normalizer: TeamNameNormalizer) -> Self {
+        self.normalizer = normalizer;
+        self
+    }
+
+    /// Parse a results file. Rows without a full-time score (e.g. fixtures
+    /// not yet played) or with unreadable fields are skipped and counted.
+    pub fn parse<R: BufRead>(&self, reader: R) -> Result<ParseSummary> {
+        let mut lines = reader.lines();
+        let header = lines.next()
+            .ok_or_else(|| anyhow!("empty results file"))??;
+        let columns: HashMap<String, usize> = split_csv_line(header.trim_start_matches('\u{feff}'))
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| (name, i))
+            .collect();
+
+        for required in ["Div", "Date", "HomeTeam", "AwayTeam", "FTHG", "FTAG"] {
+            if !columns.contains_key(required) {
+                return Err(anyhow!("results file is missing the {} column", required));
+            }
+        }
+
+        let mut summary = ParseSummary::default();
+        for (line_no, line) in lines.enumerate() {
+            let line = line?;
+            if line.trim().trim_matches(',').is_empty() {
+                continue;
+            }
+
+            let fields = split_csv_line(&line);
+            match self.parse_row(&columns, &fields) {
+                Ok(historical) => summary.matches.push(historical),
+                Err(e) => {
+                    warn!("📥 Skipping row {}: {}", line_no + 2, e);
+                    summary.skipped += 1;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    fn parse_row(&self, columns: &HashMap<String, usize>, fields: &[String]) -> Result<HistoricalMatch> {
+        let field = |name: &str| -> Option<&str> {
+            columns.get(name)
+                .and_then(|&i| fields.get(i))
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+        };
+        let required = |name: &str| field(name).ok_or_else(|| anyhow!("missing {}", name));
+
+        let division = required("Div")?;
+        let league = LEAGUES.iter()
+            .find(|(code, _)| *code == division)
+            .map(|(_, name)| name.to_string())
+            .unwrap_or_else(|| division.to_string());
+
+        let match_date = parse_kickoff(required("Date")?, field("Time"))?;
+        let team_home = self.normalizer.normalize(required("HomeTeam")?);
+        let team_away = self.normalizer.normalize(required("AwayTeam")?);
+        let home_goals: i32 = required("FTHG")?.parse().context("invalid FTHG")?;
+        let away_goals: i32 = required("FTAG")?.parse().context("invalid FTAG")?;
+
+        let closing_odds = ODDS_COLUMNS
+            .iter()
+            .filter_map(|(bookmaker, closing, pre_match)| {
+                let price = |prefix: &str| -> Option<(Decimal, Decimal, Decimal)> {
+                    let parse = |suffix: &str| field(&format!("{}{}", prefix, suffix))
+                        .and_then(|value| Decimal::from_str(value).ok())
+                        .filter(|odds| *odds > Decimal::ONE);
+                    Some((parse("H")?, parse("D")?, parse("A")?))
+                };
+                price(closing).or_else(|| price(pre_match)).map(|(home, draw, away)| ClosingOdds {
+                    bookmaker: bookmaker.to_string(),
+                    home,
+                    draw,
+                    away,
+                })
+            })
+            .collect();
+
+        Ok(HistoricalMatch {
+            match_id: historical_match_id(division, match_date, &team_home, &team_away),
+            league,
+            season: season_for(match_date),
+            match_date,
+            team_home,
+            team_away,
+            home_goals,
+            away_goals,
+            closing_odds,
+        })
+    }
+
+    /// Parse `path` and write every match to the database
+    pub async fn import_file(&self, repository: &Repository, path: impl AsRef<Path>) -> Result<ImportSummary> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("opening {}", path.display()))?;
+        let parsed = self.parse(std::io::BufReader::new(file))?;
+
+        let mut summary = ImportSummary {
+            skipped: parsed.skipped,
+            ..ImportSummary::default()
+        };
+
+        for historical in &parsed.matches {
+            let (match_record, odds) = historical.to_records();
+            match repository.import_historical_match(&match_record, &odds).await {
+                Ok(()) => summary.imported += 1,
+                Err(e) => {
+                    warn!("📥 Failed to import {}: {}", historical.match_id, e);
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        info!("📥 Imported {} matches from {} ({} skipped, {} failed)",
+              summary.imported, path.display(), summary.skipped, summary.failed);
+        Ok(summary)
+    }
+}
+
+impl Default for HistoricalImporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Seasons run July to June and are written like the live feed, e.g. "2024-25"
+pub fn season_for(date: DateTime<Utc>) -> String {
+    let start_year = if date.month() >= 7 { date.year() } else { date.year() - 1 };
+    format!("{}-{:02}", start_year, (start_year + 1) % 100)
+}
+
+/// Stable id so re-importing the same file updates rather than duplicates
+fn historical_match_id(division: &str, date: DateTime<Utc>, home: &str, away: &str) -> String {
+    let slug = |name: &str| {
+        name.to_lowercase()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-")
+    };
+    format!("{}-{}-{}-{}", division.to_lowercase(), date.format("%Y%m%d"), slug(home), slug(away))
+}
+
+/// Dates are `dd/mm/yy` or `dd/mm/yyyy`; times are UK local and treated as UTC
+fn parse_kickoff(date: &str, time: Option<&str>) -> Result<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(date, "%d/%m/%Y")
+        .ok()
+        .filter(|d| d.year() >= 1000)
+        .or_else(|| NaiveDate::parse_from_str(date, "%d/%m/%y").ok())
+        .ok_or_else(|| anyhow!("invalid date {}", date))?;
+
+    let time = match time {
+        Some(time) => NaiveTime::parse_from_str(time, "%H:%M")
+            .with_context(|| format!("invalid time {}", time))?,
+        None => NaiveTime::from_hms_opt(DEFAULT_KICKOFF.0, DEFAULT_KICKOFF.1, 0)
+            .expect("default kick-off is a valid time"),
+    };
+
+    Ok(Utc.from_utc_datetime(&date.and_time(time)))
+}
+
+/// Split a CSV line, honouring double-quoted fields
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.trim_end_matches(['\r', '\n']).chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_football_data_csv() {
+        let csv = "\
+Div,Date,Time,HomeTeam,AwayTeam,FTHG,FTAG,FTR,B365H,B365D,B365A,PSH,PSD,PSA,PSCH,PSCD,PSCA
+E0,16/08/2024,20:00,Man United,Fulham,1,0,H,1.60,4.20,5.25,1.62,4.33,5.40,1.65,4.20,5.10
+E0,17/08/2024,12:30,Ipswich,Liverpool,0,2,A,7.50,5.25,1.36,7.80,5.40,1.39,,,
+E0,18/02/25,,Nott'm Forest,Wolves,,,,2.10,3.40,3.50,,,,,,
+";
+        let parsed = HistoricalImporter::new().parse(csv.as_bytes()).unwrap();
+
+        assert_eq!(parsed.matches.len(), 2);
+        assert_eq!(parsed.skipped, 1);
+
+        let first = &parsed.matches[0];
+        assert_eq!(first.team_home, "Manchester United");
+        assert_eq!(first.league, "Premier League");
+        assert_eq!(first.season, "2024-25");
+        assert_eq!(first.match_id, "e0-20240816-manchester-united-fulham");
+        assert_eq!(first.result(), Outcome1X2::HomeWin);
+        // Pinnacle's closing columns win over its pre-match ones
+        assert_eq!(first.closing_odds[0], ClosingOdds {
+            bookmaker: "Pinnacle".to_string(),
+            home: dec!(1.65),
+            draw: dec!(4.20),
+            away: dec!(5.10),
+        });
+        assert_eq!(first.closing_odds[1].bookmaker, "Bet365");
+
+        // Without closing columns the pre-match prices are used
+        let second = &parsed.matches[1];
+        assert_eq!(second.closing_odds[0].home, dec!(7.80));
+        assert_eq!(second.result(), Outcome1X2::AwayWin);
+    }
+
+    #[test]
+    fn test_season_and_date_formats() {
+        let spring = parse_kickoff("18/02/25", None).unwrap();
+        assert_eq!(season_for(spring), "2024-25");
+        assert_eq!(spring, parse_kickoff("18/02/2025", Some("15:00")).unwrap());
+        assert_eq!(season_for(parse_kickoff("01/07/1999", None).unwrap()), "1999-00");
+    }
+}
@@ -0,0 +1,428 @@
+// Background jobs that are retried until they succeed: settlement that
+// failed the first time, retraining, and odds ticks the database missed.
+// Kept in the database when there is one, so a restart picks them up again.
+
+use crate::trader::TradingEngine;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use quant_db::{JobRecord, JobRepository, OddsRecord, OddsRepository, Repository};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+pub const SETTLE_MATCH_JOB: &str = "settle_match";
+pub const RETRAIN_MODEL_JOB: &str = "retrain_model";
+pub const BACKFILL_ODDS_JOB: &str = "backfill_odds";
+
+/// Finished jobs kept for reporting; older ones are dropped from memory
+const MAX_FINISHED_JOBS: usize = 500;
+/// Longest wait between attempts
+const MAX_RETRY_DELAY_SECONDS: i64 = 3600;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JobsConfig {
+    /// How often due jobs are looked for
+    pub poll_interval_seconds: u64,
+    /// Attempts before a job is given up on as failed
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubling with each one after
+    pub retry_delay_seconds: u64,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self { poll_interval_seconds: 5, max_attempts: 5, retry_delay_seconds: 30 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Succeeded,
+    /// Out of attempts
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Succeeded | Self::Failed)
+    }
+}
+
+impl std::str::FromStr for JobStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(status: &str) -> Result<Self> {
+        match status {
+            "pending" => Ok(Self::Pending),
+            "running" => Ok(Self::Running),
+            "succeeded" => Ok(Self::Succeeded),
+            "failed" => Ok(Self::Failed),
+            other => Err(anyhow!("unknown job status '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: Uuid,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub status: JobStatus,
+    pub attempts: u32,
+    pub max_attempts: u32,
+    /// When a pending job is next due
+    pub run_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Job {
+    pub fn new(kind: impl Into<String>, payload: serde_json::Value, max_attempts: u32) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            kind: kind.into(),
+            payload,
+            status: JobStatus::Pending,
+            attempts: 0,
+            max_attempts: max_attempts.max(1),
+            run_at: now,
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn to_record(&self) -> JobRecord {
+        JobRecord {
+            id: self.id,
+            kind: self.kind.clone(),
+            payload: self.payload.clone(),
+            status: self.status.as_str().to_string(),
+            attempts: self.attempts as i32,
+            max_attempts: self.max_attempts as i32,
+            run_at: self.run_at,
+            last_error: self.last_error.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+impl TryFrom<&JobRecord> for Job {
+    type Error = anyhow::Error;
+
+    fn try_from(record: &JobRecord) -> Result<Self> {
+        Ok(Self {
+            id: record.id,
+            kind: record.kind.clone(),
+            payload: record.payload.clone(),
+            status: record.status.parse()?,
+            attempts: record.attempts.max(0) as u32,
+            max_attempts: record.max_attempts.max(1) as u32,
+            run_at: record.run_at,
+            last_error: record.last_error.clone(),
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        })
+    }
+}
+
+/// Does the work for one kind of job. An error is retried until the job
+/// runs out of attempts.
+#[async_trait]
+pub trait JobHandler: Send + Sync {
+    async fn run(&self, payload: &serde_json::Value) -> Result<()>;
+}
+
+/// Queued jobs and the handlers that run them
+pub struct JobQueue {
+    jobs: Mutex<HashMap<Uuid, Job>>,
+    handlers: RwLock<HashMap<String, Arc<dyn JobHandler>>>,
+    repository: Option<Arc<Repository>>,
+    config: JobsConfig,
+}
+
+impl JobQueue {
+    pub fn new(config: JobsConfig) -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            handlers: RwLock::new(HashMap::new()),
+            repository: None,
+            config,
+        }
+    }
+
+    pub fn with_repository(mut self, repository: Arc<Repository>) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    /// Run jobs of `kind` with `handler`, replacing any earlier one
+    pub fn register(&self, kind: impl Into<String>, handler: Arc<dyn JobHandler>) {
+        self.handlers.write().unwrap().insert(kind.into(), handler);
+    }
+
+    /// Pick up the jobs left unfinished by the last run; those that were
+    /// running when it stopped are run again
+    pub async fn load_stored(&self) -> Result<usize> {
+        let Some(repository) = &self.repository else {
+            return Ok(0);
+        };
+        let records = repository.get_unfinished_jobs().await?;
+        let mut jobs = self.jobs.lock().await;
+        for record in &records {
+            match Job::try_from(record) {
+                Ok(mut job) => {
+                    job.status = JobStatus::Pending;
+                    jobs.insert(job.id, job);
+                }
+                Err(e) => warn!("🛠️ Skipping stored job {}: {}", record.id, e),
+            }
+        }
+        Ok(records.len())
+    }
+
+    /// Queue a job to run as soon as a worker gets to it
+    pub async fn enqueue(&self, kind: &str, payload: serde_json::Value) -> Job {
+        let job = Job::new(kind, payload, self.config.max_attempts);
+        self.save(&job).await;
+        self.jobs.lock().await.insert(job.id, job.clone());
+        debug!("🛠️ Queued {} job {}", kind, job.id);
+        job
+    }
+
+    pub async fn get(&self, id: Uuid) -> Option<Job> {
+        self.jobs.lock().await.get(&id).cloned()
+    }
+
+    /// Newest first, optionally only those with `status`
+    pub async fn list(&self, status: Option<JobStatus>, limit: usize) -> Vec<Job> {
+        let mut jobs: Vec<Job> = self.jobs.lock().await
+            .values()
+            .filter(|job| status.is_none_or(|status| job.status == status))
+            .cloned()
+            .collect();
+        jobs.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| a.id.cmp(&b.id)));
+        jobs.truncate(limit);
+        jobs
+    }
+
+    /// Run every job due at `now`, oldest due first. Returns how many ran.
+    pub async fn run_due(&self, now: DateTime<Utc>) -> usize {
+        let mut due: Vec<Job> = self.jobs.lock().await
+            .values()
+            .filter(|job| job.status == JobStatus::Pending && job.run_at <= now)
+            .cloned()
+            .collect();
+        due.sort_by_key(|job| job.run_at);
+
+        for job in &mut due {
+            job.status = JobStatus::Running;
+            job.attempts += 1;
+            job.updated_at = Utc::now();
+            self.store(job).await;
+
+            let handler = self.handlers.read().unwrap().get(&job.kind).cloned();
+            let result = match handler {
+                Some(handler) => handler.run(&job.payload).await,
+                None => Err(anyhow!("no handler for {} jobs", job.kind)),
+            };
+
+            job.updated_at = Utc::now();
+            match result {
+                Ok(()) => {
+                    job.status = JobStatus::Succeeded;
+                    job.last_error = None;
+                    debug!("🛠️ {} job {} succeeded on attempt {}", job.kind, job.id, job.attempts);
+                }
+                Err(e) if job.attempts < job.max_attempts => {
+                    job.status = JobStatus::Pending;
+                    job.run_at = job.updated_at + self.retry_delay(job.attempts);
+                    job.last_error = Some(e.to_string());
+                    warn!("🛠️ {} job {} failed, retrying at {}: {}", job.kind, job.id, job.run_at, e);
+                }
+                Err(e) => {
+                    job.status = JobStatus::Failed;
+                    job.last_error = Some(e.to_string());
+                    warn!("🛠️ {} job {} failed after {} attempts: {}", job.kind, job.id, job.attempts, e);
+                }
+            }
+            self.store(job).await;
+        }
+
+        self.drop_old_finished().await;
+        due.len()
+    }
+
+    /// Run jobs as they come due, every poll interval until cancelled
+    pub async fn run(&self, shutdown: CancellationToken) {
+        info!("🛠️ Running background jobs every {}s", self.config.poll_interval_seconds);
+        let interval = std::time::Duration::from_secs(self.config.poll_interval_seconds.max(1));
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
+            self.run_due(Utc::now()).await;
+        }
+    }
+
+    fn retry_delay(&self, attempts: u32) -> Duration {
+        let seconds = (self.config.retry_delay_seconds as i64)
+            .saturating_mul(1i64 << attempts.saturating_sub(1).min(20))
+            .min(MAX_RETRY_DELAY_SECONDS);
+        Duration::seconds(seconds)
+    }
+
+    async fn store(&self, job: &Job) {
+        self.save(job).await;
+        self.jobs.lock().await.insert(job.id, job.clone());
+    }
+
+    async fn save(&self, job: &Job) {
+        if let Some(repository) = &self.repository {
+            if let Err(e) = repository.save_job(&job.to_record()).await {
+                warn!("🛠️ Failed to store {} job {}: {}", job.kind, job.id, e);
+            }
+        }
+    }
+
+    async fn drop_old_finished(&self) {
+        let mut jobs = self.jobs.lock().await;
+        let mut finished: Vec<(DateTime<Utc>, Uuid)> = jobs.values()
+            .filter(|job| job.status.is_finished())
+            .map(|job| (job.updated_at, job.id))
+            .collect();
+        if finished.len() <= MAX_FINISHED_JOBS {
+            return;
+        }
+        finished.sort();
+        for (_, id) in &finished[..finished.len() - MAX_FINISHED_JOBS] {
+            jobs.remove(id);
+        }
+    }
+}
+
+/// A match result whose bets failed to settle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettleMatchTask {
+    pub match_id: String,
+    pub home_goals: u8,
+    pub away_goals: u8,
+}
+
+/// Settles a match's open bets; settling twice is harmless
+pub struct SettleMatchHandler {
+    trading_engine: Arc<TradingEngine>,
+}
+
+impl SettleMatchHandler {
+    pub fn new(trading_engine: Arc<TradingEngine>) -> Self {
+        Self { trading_engine }
+    }
+}
+
+#[async_trait]
+impl JobHandler for SettleMatchHandler {
+    async fn run(&self, payload: &serde_json::Value) -> Result<()> {
+        let task: SettleMatchTask = serde_json::from_value(payload.clone())?;
+        let settled = self.trading_engine.settle_match(&task.match_id, task.home_goals, task.away_goals).await?;
+        info!("🏁 Settled {} bets on {} on retry", settled, task.match_id);
+        Ok(())
+    }
+}
+
+/// Stores odds ticks the database missed when they were quoted
+pub struct BackfillOddsHandler {
+    repository: Arc<Repository>,
+}
+
+impl BackfillOddsHandler {
+    pub fn new(repository: Arc<Repository>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl JobHandler for BackfillOddsHandler {
+    async fn run(&self, payload: &serde_json::Value) -> Result<()> {
+        let ticks: Vec<OddsRecord> = serde_json::from_value(payload.clone())?;
+        self.repository.record_odds(&ticks).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Fails until it has been called `succeed_on` times
+    struct Flaky {
+        calls: AtomicU32,
+        succeed_on: u32,
+    }
+
+    #[async_trait]
+    impl JobHandler for Flaky {
+        async fn run(&self, _payload: &serde_json::Value) -> Result<()> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if call < self.succeed_on {
+                return Err(anyhow!("attempt {} failed", call));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_jobs_retried_with_backoff_until_out_of_attempts() {
+        let queue = JobQueue::new(JobsConfig { max_attempts: 3, retry_delay_seconds: 10, ..Default::default() });
+        queue.register("flaky", Arc::new(Flaky { calls: AtomicU32::new(0), succeed_on: 2 }));
+        let flaky = queue.enqueue("flaky", serde_json::Value::Null).await;
+        let orphan = queue.enqueue("unknown", serde_json::Value::Null).await;
+
+        let now = Utc::now();
+        assert_eq!(queue.run_due(now).await, 2);
+        let retrying = queue.get(flaky.id).await.unwrap();
+        assert_eq!((retrying.status, retrying.attempts), (JobStatus::Pending, 1));
+        assert_eq!(retrying.last_error.as_deref(), Some("attempt 1 failed"));
+        assert!(retrying.run_at >= now + Duration::seconds(10));
+
+        // Not due again until the delay is up
+        assert_eq!(queue.run_due(now).await, 0);
+        let later = now + Duration::seconds(20);
+        queue.run_due(later).await;
+        let succeeded = queue.get(flaky.id).await.unwrap();
+        assert_eq!((succeeded.status, succeeded.attempts, succeeded.last_error), (JobStatus::Succeeded, 2, None));
+
+        // The second delay doubles, and the third attempt is the last
+        queue.run_due(now + Duration::seconds(40)).await;
+        queue.run_due(now + Duration::seconds(80)).await;
+        let failed = queue.get(orphan.id).await.unwrap();
+        assert_eq!((failed.status, failed.attempts), (JobStatus::Failed, 3));
+        assert_eq!(failed.last_error.as_deref(), Some("no handler for unknown jobs"));
+
+        assert_eq!(queue.list(Some(JobStatus::Failed), 10).await.len(), 1);
+        assert_eq!(queue.list(None, 1).await.len(), 1);
+    }
+}
@@ -0,0 +1,172 @@
+use quant_models::{BetStatus, BettingDecision, Result};
+use rust_decimal::Decimal;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// A portfolio mutation, recorded before it is applied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JournalRecord {
+    BetPlaced { bet: Box<BettingDecision> },
+    BetSettled { bet_id: Uuid, won: bool },
+    BetVoided { bet_id: Uuid },
+    /// Quarter-line Asian handicap: half refunded, the other half won or lost
+    BetHalfSettled { bet_id: Uuid, won: bool },
+    /// One leg of an accumulator, which closes with its last or first losing leg
+    AccumulatorLegSettled { bet_id: Uuid, match_id: String, status: BetStatus },
+    /// Money deposited (positive) or withdrawn (negative)
+    BankrollAdjusted { amount: Decimal, reason: Option<String> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub record: JournalRecord,
+}
+
+/// Append-only, fsynced log of trade executions and settlements.
+///
+/// Each entry is one JSON line. An entry is durable once `append` returns, and
+/// callers only mutate the portfolio after that, so replaying the journal can
+/// never produce a bet that was not acknowledged or lose one that was.
+pub struct TradeJournal {
+    path: PathBuf,
+    inner: Mutex<JournalWriter>,
+}
+
+struct JournalWriter {
+    file: File,
+    next_seq: u64,
+}
+
+impl TradeJournal {
+    /// Open (or create) the journal and return the entries already in it.
+    /// A torn final line left by a crash mid-append is truncated away.
+    pub fn open(path: impl AsRef<Path>) -> Result<(Self, Vec<JournalEntry>)> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(&path)?;
+
+        let mut entries = Vec::new();
+        let mut valid_len = 0u64;
+        {
+            let mut reader = BufReader::new(&file);
+            let mut line = String::new();
+            loop {
+                line.clear();
+                let read = reader.read_line(&mut line)?;
+                if read == 0 {
+                    break;
+                }
+                match serde_json::from_str::<JournalEntry>(line.trim_end()) {
+                    Ok(entry) if line.ends_with('\n') => {
+                        entries.push(entry);
+                        valid_len += read as u64;
+                    }
+                    _ => {
+                        warn!("📓 Discarding incomplete journal entry at byte {} of {}",
+                              valid_len, path.display());
+                        break;
+                    }
+                }
+            }
+        }
+
+        file.set_len(valid_len)?;
+        file.seek(SeekFrom::End(0))?;
+
+        let next_seq = entries.last().map(|entry| entry.seq + 1).unwrap_or(0);
+        info!("📓 Opened trade journal {} ({} entries)", path.display(), entries.len());
+
+        Ok((
+            Self {
+                path,
+                inner: Mutex::new(JournalWriter { file, next_seq }),
+            },
+            entries,
+        ))
+    }
+
+    /// Durably append a record, returning its sequence number
+    pub fn append(&self, record: JournalRecord) -> Result<u64> {
+        let mut writer = self.inner.lock();
+        let entry = JournalEntry {
+            seq: writer.next_seq,
+            timestamp: Utc::now(),
+            record,
+        };
+
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+
+        let offset = writer.file.stream_position()?;
+        let written = writer.file.write_all(&line).and_then(|_| writer.file.sync_data());
+        if let Err(e) = written {
+            // Roll back a partial write so later entries stay line-aligned
+            writer.file.set_len(offset)?;
+            writer.file.seek(SeekFrom::Start(offset))?;
+            return Err(e.into());
+        }
+
+        writer.next_seq += 1;
+        Ok(entry.seq)
+    }
+
+    /// Sequence number the next entry will get
+    pub fn next_seq(&self) -> u64 {
+        self.inner.lock().next_seq
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_models::BetType;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_torn_tail_is_discarded() {
+        let path = std::env::temp_dir().join(format!("journal-{}.log", Uuid::new_v4()));
+        let bet = BettingDecision::new(
+            "m1".to_string(), BetType::HomeWin, dec!(10), dec!(2.0), 0.6, "test".to_string(),
+        ).unwrap();
+
+        {
+            let (journal, entries) = TradeJournal::open(&path).unwrap();
+            assert!(entries.is_empty());
+            journal.append(JournalRecord::BetPlaced { bet: Box::new(bet.clone()) }).unwrap();
+        }
+
+        // Simulate a crash halfway through writing the next entry
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"seq\":1,\"timest").unwrap();
+        drop(file);
+
+        let (journal, entries) = TradeJournal::open(&path).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(journal.append(JournalRecord::BetSettled { bet_id: bet.id, won: true }).unwrap(), 1);
+
+        let (_, entries) = TradeJournal::open(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        std::fs::remove_file(&path).ok();
+    }
+}
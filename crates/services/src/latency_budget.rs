@@ -0,0 +1,151 @@
+// Time limits on the slow stages of the event pipeline, so one stuck
+// prediction or market can't hold up every event queued behind it
+
+use dashmap::DashMap;
+use quant_models::Prediction;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LatencyBudgetConfig {
+    pub enabled: bool,
+    pub simulator_ms: u64,
+    pub prediction_ms: u64,
+    pub trading_ms: u64,
+    /// Trade on the match's previous prediction when a new one runs over,
+    /// rather than skipping the event
+    pub fallback_to_last_prediction: bool,
+}
+
+impl Default for LatencyBudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            simulator_ms: 50,
+            prediction_ms: 50,
+            trading_ms: 50,
+            fallback_to_last_prediction: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PipelineStage {
+    Simulator,
+    Prediction,
+    Trading,
+}
+
+impl PipelineStage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Simulator => "simulator",
+            Self::Prediction => "prediction",
+            Self::Trading => "trading",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{} stage ran over its {budget:?} budget", stage.name())]
+pub struct StageTimeout {
+    pub stage: PipelineStage,
+    pub budget: Duration,
+}
+
+/// Runs pipeline stages within their budgets and keeps each match's latest
+/// prediction to fall back on
+pub struct LatencyBudget {
+    config: LatencyBudgetConfig,
+    last_predictions: DashMap<String, Prediction>,
+}
+
+impl LatencyBudget {
+    pub fn new(config: LatencyBudgetConfig) -> Self {
+        Self { config, last_predictions: DashMap::new() }
+    }
+
+    /// None while budgets are off
+    pub fn budget(&self, stage: PipelineStage) -> Option<Duration> {
+        if !self.config.enabled {
+            return None;
+        }
+        let millis = match stage {
+            PipelineStage::Simulator => self.config.simulator_ms,
+            PipelineStage::Prediction => self.config.prediction_ms,
+            PipelineStage::Trading => self.config.trading_ms,
+        };
+        Some(Duration::from_millis(millis))
+    }
+
+    /// Await `work`, dropping it if it runs over the stage's budget. Only
+    /// wrap work that is safe to abandon part way.
+    pub async fn run<F: Future>(&self, stage: PipelineStage, work: F) -> Result<F::Output, StageTimeout> {
+        match self.budget(stage) {
+            Some(budget) => tokio::time::timeout(budget, work)
+                .await
+                .map_err(|_| StageTimeout { stage, budget }),
+            None => Ok(work.await),
+        }
+    }
+
+    pub fn remember(&self, prediction: &Prediction) {
+        if self.config.fallback_to_last_prediction {
+            self.last_predictions.insert(prediction.match_id.clone(), prediction.clone());
+        }
+    }
+
+    /// The match's latest prediction, if falling back is on
+    pub fn fallback(&self, match_id: &str) -> Option<Prediction> {
+        if !self.config.fallback_to_last_prediction {
+            return None;
+        }
+        self.last_predictions.get(match_id).map(|prediction| prediction.clone())
+    }
+
+    /// Drop a finished match's prediction
+    pub fn forget(&self, match_id: &str) {
+        self.last_predictions.remove(match_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_slow_stage_times_out_and_falls_back() {
+        let budget = LatencyBudget::new(LatencyBudgetConfig {
+            enabled: true,
+            prediction_ms: 10,
+            ..Default::default()
+        });
+        assert_eq!(budget.run(PipelineStage::Prediction, async { 7 }).await, Ok(7));
+
+        let slow = tokio::time::sleep(Duration::from_millis(200));
+        let timeout = budget.run(PipelineStage::Prediction, slow).await.unwrap_err();
+        assert_eq!(timeout, StageTimeout { stage: PipelineStage::Prediction, budget: Duration::from_millis(10) });
+        assert_eq!(timeout.to_string(), "prediction stage ran over its 10ms budget");
+
+        assert!(budget.fallback("m1").is_none());
+        let prediction = Prediction::new(
+            "m1".to_string(), "poisson".to_string(), "1.0".to_string(), 0.5, 0.2, chrono::Utc::now(),
+        ).unwrap();
+        budget.remember(&prediction);
+        assert_eq!(budget.fallback("m1").map(|last| last.id), Some(prediction.id));
+        budget.forget("m1");
+        assert!(budget.fallback("m1").is_none());
+
+        // With budgets off, nothing is cut short
+        let unbounded = LatencyBudget::new(LatencyBudgetConfig::default());
+        assert!(unbounded.budget(PipelineStage::Trading).is_none());
+        let slow = async {
+            tokio::time::sleep(Duration::from_millis(60)).await;
+            "done"
+        };
+        assert_eq!(unbounded.run(PipelineStage::Trading, slow).await, Ok("done"));
+    }
+}
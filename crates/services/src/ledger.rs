@@ -0,0 +1,162 @@
+use quant_db::{PredictionRecord, PredictionRepository, Repository};
+use quant_ml::EvaluationSample;
+use quant_models::{Outcome1X2, Prediction, SimpleMarketOdds};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+const DEFAULT_LEDGER_CAPACITY: usize = 50_000;
+
+#[derive(Debug, Clone)]
+struct LedgerEntry {
+    prediction: Prediction,
+    odds: Option<SimpleMarketOdds>,
+}
+
+/// Final results, forgetting the oldest once over capacity
+#[derive(Debug, Default)]
+struct Results {
+    outcomes: HashMap<String, Outcome1X2>,
+    order: VecDeque<String>,
+}
+
+/// Keeps predictions with the odds on offer when they were made, plus final
+/// results, so models can be evaluated over any window after the fact.
+pub struct PredictionLedger {
+    entries: RwLock<VecDeque<LedgerEntry>>,
+    results: RwLock<Results>,
+    capacity: usize,
+    /// Where every prediction is also stored, so evaluation isn't limited to
+    /// the predictions held here
+    repository: Option<Arc<Repository>>,
+}
+
+impl PredictionLedger {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_LEDGER_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: RwLock::new(VecDeque::new()),
+            results: RwLock::new(Results::default()),
+            capacity: capacity.max(1),
+            repository: None,
+        }
+    }
+
+    /// Store each prediction and evaluate from the stored predictions
+    pub fn with_repository(mut self, repository: Arc<Repository>) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    pub async fn record_prediction(&self, prediction: Prediction, odds: Option<SimpleMarketOdds>) {
+        if let Some(repository) = &self.repository {
+            let record = PredictionRecord::new(&prediction, odds.as_ref());
+            if let Err(e) = repository.create_prediction(&record).await {
+                warn!("💾 Failed to store prediction {}: {}", prediction.id, e);
+            }
+        }
+
+        let mut entries = self.entries.write().await;
+        entries.push_back(LedgerEntry { prediction, odds });
+        while entries.len() > self.capacity {
+            entries.pop_front();
+        }
+    }
+
+    pub async fn record_result(&self, match_id: &str, outcome: Outcome1X2) {
+        let mut results = self.results.write().await;
+        if results.outcomes.insert(match_id.to_string(), outcome).is_none() {
+            results.order.push_back(match_id.to_string());
+        }
+        while results.order.len() > self.capacity {
+            if let Some(oldest) = results.order.pop_front() {
+                results.outcomes.remove(&oldest);
+            }
+        }
+    }
+
+    /// Every prediction still held for the match, oldest first
+    pub async fn match_predictions(&self, match_id: &str) -> Vec<Prediction> {
+        self.entries.read().await
+            .iter()
+            .filter(|entry| entry.prediction.match_id == match_id)
+            .map(|entry| entry.prediction.clone())
+            .collect()
+    }
+
+    /// Settled predictions from `model_name` made within `[from, to)`, read
+    /// from the repository when there is one
+    pub async fn samples(
+        &self,
+        model_name: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Vec<EvaluationSample> {
+        if let Some(repository) = &self.repository {
+            match repository.get_settled_predictions(model_name, from, to).await {
+                Ok(records) => {
+                    return records.iter()
+                        .map(|record| EvaluationSample {
+                            prediction: record.prediction.to_prediction(),
+                            outcome: record.outcome(),
+                            odds: record.prediction.to_simple_odds(),
+                        })
+                        .collect();
+                }
+                Err(e) => warn!("💾 Failed to read stored predictions for {}, using those held: {}", model_name, e),
+            }
+        }
+
+        let entries = self.entries.read().await;
+        let results = self.results.read().await;
+
+        entries
+            .iter()
+            .filter(|entry| entry.prediction.model_name == model_name)
+            .filter(|entry| {
+                entry.prediction.prediction_timestamp >= from && entry.prediction.prediction_timestamp < to
+            })
+            .filter_map(|entry| {
+                results.outcomes.get(&entry.prediction.match_id).map(|outcome| EvaluationSample {
+                    prediction: entry.prediction.clone(),
+                    outcome: *outcome,
+                    odds: entry.odds.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl Default for PredictionLedger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn test_results_forgotten_past_capacity() {
+        let ledger = PredictionLedger::with_capacity(2);
+        for match_id in ["m1", "m2", "m3"] {
+            let prediction = Prediction::new(
+                match_id.to_string(), "poisson".to_string(), "1.0".to_string(), 0.5, 0.2, Utc::now(),
+            ).unwrap();
+            ledger.record_prediction(prediction, None).await;
+            ledger.record_result(match_id, Outcome1X2::HomeWin).await;
+        }
+
+        assert_eq!(ledger.results.read().await.outcomes.len(), 2);
+        let now = Utc::now();
+        let samples = ledger.samples("poisson", now - Duration::hours(1), now + Duration::hours(1)).await;
+        assert_eq!(samples.iter().map(|s| s.prediction.match_id.as_str()).collect::<Vec<_>>(), ["m2", "m3"]);
+    }
+}
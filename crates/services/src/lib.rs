@@ -1,15 +1,97 @@
 pub mod data_feed;
+pub mod scenario;
+pub mod generator;
+pub mod match_state;
 pub mod predictor;
+pub mod shadow;
 pub mod trader;
+pub mod settlement;
+pub mod daily_loss;
+pub mod execution;
+pub mod http;
+pub mod betfair;
 pub mod market_simulator;
+pub mod bookmakers;
+pub mod odds_refresh;
+pub mod margin_model;
+pub mod odds_history;
+pub mod prediction_history;
+pub mod prediction_cache;
+pub mod match_summary;
+pub mod fixtures;
+pub mod feature_store;
+pub mod search;
+pub mod retention;
+pub mod rebuild;
+pub mod replay;
+pub mod event_log;
+pub mod season_archive;
+pub(crate) mod compression;
+pub mod blending;
+pub mod strategy_ensemble;
 pub mod metrics;
+pub mod memory;
+pub mod journal;
+pub mod audit;
+pub mod outbox;
+pub mod ledger;
+pub mod ingest;
+pub mod model_training;
+pub mod retraining;
+pub mod jobs;
 pub mod backtester;
+pub mod blotter;
 pub mod monitor;
+pub mod alerting;
+pub mod notifier;
+pub mod latency_budget;
+pub mod provenance;
+pub mod snapshot;
 
 pub use data_feed::*;
+pub use scenario::*;
+pub use generator::*;
+pub use match_state::*;
 pub use predictor::*;
+pub use shadow::*;
 pub use trader::*;
+pub use settlement::*;
+pub use daily_loss::*;
+pub use execution::*;
+pub use betfair::*;
 pub use market_simulator::*;
+pub use bookmakers::*;
+pub use odds_refresh::*;
+pub use margin_model::*;
+pub use odds_history::*;
+pub use prediction_history::*;
+pub use prediction_cache::*;
+pub use match_summary::*;
+pub use fixtures::*;
+pub use feature_store::*;
+pub use search::*;
+pub use retention::*;
+pub use rebuild::*;
+pub use replay::*;
+pub use event_log::*;
+pub use season_archive::*;
+pub use blending::*;
+pub use strategy_ensemble::*;
 pub use metrics::*;
+pub use memory::*;
+pub use journal::*;
+pub use audit::*;
+pub use outbox::*;
+pub use ledger::*;
+pub use ingest::*;
+pub use model_training::*;
+pub use retraining::*;
+pub use jobs::*;
 pub use backtester::*;
-pub use monitor::*;
\ No newline at end of file
+pub use blotter::*;
+pub use monitor::*;
+pub use alerting::*;
+pub use notifier::*;
+pub use latency_budget::*;
+pub use provenance::*;
+pub use snapshot::*;
\ No newline at end of file
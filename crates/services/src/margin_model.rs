@@ -0,0 +1,186 @@
+// Bookmaker overround by league tier, market and time to kick-off
+
+use quant_models::MarketType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How far before kick-off prices start at their widest
+const DEFAULT_TIGHTENING_HOURS: f64 = 48.0;
+/// Extra margin, relative to the kick-off margin, on prices that far out
+const DEFAULT_EARLY_PREMIUM: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeagueTier {
+    /// Heavily traded leagues priced tightly
+    Top,
+    Second,
+    /// Anything not classified
+    Lower,
+}
+
+/// Margins the simulated books apply. A quote's margin is its league tier's
+/// match winner margin, scaled by the market's multiplier and widened the
+/// further the quote is from kick-off.
+#[derive(Debug, Clone)]
+pub struct MarginModel {
+    tier_margins: HashMap<LeagueTier, f64>,
+    /// Keyed by lowercase league name
+    league_tiers: HashMap<String, LeagueTier>,
+    /// Keyed by market, e.g. `asian_handicap`
+    market_multipliers: HashMap<String, f64>,
+    early_premium: f64,
+    tightening_hours: f64,
+}
+
+impl MarginModel {
+    pub fn new() -> Self {
+        let tier_margins = HashMap::from([
+            (LeagueTier::Top, 0.04),
+            (LeagueTier::Second, 0.06),
+            (LeagueTier::Lower, 0.08),
+        ]);
+        let league_tiers = [
+            ("Premier League", LeagueTier::Top),
+            ("La Liga", LeagueTier::Top),
+            ("Bundesliga", LeagueTier::Top),
+            ("Serie A", LeagueTier::Top),
+            ("Ligue 1", LeagueTier::Top),
+            ("Championship", LeagueTier::Second),
+            ("2. Bundesliga", LeagueTier::Second),
+            ("Serie B", LeagueTier::Second),
+            ("Segunda Division", LeagueTier::Second),
+            ("Ligue 2", LeagueTier::Second),
+            ("Eredivisie", LeagueTier::Second),
+        ]
+        .into_iter()
+        .map(|(league, tier)| (league.to_lowercase(), tier))
+        .collect();
+        // Handicap and totals are two-way and fiercely competed; props are not
+        let market_multipliers = [
+            ("match_winner", 1.0),
+            ("asian_handicap", 0.6),
+            ("over_under", 0.8),
+            ("both_teams_to_score", 1.2),
+            ("correct_score", 3.0),
+            ("first_goalscorer", 4.0),
+            ("half_result", 1.25),
+        ]
+        .into_iter()
+        .map(|(market, multiplier)| (market.to_string(), multiplier))
+        .collect();
+
+        Self {
+            tier_margins,
+            league_tiers,
+            market_multipliers,
+            early_premium: DEFAULT_EARLY_PREMIUM,
+            tightening_hours: DEFAULT_TIGHTENING_HOURS,
+        }
+    }
+
+    /// Match winner margin at kick-off for a tier
+    pub fn with_tier_margin(mut self, tier: LeagueTier, margin: f64) -> Self {
+        self.tier_margins.insert(tier, margin.max(0.0));
+        self
+    }
+
+    pub fn with_league_tier(mut self, league: &str, tier: LeagueTier) -> Self {
+        self.league_tiers.insert(league.to_lowercase(), tier);
+        self
+    }
+
+    /// Margin on a market relative to match winner, keyed like `asian_handicap`
+    pub fn with_market_multiplier(mut self, market: &str, multiplier: f64) -> Self {
+        self.market_multipliers.insert(market.to_lowercase(), multiplier.max(0.0));
+        self
+    }
+
+    /// Quotes `hours` or more before kick-off carry `premium` times more
+    /// margin; the premium shrinks linearly to nothing at kick-off
+    pub fn with_kickoff_tightening(mut self, premium: f64, hours: f64) -> Self {
+        self.early_premium = premium.max(0.0);
+        self.tightening_hours = hours.max(f64::EPSILON);
+        self
+    }
+
+    pub fn tier_for(&self, league: &str) -> LeagueTier {
+        self.league_tiers
+            .get(&league.to_lowercase())
+            .copied()
+            .unwrap_or(LeagueTier::Lower)
+    }
+
+    /// Overround for a quote. In-play quotes, and quotes for matches whose
+    /// kick-off is unknown, get the kick-off margin.
+    pub fn margin(&self, league: &str, market: &MarketType, hours_to_kickoff: Option<f64>) -> f64 {
+        self.margin_from(self.tier_margin(self.tier_for(league)), market, hours_to_kickoff)
+    }
+
+    /// Like `margin`, starting from a given match winner margin at kick-off
+    /// instead of the league tier's
+    pub fn margin_from(&self, base: f64, market: &MarketType, hours_to_kickoff: Option<f64>) -> f64 {
+        let multiplier = self.market_multipliers.get(market_key(market)).copied().unwrap_or(1.0);
+        let early = hours_to_kickoff
+            .map_or(0.0, |hours| (hours / self.tightening_hours).clamp(0.0, 1.0));
+
+        base * multiplier * (1.0 + self.early_premium * early)
+    }
+
+    /// The league's tier margin relative to the top tier, for scaling the
+    /// margins of books that quote every league
+    pub fn tier_factor(&self, league: &str) -> f64 {
+        let top = self.tier_margin(LeagueTier::Top);
+        if top > f64::EPSILON {
+            self.tier_margin(self.tier_for(league)) / top
+        } else {
+            1.0
+        }
+    }
+
+    fn tier_margin(&self, tier: LeagueTier) -> f64 {
+        self.tier_margins.get(&tier).copied().unwrap_or(0.05)
+    }
+}
+
+impl Default for MarginModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn market_key(market: &MarketType) -> &'static str {
+    match market {
+        MarketType::MatchWinner => "match_winner",
+        MarketType::OverUnder { .. } => "over_under",
+        MarketType::AsianHandicap { .. } => "asian_handicap",
+        MarketType::BothTeamsToScore => "both_teams_to_score",
+        MarketType::CorrectScore => "correct_score",
+        MarketType::FirstGoalscorer => "first_goalscorer",
+        MarketType::HalfResult { .. } => "half_result",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_margin_by_tier_market_and_kickoff() {
+        let model = MarginModel::new().with_league_tier("Allsvenskan", LeagueTier::Second);
+
+        assert!((model.margin("Premier League", &MarketType::MatchWinner, None) - 0.04).abs() < 1e-9);
+        assert!((model.margin("allsvenskan", &MarketType::MatchWinner, None) - 0.06).abs() < 1e-9);
+        assert_eq!(model.tier_for("Faroe Islands Premier League"), LeagueTier::Lower);
+        assert!((model.tier_factor("Faroe Islands Premier League") - 2.0).abs() < 1e-9);
+
+        let handicap = MarketType::AsianHandicap { line: Decimal::ZERO };
+        assert!((model.margin("Premier League", &handicap, None) - 0.024).abs() < 1e-9);
+
+        // Half the premium a day out, all of it two days out and beyond
+        assert!((model.margin("Premier League", &MarketType::MatchWinner, Some(24.0)) - 0.05).abs() < 1e-9);
+        assert!((model.margin("Premier League", &MarketType::MatchWinner, Some(96.0)) - 0.06).abs() < 1e-9);
+        assert!((model.margin("Premier League", &MarketType::MatchWinner, Some(-1.0)) - 0.04).abs() < 1e-9);
+    }
+}
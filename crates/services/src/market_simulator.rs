@@ -1,6 +1,15 @@
-use quant_models::{SimpleMarketOdds, MatchEvent, Prediction};
+use crate::bookmakers::BookmakerProfile;
+use crate::margin_model::{LeagueTier, MarginModel};
+use crate::memory::{ApproxSize, CacheKind, MemoryAccountant};
+use quant_models::{
+    SimpleMarketOdds, MatchEvent, Prediction, MarketOdds, MarketType, OddsFormat, DerivedMarketOdds,
+    AsianHandicapOdds, AsianHandicapQuote, CorrectScoreOdds, CorrectScoreQuote, HalfResultOdds, MatchHalf,
+    EventType, MatchStatus,
+};
+use quant_ml::{PoissonModel, DEFAULT_TOTAL_GOALS};
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use rust_decimal_macros::dec;
 use anyhow::Result;
 use rand::{Rng, SeedableRng};
@@ -9,10 +18,53 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{RwLock, Mutex};
 use tracing::info;
+use uuid::Uuid;
+
+/// Quarter-goal lines quoted either side of the main Asian handicap line
+const HANDICAP_LADDER_STEPS: i64 = 2;
+/// Widest home handicap, in goals, considered for the main line
+const MAX_HANDICAP_GOALS: i64 = 3;
+/// Longest price quoted for an exact score
+const MAX_CORRECT_SCORE_ODDS: Decimal = dec!(250);
+/// Hours before kick-off from which a match's liquidity starts building
+const LIQUIDITY_BUILD_UP_HOURS: f64 = 48.0;
+/// Hours before kick-off from which prices start converging on fair value
+const MARKET_MOVE_HORIZON_HOURS: f64 = 48.0;
+
+/// Metadata key of an odds update carrying moved probabilities to price
+/// every market from, as `{ "home": p, "draw": p, "away": p }`
+pub const MARKET_MOVE_METADATA: &str = "market_move";
+
+#[derive(Debug, Clone, Default)]
+struct MatchPricing {
+    league: String,
+    kickoff: Option<DateTime<Utc>>,
+    /// Half time has been reached, so the first half is no longer quoted
+    first_half_over: bool,
+    /// Finished, postponed or cancelled; its markets no longer move
+    closed: bool,
+    /// Home, draw and away probabilities the last feed event priced from,
+    /// before noise and margin, which line moves drift back toward
+    fair: Option<[f64; 3]>,
+    /// Latest feed event, which odds updates for the match are modelled on
+    last_event: Option<MatchEvent>,
+}
 
 pub struct MarketSimulator {
+    /// Per-match overrides of the match winner margin at kick-off
     base_margins: Arc<RwLock<HashMap<String, f64>>>,
+    margin_model: MarginModel,
+    matches: Arc<RwLock<HashMap<String, MatchPricing>>>,
     market_odds: Arc<RwLock<HashMap<String, SimpleMarketOdds>>>,
+    asian_handicap_odds: Arc<RwLock<HashMap<String, AsianHandicapOdds>>>,
+    correct_score_odds: Arc<RwLock<HashMap<String, CorrectScoreOdds>>>,
+    half_result_odds: Arc<RwLock<HashMap<String, HalfResultOdds>>>,
+    /// Money available across each match's markets
+    liquidity: Arc<RwLock<HashMap<String, Decimal>>>,
+    odds_updated: Arc<RwLock<HashMap<String, DateTime<Utc>>>>,
+    bookmakers: Vec<BookmakerProfile>,
+    /// Each book's latest match winner quote per match, keyed by bookmaker
+    bookmaker_quotes: Arc<RwLock<HashMap<String, HashMap<String, MarketOdds>>>>,
     rng: Arc<Mutex<SmallRng>>,
 }
 
@@ -20,11 +72,50 @@ impl MarketSimulator {
     pub fn new() -> Self {
         Self {
             base_margins: Arc::new(RwLock::new(HashMap::new())),
+            margin_model: MarginModel::new(),
+            matches: Arc::new(RwLock::new(HashMap::new())),
             market_odds: Arc::new(RwLock::new(HashMap::new())),
+            asian_handicap_odds: Arc::new(RwLock::new(HashMap::new())),
+            correct_score_odds: Arc::new(RwLock::new(HashMap::new())),
+            half_result_odds: Arc::new(RwLock::new(HashMap::new())),
+            liquidity: Arc::new(RwLock::new(HashMap::new())),
+            odds_updated: Arc::new(RwLock::new(HashMap::new())),
+            bookmakers: BookmakerProfile::defaults(),
+            bookmaker_quotes: Arc::new(RwLock::new(HashMap::new())),
             rng: Arc::new(Mutex::new(SmallRng::from_entropy())),
         }
     }
 
+    /// Draw price noise from a generator seeded with `seed`, so runs can be repeated
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Arc::new(Mutex::new(SmallRng::seed_from_u64(seed)));
+        self
+    }
+
+    pub fn with_margin_model(mut self, margin_model: MarginModel) -> Self {
+        self.margin_model = margin_model;
+        self
+    }
+
+    /// Replace the built-in bookmakers
+    pub fn with_bookmakers(mut self, bookmakers: Vec<BookmakerProfile>) -> Self {
+        self.bookmakers = bookmakers;
+        self
+    }
+
+    pub fn bookmakers(&self) -> &[BookmakerProfile] {
+        &self.bookmakers
+    }
+
+    /// Scheduled kick-off, so quotes before it carry the wider early margin.
+    /// A match start event also sets it.
+    pub async fn set_kickoff(&self, match_id: &str, league: &str, kickoff: DateTime<Utc>) {
+        self.matches.write().await
+            .entry(match_id.to_string())
+            .or_insert_with(|| MatchPricing { league: league.to_string(), ..Default::default() })
+            .kickoff = Some(kickoff);
+    }
+
     /// Generate realistic market odds based on match event and context
     pub async fn generate_market_odds(&self, event: &MatchEvent) -> Result<SimpleMarketOdds> {
         // Base probabilities influenced by team strength and match state
@@ -32,10 +123,15 @@ impl MarketSimulator {
         
         // Adjust probabilities based on current match state
         self.adjust_for_match_state(event, &mut home_prob, &mut draw_prob, &mut away_prob);
+        self.track_match(event).await;
         
-        // Add some randomness to simulate market inefficiencies
-        let noise_factor = 0.02; // 2% random variation
-        {
+        // A line move carries its own noise; anything else is fair value plus
+        // some randomness to simulate market inefficiencies
+        if market_move(event).is_none() {
+            if let Some(pricing) = self.matches.write().await.get_mut(&event.match_id) {
+                pricing.fair = Some([home_prob, draw_prob, away_prob]);
+            }
+            let noise_factor = 0.02; // 2% random variation
             let mut rng = self.rng.lock().await;
             home_prob += rng.gen_range(-noise_factor..noise_factor);
             draw_prob += rng.gen_range(-noise_factor..noise_factor);
@@ -48,14 +144,36 @@ impl MarketSimulator {
         draw_prob /= total;
         away_prob /= total;
         
-        // Get bookmaker margin (overround)
-        let margin = self.get_margin_for_match(&event.match_id).await;
+        // Get bookmaker margin (overround) for the league, market and time to kick-off
+        let margin = self.get_margin_for_match(&event.match_id, &MarketType::MatchWinner, event.timestamp).await;
+        let handicap_margin = self.get_margin_for_match(
+            &event.match_id,
+            &MarketType::AsianHandicap { line: Decimal::ZERO },
+            event.timestamp,
+        ).await;
+        let correct_score_margin = self.get_margin_for_match(&event.match_id, &MarketType::CorrectScore, event.timestamp).await;
+        let half_result_margin = self.get_margin_for_match(
+            &event.match_id,
+            &MarketType::HalfResult { half: MatchHalf::Second },
+            event.timestamp,
+        ).await;
+        let first_half_open = self.matches.read().await
+            .get(&event.match_id)
+            .is_some_and(|pricing| !pricing.first_half_over);
         
         // Convert to odds with margin
         let odds = SimpleMarketOdds::from_probabilities(home_prob, draw_prob, away_prob, margin);
         
         // Store the odds
-        self.market_odds.write().await.insert(event.match_id.clone(), odds.clone());
+        self.store_odds(&event.match_id, odds.clone()).await;
+        self.asian_handicap_odds.write().await
+            .insert(event.match_id.clone(), asian_handicap_odds(home_prob, away_prob, handicap_margin));
+        self.correct_score_odds.write().await
+            .insert(event.match_id.clone(), correct_score_odds(home_prob, away_prob, correct_score_margin));
+        self.half_result_odds.write().await
+            .insert(event.match_id.clone(), half_result_odds(home_prob, away_prob, half_result_margin, first_half_open));
+        let liquidity = self.simulated_liquidity(&event.match_id, event.timestamp).await;
+        self.liquidity.write().await.insert(event.match_id.clone(), liquidity);
         
         info!("📊 Generated market odds for {}: Home={:.2} Draw={:.2} Away={:.2}", 
               event.match_id, odds.home_win, odds.draw, odds.away_win);
@@ -63,12 +181,63 @@ impl MarketSimulator {
         Ok(odds)
     }
 
+    /// Quote the match winner market from every simulated bookmaker. Each book
+    /// sees the same underlying probabilities through its own noise and bias
+    /// and applies a margin drawn from its own range, so the best price for
+    /// an outcome can come from different books. A book still within its
+    /// update latency of its last quote keeps that quote, stale as it is.
+    pub async fn generate_bookmaker_odds(&self, event: &MatchEvent) -> Vec<MarketOdds> {
+        let (mut home_prob, mut draw_prob, mut away_prob) = self.calculate_base_probabilities(event);
+        self.adjust_for_match_state(event, &mut home_prob, &mut draw_prob, &mut away_prob);
+
+        // Every book widens its usual margin for lesser leagues and early prices
+        self.track_match(event).await;
+        let tier_factor = self.margin_model.tier_factor(&event.league);
+        let hours_to_kickoff = self.hours_to_kickoff(&event.match_id, event.timestamp).await;
+
+        let mut rng = self.rng.lock().await;
+        let mut quotes = self.bookmaker_quotes.write().await;
+        let quotes = quotes.entry(event.match_id.clone()).or_default();
+        self.bookmakers
+            .iter()
+            .map(|book| {
+                if let Some(last) = quotes.get(&book.name) {
+                    if event.timestamp < last.timestamp + book.update_latency() {
+                        return last.clone();
+                    }
+                }
+
+                let [home, draw, away] = book.view([home_prob, draw_prob, away_prob], &mut *rng);
+                let margin = book.draw_margin(&mut *rng) * tier_factor;
+                let margin = self.margin_model.margin_from(margin, &MarketType::MatchWinner, hours_to_kickoff);
+                let odds = SimpleMarketOdds::from_probabilities(home, draw, away, margin);
+
+                let quote = MarketOdds {
+                    id: Uuid::new_v4(),
+                    match_id: event.match_id.clone(),
+                    market_type: MarketType::MatchWinner,
+                    bookmaker: book.name.clone(),
+                    odds: OddsFormat::Decimal {
+                        home: odds.home_win.round_dp(2),
+                        draw: Some(odds.draw.round_dp(2)),
+                        away: odds.away_win.round_dp(2),
+                    },
+                    timestamp: event.timestamp,
+                    is_active: true,
+                };
+                quotes.insert(book.name.clone(), quote.clone());
+                quote
+            })
+            .collect()
+    }
+
     /// Update odds based on new match events (e.g., goals, cards)
     pub async fn update_odds_for_event(&self, event: &MatchEvent) -> Result<Option<SimpleMarketOdds>> {
         // Only update odds for significant events
         match &event.event_type {
             quant_models::EventType::Goal { .. } |
-            quant_models::EventType::Card { .. } => {
+            quant_models::EventType::Card { .. } |
+            quant_models::EventType::Lineup { .. } => {
                 let updated_odds = self.generate_market_odds(event).await?;
                 Ok(Some(updated_odds))
             }
@@ -100,7 +269,7 @@ impl MarketSimulator {
         );
         
         // Store the odds
-        self.market_odds.write().await.insert(prediction.match_id.clone(), odds.clone());
+        self.store_odds(&prediction.match_id, odds.clone()).await;
         
         Ok(odds)
     }
@@ -109,6 +278,80 @@ impl MarketSimulator {
         self.market_odds.read().await.get(match_id).cloned()
     }
 
+    /// Double chance and draw no bet quotes derived from the current match odds
+    pub async fn get_derived_odds(&self, match_id: &str) -> Option<DerivedMarketOdds> {
+        self.get_current_odds(match_id).await
+            .map(|odds| DerivedMarketOdds::from_match_odds(&odds))
+    }
+
+    /// Asian handicap ladder priced from the same probabilities and margin
+    /// as the current match odds
+    pub async fn get_asian_handicap_odds(&self, match_id: &str) -> Option<AsianHandicapOdds> {
+        self.asian_handicap_odds.read().await.get(match_id).cloned()
+    }
+
+    /// Exact score prices from the same probabilities as the current match odds
+    pub async fn get_correct_score_odds(&self, match_id: &str) -> Option<CorrectScoreOdds> {
+        self.correct_score_odds.read().await.get(match_id).cloned()
+    }
+
+    /// First and second half result prices; the first half is gone once
+    /// half time has been reached
+    pub async fn get_half_result_odds(&self, match_id: &str) -> Option<HalfResultOdds> {
+        self.half_result_odds.read().await.get(match_id).cloned()
+    }
+
+    /// Simulated money available across the match's markets
+    pub async fn get_liquidity(&self, match_id: &str) -> Option<Decimal> {
+        self.liquidity.read().await.get(match_id).copied()
+    }
+
+    async fn store_odds(&self, match_id: &str, odds: SimpleMarketOdds) {
+        self.market_odds.write().await.insert(match_id.to_string(), odds);
+        self.odds_updated.write().await.insert(match_id.to_string(), Utc::now());
+    }
+
+    /// Evict the least recently updated odds when the cache exceeds its memory budget
+    pub async fn enforce_memory_budget(&self, accountant: &MemoryAccountant) -> usize {
+        let mut market_odds = self.market_odds.write().await;
+        let mut odds_updated = self.odds_updated.write().await;
+
+        let entry_size = |match_id: &String, odds: &SimpleMarketOdds| {
+            match_id.capacity() + odds.approx_size() + std::mem::size_of::<DateTime<Utc>>()
+        };
+        let bytes: usize = market_odds.iter().map(|(id, odds)| entry_size(id, odds)).sum();
+        let evict = accountant.entries_over_budget(CacheKind::Odds, market_odds.len(), bytes);
+
+        if evict > 0 {
+            let mut by_age: Vec<(String, DateTime<Utc>)> = odds_updated
+                .iter()
+                .map(|(id, updated)| (id.clone(), *updated))
+                .collect();
+            by_age.sort_by_key(|(_, updated)| *updated);
+
+            let mut asian_handicap_odds = self.asian_handicap_odds.write().await;
+            let mut correct_score_odds = self.correct_score_odds.write().await;
+            let mut half_result_odds = self.half_result_odds.write().await;
+            let mut liquidity = self.liquidity.write().await;
+            let mut matches = self.matches.write().await;
+            let mut bookmaker_quotes = self.bookmaker_quotes.write().await;
+            for (match_id, _) in by_age.into_iter().take(evict) {
+                market_odds.remove(&match_id);
+                asian_handicap_odds.remove(&match_id);
+                correct_score_odds.remove(&match_id);
+                half_result_odds.remove(&match_id);
+                liquidity.remove(&match_id);
+                matches.remove(&match_id);
+                bookmaker_quotes.remove(&match_id);
+                odds_updated.remove(&match_id);
+            }
+        }
+
+        let remaining: usize = market_odds.iter().map(|(id, odds)| entry_size(id, odds)).sum();
+        accountant.record(CacheKind::Odds, market_odds.len(), remaining, evict);
+        evict
+    }
+
     fn calculate_base_probabilities(&self, event: &MatchEvent) -> (f64, f64, f64) {
         // Simplified base probabilities
         // In a real system, this would use team ratings, head-to-head records, etc.
@@ -165,6 +408,30 @@ impl MarketSimulator {
                     *draw_prob += adjustment * 0.3;
                 }
             }
+            // Missing players lengthen their side's price
+            quant_models::EventType::Lineup { team, absentees, .. } => {
+                let adjustment = absentees.iter().map(|absentee| absentee.importance.max(0.0)).sum::<f64>().min(0.3);
+                if team == &event.team_home {
+                    *home_prob -= adjustment;
+                    *away_prob += adjustment * 0.7;
+                    *draw_prob += adjustment * 0.3;
+                } else {
+                    *away_prob -= adjustment;
+                    *home_prob += adjustment * 0.7;
+                    *draw_prob += adjustment * 0.3;
+                }
+            }
+            // Line moves and scripted scenarios move the market directly
+            quant_models::EventType::OddsUpdate => {
+                if let Some([home, draw, away]) = market_move(event) {
+                    (*home_prob, *draw_prob, *away_prob) = (home, draw, away);
+                } else if let Some(shock) = event.metadata.get("odds_shock") {
+                    let factor = |outcome: &str| shock.get(outcome).and_then(|v| v.as_f64()).unwrap_or(1.0);
+                    *home_prob *= factor("home");
+                    *draw_prob *= factor("draw");
+                    *away_prob *= factor("away");
+                }
+            }
             _ => {}
         }
         
@@ -188,62 +455,201 @@ impl MarketSimulator {
         *away_prob /= total;
     }
 
-    async fn get_margin_for_match(&self, match_id: &str) -> f64 {
-        // Different bookmakers have different margins
-        let margins = self.base_margins.read().await;
-        if let Some(margin) = margins.get(match_id).copied() {
-            margin
-        } else {
-            // Typical bookmaker margins: 2-8%
-            let mut rng = self.rng.lock().await;
-            rng.gen_range(0.02..0.08)
+    async fn track_match(&self, event: &MatchEvent) {
+        let mut matches = self.matches.write().await;
+        let pricing = matches.entry(event.match_id.clone()).or_insert_with(|| MatchPricing {
+            league: event.league.clone(),
+            ..Default::default()
+        });
+        if matches!(event.event_type, quant_models::EventType::MatchStart) {
+            pricing.kickoff.get_or_insert(event.timestamp);
+        }
+        if matches!(event.event_type, quant_models::EventType::HalfTime)
+            || matches!(event.match_status, quant_models::MatchStatus::HalfTime | quant_models::MatchStatus::Finished)
+        {
+            pricing.first_half_over = true;
+        }
+        if matches!(event.event_type, EventType::FullTime | EventType::MatchEnd)
+            || matches!(event.match_status, MatchStatus::Finished | MatchStatus::Postponed | MatchStatus::Cancelled)
+        {
+            pricing.closed = true;
+        }
+        pricing.last_event = Some(event.clone());
+    }
+
+    async fn hours_to_kickoff(&self, match_id: &str, at: DateTime<Utc>) -> Option<f64> {
+        let kickoff = self.matches.read().await.get(match_id)?.kickoff?;
+        Some((kickoff - at).num_seconds() as f64 / 3600.0)
+    }
+
+    async fn get_margin_for_match(&self, match_id: &str, market: &MarketType, at: DateTime<Utc>) -> f64 {
+        let hours_to_kickoff = self.hours_to_kickoff(match_id, at).await;
+        if let Some(margin) = self.base_margins.read().await.get(match_id).copied() {
+            return self.margin_model.margin_from(margin, market, hours_to_kickoff);
         }
+        let league = self.matches.read().await
+            .get(match_id)
+            .map(|pricing| pricing.league.clone())
+            .unwrap_or_default();
+        self.margin_model.margin(&league, market, hours_to_kickoff)
+    }
+
+    /// Deepest in heavily traded leagues, and building from a tenth of the
+    /// full amount two days out to all of it at kick-off
+    async fn simulated_liquidity(&self, match_id: &str, at: DateTime<Utc>) -> Decimal {
+        let league = self.matches.read().await
+            .get(match_id)
+            .map(|pricing| pricing.league.clone())
+            .unwrap_or_default();
+        let full = match self.margin_model.tier_for(&league) {
+            LeagueTier::Top => 500_000.0,
+            LeagueTier::Second => 100_000.0,
+            LeagueTier::Lower => 20_000.0,
+        };
+        let build_up = self.hours_to_kickoff(match_id, at).await
+            .map_or(1.0, |hours| 1.0 - 0.9 * (hours / LIQUIDITY_BUILD_UP_HOURS).clamp(0.0, 1.0));
+        let noise = self.rng.lock().await.gen_range(0.8..1.2);
+
+        Decimal::from_f64(full * build_up * noise).unwrap_or_default().round_dp(0)
     }
 
     pub async fn set_margin_for_match(&self, match_id: String, margin: f64) {
         self.base_margins.write().await.insert(match_id, margin);
     }
 
-    /// Simulate market movement over time
-    pub async fn simulate_market_movement(&self, match_id: &str, time_factor: f64) -> Result<()> {
-        if let Some(odds) = self.get_current_odds(match_id).await {
-            // Markets become more efficient (tighter) closer to match time
-            let efficiency_factor = time_factor * 0.5; // 0.0 = early, 0.5 = near kickoff
-            let volatility = 0.05 * (1.0 - efficiency_factor); // Less volatile near kickoff
-            
-            // Apply small random movements
-            let (home_change, draw_change, away_change) = {
-                let mut rng = self.rng.lock().await;
-                (
-                    rng.gen_range(-volatility..volatility),
-                    rng.gen_range(-volatility..volatility),
-                    rng.gen_range(-volatility..volatility),
-                )
+    /// Where a match's prices drift next: from their current level toward
+    /// fair value, faster and with less noise as `time_factor` goes from 0
+    /// (far from kick-off) to 1 (at or past it). Returns home, draw and
+    /// away probabilities without margin, or `None` before the match has
+    /// been priced.
+    pub async fn simulate_market_movement(&self, match_id: &str, time_factor: f64) -> Result<Option<[f64; 3]>> {
+        let Some(odds) = self.get_current_odds(match_id).await else {
+            return Ok(None);
+        };
+        let implied = [odds.home_win, odds.draw, odds.away_win]
+            .map(|price| 1.0 / price.to_f64().unwrap_or(3.0).max(1.01));
+        let total: f64 = implied.iter().sum();
+        let current = implied.map(|probability| probability / total);
+        let fair = self.matches.read().await
+            .get(match_id)
+            .and_then(|pricing| pricing.fair)
+            .unwrap_or(current);
+
+        // Markets become more efficient closer to match time
+        let time_factor = time_factor.clamp(0.0, 1.0);
+        let pull = 0.1 + 0.4 * time_factor;
+        let volatility = 0.03 * (1.0 - 0.8 * time_factor);
+        let mut moved = [0.0; 3];
+        {
+            let mut rng = self.rng.lock().await;
+            for (k, probability) in moved.iter_mut().enumerate() {
+                let noise = rng.gen_range(-volatility..volatility);
+                *probability = (current[k] + (fair[k] - current[k]) * pull + noise).max(0.01);
+            }
+        }
+        let total: f64 = moved.iter().sum();
+        Ok(Some(moved.map(|probability| probability / total)))
+    }
+
+    /// An odds update for every match whose markets are still open, carrying
+    /// its moved probabilities. Pricing the update reprices every market.
+    pub async fn market_movements(&self, at: DateTime<Utc>) -> Vec<MatchEvent> {
+        let open: Vec<(MatchEvent, Option<DateTime<Utc>>)> = self.matches.read().await
+            .values()
+            .filter(|pricing| !pricing.closed)
+            .filter_map(|pricing| Some((pricing.last_event.clone()?, pricing.kickoff)))
+            .collect();
+
+        let mut updates = Vec::with_capacity(open.len());
+        for (last_event, kickoff) in open {
+            // Unknown kick-offs are taken to be here
+            let time_factor = kickoff.map_or(1.0, |kickoff| {
+                let hours = (kickoff - at).num_seconds() as f64 / 3600.0;
+                1.0 - (hours / MARKET_MOVE_HORIZON_HOURS).clamp(0.0, 1.0)
+            });
+            let Ok(Some([home, draw, away])) = self.simulate_market_movement(&last_event.match_id, time_factor).await else {
+                continue;
             };
-            
-            // Convert to probabilities, adjust, convert back
-            let home_prob = 1.0 / odds.home_win.to_f64().unwrap_or(2.0);
-            let draw_prob = 1.0 / odds.draw.to_f64().unwrap_or(3.0);
-            let away_prob = 1.0 / odds.away_win.to_f64().unwrap_or(2.0);
-            
-            let new_home_prob = (home_prob + home_change).max(0.1).min(0.8);
-            let new_draw_prob = (draw_prob + draw_change).max(0.1).min(0.4);
-            let new_away_prob = (away_prob + away_change).max(0.1).min(0.8);
-            
-            // Normalize
-            let total = new_home_prob + new_draw_prob + new_away_prob;
-            let norm_home = new_home_prob / total;
-            let norm_draw = new_draw_prob / total;
-            let norm_away = new_away_prob / total;
-            
-            // Get margin and create new odds
-            let margin = self.get_margin_for_match(match_id).await;
-            let new_odds = SimpleMarketOdds::from_probabilities(norm_home, norm_draw, norm_away, margin);
-            
-            self.market_odds.write().await.insert(match_id.to_string(), new_odds);
+            updates.push(MatchEvent {
+                id: Uuid::new_v4(),
+                timestamp: at,
+                event_type: EventType::OddsUpdate,
+                metadata: serde_json::json!({ MARKET_MOVE_METADATA: { "home": home, "draw": draw, "away": away } }),
+                sequence: None,
+                ..last_event
+            });
         }
-        
-        Ok(())
+        updates
+    }
+}
+
+/// Probabilities a line move carries, if `event` is one
+fn market_move(event: &MatchEvent) -> Option<[f64; 3]> {
+    let moved = event.metadata.get(MARKET_MOVE_METADATA)?;
+    let probability = |outcome: &str| moved.get(outcome).and_then(|p| p.as_f64()).filter(|p| *p > 0.0);
+    Some([probability("home")?, probability("draw")?, probability("away")?])
+}
+
+/// Fit goal expectations to the match odds, find the home line closest to
+/// an even split and quote the lines around it with `margin` on both sides
+fn asian_handicap_odds(home_prob: f64, away_prob: f64, margin: f64) -> AsianHandicapOdds {
+    let (lambda_home, lambda_away) = PoissonModel::implied_lambdas(home_prob, away_prob, DEFAULT_TOTAL_GOALS);
+    let line = |quarters: i64| Decimal::new(quarters * 25, 2).normalize();
+    let home_cover = |quarters: i64| {
+        PoissonModel::handicap_probabilities(lambda_home, lambda_away, line(quarters)).win_given_no_push()
+    };
+    let price = |probability: f64| {
+        Decimal::from_f64_retain(1.0 / (probability.max(0.01) * (1.0 + margin)))
+            .unwrap_or(Decimal::ONE)
+            .max(dec!(1.01))
+            .round_dp(2)
+    };
+
+    let main = (-MAX_HANDICAP_GOALS * 4..=MAX_HANDICAP_GOALS * 4)
+        .min_by(|a, b| (home_cover(*a) - 0.5).abs().total_cmp(&(home_cover(*b) - 0.5).abs()))
+        .unwrap_or(0);
+    let quotes = (main - HANDICAP_LADDER_STEPS..=main + HANDICAP_LADDER_STEPS)
+        .map(|quarters| {
+            let home = home_cover(quarters);
+            AsianHandicapQuote { line: line(quarters), home: price(home), away: price(1.0 - home) }
+        })
+        .collect();
+
+    AsianHandicapOdds { main_line: line(main), quotes }
+}
+
+/// Price every score in the Poisson grid fitted to the match odds. Margin
+/// is applied proportionally, so long shots carry most of it in price terms.
+fn correct_score_odds(home_prob: f64, away_prob: f64, margin: f64) -> CorrectScoreOdds {
+    let (lambda_home, lambda_away) = PoissonModel::implied_lambdas(home_prob, away_prob, DEFAULT_TOTAL_GOALS);
+    let quotes = PoissonModel::score_matrix(lambda_home, lambda_away)
+        .scores()
+        .map(|(home_goals, away_goals, probability)| CorrectScoreQuote {
+            home_goals,
+            away_goals,
+            price: Decimal::from_f64_retain(1.0 / (probability * (1.0 + margin)))
+                .unwrap_or(MAX_CORRECT_SCORE_ODDS)
+                .min(MAX_CORRECT_SCORE_ODDS)
+                .max(dec!(1.01))
+                .round_dp(2),
+        })
+        .collect();
+
+    CorrectScoreOdds { quotes }
+}
+
+/// Price each half's result from the Poisson rates fitted to the match
+/// odds, with each half's goals at its share of the match total
+fn half_result_odds(home_prob: f64, away_prob: f64, margin: f64, first_half_open: bool) -> HalfResultOdds {
+    let (lambda_home, lambda_away) = PoissonModel::implied_lambdas(home_prob, away_prob, DEFAULT_TOTAL_GOALS);
+    let quote = |half| {
+        let (home, draw, away) = PoissonModel::half_result_probabilities(lambda_home, lambda_away, half);
+        SimpleMarketOdds::from_probabilities(home, draw, away, margin)
+    };
+
+    HalfResultOdds {
+        first_half: first_half_open.then(|| quote(MatchHalf::First)),
+        second_half: quote(MatchHalf::Second),
     }
 }
 
@@ -257,24 +663,19 @@ impl Default for MarketSimulator {
 mod tests {
     use super::*;
     use quant_models::{EventType, MatchStatus};
-    use chrono::Utc;
-    use uuid::Uuid;
 
     #[tokio::test]
     async fn test_market_odds_generation() {
         let simulator = MarketSimulator::new();
         
-        let event = MatchEvent {
-            id: Uuid::new_v4(),
-            match_id: "test_match".to_string(),
-            timestamp: Utc::now(),
-            event_type: EventType::MatchStart,
-            team_home: "Arsenal".to_string(),
-            team_away: "Chelsea".to_string(),
-            league: "Premier League".to_string(),
-            match_status: MatchStatus::Live,
-            metadata: serde_json::Value::Null,
-        };
+        let event = MatchEvent::new(
+            "test_match".to_string(),
+            EventType::MatchStart,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        ).with_status(MatchStatus::Live);
         
         let odds = simulator.generate_market_odds(&event).await.unwrap();
         
@@ -284,5 +685,168 @@ mod tests {
         assert!(odds.draw > dec!(1.1));
         assert!(odds.away_win > dec!(1.1));
         assert!(odds.away_win < dec!(10.0));
+
+        // The home side is favoured, so it gives goals on the main line
+        let handicap = simulator.get_asian_handicap_odds("test_match").await.unwrap();
+        assert!(handicap.main_line <= Decimal::ZERO);
+        assert_eq!(handicap.quotes.len(), 5);
+        let main = handicap.price(handicap.main_line, true).unwrap();
+        assert!(main > dec!(1.6) && main < dec!(2.4));
+        assert_eq!(handicap.price(-handicap.main_line, false), Some(handicap.quotes[2].away));
+
+        // Exact scores are long shots, and lopsided ones hit the price cap
+        let correct_score = simulator.get_correct_score_odds("test_match").await.unwrap();
+        assert_eq!(correct_score.quotes.len(), 36);
+        assert!(correct_score.price(1, 1).unwrap() > dec!(5.0));
+        assert!(correct_score.price(1, 0).unwrap() < correct_score.price(0, 1).unwrap());
+        assert_eq!(correct_score.price(0, 5), Some(MAX_CORRECT_SCORE_ODDS));
+
+        // Fewer goals in a half make the draw shorter than over the match,
+        // and the first half, with fewer still, shortest of all
+        let halves = simulator.get_half_result_odds("test_match").await.unwrap();
+        let first_half = halves.first_half.clone().unwrap();
+        assert!(first_half.draw < halves.second_half.draw);
+        assert!(halves.second_half.draw < odds.draw);
+        assert!(first_half.home_win < first_half.away_win);
+
+        let half_time = MatchEvent::new(
+            "test_match".to_string(),
+            EventType::HalfTime,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        ).with_status(MatchStatus::HalfTime);
+        simulator.generate_market_odds(&half_time).await.unwrap();
+        let halves = simulator.get_half_result_odds("test_match").await.unwrap();
+        assert!(halves.first_half.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_odds_shock_moves_prices() {
+        let simulator = MarketSimulator::new();
+        let mut event = MatchEvent::new(
+            "shock".to_string(),
+            EventType::OddsUpdate,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        );
+        let before = simulator.generate_market_odds(&event).await.unwrap();
+
+        event.metadata = serde_json::json!({ "odds_shock": { "home": 0.6, "away": 2.0 } });
+        let after = simulator.generate_market_odds(&event).await.unwrap();
+        assert!(after.away_win < before.away_win);
+        assert!(after.home_win > before.home_win);
+    }
+
+    #[tokio::test]
+    async fn test_market_moves_toward_fair_value() {
+        let simulator = MarketSimulator::new().with_seed(3);
+        let event = MatchEvent::new(
+            "drift".to_string(),
+            EventType::MatchStart,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        );
+        simulator.generate_market_odds(&event).await.unwrap();
+        let fair = simulator.matches.read().await["drift"].fair.unwrap();
+
+        // Knock the away price well out, then let the market drift back
+        let mut shock = event.clone();
+        shock.event_type = EventType::OddsUpdate;
+        shock.metadata = serde_json::json!({ MARKET_MOVE_METADATA: { "home": 0.3, "draw": 0.2, "away": 0.5 } });
+        simulator.generate_market_odds(&shock).await.unwrap();
+        for _ in 0..20 {
+            let updates = simulator.market_movements(Utc::now()).await;
+            assert_eq!(updates.len(), 1);
+            assert_eq!((updates[0].event_type.clone(), updates[0].sequence), (EventType::OddsUpdate, None));
+            simulator.generate_market_odds(&updates[0]).await.unwrap();
+        }
+        let odds = simulator.get_current_odds("drift").await.unwrap();
+        let away = 1.0 / odds.away_win.to_f64().unwrap() / (1.0 + odds.overround());
+        assert!((away - fair[2]).abs() < 0.05, "away {} vs fair {}", away, fair[2]);
+        // The move didn't become the new fair value
+        assert_eq!(simulator.matches.read().await["drift"].fair, Some(fair));
+
+        let mut full_time = event.clone();
+        full_time.event_type = EventType::FullTime;
+        simulator.generate_market_odds(&full_time).await.unwrap();
+        assert!(simulator.market_movements(Utc::now()).await.is_empty());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_margin_follows_league_tier_and_kickoff() {
+        let simulator = MarketSimulator::new();
+        let overround = SimpleMarketOdds::overround;
+        let event = |match_id: &str, league: &str| MatchEvent::new(
+            match_id.to_string(),
+            EventType::MatchStart,
+            "Home".to_string(),
+            "Away".to_string(),
+            league.to_string(),
+            "2024-25".to_string(),
+        );
+
+        let top = simulator.generate_market_odds(&event("m1", "Premier League")).await.unwrap();
+        let lower = simulator.generate_market_odds(&event("m2", "Faroe Islands Premier League")).await.unwrap();
+        assert!((overround(&top) - 0.04).abs() < 1e-3);
+        assert!((overround(&lower) - 0.08).abs() < 1e-3);
+
+        // A quote two days before a scheduled kick-off carries the full early premium
+        let mut early = event("m3", "Premier League").with_status(MatchStatus::Scheduled);
+        early.event_type = EventType::OddsUpdate;
+        simulator.set_kickoff("m3", "Premier League", early.timestamp + chrono::Duration::hours(48)).await;
+        let early = simulator.generate_market_odds(&early).await.unwrap();
+        assert!((overround(&early) - 0.06).abs() < 1e-3);
+
+        // Liquidity is deepest in top leagues and thin long before kick-off
+        let top = simulator.get_liquidity("m1").await.unwrap();
+        assert!(top > simulator.get_liquidity("m2").await.unwrap());
+        assert!(top > simulator.get_liquidity("m3").await.unwrap() * dec!(4));
+    }
+
+    #[tokio::test]
+    async fn test_bookmakers_quote_with_own_margin_and_latency() {
+        let simulator = MarketSimulator::new().with_seed(11).with_bookmakers(vec![
+            BookmakerProfile::new("Sharp", 0.02).with_noise(0.0),
+            BookmakerProfile::new("Slow", 0.08)
+                .with_noise(0.0)
+                .with_update_latency(std::time::Duration::from_secs(10)),
+        ]);
+        let kickoff = MatchEvent::new(
+            "books".to_string(),
+            EventType::MatchStart,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        ).with_status(MatchStatus::Live);
+
+        let quotes = simulator.generate_bookmaker_odds(&kickoff).await;
+        assert_eq!(quotes.iter().map(|quote| quote.bookmaker.as_str()).collect::<Vec<_>>(), ["Sharp", "Slow"]);
+        let overround = |quote: &MarketOdds| {
+            let (home, draw, away) = quote.odds.to_decimal().unwrap();
+            SimpleMarketOdds::new(home, draw.unwrap(), away).overround()
+        };
+        assert!((overround(&quotes[0]) - 0.02).abs() < 0.01);
+        assert!((overround(&quotes[1]) - 0.08).abs() < 0.01);
+
+        // A goal a second later moves the sharp book; the slow one hasn't caught up
+        let mut goal = kickoff.clone();
+        goal.event_type = EventType::Goal { team: "Arsenal".to_string(), player: None, minute: 10 };
+        goal.timestamp = kickoff.timestamp + chrono::Duration::seconds(1);
+        let after_goal = simulator.generate_bookmaker_odds(&goal).await;
+        assert_ne!(after_goal[0].odds, quotes[0].odds);
+        assert_eq!(after_goal[1].id, quotes[1].id);
+        assert_eq!(after_goal[1].timestamp, kickoff.timestamp);
+
+        goal.timestamp = kickoff.timestamp + chrono::Duration::seconds(11);
+        let caught_up = simulator.generate_bookmaker_odds(&goal).await;
+        assert_ne!(caught_up[1].id, quotes[1].id);
+        assert_eq!(caught_up[1].timestamp, goal.timestamp);
+    }
+}
@@ -0,0 +1,223 @@
+// Per-match state machine: checks each event is possible given what has
+// already happened in the match
+
+use quant_models::{EventType, MatchEvent, MatchStatus};
+use dashmap::DashMap;
+use serde::Serialize;
+use thiserror::Error;
+
+/// Why an event can't have happened; the event should be dropped
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum TransitionError {
+    #[error("{event} before kick-off")]
+    NotStarted { event: &'static str },
+    #[error("match already kicked off")]
+    AlreadyStarted,
+    #[error("{event} after full time")]
+    AlreadyFinished { event: &'static str },
+    #[error("second half time")]
+    RepeatedHalfTime,
+}
+
+/// Something off about an accepted event, and how the tracked state was
+/// adjusted. Consumers keeping their own match state should do the same.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MatchCorrection {
+    /// The event's score doesn't add up with the goals seen, e.g. after a
+    /// missed goal event; the reported score is taken as right
+    Score { home: u8, away: u8, counted_home: u8, counted_away: u8 },
+    /// The event is timed before one already seen; the match clock stays put
+    MinuteWentBack { minute: u8, latest: u8 },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackedMatch {
+    pub status: MatchStatus,
+    pub minute: u8,
+    pub home_score: u8,
+    pub away_score: u8,
+}
+
+impl TrackedMatch {
+    /// A match first seen mid-stream is taken to be where its event says
+    fn first_seen(event: &MatchEvent) -> Self {
+        let status = match event.event_type {
+            EventType::MatchStart => MatchStatus::Scheduled,
+            _ => event.match_status.clone(),
+        };
+        let (home_score, away_score) = event.score.as_ref().map_or((0, 0), |score| (score.home, score.away));
+        Self { status, minute: 0, home_score, away_score }
+    }
+}
+
+/// Tracks where each match is and validates incoming events against it:
+/// nothing happens before kick-off or after full time, and scores and the
+/// clock only move forward. A match is forgotten once its MatchEnd arrives,
+/// so a replayed match starts afresh.
+pub struct MatchStateMachine {
+    matches: DashMap<String, TrackedMatch>,
+}
+
+impl MatchStateMachine {
+    pub fn new() -> Self {
+        Self { matches: DashMap::new() }
+    }
+
+    /// Apply an event, returning any corrections it needed, or why it is
+    /// impossible. Rejected events leave the state untouched.
+    pub fn apply(&self, event: &MatchEvent) -> Result<Vec<MatchCorrection>, TransitionError> {
+        let mut tracked = self.matches.entry(event.match_id.clone())
+            .or_insert_with(|| TrackedMatch::first_seen(event));
+
+        let next_status = match (&event.event_type, &tracked.status) {
+            (EventType::MatchEnd, _) => {
+                drop(tracked);
+                self.matches.remove(&event.match_id);
+                return Ok(Vec::new());
+            }
+            // Prices move and line-ups are named before, during and between
+            // halves, but not once it's over
+            (EventType::OddsUpdate | EventType::Lineup { .. }, MatchStatus::Finished | MatchStatus::Cancelled) => {
+                return Err(TransitionError::AlreadyFinished { event: event_name(&event.event_type) });
+            }
+            (EventType::OddsUpdate | EventType::Lineup { .. }, status) => status.clone(),
+            (EventType::MatchStart, MatchStatus::Scheduled | MatchStatus::Postponed) => MatchStatus::Live,
+            (EventType::MatchStart, MatchStatus::Finished | MatchStatus::Cancelled) => {
+                return Err(TransitionError::AlreadyFinished { event: event_name(&event.event_type) });
+            }
+            (EventType::MatchStart, _) => return Err(TransitionError::AlreadyStarted),
+            (event_type, MatchStatus::Scheduled | MatchStatus::Postponed) => {
+                return Err(TransitionError::NotStarted { event: event_name(event_type) });
+            }
+            (event_type, MatchStatus::Finished | MatchStatus::Cancelled) => {
+                return Err(TransitionError::AlreadyFinished { event: event_name(event_type) });
+            }
+            (EventType::HalfTime, MatchStatus::HalfTime) => return Err(TransitionError::RepeatedHalfTime),
+            (EventType::HalfTime, _) => MatchStatus::HalfTime,
+            (EventType::FullTime, _) => MatchStatus::Finished,
+            // There is no second-half kick-off event; play resuming ends the break
+            (_, _) => MatchStatus::Live,
+        };
+
+        let mut corrections = Vec::new();
+        if let Some(minute) = event.minute() {
+            if minute < tracked.minute {
+                corrections.push(MatchCorrection::MinuteWentBack { minute, latest: tracked.minute });
+            } else {
+                tracked.minute = minute;
+            }
+        }
+        if let EventType::Goal { team, .. } = &event.event_type {
+            if team == &event.team_home {
+                tracked.home_score = tracked.home_score.saturating_add(1);
+            } else {
+                tracked.away_score = tracked.away_score.saturating_add(1);
+            }
+        }
+        if let Some(score) = &event.score {
+            if (score.home, score.away) != (tracked.home_score, tracked.away_score) {
+                corrections.push(MatchCorrection::Score {
+                    home: score.home,
+                    away: score.away,
+                    counted_home: tracked.home_score,
+                    counted_away: tracked.away_score,
+                });
+                tracked.home_score = score.home;
+                tracked.away_score = score.away;
+            }
+        }
+        tracked.status = next_status;
+        Ok(corrections)
+    }
+
+    pub fn get(&self, match_id: &str) -> Option<TrackedMatch> {
+        self.matches.get(match_id).map(|entry| entry.clone())
+    }
+
+    pub fn tracked_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Forget every match, ahead of replaying the event log
+    pub fn reset(&self) {
+        self.matches.clear();
+    }
+}
+
+impl Default for MatchStateMachine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn event_name(event_type: &EventType) -> &'static str {
+    match event_type {
+        EventType::MatchStart => "kick-off",
+        EventType::Goal { .. } => "goal",
+        EventType::Card { .. } => "card",
+        EventType::Substitution { .. } => "substitution",
+        EventType::Shot { .. } => "shot",
+        EventType::HalfTime => "half time",
+        EventType::FullTime => "full time",
+        EventType::MatchEnd => "match end",
+        EventType::OddsUpdate => "odds update",
+        EventType::Lineup { .. } => "line-up",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_models::Score;
+
+    fn event(event_type: EventType) -> MatchEvent {
+        MatchEvent::new(
+            "m1".to_string(),
+            event_type,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        )
+    }
+
+    fn goal(team: &str, minute: u8) -> MatchEvent {
+        event(EventType::Goal { team: team.to_string(), player: None, minute })
+    }
+
+    fn score(home: u8, away: u8) -> Score {
+        Score { home, away, half_time_home: None, half_time_away: None }
+    }
+
+    #[test]
+    fn test_impossible_sequences_rejected_and_corrections_flagged() {
+        let machine = MatchStateMachine::new();
+        assert_eq!(machine.apply(&goal("Arsenal", 5)), Err(TransitionError::NotStarted { event: "goal" }));
+        assert!(machine.apply(&event(EventType::OddsUpdate)).unwrap().is_empty());
+
+        machine.apply(&event(EventType::MatchStart)).unwrap();
+        assert_eq!(machine.apply(&event(EventType::MatchStart)), Err(TransitionError::AlreadyStarted));
+        assert!(machine.apply(&goal("Arsenal", 20).with_score(score(1, 0))).unwrap().is_empty());
+
+        machine.apply(&event(EventType::HalfTime)).unwrap();
+        assert_eq!(machine.apply(&event(EventType::HalfTime)), Err(TransitionError::RepeatedHalfTime));
+
+        // A missed Chelsea goal shows up in the score of the next one
+        assert_eq!(machine.apply(&goal("Chelsea", 60).with_score(score(1, 2))).unwrap(), vec![
+            MatchCorrection::Score { home: 1, away: 2, counted_home: 1, counted_away: 1 },
+        ]);
+        assert_eq!(machine.get("m1").unwrap().status, MatchStatus::Live);
+        assert_eq!(machine.apply(&goal("Arsenal", 55).with_score(score(2, 2))).unwrap(), vec![
+            MatchCorrection::MinuteWentBack { minute: 55, latest: 60 },
+        ]);
+
+        machine.apply(&event(EventType::FullTime)).unwrap();
+        assert_eq!(machine.apply(&goal("Arsenal", 91)), Err(TransitionError::AlreadyFinished { event: "goal" }));
+        let tracked = machine.get("m1").unwrap();
+        assert_eq!((tracked.home_score, tracked.away_score, tracked.minute), (2, 2, 60));
+
+        machine.apply(&event(EventType::MatchEnd)).unwrap();
+        assert_eq!(machine.tracked_count(), 0);
+    }
+}
@@ -0,0 +1,221 @@
+use quant_models::{
+    BettingDecision, CardRecord, EventType, GoalRecord, MatchEvent, MatchSummary, Prediction,
+    PredictionReview, Score, SimpleMarketOdds,
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::RwLock;
+
+const DEFAULT_MAX_SUMMARIES: usize = 1_000;
+
+#[derive(Debug, Clone)]
+struct LiveMatch {
+    team_home: String,
+    team_away: String,
+    league: String,
+    season: String,
+    kickoff: Option<DateTime<Utc>>,
+    goals: Vec<GoalRecord>,
+    cards: Vec<CardRecord>,
+    kickoff_prediction: Option<Prediction>,
+}
+
+/// Follows each match from kick-off and produces a `MatchSummary` at full time
+pub struct MatchSummaryTracker {
+    live: RwLock<HashMap<String, LiveMatch>>,
+    summaries: RwLock<HashMap<String, MatchSummary>>,
+    /// Finished match ids, oldest first, for eviction
+    order: RwLock<VecDeque<String>>,
+    max_summaries: usize,
+}
+
+impl MatchSummaryTracker {
+    pub fn new() -> Self {
+        Self {
+            live: RwLock::new(HashMap::new()),
+            summaries: RwLock::new(HashMap::new()),
+            order: RwLock::new(VecDeque::new()),
+            max_summaries: DEFAULT_MAX_SUMMARIES,
+        }
+    }
+
+    /// Keep at most this many summaries in memory, dropping the oldest
+    pub fn with_max_summaries(mut self, max_summaries: usize) -> Self {
+        self.max_summaries = max_summaries.max(1);
+        self
+    }
+
+    pub async fn record_event(&self, event: &MatchEvent) {
+        let mut live = self.live.write().await;
+        let state = live.entry(event.match_id.clone()).or_insert_with(|| LiveMatch {
+            team_home: event.team_home.clone(),
+            team_away: event.team_away.clone(),
+            league: event.league.clone(),
+            season: event.season.clone(),
+            kickoff: None,
+            goals: Vec::new(),
+            cards: Vec::new(),
+            kickoff_prediction: None,
+        });
+
+        match &event.event_type {
+            EventType::MatchStart => state.kickoff = Some(event.timestamp),
+            EventType::Goal { team, player, minute } => state.goals.push(GoalRecord {
+                minute: *minute,
+                team: team.clone(),
+                player: player.clone(),
+            }),
+            EventType::Card { team, player, card_type, minute } => state.cards.push(CardRecord {
+                minute: *minute,
+                team: team.clone(),
+                player: player.clone(),
+                card_type: card_type.clone(),
+            }),
+            _ => {}
+        }
+    }
+
+    /// Only the first prediction for a match is kept, as the one made at kick-off
+    pub async fn record_prediction(&self, prediction: &Prediction) {
+        if let Some(state) = self.live.write().await.get_mut(&prediction.match_id) {
+            state.kickoff_prediction.get_or_insert_with(|| prediction.clone());
+        }
+    }
+
+    pub async fn kickoff(&self, match_id: &str) -> Option<DateTime<Utc>> {
+        self.live.read().await.get(match_id)?.kickoff
+    }
+
+    /// Close the match and build its summary. `bets` should already be
+    /// settled so that their profit and loss count.
+    pub async fn finish(
+        &self,
+        match_id: &str,
+        closing_odds: Option<SimpleMarketOdds>,
+        bets: Vec<BettingDecision>,
+    ) -> Option<MatchSummary> {
+        let state = self.live.write().await.remove(match_id)?;
+
+        let goals_for = |team: &str, until: u8| {
+            state.goals.iter().filter(|goal| goal.team == team && goal.minute <= until).count() as u8
+        };
+        let final_score = Score {
+            home: goals_for(&state.team_home, u8::MAX),
+            away: goals_for(&state.team_away, u8::MAX),
+            half_time_home: Some(goals_for(&state.team_home, 45)),
+            half_time_away: Some(goals_for(&state.team_away, 45)),
+        };
+        let outcome = MatchSummary::outcome_of(&final_score);
+        let prediction = state.kickoff_prediction.as_ref();
+
+        let summary = MatchSummary {
+            match_id: match_id.to_string(),
+            team_home: state.team_home.clone(),
+            team_away: state.team_away.clone(),
+            league: state.league.clone(),
+            season: state.season.clone(),
+            kickoff: state.kickoff,
+            expected_goals_home: prediction.and_then(|p| p.expected_goals_home),
+            expected_goals_away: prediction.and_then(|p| p.expected_goals_away),
+            prediction: prediction.map(|p| PredictionReview::new(p, &outcome)),
+            final_score,
+            outcome,
+            goals: state.goals,
+            cards: state.cards,
+            closing_odds,
+            profit_loss: bets.iter().filter_map(BettingDecision::base_settled_profit_loss).sum::<Decimal>(),
+            bets,
+            generated_at: Utc::now(),
+        };
+
+        let mut summaries = self.summaries.write().await;
+        let mut order = self.order.write().await;
+        if summaries.insert(match_id.to_string(), summary.clone()).is_none() {
+            order.push_back(match_id.to_string());
+        }
+        while order.len() > self.max_summaries {
+            if let Some(oldest) = order.pop_front() {
+                summaries.remove(&oldest);
+            }
+        }
+
+        Some(summary)
+    }
+
+    pub async fn get_summary(&self, match_id: &str) -> Option<MatchSummary> {
+        self.summaries.read().await.get(match_id).cloned()
+    }
+
+    /// Forget matches still in play, ahead of replaying the event log.
+    /// Finished summaries are kept.
+    pub async fn reset_live(&self) {
+        self.live.write().await.clear();
+    }
+
+    /// Stop following a match without summarising it
+    pub async fn discard(&self, match_id: &str) {
+        self.live.write().await.remove(match_id);
+    }
+}
+
+impl Default for MatchSummaryTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_models::{BetType, CardType, Outcome1X2};
+    use rust_decimal_macros::dec;
+
+    fn event(event_type: EventType) -> MatchEvent {
+        MatchEvent::new(
+            "m1".to_string(),
+            event_type,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_summary_built_at_full_time() {
+        let tracker = MatchSummaryTracker::new();
+        tracker.record_event(&event(EventType::MatchStart)).await;
+
+        let prediction = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.5, 0.2, Utc::now(),
+        ).unwrap().with_draw_prob(0.3).unwrap();
+        tracker.record_prediction(&prediction).await;
+
+        tracker.record_event(&event(EventType::Goal { team: "Chelsea".to_string(), player: None, minute: 20 })).await;
+        tracker.record_event(&event(EventType::Card {
+            team: "Arsenal".to_string(), player: "Player4".to_string(), card_type: CardType::Yellow, minute: 30,
+        })).await;
+        tracker.record_event(&event(EventType::Goal { team: "Arsenal".to_string(), player: None, minute: 70 })).await;
+        tracker.record_event(&event(EventType::Goal { team: "Arsenal".to_string(), player: None, minute: 85 })).await;
+
+        let mut bet = BettingDecision::new(
+            "m1".to_string(), BetType::HomeWin, dec!(10), dec!(2.0), 0.5, "test".to_string(),
+        ).unwrap();
+        bet.update_status(quant_models::BetStatus::Won);
+
+        let summary = tracker.finish("m1", None, vec![bet]).await.unwrap();
+        assert_eq!((summary.final_score.home, summary.final_score.away), (2, 1));
+        assert_eq!(summary.final_score.half_time_away, Some(1));
+        assert_eq!(summary.outcome, Outcome1X2::HomeWin);
+        assert_eq!(summary.cards.len(), 1);
+        assert_eq!(summary.profit_loss, dec!(10));
+
+        let review = summary.prediction.unwrap();
+        assert!(review.correct);
+        assert!((review.brier_score - (0.25 + 0.09 + 0.04)).abs() < 1e-9);
+
+        assert!(tracker.get_summary("m1").await.is_some());
+        assert!(tracker.finish("m1", None, vec![]).await.is_none());
+    }
+}
@@ -0,0 +1,298 @@
+use quant_models::{MatchEvent, Prediction, SimpleMarketOdds};
+use quant_ml::FeatureEngineer;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::mem::size_of;
+use std::time::Duration;
+use tracing::debug;
+
+/// Rough heap + inline footprint of a cached value. Estimates only need to be
+/// consistent enough to compare against a budget, not byte-exact.
+pub trait ApproxSize {
+    fn approx_size(&self) -> usize;
+}
+
+impl ApproxSize for MatchEvent {
+    fn approx_size(&self) -> usize {
+        let metadata = if self.metadata.is_null() {
+            0
+        } else {
+            self.metadata.to_string().len()
+        };
+
+        size_of::<Self>()
+            + self.match_id.capacity()
+            + self.team_home.capacity()
+            + self.team_away.capacity()
+            + self.league.capacity()
+            + self.season.capacity()
+            + metadata
+    }
+}
+
+impl ApproxSize for Prediction {
+    fn approx_size(&self) -> usize {
+        size_of::<Self>()
+            + self.match_id.capacity()
+            + self.model_name.capacity()
+            + self.model_version.capacity()
+            + self.features_used.iter().map(|f| f.capacity() + size_of::<String>()).sum::<usize>()
+            + self.score_matrix.as_ref().map_or(0, |matrix| {
+                matrix.probabilities.iter().map(|row| size_of::<Vec<f64>>() + row.capacity() * size_of::<f64>()).sum()
+            })
+            + self.metadata.to_string().len()
+    }
+}
+
+impl ApproxSize for SimpleMarketOdds {
+    fn approx_size(&self) -> usize {
+        size_of::<Self>()
+    }
+}
+
+impl ApproxSize for Duration {
+    fn approx_size(&self) -> usize {
+        size_of::<Self>()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheKind {
+    Events,
+    Predictions,
+    Odds,
+    MatchContexts,
+    Latency,
+}
+
+impl CacheKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CacheKind::Events => "events",
+            CacheKind::Predictions => "predictions",
+            CacheKind::Odds => "odds",
+            CacheKind::MatchContexts => "match_contexts",
+            CacheKind::Latency => "latency",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MemoryBudgets {
+    pub events_bytes: usize,
+    pub predictions_bytes: usize,
+    pub odds_bytes: usize,
+    pub contexts_bytes: usize,
+    pub latency_bytes: usize,
+}
+
+impl Default for MemoryBudgets {
+    fn default() -> Self {
+        const MB: usize = 1024 * 1024;
+        Self {
+            events_bytes: 8 * MB,
+            predictions_bytes: 4 * MB,
+            odds_bytes: 2 * MB,
+            contexts_bytes: 2 * MB,
+            latency_bytes: MB,
+        }
+    }
+}
+
+impl MemoryBudgets {
+    pub fn from_megabytes(events: f64, predictions: f64, odds: f64, contexts: f64, latency: f64) -> Self {
+        let to_bytes = |mb: f64| (mb.max(0.0) * 1024.0 * 1024.0) as usize;
+        Self {
+            events_bytes: to_bytes(events),
+            predictions_bytes: to_bytes(predictions),
+            odds_bytes: to_bytes(odds),
+            contexts_bytes: to_bytes(contexts),
+            latency_bytes: to_bytes(latency),
+        }
+    }
+
+    pub fn for_cache(&self, kind: CacheKind) -> usize {
+        match kind {
+            CacheKind::Events => self.events_bytes,
+            CacheKind::Predictions => self.predictions_bytes,
+            CacheKind::Odds => self.odds_bytes,
+            CacheKind::MatchContexts => self.contexts_bytes,
+            CacheKind::Latency => self.latency_bytes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheUsage {
+    pub entries: usize,
+    pub bytes: usize,
+    pub budget_bytes: usize,
+    pub evictions: u64,
+}
+
+/// Tracks approximate sizes of the in-memory caches and evicts the oldest
+/// entries when a cache grows past its budget.
+pub struct MemoryAccountant {
+    budgets: MemoryBudgets,
+    usage: RwLock<HashMap<CacheKind, CacheUsage>>,
+}
+
+impl MemoryAccountant {
+    pub fn new(budgets: MemoryBudgets) -> Self {
+        Self {
+            budgets,
+            usage: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn budget(&self, kind: CacheKind) -> usize {
+        self.budgets.for_cache(kind)
+    }
+
+    pub fn record(&self, kind: CacheKind, entries: usize, bytes: usize, evicted: usize) {
+        let mut usage = self.usage.write();
+        let entry = usage.entry(kind).or_default();
+        entry.entries = entries;
+        entry.bytes = bytes;
+        entry.budget_bytes = self.budget(kind);
+        entry.evictions += evicted as u64;
+    }
+
+    /// Append to a chronologically ordered vector, then drop items from the
+    /// front (oldest first) until it fits the budget. The cache's byte total
+    /// is kept as items come and go, so only the new and evicted items are
+    /// measured. Returns the number of evicted items.
+    pub fn push_vec<T: ApproxSize>(&self, kind: CacheKind, items: &mut Vec<T>, item: T) -> usize {
+        let budget = self.budget(kind);
+        let added = item.approx_size();
+        let mut usage = self.usage.write();
+        let entry = usage.entry(kind).or_default();
+        // Only a vector this accountant hasn't seen grow needs measuring whole
+        let mut bytes = if entry.entries == items.len() {
+            entry.bytes
+        } else {
+            items.iter().map(ApproxSize::approx_size).sum()
+        };
+        items.push(item);
+        bytes += added;
+
+        let mut evict = 0;
+        while bytes > budget && evict < items.len() {
+            bytes -= items[evict].approx_size();
+            evict += 1;
+        }
+        if evict > 0 {
+            items.drain(..evict);
+            debug!("🧹 Evicted {} oldest {} entries to stay within budget", evict, kind.as_str());
+        }
+
+        entry.entries = items.len();
+        entry.bytes = bytes;
+        entry.budget_bytes = budget;
+        entry.evictions += evict as u64;
+        evict
+    }
+
+    /// For caches without a natural order, how many entries must go to fit the
+    /// budget, assuming entries are roughly the same size.
+    pub fn entries_over_budget(&self, kind: CacheKind, entries: usize, bytes: usize) -> usize {
+        let budget = self.budget(kind);
+        if bytes <= budget || entries == 0 {
+            return 0;
+        }
+        let avg_entry = (bytes / entries).max(1);
+        (bytes - budget).div_ceil(avg_entry).min(entries)
+    }
+
+    pub fn enforce_context_budget(&self, feature_engineer: &FeatureEngineer) -> usize {
+        let entries = feature_engineer.context_count();
+        let bytes = entries * feature_engineer.context_entry_size();
+        let evict = self.entries_over_budget(CacheKind::MatchContexts, entries, bytes);
+        if evict > 0 {
+            feature_engineer.evict_oldest_contexts(evict);
+        }
+
+        let remaining = feature_engineer.context_count();
+        self.record(
+            CacheKind::MatchContexts,
+            remaining,
+            remaining * feature_engineer.context_entry_size(),
+            evict,
+        );
+        evict
+    }
+
+    pub fn usage(&self) -> HashMap<String, CacheUsage> {
+        self.usage
+            .read()
+            .iter()
+            .map(|(kind, usage)| (kind.as_str().to_string(), usage.clone()))
+            .collect()
+    }
+
+    pub fn total_bytes(&self) -> usize {
+        self.usage.read().values().map(|usage| usage.bytes).sum()
+    }
+}
+
+impl Default for MemoryAccountant {
+    fn default() -> Self {
+        Self::new(MemoryBudgets::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_models::EventType;
+
+    fn test_event(i: usize) -> MatchEvent {
+        MatchEvent::new(
+            format!("match_{}", i),
+            EventType::MatchStart,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_push_vec_evicts_oldest() {
+        let event_size = test_event(0).approx_size();
+        let budgets = MemoryBudgets {
+            events_bytes: event_size * 5,
+            ..MemoryBudgets::default()
+        };
+        let accountant = MemoryAccountant::new(budgets);
+
+        let mut events = Vec::new();
+        let evicted: usize = (0..8).map(|i| accountant.push_vec(CacheKind::Events, &mut events, test_event(i))).sum();
+
+        assert_eq!(evicted, 3);
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[0].match_id, "match_3");
+
+        let usage = accountant.usage();
+        let events_usage = &usage["events"];
+        assert_eq!(events_usage.entries, 5);
+        assert_eq!(events_usage.evictions, 3);
+        assert!(events_usage.bytes <= events_usage.budget_bytes);
+        // The running total matches a full recount
+        assert_eq!(events_usage.bytes, events.iter().map(ApproxSize::approx_size).sum::<usize>());
+    }
+
+    #[test]
+    fn test_entries_over_budget() {
+        let budgets = MemoryBudgets {
+            odds_bytes: 1000,
+            ..MemoryBudgets::default()
+        };
+        let accountant = MemoryAccountant::new(budgets);
+
+        assert_eq!(accountant.entries_over_budget(CacheKind::Odds, 10, 900), 0);
+        assert_eq!(accountant.entries_over_budget(CacheKind::Odds, 20, 2000), 10);
+    }
+}
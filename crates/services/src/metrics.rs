@@ -1,3 +1,11 @@
+use crate::memory::{CacheKind, CacheUsage, MemoryAccountant, MemoryBudgets};
+use crate::prediction_cache::{PredictionCache, PredictionCacheStats};
+#[cfg(feature = "batched-inference")]
+use crate::predictor::PredictorService;
+#[cfg(feature = "batched-inference")]
+use quant_ml::InferenceQueueStats;
+use quant_models::{QueryLog, SlowQuery};
+use quant_stream::{QueueMonitor, QueueStats};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
@@ -11,15 +19,39 @@ pub struct SystemMetrics {
     pub timestamp: DateTime<Utc>,
     pub uptime_seconds: u64,
     pub events_processed: u64,
+    /// When the latest event was processed
+    pub last_event_at: Option<DateTime<Utc>>,
     pub predictions_generated: u64,
     pub trades_executed: u64,
     pub api_requests: u64,
+    /// API requests turned away by the rate limiter
+    pub throttled_requests: u64,
     pub memory_usage_mb: f64,
     pub cpu_usage_percent: f64,
     pub active_connections: u32,
-    pub prediction_latency_ms: f64,
-    pub trading_latency_ms: f64,
     pub error_count: u64,
+    /// Rows deleted by retention pruning
+    pub rows_pruned: u64,
+    pub bytes_reclaimed: u64,
+    pub cache_memory_bytes: usize,
+    pub cache_usage: HashMap<String, CacheUsage>,
+    /// Database and Redis calls slower than the slow query threshold
+    pub slow_queries: u64,
+    /// Bets left open after their match finished, caught by reconciliation
+    pub missed_settlements: u64,
+    /// Pipeline stages cut off for running over their latency budget, by stage
+    #[serde(default)]
+    pub stage_timeouts: HashMap<String, u64>,
+    /// Depth and overflow counts of the ingress event queue
+    #[serde(default)]
+    pub event_queue: Option<QueueStats>,
+    /// Predictions reused because a match's features had not changed
+    #[serde(default)]
+    pub prediction_cache: Option<PredictionCacheStats>,
+    /// Queue depth, batching and latency of the inference thread pool
+    #[cfg(feature = "batched-inference")]
+    #[serde(default)]
+    pub inference: Option<InferenceQueueStats>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +63,116 @@ pub struct PerformanceStats {
     pub system_health_score: f64, // 0.0 to 1.0
     pub error_rate_percent: f64,
     pub memory_efficiency: f64,
+    /// Latency distribution per operation, e.g. `prediction`
+    pub latency: HashMap<String, LatencySummary>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub count: u64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Linear sub-buckets per power of two, which bounds the error of any
+/// reported percentile to 1/16 of its value
+const SUB_BUCKETS: u64 = 16;
+const SUB_BUCKET_BITS: u32 = SUB_BUCKETS.trailing_zeros();
+/// Samples above ~12 days are counted in the top bucket
+const MAX_TRACKABLE_MICROS: u64 = 1 << 40;
+
+/// Fixed-size log-linear histogram of durations at microsecond resolution,
+/// in the style of HdrHistogram. Memory stays constant however many samples
+/// are recorded, and every sample counts towards the percentiles.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyHistogram {
+    counts: Vec<u64>,
+    total: u64,
+    sum_micros: u128,
+    max_micros: u64,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, duration: Duration) {
+        let micros = (duration.as_micros() as u64).min(MAX_TRACKABLE_MICROS);
+        let index = bucket_index(micros);
+        if index >= self.counts.len() {
+            self.counts.resize(index + 1, 0);
+        }
+        self.counts[index] += 1;
+        self.total += 1;
+        self.sum_micros += micros as u128;
+        self.max_micros = self.max_micros.max(micros);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.total
+    }
+
+    pub fn mean_ms(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.sum_micros as f64 / self.total as f64 / 1000.0
+    }
+
+    /// Smallest bucket bound at or below which `quantile` (0 to 1) of the
+    /// samples fall
+    pub fn percentile_ms(&self, quantile: f64) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        let rank = ((quantile.clamp(0.0, 1.0) * self.total as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return bucket_upper_bound(index).min(self.max_micros) as f64 / 1000.0;
+            }
+        }
+        self.max_micros as f64 / 1000.0
+    }
+
+    pub fn summary(&self) -> LatencySummary {
+        LatencySummary {
+            count: self.total,
+            mean_ms: self.mean_ms(),
+            p50_ms: self.percentile_ms(0.50),
+            p95_ms: self.percentile_ms(0.95),
+            p99_ms: self.percentile_ms(0.99),
+            max_ms: self.max_micros as f64 / 1000.0,
+        }
+    }
+
+    fn size_bytes(&self) -> usize {
+        std::mem::size_of::<Self>() + self.counts.capacity() * std::mem::size_of::<u64>()
+    }
+}
+
+fn bucket_index(micros: u64) -> usize {
+    if micros < SUB_BUCKETS {
+        return micros as usize;
+    }
+    let magnitude = 63 - micros.leading_zeros();
+    let sub_bucket = (micros >> (magnitude - SUB_BUCKET_BITS)) - SUB_BUCKETS;
+    ((magnitude - SUB_BUCKET_BITS + 1) as u64 * SUB_BUCKETS + sub_bucket) as usize
+}
+
+fn bucket_upper_bound(index: usize) -> u64 {
+    let index = index as u64;
+    if index < SUB_BUCKETS {
+        return index;
+    }
+    let shift = (index / SUB_BUCKETS - 1) as u32;
+    let lower = (SUB_BUCKETS + index % SUB_BUCKETS) << shift;
+    lower + (1 << shift) - 1
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,9 +212,15 @@ impl LatencyTracker {
 pub struct MetricsCollector {
     start_time: Instant,
     metrics: Arc<RwLock<SystemMetrics>>,
-    operation_times: Arc<RwLock<HashMap<String, Vec<Duration>>>>,
+    operation_times: Arc<RwLock<HashMap<String, LatencyHistogram>>>,
     model_performance: Arc<RwLock<HashMap<String, ModelPerformance>>>,
     hourly_stats: Arc<RwLock<Vec<(DateTime<Utc>, SystemMetrics)>>>,
+    memory: Arc<MemoryAccountant>,
+    query_log: Arc<QueryLog>,
+    event_queue: Option<QueueMonitor>,
+    prediction_cache: Option<Arc<PredictionCache>>,
+    #[cfg(feature = "batched-inference")]
+    predictor: Option<Arc<PredictorService>>,
 }
 
 impl MetricsCollector {
@@ -82,15 +230,26 @@ impl MetricsCollector {
             timestamp: Utc::now(),
             uptime_seconds: 0,
             events_processed: 0,
+            last_event_at: None,
             predictions_generated: 0,
             trades_executed: 0,
             api_requests: 0,
+            throttled_requests: 0,
             memory_usage_mb: 0.0,
             cpu_usage_percent: 0.0,
             active_connections: 0,
-            prediction_latency_ms: 0.0,
-            trading_latency_ms: 0.0,
             error_count: 0,
+            rows_pruned: 0,
+            bytes_reclaimed: 0,
+            cache_memory_bytes: 0,
+            cache_usage: HashMap::new(),
+            slow_queries: 0,
+            missed_settlements: 0,
+            stage_timeouts: HashMap::new(),
+            event_queue: None,
+            prediction_cache: None,
+            #[cfg(feature = "batched-inference")]
+            inference: None,
         };
 
         Self {
@@ -99,13 +258,64 @@ impl MetricsCollector {
             operation_times: Arc::new(RwLock::new(HashMap::new())),
             model_performance: Arc::new(RwLock::new(HashMap::new())),
             hourly_stats: Arc::new(RwLock::new(Vec::new())),
+            memory: Arc::new(MemoryAccountant::default()),
+            query_log: Arc::new(QueryLog::default()),
+            event_queue: None,
+            prediction_cache: None,
+            #[cfg(feature = "batched-inference")]
+            predictor: None,
         }
     }
 
+    pub fn with_memory_budgets(mut self, budgets: MemoryBudgets) -> Self {
+        self.memory = Arc::new(MemoryAccountant::new(budgets));
+        self
+    }
+
+    pub fn memory_accountant(&self) -> Arc<MemoryAccountant> {
+        self.memory.clone()
+    }
+
+    /// Report the ingress event queue's depth alongside the other metrics
+    pub fn with_event_queue(mut self, queue: QueueMonitor) -> Self {
+        self.event_queue = Some(queue);
+        self
+    }
+
+    /// Report how often the predictor reuses a match's last prediction
+    pub fn with_prediction_cache(mut self, cache: Arc<PredictionCache>) -> Self {
+        self.prediction_cache = Some(cache);
+        self
+    }
+
+    /// Report the predictor's inference thread pool, when it has one
+    #[cfg(feature = "batched-inference")]
+    pub fn with_inference(mut self, predictor: Arc<PredictorService>) -> Self {
+        self.predictor = Some(predictor);
+        self
+    }
+
+    /// Database and Redis calls taking at least `threshold` are kept as slow queries
+    pub fn with_slow_query_threshold(mut self, threshold: Duration) -> Self {
+        self.query_log = Arc::new(QueryLog::new(threshold));
+        self
+    }
+
+    /// Shared with the repository and Redis streams, which record their calls in it
+    pub fn query_log(&self) -> Arc<QueryLog> {
+        self.query_log.clone()
+    }
+
+    /// Most recent first
+    pub fn get_slow_queries(&self) -> Vec<SlowQuery> {
+        self.query_log.slow_queries()
+    }
+
     pub async fn increment_events_processed(&self) {
         let mut metrics = self.metrics.write().await;
         metrics.events_processed += 1;
         metrics.timestamp = Utc::now();
+        metrics.last_event_at = Some(metrics.timestamp);
         metrics.uptime_seconds = self.start_time.elapsed().as_secs();
     }
 
@@ -124,11 +334,29 @@ impl MetricsCollector {
         metrics.api_requests += 1;
     }
 
+    pub async fn increment_throttled_requests(&self) {
+        self.metrics.write().await.throttled_requests += 1;
+    }
+
     pub async fn increment_errors(&self) {
         let mut metrics = self.metrics.write().await;
         metrics.error_count += 1;
     }
 
+    pub async fn record_pruned(&self, rows: u64, bytes: u64) {
+        let mut metrics = self.metrics.write().await;
+        metrics.rows_pruned += rows;
+        metrics.bytes_reclaimed += bytes;
+    }
+
+    pub async fn record_missed_settlements(&self, bets: u64) {
+        self.metrics.write().await.missed_settlements += bets;
+    }
+
+    pub async fn record_stage_timeout(&self, stage: &str) {
+        *self.metrics.write().await.stage_timeouts.entry(stage.to_string()).or_default() += 1;
+    }
+
     pub async fn update_active_connections(&self, count: u32) {
         let mut metrics = self.metrics.write().await;
         metrics.active_connections = count;
@@ -138,33 +366,15 @@ impl MetricsCollector {
         tokio::spawn({
             let operation = operation.to_string();
             let operation_times = self.operation_times.clone();
-            let metrics = self.metrics.clone();
+            let memory = self.memory.clone();
             
             async move {
-                let duration_ms = duration.as_secs_f64() * 1000.0;
-                
-                // Store individual operation time
-                {
-                    let mut times = operation_times.write().await;
-                    times.entry(operation.clone())
-                        .or_insert_with(Vec::new)
-                        .push(duration);
-                    
-                    // Keep only last 1000 measurements per operation
-                    if let Some(op_times) = times.get_mut(&operation) {
-                        if op_times.len() > 1000 {
-                            op_times.remove(0);
-                        }
-                    }
-                }
+                let mut times = operation_times.write().await;
+                times.entry(operation).or_default().record(duration);
                 
-                // Update relevant metric
-                let mut metrics = metrics.write().await;
-                match operation.as_str() {
-                    "prediction" => metrics.prediction_latency_ms = duration_ms,
-                    "trading_decision" => metrics.trading_latency_ms = duration_ms,
-                    _ => {}
-                }
+                let buckets = times.values().map(|histogram| histogram.counts.len()).sum();
+                let bytes = times.values().map(LatencyHistogram::size_bytes).sum();
+                memory.record(CacheKind::Latency, buckets, bytes, 0);
             }
         });
     }
@@ -181,29 +391,28 @@ impl MetricsCollector {
         // Update system resource usage (simplified)
         metrics.memory_usage_mb = self.get_memory_usage_mb().await;
         metrics.cpu_usage_percent = self.get_cpu_usage_percent().await;
+        metrics.cache_memory_bytes = self.memory.total_bytes();
+        metrics.cache_usage = self.memory.usage();
+        metrics.slow_queries = self.query_log.slow_query_count();
+        metrics.event_queue = self.event_queue.as_ref().map(QueueMonitor::stats);
+        metrics.prediction_cache = self.prediction_cache.as_ref().map(|cache| cache.stats());
+        #[cfg(feature = "batched-inference")]
+        {
+            metrics.inference = self.predictor.as_ref().and_then(|predictor| predictor.inference_stats());
+        }
         
         metrics
     }
 
     pub async fn get_performance_stats(&self) -> PerformanceStats {
         let metrics = self.get_current_metrics().await;
-        let operation_times = self.operation_times.read().await;
+        let latency: HashMap<String, LatencySummary> = self.operation_times.read().await
+            .iter()
+            .map(|(operation, histogram)| (operation.clone(), histogram.summary()))
+            .collect();
         
-        let avg_prediction_time = operation_times
-            .get("prediction")
-            .map(|times| {
-                let sum: Duration = times.iter().sum();
-                sum.as_secs_f64() * 1000.0 / times.len() as f64
-            })
-            .unwrap_or(0.0);
-            
-        let avg_trading_time = operation_times
-            .get("trading_decision")
-            .map(|times| {
-                let sum: Duration = times.iter().sum();
-                sum.as_secs_f64() * 1000.0 / times.len() as f64
-            })
-            .unwrap_or(0.0);
+        let avg_prediction_time = latency.get("prediction").map_or(0.0, |summary| summary.mean_ms);
+        let avg_trading_time = latency.get("trading_decision").map_or(0.0, |summary| summary.mean_ms);
 
         let uptime_hours = metrics.uptime_seconds as f64 / 3600.0;
         let predictions_per_second = if uptime_hours > 0.0 {
@@ -241,6 +450,7 @@ impl MetricsCollector {
             system_health_score,
             error_rate_percent: error_rate,
             memory_efficiency: (1.0 - (metrics.memory_usage_mb / 1000.0)).max(0.0),
+            latency,
         }
     }
 
@@ -298,11 +508,13 @@ impl MetricsCollector {
                 
                 // Log current performance stats
                 let stats = metrics_collector.get_performance_stats().await;
+                let prediction = stats.latency.get("prediction").cloned().unwrap_or_default();
                 info!(
-                    "📊 Performance: {:.1} pred/s, {:.1} events/s, {:.1}ms avg latency, {:.1}% health",
+                    "📊 Performance: {:.1} pred/s, {:.1} events/s, {:.1}ms p50 / {:.1}ms p99 latency, {:.1}% health",
                     stats.predictions_per_second,
                     stats.events_per_second,
-                    stats.avg_prediction_time_ms,
+                    prediction.p50_ms,
+                    prediction.p99_ms,
                     stats.system_health_score * 100.0
                 );
                 
@@ -330,6 +542,24 @@ impl MetricsCollector {
         info!("   API requests: {}", metrics.api_requests);
         info!("   Average prediction time: {:.2}ms", stats.avg_prediction_time_ms);
         info!("   Average trading time: {:.2}ms", stats.avg_trading_decision_time_ms);
+        let mut operations: Vec<_> = stats.latency.iter().collect();
+        operations.sort_by(|a, b| a.0.cmp(b.0));
+        for (operation, summary) in operations {
+            info!("   {} latency: p50 {:.2}ms, p95 {:.2}ms, p99 {:.2}ms, max {:.2}ms ({} samples)",
+                  operation, summary.p50_ms, summary.p95_ms, summary.p99_ms, summary.max_ms, summary.count);
+        }
+        info!("   Slow queries: {} (threshold {}ms)", metrics.slow_queries, self.query_log.threshold().as_millis());
+        if let Some(queue) = &metrics.event_queue {
+            info!("   Event queue: {}/{} deep, {} dropped, {} spilled", queue.depth, queue.capacity, queue.dropped, queue.spilled);
+        }
+        if let Some(cache) = &metrics.prediction_cache {
+            info!("   Prediction cache: {:.1}% hit rate ({} hits, {} misses)", cache.hit_rate * 100.0, cache.hits, cache.misses);
+        }
+        #[cfg(feature = "batched-inference")]
+        if let Some(inference) = &metrics.inference {
+            info!("   Inference: {} queued, {:.1} per batch, {:.2}ms average, {} rejected",
+                  inference.queue_depth, inference.avg_batch_size, inference.avg_latency_ms, inference.rejected);
+        }
         info!("   Events per second: {:.2}", stats.events_per_second);
         info!("   Predictions per second: {:.2}", stats.predictions_per_second);
         info!("   System health: {:.1}%", stats.system_health_score * 100.0);
@@ -346,6 +576,12 @@ impl Clone for MetricsCollector {
             operation_times: self.operation_times.clone(),
             model_performance: self.model_performance.clone(),
             hourly_stats: self.hourly_stats.clone(),
+            memory: self.memory.clone(),
+            query_log: self.query_log.clone(),
+            event_queue: self.event_queue.clone(),
+            prediction_cache: self.prediction_cache.clone(),
+            #[cfg(feature = "batched-inference")]
+            predictor: self.predictor.clone(),
         }
     }
 }
@@ -404,6 +640,31 @@ mod tests {
         assert!(operation_times.contains_key("test_operation"));
     }
 
+    #[test]
+    fn test_latency_histogram_percentiles() {
+        let mut histogram = LatencyHistogram::new();
+        assert_eq!(histogram.summary(), LatencySummary::default());
+
+        // 1ms to 100ms in 1ms steps
+        for ms in 1..=100 {
+            histogram.record(Duration::from_millis(ms));
+        }
+        let summary = histogram.summary();
+        assert_eq!(summary.count, 100);
+        assert!((summary.mean_ms - 50.5).abs() < 1e-9);
+        assert_eq!(summary.max_ms, 100.0);
+        for (actual, expected) in [(summary.p50_ms, 50.0), (summary.p95_ms, 95.0), (summary.p99_ms, 99.0)] {
+            assert!(actual >= expected && actual <= expected * (1.0 + 1.0 / SUB_BUCKETS as f64), "{} vs {}", actual, expected);
+        }
+
+        // Bucket bounds tile the range without gaps
+        for micros in [0, 15, 16, 17, 31, 32, 1_000, 123_456, MAX_TRACKABLE_MICROS] {
+            let index = bucket_index(micros);
+            assert!(bucket_upper_bound(index) >= micros);
+            assert!(index == 0 || bucket_upper_bound(index - 1) < micros);
+        }
+    }
+
     #[tokio::test]
     async fn test_performance_stats() {
         let collector = MetricsCollector::new();
@@ -418,4 +679,28 @@ mod tests {
         assert!(stats.system_health_score > 0.0);
         assert!(stats.system_health_score <= 1.0);
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "batched-inference")]
+    #[tokio::test]
+    async fn test_inference_pool_reported() {
+        let predictor = PredictorService::new()
+            .with_batched_inference(quant_ml::BatchInferenceConfig::default())
+            .unwrap();
+        let predictor = Arc::new(predictor);
+        let collector = MetricsCollector::new().with_inference(predictor.clone());
+
+        let event = quant_models::MatchEvent::new(
+            "m1".to_string(),
+            quant_models::EventType::MatchStart,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        );
+        predictor.predict(&event).await.unwrap();
+
+        let inference = collector.get_current_metrics().await.inference.unwrap();
+        assert_eq!((inference.submitted, inference.completed), (1, 1));
+        assert!(MetricsCollector::new().get_current_metrics().await.inference.is_none());
+    }
+}
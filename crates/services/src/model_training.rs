@@ -0,0 +1,499 @@
+// Offline training job: labeled examples from the database to a model artifact
+// on disk, and stored results to Dixon-Coles team strengths
+
+use quant_db::{
+    HistoricalRepository, MatchRecord, MatchRepository, ModelPerformanceRecord, ModelPerformanceRepository,
+    Repository, TrainingDataRepository, TrainingExampleRecord,
+};
+use quant_ml::{
+    DixonColesFit, DixonColesFitter, EvaluationExample, EvaluationReport, EvaluationSample, Evaluator,
+    LabeledExample, LogisticRegressionModel, MatchResult, Model, ModelArtifact, Trainer,
+};
+use chrono::{DateTime, Duration, Utc};
+use quant_models::{Outcome1X2, Prediction, SimpleMarketOdds};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+const DEFAULT_MAX_EXAMPLES: i64 = 100_000;
+const DEFAULT_HOLDOUT_FRACTION: f64 = 0.2;
+/// Results older than this weigh next to nothing in a Dixon-Coles fit
+const DEFAULT_RESULT_HISTORY_DAYS: i64 = 730;
+
+pub struct TrainingRun {
+    pub artifact: ModelArtifact,
+    pub path: PathBuf,
+    /// Scores on the most recent examples, which the trainer never saw
+    pub holdout: EvaluationReport,
+}
+
+/// How a model trained on some leagues fares on others it has never seen
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferReport {
+    pub train_leagues: Vec<String>,
+    pub train_examples: usize,
+    /// The most recent examples from the training leagues, held out: what
+    /// the model manages at home
+    pub in_league: EvaluationReport,
+    /// Every example from the test leagues together
+    pub transfer: EvaluationReport,
+    /// Predicting the training leagues' outcome frequencies for every test
+    /// match; a model that can't beat this has learned nothing that carries over
+    pub base_rate: EvaluationReport,
+    pub per_league: BTreeMap<String, EvaluationReport>,
+}
+
+/// A retrained model and the live one scored on the same holdout
+#[derive(Debug, Clone, Serialize)]
+pub struct RetrainingComparison {
+    pub live: EvaluationReport,
+    pub candidate: EvaluationReport,
+}
+
+impl RetrainingComparison {
+    /// Lower log loss without a worse Brier score
+    pub fn improves(&self) -> bool {
+        self.candidate.log_loss < self.live.log_loss && self.candidate.brier_score <= self.live.brier_score
+    }
+}
+
+impl TransferReport {
+    /// Extra log loss per match away from the training leagues
+    pub fn log_loss_increase(&self) -> f64 {
+        self.transfer.log_loss - self.in_league.log_loss
+    }
+
+    pub fn beats_base_rate(&self) -> bool {
+        self.transfer.log_loss < self.base_rate.log_loss
+    }
+}
+
+/// Rolling train/test windows over the labeled examples, oldest first. Each
+/// window trains on `train_size` examples and is scored on the
+/// `test_size` after them; the next window starts `test_size` later.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WalkForwardConfig {
+    pub train_size: usize,
+    pub test_size: usize,
+    /// Train each window on everything before its test set instead of the
+    /// last `train_size` examples
+    #[serde(default)]
+    pub expanding: bool,
+}
+
+/// One retrained model scored on the examples that came after its training set
+#[derive(Debug, Clone, Serialize)]
+pub struct WalkForwardWindow {
+    pub index: usize,
+    pub train_examples: usize,
+    pub test_from: DateTime<Utc>,
+    pub test_to: DateTime<Utc>,
+    pub evaluation: EvaluationReport,
+    /// The window's out-of-sample predictions, e.g. to backtest a strategy on
+    #[serde(skip)]
+    pub samples: Vec<EvaluationSample>,
+}
+
+pub struct ModelTrainingJob {
+    trainer: Trainer,
+    fitter: DixonColesFitter,
+    max_examples: i64,
+    holdout_fraction: f64,
+}
+
+impl ModelTrainingJob {
+    pub fn new() -> Self {
+        Self {
+            trainer: Trainer::new(),
+            fitter: DixonColesFitter::new(),
+            max_examples: DEFAULT_MAX_EXAMPLES,
+            holdout_fraction: DEFAULT_HOLDOUT_FRACTION,
+        }
+    }
+
+    pub fn with_trainer(mut self, trainer: Trainer) -> Self {
+        self.trainer = trainer;
+        self
+    }
+
+    pub fn with_fitter(mut self, fitter: DixonColesFitter) -> Self {
+        self.fitter = fitter;
+        self
+    }
+
+    pub fn with_max_examples(mut self, max_examples: i64) -> Self {
+        self.max_examples = max_examples;
+        self
+    }
+
+    pub fn with_holdout_fraction(mut self, holdout_fraction: f64) -> Self {
+        self.holdout_fraction = holdout_fraction.clamp(0.05, 0.5);
+        self
+    }
+
+    /// Train on labeled examples, holding the most recent ones back for an
+    /// out-of-sample evaluation at closing odds. The artifact is written to
+    /// `model_dir` and the evaluation to `model_performance`.
+    pub async fn run(&self, repository: &Repository, model_dir: impl AsRef<Path>) -> Result<TrainingRun> {
+        let (run, _) = self.train_with_holdout(repository, model_dir).await?;
+        Ok(run)
+    }
+
+    /// Train a candidate as `run` does and score `live` on the same holdout,
+    /// so the two can be compared before the candidate is installed
+    pub async fn retrain(
+        &self,
+        repository: &Repository,
+        model_dir: impl AsRef<Path>,
+        live: &Model,
+    ) -> Result<(TrainingRun, RetrainingComparison)> {
+        let (run, evaluation_set) = self.train_with_holdout(repository, model_dir).await?;
+        let live = Evaluator::new().evaluate_model(live, &evaluation_set).await?;
+        let comparison = RetrainingComparison { live, candidate: run.holdout.clone() };
+        Ok((run, comparison))
+    }
+
+    async fn train_with_holdout(
+        &self,
+        repository: &Repository,
+        model_dir: impl AsRef<Path>,
+    ) -> Result<(TrainingRun, Vec<EvaluationExample>)> {
+        let records = repository.get_labeled_examples(self.max_examples).await?;
+        let (train, holdout) = self.split_holdout(&records)?;
+
+        let artifact = self.trainer.train(&train)?;
+        let path = artifact.save(model_dir)?;
+        info!("🧠 Saved {} {} to {}", artifact.name, artifact.version, path.display());
+
+        let evaluation_set = with_closing_odds(repository, holdout).await;
+        let model = Model::LogisticRegression(LogisticRegressionModel::from_artifact(&artifact));
+        let report = Evaluator::new().evaluate_model(&model, &evaluation_set).await?;
+
+        let period_start = evaluation_set.iter().map(|e| e.features.timestamp).min();
+        let period_end = evaluation_set.iter().map(|e| e.features.timestamp).max();
+        if let (Some(start), Some(end)) = (period_start, period_end) {
+            let performance = report.to_model_performance(&artifact.name, &artifact.version);
+            repository.save_model_performance(&ModelPerformanceRecord::new(&performance, start, end)).await?;
+        }
+
+        info!("🧠 Holdout of {} examples: accuracy {:.1}%, log loss {:.4}, Brier {:.4}, ROI at closing odds {:.1}% over {} bets",
+              report.sample_count, report.accuracy * 100.0, report.log_loss, report.brier_score,
+              report.roi_if_followed * 100.0, report.bets_followed);
+
+        Ok((TrainingRun { artifact, path, holdout: report }, evaluation_set))
+    }
+
+    /// Train on `train_leagues` only and score the model on each of
+    /// `test_leagues`, to judge whether it can be trusted in a league it has
+    /// no history for. Nothing is saved.
+    pub async fn evaluate_transfer(
+        &self,
+        repository: &Repository,
+        train_leagues: &[String],
+        test_leagues: &[String],
+    ) -> Result<TransferReport> {
+        if let Some(league) = test_leagues.iter().find(|league| train_leagues.contains(league)) {
+            return Err(anyhow!("{} is in both the training and test leagues", league));
+        }
+
+        let records = repository.get_labeled_examples_for_leagues(train_leagues, self.max_examples).await?;
+        let (train, holdout) = self.split_holdout(&records)?;
+        let artifact = self.trainer.train(&train)?;
+        let model = Model::LogisticRegression(LogisticRegressionModel::from_artifact(&artifact));
+        let evaluator = Evaluator::new();
+        let in_league = evaluator.evaluate_model(&model, &with_closing_odds(repository, holdout).await).await?;
+
+        let mut per_league = BTreeMap::new();
+        let mut test_set = Vec::new();
+        for league in test_leagues {
+            let records = repository
+                .get_labeled_examples_for_leagues(std::slice::from_ref(league), self.max_examples)
+                .await?;
+            let examples = with_closing_odds(repository, labeled_examples(&records)).await;
+            if examples.is_empty() {
+                warn!("🧠 No labeled examples for {}, leaving it out", league);
+                continue;
+            }
+            per_league.insert(league.clone(), evaluator.evaluate_model(&model, &examples).await?);
+            test_set.extend(examples);
+        }
+        if test_set.is_empty() {
+            return Err(anyhow!("no labeled examples in any of the test leagues"));
+        }
+
+        let transfer = evaluator.evaluate_model(&model, &test_set).await?;
+        let base_rate = evaluator.evaluate(&base_rate_samples(&train, &test_set)?);
+        Ok(TransferReport {
+            train_leagues: train_leagues.to_vec(),
+            train_examples: train.len(),
+            in_league,
+            transfer,
+            base_rate,
+            per_league,
+        })
+    }
+
+    /// Train on all but the most recent examples, saving nothing, and
+    /// predict the held-out ones at their closing odds, so strategies can be
+    /// backtested out of sample
+    pub async fn holdout_samples(&self, repository: &Repository) -> Result<Vec<EvaluationSample>> {
+        let records = repository.get_labeled_examples(self.max_examples).await?;
+        let (train, holdout) = self.split_holdout(&records)?;
+        let artifact = self.trainer.train(&train)?;
+        let model = Model::LogisticRegression(LogisticRegressionModel::from_artifact(&artifact));
+        let samples = Evaluator::new().predict_samples(&model, &with_closing_odds(repository, holdout).await).await?;
+        info!("🧠 Trained on {} examples and predicted {} held out", train.len(), samples.len());
+        Ok(samples)
+    }
+
+    /// Retrain on each walk-forward window and score it only on the
+    /// examples that follow, so performance can be compared window by window
+    pub async fn walk_forward(&self, repository: &Repository, config: &WalkForwardConfig) -> Result<Vec<WalkForwardWindow>> {
+        let records = repository.get_labeled_examples(self.max_examples).await?;
+        let mut examples = labeled_examples(&records);
+        examples.reverse();
+        let splits = walk_forward_splits(examples.len(), config);
+        if splits.is_empty() {
+            return Err(anyhow!(
+                "{} labeled examples are too few for a {} example training window and a {} example test window",
+                examples.len(), config.train_size, config.test_size,
+            ));
+        }
+
+        let evaluator = Evaluator::new();
+        let mut windows = Vec::with_capacity(splits.len());
+        for (index, (train, test)) in splits.into_iter().enumerate() {
+            let artifact = self.trainer.train(&examples[train.clone()])?;
+            let model = Model::LogisticRegression(LogisticRegressionModel::from_artifact(&artifact));
+            let test_set = with_closing_odds(repository, examples[test].to_vec()).await;
+            let samples = evaluator.predict_samples(&model, &test_set).await?;
+            let evaluation = evaluator.evaluate(&samples);
+            let (Some(test_from), Some(test_to)) = (
+                test_set.iter().map(|e| e.features.timestamp).min(),
+                test_set.iter().map(|e| e.features.timestamp).max(),
+            ) else {
+                continue;
+            };
+            info!("🧠 Window {}: trained on {}, {} to {}: log loss {:.4}, Brier {:.4}, ROI {:.1}%",
+                  index + 1, train.len(), test_from.date_naive(), test_to.date_naive(),
+                  evaluation.log_loss, evaluation.brier_score, evaluation.roi_if_followed * 100.0);
+            windows.push(WalkForwardWindow {
+                index: index + 1,
+                train_examples: train.len(),
+                test_from,
+                test_to,
+                evaluation,
+                samples,
+            });
+        }
+        Ok(windows)
+    }
+
+    /// Fit team strengths to the results stored over the last two years
+    pub async fn fit_team_strengths(&self, repository: &Repository) -> Result<DixonColesFit> {
+        self.fitter.fit(&self.recent_results(repository).await?)
+    }
+
+    /// The results stored over the last two years, oldest first
+    pub async fn recent_results(&self, repository: &Repository) -> Result<Vec<MatchResult>> {
+        let since = Utc::now() - Duration::days(DEFAULT_RESULT_HISTORY_DAYS);
+        let records = repository.get_results_since(since).await?;
+        Ok(records.iter().filter_map(match_result).collect())
+    }
+
+    /// Split newest-first records into training examples and the most recent
+    /// `holdout_fraction` of them
+    fn split_holdout(&self, records: &[TrainingExampleRecord]) -> Result<(Vec<LabeledExample>, Vec<LabeledExample>)> {
+        let mut examples = labeled_examples(records);
+        if examples.len() < records.len() {
+            warn!("🧠 Skipped {} unreadable training examples", records.len() - examples.len());
+        }
+
+        let holdout_len = (examples.len() as f64 * self.holdout_fraction).round() as usize;
+        if holdout_len == 0 {
+            return Err(anyhow!("not enough labeled examples to hold any out for evaluation"));
+        }
+        let train = examples.split_off(holdout_len);
+        Ok((train, examples))
+    }
+}
+
+impl Default for ModelTrainingJob {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Attach each match's closing price, when one was imported
+async fn with_closing_odds(repository: &Repository, examples: Vec<LabeledExample>) -> Vec<EvaluationExample> {
+    let mut closing: HashMap<String, Option<SimpleMarketOdds>> = HashMap::new();
+    let mut evaluation_set = Vec::with_capacity(examples.len());
+
+    for example in examples {
+        let match_id = example.features.match_id.clone();
+        if !closing.contains_key(&match_id) {
+            let odds = match repository.get_closing_odds(&match_id).await {
+                Ok(record) => record.and_then(|odds| Some(SimpleMarketOdds::new(
+                    odds.home_odds?, odds.draw_odds?, odds.away_odds?,
+                ))),
+                Err(e) => {
+                    warn!("🧠 Failed to load closing odds for {}: {}", match_id, e);
+                    None
+                }
+            };
+            closing.insert(match_id.clone(), odds);
+        }
+
+        evaluation_set.push(EvaluationExample {
+            features: example.features,
+            outcome: example.outcome,
+            closing_odds: closing[&match_id].clone(),
+        });
+    }
+    evaluation_set
+}
+
+/// Predict the outcome frequencies seen in `train` for every test example
+fn base_rate_samples(train: &[LabeledExample], test: &[EvaluationExample]) -> Result<Vec<EvaluationSample>> {
+    let share = |outcome: Outcome1X2| {
+        train.iter().filter(|example| example.outcome == outcome).count() as f64 / train.len().max(1) as f64
+    };
+    let (home, away) = (share(Outcome1X2::HomeWin), share(Outcome1X2::AwayWin));
+
+    test.iter()
+        .map(|example| {
+            let prediction = Prediction::new(
+                example.features.match_id.clone(),
+                "BaseRate".to_string(),
+                "1.0".to_string(),
+                home,
+                away,
+                example.features.timestamp,
+            )?;
+            Ok(EvaluationSample {
+                prediction,
+                outcome: example.outcome,
+                odds: example.closing_odds.clone(),
+            })
+        })
+        .collect()
+}
+
+/// None for a match without a final score
+fn match_result(record: &MatchRecord) -> Option<MatchResult> {
+    Some(MatchResult {
+        home_team: record.team_home.clone(),
+        away_team: record.team_away.clone(),
+        home_goals: u8::try_from(record.home_score?).ok()?,
+        away_goals: u8::try_from(record.away_score?).ok()?,
+        played_at: record.match_date,
+        season: record.season.clone(),
+        league: record.league.clone(),
+    })
+}
+
+/// Training and test ranges of each walk-forward window over `len`
+/// examples, oldest first; a final test set shorter than `test_size` is dropped
+fn walk_forward_splits(len: usize, config: &WalkForwardConfig) -> Vec<(Range<usize>, Range<usize>)> {
+    if config.train_size == 0 || config.test_size == 0 {
+        return Vec::new();
+    }
+    let mut splits = Vec::new();
+    let mut test_start = config.train_size;
+    while test_start + config.test_size <= len {
+        let train_start = if config.expanding { 0 } else { test_start - config.train_size };
+        splits.push((train_start..test_start, test_start..test_start + config.test_size));
+        test_start += config.test_size;
+    }
+    splits
+}
+
+fn labeled_examples(records: &[TrainingExampleRecord]) -> Vec<LabeledExample> {
+    records
+        .iter()
+        .filter_map(|record| {
+            Some(LabeledExample {
+                features: record.to_feature_vector()?,
+                outcome: record.parsed_outcome()?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use quant_models::FeatureVector;
+
+    fn example(match_id: &str, outcome: Outcome1X2) -> LabeledExample {
+        LabeledExample {
+            features: FeatureVector {
+                match_id: match_id.to_string(),
+                features: HashMap::new(),
+                timestamp: Utc::now(),
+                schema: None,
+            },
+            outcome,
+        }
+    }
+
+    #[test]
+    fn test_base_rate_predicts_training_frequencies() {
+        let train = [
+            example("a", Outcome1X2::HomeWin),
+            example("b", Outcome1X2::HomeWin),
+            example("c", Outcome1X2::Draw),
+            example("d", Outcome1X2::AwayWin),
+        ];
+        let test: Vec<EvaluationExample> = [example("e", Outcome1X2::Draw)].into_iter()
+            .map(|example| EvaluationExample { features: example.features, outcome: example.outcome, closing_odds: None })
+            .collect();
+
+        let samples = base_rate_samples(&train, &test).unwrap();
+        let prediction = &samples[0].prediction;
+        assert_eq!((prediction.home_win_prob, prediction.away_win_prob), (0.5, 0.25));
+        assert_eq!(prediction.draw_prob, Some(0.25));
+
+        let report = Evaluator::new().evaluate(&samples);
+        assert!((report.log_loss - 4f64.ln()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_walk_forward_windows_roll_over_the_examples() {
+        let rolling = WalkForwardConfig { train_size: 4, test_size: 2, expanding: false };
+        assert_eq!(walk_forward_splits(11, &rolling), vec![(0..4, 4..6), (2..6, 6..8), (4..8, 8..10)]);
+
+        let expanding = WalkForwardConfig { expanding: true, ..rolling };
+        assert_eq!(walk_forward_splits(10, &expanding), vec![(0..4, 4..6), (0..6, 6..8), (0..8, 8..10)]);
+
+        assert!(walk_forward_splits(5, &rolling).is_empty());
+    }
+
+    #[test]
+    fn test_retrained_model_must_beat_live_one() {
+        let report = |home_win_prob: f64| {
+            let samples: Vec<EvaluationSample> = ["a", "b", "c"].iter()
+                .map(|match_id| EvaluationSample {
+                    prediction: Prediction::new(
+                        match_id.to_string(), "LogisticRegression".to_string(), "v1.0".to_string(),
+                        home_win_prob, (1.0 - home_win_prob) / 2.0, Utc::now(),
+                    ).unwrap(),
+                    outcome: Outcome1X2::HomeWin,
+                    odds: None,
+                })
+                .collect();
+            Evaluator::new().evaluate(&samples)
+        };
+
+        let sharper = RetrainingComparison { live: report(0.4), candidate: report(0.6) };
+        assert!(sharper.improves());
+        let worse = RetrainingComparison { live: report(0.6), candidate: report(0.4) };
+        assert!(!worse.improves());
+        let same = RetrainingComparison { live: report(0.6), candidate: report(0.6) };
+        assert!(!same.improves());
+    }
+}
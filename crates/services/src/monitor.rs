@@ -1,11 +1,172 @@
 // Monitoring and metrics service
 
+use crate::alerting::{Alert, AlertKind, AlertRules, HealthSnapshot, NotificationChannel};
+use crate::metrics::MetricsCollector;
+use crate::predictor::PredictorService;
+use chrono::Utc;
+use quant_models::{DomainEvent, RiskLimit};
+use quant_stream::DomainEventMessage;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// Writes the domain event feed to the `audit` log target: a one-line
+/// summary and the event as JSON, so the log can be replayed or grepped.
+/// Also checks system health against alert rules and sends alerts through
+/// the configured notification channels.
 pub struct MonitorService {
     name: String,
+    rules: AlertRules,
+    channels: Vec<Box<dyn NotificationChannel>>,
+    metrics: Option<Arc<MetricsCollector>>,
+    predictor: Option<Arc<PredictorService>>,
+    check_interval: Duration,
 }
 
 impl MonitorService {
     pub fn new(name: String) -> Self {
-        Self { name }
+        Self {
+            name,
+            rules: AlertRules::default(),
+            channels: Vec::new(),
+            metrics: None,
+            predictor: None,
+            check_interval: Duration::from_secs(30),
+        }
+    }
+
+    pub fn with_alert_rules(mut self, rules: AlertRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn with_channel(mut self, channel: Box<dyn NotificationChannel>) -> Self {
+        self.channels.push(channel);
+        self
     }
-}
\ No newline at end of file
+
+    /// Check error rate, feed activity and model health every `interval`;
+    /// without these only drawdowns are alerted on
+    pub fn with_health_checks(mut self, metrics: Arc<MetricsCollector>, predictor: Arc<PredictorService>, interval: Duration) -> Self {
+        self.metrics = Some(metrics);
+        self.predictor = Some(predictor);
+        self.check_interval = interval.max(Duration::from_secs(1));
+        self
+    }
+
+    /// Audit every event on `feed` and check health until cancelled.
+    /// Subscribe before anything publishes, or the first events are missed.
+    pub async fn run(&self, mut feed: broadcast::Receiver<DomainEventMessage>, shutdown: CancellationToken) {
+        info!("🛰️ {} auditing domain events, alerting through {} channels", self.name, self.channels.len());
+        let mut checks = tokio::time::interval(self.check_interval);
+        let started_at = Utc::now();
+        let mut previous = None;
+        // Health alerts are sent when a rule starts failing, not on every check
+        let mut firing = HashSet::new();
+        loop {
+            tokio::select! {
+                message = feed.recv() => match message {
+                    Ok(message) => {
+                        self.audit(&message);
+                        if let DomainEvent::DrawdownThresholdCrossed { drawdown, .. } = message.event {
+                            if let Some(alert) = self.rules.drawdown_alert(drawdown) {
+                                self.dispatch(&alert).await;
+                            }
+                        }
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!(target: "audit", "🛰️ {} fell behind and missed {} domain events", self.name, missed)
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                _ = checks.tick() => {
+                    let Some(current) = self.health(started_at).await else { continue };
+                    let alerts = match &previous {
+                        Some(previous) => self.rules.evaluate(previous, &current),
+                        None => Vec::new(),
+                    };
+                    let failing: HashSet<AlertKind> = alerts.iter().map(|alert| alert.kind).collect();
+                    for alert in alerts.iter().filter(|alert| !firing.contains(&alert.kind)) {
+                        self.dispatch(alert).await;
+                    }
+                    for resolved in firing.difference(&failing) {
+                        info!(target: "alerts", "✅ {:?} resolved", resolved);
+                    }
+                    firing = failing;
+                    previous = Some(current);
+                }
+                _ = shutdown.cancelled() => break,
+            }
+        }
+    }
+
+    async fn health(&self, started_at: chrono::DateTime<Utc>) -> Option<HealthSnapshot> {
+        let metrics = self.metrics.as_ref()?.get_current_metrics().await;
+        // The ensemble moves weight off a bad component, so the model is
+        // only degraded once its best component is
+        let model_brier = match &self.predictor {
+            Some(predictor) => predictor.ensemble_weights().await
+                .and_then(|weights| [weights.logistic_brier, weights.poisson_brier].into_iter().flatten().reduce(f64::min)),
+            None => None,
+        };
+        let drifted = match &self.predictor {
+            Some(predictor) => predictor.drift_report().await
+                .map(|report| report.drifted().map(|score| score.name.clone()).collect())
+                .unwrap_or_default(),
+            None => Vec::new(),
+        };
+        Some(HealthSnapshot {
+            events_processed: metrics.events_processed,
+            error_count: metrics.error_count,
+            last_event_at: metrics.last_event_at.unwrap_or(started_at),
+            model_brier,
+            drifted,
+            taken_at: Utc::now(),
+        })
+    }
+
+    async fn dispatch(&self, alert: &Alert) {
+        warn!(target: "alerts", "🚨 {:?} {:?}: {}", alert.severity, alert.kind, alert.message);
+        for channel in &self.channels {
+            if let Err(e) = channel.send(alert).await {
+                warn!(target: "alerts", "🚨 Failed to send {:?} alert by {}: {}", alert.kind, channel.name(), e);
+            }
+        }
+    }
+
+    fn audit(&self, message: &DomainEventMessage) {
+        let json = serde_json::to_string(message).unwrap_or_default();
+        let summary = summarize(&message.event);
+        match message.event {
+            DomainEvent::RiskLimitBreached { .. } | DomainEvent::DrawdownThresholdCrossed { .. } => {
+                warn!(target: "audit", "#{} {} {}", message.sequence, summary, json)
+            }
+            _ => info!(target: "audit", "#{} {} {}", message.sequence, summary, json),
+        }
+    }
+}
+
+fn summarize(event: &DomainEvent) -> String {
+    match event {
+        DomainEvent::BetPlaced { bet } => format!("bet {} placed on {}: {} at {}", bet.id, bet.match_id, bet.stake, bet.odds),
+        DomainEvent::BetSettled { bet, profit_loss } => {
+            format!("bet {} settled on {} {:?}: {}", bet.id, bet.match_id, bet.status, profit_loss)
+        }
+        DomainEvent::RiskLimitBreached { limit, .. } => match limit {
+            RiskLimit::DailyLoss { limit, realized_loss } => format!("daily loss limit {} breached: {} lost", limit, realized_loss),
+            RiskLimit::ConcurrentBets { limit } => format!("concurrent bet limit {} breached", limit),
+            RiskLimit::Concentration { dimension, hhi, threshold, .. } => {
+                format!("{} concentration {:.2} above {:.2}", dimension, hhi, threshold)
+            }
+        },
+        DomainEvent::DrawdownThresholdCrossed { threshold, drawdown } => {
+            format!("drawdown {:.1}% crossed {:.0}%", drawdown * 100.0, threshold * 100.0)
+        }
+        DomainEvent::ModelPromoted { name, version, validation_log_loss } => {
+            format!("model {} {} promoted (validation log loss {:.4})", name, version, validation_log_loss)
+        }
+    }
+}
@@ -0,0 +1,302 @@
+// Posts strong trading signals and settled bets to chat, through a Telegram
+// bot or a Discord webhook
+
+use crate::http::{self, HttpTransport};
+use crate::trader::{describe_bet_type, TradingSignal};
+use anyhow::{bail, Result};
+use quant_models::{BettingDecision, DomainEvent};
+use quant_stream::DomainEventMessage;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    pub enabled: bool,
+    /// Signals at least this strong are posted
+    pub min_signal_strength: f64,
+    /// Messages posted per minute; the rest are dropped, and the next
+    /// message posted says how many were
+    pub max_per_minute: usize,
+    /// Fills in `{match_id}`, `{bet}`, `{odds}`, `{stake}`, `{currency}`,
+    /// `{strength}`, `{ev}`, `{strategy}` and `{reasoning}`
+    pub signal_template: String,
+    /// Fills in `{match_id}`, `{bet}`, `{odds}`, `{stake}`, `{currency}`,
+    /// `{strategy}`, `{status}` and `{profit_loss}`
+    pub settlement_template: String,
+    pub targets: Vec<NotifierTarget>,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_signal_strength: 0.6,
+            max_per_minute: 20,
+            signal_template: "💡 {match_id}: {bet} at {odds}, {stake} {currency} staked ({strength} strength, EV {ev})"
+                .to_string(),
+            settlement_template: "🏁 {match_id}: {bet} at {odds} {status}, {profit_loss} {currency}".to_string(),
+            targets: Vec::new(),
+        }
+    }
+}
+
+/// A chat messages are posted to, as configured under `[[notifications.targets]]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotifierTarget {
+    Telegram { bot_token: String, chat_id: String },
+    Discord { webhook_url: String },
+}
+
+impl NotifierTarget {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Telegram { .. } => "telegram",
+            Self::Discord { .. } => "discord",
+        }
+    }
+
+    /// Where `text` is posted and the JSON body that carries it
+    fn request(&self, text: &str) -> (String, String) {
+        match self {
+            Self::Telegram { bot_token, chat_id } => (
+                format!("https://api.telegram.org/bot{}/sendMessage", bot_token),
+                serde_json::json!({ "chat_id": chat_id, "text": text }).to_string(),
+            ),
+            Self::Discord { webhook_url } => {
+                (webhook_url.clone(), serde_json::json!({ "content": text }).to_string())
+            }
+        }
+    }
+}
+
+/// Replace each `{name}` in `template` with its value; unknown names are left as they are
+pub fn render_template(template: &str, fields: &[(&str, String)]) -> String {
+    fields.iter().fold(template.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
+
+/// Messages allowed in the last minute
+struct RateLimiter {
+    max_per_minute: usize,
+    sent: VecDeque<Instant>,
+    dropped: usize,
+}
+
+impl RateLimiter {
+    fn allow(&mut self, now: Instant) -> bool {
+        while self.sent.front().is_some_and(|sent| now.duration_since(*sent) >= RATE_WINDOW) {
+            self.sent.pop_front();
+        }
+        if self.sent.len() >= self.max_per_minute {
+            self.dropped += 1;
+            return false;
+        }
+        self.sent.push_back(now);
+        true
+    }
+}
+
+/// Posts strong signals from the trading engine and settled bets from the
+/// domain event feed to every target
+pub struct Notifier {
+    config: NotificationsConfig,
+    transport: Arc<dyn HttpTransport>,
+    limiter: RateLimiter,
+}
+
+impl Notifier {
+    pub fn new(config: NotificationsConfig) -> Self {
+        let limiter = RateLimiter { max_per_minute: config.max_per_minute, sent: VecDeque::new(), dropped: 0 };
+        Self { config, transport: Arc::new(http::client()), limiter }
+    }
+
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// The message for a signal strong enough to post, with a bet to back
+    pub fn signal_message(&self, signal: &TradingSignal) -> Option<String> {
+        if signal.signal_strength < self.config.min_signal_strength {
+            return None;
+        }
+        let bet = signal.recommended_bet.as_ref()?;
+        let mut fields = bet_fields(bet);
+        fields.push(("strength", format!("{:.0}%", signal.signal_strength * 100.0)));
+        fields.push(("ev", format!("{:+.1}%", bet.expected_value * 100.0)));
+        fields.push(("reasoning", signal.reasoning.clone()));
+        Some(render_template(&self.config.signal_template, &fields))
+    }
+
+    pub fn settlement_message(&self, bet: &BettingDecision, profit_loss: Decimal) -> String {
+        let mut fields = bet_fields(bet);
+        fields.push(("status", format!("{:?}", bet.status).to_lowercase()));
+        let sign = if profit_loss > Decimal::ZERO { "+" } else { "" };
+        fields.push(("profit_loss", format!("{}{}", sign, profit_loss.round_dp(2))));
+        render_template(&self.config.settlement_template, &fields)
+    }
+
+    /// Post `text` to every target unless the rate limit is spent. Returns
+    /// whether it was posted.
+    pub async fn notify(&mut self, text: &str) -> bool {
+        self.notify_at(text, Instant::now()).await
+    }
+
+    async fn notify_at(&mut self, text: &str, now: Instant) -> bool {
+        if !self.limiter.allow(now) {
+            debug!("📣 Notification rate limit reached, dropping: {}", text);
+            return false;
+        }
+        let text = match std::mem::take(&mut self.limiter.dropped) {
+            0 => text.to_string(),
+            dropped => format!("{}\n({} more dropped by the rate limit)", text, dropped),
+        };
+        for target in &self.config.targets {
+            if let Err(e) = self.post(target, &text).await {
+                warn!("📣 Could not notify {}: {}", target.name(), e);
+            }
+        }
+        true
+    }
+
+    /// Errors name the target rather than the URL, which may hold a bot token
+    async fn post(&self, target: &NotifierTarget, text: &str) -> Result<()> {
+        let (url, body) = target.request(text);
+        let response = self.transport.post(&url, &[("Content-Type", "application/json")], body).await?;
+        if !(200..300).contains(&response.status) {
+            bail!("{} answered {}: {}", target.name(), response.status, response.body);
+        }
+        Ok(())
+    }
+
+    /// Post from both feeds until cancelled. Subscribe before anything
+    /// publishes, or the first messages are missed.
+    pub async fn run(
+        mut self,
+        mut signals: broadcast::Receiver<TradingSignal>,
+        mut events: broadcast::Receiver<DomainEventMessage>,
+        shutdown: CancellationToken,
+    ) {
+        let targets: Vec<_> = self.config.targets.iter().map(NotifierTarget::name).collect();
+        info!("📣 Notifying {} of signals over {:.0}% and settlements", targets.join(" and "),
+              self.config.min_signal_strength * 100.0);
+        loop {
+            let text = tokio::select! {
+                signal = signals.recv() => match signal {
+                    Ok(signal) => self.signal_message(&signal),
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("📣 Notifier fell behind and missed {} signals", missed);
+                        None
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                message = events.recv() => match message {
+                    Ok(DomainEventMessage { event: DomainEvent::BetSettled { bet, profit_loss }, .. }) => {
+                        Some(self.settlement_message(&bet, profit_loss))
+                    }
+                    Ok(_) => None,
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("📣 Notifier fell behind and missed {} domain events", missed);
+                        None
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                _ = shutdown.cancelled() => break,
+            };
+            if let Some(text) = text {
+                self.notify(&text).await;
+            }
+        }
+    }
+}
+
+fn bet_fields(bet: &BettingDecision) -> Vec<(&'static str, String)> {
+    vec![
+        ("match_id", bet.match_id.clone()),
+        ("bet", describe_bet_type(&bet.bet_type)),
+        ("odds", bet.odds.to_string()),
+        ("stake", bet.stake.to_string()),
+        ("currency", bet.currency.to_string()),
+        ("strategy", bet.strategy.clone()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HttpResponse;
+    use crate::trader::RiskAssessment;
+    use async_trait::async_trait;
+    use quant_models::{BetStatus, BetType};
+    use rust_decimal_macros::dec;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingTransport {
+        posts: Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl HttpTransport for RecordingTransport {
+        async fn post(&self, url: &str, _headers: &[(&str, &str)], body: String) -> Result<HttpResponse> {
+            self.posts.lock().unwrap().push((url.to_string(), body));
+            Ok(HttpResponse { status: 200, body: "ok".to_string() })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_strong_signals_and_settlements_posted_within_rate_limit() {
+        let config = NotificationsConfig {
+            enabled: true,
+            max_per_minute: 2,
+            targets: vec![
+                NotifierTarget::Telegram { bot_token: "123:abc".to_string(), chat_id: "-100".to_string() },
+                NotifierTarget::Discord { webhook_url: "https://discord.com/api/webhooks/1/x".to_string() },
+            ],
+            ..Default::default()
+        };
+        let transport = Arc::new(RecordingTransport::default());
+        let mut notifier = Notifier::new(config).with_transport(transport.clone());
+
+        let mut bet = BettingDecision::new(
+            "m1".to_string(), BetType::HomeWin, dec!(25), dec!(2.10), 0.55, "moderate".to_string(),
+        ).unwrap();
+        let weak = TradingSignal::new("m1".to_string(), 0.4, Some(bet.clone()), RiskAssessment::default(), String::new());
+        assert!(notifier.signal_message(&weak).is_none());
+        let strong = TradingSignal::new("m1".to_string(), 0.8, Some(bet.clone()), RiskAssessment::default(), String::new());
+        let message = notifier.signal_message(&strong).unwrap();
+        assert_eq!(message, "💡 m1: Home Win at 2.10, 25 USD staked (80% strength, EV +15.5%)");
+
+        bet.update_status(BetStatus::Won);
+        let settled = notifier.settlement_message(&bet, dec!(27.5));
+        assert_eq!(settled, "🏁 m1: Home Win at 2.10 won, +27.5 USD");
+
+        let now = Instant::now();
+        assert!(notifier.notify_at(&message, now).await);
+        assert!(notifier.notify_at(&settled, now).await);
+        assert!(!notifier.notify_at(&settled, now).await);
+        {
+            let posts = transport.posts.lock().unwrap();
+            assert_eq!(posts.len(), 4);
+            assert_eq!(posts[0].0, "https://api.telegram.org/bot123:abc/sendMessage");
+            let telegram: serde_json::Value = serde_json::from_str(&posts[0].1).unwrap();
+            assert_eq!((telegram["chat_id"].as_str(), telegram["text"].as_str()), (Some("-100"), Some(message.as_str())));
+            let discord: serde_json::Value = serde_json::from_str(&posts[1].1).unwrap();
+            assert_eq!(discord["content"].as_str(), Some(message.as_str()));
+        }
+
+        // A minute on, the next message owns up to the one dropped
+        assert!(notifier.notify_at(&settled, now + RATE_WINDOW).await);
+        let posts = transport.posts.lock().unwrap();
+        assert!(posts[4].1.ends_with("(1 more dropped by the rate limit)\"}"), "{}", posts[4].1);
+    }
+}
@@ -0,0 +1,274 @@
+use crate::jobs::{JobQueue, BACKFILL_ODDS_JOB};
+use quant_db::{OddsRecord, OddsRepository, Repository};
+use quant_models::{MarketOdds, MarketType, OddsBook, OddsTimeline, SimpleMarketOdds, TimelineStats};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+const DEFAULT_MAX_MATCHES: usize = 1_000;
+
+#[derive(Debug, Default)]
+struct MatchHistory {
+    /// Keyed by bookmaker
+    timelines: HashMap<String, OddsTimeline>,
+    last_updated: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OddsQuote {
+    pub timestamp: DateTime<Utc>,
+    pub odds: SimpleMarketOdds,
+}
+
+/// One bookmaker's prices for a match, oldest first
+#[derive(Debug, Clone, Serialize)]
+pub struct OddsSeries {
+    pub bookmaker: String,
+    pub quotes: Vec<OddsQuote>,
+}
+
+/// Delta-encoded odds history for every match and bookmaker, so backtests
+/// and closing line value can read the prices in force at any moment
+pub struct OddsHistory {
+    matches: RwLock<HashMap<String, MatchHistory>>,
+    keyframe_interval: usize,
+    max_matches: usize,
+    /// Where every price change is also stored, beyond the matches held here
+    repository: Option<Arc<Repository>>,
+    /// Ticks the repository fails to store are queued here to be stored later
+    jobs: Option<Arc<JobQueue>>,
+}
+
+impl OddsHistory {
+    pub fn new() -> Self {
+        Self {
+            matches: RwLock::new(HashMap::new()),
+            keyframe_interval: quant_models::DEFAULT_KEYFRAME_INTERVAL,
+            max_matches: DEFAULT_MAX_MATCHES,
+            repository: None,
+            jobs: None,
+        }
+    }
+
+    /// Store each price change as an odds tick and read series back from there
+    pub fn with_repository(mut self, repository: Arc<Repository>) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    /// Retry ticks that fail to store as backfill jobs instead of dropping them
+    pub fn with_jobs(mut self, jobs: Arc<JobQueue>) -> Self {
+        self.jobs = Some(jobs);
+        self
+    }
+
+    pub fn with_keyframe_interval(mut self, keyframe_interval: usize) -> Self {
+        self.keyframe_interval = keyframe_interval.max(1);
+        self
+    }
+
+    /// Keep at most this many matches, dropping the least recently updated
+    pub fn with_max_matches(mut self, max_matches: usize) -> Self {
+        self.max_matches = max_matches.max(1);
+        self
+    }
+
+    /// Record a quote; returns whether anything changed and was stored
+    pub async fn record(&self, match_id: &str, bookmaker: &str, timestamp: DateTime<Utc>, odds: &SimpleMarketOdds) -> bool {
+        let stored = self.record_in_memory(match_id, bookmaker, timestamp, odds).await;
+        if let (true, Some(repository)) = (stored, &self.repository) {
+            let tick = OddsRecord::tick(match_id.to_string(), bookmaker.to_string(), odds, timestamp);
+            if let Err(e) = repository.record_odds(std::slice::from_ref(&tick)).await {
+                warn!("💾 Failed to store {} odds for {}: {}", bookmaker, match_id, e);
+                if let Some(jobs) = &self.jobs {
+                    match serde_json::to_value([tick]) {
+                        Ok(payload) => { jobs.enqueue(BACKFILL_ODDS_JOB, payload).await; }
+                        Err(e) => warn!("💾 Failed to queue {} odds for {}: {}", bookmaker, match_id, e),
+                    }
+                }
+            }
+        }
+        stored
+    }
+
+    async fn record_in_memory(&self, match_id: &str, bookmaker: &str, timestamp: DateTime<Utc>, odds: &SimpleMarketOdds) -> bool {
+        let mut matches = self.matches.write().await;
+
+        if !matches.contains_key(match_id) && matches.len() >= self.max_matches {
+            let oldest = matches.iter()
+                .min_by_key(|(_, history)| history.last_updated)
+                .map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                matches.remove(&oldest);
+            }
+        }
+
+        let history = matches.entry(match_id.to_string()).or_default();
+        let stored = history.timelines
+            .entry(bookmaker.to_string())
+            .or_insert_with(|| OddsTimeline::with_keyframe_interval(self.keyframe_interval))
+            .push(timestamp, odds);
+        if stored {
+            history.last_updated = history.last_updated.max(Some(timestamp));
+        }
+        stored
+    }
+
+    /// Record a bookmaker quote from the feed. Only three-way match winner
+    /// quotes are kept.
+    pub async fn record_market_odds(&self, odds: &MarketOdds) -> bool {
+        if odds.market_type != MarketType::MatchWinner || !odds.is_active {
+            return false;
+        }
+        let Ok((home_win, Some(draw), away_win)) = odds.odds.to_decimal() else {
+            return false;
+        };
+        self.record(&odds.match_id, &odds.bookmaker, odds.timestamp, &SimpleMarketOdds::new(home_win, draw, away_win)).await
+    }
+
+    /// One bookmaker's quote as it stood at `at`
+    pub async fn snapshot_at(&self, match_id: &str, bookmaker: &str, at: DateTime<Utc>) -> Option<SimpleMarketOdds> {
+        self.matches.read().await
+            .get(match_id)?
+            .timelines.get(bookmaker)?
+            .at(at)
+    }
+
+    /// Every bookmaker's quote as it stood at `at`; the prices in force at
+    /// kick-off are the closing line
+    pub async fn book_at(&self, match_id: &str, at: DateTime<Utc>) -> Option<OddsBook> {
+        let matches = self.matches.read().await;
+        let history = matches.get(match_id)?;
+
+        let mut book = OddsBook::new(match_id.to_string());
+        for (bookmaker, timeline) in &history.timelines {
+            if let Some((quoted_at, odds)) = timeline.quote_at(at) {
+                book.insert_simple_at(bookmaker, &odds, quoted_at);
+            }
+        }
+        (!book.is_empty()).then_some(book)
+    }
+
+    /// Each bookmaker's quotes within `[from, to)`, at most `limit` in all,
+    /// from the database when there is one and from memory otherwise
+    pub async fn series(
+        &self,
+        match_id: &str,
+        bookmaker: Option<&str>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<OddsSeries>> {
+        let mut quotes: Vec<(String, OddsQuote)> = match &self.repository {
+            Some(repository) => repository.get_odds_history(match_id, bookmaker, from, to, limit as i64).await?
+                .into_iter()
+                .filter_map(|tick| {
+                    let odds = tick.to_simple_odds()?;
+                    Some((tick.bookmaker, OddsQuote { timestamp: tick.timestamp, odds }))
+                })
+                .collect(),
+            None => {
+                let matches = self.matches.read().await;
+                let timelines = matches.get(match_id).into_iter().flat_map(|history| &history.timelines);
+                timelines
+                    .filter(|(name, _)| bookmaker.is_none_or(|bookmaker| *name == bookmaker))
+                    .flat_map(|(name, timeline)| {
+                        timeline.entries().iter()
+                            .map(|entry| entry.timestamp())
+                            .filter(|timestamp| *timestamp >= from && *timestamp < to)
+                            .filter_map(|timestamp| {
+                                let odds = timeline.at(timestamp)?;
+                                Some((name.clone(), OddsQuote { timestamp, odds }))
+                            })
+                    })
+                    .collect()
+            }
+        };
+        quotes.sort_by(|(a, a_quote), (b, b_quote)| (a_quote.timestamp, a).cmp(&(b_quote.timestamp, b)));
+        quotes.truncate(limit);
+
+        let mut series: BTreeMap<String, Vec<OddsQuote>> = BTreeMap::new();
+        for (bookmaker, quote) in quotes {
+            series.entry(bookmaker).or_default().push(quote);
+        }
+        Ok(series.into_iter().map(|(bookmaker, quotes)| OddsSeries { bookmaker, quotes }).collect())
+    }
+
+    /// The stored timeline, e.g. to persist or hand to a backtest
+    pub async fn timeline(&self, match_id: &str, bookmaker: &str) -> Option<OddsTimeline> {
+        self.matches.read().await.get(match_id)?.timelines.get(bookmaker).cloned()
+    }
+
+    pub async fn stats(&self) -> TimelineStats {
+        let matches = self.matches.read().await;
+        matches.values()
+            .flat_map(|history| history.timelines.values())
+            .map(OddsTimeline::stats)
+            .fold(TimelineStats::default(), |total, stats| TimelineStats {
+                keyframes: total.keyframes + stats.keyframes,
+                deltas: total.deltas + stats.deltas,
+                prices_stored: total.prices_stored + stats.prices_stored,
+                prices_uncompressed: total.prices_uncompressed + stats.prices_uncompressed,
+            })
+    }
+}
+
+impl Default for OddsHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_book_at_reconstructs_each_bookmaker() {
+        let history = OddsHistory::new().with_max_matches(1);
+        let kickoff = Utc::now();
+        let before = kickoff - Duration::minutes(5);
+
+        history.record("m1", "Pinnacle", before, &SimpleMarketOdds::new(dec!(2.00), dec!(3.40), dec!(3.90))).await;
+        history.record("m1", "Bet365", before, &SimpleMarketOdds::new(dec!(1.95), dec!(3.30), dec!(4.00))).await;
+        history.record("m1", "Pinnacle", kickoff, &SimpleMarketOdds::new(dec!(1.90), dec!(3.50), dec!(4.20))).await;
+        history.record("m1", "Pinnacle", kickoff + Duration::minutes(30), &SimpleMarketOdds::new(dec!(1.40), dec!(4.50), dec!(8.00))).await;
+
+        let closing = history.book_at("m1", kickoff).await.unwrap();
+        assert_eq!(closing.quote("Pinnacle").unwrap().home_win, dec!(1.90));
+        assert_eq!(closing.quote("Bet365").unwrap().timestamp, before);
+        assert!(history.book_at("m1", before - Duration::seconds(1)).await.is_none());
+
+        // The oldest match is dropped once the cap is reached
+        history.record("m2", "Pinnacle", kickoff, &SimpleMarketOdds::new(dec!(2.5), dec!(3.2), dec!(2.9))).await;
+        assert!(history.snapshot_at("m1", "Pinnacle", kickoff).await.is_none());
+        assert_eq!(history.stats().await.keyframes, 1);
+    }
+
+    #[tokio::test]
+    async fn test_series_lists_each_bookmakers_moves_in_range() {
+        let history = OddsHistory::new();
+        let start = Utc::now();
+        for (minutes, home) in [(0, dec!(2.10)), (10, dec!(2.10)), (20, dec!(1.95)), (30, dec!(1.80))] {
+            let odds = SimpleMarketOdds::new(home, dec!(3.40), dec!(3.90));
+            history.record("m1", "Pinnacle", start + Duration::minutes(minutes), &odds).await;
+        }
+        history.record("m1", "Bet365", start + Duration::minutes(5), &SimpleMarketOdds::new(dec!(2.00), dec!(3.30), dec!(4.00))).await;
+
+        let series = history.series("m1", None, start, start + Duration::minutes(30), 100).await.unwrap();
+        assert_eq!(series.iter().map(|s| s.bookmaker.as_str()).collect::<Vec<_>>(), ["Bet365", "Pinnacle"]);
+        // Unchanged quotes aren't stored, and the end of the range is excluded
+        let pinnacle: Vec<_> = series[1].quotes.iter().map(|quote| quote.odds.home_win).collect();
+        assert_eq!(pinnacle, [dec!(2.10), dec!(1.95)]);
+
+        let first_two = history.series("m1", None, start, start + Duration::hours(1), 2).await.unwrap();
+        assert_eq!(first_two.iter().map(|s| s.quotes.len()).sum::<usize>(), 2);
+        assert!(history.series("m1", Some("William Hill"), start, start + Duration::hours(1), 100).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+// Moves the simulated markets between feed events and sends the moves down
+// the event pipeline as odds updates, so predictions and trading react to
+// line moves the way they do to match events
+
+use crate::market_simulator::MarketSimulator;
+use anyhow::Result;
+use chrono::Utc;
+use quant_stream::EventQueueSender;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OddsRefreshConfig {
+    pub enabled: bool,
+    pub interval_seconds: u64,
+}
+
+impl Default for OddsRefreshConfig {
+    fn default() -> Self {
+        Self { enabled: true, interval_seconds: 30 }
+    }
+}
+
+pub struct OddsRefresher {
+    market_simulator: Arc<MarketSimulator>,
+    events: EventQueueSender,
+    interval: Duration,
+}
+
+impl OddsRefresher {
+    pub fn new(market_simulator: Arc<MarketSimulator>, events: EventQueueSender) -> Self {
+        Self {
+            market_simulator,
+            events,
+            interval: Duration::from_secs(OddsRefreshConfig::default().interval_seconds),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval.max(Duration::from_secs(1));
+        self
+    }
+
+    /// Move every open market once; returns the updates sent. Fails once the
+    /// pipeline has stopped.
+    pub async fn refresh_once(&self) -> Result<usize> {
+        let updates = self.market_simulator.market_movements(Utc::now()).await;
+        let sent = updates.len();
+        for update in updates {
+            self.events.send(update).await?;
+        }
+        Ok(sent)
+    }
+
+    /// Refresh every `interval` until cancelled. Consumes the refresher so
+    /// its sender is dropped on return and the pipeline can close.
+    pub async fn run(self, shutdown: CancellationToken) {
+        info!("📈 Refreshing odds every {:?}", self.interval);
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
+            match self.refresh_once().await {
+                Ok(sent) => debug!("📈 Moved {} markets", sent),
+                Err(_) => break,
+            }
+        }
+        info!("📈 Odds refresh stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_simulator::MARKET_MOVE_METADATA;
+    use quant_models::{EventType, MatchEvent};
+    use quant_stream::{event_queue, QueueConfig};
+
+    #[tokio::test]
+    async fn test_moves_reach_the_pipeline() {
+        let market_simulator = Arc::new(MarketSimulator::new().with_seed(11));
+        let (sender, mut receiver) = event_queue(&QueueConfig::default(), None);
+        let refresher = OddsRefresher::new(market_simulator.clone(), sender);
+        assert_eq!(refresher.refresh_once().await.unwrap(), 0);
+
+        for match_id in ["m1", "m2"] {
+            let event = MatchEvent::new(
+                match_id.to_string(),
+                EventType::MatchStart,
+                "Arsenal".to_string(),
+                "Chelsea".to_string(),
+                "Premier League".to_string(),
+                "2024-25".to_string(),
+            );
+            market_simulator.generate_market_odds(&event).await.unwrap();
+        }
+        assert_eq!(refresher.refresh_once().await.unwrap(), 2);
+        let update = receiver.try_recv().unwrap();
+        assert_eq!(update.event_type, EventType::OddsUpdate);
+        assert_eq!(update.team_home, "Arsenal");
+        assert!(update.metadata[MARKET_MOVE_METADATA]["draw"].as_f64().unwrap() > 0.0);
+
+        // Once the pipeline is gone the refresher stops
+        drop(receiver);
+        assert!(refresher.refresh_once().await.is_err());
+    }
+}
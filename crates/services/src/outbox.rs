@@ -0,0 +1,176 @@
+use crate::trader::{MatchInfo, TradingSignal};
+use quant_db::{
+    bet_type_label, BetRecord, BetRepository, MatchRecord, OutboxRecord, OutboxRepository, Repository,
+    TradingSignalRecord, TradingSignalRepository,
+};
+use quant_models::{BettingDecision, DomainEvent};
+use quant_stream::{DomainEventMessage, RedisStream, TradeMessage};
+use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// Store an executed bet and its `bet_executed` stream message in one
+/// transaction, so the database and Redis can never disagree about it.
+pub async fn persist_executed_bet(
+    repository: &Repository,
+    stream_key: &str,
+    bet: &BettingDecision,
+    match_info: Option<&MatchInfo>,
+) -> Result<()> {
+    let match_record = match_info.map(|info| MatchRecord::new(
+        bet.match_id.clone(),
+        info.team_home.clone(),
+        info.team_away.clone(),
+        info.league.clone(),
+        info.season.clone(),
+    ));
+    let payload = serde_json::to_value(TradeMessage::BetExecuted { bet: bet.clone() })?;
+    let message = OutboxRecord::new(stream_key, payload);
+
+    repository
+        .create_bet_with_outbox(match_record.as_ref(), &BetRecord::from(bet), &message)
+        .await?;
+
+    debug!("💾 Stored bet {} with outbox message {}", bet.id, message.id);
+    Ok(())
+}
+
+/// Write bets back as the portfolio settles, half-settles or voids them, so
+/// the row stored when a bet was placed doesn't stay open. Settlements
+/// already published when `shutdown` fires are still written.
+pub async fn persist_settled_bets(
+    repository: Arc<Repository>,
+    mut events: broadcast::Receiver<DomainEventMessage>,
+    shutdown: CancellationToken,
+) {
+    loop {
+        let message = tokio::select! {
+            message = events.recv() => message,
+            _ = shutdown.cancelled() => break,
+        };
+        match message {
+            Ok(message) => store_settlement(&repository, &message.event).await,
+            Err(RecvError::Lagged(missed)) => {
+                warn!("💾 Missed {} domain events; bets settled among them stay open in the database", missed);
+            }
+            Err(RecvError::Closed) => return,
+        }
+    }
+    while let Ok(message) = events.try_recv() {
+        store_settlement(&repository, &message.event).await;
+    }
+}
+
+async fn store_settlement(repository: &Repository, event: &DomainEvent) {
+    let DomainEvent::BetSettled { bet, .. } = event else {
+        return;
+    };
+    match repository.create_bet(&BetRecord::from(bet)).await {
+        Ok(record) => debug!("💾 Stored bet {} as {}", bet.id, record.status),
+        Err(e) => warn!("💾 Failed to store the settlement of bet {}: {}", bet.id, e),
+    }
+}
+
+/// Store a generated signal for the signal log. Signals are advisory, so
+/// there is no outbox message to go with them.
+pub async fn persist_trading_signal(repository: &Repository, signal: &TradingSignal) -> Result<()> {
+    let bet = signal.recommended_bet.as_ref();
+    let record = TradingSignalRecord {
+        id: signal.id,
+        match_id: signal.match_id.clone(),
+        signal_strength: signal.signal_strength,
+        bet_type: bet.map(|bet| bet_type_label(&bet.bet_type)),
+        stake: bet.map(|bet| bet.stake),
+        odds: bet.map(|bet| bet.odds),
+        strategy: bet.map(|bet| bet.strategy.clone()),
+        risk_score: signal.risk_assessment.risk_score,
+        warnings: signal.risk_assessment.warnings.clone(),
+        reasoning: signal.reasoning.clone(),
+        generated_at: signal.generated_at,
+        created_at: chrono::Utc::now(),
+    };
+    repository.create_trading_signal(&record).await?;
+
+    debug!("💾 Stored trading signal {} for {}", signal.id, signal.match_id);
+    Ok(())
+}
+
+/// Publishes pending outbox messages to Redis streams.
+///
+/// Delivery is at-least-once: a crash between publishing and marking a row
+/// leads to a redelivery, which consumers drop using the `message_id` field.
+pub struct OutboxRelay {
+    repository: Arc<Repository>,
+    stream: Arc<RedisStream>,
+    batch_size: i64,
+    poll_interval: Duration,
+}
+
+impl OutboxRelay {
+    pub fn new(repository: Arc<Repository>, stream: Arc<RedisStream>) -> Self {
+        Self {
+            repository,
+            stream,
+            batch_size: 100,
+            poll_interval: Duration::from_millis(500),
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Publish pending messages oldest first. Stops at the first failure so
+    /// messages are never published out of order.
+    pub async fn relay_pending(&self) -> Result<usize> {
+        let pending = self.repository.get_pending_outbox(self.batch_size).await?;
+        let mut published = 0;
+
+        for message in pending {
+            let payload = message.payload.to_string();
+            match self.stream.publish(&message.stream_key, &message.id.to_string(), &payload).await {
+                Ok(_) => {
+                    self.repository.mark_outbox_published(message.id).await?;
+                    published += 1;
+                }
+                Err(e) => {
+                    warn!("📤 Failed to publish outbox message {}: {}", message.id, e);
+                    self.repository.record_outbox_failure(message.id, &e.to_string()).await?;
+                    break;
+                }
+            }
+        }
+
+        if published > 0 {
+            debug!("📤 Relayed {} outbox messages", published);
+        }
+        Ok(published)
+    }
+
+    /// Poll until cancelled, then make a final pass so nothing committed
+    /// before shutdown is left behind.
+    pub async fn run(&self, shutdown: CancellationToken) {
+        info!("📤 Outbox relay started");
+        let mut ticker = tokio::time::interval(self.poll_interval);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
+
+            if let Err(e) = self.relay_pending().await {
+                warn!("📤 Outbox relay pass failed: {}", e);
+            }
+        }
+
+        if let Err(e) = self.relay_pending().await {
+            warn!("📤 Final outbox relay pass failed: {}", e);
+        }
+        info!("📤 Outbox relay stopped");
+    }
+}
@@ -0,0 +1,133 @@
+// Each match's last prediction, reused while the state it was made from is
+// unchanged, e.g. over a run of substitutions that move no feature
+
+use dashmap::DashMap;
+use quant_models::{FeatureVector, Prediction};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PredictionCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Matches with a prediction held
+    pub entries: usize,
+    /// Share of lookups answered from the cache, 0 to 1
+    pub hit_rate: f64,
+}
+
+/// The latest prediction per match, keyed by the hash of the state it was
+/// made from. Cleared whenever the model changes.
+#[derive(Debug, Default)]
+pub struct PredictionCache {
+    entries: DashMap<String, (u64, Prediction)>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl PredictionCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The match's last prediction if it was made from `state`, as a new
+    /// prediction with an id and timestamp of its own. The id it was copied
+    /// from is kept under `metadata.cached_from`.
+    pub fn get(&self, match_id: &str, state: u64) -> Option<Prediction> {
+        let cached = self.entries.get(match_id)
+            .filter(|entry| entry.0 == state)
+            .map(|entry| entry.1.clone());
+        let Some(mut prediction) = cached else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+        self.hits.fetch_add(1, Ordering::Relaxed);
+
+        if !prediction.metadata.is_object() {
+            prediction.metadata = serde_json::json!({});
+        }
+        prediction.metadata["cached_from"] = serde_json::json!(prediction.id);
+        prediction.id = Uuid::new_v4();
+        prediction.prediction_timestamp = chrono::Utc::now();
+        Some(prediction)
+    }
+
+    pub fn insert(&self, state: u64, prediction: &Prediction) {
+        self.entries.insert(prediction.match_id.clone(), (state, prediction.clone()));
+    }
+
+    /// Forget a match, e.g. once it has finished
+    pub fn remove(&self, match_id: &str) {
+        self.entries.remove(match_id);
+    }
+
+    pub fn clear(&self) {
+        self.entries.clear();
+    }
+
+    pub fn stats(&self) -> PredictionCacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+        PredictionCacheStats {
+            hits,
+            misses,
+            entries: self.entries.len(),
+            hit_rate: if lookups > 0 { hits as f64 / lookups as f64 } else { 0.0 },
+        }
+    }
+}
+
+/// Hash of everything a prediction is made from: the feature values, the
+/// feature set they were built for, and whether the in-play model makes it
+pub fn state_hash(features: &FeatureVector, in_play: bool) -> u64 {
+    let mut values: Vec<(&String, &f64)> = features.features.iter().collect();
+    values.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut hasher = DefaultHasher::new();
+    for (name, value) in values {
+        name.hash(&mut hasher);
+        value.to_bits().hash(&mut hasher);
+    }
+    features.schema.hash(&mut hasher);
+    in_play.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_reused_only_for_the_same_state() {
+        let features = |minute: f64| FeatureVector {
+            match_id: "m1".to_string(),
+            features: HashMap::from([("minute".to_string(), minute), ("momentum".to_string(), 0.2)]),
+            timestamp: chrono::Utc::now(),
+            schema: None,
+        };
+        let state = state_hash(&features(10.0), true);
+        assert_eq!(state, state_hash(&features(10.0), true));
+        assert_ne!(state, state_hash(&features(11.0), true));
+        assert_ne!(state, state_hash(&features(10.0), false));
+
+        let cache = PredictionCache::new();
+        assert!(cache.get("m1", state).is_none());
+        let prediction = Prediction::new("m1".to_string(), "test".to_string(), "1".to_string(), 0.5, 0.2, chrono::Utc::now()).unwrap();
+        cache.insert(state, &prediction);
+
+        let reused = cache.get("m1", state).unwrap();
+        assert_ne!(reused.id, prediction.id);
+        assert_eq!(reused.metadata["cached_from"], serde_json::json!(prediction.id));
+        assert_eq!(reused.home_win_prob, prediction.home_win_prob);
+        assert!(cache.get("m1", state_hash(&features(11.0), true)).is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 2, 1));
+        assert!((stats.hit_rate - 1.0 / 3.0).abs() < 1e-9);
+    }
+}
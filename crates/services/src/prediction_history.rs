@@ -0,0 +1,186 @@
+use quant_models::Prediction;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::sync::{broadcast, RwLock};
+use uuid::Uuid;
+
+const DEFAULT_MAX_MATCHES: usize = 500;
+/// A prediction per event comfortably fits a match under this
+const DEFAULT_MAX_POINTS: usize = 1_000;
+/// Deltas a slow subscriber can fall behind by before it misses some
+const CHANNEL_CAPACITY: usize = 256;
+
+/// One prediction in a match's series
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PredictionPoint {
+    pub prediction_id: Uuid,
+    pub model_name: String,
+    pub predicted_at: DateTime<Utc>,
+    /// Match minute when the prediction was made, if known
+    pub minute: Option<u8>,
+    pub home_win_prob: f64,
+    pub draw_prob: Option<f64>,
+    pub away_win_prob: f64,
+    pub confidence: f64,
+    pub expected_goals_home: Option<f64>,
+    pub expected_goals_away: Option<f64>,
+}
+
+impl PredictionPoint {
+    fn new(prediction: &Prediction, minute: Option<u8>) -> Self {
+        Self {
+            prediction_id: prediction.id,
+            model_name: prediction.model_name.clone(),
+            predicted_at: prediction.prediction_timestamp,
+            minute,
+            home_win_prob: prediction.home_win_prob,
+            draw_prob: prediction.draw_prob,
+            away_win_prob: prediction.away_win_prob,
+            confidence: prediction.confidence,
+            expected_goals_home: prediction.expected_goals_home,
+            expected_goals_away: prediction.expected_goals_away,
+        }
+    }
+}
+
+/// A new point and how far each probability moved since the match's last one
+#[derive(Debug, Clone, Serialize)]
+pub struct PredictionDelta {
+    pub match_id: String,
+    pub point: PredictionPoint,
+    /// Zero for a match's first prediction
+    pub home_win_change: f64,
+    pub draw_change: f64,
+    pub away_win_change: f64,
+}
+
+#[derive(Debug, Default)]
+struct MatchPredictions {
+    points: Vec<PredictionPoint>,
+    last_updated: Option<DateTime<Utc>>,
+}
+
+/// Every prediction made during each match, in order, so probabilities can
+/// be charted over the match. New points are also broadcast as deltas.
+pub struct PredictionHistory {
+    matches: RwLock<HashMap<String, MatchPredictions>>,
+    max_matches: usize,
+    max_points: usize,
+    updates: broadcast::Sender<PredictionDelta>,
+}
+
+impl PredictionHistory {
+    pub fn new() -> Self {
+        let (updates, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            matches: RwLock::new(HashMap::new()),
+            max_matches: DEFAULT_MAX_MATCHES,
+            max_points: DEFAULT_MAX_POINTS,
+            updates,
+        }
+    }
+
+    /// Keep at most this many matches, dropping the least recently updated
+    pub fn with_max_matches(mut self, max_matches: usize) -> Self {
+        self.max_matches = max_matches.max(1);
+        self
+    }
+
+    /// Keep at most this many points per match, dropping the oldest
+    pub fn with_max_points(mut self, max_points: usize) -> Self {
+        self.max_points = max_points.max(1);
+        self
+    }
+
+    /// Append a prediction to its match's series and broadcast the delta
+    pub async fn record(&self, prediction: &Prediction, minute: Option<u8>) -> PredictionDelta {
+        let mut matches = self.matches.write().await;
+
+        if !matches.contains_key(&prediction.match_id) && matches.len() >= self.max_matches {
+            let oldest = matches.iter()
+                .min_by_key(|(_, history)| history.last_updated)
+                .map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                matches.remove(&oldest);
+            }
+        }
+
+        let history = matches.entry(prediction.match_id.clone()).or_default();
+        let point = PredictionPoint::new(prediction, minute);
+        let change = |now: f64, before: Option<f64>| before.map_or(0.0, |before| now - before);
+        let previous = history.points.last();
+        let delta = PredictionDelta {
+            match_id: prediction.match_id.clone(),
+            home_win_change: change(point.home_win_prob, previous.map(|p| p.home_win_prob)),
+            draw_change: change(point.draw_prob.unwrap_or(0.0), previous.map(|p| p.draw_prob.unwrap_or(0.0))),
+            away_win_change: change(point.away_win_prob, previous.map(|p| p.away_win_prob)),
+            point: point.clone(),
+        };
+
+        history.points.push(point);
+        if history.points.len() > self.max_points {
+            let excess = history.points.len() - self.max_points;
+            history.points.drain(..excess);
+        }
+        history.last_updated = Some(Utc::now());
+
+        // No subscribers is fine
+        let _ = self.updates.send(delta.clone());
+        delta
+    }
+
+    /// The match's predictions, oldest first
+    pub async fn get(&self, match_id: &str) -> Option<Vec<PredictionPoint>> {
+        self.matches.read().await.get(match_id).map(|history| history.points.clone())
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<PredictionDelta> {
+        self.updates.subscribe()
+    }
+
+    pub async fn match_count(&self) -> usize {
+        self.matches.read().await.len()
+    }
+}
+
+impl Default for PredictionHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prediction(match_id: &str, home: f64, away: f64) -> Prediction {
+        Prediction::new(match_id.to_string(), "test".to_string(), "1.0".to_string(), home, away, Utc::now())
+            .unwrap()
+            .with_draw_prob(1.0 - home - away)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_series_kept_per_match_and_deltas_broadcast() {
+        let history = PredictionHistory::new().with_max_matches(2).with_max_points(2);
+        let mut updates = history.subscribe();
+
+        history.record(&prediction("m1", 0.5, 0.2), Some(0)).await;
+        history.record(&prediction("m1", 0.6, 0.2), Some(30)).await;
+        history.record(&prediction("m1", 0.7, 0.1), Some(60)).await;
+
+        let points = history.get("m1").await.unwrap();
+        assert_eq!(points.iter().map(|p| p.minute).collect::<Vec<_>>(), [Some(30), Some(60)]);
+
+        assert_eq!(updates.recv().await.unwrap().home_win_change, 0.0);
+        let delta = updates.recv().await.unwrap();
+        assert!((delta.home_win_change - 0.1).abs() < 1e-9);
+        assert!((delta.draw_change + 0.1).abs() < 1e-9);
+
+        history.record(&prediction("m2", 0.4, 0.3), None).await;
+        history.record(&prediction("m3", 0.4, 0.3), None).await;
+        assert_eq!(history.match_count().await, 2);
+        assert!(history.get("m1").await.is_none());
+    }
+}
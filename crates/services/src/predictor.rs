@@ -1,5 +1,13 @@
-use quant_models::{Prediction, MatchEvent};
-use quant_ml::{FeatureEngineer, Model, EnsembleModel};
+use crate::prediction_cache::{state_hash, PredictionCache};
+use crate::shadow::ShadowModel;
+use quant_models::{Prediction, MatchEvent, FeatureVector, DomainEvent, SimpleMarketOdds};
+use quant_ml::{
+    DixonColesFit, FeatureEngineer, InPlayModel, LogisticRegressionModel, Model, EnsembleModel, EnsembleWeights,
+    ModelArtifact, ModelFeedback, ModelState, DriftMonitor, DriftReport, DriftThresholds, MatchResult,
+};
+#[cfg(feature = "batched-inference")]
+use quant_ml::{BatchInferenceConfig, InferenceExecutor, InferenceQueueStats};
+use quant_stream::DomainEventBus;
 use anyhow::Result;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -7,7 +15,28 @@ use tokio::sync::RwLock;
 pub struct PredictorService {
     feature_engineer: Arc<FeatureEngineer>,
     model: Arc<RwLock<Model>>,
+    /// Predicts matches under way instead of `model`, when enabled
+    in_play: Option<Arc<Model>>,
+    /// Predicts alongside `model` for comparison, never for trading
+    challenger: Option<Arc<ShadowModel>>,
     prediction_count: Arc<RwLock<u64>>,
+    #[cfg(feature = "batched-inference")]
+    inference: Option<Arc<InferenceExecutor>>,
+    /// Where installed models are announced
+    events: Option<DomainEventBus>,
+    drift: Arc<RwLock<Option<DriftTracking>>>,
+    drift_thresholds: DriftThresholds,
+    /// Each match's last prediction, reused while its features are unchanged
+    cache: Arc<PredictionCache>,
+}
+
+/// Live pre-match inputs, and the installed artifact's predictions from
+/// them, against the artifact's training set
+struct DriftTracking {
+    monitor: DriftMonitor,
+    /// The artifact as trained, so online updates and ensembling don't
+    /// count as drift
+    model: LogisticRegressionModel,
 }
 
 impl PredictorService {
@@ -18,11 +47,108 @@ impl PredictorService {
         Self {
             feature_engineer,
             model: Arc::new(RwLock::new(model)),
+            in_play: Some(Arc::new(Model::InPlay(InPlayModel::new()))),
+            challenger: None,
             prediction_count: Arc::new(RwLock::new(0)),
+            #[cfg(feature = "batched-inference")]
+            inference: None,
+            events: None,
+            drift: Arc::new(RwLock::new(None)),
+            drift_thresholds: DriftThresholds::default(),
+            cache: Arc::new(PredictionCache::new()),
+        }
+    }
+    
+    /// Predict from this feature engineer instead of a default one
+    pub fn with_feature_engineer(mut self, feature_engineer: FeatureEngineer) -> Self {
+        self.feature_engineer = Arc::new(feature_engineer);
+        self
+    }
+    
+    /// A predictor sharing this one's model and team ratings but with match
+    /// contexts and team stats of its own, so events can be replayed through
+    /// it without touching live state
+    pub fn sandbox(&self) -> Self {
+        let feature_engineer = FeatureEngineer::new()
+            .with_elo_ratings(self.feature_engineer.elo_ratings())
+            .with_standings(self.feature_engineer.standings())
+            .with_pipeline(self.feature_engineer.pipeline().clone());
+        if let Some(fit) = self.feature_engineer.dixon_coles() {
+            feature_engineer.set_dixon_coles(fit);
         }
+
+        Self {
+            feature_engineer: Arc::new(feature_engineer),
+            model: self.model.clone(),
+            in_play: self.in_play.clone(),
+            challenger: None,
+            prediction_count: Arc::new(RwLock::new(0)),
+            #[cfg(feature = "batched-inference")]
+            inference: None,
+            events: None,
+            drift: Arc::new(RwLock::new(None)),
+            drift_thresholds: DriftThresholds::default(),
+            cache: Arc::new(PredictionCache::new()),
+        }
+    }
+    
+    /// Whether live and half-time events are predicted by the in-play model
+    /// rather than the pre-match one
+    pub fn with_in_play(mut self, enabled: bool) -> Self {
+        self.in_play = enabled.then(|| Arc::new(Model::InPlay(InPlayModel::new())));
+        self
+    }
+    
+    /// Run `challenger` in shadow: it predicts whatever the pre-match model
+    /// predicts, and is scored against it
+    pub fn with_challenger(mut self, challenger: Model) -> Self {
+        self.challenger = Some(Arc::new(ShadowModel::new(challenger)));
+        self
+    }
+
+    /// Shadow a logistic regression loaded from an offline-trained artifact.
+    /// Fails if it was trained on a different feature set.
+    pub fn with_challenger_artifact(self, artifact: &ModelArtifact) -> Result<Self> {
+        self.check_schema(artifact)?;
+        Ok(self.with_challenger(Model::LogisticRegression(LogisticRegressionModel::from_artifact(artifact))))
+    }
+
+    pub fn challenger(&self) -> Option<Arc<ShadowModel>> {
+        self.challenger.clone()
+    }
+    
+    /// Windows and limits for drift from an installed artifact's training set
+    pub fn with_drift_thresholds(mut self, thresholds: DriftThresholds) -> Self {
+        self.drift_thresholds = thresholds;
+        self
+    }
+
+    pub fn with_event_bus(mut self, events: DomainEventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+    
+    /// Route predictions through a dedicated inference thread pool instead of
+    /// running the model inline on the async runtime.
+    #[cfg(feature = "batched-inference")]
+    pub fn with_batched_inference(mut self, config: BatchInferenceConfig) -> Result<Self> {
+        let executor = InferenceExecutor::new(self.model.clone(), config)?;
+        self.inference = Some(Arc::new(executor));
+        Ok(self)
+    }
+    
+    #[cfg(feature = "batched-inference")]
+    pub fn inference_stats(&self) -> Option<InferenceQueueStats> {
+        self.inference.as_ref().map(|executor| executor.stats())
     }
     
     pub async fn predict(&self, event: &MatchEvent) -> Result<Prediction> {
+        self.predict_with_features(event).await.map(|(prediction, _)| prediction)
+    }
+    
+    /// Predict and also hand back the feature vector the model saw, so it can
+    /// be stored as a training example
+    pub async fn predict_with_features(&self, event: &MatchEvent) -> Result<(Prediction, FeatureVector)> {
         // Extract features from the event
         let features = self.feature_engineer.extract_features(event).await?;
         
@@ -30,9 +156,29 @@ impl PredictorService {
                        features.features.len(), 
                        event.match_id);
         
-        // Generate prediction using the ML model
-        let model = self.model.read().await;
-        let prediction = model.predict(&features).await?;
+        // Nothing the prediction depends on has moved since the last one,
+        // e.g. a substitution, so the model need not run again
+        let in_play = self.in_play.is_some() && event.is_live();
+        let state = state_hash(&features, in_play);
+        let prediction = match self.cache.get(&event.match_id, state) {
+            Some(prediction) => {
+                tracing::debug!("🧠 Reusing the last prediction for {}, its features are unchanged", event.match_id);
+                prediction
+            }
+            // Generate prediction using the ML model, or from the score and
+            // clock once the match is under way
+            None => {
+                let prediction = match &self.in_play {
+                    Some(in_play) if event.is_live() => in_play.predict(&features).await?,
+                    _ => {
+                        self.track_drift(&features).await;
+                        self.run_model(&features).await?
+                    }
+                };
+                self.cache.insert(state, &prediction);
+                prediction
+            }
+        };
         
         // Update prediction count
         let mut count = self.prediction_count.write().await;
@@ -55,7 +201,151 @@ impl PredictorService {
                            features.features.get("intensity").unwrap_or(&0.0));
         }
         
-        Ok(prediction)
+        Ok((prediction, features))
+    }
+    
+    #[cfg(not(feature = "batched-inference"))]
+    async fn run_model(&self, features: &FeatureVector) -> Result<Prediction> {
+        let model = self.model.read().await;
+        model.predict(features).await
+    }
+    
+    #[cfg(feature = "batched-inference")]
+    async fn run_model(&self, features: &FeatureVector) -> Result<Prediction> {
+        if let Some(executor) = &self.inference {
+            return executor.predict(features.clone()).await;
+        }
+        let model = self.model.read().await;
+        model.predict(features).await
+    }
+    
+    async fn track_drift(&self, features: &FeatureVector) {
+        let mut drift = self.drift.write().await;
+        let Some(tracking) = drift.as_mut() else { return };
+        tracking.monitor.observe_features(features);
+        match tracking.model.predict(features).await {
+            Ok(prediction) => tracking.monitor.observe_prediction(&prediction),
+            Err(e) => tracing::debug!("🧠 Skipping drift tracking for {}: {}", features.match_id, e),
+        }
+    }
+
+    /// How far recent pre-match features and predictions have moved from
+    /// the installed artifact's training set, if it recorded one
+    pub async fn drift_report(&self) -> Option<DriftReport> {
+        self.drift.read().await.as_ref().map(|tracking| tracking.monitor.report())
+    }
+    
+    /// The challenger's prediction from the features the champion predicted
+    /// `event` from, or `None` without a challenger or once the in-play
+    /// model has taken over
+    pub async fn predict_challenger(
+        &self,
+        event: &MatchEvent,
+        features: &FeatureVector,
+        odds: Option<SimpleMarketOdds>,
+    ) -> Result<Option<Prediction>> {
+        let Some(challenger) = &self.challenger else {
+            return Ok(None);
+        };
+        if self.in_play.is_some() && event.is_live() {
+            return Ok(None);
+        }
+        challenger.predict(features, odds).await.map(Some)
+    }
+    
+    /// Run the model on an already extracted feature vector, skipping feature
+    /// engineering and prediction bookkeeping
+    pub async fn predict_from_features(&self, features: &FeatureVector) -> Result<Prediction> {
+        self.run_model(features).await
+    }
+    
+    /// Feed a settled outcome back into the model for an online SGD step
+    pub async fn apply_feedback(&self, feedback: &ModelFeedback) -> Result<()> {
+        let mut model = self.model.write().await;
+        model.update_weights(feedback).await?;
+        self.cache.clear();
+        Ok(())
+    }
+    
+    /// Name and version of the model making predictions
+    pub async fn active_model(&self) -> (String, String) {
+        let model = self.model.read().await;
+        (model.model_name().to_string(), model.model_version().to_string())
+    }
+    
+    /// How much the ensemble currently trusts each component
+    pub async fn ensemble_weights(&self) -> Option<EnsembleWeights> {
+        self.model.read().await.ensemble_weights()
+    }
+    
+    /// Replace the logistic weights with an offline-trained artifact. Fails
+    /// if it was trained on a different feature set than the one the
+    /// feature engineer builds.
+    pub async fn install_artifact(&self, artifact: &ModelArtifact) -> Result<()> {
+        self.check_schema(artifact)?;
+        let mut model = self.model.write().await;
+        model.install_artifact(artifact);
+        self.cache.clear();
+        // A new artifact brings its own baseline; older ones have none
+        *self.drift.write().await = artifact.drift_baseline.clone().map(|baseline| DriftTracking {
+            monitor: DriftMonitor::new(baseline, self.drift_thresholds.clone()),
+            model: LogisticRegressionModel::from_artifact(artifact),
+        });
+        tracing::info!("🧠 Installed {} {} (validation log loss {:.4})",
+                      artifact.name, artifact.version, artifact.metrics.validation_log_loss);
+        if let Some(events) = &self.events {
+            events.publish(DomainEvent::ModelPromoted {
+                name: artifact.name.clone(),
+                version: artifact.version.clone(),
+                validation_log_loss: artifact.metrics.validation_log_loss,
+            });
+        }
+        Ok(())
+    }
+    
+    fn check_schema(&self, artifact: &ModelArtifact) -> Result<()> {
+        let trained_on = artifact.schema().id();
+        let built = self.feature_engineer.pipeline().schema().id();
+        if trained_on != built {
+            anyhow::bail!("{} {} was trained on {} features, but the feature pipeline builds {}",
+                          artifact.name, artifact.version, trained_on, built);
+        }
+        Ok(())
+    }
+    
+    /// Online-learned weights of the model making predictions
+    pub async fn model_state(&self) -> ModelState {
+        self.model.read().await.state()
+    }
+    
+    /// The live logistic weights, or the ensemble's logistic component's, as
+    /// a model of their own: what an installed artifact would replace. Later
+    /// online updates don't reach it.
+    pub async fn logistic_snapshot(&self) -> Result<Option<Model>> {
+        let Some(weights) = self.model_state().await.logistic else {
+            return Ok(None);
+        };
+        let model = LogisticRegressionModel::new().with_schema(self.feature_engineer.pipeline().schema().clone());
+        model.set_logistic_weights(&weights)?;
+        Ok(Some(Model::LogisticRegression(model)))
+    }
+    
+    /// Carry on from weights saved by an earlier run of the same model
+    pub async fn restore_model_state(&self, state: &ModelState) -> Result<()> {
+        self.model.write().await.restore_state(state)?;
+        self.cache.clear();
+        tracing::info!("🧠 Restored learned weights of {} {}", state.name, state.version);
+        Ok(())
+    }
+    
+    /// Price goals from fitted team strengths and their low-score correlation
+    pub async fn install_dixon_coles(&self, fit: DixonColesFit) {
+        if let Some(poisson) = self.model.read().await.poisson_model() {
+            poisson.set_rho(fit.rho);
+        }
+        tracing::info!("🧠 Installed Dixon-Coles strengths for {} teams (rho {:.3})", fit.teams.len(), fit.rho);
+        self.feature_engineer.set_dixon_coles(fit);
+        self.cache.clear();
     }
     
     pub async fn update_team_performance(&self, team: &str, goals_for: u32, goals_against: u32) {
@@ -63,6 +353,32 @@ impl PredictorService {
         tracing::debug!("📈 Updated team stats for {}: GF={}, GA={}", team, goals_for, goals_against);
     }
     
+    /// Fold a finished match into both teams' stats, Elo ratings and its
+    /// league table; returns the rating points the home side gained
+    pub async fn record_result(&self, event: &MatchEvent, home_goals: u8, away_goals: u8) -> f64 {
+        self.update_team_performance(&event.team_home, home_goals.into(), away_goals.into()).await;
+        self.update_team_performance(&event.team_away, away_goals.into(), home_goals.into()).await;
+        let change = self.feature_engineer.elo_ratings()
+            .record_result(&event.team_home, &event.team_away, home_goals, away_goals, &event.season);
+        self.feature_engineer.standings()
+            .record_result(&event.league, &event.season, &event.team_home, &event.team_away, home_goals, away_goals);
+        self.cache.remove(&event.match_id);
+        tracing::debug!("📈 Elo: {} {:+.1}, {} {:+.1}", event.team_home, change, event.team_away, -change);
+        change
+    }
+    
+    /// Seed team form and strengths, and Elo ratings if none are loaded,
+    /// from each team's last `per_team` stored results
+    pub fn warm_start(&self, results: &[MatchResult], per_team: usize) {
+        let teams = self.feature_engineer.warm_start(results, per_team);
+        tracing::info!("📈 Warmed up form for {} teams from {} stored results", teams, results.len());
+    }
+    
+    /// Predictions reused per match state, with their hit rate
+    pub fn prediction_cache(&self) -> Arc<PredictionCache> {
+        self.cache.clone()
+    }
+    
     pub async fn get_prediction_count(&self) -> u64 {
         *self.prediction_count.read().await
     }
@@ -0,0 +1,144 @@
+// What a run was started from, so its outputs can be traced back to the
+// exact code, configuration, models and data it ran on
+
+use chrono::{DateTime, Utc};
+use quant_models::FeatureSchemaId;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use tracing::info;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelProvenance {
+    pub name: String,
+    pub version: String,
+    /// The artifact file the weights were loaded from; `None` for built-in weights
+    pub artifact: Option<String>,
+    /// SHA-256 of that file
+    pub sha256: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Provenance {
+    pub package_version: String,
+    pub git_commit: Option<String>,
+    pub started_at: DateTime<Utc>,
+    /// Configuration files read, in the order they were layered, then
+    /// `environment` for overrides from environment variables
+    pub config_sources: Vec<String>,
+    pub models: Vec<ModelProvenance>,
+    pub feature_schema: Option<FeatureSchemaId>,
+    /// Latest database migration applied; `None` without a database
+    pub migration_level: Option<i64>,
+    pub simulation_seed: Option<u64>,
+}
+
+impl Provenance {
+    pub fn new(package_version: impl Into<String>) -> Self {
+        Self {
+            package_version: package_version.into(),
+            git_commit: None,
+            started_at: Utc::now(),
+            config_sources: Vec::new(),
+            models: Vec::new(),
+            feature_schema: None,
+            migration_level: None,
+            simulation_seed: None,
+        }
+    }
+
+    pub fn with_git_commit(mut self, commit: Option<String>) -> Self {
+        self.git_commit = commit;
+        self
+    }
+
+    pub fn with_config_sources(mut self, sources: Vec<String>) -> Self {
+        self.config_sources = sources;
+        self
+    }
+
+    pub fn with_model(mut self, model: ModelProvenance) -> Self {
+        self.models.push(model);
+        self
+    }
+
+    pub fn with_feature_schema(mut self, schema: FeatureSchemaId) -> Self {
+        self.feature_schema = Some(schema);
+        self
+    }
+
+    pub fn with_migration_level(mut self, level: Option<i64>) -> Self {
+        self.migration_level = level;
+        self
+    }
+
+    pub fn with_simulation_seed(mut self, seed: u64) -> Self {
+        self.simulation_seed = Some(seed);
+        self
+    }
+
+    /// Write the startup banner
+    pub fn log(&self) {
+        let unknown = || "unknown".to_string();
+        info!("🧾 Provenance: v{} at commit {}", self.package_version, self.git_commit.clone().unwrap_or_else(unknown));
+        info!("🧾 Config sources: {}", self.config_sources.join(", "));
+        for model in &self.models {
+            match (&model.artifact, &model.sha256) {
+                (Some(artifact), Some(sha256)) => {
+                    info!("🧾 Model {} {} from {} (sha256 {})", model.name, model.version, artifact, sha256)
+                }
+                _ => info!("🧾 Model {} {} with built-in weights", model.name, model.version),
+            }
+        }
+        info!("🧾 Feature schema {}, migration level {}, simulation seed {}",
+              self.feature_schema.as_ref().map_or_else(unknown, ToString::to_string),
+              self.migration_level.map_or_else(unknown, |level| level.to_string()),
+              self.simulation_seed.map_or_else(unknown, |seed| seed.to_string()));
+    }
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The commit checked out in the git repository at `dir`, read straight
+/// from `.git` so no git binary is needed
+pub fn git_commit(dir: &Path) -> Option<String> {
+    let git = dir.join(".git");
+    let head = std::fs::read_to_string(git.join("HEAD")).ok()?;
+    let Some(reference) = head.trim().strip_prefix("ref: ") else {
+        // Detached
+        return Some(head.trim().to_string());
+    };
+    if let Ok(commit) = std::fs::read_to_string(git.join(reference)) {
+        return Some(commit.trim().to_string());
+    }
+    // After `git gc` refs live in one file, as `<commit> <ref>` lines
+    std::fs::read_to_string(git.join("packed-refs")).ok()?
+        .lines()
+        .find_map(|line| {
+            let (commit, name) = line.split_once(' ')?;
+            (name == reference).then(|| commit.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_git_commit_read_from_loose_and_packed_refs() {
+        let dir = std::env::temp_dir().join(format!("provenance-{}", uuid::Uuid::new_v4()));
+        let git = dir.join(".git");
+        std::fs::create_dir_all(git.join("refs/heads")).unwrap();
+        std::fs::write(git.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        std::fs::write(git.join("packed-refs"), "# pack-refs with: peeled\nabc123 refs/heads/main\n").unwrap();
+        assert_eq!(git_commit(&dir).as_deref(), Some("abc123"));
+
+        std::fs::write(git.join("refs/heads/main"), "def456\n").unwrap();
+        assert_eq!(git_commit(&dir).as_deref(), Some("def456"));
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(sha256_hex(b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+}
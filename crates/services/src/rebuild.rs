@@ -0,0 +1,275 @@
+// Rebuilds derived state from scratch by replaying the stored event log,
+// e.g. after a bug has left match contexts or team stats wrong
+
+use crate::data_feed::FeedControl;
+use crate::match_state::{MatchCorrection, MatchStateMachine};
+use crate::match_summary::MatchSummaryTracker;
+use quant_db::{EventRepository, Repository};
+use quant_ml::FeatureEngineer;
+use quant_models::{EventType, MatchEvent};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, error, info, warn};
+
+/// Events read from the log per query
+const DEFAULT_BATCH_SIZE: i64 = 500;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RebuildState {
+    #[default]
+    Idle,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RebuildProgress {
+    pub state: RebuildState,
+    /// Replayable events in the log when the rebuild started
+    pub events_total: u64,
+    pub events_processed: u64,
+    /// Events the match state machine turned down, or that no longer parse
+    pub events_rejected: u64,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+}
+
+/// Resets the feature engineer's match contexts and team stats, the match
+/// state machine and in-play match timelines, then replays every logged
+/// event through them the way the live pipeline does. Predictions, trades
+/// and finished match summaries are left alone.
+#[derive(Clone)]
+pub struct StateRebuilder {
+    repository: Option<Arc<Repository>>,
+    feature_engineer: Arc<FeatureEngineer>,
+    match_states: Arc<MatchStateMachine>,
+    match_summaries: Arc<MatchSummaryTracker>,
+    feed_control: Option<FeedControl>,
+    batch_size: i64,
+    progress: Arc<RwLock<RebuildProgress>>,
+}
+
+impl StateRebuilder {
+    pub fn new(
+        feature_engineer: Arc<FeatureEngineer>,
+        match_states: Arc<MatchStateMachine>,
+        match_summaries: Arc<MatchSummaryTracker>,
+    ) -> Self {
+        Self {
+            repository: None,
+            feature_engineer,
+            match_states,
+            match_summaries,
+            feed_control: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            progress: Arc::new(RwLock::new(RebuildProgress::default())),
+        }
+    }
+
+    /// Where the event log is read from; without one there is nothing to rebuild from
+    pub fn with_repository(mut self, repository: Arc<Repository>) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    /// Pause this feed while rebuilding, so live events don't interleave with the replay
+    pub fn with_feed_control(mut self, feed_control: FeedControl) -> Self {
+        self.feed_control = Some(feed_control);
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: i64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn has_event_log(&self) -> bool {
+        self.repository.is_some()
+    }
+
+    pub async fn progress(&self) -> RebuildProgress {
+        self.progress.read().await.clone()
+    }
+
+    /// Start a rebuild in the background and return its initial progress.
+    /// Fails if there is no event log or a rebuild is already running.
+    pub async fn start(&self) -> Result<RebuildProgress> {
+        let repository = self.repository.clone()
+            .ok_or_else(|| anyhow!("no database, so no event log to rebuild from"))?;
+
+        let started = {
+            let mut progress = self.progress.write().await;
+            if progress.state == RebuildState::Running {
+                return Err(anyhow!("a rebuild is already running"));
+            }
+            *progress = RebuildProgress {
+                state: RebuildState::Running,
+                started_at: Some(Utc::now()),
+                ..Default::default()
+            };
+            progress.clone()
+        };
+
+        let rebuilder = self.clone();
+        tokio::spawn(async move { rebuilder.run(&repository).await });
+        Ok(started)
+    }
+
+    async fn run(&self, repository: &Repository) {
+        info!("🔁 Rebuilding derived state from the event log");
+        let paused_here = match &self.feed_control {
+            Some(control) if !control.is_paused() => {
+                control.pause();
+                true
+            }
+            _ => false,
+        };
+
+        let result = self.replay_log(repository).await;
+        if paused_here {
+            if let Some(control) = &self.feed_control {
+                control.resume();
+            }
+        }
+
+        let mut progress = self.progress.write().await;
+        progress.finished_at = Some(Utc::now());
+        match result {
+            Ok(()) => {
+                progress.state = RebuildState::Completed;
+                info!("🔁 Rebuild complete: {} events replayed, {} rejected",
+                      progress.events_processed - progress.events_rejected, progress.events_rejected);
+            }
+            Err(e) => {
+                progress.state = RebuildState::Failed;
+                progress.error = Some(e.to_string());
+                error!("🔁 Rebuild failed after {} events: {}", progress.events_processed, e);
+            }
+        }
+    }
+
+    async fn replay_log(&self, repository: &Repository) -> Result<()> {
+        let total = repository.count_events().await?;
+        self.progress.write().await.events_total = total.max(0) as u64;
+        self.reset().await;
+
+        let mut offset = 0;
+        loop {
+            let batch = repository.get_events(offset, self.batch_size).await?;
+            if batch.is_empty() {
+                return Ok(());
+            }
+            offset += batch.len() as i64;
+
+            let mut rejected = 0;
+            for record in &batch {
+                let replayed = match record.to_match_event() {
+                    Some(event) => self.replay(&event).await,
+                    None => false,
+                };
+                if !replayed {
+                    rejected += 1;
+                }
+            }
+
+            let mut progress = self.progress.write().await;
+            progress.events_processed += batch.len() as u64;
+            progress.events_rejected += rejected;
+            debug!("🔁 Replayed {}/{} events", progress.events_processed, progress.events_total);
+        }
+    }
+
+    /// Forget everything the replay will rebuild
+    pub async fn reset(&self) {
+        self.feature_engineer.reset();
+        self.match_states.reset();
+        self.match_summaries.reset_live().await;
+    }
+
+    /// Apply one event to the derived state as the live pipeline does.
+    /// Returns false if the match state machine rejects it.
+    pub async fn replay(&self, event: &MatchEvent) -> bool {
+        match self.match_states.apply(event) {
+            Ok(corrections) => {
+                for correction in corrections {
+                    if let MatchCorrection::Score { home, away, .. } = correction {
+                        self.feature_engineer.correct_score(&event.match_id, home, away);
+                    }
+                }
+            }
+            Err(e) => {
+                debug!("🔁 Skipping {:?} for {}: {}", event.event_type, event.match_id, e);
+                return false;
+            }
+        }
+
+        self.match_summaries.record_event(event).await;
+        match event.event_type {
+            // Its summary was made when the match first finished
+            EventType::MatchEnd => self.match_summaries.discard(&event.match_id).await,
+            // Live, full time settles bets and is never priced
+            EventType::FullTime => {}
+            _ => {
+                if let Err(e) = self.feature_engineer.extract_features(event).await {
+                    warn!("🔁 Failed to replay {:?} for {}: {}", event.event_type, event.match_id, e);
+                }
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_models::Score;
+
+    fn event(event_type: EventType) -> MatchEvent {
+        MatchEvent::new(
+            "m1".to_string(),
+            event_type,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        )
+    }
+
+    fn goal(minute: u8, home: u8, away: u8) -> MatchEvent {
+        event(EventType::Goal { team: "Arsenal".to_string(), player: None, minute })
+            .with_score(Score { home, away, half_time_home: None, half_time_away: None })
+    }
+
+    #[tokio::test]
+    async fn test_reset_and_replay_rebuilds_match_context() {
+        let feature_engineer = Arc::new(FeatureEngineer::new());
+        let rebuilder = StateRebuilder::new(
+            feature_engineer.clone(),
+            Arc::new(MatchStateMachine::new()),
+            Arc::new(MatchSummaryTracker::new()),
+        );
+        // No database, no event log
+        assert!(rebuilder.start().await.is_err());
+
+        // Derived state a bug left wrong
+        assert!(rebuilder.replay(&event(EventType::MatchStart)).await);
+        feature_engineer.correct_score("m1", 5, 5);
+
+        rebuilder.reset().await;
+        assert!(feature_engineer.get_match_context("m1").is_none());
+        assert!(!rebuilder.replay(&goal(10, 1, 0)).await);
+        for replayed in [event(EventType::MatchStart), goal(10, 1, 0), goal(30, 2, 0)] {
+            assert!(rebuilder.replay(&replayed).await);
+        }
+
+        let context = feature_engineer.get_match_context("m1").unwrap();
+        assert_eq!((context.home_score, context.away_score), (2, 0));
+        assert_eq!(rebuilder.progress().await.state, RebuildState::Idle);
+    }
+}
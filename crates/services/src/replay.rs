@@ -0,0 +1,238 @@
+// Replays one match's events through a sandboxed predictor and trading
+// engine, to debug why a bet was or wasn't made
+
+use crate::audit::MemoryAuditLog;
+use crate::market_simulator::MarketSimulator;
+use crate::match_state::{MatchCorrection, MatchStateMachine};
+use crate::odds_history::OddsHistory;
+use crate::predictor::PredictorService;
+use crate::trader::{TradingEngine, TradingSignal, MARKET_FEED_BOOKMAKER, MIN_EXECUTION_STRENGTH};
+use quant_db::{EventRepository, Repository};
+use quant_models::{AuditEntry, BettingDecision, EventType, MatchEvent, MatchStatus, Prediction, SimpleMarketOdds};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+/// What one event did in the sandbox
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayStep {
+    pub event_id: Uuid,
+    pub event_type: EventType,
+    pub timestamp: DateTime<Utc>,
+    /// Why the match state machine turned the event down, if it did
+    pub rejected: Option<String>,
+    /// Prices the signal was made against
+    pub odds: Option<SimpleMarketOdds>,
+    /// False when no prices were recorded then and simulated ones were used
+    pub odds_recorded: bool,
+    pub prediction: Option<Prediction>,
+    pub signal: Option<TradingSignal>,
+    pub executed: bool,
+    /// Risk decisions and executions, in the order they were made
+    pub audit: Vec<AuditEntry>,
+    /// A stage that failed, e.g. feature extraction
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayReport {
+    pub match_id: String,
+    pub starting_bankroll: Decimal,
+    pub events_replayed: usize,
+    pub steps: Vec<ReplayStep>,
+    /// Bets the sandbox placed, settled if the match finished
+    pub bets: Vec<BettingDecision>,
+    pub profit_loss: Decimal,
+}
+
+/// Each replay starts from a blank match state, fresh match contexts and an
+/// empty portfolio; only the model and team ratings are shared with the live
+/// pipeline, and nothing the replay does reaches it.
+pub struct EventReplayer {
+    repository: Option<Arc<Repository>>,
+    predictor: Arc<PredictorService>,
+    odds_history: Arc<OddsHistory>,
+}
+
+impl EventReplayer {
+    pub fn new(predictor: Arc<PredictorService>, odds_history: Arc<OddsHistory>) -> Self {
+        Self { repository: None, predictor, odds_history }
+    }
+
+    /// Where stored events are read from
+    pub fn with_repository(mut self, repository: Arc<Repository>) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    pub fn has_event_log(&self) -> bool {
+        self.repository.is_some()
+    }
+
+    /// The match's events from the event log, oldest first
+    pub async fn stored_events(&self, match_id: &str) -> Result<Vec<MatchEvent>> {
+        let Some(repository) = &self.repository else {
+            return Ok(Vec::new());
+        };
+        let records = repository.get_match_events(match_id).await?;
+        Ok(records.iter().filter_map(|record| record.to_match_event()).collect())
+    }
+
+    /// Replay `events` as the live pipeline would have processed them,
+    /// trading from a sandbox portfolio of `bankroll`
+    pub async fn replay(&self, match_id: &str, events: &[MatchEvent], bankroll: Decimal) -> ReplayReport {
+        let predictor = self.predictor.sandbox();
+        let audit = Arc::new(MemoryAuditLog::new());
+        let engine = TradingEngine::new(bankroll).with_audit_log(audit.clone());
+        let match_states = MatchStateMachine::new();
+        let simulator = MarketSimulator::new();
+
+        let events: Vec<&MatchEvent> = events.iter().filter(|event| event.match_id == match_id).collect();
+        info!("⏪ Replaying {} events for {}", events.len(), match_id);
+
+        let mut steps = Vec::with_capacity(events.len());
+        for event in &events {
+            let audited = audit.entries().await.len();
+            let mut step = ReplayStep {
+                event_id: event.id,
+                event_type: event.event_type.clone(),
+                timestamp: event.timestamp,
+                rejected: None,
+                odds: None,
+                odds_recorded: false,
+                prediction: None,
+                signal: None,
+                executed: false,
+                audit: Vec::new(),
+                error: None,
+            };
+
+            match match_states.apply(event) {
+                Ok(corrections) => {
+                    for correction in corrections {
+                        if let MatchCorrection::Score { home, away, .. } = correction {
+                            predictor.get_feature_engineer().correct_score(&event.match_id, home, away);
+                        }
+                    }
+                    engine.register_match(event).await;
+                    if let Err(e) = self.replay_event(&predictor, &engine, &simulator, event, &mut step).await {
+                        debug!("⏪ {:?} for {} failed: {}", event.event_type, match_id, e);
+                        step.error = Some(e.to_string());
+                    }
+                }
+                Err(e) => step.rejected = Some(e.to_string()),
+            }
+
+            step.audit = audit.entries().await.split_off(audited);
+            steps.push(step);
+        }
+
+        let bets = engine.get_match_bets(match_id).await;
+        let profit_loss = bets.iter().filter_map(BettingDecision::base_settled_profit_loss).sum();
+        ReplayReport {
+            match_id: match_id.to_string(),
+            starting_bankroll: bankroll,
+            events_replayed: events.len(),
+            steps,
+            bets,
+            profit_loss,
+        }
+    }
+
+    async fn replay_event(
+        &self,
+        predictor: &PredictorService,
+        engine: &TradingEngine,
+        simulator: &MarketSimulator,
+        event: &MatchEvent,
+        step: &mut ReplayStep,
+    ) -> Result<()> {
+        match event.event_type {
+            EventType::FullTime => {
+                if let Some(context) = predictor.get_feature_engineer().get_match_context(&event.match_id) {
+                    engine.settle_match(&event.match_id, context.home_score, context.away_score).await?;
+                }
+                return Ok(());
+            }
+            EventType::MatchEnd => {
+                if matches!(event.match_status, MatchStatus::Postponed | MatchStatus::Cancelled) {
+                    engine.void_match(&event.match_id).await?;
+                }
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        let recorded = self.odds_history.snapshot_at(&event.match_id, MARKET_FEED_BOOKMAKER, event.timestamp).await;
+        step.odds_recorded = recorded.is_some();
+        let odds = match recorded {
+            Some(odds) => odds,
+            None => simulator.generate_market_odds(event).await?,
+        };
+        engine.update_market_odds(event.match_id.clone(), odds.clone()).await;
+        step.odds = Some(odds);
+
+        let prediction = predictor.predict(event).await?;
+        step.prediction = Some(prediction.clone());
+
+        let signal = engine.process_prediction(&prediction).await?;
+        step.signal = Some(signal.clone());
+        if signal.signal_strength > MIN_EXECUTION_STRENGTH {
+            step.executed = engine.execute_trade(&signal).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_models::{BetStatus, Score};
+    use rust_decimal_macros::dec;
+
+    fn event(event_type: EventType) -> MatchEvent {
+        MatchEvent::new(
+            "m1".to_string(),
+            event_type,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_replay_runs_in_a_sandbox() {
+        let predictor = Arc::new(PredictorService::new());
+        let odds_history = Arc::new(OddsHistory::new());
+        let replayer = EventReplayer::new(predictor.clone(), odds_history);
+        assert!(replayer.stored_events("m1").await.unwrap().is_empty());
+
+        let goal = event(EventType::Goal { team: "Arsenal".to_string(), player: None, minute: 30 })
+            .with_score(Score { home: 1, away: 0, half_time_home: None, half_time_away: None });
+        let events = vec![
+            event(EventType::MatchStart),
+            goal,
+            // Can't happen before kick-off is over, so the state machine rejects it
+            event(EventType::MatchStart),
+            event(EventType::FullTime),
+        ];
+
+        let report = replayer.replay("m1", &events, dec!(1000)).await;
+        assert_eq!(report.events_replayed, 4);
+        assert!(report.steps[1].prediction.is_some());
+        assert!(report.steps[1].signal.is_some());
+        assert!(!report.steps[1].odds_recorded);
+        assert!(report.steps[2].rejected.is_some());
+        assert!(report.steps[3].prediction.is_none());
+        assert!(report.bets.iter().all(|bet| !matches!(bet.status, BetStatus::Pending | BetStatus::Placed)));
+
+        // The live predictor never saw the match
+        assert!(predictor.get_feature_engineer().get_match_context("m1").is_none());
+        assert_eq!(predictor.get_prediction_count().await, 0);
+    }
+}
@@ -0,0 +1,340 @@
+// Age-based pruning of raw data, with optional archival to JSON Lines files
+
+use crate::compression::gzip;
+use crate::metrics::MetricsCollector;
+use quant_db::{PrunedRow, Repository, RetentionRepository, RetentionTarget};
+use quant_stream::RedisStream;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+const DEFAULT_BATCH_SIZE: i64 = 5_000;
+const DEFAULT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// How long each kind of data is kept. Targets and streams without a window
+/// are kept forever.
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    windows: HashMap<RetentionTarget, Duration>,
+    /// Keyed by Redis stream key
+    stream_windows: HashMap<String, Duration>,
+    archive_dir: Option<PathBuf>,
+    compress: bool,
+    batch_size: i64,
+}
+
+impl RetentionPolicy {
+    /// Raw events for 90 days, odds ticks for 30 and relayed outbox messages
+    /// for 7; everything else forever
+    pub fn new() -> Self {
+        Self {
+            windows: HashMap::from([
+                (RetentionTarget::Events, Duration::days(90)),
+                (RetentionTarget::OddsTicks, Duration::days(30)),
+                (RetentionTarget::PublishedOutbox, Duration::days(7)),
+            ]),
+            stream_windows: HashMap::new(),
+            archive_dir: None,
+            compress: true,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Keep the target's rows this many days; `None` keeps them forever
+    pub fn with_window(mut self, target: RetentionTarget, days: Option<u32>) -> Self {
+        match days {
+            Some(days) => self.windows.insert(target, Duration::days(days as i64)),
+            None => self.windows.remove(&target),
+        };
+        self
+    }
+
+    pub fn with_stream_window(mut self, stream_key: &str, days: u32) -> Self {
+        self.stream_windows.insert(stream_key.to_string(), Duration::days(days as i64));
+        self
+    }
+
+    /// Write pruned rows to `dir` before deleting them, gzipped if `compress`
+    pub fn with_archive(mut self, dir: impl Into<PathBuf>, compress: bool) -> Self {
+        self.archive_dir = Some(dir.into());
+        self.compress = compress;
+        self
+    }
+
+    /// Rows deleted per transaction
+    pub fn with_batch_size(mut self, batch_size: i64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn window(&self, target: RetentionTarget) -> Option<Duration> {
+        self.windows.get(&target).copied()
+    }
+
+    /// Rows of the target from before this are due for pruning
+    pub fn cutoff(&self, target: RetentionTarget, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.window(target).map(|window| now - window)
+    }
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PruneReport {
+    pub target: RetentionTarget,
+    pub cutoff: DateTime<Utc>,
+    pub rows: u64,
+    /// On-disk size of the deleted rows, reusable once Postgres vacuums
+    pub bytes_reclaimed: u64,
+    pub archive: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamTrimReport {
+    pub stream_key: String,
+    pub cutoff: DateTime<Utc>,
+    pub entries: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RetentionReport {
+    pub tables: Vec<PruneReport>,
+    pub streams: Vec<StreamTrimReport>,
+}
+
+impl RetentionReport {
+    pub fn rows_pruned(&self) -> u64 {
+        self.tables.iter().map(|report| report.rows).sum()
+    }
+
+    pub fn bytes_reclaimed(&self) -> u64 {
+        self.tables.iter().map(|report| report.bytes_reclaimed).sum()
+    }
+}
+
+/// Periodically prunes tables and streams past their retention window
+pub struct RetentionJob {
+    repository: Arc<Repository>,
+    stream: Option<Arc<RedisStream>>,
+    metrics: Option<Arc<MetricsCollector>>,
+    policy: RetentionPolicy,
+    interval: std::time::Duration,
+}
+
+impl RetentionJob {
+    pub fn new(repository: Arc<Repository>, policy: RetentionPolicy) -> Self {
+        Self {
+            repository,
+            stream: None,
+            metrics: None,
+            policy,
+            interval: DEFAULT_INTERVAL,
+        }
+    }
+
+    /// Also trim Redis streams that have a window
+    pub fn with_stream(mut self, stream: Arc<RedisStream>) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn with_interval(mut self, interval: std::time::Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Prune everything past its window as of `now`. A target that fails is
+    /// logged and skipped so the others still get pruned.
+    pub async fn run_once(&self, now: DateTime<Utc>) -> RetentionReport {
+        let mut report = RetentionReport::default();
+
+        for target in RetentionTarget::ALL {
+            let Some(cutoff) = self.policy.cutoff(target, now) else {
+                continue;
+            };
+            match self.prune(target, cutoff, now).await {
+                Ok(pruned) if pruned.rows > 0 => report.tables.push(pruned),
+                Ok(_) => {}
+                Err(e) => warn!("🧹 Failed to prune {}: {}", target.as_str(), e),
+            }
+        }
+
+        if let Some(stream) = &self.stream {
+            for (stream_key, window) in &self.policy.stream_windows {
+                let cutoff = now - *window;
+                match stream.trim_before(stream_key, cutoff).await {
+                    Ok(entries) if entries > 0 => report.streams.push(StreamTrimReport {
+                        stream_key: stream_key.clone(),
+                        cutoff,
+                        entries,
+                    }),
+                    Ok(_) => {}
+                    Err(e) => warn!("🧹 Failed to trim stream {}: {}", stream_key, e),
+                }
+            }
+        }
+
+        if let Some(metrics) = &self.metrics {
+            metrics.record_pruned(report.rows_pruned(), report.bytes_reclaimed()).await;
+        }
+        report
+    }
+
+    async fn prune(&self, target: RetentionTarget, cutoff: DateTime<Utc>, now: DateTime<Utc>) -> Result<PruneReport> {
+        let mut archive = self.policy.archive_dir.as_ref().map(|dir| {
+            ArchiveWriter::new(dir, target, now, self.policy.compress)
+        });
+        let mut report = PruneReport {
+            target,
+            cutoff,
+            rows: 0,
+            bytes_reclaimed: 0,
+            archive: None,
+        };
+
+        loop {
+            let rows = self.repository
+                .prune_batch(target, cutoff, self.policy.batch_size, |rows| match archive.as_mut() {
+                    Some(archive) => archive.append(rows),
+                    None => Ok(()),
+                })
+                .await?;
+
+            report.rows += rows.len() as u64;
+            report.bytes_reclaimed += rows.iter().map(|row| row.bytes.max(0) as u64).sum::<u64>();
+            if (rows.len() as i64) < self.policy.batch_size {
+                break;
+            }
+        }
+
+        report.archive = archive.and_then(|archive| archive.written().map(Path::to_path_buf));
+        if report.rows > 0 {
+            info!("🧹 Pruned {} {} rows older than {} ({} bytes)",
+                  report.rows, target.as_str(), cutoff.format("%Y-%m-%d"), report.bytes_reclaimed);
+        }
+        Ok(report)
+    }
+
+    /// Prune every `interval` until cancelled
+    pub async fn run(&self, shutdown: CancellationToken) {
+        info!("🧹 Retention job started");
+        let mut ticker = tokio::time::interval(self.interval);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
+            self.run_once(Utc::now()).await;
+        }
+        info!("🧹 Retention job stopped");
+    }
+}
+
+/// Appends pruned rows to `<dir>/<target>-<timestamp>.jsonl[.gz]`, creating
+/// the file on the first non-empty batch. Each batch is its own gzip member,
+/// which `zcat` and gzip readers read back as one stream.
+struct ArchiveWriter {
+    path: PathBuf,
+    compress: bool,
+    file: Option<File>,
+}
+
+impl ArchiveWriter {
+    fn new(dir: &Path, target: RetentionTarget, now: DateTime<Utc>, compress: bool) -> Self {
+        let extension = if compress { "jsonl.gz" } else { "jsonl" };
+        let name = format!("{}-{}.{}", target.as_str(), now.format("%Y%m%dT%H%M%S"), extension);
+        Self {
+            path: dir.join(name),
+            compress,
+            file: None,
+        }
+    }
+
+    fn append(&mut self, rows: &[PrunedRow]) -> Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let mut lines = Vec::new();
+        for row in rows {
+            serde_json::to_writer(&mut lines, &row.data)?;
+            lines.push(b'\n');
+        }
+
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => {
+                if let Some(dir) = self.path.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                self.file.insert(OpenOptions::new().create(true).append(true).open(&self.path)?)
+            }
+        };
+        if self.compress {
+            file.write_all(&gzip(&lines))?;
+        } else {
+            file.write_all(&lines)?;
+        }
+        // Rows are deleted once this returns, so they must be on disk first
+        file.sync_data()?;
+        Ok(())
+    }
+
+    fn written(&self) -> Option<&Path> {
+        self.file.as_ref().map(|_| self.path.as_path())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_windows() {
+        let now = Utc::now();
+        let policy = RetentionPolicy::new()
+            .with_window(RetentionTarget::OddsTicks, Some(14))
+            .with_window(RetentionTarget::Events, None);
+
+        assert_eq!(policy.cutoff(RetentionTarget::OddsTicks, now), Some(now - Duration::days(14)));
+        assert_eq!(policy.cutoff(RetentionTarget::PublishedOutbox, now), Some(now - Duration::days(7)));
+        assert!(policy.cutoff(RetentionTarget::Events, now).is_none());
+        assert!(policy.cutoff(RetentionTarget::TrainingExamples, now).is_none());
+    }
+
+    #[test]
+    fn test_archive_written_as_gzipped_json_lines() {
+        let dir = std::env::temp_dir().join(format!("retention-test-{}", uuid::Uuid::new_v4()));
+        let mut archive = ArchiveWriter::new(&dir, RetentionTarget::Events, Utc::now(), true);
+        assert!(archive.written().is_none());
+
+        let row = |id: u32| PrunedRow { data: serde_json::json!({ "id": id }), bytes: 64 };
+        archive.append(&[row(1), row(2)]).unwrap();
+        archive.append(&[row(3)]).unwrap();
+        let path = archive.written().unwrap().to_path_buf();
+        assert!(path.to_string_lossy().ends_with(".jsonl.gz"));
+
+        let bytes = std::fs::read(&path).unwrap();
+        let text = String::from_utf8(crate::compression::gunzip(&bytes).unwrap()).unwrap();
+        assert_eq!(text, "{\"id\":1}\n{\"id\":2}\n{\"id\":3}\n");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
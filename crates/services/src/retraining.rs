@@ -0,0 +1,103 @@
+// Scheduled retraining: a new model from the latest settled matches every
+// few hours, installed only when it beats the live one on a holdout
+
+use crate::jobs::{JobHandler, JobQueue, RETRAIN_MODEL_JOB};
+use crate::model_training::ModelTrainingJob;
+use crate::predictor::PredictorService;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use quant_db::Repository;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+pub struct ModelRetrainer {
+    job: ModelTrainingJob,
+    repository: Arc<Repository>,
+    predictor: Arc<PredictorService>,
+    model_dir: PathBuf,
+    interval: Duration,
+    /// When set, each scheduled retrain is queued here so a failed one is retried
+    jobs: Option<Arc<JobQueue>>,
+}
+
+impl ModelRetrainer {
+    /// Retrains daily
+    pub fn new(repository: Arc<Repository>, predictor: Arc<PredictorService>, model_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            job: ModelTrainingJob::new(),
+            repository,
+            predictor,
+            model_dir: model_dir.into(),
+            interval: Duration::from_secs(24 * 3600),
+            jobs: None,
+        }
+    }
+
+    pub fn with_job(mut self, job: ModelTrainingJob) -> Self {
+        self.job = job;
+        self
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval.max(Duration::from_secs(60));
+        self
+    }
+
+    /// Queue scheduled retrains as jobs on `jobs`, which must have this
+    /// retrainer registered for them
+    pub fn with_jobs(mut self, jobs: Arc<JobQueue>) -> Self {
+        self.jobs = Some(jobs);
+        self
+    }
+
+    /// Train a candidate, score it and the live model on the same holdout,
+    /// and install it if it does better. Returns whether it was installed.
+    pub async fn retrain_once(&self) -> Result<bool> {
+        let live = self.predictor.logistic_snapshot().await?
+            .ok_or_else(|| anyhow!("the live model has no logistic weights to compare a retrained one with"))?;
+        let (run, comparison) = self.job.retrain(&self.repository, &self.model_dir, &live).await?;
+        let (before, after) = (&comparison.live, &comparison.candidate);
+        let metrics = format!(
+            "holdout of {} examples, log loss {:.4} -> {:.4}, Brier {:.4} -> {:.4}, accuracy {:.1}% -> {:.1}%",
+            after.sample_count, before.log_loss, after.log_loss, before.brier_score, after.brier_score,
+            before.accuracy * 100.0, after.accuracy * 100.0,
+        );
+
+        if !comparison.improves() {
+            info!("🧠 Keeping the live model over {} {}: {}", run.artifact.name, run.artifact.version, metrics);
+            return Ok(false);
+        }
+        self.predictor.install_artifact(&run.artifact).await?;
+        info!("🧠 Promoted {} {} from {}: {}", run.artifact.name, run.artifact.version, run.path.display(), metrics);
+        Ok(true)
+    }
+
+    /// Retrain every `interval` until cancelled, starting one interval in
+    pub async fn run(&self, shutdown: CancellationToken) {
+        info!("🧠 Retraining every {:?}, promoting only on a better holdout", self.interval);
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
+            if let Some(jobs) = &self.jobs {
+                jobs.enqueue(RETRAIN_MODEL_JOB, serde_json::Value::Null).await;
+            } else if let Err(e) = self.retrain_once().await {
+                warn!("🧠 Scheduled retraining failed: {}", e);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl JobHandler for ModelRetrainer {
+    async fn run(&self, _payload: &serde_json::Value) -> Result<()> {
+        self.retrain_once().await.map(|_| ())
+    }
+}
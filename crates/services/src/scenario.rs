@@ -0,0 +1,262 @@
+// Scripted simulation scenarios: fixtures with goals, cards and odds shocks
+// at fixed minutes, played back exactly by the data feed
+
+use crate::data_feed::SimulatedFixture;
+use anyhow::{anyhow, bail, Context, Result};
+use quant_models::{CardType, EventType};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Full time when a script doesn't say otherwise
+pub const DEFAULT_FULL_TIME_MINUTE: u8 = 90;
+/// Latest a scripted match may end, stoppage time included
+const MAX_FULL_TIME_MINUTE: u8 = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScenarioSide {
+    Home,
+    Away,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScriptedAction {
+    Goal {
+        team: ScenarioSide,
+        #[serde(default)]
+        player: Option<String>,
+    },
+    YellowCard {
+        team: ScenarioSide,
+        #[serde(default)]
+        player: Option<String>,
+    },
+    RedCard {
+        team: ScenarioSide,
+        #[serde(default)]
+        player: Option<String>,
+    },
+    Substitution {
+        team: ScenarioSide,
+        player_in: String,
+        player_out: String,
+    },
+    /// Scale the market's outcome probabilities for one quote, e.g. money
+    /// piling onto the away side. Prices are renormalized afterwards.
+    OddsShock {
+        #[serde(default = "unit_factor")]
+        home: f64,
+        #[serde(default = "unit_factor")]
+        draw: f64,
+        #[serde(default = "unit_factor")]
+        away: f64,
+    },
+}
+
+fn unit_factor() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptedEvent {
+    pub minute: u8,
+    #[serde(flatten)]
+    pub action: ScriptedAction,
+}
+
+impl ScriptedEvent {
+    /// The feed event for this action. Goals and cards are credited to the
+    /// named side; unnamed players get a placeholder.
+    pub fn event_type(&self, team_home: &str, team_away: &str) -> EventType {
+        let side_name = |side: &ScenarioSide| match side {
+            ScenarioSide::Home => team_home.to_string(),
+            ScenarioSide::Away => team_away.to_string(),
+        };
+        let player = |player: &Option<String>| player.clone().unwrap_or_else(|| "Unknown".to_string());
+        match &self.action {
+            ScriptedAction::Goal { team, player } => EventType::Goal {
+                team: side_name(team),
+                player: player.clone(),
+                minute: self.minute,
+            },
+            ScriptedAction::YellowCard { team, player: name } => EventType::Card {
+                team: side_name(team),
+                player: player(name),
+                card_type: CardType::Yellow,
+                minute: self.minute,
+            },
+            ScriptedAction::RedCard { team, player: name } => EventType::Card {
+                team: side_name(team),
+                player: player(name),
+                card_type: CardType::Red,
+                minute: self.minute,
+            },
+            ScriptedAction::Substitution { team, player_in, player_out } => EventType::Substitution {
+                team: side_name(team),
+                player_in: player_in.clone(),
+                player_out: player_out.clone(),
+                minute: self.minute,
+            },
+            ScriptedAction::OddsShock { .. } => EventType::OddsUpdate,
+        }
+    }
+
+    /// Event metadata the market simulator reads, for odds shocks
+    pub fn metadata(&self) -> Option<serde_json::Value> {
+        match self.action {
+            ScriptedAction::OddsShock { home, draw, away } => Some(serde_json::json!({
+                "odds_shock": { "home": home, "draw": draw, "away": away }
+            })),
+            _ => None,
+        }
+    }
+}
+
+/// What happens in a scripted match, minute by minute. Half time is at 45
+/// and full time at `full_time`; nothing else happens unless scripted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchScript {
+    #[serde(default = "default_full_time")]
+    pub full_time: u8,
+    #[serde(default)]
+    pub events: Vec<ScriptedEvent>,
+}
+
+fn default_full_time() -> u8 {
+    DEFAULT_FULL_TIME_MINUTE
+}
+
+impl MatchScript {
+    pub fn validate(&self) -> Result<()> {
+        if !(45..=MAX_FULL_TIME_MINUTE).contains(&self.full_time) {
+            bail!("full time must be between minute 45 and {}, got {}", MAX_FULL_TIME_MINUTE, self.full_time);
+        }
+        for event in &self.events {
+            if event.minute == 0 || event.minute >= self.full_time {
+                bail!("event at minute {} is outside the match (1 to {})", event.minute, self.full_time - 1);
+            }
+            if let ScriptedAction::OddsShock { home, draw, away } = event.action {
+                if [home, draw, away].iter().any(|factor| !(factor.is_finite() && *factor > 0.0)) {
+                    bail!("odds shock at minute {} needs positive factors", event.minute);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Scripted events at `minute`, in script order
+    pub fn events_at(&self, minute: u8) -> impl Iterator<Item = &ScriptedEvent> {
+        self.events.iter().filter(move |event| event.minute == minute)
+    }
+}
+
+/// A set of scripted fixtures, usually loaded from a TOML file:
+///
+/// ```toml
+/// name = "Late equalizer"
+///
+/// [[matches]]
+/// match_id = "late_equalizer"
+/// team_home = "Arsenal"
+/// team_away = "Chelsea"
+/// league = "Premier League"
+///
+/// [[matches.script.events]]
+/// minute = 89
+/// type = "goal"
+/// team = "away"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub matches: Vec<SimulatedFixture>,
+}
+
+impl Scenario {
+    pub fn from_toml_str(source: &str) -> Result<Self> {
+        let scenario: Scenario = toml::from_str(source)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read scenario {}", path.display()))?;
+        Self::from_toml_str(&source).with_context(|| format!("invalid scenario {}", path.display()))
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.matches.is_empty() {
+            bail!("scenario '{}' has no matches", self.name);
+        }
+        for fixture in &self.matches {
+            let script = fixture.script.as_ref()
+                .ok_or_else(|| anyhow!("match {} has no script", fixture.match_id))?;
+            script.validate().with_context(|| format!("match {}", fixture.match_id))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scenario_parsed_from_toml() {
+        let scenario = Scenario::from_toml_str(r#"
+            name = "Early red card"
+
+            [[matches]]
+            match_id = "red_card"
+            team_home = "Arsenal"
+            team_away = "Chelsea"
+            league = "Premier League"
+
+            [matches.script]
+            full_time = 94
+
+            [[matches.script.events]]
+            minute = 4
+            type = "red_card"
+            team = "home"
+            player = "Saliba"
+
+            [[matches.script.events]]
+            minute = 5
+            type = "odds_shock"
+            away = 1.3
+        "#).unwrap();
+
+        let script = scenario.matches[0].script.as_ref().unwrap();
+        assert_eq!(script.full_time, 94);
+        assert_eq!(script.events_at(4).count(), 1);
+        assert_eq!(script.events[0].event_type("Arsenal", "Chelsea"), EventType::Card {
+            team: "Arsenal".to_string(),
+            player: "Saliba".to_string(),
+            card_type: CardType::Red,
+            minute: 4,
+        });
+        assert_eq!(script.events[1].action, ScriptedAction::OddsShock { home: 1.0, draw: 1.0, away: 1.3 });
+        assert_eq!(script.events[1].metadata().unwrap()["odds_shock"]["away"], 1.3);
+
+        // Events must fall inside the match
+        let late = "name = \"x\"\n[[matches]]\nmatch_id = \"m\"\nteam_home = \"a\"\nteam_away = \"b\"\nleague = \"l\"\n\
+                    [[matches.script.events]]\nminute = 90\ntype = \"goal\"\nteam = \"home\"\n";
+        assert!(Scenario::from_toml_str(late).is_err());
+    }
+
+    #[test]
+    fn test_example_scenarios_are_valid() {
+        for source in [
+            include_str!("../../../examples/scenarios/late_equalizer.toml"),
+            include_str!("../../../examples/scenarios/early_red_card.toml"),
+        ] {
+            Scenario::from_toml_str(source).unwrap();
+        }
+    }
+}
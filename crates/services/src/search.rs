@@ -0,0 +1,318 @@
+// Trigram search over the matches, teams, leagues and bets seen this session
+
+use crate::trader::describe_bet_type;
+use quant_models::{match_key, BettingDecision, EventType, MatchEvent, TeamNameNormalizer};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use tokio::sync::RwLock;
+
+const DEFAULT_MAX_DOCUMENTS: usize = 50_000;
+/// Lowest score a result may have, as with Postgres' `pg_trgm` default
+const DEFAULT_SEARCH_THRESHOLD: f64 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchResultKind {
+    Match,
+    Team,
+    League,
+    Bet,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub kind: SearchResultKind,
+    pub id: String,
+    pub title: String,
+    pub subtitle: Option<String>,
+    /// API path with the entity's details
+    pub link: String,
+    /// 0 to 1, with 1 an exact match
+    pub score: f64,
+}
+
+#[derive(Debug, Clone)]
+struct SearchDocument {
+    kind: SearchResultKind,
+    id: String,
+    title: String,
+    subtitle: Option<String>,
+    link: String,
+    /// Match keys the document can be found by
+    terms: Vec<String>,
+}
+
+#[derive(Default)]
+struct IndexState {
+    /// Keyed by `kind:id`
+    documents: HashMap<String, SearchDocument>,
+    trigrams: HashMap<String, HashSet<String>>,
+    /// Document keys, oldest first, for eviction
+    order: VecDeque<String>,
+}
+
+impl IndexState {
+    fn insert(&mut self, document: SearchDocument, max_documents: usize) {
+        let key = document_key(document.kind, &document.id);
+        if let Some(previous) = self.documents.remove(&key) {
+            self.unindex(&key, &previous);
+        } else {
+            self.order.push_back(key.clone());
+        }
+        for trigram in document.terms.iter().flat_map(|term| trigrams(term)) {
+            self.trigrams.entry(trigram).or_default().insert(key.clone());
+        }
+        self.documents.insert(key, document);
+
+        while self.order.len() > max_documents {
+            if let Some(oldest) = self.order.pop_front() {
+                if let Some(document) = self.documents.remove(&oldest) {
+                    self.unindex(&oldest, &document);
+                }
+            }
+        }
+    }
+
+    fn unindex(&mut self, key: &str, document: &SearchDocument) {
+        for trigram in document.terms.iter().flat_map(|term| trigrams(term)) {
+            if let Some(keys) = self.trigrams.get_mut(&trigram) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    self.trigrams.remove(&trigram);
+                }
+            }
+        }
+    }
+}
+
+/// In-memory index the dashboard searches. Every indexed name is split into
+/// trigrams; a query is scored against the documents sharing a trigram with
+/// it, so lookups stay cheap as the index grows.
+pub struct SearchIndex {
+    state: RwLock<IndexState>,
+    normalizer: TeamNameNormalizer,
+    max_documents: usize,
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(IndexState::default()),
+            normalizer: TeamNameNormalizer::new(),
+            max_documents: DEFAULT_MAX_DOCUMENTS,
+        }
+    }
+
+    /// Keep at most this many documents, dropping the oldest
+    pub fn with_max_documents(mut self, max_documents: usize) -> Self {
+        self.max_documents = max_documents.max(1);
+        self
+    }
+
+    /// Index the event's match, teams and league. Matches link to their
+    /// prediction while live and to their summary once finished.
+    pub async fn index_event(&self, event: &MatchEvent) {
+        let finished = matches!(event.event_type, EventType::FullTime);
+        let key = document_key(SearchResultKind::Match, &event.match_id);
+        let mut state = self.state.write().await;
+        if !finished && state.documents.contains_key(&key) {
+            return;
+        }
+
+        let link = if finished {
+            format!("/api/v1/matches/{}/summary", event.match_id)
+        } else {
+            format!("/api/v1/predictions/{}", event.match_id)
+        };
+        state.insert(SearchDocument {
+            kind: SearchResultKind::Match,
+            id: event.match_id.clone(),
+            title: format!("{} vs {}", event.team_home, event.team_away),
+            subtitle: Some(format!("{} {}", event.league, event.season)),
+            link,
+            terms: vec![
+                match_key(&event.match_id),
+                match_key(&format!("{} {}", event.team_home, event.team_away)),
+            ],
+        }, self.max_documents);
+
+        for team in [&event.team_home, &event.team_away] {
+            state.insert(SearchDocument {
+                kind: SearchResultKind::Team,
+                id: team.clone(),
+                title: team.clone(),
+                subtitle: Some(event.league.clone()),
+                link: format!("/api/v1/search?q={}&kind=match", encode_query(team)),
+                terms: vec![match_key(&self.normalizer.normalize(team))],
+            }, self.max_documents);
+        }
+        state.insert(SearchDocument {
+            kind: SearchResultKind::League,
+            id: event.league.clone(),
+            title: event.league.clone(),
+            subtitle: None,
+            link: format!("/api/v1/search?q={}&kind=match", encode_query(&event.league)),
+            terms: vec![match_key(&event.league)],
+        }, self.max_documents);
+    }
+
+    pub async fn index_bet(&self, bet: &BettingDecision) {
+        let id = bet.id.to_string();
+        let document = SearchDocument {
+            kind: SearchResultKind::Bet,
+            terms: vec![match_key(&id)],
+            title: format!("{} @ {}", describe_bet_type(&bet.bet_type), bet.odds),
+            subtitle: Some(format!("{} on {}", bet.stake, bet.match_id)),
+            link: format!("/api/v1/trades?match_id={}", encode_query(&bet.match_id)),
+            id,
+        };
+        self.state.write().await.insert(document, self.max_documents);
+    }
+
+    /// Best matches first. Team aliases in the query are resolved, so "Man
+    /// Utd" finds Manchester United's matches.
+    pub async fn search(&self, query: &str, kind: Option<SearchResultKind>, limit: usize) -> Vec<SearchResult> {
+        let mut queries = vec![match_key(query)];
+        let canonical = match_key(&self.normalizer.normalize(query));
+        if canonical != queries[0] {
+            queries.push(canonical);
+        }
+        queries.retain(|q| !q.is_empty());
+        if queries.is_empty() {
+            return Vec::new();
+        }
+
+        let state = self.state.read().await;
+        let candidates: HashSet<&String> = queries.iter()
+            .flat_map(|q| trigrams(q))
+            .filter_map(|trigram| state.trigrams.get(&trigram))
+            .flatten()
+            .collect();
+
+        let mut results: Vec<SearchResult> = candidates.into_iter()
+            .filter_map(|key| state.documents.get(key))
+            .filter(|document| kind.is_none_or(|kind| document.kind == kind))
+            .filter_map(|document| {
+                let score = queries.iter()
+                    .flat_map(|q| document.terms.iter().map(move |term| term_score(q, term)))
+                    .fold(0.0, f64::max);
+                (score >= DEFAULT_SEARCH_THRESHOLD).then(|| SearchResult {
+                    kind: document.kind,
+                    id: document.id.clone(),
+                    title: document.title.clone(),
+                    subtitle: document.subtitle.clone(),
+                    link: document.link.clone(),
+                    score,
+                })
+            })
+            .collect();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.title.cmp(&b.title)));
+        results.truncate(limit);
+        results
+    }
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn document_key(kind: SearchResultKind, id: &str) -> String {
+    format!("{:?}:{}", kind, id)
+}
+
+/// Exact match scores 1, a term containing the query 0.9 to 1 depending on
+/// how much of it the query covers, anything else its trigram similarity
+fn term_score(query: &str, term: &str) -> f64 {
+    if term == query {
+        1.0
+    } else if term.contains(query) {
+        0.9 + 0.1 * query.chars().count() as f64 / term.chars().count() as f64
+    } else {
+        let (q, t) = (trigrams(query), trigrams(term));
+        let shared = q.intersection(&t).count() as f64;
+        let union = q.union(&t).count() as f64;
+        if union > 0.0 { shared / union } else { 0.0 }
+    }
+}
+
+/// Trigrams of each word, padded like `pg_trgm` so word starts weigh more
+fn trigrams(key: &str) -> HashSet<String> {
+    key.split_whitespace()
+        .flat_map(|word| {
+            let padded: Vec<char> = format!("  {} ", word).chars().collect();
+            padded.windows(3).map(|w| w.iter().collect::<String>()).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn encode_query(value: &str) -> String {
+    value.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_models::BetType;
+    use rust_decimal_macros::dec;
+
+    fn event(match_id: &str, home: &str, away: &str, event_type: EventType) -> MatchEvent {
+        MatchEvent::new(
+            match_id.to_string(),
+            event_type,
+            home.to_string(),
+            away.to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_search_across_matches_teams_leagues_and_bets() {
+        let index = SearchIndex::new();
+        index.index_event(&event("match_1", "Manchester United", "Chelsea", EventType::MatchStart)).await;
+        index.index_event(&event("match_2", "Arsenal", "Tottenham Hotspur", EventType::MatchStart)).await;
+
+        let bet = BettingDecision::new(
+            "match_1".to_string(), BetType::HomeWin, dec!(10), dec!(2.0), 0.5, "test".to_string(),
+        ).unwrap();
+        index.index_bet(&bet).await;
+
+        let results = index.search("Man Utd", None, 10).await;
+        assert_eq!(results[0].kind, SearchResultKind::Team);
+        assert_eq!(results[0].id, "Manchester United");
+        assert!(results.iter().any(|r| r.kind == SearchResultKind::Match && r.id == "match_1"));
+
+        let results = index.search("premier", Some(SearchResultKind::League), 10).await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].link, "/api/v1/search?q=Premier%20League&kind=match");
+
+        let results = index.search(&bet.id.to_string()[..8], None, 10).await;
+        assert_eq!(results[0].kind, SearchResultKind::Bet);
+        assert_eq!(results[0].link, "/api/v1/trades?match_id=match_1");
+
+        // Typos still find the team
+        assert_eq!(index.search("Arsnal", Some(SearchResultKind::Team), 10).await[0].id, "Arsenal");
+        assert!(index.search("Barcelona", None, 10).await.is_empty());
+
+        index.index_event(&event("match_1", "Manchester United", "Chelsea", EventType::FullTime)).await;
+        let results = index.search("match_1", Some(SearchResultKind::Match), 10).await;
+        assert_eq!(results[0].link, "/api/v1/matches/match_1/summary");
+    }
+
+    #[tokio::test]
+    async fn test_oldest_documents_evicted() {
+        let index = SearchIndex::new().with_max_documents(4);
+        index.index_event(&event("match_1", "Arsenal", "Chelsea", EventType::MatchStart)).await;
+        index.index_event(&event("match_2", "Everton", "Fulham", EventType::MatchStart)).await;
+
+        assert!(index.search("Arsenal", None, 10).await.is_empty());
+        assert_eq!(index.search("Fulham", Some(SearchResultKind::Team), 10).await.len(), 1);
+    }
+}
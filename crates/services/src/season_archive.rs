@@ -0,0 +1,270 @@
+// Cold storage for completed seasons: export to compressed columnar files,
+// drop from the live tables, and load back for backtesting
+
+use crate::compression::{gunzip, gzip};
+use quant_db::{ArchiveTable, Repository, SeasonArchiveRepository};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+const FORMAT_VERSION: u32 = 1;
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// One table's rows stored column by column, so repeated values such as
+/// match ids and bookmakers sit next to each other and compress well
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ColumnarTable {
+    rows: usize,
+    columns: BTreeMap<String, Vec<Value>>,
+}
+
+impl ColumnarTable {
+    fn from_rows(rows: &[Value]) -> Result<Self> {
+        let mut columns: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+        for (index, row) in rows.iter().enumerate() {
+            let row = row.as_object().context("archived row is not an object")?;
+            for name in row.keys() {
+                // A column first seen part-way through is null in earlier rows
+                columns.entry(name.clone()).or_insert_with(|| vec![Value::Null; index]);
+            }
+            for (name, values) in columns.iter_mut() {
+                values.push(row.get(name).cloned().unwrap_or(Value::Null));
+            }
+        }
+        Ok(Self { rows: rows.len(), columns })
+    }
+
+    fn into_rows(self) -> Result<Vec<Value>> {
+        if self.columns.values().any(|values| values.len() != self.rows) {
+            bail!("archived columns do not all have {} rows", self.rows);
+        }
+        let mut rows = vec![Map::new(); self.rows];
+        for (name, values) in self.columns {
+            for (row, value) in rows.iter_mut().zip(values) {
+                row.insert(name.clone(), value);
+            }
+        }
+        Ok(rows.into_iter().map(Value::Object).collect())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedTable {
+    pub table: ArchiveTable,
+    pub rows: usize,
+    /// File name within the archive directory
+    pub file: String,
+}
+
+/// Written last, so a directory without one is an incomplete archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeasonArchiveManifest {
+    pub format_version: u32,
+    pub season: String,
+    pub league: Option<String>,
+    pub exported_at: DateTime<Utc>,
+    pub tables: Vec<ArchivedTable>,
+}
+
+impl SeasonArchiveManifest {
+    pub fn total_rows(&self) -> usize {
+        self.tables.iter().map(|table| table.rows).sum()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SeasonArchiveReport {
+    pub path: PathBuf,
+    pub manifest: SeasonArchiveManifest,
+    /// Rows removed from the live tables; 0 when they were kept
+    pub rows_purged: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SeasonRestoreReport {
+    pub season: String,
+    pub league: Option<String>,
+    /// Rows inserted; rows already in the database are skipped
+    pub rows_restored: u64,
+    pub rows_archived: usize,
+}
+
+/// Exports a finished season's matches, events, odds, predictions and bets
+/// to `<dir>/<season>[-<league>]/`, one gzipped columnar file per table.
+pub struct SeasonArchiver {
+    dir: PathBuf,
+}
+
+impl SeasonArchiver {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Directory the season's archive is written to
+    pub fn season_dir(&self, season: &str, league: Option<&str>) -> PathBuf {
+        let name = match league {
+            Some(league) => format!("{}-{}", slug(season), slug(league)),
+            None => slug(season),
+        };
+        self.dir.join(name)
+    }
+
+    /// Archive the season and, if `purge`, delete it from the live tables.
+    /// Seasons with unfinished matches are refused, and the files are read
+    /// back and compared with the database before anything is deleted.
+    pub async fn archive(&self, repository: &Repository, season: &str, league: Option<&str>, purge: bool) -> Result<SeasonArchiveReport> {
+        let status = repository.get_season_status(season, league).await?;
+        if status.matches == 0 {
+            bail!("no matches stored for season {}", describe(season, league));
+        }
+        if status.unfinished > 0 {
+            bail!("season {} still has {} unfinished matches", describe(season, league), status.unfinished);
+        }
+
+        let path = self.season_dir(season, league);
+        if path.join(MANIFEST_FILE).exists() {
+            bail!("{} already holds an archive", path.display());
+        }
+        std::fs::create_dir_all(&path)?;
+
+        let mut tables = Vec::new();
+        for table in ArchiveTable::ALL {
+            let rows = repository.export_season_table(table, season, league).await?;
+            let file = format!("{}.json.gz", table.table());
+            write_table(&path.join(&file), &rows)?;
+            if read_table(&path.join(&file))? != rows {
+                bail!("archived {} does not match the database", table.table());
+            }
+            tables.push(ArchivedTable { table, rows: rows.len(), file });
+        }
+
+        let manifest = SeasonArchiveManifest {
+            format_version: FORMAT_VERSION,
+            season: season.to_string(),
+            league: league.map(str::to_string),
+            exported_at: Utc::now(),
+            tables,
+        };
+        std::fs::write(path.join(MANIFEST_FILE), serde_json::to_vec_pretty(&manifest)?)?;
+        info!("🗄️ Archived season {}: {} rows to {}", describe(season, league), manifest.total_rows(), path.display());
+
+        let rows_purged = if purge {
+            let purged = repository.delete_season(season, league).await?;
+            info!("🗄️ Removed {} rows of season {} from the live tables", purged, describe(season, league));
+            purged
+        } else {
+            0
+        };
+
+        Ok(SeasonArchiveReport { path, manifest, rows_purged })
+    }
+
+    /// Load an archive directory back into the database, e.g. to backtest
+    /// against a season that is no longer live
+    pub async fn restore(repository: &Repository, path: impl AsRef<Path>) -> Result<SeasonRestoreReport> {
+        let path = path.as_ref();
+        let manifest = read_manifest(path)?;
+
+        let mut tables = Vec::new();
+        for archived in &manifest.tables {
+            let rows = read_table(&path.join(&archived.file))?;
+            if rows.len() != archived.rows {
+                bail!("{} has {} rows, the manifest expects {}", archived.file, rows.len(), archived.rows);
+            }
+            tables.push((archived.table, rows));
+        }
+        // Parents before children, whatever order the manifest lists them in
+        tables.sort_by_key(|(table, _)| ArchiveTable::ALL.iter().position(|t| t == table));
+
+        let rows_restored = repository.restore_season(&tables).await?;
+        info!("🗄️ Restored season {}: {} rows from {}",
+              describe(&manifest.season, manifest.league.as_deref()), rows_restored, path.display());
+
+        Ok(SeasonRestoreReport {
+            season: manifest.season,
+            league: manifest.league,
+            rows_restored,
+            rows_archived: manifest.tables.iter().map(|table| table.rows).sum(),
+        })
+    }
+}
+
+pub fn read_manifest(path: &Path) -> Result<SeasonArchiveManifest> {
+    let file = path.join(MANIFEST_FILE);
+    let manifest: SeasonArchiveManifest = serde_json::from_slice(
+        &std::fs::read(&file).with_context(|| format!("no archive manifest at {}", file.display()))?,
+    )?;
+    if manifest.format_version != FORMAT_VERSION {
+        bail!("unsupported archive format version {}", manifest.format_version);
+    }
+    Ok(manifest)
+}
+
+fn write_table(path: &Path, rows: &[Value]) -> Result<()> {
+    let table = ColumnarTable::from_rows(rows)?;
+    std::fs::write(path, gzip(&serde_json::to_vec(&table)?))?;
+    Ok(())
+}
+
+fn read_table(path: &Path) -> Result<Vec<Value>> {
+    let bytes = std::fs::read(path).with_context(|| format!("cannot read {}", path.display()))?;
+    let table: ColumnarTable = serde_json::from_slice(&gunzip(&bytes)?)?;
+    table.into_rows()
+}
+
+fn slug(value: &str) -> String {
+    let slug: String = value.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    slug.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-")
+}
+
+fn describe(season: &str, league: Option<&str>) -> String {
+    match league {
+        Some(league) => format!("{} ({})", season, league),
+        None => season.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_columnar_table_round_trip() {
+        let dir = std::env::temp_dir().join(format!("season-archive-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("odds.json.gz");
+
+        let rows = vec![
+            json!({ "match_id": "m1", "home_odds": 2.1, "is_active": false }),
+            json!({ "match_id": "m1", "home_odds": 2.05, "is_active": true, "draw_odds": null }),
+            json!({ "match_id": "m2", "home_odds": 1.8, "is_active": false, "draw_odds": 3.4 }),
+        ];
+        write_table(&path, &rows).unwrap();
+
+        let table: ColumnarTable = serde_json::from_slice(&gunzip(&std::fs::read(&path).unwrap()).unwrap()).unwrap();
+        assert_eq!(table.rows, 3);
+        assert_eq!(table.columns["match_id"], vec![json!("m1"), json!("m1"), json!("m2")]);
+        assert_eq!(table.columns["draw_odds"], vec![Value::Null, Value::Null, json!(3.4)]);
+
+        // Columns missing from a row come back as null
+        let restored = read_table(&path).unwrap();
+        assert_eq!(restored[0]["draw_odds"], Value::Null);
+        assert_eq!(restored[1..], rows[1..]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_season_dir() {
+        let archiver = SeasonArchiver::new("archive");
+        assert_eq!(archiver.season_dir("2023/24", None), PathBuf::from("archive/2023-24"));
+        assert_eq!(archiver.season_dir("2023-24", Some("Premier League")), PathBuf::from("archive/2023-24-premier-league"));
+    }
+}
@@ -0,0 +1,173 @@
+// Nightly safety net for dropped settlement events: bets still open on
+// matches the database has as finished are settled from the stored score
+
+use crate::metrics::MetricsCollector;
+use crate::trader::TradingEngine;
+use quant_db::{MatchRecord, MatchRepository, Repository};
+use quant_stream::EventBus;
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// A match's open bets settled from its final score, as published on the
+/// event bus
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SettlementResult {
+    pub match_id: String,
+    pub home_goals: u8,
+    pub away_goals: u8,
+    pub bets_settled: usize,
+    pub settled_at: DateTime<Utc>,
+}
+
+impl SettlementResult {
+    pub fn new(match_id: impl Into<String>, home_goals: u8, away_goals: u8, bets_settled: usize) -> Self {
+        Self { match_id: match_id.into(), home_goals, away_goals, bets_settled, settled_at: Utc::now() }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReconciliationReport {
+    /// Matches with open bets
+    pub matches_checked: usize,
+    /// Finished matches whose bets had been left open
+    pub matches_settled: Vec<String>,
+    pub bets_settled: usize,
+}
+
+pub struct SettlementReconciler {
+    repository: Arc<Repository>,
+    trading_engine: Arc<TradingEngine>,
+    metrics: Option<Arc<MetricsCollector>>,
+    /// Where caught-up settlements are published
+    events: Option<EventBus>,
+    /// Time of day, UTC, the nightly run starts
+    run_at: NaiveTime,
+}
+
+impl SettlementReconciler {
+    /// Runs at 03:00 UTC
+    pub fn new(repository: Arc<Repository>, trading_engine: Arc<TradingEngine>) -> Self {
+        Self {
+            repository,
+            trading_engine,
+            metrics: None,
+            events: None,
+            run_at: NaiveTime::from_hms_opt(3, 0, 0).expect("valid time"),
+        }
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn with_event_bus(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub fn with_run_at(mut self, run_at: NaiveTime) -> Self {
+        self.run_at = run_at;
+        self
+    }
+
+    /// Settle open bets, and accumulators' open legs, on every match stored
+    /// as finished
+    pub async fn run_once(&self) -> Result<ReconciliationReport> {
+        let match_ids: Vec<String> = self.trading_engine.get_active_bets().await
+            .into_iter()
+            .flat_map(|bet| match bet.bet_type.legs() {
+                [] => vec![bet.match_id],
+                legs => legs.iter().filter(|leg| !leg.is_settled()).map(|leg| leg.match_id.clone()).collect(),
+            })
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let mut report = ReconciliationReport {
+            matches_checked: match_ids.len(),
+            ..Default::default()
+        };
+        if match_ids.is_empty() {
+            return Ok(report);
+        }
+
+        for record in self.repository.get_finished_matches(&match_ids).await? {
+            let Some((home, away)) = final_score(&record) else {
+                warn!("🧾 Stored score for {} is out of range, leaving its bets open", record.match_id);
+                continue;
+            };
+            match self.trading_engine.settle_match(&record.match_id, home, away).await {
+                Ok(0) => {}
+                Ok(settled) => {
+                    warn!("🧾 Settled {} bets on {} ({}-{}) that missed their settlement",
+                          settled, record.match_id, home, away);
+                    report.bets_settled += settled;
+                    if let Some(events) = &self.events {
+                        events.publish(SettlementResult::new(record.match_id.clone(), home, away, settled));
+                    }
+                    report.matches_settled.push(record.match_id);
+                }
+                Err(e) => warn!("🧾 Failed to settle {}: {}", record.match_id, e),
+            }
+        }
+
+        if let Some(metrics) = &self.metrics {
+            metrics.record_missed_settlements(report.bets_settled as u64).await;
+        }
+        Ok(report)
+    }
+
+    /// Run now, to catch up after downtime, then nightly until cancelled
+    pub async fn run(&self, shutdown: CancellationToken) {
+        info!("🧾 Settlement reconciliation scheduled daily at {} UTC", self.run_at.format("%H:%M"));
+        loop {
+            match self.run_once().await {
+                Ok(report) => info!("🧾 Reconciliation checked {} matches with open bets, caught {} missed settlements",
+                                    report.matches_checked, report.bets_settled),
+                Err(e) => warn!("🧾 Reconciliation failed: {}", e),
+            }
+
+            let until_next = (next_run(self.run_at, Utc::now()) - Utc::now()).to_std().unwrap_or_default();
+            tokio::select! {
+                _ = tokio::time::sleep(until_next) => {}
+                _ = shutdown.cancelled() => break,
+            }
+        }
+    }
+}
+
+fn final_score(record: &MatchRecord) -> Option<(u8, u8)> {
+    let home = u8::try_from(record.home_score?).ok()?;
+    let away = u8::try_from(record.away_score?).ok()?;
+    Some((home, away))
+}
+
+/// The first `run_at` after `now`
+fn next_run(run_at: NaiveTime, now: DateTime<Utc>) -> DateTime<Utc> {
+    let today = now.date_naive().and_time(run_at).and_utc();
+    if today > now {
+        today
+    } else {
+        today + Duration::days(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_next_run_rolls_to_tomorrow() {
+        let run_at = NaiveTime::from_hms_opt(3, 0, 0).unwrap();
+        let before = Utc.with_ymd_and_hms(2024, 3, 10, 2, 59, 0).unwrap();
+        assert_eq!(next_run(run_at, before), Utc.with_ymd_and_hms(2024, 3, 10, 3, 0, 0).unwrap());
+        let at = Utc.with_ymd_and_hms(2024, 3, 10, 3, 0, 0).unwrap();
+        assert_eq!(next_run(run_at, at), Utc.with_ymd_and_hms(2024, 3, 11, 3, 0, 0).unwrap());
+    }
+}
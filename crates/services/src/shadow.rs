@@ -0,0 +1,141 @@
+// Runs a challenger model in shadow beside the live one: it predicts the same
+// matches from the same features, but only the champion's predictions are
+// traded
+
+use crate::ledger::PredictionLedger;
+use quant_ml::{EvaluationReport, Evaluator, Model};
+use quant_models::{FeatureVector, Outcome1X2, Prediction, SimpleMarketOdds};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+use tokio::sync::RwLock;
+
+/// One model's record over the matches both models predicted
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelScorecard {
+    pub model_name: String,
+    pub model_version: String,
+    /// Brier score, log loss and ROI if every pick had been bet at the
+    /// prices of the time
+    pub report: EvaluationReport,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShadowComparison {
+    pub since: DateTime<Utc>,
+    pub champion: ModelScorecard,
+    pub challenger: ModelScorecard,
+}
+
+/// The challenger is not updated online, so it is judged as it was trained
+pub struct ShadowModel {
+    model: RwLock<Model>,
+    /// The challenger's predictions, with the odds on offer when each was made
+    ledger: PredictionLedger,
+    started_at: DateTime<Utc>,
+}
+
+impl ShadowModel {
+    pub fn new(model: Model) -> Self {
+        Self {
+            model: RwLock::new(model),
+            ledger: PredictionLedger::new(),
+            started_at: Utc::now(),
+        }
+    }
+
+    /// Name and version of the challenger
+    pub async fn model(&self) -> (String, String) {
+        let model = self.model.read().await;
+        (model.model_name().to_string(), model.model_version().to_string())
+    }
+
+    /// Predict from the features the champion saw and keep the prediction
+    /// for scoring once the match is over
+    pub async fn predict(&self, features: &FeatureVector, odds: Option<SimpleMarketOdds>) -> Result<Prediction> {
+        let prediction = self.model.read().await.predict(features).await?;
+        self.ledger.record_prediction(prediction.clone(), odds).await;
+        Ok(prediction)
+    }
+
+    pub async fn record_result(&self, match_id: &str, outcome: Outcome1X2) {
+        self.ledger.record_result(match_id, outcome).await;
+    }
+
+    /// Score the challenger against the champion's predictions in
+    /// `champion_ledger`, on settled matches both have predicted since the
+    /// challenger started
+    pub async fn compare(&self, champion_ledger: &PredictionLedger, champion: (String, String)) -> ShadowComparison {
+        let (challenger_name, challenger_version) = self.model().await;
+        let now = Utc::now();
+        let challenger_samples = self.ledger.samples(&challenger_name, self.started_at, now).await;
+        let shadowed: HashSet<&str> = challenger_samples.iter()
+            .map(|sample| sample.prediction.match_id.as_str())
+            .collect();
+        let champion_samples: Vec<_> = champion_ledger.samples(&champion.0, self.started_at, now).await
+            .into_iter()
+            .filter(|sample| shadowed.contains(sample.prediction.match_id.as_str()))
+            .collect();
+
+        let evaluator = Evaluator::new();
+        ShadowComparison {
+            since: self.started_at,
+            champion: ModelScorecard {
+                model_name: champion.0,
+                model_version: champion.1,
+                report: evaluator.evaluate(&champion_samples),
+            },
+            challenger: ModelScorecard {
+                model_name: challenger_name,
+                model_version: challenger_version,
+                report: evaluator.evaluate(&challenger_samples),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_ml::{EnsembleModel, PoissonModel};
+    use rust_decimal_macros::dec;
+    use std::collections::HashMap;
+
+    fn features(match_id: &str) -> FeatureVector {
+        FeatureVector {
+            match_id: match_id.to_string(),
+            features: HashMap::from([("home_attack_strength".to_string(), 1.4)]),
+            timestamp: Utc::now(),
+            schema: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_challenger_scored_on_the_champions_matches() {
+        let shadow = ShadowModel::new(Model::Poisson(PoissonModel::new()));
+        let champion = Model::Ensemble(EnsembleModel::new());
+        let champion_ledger = PredictionLedger::new();
+        let odds = SimpleMarketOdds::new(dec!(2.0), dec!(3.4), dec!(4.0));
+
+        for match_id in ["m1", "m2", "m3"] {
+            let features = features(match_id);
+            champion_ledger.record_prediction(champion.predict(&features).await.unwrap(), Some(odds.clone())).await;
+            // The challenger only came in after m1
+            if match_id != "m1" {
+                shadow.predict(&features, Some(odds.clone())).await.unwrap();
+            }
+        }
+        for match_id in ["m1", "m2"] {
+            champion_ledger.record_result(match_id, Outcome1X2::HomeWin).await;
+            shadow.record_result(match_id, Outcome1X2::HomeWin).await;
+        }
+
+        let champion_model = (champion.model_name().to_string(), champion.model_version().to_string());
+        let comparison = shadow.compare(&champion_ledger, champion_model).await;
+        assert_eq!(comparison.challenger.model_name, "PoissonGoals");
+        assert_eq!(comparison.challenger.report.sample_count, 1);
+        assert_eq!(comparison.champion.report.sample_count, 1);
+        assert!(comparison.challenger.report.brier_score > 0.0);
+    }
+}
@@ -0,0 +1,200 @@
+// Periodic snapshots of the live engine state to disk, so a crash or deploy
+// can resume trading where it stopped instead of starting cold
+
+use crate::predictor::PredictorService;
+use crate::trader::{TradingEngine, TradingState};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use quant_ml::{FeatureState, ModelState};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+/// Bumped whenever a snapshot written by an older build can't be read as is
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotConfig {
+    pub enabled: bool,
+    /// Written while running, and read back by `--restore`
+    pub path: String,
+    pub interval_seconds: u64,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "data/engine_snapshot.json".to_string(),
+            interval_seconds: 60,
+        }
+    }
+}
+
+/// The portfolio and risk state, what the feature engineer knows about
+/// teams and live matches, and the model's online-learned weights
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineSnapshot {
+    pub version: u32,
+    pub taken_at: DateTime<Utc>,
+    pub trading: TradingState,
+    pub features: FeatureState,
+    pub model: ModelState,
+}
+
+impl EngineSnapshot {
+    /// Write the snapshot to a temporary file beside `path` and rename it
+    /// into place, so a crash mid-write leaves the previous one intact
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("creating {}", parent.display()))?;
+        }
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        let temporary = PathBuf::from(temporary);
+
+        std::fs::write(&temporary, serde_json::to_vec(self)?)
+            .with_context(|| format!("writing {}", temporary.display()))?;
+        std::fs::File::open(&temporary)?.sync_all()?;
+        std::fs::rename(&temporary, path)
+            .with_context(|| format!("renaming {} to {}", temporary.display(), path.display()))?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        let snapshot: Self = serde_json::from_slice(&bytes)
+            .with_context(|| format!("parsing {}", path.display()))?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            bail!("{} is a version {} snapshot, this build reads version {}",
+                  path.display(), snapshot.version, SNAPSHOT_VERSION);
+        }
+        Ok(snapshot)
+    }
+}
+
+/// Takes snapshots of a running engine on an interval and once more at shutdown
+pub struct StateSnapshotter {
+    trading_engine: Arc<TradingEngine>,
+    predictor: Arc<PredictorService>,
+    path: PathBuf,
+    interval: Duration,
+}
+
+impl StateSnapshotter {
+    pub fn new(trading_engine: Arc<TradingEngine>, predictor: Arc<PredictorService>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            trading_engine,
+            predictor,
+            path: path.into(),
+            interval: Duration::from_secs(SnapshotConfig::default().interval_seconds),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval.max(Duration::from_secs(1));
+        self
+    }
+
+    pub async fn capture(&self) -> EngineSnapshot {
+        EngineSnapshot {
+            version: SNAPSHOT_VERSION,
+            taken_at: Utc::now(),
+            trading: self.trading_engine.state().await,
+            features: self.predictor.get_feature_engineer().state(),
+            model: self.predictor.model_state().await,
+        }
+    }
+
+    /// Capture the engine and write it out
+    pub async fn save(&self) -> Result<EngineSnapshot> {
+        let snapshot = self.capture().await;
+        let path = self.path.clone();
+        let written = snapshot.clone();
+        tokio::task::spawn_blocking(move || written.save(path)).await??;
+        Ok(snapshot)
+    }
+
+    /// Snapshot every `interval` until cancelled. The last snapshot is left
+    /// to the caller, once work still in flight at shutdown has finished.
+    pub async fn run(&self, shutdown: CancellationToken) {
+        info!("💾 Snapshotting engine state to {} every {:?}", self.path.display(), self.interval);
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
+            if let Err(e) = self.save().await {
+                error!("💾 Could not snapshot engine state: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_models::{EventType, MatchEvent};
+    use rust_decimal_macros::dec;
+
+    fn event(minute: u8) -> MatchEvent {
+        MatchEvent::new(
+            "snap_match".to_string(),
+            EventType::Goal { team: "Arsenal".to_string(), player: None, minute },
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip_resumes_engine() {
+        let dir = std::env::temp_dir().join(format!("engine-snapshot-{}", uuid::Uuid::new_v4()));
+        let journal = dir.join("journal.log");
+        let path = dir.join("snapshot.json");
+
+        let trading_engine = Arc::new(TradingEngine::new(dec!(1000)).with_journal(&journal).unwrap());
+        let predictor = Arc::new(PredictorService::new());
+        trading_engine.register_match(&event(10)).await;
+        predictor.predict(&event(10)).await.unwrap();
+        predictor.update_team_performance("Arsenal", 3, 1).await;
+        trading_engine.adjust_bankroll(dec!(100), None).await.unwrap();
+        let snapshotter = StateSnapshotter::new(trading_engine.clone(), predictor.clone(), &path);
+        let saved = snapshotter.save().await.unwrap();
+        assert_eq!(saved.trading.journal_seq, 1);
+
+        // Written to the journal after the snapshot, so replayed on top of it
+        trading_engine.adjust_bankroll(dec!(-40), None).await.unwrap();
+
+        let snapshot = EngineSnapshot::load(&path).unwrap();
+        let restored = TradingEngine::new(dec!(1000))
+            .with_restored_state(snapshot.trading)
+            .unwrap()
+            .with_journal(&journal)
+            .unwrap();
+        assert_eq!(restored.get_portfolio_summary().await.available_bankroll, dec!(1060));
+        assert!(restored.get_match_info("snap_match").await.is_some());
+
+        let restarted = PredictorService::new();
+        restarted.get_feature_engineer().restore_state(snapshot.features);
+        restarted.restore_model_state(&snapshot.model).await.unwrap();
+        let context = restarted.get_feature_engineer().get_match_context("snap_match").unwrap();
+        assert_eq!((context.home_score, context.minute), (1, 10));
+        assert_eq!(restarted.get_feature_engineer().get_team_stats("Arsenal").unwrap().goals_for, 3);
+        assert_eq!(restarted.model_state().await.logistic, snapshot.model.logistic);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
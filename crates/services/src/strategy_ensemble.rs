@@ -0,0 +1,179 @@
+// Several betting strategies vote on each opportunity; a quorum decides
+// whether to trade and their stakes are combined into one
+
+use quant_models::{BettingDecision, BettingStrategy};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Name bets placed by the ensemble carry instead of a single strategy's
+pub const ENSEMBLE_STRATEGY: &str = "Strategy Ensemble";
+/// Where each bet keeps the votes behind it
+const VOTES_KEY: &str = "strategy_votes";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StakeSizing {
+    /// Average stake of the strategies that approved
+    #[default]
+    Mean,
+    Median,
+    /// Smallest approving stake: only as much as every backer would put on
+    Min,
+}
+
+/// One strategy's view of an opportunity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategyVote {
+    pub strategy: String,
+    pub approved: bool,
+    /// What the strategy would have staked on its own; zero if it declined
+    pub stake: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnsembleDecision {
+    pub approved: bool,
+    pub stake: Decimal,
+    pub votes: Vec<StrategyVote>,
+}
+
+/// How the bets a strategy voted on have turned out
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StrategyAttribution {
+    pub bets_backed: usize,
+    pub bets_opposed: usize,
+    /// Settled profit or loss of the bets it approved
+    pub profit_loss_backed: Decimal,
+    /// Settled profit or loss of the bets placed over its objection
+    pub profit_loss_opposed: Decimal,
+}
+
+#[derive(Debug, Clone)]
+pub struct StrategyEnsemble {
+    strategies: Vec<BettingStrategy>,
+    min_approvals: usize,
+    sizing: StakeSizing,
+}
+
+impl StrategyEnsemble {
+    /// A simple majority of `strategies` must approve, and the approving
+    /// stakes are averaged
+    pub fn new(strategies: Vec<BettingStrategy>) -> Self {
+        let min_approvals = strategies.len() / 2 + 1;
+        Self {
+            strategies,
+            min_approvals,
+            sizing: StakeSizing::default(),
+        }
+    }
+
+    pub fn with_min_approvals(mut self, min_approvals: usize) -> Self {
+        self.min_approvals = min_approvals.clamp(1, self.strategies.len().max(1));
+        self
+    }
+
+    pub fn with_sizing(mut self, sizing: StakeSizing) -> Self {
+        self.sizing = sizing;
+        self
+    }
+
+    pub fn strategies(&self) -> &[BettingStrategy] {
+        &self.strategies
+    }
+
+    pub fn min_approvals(&self) -> usize {
+        self.min_approvals
+    }
+
+    /// Tally the votes and size the combined stake
+    pub fn decide(&self, votes: Vec<StrategyVote>) -> EnsembleDecision {
+        let mut stakes: Vec<Decimal> = votes.iter()
+            .filter(|vote| vote.approved)
+            .map(|vote| vote.stake)
+            .collect();
+        if stakes.len() < self.min_approvals {
+            return EnsembleDecision { approved: false, stake: Decimal::ZERO, votes };
+        }
+
+        stakes.sort();
+        let stake = match self.sizing {
+            StakeSizing::Mean => stakes.iter().sum::<Decimal>() / Decimal::from(stakes.len()),
+            StakeSizing::Median if stakes.len().is_multiple_of(2) => {
+                (stakes[stakes.len() / 2 - 1] + stakes[stakes.len() / 2]) / Decimal::TWO
+            }
+            StakeSizing::Median => stakes[stakes.len() / 2],
+            StakeSizing::Min => stakes[0],
+        };
+        EnsembleDecision { approved: true, stake: stake.round_dp(2), votes }
+    }
+}
+
+/// Record the votes behind a bet on the bet itself
+pub fn attach_votes(bet: &mut BettingDecision, votes: &[StrategyVote]) {
+    bet.metadata[VOTES_KEY] = serde_json::to_value(votes).unwrap_or_default();
+}
+
+pub fn bet_votes(bet: &BettingDecision) -> Vec<StrategyVote> {
+    bet.metadata.get(VOTES_KEY)
+        .and_then(|votes| serde_json::from_value(votes.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Per-strategy record over the bets the ensemble placed
+pub fn attribute<'a>(bets: impl IntoIterator<Item = &'a BettingDecision>) -> BTreeMap<String, StrategyAttribution> {
+    let mut attribution: BTreeMap<String, StrategyAttribution> = BTreeMap::new();
+    for bet in bets {
+        let profit_loss = bet.base_settled_profit_loss().unwrap_or_default();
+        for vote in bet_votes(bet) {
+            let entry = attribution.entry(vote.strategy).or_default();
+            if vote.approved {
+                entry.bets_backed += 1;
+                entry.profit_loss_backed += profit_loss;
+            } else {
+                entry.bets_opposed += 1;
+                entry.profit_loss_opposed += profit_loss;
+            }
+        }
+    }
+    attribution
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_models::{BetStatus, BetType};
+    use rust_decimal_macros::dec;
+
+    fn vote(strategy: &str, stake: Option<Decimal>) -> StrategyVote {
+        StrategyVote {
+            strategy: strategy.to_string(),
+            approved: stake.is_some(),
+            stake: stake.unwrap_or_default(),
+        }
+    }
+
+    #[test]
+    fn test_quorum_and_stake_sizing() {
+        let strategies = vec![BettingStrategy::conservative(), BettingStrategy::moderate(), BettingStrategy::aggressive()];
+        let ensemble = StrategyEnsemble::new(strategies.clone());
+        assert_eq!(ensemble.min_approvals(), 2);
+
+        let split = vec![vote("a", None), vote("b", Some(dec!(20))), vote("c", Some(dec!(50)))];
+        let decision = ensemble.decide(split.clone());
+        assert!(decision.approved);
+        assert_eq!(decision.stake, dec!(35));
+        assert_eq!(StrategyEnsemble::new(strategies.clone()).with_sizing(StakeSizing::Min).decide(split.clone()).stake, dec!(20));
+        assert!(!StrategyEnsemble::new(strategies).with_min_approvals(3).decide(split.clone()).approved);
+
+        // Votes ride on the bet and attribute its result
+        let mut bet = BettingDecision::new(
+            "m1".to_string(), BetType::HomeWin, dec!(35), dec!(2.0), 0.6, ENSEMBLE_STRATEGY.to_string(),
+        ).unwrap();
+        attach_votes(&mut bet, &split);
+        bet.status = BetStatus::Won;
+        let attribution = attribute([&bet]);
+        assert_eq!(attribution["a"].profit_loss_opposed, dec!(35));
+        assert_eq!((attribution["c"].bets_backed, attribution["c"].profit_loss_backed), (1, dec!(35)));
+    }
+}
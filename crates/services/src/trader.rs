@@ -1,22 +1,195 @@
+use crate::audit::{MemoryAuditLog, TradeAuditLog};
+use crate::betfair::ladder_ticks;
+use crate::blotter::{Blotter, BlotterMessage};
+use crate::blending::{LeagueSkill, ProbabilityBlender};
+use crate::daily_loss::{DailyLossStatus, DailyLossTracker, DailyResetSchedule};
+use crate::execution::{ExecutionVenue, OrderReceipt, PaperVenue, StakeLimits};
+use crate::journal::{JournalRecord, TradeJournal};
+use crate::strategy_ensemble::{self, StrategyAttribution, StrategyEnsemble, StrategyVote, ENSEMBLE_STRATEGY};
+use quant_ml::{EvaluationSample, PoissonModel, DEFAULT_TOTAL_GOALS};
 use quant_models::{
     Prediction, BettingDecision, BetType, BettingStrategy, Portfolio, 
-    SimpleMarketOdds, RiskTolerance, QuantsError, Result
+    SimpleMarketOdds, RiskTolerance, QuantsError, Result, MatchEvent,
+    MarketOdds, OddsBook, BestOdds, BestPrice, Promotion, net_odds, free_bet_expected_value,
+    BetStatus, DerivedMarketOdds, DoubleChanceSelection, EquityCurve, AsianHandicapOdds,
+    HandicapResult, CorrectScoreOdds, ScoreMatrix, MarketFilter, OddsTolerance, DomainEvent, RiskLimit,
+    AuditEntry, AuditEvent, StakeContext, StakingPlan, RiskConstraint, ExecutionOutcome, execution_key, Currency, FixedRates, RateProvider,
+    OddsFormat, kelly_fraction, BankrollAdjustment, HalfResultOdds, MatchHalf, Outcome1X2,
+    AccumulatorLeg, BetSlip,
 };
+use quant_stream::DomainEventBus;
 use rust_decimal::Decimal;
-use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use rust_decimal_macros::dec;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{info, warn, debug, error};
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Executed trades and generated signals kept in memory for the API
+const DEFAULT_HISTORY_CAPACITY: usize = 1_000;
+
+/// Odds ceiling for exact scores, which are priced past every strategy's
+const MAX_CORRECT_SCORE_ODDS: Decimal = dec!(15.0);
+
+/// Concentration is only judged once there are enough open bets for it to mean anything
+const MIN_BETS_FOR_CONCENTRATION: usize = 3;
 
 pub struct TradingEngine {
     portfolio: Arc<RwLock<Portfolio>>,
     strategies: HashMap<String, BettingStrategy>,
     market_odds: Arc<RwLock<HashMap<String, SimpleMarketOdds>>>,
+    odds_books: Arc<RwLock<HashMap<String, OddsBook>>>,
+    derived_odds: Arc<RwLock<HashMap<String, DerivedMarketOdds>>>,
+    asian_handicap_odds: Arc<RwLock<HashMap<String, AsianHandicapOdds>>>,
+    correct_score_odds: Arc<RwLock<HashMap<String, CorrectScoreOdds>>>,
+    half_result_odds: Arc<RwLock<HashMap<String, HalfResultOdds>>>,
+    /// Money available in each match's markets, where known
+    market_liquidity: Arc<RwLock<HashMap<String, Decimal>>>,
     risk_manager: RiskManager,
+    match_info: Arc<RwLock<HashMap<String, MatchInfo>>>,
     trade_count: Arc<RwLock<u64>>,
+    journal: Option<Arc<TradeJournal>>,
+    /// Commission on net winnings per venue, keyed by lowercase bookmaker name
+    venue_commissions: HashMap<String, Decimal>,
+    /// Stakes each execution venue accepts, keyed by lowercase venue name
+    venue_stake_limits: HashMap<String, StakeLimits>,
+    /// Currency bookmakers quote in, keyed by lowercase name; the rest quote
+    /// in the portfolio's currency
+    bookmaker_currencies: HashMap<String, Currency>,
+    rates: Arc<dyn RateProvider>,
+    promotions: Arc<RwLock<HashMap<Uuid, Promotion>>>,
+    trade_history: Arc<RwLock<VecDeque<BettingDecision>>>,
+    signal_history: Arc<RwLock<VecDeque<TradingSignal>>>,
+    history_capacity: usize,
+    blender: Arc<RwLock<ProbabilityBlender>>,
+    /// When set, these strategies vote on every opportunity instead of the
+    /// active strategy deciding alone
+    ensemble: Option<StrategyEnsemble>,
+    /// Where executed trades are placed
+    venue: Arc<dyn ExecutionVenue>,
+    /// Where bets, settlements and risk breaches are published
+    events: Option<DomainEventBus>,
+    /// Trail of signals, risk decisions and executions, which also stops a
+    /// bet being executed twice
+    audit: Arc<dyn TradeAuditLog>,
+    /// Every order lifecycle step as it happens, for `/ws/blotter`
+    blotter: Arc<Blotter>,
+    /// Virtual portfolios paper trading each enabled strategy preset on its
+    /// own bankroll, keyed by preset name
+    strategy_books: Arc<RwLock<BTreeMap<String, Portfolio>>>,
+    /// First journal entry not yet reflected in a restored portfolio
+    journal_replay_from: u64,
+    /// Slots and stake held for orders out at the venue but not yet booked
+    reservations: Arc<std::sync::Mutex<Reservations>>,
+}
+
+#[derive(Debug, Default)]
+struct Reservations {
+    bets: usize,
+    stake: Decimal,
+}
+
+/// One order's hold on an open-bet slot and its stake, released when dropped
+struct Reservation {
+    reservations: Arc<std::sync::Mutex<Reservations>>,
+    stake: Decimal,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        let mut reserved = self.reservations.lock().unwrap();
+        reserved.bets -= 1;
+        reserved.stake -= self.stake;
+    }
+}
+
+/// The match a bet is on, and each of an accumulator's legs' matches
+fn covered_matches(bet: &BettingDecision) -> impl Iterator<Item = &str> {
+    std::iter::once(bet.match_id.as_str()).chain(bet.bet_type.legs().iter().map(|leg| leg.match_id.as_str()))
+}
+
+/// League and teams for a match, used to group correlated positions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchInfo {
+    pub league: String,
+    pub season: String,
+    pub team_home: String,
+    pub team_away: String,
+    /// Score at half time, once the first half's bets have been settled
+    pub half_time_score: Option<(u8, u8)>,
+    /// When the match's bets were settled or voided at full time
+    #[serde(default)]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl MatchInfo {
+    fn exposure_groups(&self) -> [ExposureGroup; 3] {
+        [
+            ExposureGroup::League(self.league.clone()),
+            ExposureGroup::Team(self.team_home.clone()),
+            ExposureGroup::Team(self.team_away.clone()),
+        ]
+    }
+}
+
+/// A league or team whose fixtures' outcomes move together
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ExposureGroup {
+    League(String),
+    Team(String),
+}
+
+/// Caps on open stake per league and per team, as fractions of bankroll.
+/// Unset caps fall back to `max_correlated_exposure`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExposureLimits {
+    /// Cap for any one league
+    #[serde(default)]
+    pub max_league: Option<f64>,
+    /// Cap for any one team, counting the full stake of each of its matches
+    #[serde(default)]
+    pub max_team: Option<f64>,
+    /// Caps for particular leagues by name, overriding `max_league`
+    #[serde(default)]
+    pub leagues: HashMap<String, f64>,
+    /// Caps for particular teams by name, overriding `max_team`
+    #[serde(default)]
+    pub teams: HashMap<String, f64>,
+}
+
+impl ExposureLimits {
+    fn limit_for(&self, group: &ExposureGroup, default: f64) -> f64 {
+        match group {
+            ExposureGroup::League(league) => {
+                self.leagues.get(league).copied().or(self.max_league).unwrap_or(default)
+            }
+            ExposureGroup::Team(team) => {
+                self.teams.get(team).copied().or(self.max_team).unwrap_or(default)
+            }
+        }
+    }
+}
+
+/// Open stake on one league or team against its cap
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupExposure {
+    pub name: String,
+    pub exposure: Decimal,
+    pub limit: Decimal,
+    /// Exposure as a fraction of the limit
+    pub utilization: f64,
+}
+
+/// Open stake per league and per team, largest first
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExposureReport {
+    pub leagues: Vec<GroupExposure>,
+    pub teams: Vec<GroupExposure>,
 }
 
 #[derive(Debug, Clone)]
@@ -25,20 +198,210 @@ pub struct RiskManager {
     pub max_concurrent_bets: usize,
     pub max_exposure_per_match: Decimal,
     pub correlation_threshold: f64,
-    pub current_daily_loss: Decimal,
-    pub daily_reset_time: DateTime<Utc>,
+    /// Max combined stake on any league/team group, as a fraction of bankroll
+    pub max_correlated_exposure: f64,
+    /// Per-league and per-team caps, where they differ from `max_correlated_exposure`
+    pub exposure_limits: ExposureLimits,
+    /// Herfindahl index of open exposure above which a dimension is flagged
+    pub max_concentration: f64,
+    /// Max stake on an exact score, as a fraction of available bankroll
+    pub max_correct_score_stake: f64,
+    /// Realized losses since the last daily reset, fed by settlements
+    pub daily_loss: Arc<RwLock<DailyLossTracker>>,
+    /// Drawdowns from peak equity, ascending, that are announced when crossed
+    pub drawdown_thresholds: Vec<f64>,
 }
 
-#[derive(Debug, Clone)]
+/// The engine's live trading state, saved so a restart can resume it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradingState {
+    pub portfolio: Portfolio,
+    pub strategy_books: BTreeMap<String, Portfolio>,
+    pub trade_count: u64,
+    pub match_info: HashMap<String, MatchInfo>,
+    pub daily_loss: DailyLossTracker,
+    /// Journal entries before this one are already in `portfolio`
+    pub journal_seq: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct TradingSignal {
+    pub id: Uuid,
     pub match_id: String,
+    /// The prediction the signal was generated from
+    pub prediction_id: Option<Uuid>,
     pub signal_strength: f64,
     pub recommended_bet: Option<BettingDecision>,
     pub risk_assessment: RiskAssessment,
     pub reasoning: String,
+    /// Markets passed over because a strategy's filter kept it out
+    pub rejections: Vec<MarketRejection>,
+    pub generated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone)]
+/// Why a strategy stayed out of a market regardless of edge
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum MarketRejection {
+    Overround {
+        strategy: String,
+        market: String,
+        overround: f64,
+        max_overround: f64,
+    },
+    Liquidity {
+        strategy: String,
+        /// None when no liquidity figure is known for the match
+        liquidity: Option<Decimal>,
+        min_liquidity: Decimal,
+    },
+}
+
+impl TradingSignal {
+    pub fn new(
+        match_id: String,
+        signal_strength: f64,
+        recommended_bet: Option<BettingDecision>,
+        risk_assessment: RiskAssessment,
+        reasoning: String,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            match_id,
+            prediction_id: None,
+            signal_strength,
+            recommended_bet,
+            risk_assessment,
+            reasoning,
+            rejections: Vec::new(),
+            generated_at: Utc::now(),
+        }
+    }
+
+    pub fn with_rejections(mut self, rejections: Vec<MarketRejection>) -> Self {
+        self.rejections = rejections;
+        self
+    }
+
+    pub fn with_prediction(mut self, prediction_id: Uuid) -> Self {
+        self.prediction_id = Some(prediction_id);
+        self
+    }
+}
+
+/// Narrows trade and signal history queries; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub match_id: Option<String>,
+    pub strategy: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl HistoryFilter {
+    fn accepts(&self, match_id: &str, strategy: Option<&str>, at: DateTime<Utc>) -> bool {
+        self.match_id.as_deref().is_none_or(|id| id == match_id)
+            && self.strategy.as_deref().is_none_or(|wanted| strategy == Some(wanted))
+            && self.from.is_none_or(|from| at >= from)
+            && self.to.is_none_or(|to| at < to)
+    }
+}
+
+/// Narrows a value bet scan; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct ValueBetFilter {
+    /// Least the true probability must beat the implied one by
+    pub min_edge: f64,
+    pub league: Option<String>,
+}
+
+/// A quoted price the latest prediction says pays more than it should
+#[derive(Debug, Clone, Serialize)]
+pub struct ValueBetOpportunity {
+    pub match_id: String,
+    pub league: Option<String>,
+    pub bookmaker: String,
+    pub bet_type: BetType,
+    pub odds: Decimal,
+    pub true_prob: f64,
+    pub implied_prob: f64,
+    pub edge: f64,
+    pub expected_value: f64,
+    /// Net of the bookmaker's commission
+    pub kelly_fraction: f64,
+    /// What the active strategy would stake, before portfolio risk limits
+    pub recommended_stake: Decimal,
+}
+
+/// What a what-if trade is sized with; unset fields follow the live engine
+#[derive(Debug, Clone, Default)]
+pub struct TradeSimulationParams {
+    /// Strategy preset to size with instead of the active strategy
+    pub strategy: Option<String>,
+    /// Only this match winner outcome
+    pub bet_type: Option<BetType>,
+    /// Price to size at, commission free, instead of the best quoted one
+    pub odds: Option<Decimal>,
+}
+
+/// A limit that cut a what-if stake down or ruled the bet out, in the
+/// order the engine applies them
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "constraint", rename_all = "snake_case")]
+pub enum StakeConstraint {
+    /// The strategy's market filter keeps it out
+    MarketFilter { rejection: MarketRejection },
+    /// Odds outside the strategy's range, or too little edge or confidence
+    StrategyFilter { strategy: String },
+    /// The strategy's cap on stake as a share of bankroll
+    MaxStakePercent { max_stake_percent: f64 },
+    /// More than the available bankroll, so 95% of it is staked instead
+    Bankroll { available: Decimal },
+    MatchExposure { limit: Decimal },
+    /// A league or team cap leaves only `allowed`
+    CorrelatedExposure { allowed: Decimal },
+    DailyLoss { limit: Decimal },
+    ConcurrentBets { limit: usize },
+    /// Fitted to the venue's minimum, maximum and stake increment
+    StakeLimits { venue: String },
+    /// The bet would be turned away at execution as too risky
+    RiskScore { risk_score: f64 },
+    /// The daily loss kill switch has stopped all trading
+    TradingHalted,
+}
+
+/// One outcome sized the way the engine would size it, without placing it
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedTrade {
+    pub match_id: String,
+    pub strategy: String,
+    pub bet_type: BetType,
+    pub bookmaker: String,
+    pub odds: Decimal,
+    pub commission: Decimal,
+    /// After blending with the market, as the engine trades on it
+    pub true_probability: f64,
+    /// Net of commission, like the two below
+    pub edge: f64,
+    pub expected_value: f64,
+    pub kelly_fraction: f64,
+    /// What the strategy's staking plan asks for before any cap or limit;
+    /// its fractional Kelly stake unless another plan is configured
+    pub kelly_stake: Decimal,
+    /// In the base currency; zero when the bet would not be placed
+    pub stake: Decimal,
+    pub binding_constraints: Vec<StakeConstraint>,
+}
+
+/// Whose bankroll a prediction is sized against: the live portfolio under
+/// the active strategy or ensemble, or one strategy's virtual book
+#[derive(Clone, Copy)]
+enum Seat<'a> {
+    Live,
+    Virtual { strategy: &'a BettingStrategy, portfolio: &'a Portfolio },
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct RiskAssessment {
     pub risk_score: f64, // 0.0 (low) to 1.0 (high)
     pub correlation_risk: f64,
@@ -60,37 +423,408 @@ impl TradingEngine {
             max_concurrent_bets: 10,
             max_exposure_per_match: initial_bankroll * dec!(0.1), // 10% per match
             correlation_threshold: 0.7,
-            current_daily_loss: dec!(0.0),
-            daily_reset_time: Utc::now(),
+            max_correlated_exposure: 0.15, // 15% per league/team group
+            exposure_limits: ExposureLimits::default(),
+            max_concentration: 0.5,
+            max_correct_score_stake: 0.005, // 0.5% of bankroll
+            daily_loss: Arc::new(RwLock::new(DailyLossTracker::default())),
+            drawdown_thresholds: vec![0.05, 0.1, 0.2],
         };
 
         Self {
             portfolio: Arc::new(RwLock::new(Portfolio::new(initial_bankroll))),
             strategies,
             market_odds: Arc::new(RwLock::new(HashMap::new())),
+            odds_books: Arc::new(RwLock::new(HashMap::new())),
+            derived_odds: Arc::new(RwLock::new(HashMap::new())),
+            asian_handicap_odds: Arc::new(RwLock::new(HashMap::new())),
+            correct_score_odds: Arc::new(RwLock::new(HashMap::new())),
+            half_result_odds: Arc::new(RwLock::new(HashMap::new())),
+            market_liquidity: Arc::new(RwLock::new(HashMap::new())),
             risk_manager,
+            match_info: Arc::new(RwLock::new(HashMap::new())),
             trade_count: Arc::new(RwLock::new(0)),
+            journal: None,
+            venue_commissions: HashMap::new(),
+            venue_stake_limits: HashMap::new(),
+            bookmaker_currencies: HashMap::new(),
+            rates: Arc::new(FixedRates::default()),
+            promotions: Arc::new(RwLock::new(HashMap::new())),
+            trade_history: Arc::new(RwLock::new(VecDeque::new())),
+            signal_history: Arc::new(RwLock::new(VecDeque::new())),
+            history_capacity: DEFAULT_HISTORY_CAPACITY,
+            blender: Arc::new(RwLock::new(ProbabilityBlender::new())),
+            ensemble: None,
+            venue: Arc::new(PaperVenue::new()),
+            events: None,
+            audit: Arc::new(MemoryAuditLog::new()),
+            blotter: Arc::new(Blotter::new()),
+            strategy_books: Arc::new(RwLock::new(BTreeMap::new())),
+            journal_replay_from: 0,
+            reservations: Arc::new(std::sync::Mutex::new(Reservations::default())),
+        }
+    }
+
+    /// Resume from the state an earlier run saved. Must come before
+    /// `with_journal`, which then replays only the entries written since.
+    pub fn with_restored_state(mut self, state: TradingState) -> Result<Self> {
+        const SHARED: &str = "state must be restored before the engine is shared";
+        let portfolio = Arc::get_mut(&mut self.portfolio).expect(SHARED).get_mut();
+        if state.portfolio.currency != portfolio.currency {
+            return Err(QuantsError::Config(format!(
+                "saved portfolio is in {} but trading is in {}", state.portfolio.currency, portfolio.currency
+            )));
+        }
+        *portfolio = state.portfolio;
+        *Arc::get_mut(&mut self.strategy_books).expect(SHARED).get_mut() = state.strategy_books;
+        *Arc::get_mut(&mut self.trade_count).expect(SHARED).get_mut() = state.trade_count;
+        *Arc::get_mut(&mut self.match_info).expect(SHARED).get_mut() = state.match_info;
+        let daily_loss = Arc::get_mut(&mut self.risk_manager.daily_loss).expect(SHARED).get_mut();
+        let schedule = daily_loss.schedule();
+        *daily_loss = state.daily_loss;
+        daily_loss.set_schedule(schedule);
+        self.journal_replay_from = state.journal_seq;
+        Ok(self)
+    }
+
+    /// Journal every trade and settlement to `path`, first replaying whatever
+    /// the journal already holds so the portfolio resumes where it left off.
+    pub fn with_journal(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        let (journal, entries) = TradeJournal::open(path)?;
+
+        {
+            let portfolio = Arc::get_mut(&mut self.portfolio)
+                .expect("journal must be attached before the engine is shared")
+                .get_mut();
+            let trade_count = Arc::get_mut(&mut self.trade_count)
+                .expect("journal must be attached before the engine is shared")
+                .get_mut();
+            let trade_history = Arc::get_mut(&mut self.trade_history)
+                .expect("journal must be attached before the engine is shared")
+                .get_mut();
+            let daily_loss = Arc::get_mut(&mut self.risk_manager.daily_loss)
+                .expect("journal must be attached before the engine is shared")
+                .get_mut();
+            let now = Utc::now();
+
+            let next_seq = entries.last().map_or(0, |entry| entry.seq + 1);
+            if next_seq < self.journal_replay_from {
+                warn!("📓 Journal ends at #{} but the restored state was saved at #{}; replaying none of it",
+                      next_seq, self.journal_replay_from);
+            }
+            let entries: Vec<_> = entries.into_iter()
+                .filter(|entry| entry.seq >= self.journal_replay_from)
+                .collect();
+            // An entry the portfolio refuses, e.g. one written before a
+            // failed mutation, is skipped so the rest still restore
+            for entry in &entries {
+                let replayed = match &entry.record {
+                    JournalRecord::BetPlaced { bet } => {
+                        portfolio.place_bet((**bet).clone()).map(|()| {
+                            *trade_count += 1;
+                            push_bounded(trade_history, (**bet).clone(), self.history_capacity);
+                        })
+                    }
+                    JournalRecord::BetSettled { bet_id, won } => {
+                        portfolio.settle_bet_at(*bet_id, *won, entry.timestamp).map(|()| {
+                            daily_loss.record(entry.timestamp, last_settled_profit_loss(portfolio), now);
+                        })
+                    }
+                    JournalRecord::BetVoided { bet_id } => {
+                        portfolio.void_bet_at(*bet_id, entry.timestamp)
+                    }
+                    JournalRecord::BetHalfSettled { bet_id, won } => {
+                        portfolio.settle_half_bet_at(*bet_id, *won, entry.timestamp).map(|()| {
+                            daily_loss.record(entry.timestamp, last_settled_profit_loss(portfolio), now);
+                        })
+                    }
+                    JournalRecord::AccumulatorLegSettled { bet_id, match_id, status } => {
+                        portfolio.settle_leg_at(*bet_id, match_id, status.clone(), entry.timestamp).map(|closed| {
+                            if closed.is_some() {
+                                daily_loss.record(entry.timestamp, last_settled_profit_loss(portfolio), now);
+                            }
+                        })
+                    }
+                    JournalRecord::BankrollAdjusted { amount, reason } => {
+                        portfolio.adjust_bankroll_at(*amount, reason.clone(), entry.timestamp).map(|_| ())
+                    }
+                };
+                if let Err(e) = replayed {
+                    warn!("📓 Skipping journal entry #{}: {}", entry.seq, e);
+                }
+            }
+            if daily_loss.check(self.risk_manager.max_daily_loss, now) {
+                warn!("🛑 Journal shows the daily loss limit of {} already reached; trading halted until {}",
+                      self.risk_manager.max_daily_loss, daily_loss.schedule().next_reset(now));
+            }
+
+            if !entries.is_empty() {
+                info!("📓 Replayed {} journal entries: {} active bets, ${} available",
+                      entries.len(), portfolio.active_bets.len(), portfolio.available_bankroll);
+            }
+        }
+
+        self.journal = Some(Arc::new(journal));
+        Ok(self)
+    }
+
+    pub fn with_max_correlated_exposure(mut self, fraction: f64) -> Self {
+        self.risk_manager.max_correlated_exposure = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Cap open stake per league and per team separately, and for particular ones by name
+    pub fn with_exposure_limits(mut self, mut limits: ExposureLimits) -> Self {
+        limits.max_league = limits.max_league.map(|fraction| fraction.clamp(0.0, 1.0));
+        limits.max_team = limits.max_team.map(|fraction| fraction.clamp(0.0, 1.0));
+        for fraction in limits.leagues.values_mut().chain(limits.teams.values_mut()) {
+            *fraction = fraction.clamp(0.0, 1.0);
+        }
+        self.risk_manager.exposure_limits = limits;
+        self
+    }
+
+    /// Blend model probabilities toward the market before computing edges
+    pub fn with_blender(mut self, blender: ProbabilityBlender) -> Self {
+        self.blender = Arc::new(RwLock::new(blender));
+        self
+    }
+
+    pub fn with_strategy_ensemble(mut self, ensemble: StrategyEnsemble) -> Self {
+        self.ensemble = Some(ensemble);
+        self
+    }
+
+    /// Send executed trades to `venue` instead of only paper trading them
+    pub fn with_execution_venue(mut self, venue: Arc<dyn ExecutionVenue>) -> Self {
+        self.venue = venue;
+        self
+    }
+
+    /// Write the audit trail to `audit`, e.g. the database so execution
+    /// idempotency survives a restart
+    pub fn with_audit_log(mut self, audit: Arc<dyn TradeAuditLog>) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    /// Size the `preset` strategy's bets with `plan` instead of fractional Kelly
+    pub fn with_staking_plan(mut self, preset: &str, plan: Arc<dyn StakingPlan>) -> Self {
+        if let Some(strategy) = self.strategies.get_mut(preset) {
+            strategy.staking = Some(plan);
+        }
+        self
+    }
+
+    /// Keep the `preset` strategy out of markets `filter` rejects
+    pub fn with_market_filter(mut self, preset: &str, filter: MarketFilter) -> Self {
+        if let Some(strategy) = self.strategies.get_mut(preset) {
+            strategy.market_filter = filter;
+        }
+        self
+    }
+
+    /// Call off the `preset` strategy's trades when the price has moved
+    /// further than `tolerance` allows by the time they are executed
+    pub fn with_odds_tolerance(mut self, preset: &str, tolerance: OddsTolerance) -> Self {
+        if let Some(strategy) = self.strategies.get_mut(preset) {
+            strategy.odds_tolerance = tolerance;
+        }
+        self
+    }
+
+    /// Also paper trade each of `presets` (`conservative`, `moderate`,
+    /// `aggressive`) on a virtual bankroll of its own, so their live
+    /// performance can be compared. Every prediction is sized for each of
+    /// them independently of the live book. Unknown presets are ignored;
+    /// books are in the currency set by `with_currency`.
+    pub fn with_strategy_books(self, presets: &[String], bankroll: Decimal) -> Self {
+        let currency = self.portfolio.try_read()
+            .map(|portfolio| portfolio.currency)
+            .unwrap_or_default();
+        // Books restored from a saved state carry on where they left off
+        let restored = self.strategy_books.try_read()
+            .map(|books| books.clone())
+            .unwrap_or_default();
+        let books = presets.iter()
+            .filter(|preset| self.strategies.contains_key(*preset))
+            .map(|preset| {
+                let book = restored.get(preset).cloned()
+                    .unwrap_or_else(|| Portfolio::new(bankroll).with_currency(currency));
+                (preset.clone(), book)
+            })
+            .collect();
+        Self { strategy_books: Arc::new(RwLock::new(books)), ..self }
+    }
+
+    /// How many executed trades and generated signals to keep in memory
+    pub fn with_history_capacity(mut self, capacity: usize) -> Self {
+        self.history_capacity = capacity.max(1);
+        self
+    }
+
+    /// Roll the daily loss limit over on this schedule rather than at midnight UTC
+    pub fn with_daily_reset(mut self, schedule: DailyResetSchedule) -> Self {
+        Arc::get_mut(&mut self.risk_manager.daily_loss)
+            .expect("daily reset must be scheduled before the engine is shared")
+            .get_mut()
+            .set_schedule(schedule);
+        self
+    }
+
+    pub fn with_event_bus(mut self, events: DomainEventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Announce drawdowns from peak equity at these fractions instead of 5%, 10% and 20%
+    pub fn with_drawdown_thresholds(mut self, mut thresholds: Vec<f64>) -> Self {
+        thresholds.retain(|threshold| *threshold > 0.0 && *threshold <= 1.0);
+        thresholds.sort_by(f64::total_cmp);
+        thresholds.dedup();
+        self.risk_manager.drawdown_thresholds = thresholds;
+        self
+    }
+
+    pub fn with_max_concentration(mut self, hhi: f64) -> Self {
+        self.risk_manager.max_concentration = hhi.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Cap stakes on exact scores at this fraction of available bankroll
+    pub fn with_max_correct_score_stake(mut self, fraction: f64) -> Self {
+        self.risk_manager.max_correct_score_stake = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Charge commission on winnings at these venues when pricing bets there
+    pub fn with_venue_commissions(mut self, rates: HashMap<String, Decimal>) -> Self {
+        self.venue_commissions = rates
+            .into_iter()
+            .map(|(venue, rate)| (venue.to_lowercase(), rate))
+            .collect();
+        self
+    }
+
+    pub fn commission_for(&self, bookmaker: &str) -> Decimal {
+        self.venue_commissions
+            .get(&bookmaker.to_lowercase())
+            .copied()
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    /// Round and bound stakes to what each execution venue accepts, in the
+    /// portfolio's currency
+    pub fn with_venue_stake_limits(mut self, limits: HashMap<String, StakeLimits>) -> Self {
+        self.venue_stake_limits = limits
+            .into_iter()
+            .map(|(venue, limits)| (venue.to_lowercase(), limits))
+            .collect();
+        self
+    }
+
+    /// Stake limits of the venue trades are executed on
+    pub fn stake_limits(&self) -> StakeLimits {
+        self.venue_stake_limits
+            .get(&self.venue.name().to_lowercase())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Keep the bankroll and report in `currency`, converting bets placed in
+    /// other currencies with `rates`
+    pub fn with_currency(mut self, currency: Currency, rates: Arc<dyn RateProvider>) -> Self {
+        Arc::get_mut(&mut self.portfolio)
+            .expect("currency must be set before the engine is shared")
+            .get_mut()
+            .currency = currency;
+        self.rates = rates;
+        self
+    }
+
+    /// Bookmakers quoting in a currency other than the portfolio's
+    pub fn with_bookmaker_currencies(mut self, currencies: HashMap<String, Currency>) -> Self {
+        self.bookmaker_currencies = currencies
+            .into_iter()
+            .map(|(bookmaker, currency)| (bookmaker.to_lowercase(), currency))
+            .collect();
+        self
+    }
+
+    /// What `bookmaker` quotes in, and the value of one unit of it in the
+    /// portfolio's currency
+    async fn currency_for(&self, bookmaker: &str) -> Result<(Currency, Decimal)> {
+        let base = self.portfolio.read().await.currency;
+        let currency = self.bookmaker_currencies.get(&bookmaker.to_lowercase()).copied().unwrap_or(base);
+        let rate = self.rates.rate(currency, base).ok_or(QuantsError::MissingRate { from: currency, to: base })?;
+        Ok((currency, rate))
+    }
+
+    /// Remember league/team context for a match so correlated bets can be grouped
+    pub async fn register_match(&self, event: &MatchEvent) {
+        self.match_info.write().await
+            .entry(event.match_id.clone())
+            .or_insert_with(|| MatchInfo {
+                league: event.league.clone(),
+                season: event.season.clone(),
+                team_home: event.team_home.clone(),
+                team_away: event.team_away.clone(),
+                half_time_score: None,
+                finished_at: None,
+            });
+    }
+
+    /// Mark a match finished, then forget the longest-finished matches past
+    /// the history capacity. Matches an open bet still covers, in the live
+    /// portfolio or a strategy book, are kept.
+    async fn retire_match(&self, match_id: &str) {
+        let mut open: HashSet<String> = self.strategy_books.read().await.values()
+            .flat_map(|book| &book.active_bets)
+            .flat_map(covered_matches)
+            .map(str::to_string)
+            .collect();
+        open.extend(self.portfolio.read().await.active_bets.iter().flat_map(covered_matches).map(str::to_string));
+
+        let mut match_info = self.match_info.write().await;
+        if let Some(info) = match_info.get_mut(match_id) {
+            info.finished_at.get_or_insert_with(Utc::now);
+        }
+        let mut finished: Vec<(DateTime<Utc>, String)> = match_info.iter()
+            .filter(|(id, _)| !open.contains(*id))
+            .filter_map(|(id, info)| info.finished_at.map(|at| (at, id.clone())))
+            .collect();
+        if finished.len() <= self.history_capacity {
+            return;
+        }
+        finished.sort();
+        let excess = finished.len() - self.history_capacity;
+        for (_, id) in finished.into_iter().take(excess) {
+            match_info.remove(&id);
         }
+        debug!("🏁 Forgot {} finished matches", excess);
     }
 
     pub async fn process_prediction(&self, prediction: &Prediction) -> Result<TradingSignal> {
         debug!("🧮 Processing prediction for match {}", prediction.match_id);
 
-        let market_odds = self.get_market_odds(&prediction.match_id).await;
+        let book = self.odds_book_with_feed(&prediction.match_id).await;
+        let market_odds = self.best_odds(&book).await;
         
         if market_odds.is_none() {
             warn!("📊 No market odds available for match {}", prediction.match_id);
-            return Ok(TradingSignal {
-                match_id: prediction.match_id.clone(),
-                signal_strength: 0.0,
-                recommended_bet: None,
-                risk_assessment: RiskAssessment::default(),
-                reasoning: "No market odds available".to_string(),
-            });
+            return Ok(TradingSignal::new(
+                prediction.match_id.clone(),
+                0.0,
+                None,
+                RiskAssessment::default(),
+                "No market odds available".to_string(),
+            ));
         }
 
         let odds = market_odds.unwrap();
-        let signal = self.generate_trading_signal(prediction, &odds).await?;
+        let prediction = &self.blend_with_market(prediction).await;
+        let signal = self.generate_trading_signal(prediction, &book, &odds, Seat::Live).await?;
+        self.trade_strategy_books(prediction, &book, &odds).await;
 
         if let Some(ref bet) = signal.recommended_bet {
             info!("💰 Trading signal generated for {}: {} stake with {:.1}% edge", 
@@ -99,45 +833,140 @@ impl TradingEngine {
                   bet.confidence * 100.0);
         }
 
+        // Only signals that recommend something, or that say why a market
+        // was passed over, are worth keeping
+        if signal.signal_strength > 0.0 || !signal.rejections.is_empty() {
+            push_bounded(&mut *self.signal_history.write().await, signal.clone(), self.history_capacity);
+            let event = AuditEvent::Signal {
+                signal_strength: signal.signal_strength,
+                risk_score: signal.risk_assessment.risk_score,
+                reasoning: signal.reasoning.clone(),
+                bet: signal.recommended_bet.clone().map(Box::new),
+            };
+            self.audit(AuditEntry::new(signal.match_id.clone(), event).with_signal(signal.id)).await;
+        }
+
         Ok(signal)
     }
 
+    /// Size the prediction for each strategy book and paper trade whatever
+    /// its strategy picks on the book's own bankroll
+    async fn trade_strategy_books(&self, prediction: &Prediction, book: &OddsBook, market_odds: &BestOdds) {
+        let presets: Vec<String> = self.strategy_books.read().await.keys().cloned().collect();
+        for preset in presets {
+            let Some(strategy) = self.strategies.get(&preset) else {
+                continue;
+            };
+            let signal = {
+                let books = self.strategy_books.read().await;
+                let Some(portfolio) = books.get(&preset) else {
+                    continue;
+                };
+                self.generate_trading_signal(prediction, book, market_odds, Seat::Virtual { strategy, portfolio }).await
+            };
+            let bet = match signal {
+                Ok(signal) => signal.recommended_bet,
+                Err(e) => {
+                    warn!("📒 Could not size {} for the {} book: {}", prediction.match_id, preset, e);
+                    None
+                }
+            };
+            let Some(bet) = bet else {
+                continue;
+            };
+            if let Some(portfolio) = self.strategy_books.write().await.get_mut(&preset) {
+                let stake = bet.stake;
+                match portfolio.place_bet(bet) {
+                    Ok(()) => debug!("📒 {} book backs {} with {}", preset, prediction.match_id, stake),
+                    Err(e) => debug!("📒 {} book passes on {}: {}", preset, prediction.match_id, e),
+                }
+            }
+        }
+    }
+
+    /// The prediction shrunk toward the feed's prices, weighted by how well
+    /// the model has done against the market in the match's league
+    async fn blend_with_market(&self, prediction: &Prediction) -> Prediction {
+        let Some(market) = self.market_odds.read().await.get(&prediction.match_id).cloned() else {
+            return prediction.clone();
+        };
+        let league = self.match_info.read().await
+            .get(&prediction.match_id)
+            .map(|info| info.league.clone());
+
+        let blender = self.blender.read().await;
+        let blended = blender.blend(prediction, &market, league.as_deref());
+        if blended.home_win_prob != prediction.home_win_prob {
+            debug!("🧮 Blended {} toward market at model weight {:.2}: home {:.3} -> {:.3}",
+                   prediction.match_id,
+                   blender.model_weight(league.as_deref()),
+                   prediction.home_win_prob,
+                   blended.home_win_prob);
+        }
+        blended
+    }
+
+    /// Recalibrate per-league blend weights from settled predictions.
+    /// Samples for matches the engine has no league for are skipped.
+    pub async fn calibrate_blending(&self, samples: &[EvaluationSample]) -> usize {
+        let match_info = self.match_info.read().await;
+        let labeled = samples.iter().filter_map(|sample| {
+            match_info.get(&sample.prediction.match_id).map(|info| (info.league.as_str(), sample))
+        });
+        let calibrated = self.blender.write().await.calibrate(labeled);
+        if calibrated > 0 {
+            info!("🧮 Recalibrated model/market blend weights for {} leagues", calibrated);
+        }
+        calibrated
+    }
+
+    pub async fn get_blend_weights(&self) -> HashMap<String, LeagueSkill> {
+        self.blender.read().await.league_skill().clone()
+    }
+
     async fn generate_trading_signal(
         &self, 
         prediction: &Prediction, 
-        market_odds: &SimpleMarketOdds
+        book: &OddsBook,
+        market_odds: &BestOdds,
+        seat: Seat<'_>,
     ) -> Result<TradingSignal> {
         let mut best_bet: Option<BettingDecision> = None;
         let mut best_edge = 0.0;
         let mut reasoning = String::new();
+        let mut rejections = Vec::new();
 
         // Analyze home win opportunity
         if let Some(bet) = self.analyze_bet_opportunity(
-            &prediction.match_id,
+            prediction,
             BetType::HomeWin,
             prediction.home_win_prob,
-            market_odds.home_win,
-            prediction.confidence,
+            market_odds.home_win.price,
+            market_odds.home_win.commission,
+            &mut rejections,
+            seat,
         ).await? {
             if bet.confidence > best_edge {
                 best_edge = bet.confidence;
-                best_bet = Some(bet);
+                best_bet = Some(self.attach_price(bet, &market_odds.home_win).await?);
                 reasoning = format!("Home win edge: {:.1}%", best_edge * 100.0);
             }
         }
 
         // Analyze draw opportunity
-        if let Some(draw_prob) = prediction.draw_prob {
+        if let (Some(draw_prob), Some(draw_price)) = (prediction.draw_prob, &market_odds.draw) {
             if let Some(bet) = self.analyze_bet_opportunity(
-                &prediction.match_id,
+                prediction,
                 BetType::Draw,
                 draw_prob,
-                market_odds.draw,
-                prediction.confidence,
+                draw_price.price,
+                draw_price.commission,
+                &mut rejections,
+                seat,
             ).await? {
                 if bet.confidence > best_edge {
                     best_edge = bet.confidence;
-                    best_bet = Some(bet);
+                    best_bet = Some(self.attach_price(bet, draw_price).await?);
                     reasoning = format!("Draw edge: {:.1}%", best_edge * 100.0);
                 }
             }
@@ -145,70 +974,288 @@ impl TradingEngine {
 
         // Analyze away win opportunity
         if let Some(bet) = self.analyze_bet_opportunity(
-            &prediction.match_id,
+            prediction,
             BetType::AwayWin,
             prediction.away_win_prob,
-            market_odds.away_win,
-            prediction.confidence,
+            market_odds.away_win.price,
+            market_odds.away_win.commission,
+            &mut rejections,
+            seat,
         ).await? {
             if bet.confidence > best_edge {
                 best_edge = bet.confidence;
-                best_bet = Some(bet);
+                best_bet = Some(self.attach_price(bet, &market_odds.away_win).await?);
                 reasoning = format!("Away win edge: {:.1}%", best_edge * 100.0);
             }
         }
 
-        let risk_assessment = self.assess_risk(&prediction.match_id, &best_bet).await;
-        let signal_strength = if best_bet.is_some() { 
+        // Derived markets can hold value when one match winner price is off,
+        // and Asian handicap lines when the model disagrees on the goal margin
+        let mut candidates = Vec::new();
+        if let Some(derived) = self.derived_odds.read().await.get(&prediction.match_id).cloned() {
+            for (bet_type, probability, push_probability) in derived_market_probabilities(prediction) {
+                if let Some(price) = derived.price_for(&bet_type) {
+                    candidates.push((bet_type, price, probability, push_probability));
+                }
+            }
+        }
+        let handicap = self.asian_handicap_odds.read().await.get(&prediction.match_id).cloned();
+        if let (Some(handicap), Some(info)) = (handicap, self.get_match_info(&prediction.match_id).await) {
+            candidates.extend(asian_handicap_probabilities(prediction, &handicap, &info));
+        }
+        let halves = self.half_result_odds.read().await.get(&prediction.match_id).cloned();
+        if let Some(halves) = halves {
+            let first_half_settled = self.get_match_info(&prediction.match_id).await
+                .is_some_and(|info| info.half_time_score.is_some());
+            for (bet_type, probability) in half_result_probabilities(prediction) {
+                if first_half_settled && bet_type.half() == Some(MatchHalf::First) {
+                    continue;
+                }
+                if let Some(price) = halves.price_for(&bet_type) {
+                    candidates.push((bet_type, price, probability, 0.0));
+                }
+            }
+        }
+
+        for (bet_type, price, probability, push_probability) in candidates {
+            let label = describe_bet_type(&bet_type);
+            if let Some(bet) = self.analyze_bet_opportunity(
+                prediction,
+                bet_type,
+                probability,
+                price,
+                self.commission_for(MARKET_FEED_BOOKMAKER),
+                &mut rejections,
+                seat,
+            ).await? {
+                if bet.confidence > best_edge {
+                    best_edge = bet.confidence;
+                    let feed_price = BestPrice {
+                        price,
+                        bookmaker: MARKET_FEED_BOOKMAKER.to_string(),
+                        commission: bet.commission,
+                        promotion: None,
+                    };
+                    let bet = bet.with_push_probability(push_probability);
+                    best_bet = Some(self.attach_price(bet, &feed_price).await?);
+                    reasoning = format!("{} edge: {:.1}%", label, best_edge * 100.0);
+                }
+            }
+        }
+
+        // Exact scores are long shots: they may be priced past the strategy's
+        // odds ceiling, only the best-value score is considered, and its
+        // stake is held to a small slice of the bankroll
+        let correct_score = self.correct_score_odds.read().await.get(&prediction.match_id).cloned();
+        if let Some(odds) = correct_score {
+            let mut strategy = match seat {
+                Seat::Live => self.get_active_strategy().await,
+                Seat::Virtual { strategy, .. } => strategy.clone(),
+            };
+            strategy.max_odds = strategy.max_odds.max(MAX_CORRECT_SCORE_ODDS);
+            let commission = self.commission_for(MARKET_FEED_BOOKMAKER);
+            let matrix = score_matrix_for(prediction);
+            let best_score = odds.quotes.iter()
+                .filter(|quote| quote.price <= strategy.max_odds)
+                .map(|quote| (quote, matrix.probability(quote.home_goals, quote.away_goals)))
+                .max_by(|(a, pa), (b, pb)| {
+                    (pa * net_odds(a.price, commission)).total_cmp(&(pb * net_odds(b.price, commission)))
+                });
+            if let Some((quote, probability)) = best_score {
+                let bet_type = BetType::CorrectScore { home_goals: quote.home_goals, away_goals: quote.away_goals };
+                let label = describe_bet_type(&bet_type);
+                if let Some(mut bet) = self.analyze_with_strategies(
+                    prediction,
+                    Some(MAX_CORRECT_SCORE_ODDS),
+                    bet_type,
+                    probability,
+                    (quote.price, commission),
+                    &mut rejections,
+                    seat,
+                ).await? {
+                    if bet.confidence > best_edge {
+                        best_edge = bet.confidence;
+                        let available = match seat {
+                            Seat::Live => self.portfolio.read().await.available_bankroll,
+                            Seat::Virtual { portfolio, .. } => portfolio.available_bankroll,
+                        };
+                        let cap = Decimal::from_f64(self.risk_manager.max_correct_score_stake).unwrap_or(Decimal::ZERO);
+                        bet.stake = bet.stake.min((available * cap).round_dp(2));
+                        let feed_price = BestPrice {
+                            price: quote.price,
+                            bookmaker: MARKET_FEED_BOOKMAKER.to_string(),
+                            commission,
+                            promotion: None,
+                        };
+                        best_bet = Some(self.attach_price(bet, &feed_price).await?);
+                        reasoning = format!("{} edge: {:.1}%", label, best_edge * 100.0);
+                    }
+                }
+            }
+        }
+
+        let mut signal_strength = if best_bet.is_some() { 
             (best_edge * prediction.confidence).min(1.0) 
         } else { 
             0.0 
         };
 
-        Ok(TradingSignal {
-            match_id: prediction.match_id.clone(),
+        // With no cash bet worth making, a free bet is still worth using.
+        // Promotions belong to the live account.
+        if best_bet.is_none() && matches!(seat, Seat::Live) {
+            if let Some(bet) = self.best_free_bet_use(prediction, book).await? {
+                reasoning = format!("Free bet: {:.2} expected profit per unit", bet.expected_value);
+                signal_strength = 1.0;
+                best_bet = Some(bet);
+            }
+        }
+
+        let risk_assessment = match seat {
+            Seat::Live => self.assess_risk(&prediction.match_id, &best_bet).await,
+            Seat::Virtual { .. } => RiskAssessment::default(),
+        };
+
+        Ok(TradingSignal::new(
+            prediction.match_id.clone(),
             signal_strength,
-            recommended_bet: best_bet,
+            best_bet,
             risk_assessment,
             reasoning,
-        })
+        ).with_rejections(rejections).with_prediction(prediction.id))
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn analyze_bet_opportunity(
         &self,
-        match_id: &str,
+        prediction: &Prediction,
         bet_type: BetType,
         true_probability: f64,
         market_odds: Decimal,
-        confidence: f64,
+        commission: Decimal,
+        rejections: &mut Vec<MarketRejection>,
+        seat: Seat<'_>,
     ) -> Result<Option<BettingDecision>> {
-        let strategy = self.get_active_strategy().await;
-        
-        if !strategy.should_bet(market_odds, true_probability, confidence) {
+        self.analyze_with_strategies(prediction, None, bet_type, true_probability, (market_odds, commission), rejections, seat).await
+    }
+
+    /// The active strategy's decision, or the ensemble's vote if one is
+    /// configured; a strategy book's own strategy decides for it.
+    /// `odds_ceiling` lifts each strategy's maximum odds. Markets a
+    /// strategy's filter keeps it out of are added to `rejections`.
+    #[allow(clippy::too_many_arguments)]
+    async fn analyze_with_strategies(
+        &self,
+        prediction: &Prediction,
+        odds_ceiling: Option<Decimal>,
+        bet_type: BetType,
+        true_probability: f64,
+        price: (Decimal, Decimal),
+        rejections: &mut Vec<MarketRejection>,
+        seat: Seat<'_>,
+    ) -> Result<Option<BettingDecision>> {
+        let match_id = prediction.match_id.as_str();
+        let lift = |mut strategy: BettingStrategy| {
+            if let Some(ceiling) = odds_ceiling {
+                strategy.max_odds = strategy.max_odds.max(ceiling);
+            }
+            strategy
+        };
+        if let Seat::Virtual { strategy, .. } = seat {
+            let strategy = lift(strategy.clone());
+            return self.analyze_with_strategy(&strategy, prediction, bet_type, true_probability, price, rejections, seat).await;
+        }
+        let Some(ensemble) = &self.ensemble else {
+            let strategy = lift(self.get_active_strategy().await);
+            return self.analyze_with_strategy(&strategy, prediction, bet_type, true_probability, price, rejections, seat).await;
+        };
+
+        let mut votes = Vec::with_capacity(ensemble.strategies().len());
+        let mut template = None;
+        for member in ensemble.strategies() {
+            let member = lift(member.clone());
+            let bet = self.analyze_with_strategy(&member, prediction, bet_type.clone(), true_probability, price, rejections, seat).await?;
+            votes.push(StrategyVote {
+                strategy: member.name.clone(),
+                approved: bet.is_some(),
+                stake: bet.as_ref().map_or(Decimal::ZERO, |bet| bet.stake),
+            });
+            template = template.or(bet);
+        }
+
+        let decision = ensemble.decide(votes);
+        debug!("🗳️ {} of {} strategies back {} on {}",
+               decision.votes.iter().filter(|vote| vote.approved).count(),
+               decision.votes.len(), describe_bet_type(&bet_type), match_id);
+        match template {
+            Some(mut bet) if decision.approved => {
+                // An average of stakes on the venue's increments may fall between them
+                bet.stake = self.stake_limits().fit(decision.stake);
+                if bet.stake <= Decimal::ZERO {
+                    return Ok(None);
+                }
+                bet.strategy = ENSEMBLE_STRATEGY.to_string();
+                strategy_ensemble::attach_votes(&mut bet, &decision.votes);
+                Ok(Some(bet))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// `price` is the quoted odds and the venue's commission
+    #[allow(clippy::too_many_arguments)]
+    async fn analyze_with_strategy(
+        &self,
+        strategy: &BettingStrategy,
+        prediction: &Prediction,
+        bet_type: BetType,
+        true_probability: f64,
+        (market_odds, commission): (Decimal, Decimal),
+        rejections: &mut Vec<MarketRejection>,
+        seat: Seat<'_>,
+    ) -> Result<Option<BettingDecision>> {
+        let match_id = prediction.match_id.as_str();
+        if let Some(rejection) = self.market_rejection(strategy, match_id, &bet_type).await {
+            debug!("🚫 {} stays out of {} on {}: {:?}", strategy.name, describe_bet_type(&bet_type), match_id, rejection);
+            if !rejections.contains(&rejection) {
+                rejections.push(rejection);
+            }
+            return Ok(None);
+        }
+        if !strategy.should_bet_with_commission(market_odds, true_probability, prediction.confidence, commission) {
             return Ok(None);
         }
 
-        let portfolio = self.portfolio.read().await;
-        let bet = BettingDecision::new(
+        let live;
+        let portfolio = match seat {
+            Seat::Live => {
+                live = self.portfolio.read().await;
+                &*live
+            }
+            Seat::Virtual { portfolio, .. } => portfolio,
+        };
+        let bet = BettingDecision::with_commission(
             match_id.to_string(),
             bet_type.clone(),
-            dec!(0.0), // Will be calculated below
+            dec!(1.0), // Probe stake; the Kelly fraction does not depend on it
             market_odds,
             true_probability,
             strategy.name.clone(),
+            commission,
         )?;
 
-        // Calculate optimal stake using Kelly criterion with strategy constraints
-        let kelly_stake = strategy.calculate_stake(
-            portfolio.available_bankroll,
-            bet.kelly_fraction,
+        // Size with the strategy's staking plan, within its stake cap
+        let stake = strategy.size_stake(
+            &StakeContext::new(portfolio.available_bankroll, bet.kelly_fraction)
+                .with_losing_streak(portfolio.losing_streak(&strategy.name)),
         );
 
         // Apply risk management constraints
         let adjusted_stake = self.apply_risk_constraints(
-            kelly_stake,
+            stake,
             match_id,
-            &portfolio,
+            bet_type.half(),
+            portfolio,
+            matches!(seat, Seat::Live),
         ).await;
 
         if adjusted_stake <= dec!(0.0) {
@@ -216,69 +1263,177 @@ impl TradingEngine {
         }
 
         // Create final betting decision with adjusted stake
-        let final_bet = BettingDecision::new(
+        let final_bet = BettingDecision::with_commission(
             match_id.to_string(),
             bet_type,
             adjusted_stake,
             market_odds,
             true_probability,
             strategy.name.clone(),
+            commission,
         )?;
 
         Ok(Some(final_bet))
     }
 
+    /// Why `strategy`'s market filter keeps it out of this market, if it does
+    async fn market_rejection(&self, strategy: &BettingStrategy, match_id: &str, bet_type: &BetType) -> Option<MarketRejection> {
+        let MarketFilter { max_overround, min_liquidity } = &strategy.market_filter;
+
+        if let Some(max_overround) = *max_overround {
+            if let Some(overround) = self.market_overround(match_id, bet_type).await {
+                if overround > max_overround {
+                    return Some(MarketRejection::Overround {
+                        strategy: strategy.name.clone(),
+                        market: describe_bet_type(bet_type),
+                        overround,
+                        max_overround,
+                    });
+                }
+            }
+        }
+
+        if let Some(min_liquidity) = *min_liquidity {
+            let liquidity = self.market_liquidity.read().await.get(match_id).copied();
+            if liquidity.is_none_or(|liquidity| liquidity < min_liquidity) {
+                return Some(MarketRejection::Liquidity {
+                    strategy: strategy.name.clone(),
+                    liquidity,
+                    min_liquidity,
+                });
+            }
+        }
+
+        None
+    }
+
+    /// The feed's margin on the market `bet_type` belongs to. Double chance
+    /// and draw no bet are priced off the match winner book, so share its margin.
+    async fn market_overround(&self, match_id: &str, bet_type: &BetType) -> Option<f64> {
+        match bet_type {
+            BetType::HomeWin | BetType::Draw | BetType::AwayWin
+            | BetType::DoubleChance { .. } | BetType::DrawNoBet { .. } => {
+                self.market_odds.read().await.get(match_id).map(|odds| odds.overround())
+            }
+            BetType::AsianHandicap { line, team } => {
+                let info = self.get_match_info(match_id).await?;
+                let home_line = if *team == info.team_home { *line } else { -*line };
+                self.asian_handicap_odds.read().await.get(match_id)?
+                    .quotes.iter()
+                    .find(|quote| quote.line == home_line)
+                    .map(|quote| quote.overround())
+            }
+            BetType::CorrectScore { .. } => {
+                self.correct_score_odds.read().await.get(match_id).map(|odds| odds.overround())
+            }
+            BetType::HalfResult { half, .. } => {
+                self.half_result_odds.read().await.get(match_id)?.odds_for(*half).map(|odds| odds.overround())
+            }
+            BetType::OverUnder { .. } | BetType::BothTeamsToScore { .. } | BetType::Accumulator { .. } => None,
+        }
+    }
+
+    /// The risk limits applied to a stake on `portfolio`. The daily loss
+    /// limit only holds for the live book, whose losses it tracks. Each
+    /// half's result market has its own per-match exposure allowance,
+    /// apart from the markets on the whole match.
     async fn apply_risk_constraints(
         &self,
         proposed_stake: Decimal,
         match_id: &str,
+        half: Option<MatchHalf>,
         portfolio: &Portfolio,
+        daily_loss_limit: bool,
     ) -> Decimal {
+        self.constrain_stake(proposed_stake, match_id, half, portfolio, daily_loss_limit).await.0
+    }
+
+    /// `apply_risk_constraints`, also returning the limits that changed the stake
+    async fn constrain_stake(
+        &self,
+        proposed_stake: Decimal,
+        match_id: &str,
+        half: Option<MatchHalf>,
+        portfolio: &Portfolio,
+        daily_loss_limit: bool,
+    ) -> (Decimal, Vec<StakeConstraint>) {
         let mut final_stake = proposed_stake;
+        let mut bound = Vec::new();
 
         // Check available bankroll
         if final_stake > portfolio.available_bankroll {
             final_stake = portfolio.available_bankroll * dec!(0.95); // Leave 5% buffer
+            bound.push(StakeConstraint::Bankroll { available: portfolio.available_bankroll });
             debug!("🛡️ Stake reduced due to bankroll constraints: {}", final_stake);
         }
 
         // Check maximum exposure per match
         let current_match_exposure = portfolio.active_bets
             .iter()
-            .filter(|bet| bet.match_id == match_id)
-            .map(|bet| bet.stake)
+            .filter(|bet| bet.match_id == match_id && bet.bet_type.half() == half)
+            .map(|bet| bet.base_cash_stake())
             .sum::<Decimal>();
 
         if current_match_exposure + final_stake > self.risk_manager.max_exposure_per_match {
             final_stake = (self.risk_manager.max_exposure_per_match - current_match_exposure)
                 .max(dec!(0.0));
+            bound.push(StakeConstraint::MatchExposure { limit: self.risk_manager.max_exposure_per_match });
             debug!("🛡️ Stake reduced due to match exposure limits: {}", final_stake);
         }
 
+        // Check correlated exposure across league/team groups
+        let correlated_stake = {
+            let match_info = self.match_info.read().await;
+            self.risk_manager.size_for_correlation(
+                final_stake,
+                match_id,
+                portfolio.total_bankroll,
+                &portfolio.active_bets,
+                &match_info,
+            )
+        };
+        if correlated_stake < final_stake {
+            final_stake = correlated_stake;
+            bound.push(StakeConstraint::CorrelatedExposure { allowed: correlated_stake });
+            debug!("🛡️ Stake reduced due to correlated group exposure: {}", final_stake);
+        }
+
         // Check daily loss limits
-        if self.risk_manager.current_daily_loss + final_stake > self.risk_manager.max_daily_loss {
-            final_stake = (self.risk_manager.max_daily_loss - self.risk_manager.current_daily_loss)
-                .max(dec!(0.0));
-            debug!("🛡️ Stake reduced due to daily loss limits: {}", final_stake);
+        if daily_loss_limit {
+            let daily_loss = self.risk_manager.daily_loss.read().await.realized_loss(Utc::now());
+            if daily_loss + final_stake > self.risk_manager.max_daily_loss {
+                final_stake = (self.risk_manager.max_daily_loss - daily_loss)
+                    .max(dec!(0.0));
+                bound.push(StakeConstraint::DailyLoss { limit: self.risk_manager.max_daily_loss });
+                debug!("🛡️ Stake reduced due to daily loss limits: {}", final_stake);
+            }
         }
 
         // Check concurrent bet limits
         if portfolio.active_bets.len() >= self.risk_manager.max_concurrent_bets {
             debug!("🛡️ Max concurrent bets reached, rejecting new bet");
-            return dec!(0.0);
+            bound.push(StakeConstraint::ConcurrentBets { limit: self.risk_manager.max_concurrent_bets });
+            return (dec!(0.0), bound);
+        }
+
+        // Fit the venue's minimum, maximum and stake increment
+        let fitted_stake = self.stake_limits().fit(final_stake);
+        if fitted_stake != final_stake {
+            bound.push(StakeConstraint::StakeLimits { venue: self.venue.name().to_string() });
+            debug!("🛡️ Stake fitted to {} limits: {} -> {}", self.venue.name(), final_stake, fitted_stake);
         }
 
-        final_stake
+        (fitted_stake, bound)
     }
 
-    async fn assess_risk(&self, match_id: &str, bet: &Option<BettingDecision>) -> RiskAssessment {
+    pub async fn assess_risk(&self, match_id: &str, bet: &Option<BettingDecision>) -> RiskAssessment {
         let mut warnings = Vec::new();
         let mut risk_score: f64 = 0.0;
 
         if let Some(bet) = bet {
             // Assess stake size risk
             let portfolio = self.portfolio.read().await;
-            let stake_percentage = (bet.stake / portfolio.total_bankroll).to_f64().unwrap_or(0.0);
+            let stake_percentage = (bet.to_base(bet.stake) / portfolio.total_bankroll).to_f64().unwrap_or(0.0);
             
             if stake_percentage > 0.05 {
                 warnings.push("High stake percentage (>5%)".to_string());
@@ -299,11 +1454,25 @@ impl TradingEngine {
             }
 
             // Assess correlation risk
-            let correlation_risk = self.calculate_correlation_risk(match_id, bet).await;
+            let correlation_risk = Self::calculate_correlation_risk(match_id, &portfolio);
             if correlation_risk > self.risk_manager.correlation_threshold {
                 warnings.push("High correlation with existing positions".to_string());
                 risk_score += 0.3;
             }
+
+            // Assess how concentrated the book would be with this bet on it
+            let concentration = {
+                let mut bets = portfolio.active_bets.clone();
+                bets.push(bet.clone());
+                self.risk_manager.concentration(&bets, &*self.match_info.read().await)
+            };
+            if !concentration.alerts.is_empty() {
+                for alert in &concentration.alerts {
+                    warnings.push(format!("Concentrated in {} {} ({:.0}% of exposure)",
+                                          alert.dimension, alert.largest_group, alert.largest_share * 100.0));
+                }
+                risk_score += 0.2;
+            }
         }
 
         RiskAssessment {
@@ -316,19 +1485,20 @@ impl TradingEngine {
         }
     }
 
-    async fn calculate_correlation_risk(&self, match_id: &str, _bet: &BettingDecision) -> f64 {
+    fn calculate_correlation_risk(match_id: &str, portfolio: &Portfolio) -> f64 {
         // Simplified correlation calculation
         // In a real system, this would analyze correlations between:
         // - Same league matches
         // - Same teams
         // - Similar market conditions
-        
-        let portfolio = self.portfolio.read().await;
+        //
+        // Takes the portfolio the caller already holds: a second read of the
+        // lock queues behind any waiting writer and deadlocks
         let same_league_bets = portfolio.active_bets
             .iter()
             .filter(|active_bet| {
                 // Simplified: assume matches with similar IDs are correlated
-                active_bet.match_id.starts_with(&match_id[..3])
+                active_bet.match_id.starts_with(match_id.get(..3).unwrap_or(match_id))
             })
             .count();
 
@@ -341,27 +1511,143 @@ impl TradingEngine {
             if signal.risk_assessment.risk_score > 0.8 {
                 warn!("🚫 Trade rejected due to high risk score: {:.2}", 
                       signal.risk_assessment.risk_score);
+                let detail = format!("risk score {:.2} > 0.80", signal.risk_assessment.risk_score);
+                self.audit_risk_decision(signal, bet, Some(RiskConstraint::RiskScore), Some(detail)).await;
                 return Ok(false);
             }
 
-            let mut portfolio = self.portfolio.write().await;
-            portfolio.place_bet(bet.clone())?;
+            // Kill switch: no new trades once the day's losses hit the limit
+            if self.risk_manager.daily_loss.read().await.is_halted(Utc::now()) {
+                debug!("🛑 Trade on {} rejected: daily loss limit reached", signal.match_id);
+                self.audit_risk_decision(signal, bet, Some(RiskConstraint::DailyLoss), None).await;
+                return Ok(false);
+            }
 
-            let mut count = self.trade_count.write().await;
-            *count += 1;
+            // Prices may have moved since the signal was generated
+            if let Some(detail) = self.price_moved(bet).await {
+                warn!("📉 Trade on {} rejected: {}", signal.match_id, detail);
+                self.audit_risk_decision(signal, bet, Some(RiskConstraint::PriceMoved), Some(detail)).await;
+                return Ok(false);
+            }
 
-            info!("✅ Trade executed #{}: {} stake on {} (odds: {}, EV: {:.1}%)",
-                  *count,
-                  bet.stake,
-                  match bet.bet_type {
-                      BetType::HomeWin => "Home Win",
-                      BetType::Draw => "Draw", 
-                      BetType::AwayWin => "Away Win",
-                      _ => "Other"
-                  },
-                  bet.odds,
-                  bet.expected_value * 100.0
-            );
+            // A free bet can only be used once
+            let mut free_bet = None;
+            if bet.free_bet {
+                let promotion = promotion_id(bet);
+                free_bet = match promotion {
+                    Some(id) => self.promotions.write().await.remove(&id),
+                    None => None,
+                };
+                if free_bet.is_none() {
+                    warn!("🎁 Free bet promotion {:?} is no longer available", promotion);
+                    self.audit_risk_decision(signal, bet, Some(RiskConstraint::FreeBetUnavailable), None).await;
+                    return Ok(false);
+                }
+            }
+
+            // Hold an open-bet slot and the stake while the order is out, so
+            // parallel workers can't between them overrun either limit
+            let reservation = {
+                let portfolio = self.portfolio.read().await;
+                let mut reserved = self.reservations.lock().unwrap();
+                let open_bets = portfolio.active_bets.len() + reserved.bets;
+                let available = portfolio.available_bankroll - reserved.stake;
+                if open_bets >= self.risk_manager.max_concurrent_bets {
+                    Err((RiskConstraint::ConcurrentBets, format!("{} open bets", open_bets)))
+                } else if bet.base_cash_stake() > available {
+                    Err((RiskConstraint::InsufficientFunds, format!("{} > {}", bet.base_cash_stake(), available)))
+                } else {
+                    reserved.bets += 1;
+                    reserved.stake += bet.base_cash_stake();
+                    Ok(Reservation { reservations: self.reservations.clone(), stake: bet.base_cash_stake() })
+                }
+            };
+            let reservation = match reservation {
+                Ok(reservation) => reservation,
+                Err((constraint, detail)) => {
+                    debug!("🛡️ Trade on {} rejected: {:?} ({})", signal.match_id, constraint, detail);
+                    self.audit_risk_decision(signal, bet, Some(constraint), Some(detail.clone())).await;
+                    self.return_free_bet(free_bet).await;
+                    if constraint == RiskConstraint::InsufficientFunds {
+                        return Err(QuantsError::InvalidStake { amount: format!("Insufficient funds: {}", detail) });
+                    }
+                    return Ok(false);
+                }
+            };
+            self.audit_risk_decision(signal, bet, None, None).await;
+
+            // Claim the bet's key before the order goes out, so a retry
+            // (after a crash, say) cannot place it a second time
+            let key = execution_key(signal.prediction_id.unwrap_or(signal.id), bet);
+            let attempt = AuditEntry::new(
+                signal.match_id.clone(),
+                AuditEvent::ExecutionAttempt { idempotency_key: key.clone(), venue: self.venue.name().to_string() },
+            ).with_signal(signal.id).with_bet(bet.id);
+            let claimed = match self.audit.claim_execution(&attempt).await {
+                Ok(true) => true,
+                Ok(false) => {
+                    warn!("🧾 Bet {} on {} was already executed; not placing it again", bet.id, signal.match_id);
+                    self.audit_risk_decision(signal, bet, Some(RiskConstraint::DuplicateExecution), None).await;
+                    false
+                }
+                Err(e) => {
+                    error!("🧾 Could not claim execution of bet {}: {}", bet.id, e);
+                    false
+                }
+            };
+            if !claimed {
+                self.return_free_bet(free_bet).await;
+                return Ok(false);
+            }
+
+            let receipt = match self.venue.place_order(bet).await {
+                Ok(receipt) if receipt.is_matched() => Some(receipt),
+                Ok(_) => {
+                    warn!("🏦 {} did not match the order on {}", self.venue.name(), describe_bet_type(&bet.bet_type));
+                    self.audit_execution(signal, bet, &key, ExecutionOutcome::Unmatched, None).await;
+                    None
+                }
+                Err(e) => {
+                    error!("🏦 {} order on {} failed: {}", self.venue.name(), describe_bet_type(&bet.bet_type), e);
+                    self.audit_execution(signal, bet, &key, ExecutionOutcome::Failed, Some(e.to_string())).await;
+                    None
+                }
+            };
+            let Some(receipt) = receipt else {
+                self.return_free_bet(free_bet).await;
+                return Ok(false);
+            };
+            // Booked as filled: an exchange matches at a ladder price that
+            // can beat the odds asked for
+            let mut bet = bet.clone();
+            bet.fill(receipt.size_matched, receipt.average_price);
+            bet.metadata["execution"] = serde_json::to_value(&receipt).unwrap_or_default();
+
+            // From here the venue holds a live position: anything that stops
+            // it being booked has to unwind the order
+            let mut portfolio = self.portfolio.write().await;
+            let unbooked = if portfolio.active_bets.len() >= self.risk_manager.max_concurrent_bets {
+                self.publish(DomainEvent::RiskLimitBreached {
+                    limit: RiskLimit::ConcurrentBets { limit: self.risk_manager.max_concurrent_bets },
+                    match_id: Some(signal.match_id.clone()),
+                });
+                Some("max concurrent bets reached".to_string())
+            } else if let Err(e) = portfolio.check_bet(&bet) {
+                Some(e.to_string())
+            } else {
+                // Only a bet the portfolio will take is journaled, and under
+                // its lock so entries follow mutation order
+                self.journal.as_ref()
+                    .and_then(|journal| journal.append(JournalRecord::BetPlaced { bet: Box::new(bet.clone()) }).err())
+                    .map(|e| format!("journal write failed: {}", e))
+            };
+            if let Some(reason) = unbooked {
+                drop(portfolio);
+                return self.unwind_order(signal, &bet, &key, &receipt, free_bet, reason).await;
+            }
+            portfolio.place_bet(bet.clone())?;
+            drop(reservation);
+            self.record_booked(signal, &bet, &key, &portfolio).await;
 
             Ok(true)
         } else {
@@ -370,101 +1656,1421 @@ impl TradingEngine {
         }
     }
 
-    async fn get_active_strategy(&self) -> BettingStrategy {
+    /// Cancel a matched order that can't be booked and hand back its free
+    /// bet. Should the venue refuse to cancel, the bet is booked regardless,
+    /// so the portfolio never misses a live position.
+    async fn unwind_order(
+        &self,
+        signal: &TradingSignal,
+        bet: &BettingDecision,
+        key: &str,
+        receipt: &OrderReceipt,
+        free_bet: Option<Promotion>,
+        reason: String,
+    ) -> Result<bool> {
+        match self.venue.cancel_order(receipt).await {
+            Ok(()) => {
+                warn!("🏦 Cancelled {} order {} on {}: {}", self.venue.name(), receipt.order_id, signal.match_id, reason);
+                self.audit_execution(signal, bet, key, ExecutionOutcome::NotBooked, Some(reason)).await;
+                self.return_free_bet(free_bet).await;
+                Ok(false)
+            }
+            Err(e) => {
+                error!("🏦 Could not cancel {} order {} on {} ({}); booking it regardless: {}",
+                       self.venue.name(), receipt.order_id, signal.match_id, reason, e);
+                let mut portfolio = self.portfolio.write().await;
+                if let Err(e) = portfolio.check_bet(bet) {
+                    error!("🏦 Order {} is live on {} but can't be booked: {}", receipt.order_id, self.venue.name(), e);
+                    self.audit_execution(signal, bet, key, ExecutionOutcome::NotBooked, Some(e.to_string())).await;
+                    return Err(e);
+                }
+                if let Some(journal) = &self.journal {
+                    if let Err(e) = journal.append(JournalRecord::BetPlaced { bet: Box::new(bet.clone()) }) {
+                        error!("📓 Bet {} booked without a journal entry: {}", bet.id, e);
+                    }
+                }
+                portfolio.place_bet(bet.clone())?;
+                self.record_booked(signal, bet, key, &portfolio).await;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Announce, audit and count a bet just added to `portfolio`
+    async fn record_booked(&self, signal: &TradingSignal, bet: &BettingDecision, key: &str, portfolio: &Portfolio) {
+        let concentration = self.risk_manager.concentration(&portfolio.active_bets, &*self.match_info.read().await);
+        for alert in &concentration.alerts {
+            warn!("⚖️ Portfolio concentrated by {}: HHI {:.2} > {:.2}, {} holds {:.0}% of exposure",
+                  alert.dimension, alert.hhi, alert.threshold, alert.largest_group, alert.largest_share * 100.0);
+            self.publish(DomainEvent::RiskLimitBreached {
+                limit: RiskLimit::Concentration {
+                    dimension: alert.dimension.clone(),
+                    hhi: alert.hhi,
+                    threshold: alert.threshold,
+                    largest_group: alert.largest_group.clone(),
+                },
+                match_id: Some(signal.match_id.clone()),
+            });
+        }
+        self.publish(DomainEvent::BetPlaced { bet: bet.clone() });
+        self.audit_execution(signal, bet, key, ExecutionOutcome::Booked, None).await;
+
+        push_bounded(&mut *self.trade_history.write().await, bet.clone(), self.history_capacity);
+
+        let mut count = self.trade_count.write().await;
+        *count += 1;
+
+        info!("✅ Trade executed #{}: {} {} stake on {} (odds: {}, EV: {:.1}%)",
+              *count,
+              bet.stake,
+              bet.currency,
+              describe_bet_type(&bet.bet_type),
+              bet.odds,
+              bet.expected_value * 100.0
+        );
+    }
+
+    /// Put back a free bet taken for an order that never went through
+    async fn return_free_bet(&self, free_bet: Option<Promotion>) {
+        if let Some(promotion) = free_bet {
+            self.promotions.write().await.insert(promotion.id, promotion);
+        }
+    }
+
+    /// Place a slip of selections on different matches as one accumulator.
+    /// Its legs must pass the correlation checks and its stake the exposure
+    /// limits of every league and team it rides on, before it goes through
+    /// the same execution as the engine's own trades. Returns the booked bet,
+    /// or `None` if a pre-trade check or the venue turned it down.
+    pub async fn place_accumulator(&self, slip: BetSlip) -> Result<Option<BettingDecision>> {
+        let mut bet = slip.build()?;
+        if bet.bet_type.legs().len() < 2 {
+            return Err(QuantsError::InvalidAccumulator("an accumulator needs at least two legs".to_string()));
+        }
+        {
+            let portfolio = self.portfolio.read().await;
+            bet.currency = portfolio.currency;
+            let match_info = self.match_info.read().await;
+            self.risk_manager.check_accumulator(bet.bet_type.legs(), &match_info)?;
+            let allowed = bet.bet_type.legs().iter().fold(bet.base_cash_stake(), |stake, leg| {
+                self.risk_manager.size_for_correlation(
+                    stake,
+                    &leg.match_id,
+                    portfolio.total_bankroll,
+                    &portfolio.active_bets,
+                    &match_info,
+                )
+            });
+            if allowed < bet.base_cash_stake() {
+                return Err(QuantsError::InvalidStake {
+                    amount: format!("{} is over the correlated exposure limits, which allow {}", bet.stake, allowed)
+                });
+            }
+        }
+
+        let risk_assessment = self.assess_risk(&bet.match_id, &Some(bet.clone())).await;
+        let reasoning = format!("Accumulator of {} legs at {}", bet.bet_type.legs().len(), bet.odds);
+        let signal = TradingSignal::new(bet.match_id.clone(), 1.0, Some(bet.clone()), risk_assessment, reasoning);
+        if !self.execute_trade(&signal).await? {
+            return Ok(None);
+        }
+        Ok(self.portfolio.read().await.active_bets.iter().find(|booked| booked.id == bet.id).cloned())
+    }
+
+    pub async fn get_active_strategy(&self) -> BettingStrategy {
         // For now, return moderate strategy
         // In a real system, this could be dynamic based on performance
         self.strategies.get("moderate").unwrap().clone()
     }
 
-    async fn get_market_odds(&self, match_id: &str) -> Option<SimpleMarketOdds> {
-        self.market_odds.read().await.get(match_id).cloned()
+    /// Why the best price on offer now is no longer good enough for `bet`,
+    /// if its strategy has an odds tolerance. Only match winner bets are
+    /// re-priced, and a price off the exchange ladder has no ticks to count.
+    async fn price_moved(&self, bet: &BettingDecision) -> Option<String> {
+        let strategy = self.strategies.values().find(|strategy| strategy.name == bet.strategy)?;
+        let OddsTolerance { max_ticks, recheck_edge } = strategy.odds_tolerance;
+        if max_ticks.is_none() && !recheck_edge {
+            return None;
+        }
+
+        let best = self.best_odds(&self.odds_book_with_feed(&bet.match_id).await).await?;
+        let current = Outcome1X2::try_from(&bet.bet_type).ok()?
+            .select([Some(best.home_win), best.draw, Some(best.away_win)])?;
+
+        if let Some(max_ticks) = max_ticks {
+            if let Some(ticks) = ladder_ticks(bet.odds, current.price).filter(|ticks| *ticks > max_ticks) {
+                return Some(format!("{} moved {} ticks to {} at {}", bet.odds, ticks, current.price, current.bookmaker));
+            }
+        }
+        if recheck_edge {
+            let edge = quant_models::edge(bet.true_probability(), net_odds(current.price, current.commission));
+            if edge < strategy.min_edge {
+                return Some(format!("edge {:.3} at {} is below {:.3}", edge, current.price, strategy.min_edge));
+            }
+        }
+        None
+    }
+
+    /// Every bookmaker's prices for a match, with the aggregated market feed
+    /// treated as one more book
+    async fn odds_book_with_feed(&self, match_id: &str) -> OddsBook {
+        let mut book = self.odds_books.read().await
+            .get(match_id)
+            .cloned()
+            .unwrap_or_else(|| OddsBook::new(match_id.to_string()));
+
+        if let Some(odds) = self.market_odds.read().await.get(match_id) {
+            book.insert_simple(MARKET_FEED_BOOKMAKER, odds);
+        }
+
+        book
+    }
+
+    /// Every bookmaker's match winner prices that beat the latest of
+    /// `predictions` for their match, highest expected value first
+    pub async fn find_value_bets(&self, predictions: &[Prediction], filter: &ValueBetFilter) -> Vec<ValueBetOpportunity> {
+        let mut latest: HashMap<&str, &Prediction> = HashMap::new();
+        for prediction in predictions {
+            let entry = latest.entry(prediction.match_id.as_str()).or_insert(prediction);
+            if prediction.prediction_timestamp >= entry.prediction_timestamp {
+                *entry = prediction;
+            }
+        }
+
+        let strategy = self.get_active_strategy().await;
+        let (bankroll, losing_streak) = {
+            let portfolio = self.portfolio.read().await;
+            (portfolio.available_bankroll, portfolio.losing_streak(&strategy.name))
+        };
+        let mut opportunities = Vec::new();
+        for (match_id, prediction) in latest {
+            let league = self.get_match_info(match_id).await.map(|info| info.league);
+            if let Some(wanted) = &filter.league {
+                if !league.as_deref().is_some_and(|league| league.eq_ignore_ascii_case(wanted)) {
+                    continue;
+                }
+            }
+
+            for quote in self.odds_book_with_feed(match_id).await.ladder() {
+                let odds = OddsFormat::Decimal { home: quote.home_win, draw: quote.draw, away: quote.away_win };
+                let Ok(value) = odds.has_value(prediction.home_win_prob, prediction.away_win_prob, prediction.draw_prob) else {
+                    continue;
+                };
+                let commission = self.commission_for(&quote.bookmaker);
+                for opportunity in value.opportunities.iter().filter(|opportunity| opportunity.edge() >= filter.min_edge) {
+                    let kelly = kelly_fraction(opportunity.true_prob(), net_odds(opportunity.odds(), commission));
+                    opportunities.push(ValueBetOpportunity {
+                        match_id: match_id.to_string(),
+                        league: league.clone(),
+                        bookmaker: quote.bookmaker.clone(),
+                        bet_type: opportunity.bet_type(),
+                        odds: opportunity.odds(),
+                        true_prob: opportunity.true_prob(),
+                        implied_prob: opportunity.implied_prob(),
+                        edge: opportunity.edge(),
+                        expected_value: opportunity.expected_value(),
+                        kelly_fraction: kelly,
+                        recommended_stake: strategy.size_stake(&StakeContext::new(bankroll, kelly).with_losing_streak(losing_streak)),
+                    });
+                }
+            }
+        }
+
+        opportunities.sort_by(|a, b| b.expected_value.total_cmp(&a.expected_value));
+        opportunities
+    }
+
+    /// Size each match winner outcome of `prediction` the way
+    /// `process_prediction` would against the live portfolio, with the
+    /// active strategy (or `params.strategy`, never the ensemble's vote),
+    /// without placing anything or recording a signal. Outcomes the engine
+    /// would back come first, largest edge first. A given price is quoted
+    /// as bookmaker `what_if`.
+    pub async fn simulate_trade(&self, prediction: &Prediction, params: &TradeSimulationParams) -> Result<Vec<SimulatedTrade>> {
+        let strategy = match &params.strategy {
+            Some(name) => self.strategies.get(name).cloned()
+                .ok_or_else(|| QuantsError::Config(format!("Unknown strategy: {}", name)))?,
+            None => self.get_active_strategy().await,
+        };
+        let match_id = prediction.match_id.as_str();
+        let best = match params.odds {
+            Some(odds) if odds <= Decimal::ONE => {
+                return Err(QuantsError::InvalidOdds(format!("{} pays nothing back", odds)));
+            }
+            Some(_) => None,
+            None => Some(self.best_odds(&self.odds_book_with_feed(match_id).await).await
+                .ok_or_else(|| QuantsError::MatchNotFound { match_id: match_id.to_string() })?),
+        };
+        let prediction = self.blend_with_market(prediction).await;
+        let halted = self.risk_manager.daily_loss.read().await.is_halted(Utc::now());
+
+        let outcomes = [
+            (BetType::HomeWin, Some(prediction.home_win_prob)),
+            (BetType::Draw, prediction.draw_prob),
+            (BetType::AwayWin, Some(prediction.away_win_prob)),
+        ];
+        let mut trades = Vec::new();
+        for (bet_type, probability) in outcomes {
+            if params.bet_type.as_ref().is_some_and(|wanted| *wanted != bet_type) {
+                continue;
+            }
+            let price = match (params.odds, &best) {
+                (Some(odds), _) => Some(BestPrice {
+                    price: odds,
+                    bookmaker: "what_if".to_string(),
+                    commission: Decimal::ZERO,
+                    promotion: None,
+                }),
+                (None, Some(best)) => Outcome1X2::try_from(&bet_type).ok()
+                    .and_then(|outcome| outcome.select([Some(&best.home_win), best.draw.as_ref(), Some(&best.away_win)]).cloned()),
+                (None, None) => None,
+            };
+            let (Some(probability), Some(price)) = (probability, price) else {
+                continue;
+            };
+
+            let net = net_odds(price.price, price.commission);
+            let kelly = kelly_fraction(probability, net);
+            let (mut stake, mut binding_constraints, kelly_stake) = {
+                let portfolio = self.portfolio.read().await;
+                let context = StakeContext::new(portfolio.available_bankroll, kelly)
+                    .with_losing_streak(portfolio.losing_streak(&strategy.name));
+                let kelly_stake = strategy.planned_stake(&context);
+                let mut bound = Vec::new();
+                let mut stake = Decimal::ZERO;
+                if let Some(rejection) = self.market_rejection(&strategy, match_id, &bet_type).await {
+                    bound.push(StakeConstraint::MarketFilter { rejection });
+                } else if !strategy.should_bet_with_commission(price.price, probability, prediction.confidence, price.commission) {
+                    bound.push(StakeConstraint::StrategyFilter { strategy: strategy.name.clone() });
+                } else {
+                    let sized = strategy.size_stake(&context);
+                    if sized < kelly_stake {
+                        bound.push(StakeConstraint::MaxStakePercent { max_stake_percent: strategy.max_stake_percent });
+                    }
+                    let (constrained, limits) = self.constrain_stake(sized, match_id, None, &portfolio, true).await;
+                    bound.extend(limits);
+                    stake = constrained;
+                }
+                (stake, bound, kelly_stake.round_dp(2))
+            };
+
+            // The checks `execute_trade` makes before an order goes out
+            if stake > Decimal::ZERO {
+                let bet = BettingDecision::with_commission(
+                    match_id.to_string(),
+                    bet_type.clone(),
+                    stake,
+                    price.price,
+                    probability,
+                    strategy.name.clone(),
+                    price.commission,
+                )?;
+                let risk_score = self.assess_risk(match_id, &Some(bet)).await.risk_score;
+                if risk_score > 0.8 {
+                    binding_constraints.push(StakeConstraint::RiskScore { risk_score });
+                    stake = Decimal::ZERO;
+                }
+                if halted {
+                    binding_constraints.push(StakeConstraint::TradingHalted);
+                    stake = Decimal::ZERO;
+                }
+            }
+
+            trades.push(SimulatedTrade {
+                match_id: match_id.to_string(),
+                strategy: strategy.name.clone(),
+                bet_type,
+                bookmaker: price.bookmaker,
+                odds: price.price,
+                commission: price.commission,
+                true_probability: probability,
+                edge: probability - 1.0 / net,
+                expected_value: probability * net - 1.0,
+                kelly_fraction: kelly,
+                kelly_stake,
+                stake,
+                binding_constraints,
+            });
+        }
+
+        trades.sort_by(|a, b| {
+            (b.stake > Decimal::ZERO).cmp(&(a.stake > Decimal::ZERO)).then(b.edge.total_cmp(&a.edge))
+        });
+        Ok(trades)
+    }
+
+    /// Best net price per outcome, where a boosted price beats the field
+    /// whenever it pays more after commission
+    async fn best_odds(&self, book: &OddsBook) -> Option<BestOdds> {
+        let mut best = book.best_with_commission(|bookmaker| self.commission_for(bookmaker))?;
+
+        for promotion in self.promotions_for(&book.match_id).await.iter().filter(|p| !p.is_free_bet()) {
+            let Some(quote) = book.quote(&promotion.bookmaker) else {
+                continue;
+            };
+            let commission = self.commission_for(&quote.bookmaker);
+            let outcomes = [
+                (BetType::HomeWin, Some(&mut best.home_win)),
+                (BetType::Draw, best.draw.as_mut()),
+                (BetType::AwayWin, Some(&mut best.away_win)),
+            ];
+
+            for (bet_type, current) in outcomes {
+                let (Some(current), Some(price)) = (current, quote.price_for(&bet_type)) else {
+                    continue;
+                };
+                if !promotion.applies_to(&book.match_id, &bet_type, &quote.bookmaker, price) {
+                    continue;
+                }
+
+                let boosted = promotion.boosted_odds(price);
+                if net_odds(boosted, commission) > net_odds(current.price, current.commission) {
+                    *current = BestPrice {
+                        price: boosted,
+                        bookmaker: quote.bookmaker.clone(),
+                        commission,
+                        promotion: Some(promotion.id),
+                    };
+                }
+            }
+        }
+
+        Some(best)
+    }
+
+    /// Tag a bet with where it is placed, converting the stake into the
+    /// bookmaker's currency and capping it to what a boost covers
+    async fn attach_price(&self, mut bet: BettingDecision, price: &BestPrice) -> Result<BettingDecision> {
+        bet.metadata["bookmaker"] = serde_json::json!(price.bookmaker);
+        let (currency, fx_rate) = self.currency_for(&price.bookmaker).await?;
+        if fx_rate != Decimal::ONE {
+            bet.stake = (bet.stake / fx_rate).round_dp(2);
+        }
+        bet.currency = currency;
+        bet.fx_rate = fx_rate;
+
+        if let Some(id) = price.promotion {
+            bet.metadata["promotion"] = serde_json::json!(id);
+            if let Some(max_stake) = self.promotions.read().await.get(&id).and_then(|p| p.max_stake()) {
+                bet.stake = bet.stake.min(max_stake);
+            }
+        }
+
+        Ok(bet)
+    }
+
+    /// The outcome where a free bet is worth the most. With stake not
+    /// returned, that is the highest p * (odds - 1), which favours longer odds
+    /// than cash betting would.
+    async fn best_free_bet_use(&self, prediction: &Prediction, book: &OddsBook) -> Result<Option<BettingDecision>> {
+        let mut best: Option<(f64, BettingDecision)> = None;
+
+        for promotion in self.promotions_for(&prediction.match_id).await.iter().filter(|p| p.is_free_bet()) {
+            let Some(quote) = book.quote(&promotion.bookmaker) else {
+                continue;
+            };
+            let Some(stake) = promotion.max_stake() else {
+                continue;
+            };
+            let outcomes = [
+                (BetType::HomeWin, Some(prediction.home_win_prob)),
+                (BetType::Draw, prediction.draw_prob),
+                (BetType::AwayWin, Some(prediction.away_win_prob)),
+            ];
+
+            for (bet_type, probability) in outcomes {
+                let (Some(probability), Some(price)) = (probability, quote.price_for(&bet_type)) else {
+                    continue;
+                };
+                if !promotion.applies_to(&prediction.match_id, &bet_type, &quote.bookmaker, price) {
+                    continue;
+                }
+
+                let value = free_bet_expected_value(probability, price, stake);
+                if best.as_ref().is_some_and(|(best_value, _)| *best_value >= value) {
+                    continue;
+                }
+
+                let mut bet = BettingDecision::free_bet(
+                    prediction.match_id.clone(),
+                    bet_type,
+                    stake,
+                    price,
+                    probability,
+                    "free_bet".to_string(),
+                )?;
+                bet.metadata = serde_json::json!({
+                    "bookmaker": quote.bookmaker,
+                    "promotion": promotion.id,
+                });
+                // The promotion's stake is already in the bookmaker's currency
+                (bet.currency, bet.fx_rate) = self.currency_for(&quote.bookmaker).await?;
+                best = Some((value, bet));
+            }
+        }
+
+        Ok(best.map(|(_, bet)| bet))
+    }
+
+    /// Offer a promotion to the engine until it expires or, for free bets, is used
+    pub async fn register_promotion(&self, promotion: Promotion) -> Result<Uuid> {
+        if promotion.is_expired(Utc::now()) {
+            return Err(QuantsError::InvalidPromotion(
+                format!("Promotion expired at {}", promotion.expires_at)
+            ));
+        }
+
+        let id = promotion.id;
+        info!("🎁 Registered {} promotion {} ({:?})", promotion.bookmaker, id, promotion.kind);
+        self.promotions.write().await.insert(id, promotion);
+        Ok(id)
+    }
+
+    pub async fn remove_promotion(&self, id: Uuid) -> Option<Promotion> {
+        self.promotions.write().await.remove(&id)
+    }
+
+    /// Unexpired promotions, soonest to expire first
+    pub async fn get_promotions(&self) -> Vec<Promotion> {
+        let now = Utc::now();
+        let mut promotions = self.promotions.write().await;
+        promotions.retain(|_, promotion| !promotion.is_expired(now));
+
+        let mut active: Vec<_> = promotions.values().cloned().collect();
+        active.sort_by_key(|promotion| promotion.expires_at);
+        active
+    }
+
+    async fn promotions_for(&self, match_id: &str) -> Vec<Promotion> {
+        let now = Utc::now();
+        self.promotions.read().await
+            .values()
+            .filter(|promotion| !promotion.is_expired(now))
+            .filter(|promotion| promotion.match_id.as_deref().is_none_or(|id| id == match_id))
+            .cloned()
+            .collect()
     }
 
     pub async fn update_market_odds(&self, match_id: String, odds: SimpleMarketOdds) {
         self.market_odds.write().await.insert(match_id, odds);
     }
 
+    pub async fn update_bookmaker_odds(&self, odds: &MarketOdds) -> Result<()> {
+        self.odds_books.write().await
+            .entry(odds.match_id.clone())
+            .or_insert_with(|| OddsBook::new(odds.match_id.clone()))
+            .upsert(odds)
+    }
+
+    pub async fn update_derived_odds(&self, match_id: String, odds: DerivedMarketOdds) {
+        self.derived_odds.write().await.insert(match_id, odds);
+    }
+
+    pub async fn update_asian_handicap_odds(&self, match_id: String, odds: AsianHandicapOdds) {
+        self.asian_handicap_odds.write().await.insert(match_id, odds);
+    }
+
+    pub async fn update_correct_score_odds(&self, match_id: String, odds: CorrectScoreOdds) {
+        self.correct_score_odds.write().await.insert(match_id, odds);
+    }
+
+    pub async fn update_half_result_odds(&self, match_id: String, odds: HalfResultOdds) {
+        self.half_result_odds.write().await.insert(match_id, odds);
+    }
+
+    pub async fn update_market_liquidity(&self, match_id: String, liquidity: Decimal) {
+        self.market_liquidity.write().await.insert(match_id, liquidity);
+    }
+
+    /// Latest prices from the aggregated market feed
+    pub async fn get_market_odds(&self, match_id: &str) -> Option<SimpleMarketOdds> {
+        self.market_odds.read().await.get(match_id).cloned()
+    }
+
+    pub async fn get_odds_book(&self, match_id: &str) -> Option<OddsBook> {
+        self.odds_books.read().await.get(match_id).cloned()
+    }
+
+    /// The live portfolio, with each strategy book's results under `strategies`
     pub async fn get_portfolio_summary(&self) -> PortfolioSummary {
+        let strategies = self.get_strategy_book_summaries().await;
         let portfolio = self.portfolio.read().await;
         let trade_count = *self.trade_count.read().await;
+        let match_info = self.match_info.read().await;
+        PortfolioSummary {
+            strategies,
+            ..self.summarize(&portfolio, trade_count, &match_info)
+        }
+    }
+
+    /// Each strategy book's results, by preset name
+    pub async fn get_strategy_book_summaries(&self) -> BTreeMap<String, PortfolioSummary> {
+        let books = self.strategy_books.read().await;
+        let match_info = self.match_info.read().await;
+        books.iter()
+            .map(|(preset, portfolio)| {
+                let trades = (portfolio.active_bets.len() + portfolio.historical_bets.len()) as u64;
+                (preset.clone(), self.summarize(portfolio, trades, &match_info))
+            })
+            .collect()
+    }
+
+    fn summarize(&self, portfolio: &Portfolio, total_trades: u64, match_info: &HashMap<String, MatchInfo>) -> PortfolioSummary {
+        let concentration = self.risk_manager.concentration(&portfolio.active_bets, match_info);
+        let exposure = self.risk_manager.exposure_report(portfolio.total_bankroll, &portfolio.active_bets, match_info);
 
         PortfolioSummary {
+            currency: portfolio.currency,
             total_bankroll: portfolio.total_bankroll,
             available_bankroll: portfolio.available_bankroll,
             total_exposure: portfolio.total_exposure(),
             active_bets_count: portfolio.active_bets.len(),
-            total_trades: trade_count,
+            total_trades,
             roi: portfolio.roi,
+            return_on_capital: portfolio.return_on_capital(),
             win_rate: portfolio.win_rate,
             profit_loss: portfolio.total_profit_loss,
+            max_drawdown: portfolio.max_drawdown,
+            sharpe_ratio: portfolio.sharpe_ratio,
+            concentration,
+            exposure,
+            exposure_by_currency: portfolio.exposure_by_currency(),
+            strategies: BTreeMap::new(),
+        }
+    }
+
+    /// Deposit (positive `amount`) or withdraw (negative) bankroll while
+    /// trading continues. The change is journaled before it is applied.
+    pub async fn adjust_bankroll(&self, amount: Decimal, reason: Option<String>) -> Result<BankrollAdjustment> {
+        let mut portfolio = self.portfolio.write().await;
+        portfolio.check_adjustment(amount)?;
+        let reserved = self.reservations.lock().unwrap().stake;
+        if -amount > portfolio.available_bankroll - reserved {
+            return Err(QuantsError::InvalidAdjustment(
+                format!("cannot withdraw {} while {} is held for orders being placed", -amount, reserved)
+            ));
+        }
+        if let Some(journal) = &self.journal {
+            journal.append(JournalRecord::BankrollAdjusted { amount, reason: reason.clone() })?;
+        }
+        let adjustment = portfolio.adjust_bankroll(amount, reason)?;
+        info!("🏦 Bankroll {} by {}: {} available of {} contributed",
+              if amount > Decimal::ZERO { "topped up" } else { "withdrawn" },
+              amount.abs(), adjustment.available_bankroll, adjustment.total_bankroll);
+        Ok(adjustment)
+    }
+
+    /// Every deposit and withdrawal, oldest first
+    pub async fn get_bankroll_adjustments(&self) -> Vec<BankrollAdjustment> {
+        self.portfolio.read().await.adjustments.clone()
+    }
+
+    pub async fn get_equity_curve(&self) -> EquityCurve {
+        self.portfolio.read().await.equity_curve.clone()
+    }
+
+    /// Executed trades matching `filter`, most recent first
+    pub async fn get_recent_trades(&self, filter: &HistoryFilter) -> Vec<BettingDecision> {
+        self.trade_history.read().await
+            .iter()
+            .rev()
+            .filter(|bet| filter.accepts(&bet.match_id, Some(&bet.strategy), bet.timestamp))
+            .cloned()
+            .collect()
+    }
+
+    /// Generated signals matching `filter`, most recent first. A signal's
+    /// strategy is that of the bet it recommended.
+    pub async fn get_trading_signals(&self, filter: &HistoryFilter) -> Vec<TradingSignal> {
+        self.signal_history.read().await
+            .iter()
+            .rev()
+            .filter(|signal| filter.accepts(
+                &signal.match_id,
+                signal.recommended_bet.as_ref().map(|bet| bet.strategy.as_str()),
+                signal.generated_at,
+            ))
+            .cloned()
+            .collect()
+    }
+
+    /// How each ensemble strategy's votes have played out over the bets placed
+    pub async fn strategy_attribution(&self) -> BTreeMap<String, StrategyAttribution> {
+        let portfolio = self.portfolio.read().await;
+        strategy_ensemble::attribute(portfolio.active_bets.iter().chain(&portfolio.historical_bets))
+    }
+
+    pub async fn get_active_bets(&self) -> Vec<BettingDecision> {
+        self.portfolio.read().await.active_bets.clone()
+    }
+
+    /// A bet as booked, which may differ from the one signalled in stake and
+    /// odds if the venue filled it in part or at a better price
+    pub async fn get_bet(&self, bet_id: Uuid) -> Option<BettingDecision> {
+        let portfolio = self.portfolio.read().await;
+        portfolio.active_bets.iter()
+            .chain(&portfolio.historical_bets)
+            .find(|bet| bet.id == bet_id)
+            .cloned()
+    }
+
+    /// Settled and open bets on one match
+    pub async fn get_match_bets(&self, match_id: &str) -> Vec<BettingDecision> {
+        let portfolio = self.portfolio.read().await;
+        portfolio.historical_bets.iter()
+            .chain(&portfolio.active_bets)
+            .filter(|bet| bet.covers_match(match_id))
+            .cloned()
+            .collect()
+    }
+
+    pub async fn get_match_info(&self, match_id: &str) -> Option<MatchInfo> {
+        self.match_info.read().await.get(match_id).cloned()
+    }
+
+    /// Settle on the result alone. Asian handicap and correct score bets
+    /// need the score and stay open; use `settle_match` when it is known.
+    /// Returns how many bets were settled.
+    pub async fn settle_bet(&self, match_id: &str, outcome: impl Into<BetOutcome>) -> Result<usize> {
+        self.settle_bets(match_id, &outcome.into(), None, None).await
+    }
+
+    /// Void every open bet on a postponed or cancelled match, returning the
+    /// stakes to the bankroll. Returns how many bets were voided.
+    pub async fn void_match(&self, match_id: &str) -> Result<usize> {
+        self.settle_bets(match_id, &BetOutcome::Void, None, None).await
+    }
+
+    /// Settle every open bet on a match from its final score. Bets already
+    /// settled are untouched, so settling twice is harmless. Returns how many
+    /// bets were settled.
+    pub async fn settle_match(&self, match_id: &str, home_goals: u8, away_goals: u8) -> Result<usize> {
+        let outcome = Outcome1X2::from_score(home_goals, away_goals).into();
+        self.settle_bets(match_id, &outcome, Some((home_goals, away_goals)), None).await
+    }
+
+    /// Settle the first half's result bets from the half-time score, which
+    /// is kept for settling the second half at full time. Returns how many
+    /// bets were settled.
+    pub async fn settle_first_half(&self, match_id: &str, home_goals: u8, away_goals: u8) -> Result<usize> {
+        match self.match_info.write().await.get_mut(match_id) {
+            Some(info) => info.half_time_score = Some((home_goals, away_goals)),
+            None => {
+                warn!("🏁 No match info for {}; its first half bets stay open", match_id);
+                return Ok(0);
+            }
         }
+        let outcome = Outcome1X2::from_score(home_goals, away_goals).into();
+        self.settle_bets(match_id, &outcome, None, Some(MatchHalf::First)).await
     }
 
-    pub async fn settle_bet(&self, match_id: &str, outcome: BetOutcome) -> Result<()> {
+    /// Settle the open bets on a match, or only those on `half` if given
+    async fn settle_bets(
+        &self,
+        match_id: &str,
+        outcome: &BetOutcome,
+        score: Option<(u8, u8)>,
+        half: Option<MatchHalf>,
+    ) -> Result<usize> {
+        let info = self.get_match_info(match_id).await;
         let mut portfolio = self.portfolio.write().await;
+        let drawdown_before = portfolio.equity_curve.current_drawdown();
         
         // Find bets for this match and settle them
         let bet_ids: Vec<_> = portfolio.active_bets
             .iter()
-            .filter(|bet| bet.match_id == match_id)
+            .filter(|bet| bet.match_id == match_id && (half.is_none() || bet.bet_type.half() == half))
             .map(|bet| bet.id)
             .collect();
 
+        let mut settled = 0;
         for bet_id in bet_ids {
-            let won = self.determine_bet_result(&portfolio, bet_id, &outcome)?;
-            portfolio.settle_bet(bet_id, won)?;
+            let Some(result) = self.determine_bet_result(&portfolio, bet_id, outcome, score, info.as_ref())? else {
+                warn!("🏁 Leaving bet {} on {} open: its market needs the final score", bet_id, match_id);
+                continue;
+            };
+            let record = match result {
+                BetStatus::Void => JournalRecord::BetVoided { bet_id },
+                BetStatus::HalfWon | BetStatus::HalfLost => {
+                    JournalRecord::BetHalfSettled { bet_id, won: result == BetStatus::HalfWon }
+                }
+                _ => JournalRecord::BetSettled { bet_id, won: result == BetStatus::Won },
+            };
+            if let Some(journal) = &self.journal {
+                journal.append(record.clone())?;
+            }
+            match record {
+                JournalRecord::BetSettled { won, .. } => portfolio.settle_bet(bet_id, won)?,
+                JournalRecord::BetHalfSettled { won, .. } => portfolio.settle_half_bet(bet_id, won)?,
+                _ => portfolio.void_bet(bet_id)?,
+            }
             
-            info!("🏁 Bet settled for {}: {} ({})", 
-                  match_id, 
-                  if won { "WON" } else { "LOST" },
-                  bet_id
-            );
+            info!("🏁 Bet settled for {}: {:?} ({})", match_id, result, bet_id);
+            self.record_settlement(&portfolio).await;
+            settled += 1;
         }
 
-        Ok(())
+        // Accumulator legs on the match; an accumulator only closes with its
+        // first losing leg or its last leg
+        let legs: Vec<_> = portfolio.active_bets
+            .iter()
+            .flat_map(|bet| {
+                bet.bet_type.legs().iter()
+                    .filter(|leg| leg.match_id == match_id && !leg.is_settled())
+                    .filter(|leg| half.is_none() || leg.bet_type.half() == half)
+                    .map(move |leg| (bet.id, leg.bet_type.clone()))
+            })
+            .collect();
+        for (bet_id, bet_type) in legs {
+            let Some(result) = bet_type_result(&bet_type, outcome, score, info.as_ref()) else {
+                warn!("🏁 Leaving accumulator {}'s leg on {} open: its market needs the final score", bet_id, match_id);
+                continue;
+            };
+            if let Some(journal) = &self.journal {
+                journal.append(JournalRecord::AccumulatorLegSettled {
+                    bet_id,
+                    match_id: match_id.to_string(),
+                    status: result.clone(),
+                })?;
+            }
+            let Some(closed) = portfolio.settle_leg(bet_id, match_id, result.clone())? else {
+                debug!("🏁 Accumulator leg settled for {}: {:?} ({})", match_id, result, bet_id);
+                continue;
+            };
+            info!("🏁 Accumulator settled with {}: {:?} ({})", match_id, closed, bet_id);
+            self.record_settlement(&portfolio).await;
+            settled += 1;
+        }
+
+        if settled > 0 {
+            let drawdown = portfolio.equity_curve.current_drawdown();
+            for threshold in self.risk_manager.drawdown_crossed(drawdown_before, drawdown) {
+                warn!("📉 Drawdown of {:.1}% crossed {:.0}%", drawdown * 100.0, threshold * 100.0);
+                self.publish(DomainEvent::DrawdownThresholdCrossed { threshold, drawdown });
+            }
+        }
+        // Sizing for the books reads the live portfolio while holding them
+        drop(portfolio);
+        self.settle_strategy_books(match_id, outcome, score, half, info.as_ref()).await;
+        if half.is_none() {
+            self.retire_match(match_id).await;
+        }
+        Ok(settled)
     }
 
-    fn determine_bet_result(
-        &self, 
-        portfolio: &Portfolio, 
-        bet_id: uuid::Uuid, 
-        outcome: &BetOutcome
-    ) -> Result<bool> {
-        let bet = portfolio.active_bets
-            .iter()
-            .find(|b| b.id == bet_id)
-            .ok_or_else(|| QuantsError::MatchNotFound { 
-                match_id: bet_id.to_string() 
-            })?;
+    /// Settle the strategy books' bets on a match the same way. They are
+    /// not journaled, so a failure is only logged.
+    async fn settle_strategy_books(
+        &self,
+        match_id: &str,
+        outcome: &BetOutcome,
+        score: Option<(u8, u8)>,
+        half: Option<MatchHalf>,
+        info: Option<&MatchInfo>,
+    ) {
+        let mut books = self.strategy_books.write().await;
+        for (preset, portfolio) in books.iter_mut() {
+            let bet_ids: Vec<_> = portfolio.active_bets.iter()
+                .filter(|bet| bet.match_id == match_id && (half.is_none() || bet.bet_type.half() == half))
+                .map(|bet| bet.id)
+                .collect();
+            for bet_id in bet_ids {
+                let settled = match self.determine_bet_result(portfolio, bet_id, outcome, score, info) {
+                    Ok(Some(BetStatus::Void)) => portfolio.void_bet(bet_id),
+                    Ok(Some(result @ (BetStatus::HalfWon | BetStatus::HalfLost))) => {
+                        portfolio.settle_half_bet(bet_id, result == BetStatus::HalfWon)
+                    }
+                    Ok(Some(result)) => portfolio.settle_bet(bet_id, result == BetStatus::Won),
+                    Ok(None) => Ok(()),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = settled {
+                    warn!("📒 Could not settle bet {} in the {} book: {}", bet_id, preset, e);
+                }
+            }
+        }
+    }
 
-        let won = match (&bet.bet_type, outcome) {
-            (BetType::HomeWin, BetOutcome::HomeWin) => true,
-            (BetType::Draw, BetOutcome::Draw) => true,
-            (BetType::AwayWin, BetOutcome::AwayWin) => true,
-            _ => false,
-        };
+    /// Announce the bet the portfolio settled last and count it towards the
+    /// daily loss limit
+    async fn record_settlement(&self, portfolio: &Portfolio) {
+        let profit_loss = last_settled_profit_loss(portfolio);
+        if let Some(bet) = portfolio.historical_bets.last() {
+            self.publish(DomainEvent::BetSettled { bet: bet.clone(), profit_loss });
+        }
+        self.record_daily_loss(profit_loss).await;
+    }
 
-        Ok(won)
+    /// Count a settlement towards the daily loss limit, tripping the kill
+    /// switch if it takes the day's losses to the limit
+    async fn record_daily_loss(&self, profit_loss: Decimal) {
+        let now = Utc::now();
+        let mut daily_loss = self.risk_manager.daily_loss.write().await;
+        daily_loss.record(now, profit_loss, now);
+        if daily_loss.check(self.risk_manager.max_daily_loss, now) {
+            let realized_loss = daily_loss.realized_loss(now);
+            warn!("🛑 Daily loss limit of {} reached ({} lost); trading halted until {}",
+                  self.risk_manager.max_daily_loss, realized_loss, daily_loss.schedule().next_reset(now));
+            self.publish(DomainEvent::RiskLimitBreached {
+                limit: RiskLimit::DailyLoss { limit: self.risk_manager.max_daily_loss, realized_loss },
+                match_id: None,
+            });
+        }
     }
-}
 
-#[derive(Debug, Clone)]
+    /// Signals, risk checks, placements and settlements as they happen
+    pub fn subscribe_blotter(&self) -> tokio::sync::broadcast::Receiver<BlotterMessage> {
+        self.blotter.subscribe()
+    }
+
+    fn publish(&self, event: DomainEvent) {
+        self.blotter.record_domain_event(&event);
+        if let Some(events) = &self.events {
+            events.publish(event);
+        }
+    }
+
+    /// Add to the audit trail; a failed write is logged rather than
+    /// stopping the trade
+    async fn audit(&self, entry: AuditEntry) {
+        self.blotter.record_audit(&entry);
+        if let Err(e) = self.audit.append(&entry).await {
+            error!("🧾 Could not write {} audit entry for {}: {}", entry.kind(), entry.match_id, e);
+        }
+    }
+
+    /// Record the outcome of the pre-execution checks: approved when no
+    /// `constraint` stopped the trade
+    async fn audit_risk_decision(
+        &self,
+        signal: &TradingSignal,
+        bet: &BettingDecision,
+        constraint: Option<RiskConstraint>,
+        detail: Option<String>,
+    ) {
+        let event = AuditEvent::RiskDecision { approved: constraint.is_none(), constraint, detail };
+        self.audit(AuditEntry::new(signal.match_id.clone(), event).with_signal(signal.id).with_bet(bet.id)).await;
+    }
+
+    async fn audit_execution(
+        &self,
+        signal: &TradingSignal,
+        bet: &BettingDecision,
+        key: &str,
+        outcome: ExecutionOutcome,
+        detail: Option<String>,
+    ) {
+        let event = AuditEvent::Execution { idempotency_key: key.to_string(), outcome, detail };
+        self.audit(AuditEntry::new(signal.match_id.clone(), event).with_signal(signal.id).with_bet(bet.id)).await;
+    }
+
+    /// Start a new trading day, lifting the kill switch
+    pub async fn reset_daily_loss(&self) {
+        let now = Utc::now();
+        let closing_loss = self.risk_manager.daily_loss.write().await.reset(now);
+        info!("🌅 Daily loss limit reset; the previous day closed {} down", closing_loss);
+    }
+
+    /// What a restart needs to carry on trading where this run is now
+    pub async fn state(&self) -> TradingState {
+        // Books are taken first: settling them reads the portfolio while holding them
+        let strategy_books = self.strategy_books.read().await.clone();
+        // Journal appends happen under the portfolio lock, so the sequence matches it
+        let portfolio = self.portfolio.read().await;
+        TradingState {
+            portfolio: portfolio.clone(),
+            strategy_books,
+            trade_count: *self.trade_count.read().await,
+            match_info: self.match_info.read().await.clone(),
+            daily_loss: self.risk_manager.daily_loss.read().await.clone(),
+            journal_seq: self.journal.as_ref().map_or(0, |journal| journal.next_seq()),
+        }
+    }
+
+    pub async fn get_daily_loss_status(&self) -> DailyLossStatus {
+        self.risk_manager.daily_loss.read().await.status(self.risk_manager.max_daily_loss, Utc::now())
+    }
+
+    pub async fn daily_reset_schedule(&self) -> DailyResetSchedule {
+        self.risk_manager.daily_loss.read().await.schedule()
+    }
+
+    /// `None` when the bet cannot be settled from what is known
+    fn determine_bet_result(
+        &self, 
+        portfolio: &Portfolio, 
+        bet_id: uuid::Uuid, 
+        outcome: &BetOutcome,
+        score: Option<(u8, u8)>,
+        info: Option<&MatchInfo>,
+    ) -> Result<Option<BetStatus>> {
+        let bet = portfolio.active_bets
+            .iter()
+            .find(|b| b.id == bet_id)
+            .ok_or_else(|| QuantsError::MatchNotFound { 
+                match_id: bet_id.to_string() 
+            })?;
+        Ok(bet_type_result(&bet.bet_type, outcome, score, info))
+    }
+}
+
+/// How a selection on a match settles, or `None` when it cannot be settled
+/// from what is known
+fn bet_type_result(
+    bet_type: &BetType,
+    outcome: &BetOutcome,
+    score: Option<(u8, u8)>,
+    info: Option<&MatchInfo>,
+) -> Option<BetStatus> {
+    let BetOutcome::Result(outcome) = *outcome else {
+        return Some(BetStatus::Void);
+    };
+
+    let won = match (bet_type, outcome) {
+        (BetType::HomeWin, Outcome1X2::HomeWin) => true,
+        (BetType::Draw, Outcome1X2::Draw) => true,
+        (BetType::AwayWin, Outcome1X2::AwayWin) => true,
+        (BetType::DoubleChance { selection }, outcome) => match selection {
+            DoubleChanceSelection::HomeOrDraw => outcome != Outcome1X2::AwayWin,
+            DoubleChanceSelection::DrawOrAway => outcome != Outcome1X2::HomeWin,
+            DoubleChanceSelection::HomeOrAway => outcome != Outcome1X2::Draw,
+        },
+        (BetType::DrawNoBet { .. }, Outcome1X2::Draw) => return Some(BetStatus::Void),
+        (BetType::DrawNoBet { home }, Outcome1X2::HomeWin) => *home,
+        (BetType::DrawNoBet { home }, Outcome1X2::AwayWin) => !*home,
+        (BetType::AsianHandicap { line, team }, _) => {
+            let (Some((home_goals, away_goals)), Some(info)) = (score, info) else {
+                return None;
+            };
+            let margin = i32::from(home_goals) - i32::from(away_goals);
+            let margin = if *team == info.team_home {
+                margin
+            } else if *team == info.team_away {
+                -margin
+            } else {
+                return None;
+            };
+            return Some(HandicapResult::settle(*line, margin).bet_status());
+        }
+        (BetType::CorrectScore { home_goals, away_goals }, _) => score? == (*home_goals, *away_goals),
+        (BetType::HalfResult { half, outcome }, _) => {
+            // Without the half-time score, neither half can be settled;
+            // at full time it never will be, so the bet is void
+            let Some((half_home, half_away)) = info.and_then(|info| info.half_time_score) else {
+                return score.map(|_| BetStatus::Void);
+            };
+            let goals = match half {
+                MatchHalf::First => (half_home, half_away),
+                MatchHalf::Second => {
+                    let (home_goals, away_goals) = score?;
+                    (home_goals.saturating_sub(half_home), away_goals.saturating_sub(half_away))
+                }
+            };
+            Outcome1X2::from_score(goals.0, goals.1) == *outcome
+        }
+        // Settled leg by leg instead
+        (BetType::Accumulator { .. }, _) => return None,
+        _ => false,
+    };
+
+    Some(if won { BetStatus::Won } else { BetStatus::Lost })
+}
+
+/// Bookmaker name used for prices from the aggregated market feed
+pub const MARKET_FEED_BOOKMAKER: &str = "market";
+
+/// Signals weaker than this are not traded
+pub const MIN_EXECUTION_STRENGTH: f64 = 0.3;
+
+/// Human-readable market and selection, e.g. "Correct Score 2-1"
+pub fn describe_bet_type(bet_type: &BetType) -> String {
+    match bet_type {
+        BetType::HomeWin => "Home Win".to_string(),
+        BetType::Draw => "Draw".to_string(),
+        BetType::AwayWin => "Away Win".to_string(),
+        BetType::DoubleChance { selection } => format!("Double Chance {}", selection.as_str()),
+        BetType::DrawNoBet { home } => format!("Draw No Bet {}", if *home { "Home" } else { "Away" }),
+        BetType::CorrectScore { home_goals, away_goals } => format!("Correct Score {}-{}", home_goals, away_goals),
+        BetType::HalfResult { half, outcome } => {
+            let half = match half {
+                MatchHalf::First => "First Half",
+                MatchHalf::Second => "Second Half",
+            };
+            format!("{} {}", half, outcome)
+        }
+        BetType::AsianHandicap { line, team } => {
+            format!("Asian Handicap {} {}{}", team, if *line > Decimal::ZERO { "+" } else { "" }, line)
+        }
+        BetType::Accumulator { legs } => format!("Accumulator ({} legs)", legs.len()),
+        _ => "Other".to_string(),
+    }
+}
+
+/// Win probability for each double chance and draw no bet selection, with
+/// the chance the bet is refunded. Draw no bet is priced on the probability
+/// of winning given no draw, which is what its odds and Kelly stake reflect.
+fn derived_market_probabilities(prediction: &Prediction) -> Vec<(BetType, f64, f64)> {
+    let Some(draw) = prediction.draw_prob else {
+        return Vec::new();
+    };
+    let (home, away) = (prediction.home_win_prob, prediction.away_win_prob);
+    let decided = home + away;
+
+    let mut markets: Vec<_> = DoubleChanceSelection::ALL
+        .into_iter()
+        .map(|selection| {
+            let probability = match selection {
+                DoubleChanceSelection::HomeOrDraw => home + draw,
+                DoubleChanceSelection::DrawOrAway => draw + away,
+                DoubleChanceSelection::HomeOrAway => decided,
+            };
+            (BetType::DoubleChance { selection }, probability.min(1.0), 0.0)
+        })
+        .collect();
+
+    if decided > 0.0 {
+        markets.push((BetType::DrawNoBet { home: true }, home / decided, draw));
+        markets.push((BetType::DrawNoBet { home: false }, away / decided, draw));
+    }
+    markets
+}
+
+/// The prediction's exact score grid, or one fitted to its match winner
+/// probabilities for models that do not predict goals
+pub fn score_matrix_for(prediction: &Prediction) -> ScoreMatrix {
+    if let Some(matrix) = &prediction.score_matrix {
+        return matrix.clone();
+    }
+    let (lambda_home, lambda_away) = implied_lambdas(prediction);
+    PoissonModel::score_matrix(lambda_home, lambda_away)
+}
+
+fn implied_lambdas(prediction: &Prediction) -> (f64, f64) {
+    let total_goals = match (prediction.expected_goals_home, prediction.expected_goals_away) {
+        (Some(home), Some(away)) => home + away,
+        _ => DEFAULT_TOTAL_GOALS,
+    };
+    PoissonModel::implied_lambdas(prediction.home_win_prob, prediction.away_win_prob, total_goals)
+}
+
+/// Win probability for each outcome of each half, from goal expectations
+/// fitted to the prediction and split between the halves
+fn half_result_probabilities(prediction: &Prediction) -> Vec<(BetType, f64)> {
+    let (lambda_home, lambda_away) = implied_lambdas(prediction);
+
+    [MatchHalf::First, MatchHalf::Second]
+        .into_iter()
+        .flat_map(|half| {
+            let (home, draw, away) = PoissonModel::half_result_probabilities(lambda_home, lambda_away, half);
+            [
+                (BetType::HalfResult { half, outcome: Outcome1X2::HomeWin }, home),
+                (BetType::HalfResult { half, outcome: Outcome1X2::Draw }, draw),
+                (BetType::HalfResult { half, outcome: Outcome1X2::AwayWin }, away),
+            ]
+        })
+        .collect()
+}
+
+/// Both sides of every quoted Asian handicap line, priced from goal
+/// expectations fitted to the prediction. Each comes with its quote, the
+/// probability of winning given no refund and the refunded share of the stake.
+fn asian_handicap_probabilities(
+    prediction: &Prediction,
+    odds: &AsianHandicapOdds,
+    info: &MatchInfo,
+) -> Vec<(BetType, Decimal, f64, f64)> {
+    let (lambda_home, lambda_away) = implied_lambdas(prediction);
+
+    odds.quotes.iter()
+        .flat_map(|quote| {
+            let home = PoissonModel::handicap_probabilities(lambda_home, lambda_away, quote.line);
+            let away = PoissonModel::handicap_probabilities(lambda_away, lambda_home, -quote.line);
+            [
+                (
+                    BetType::AsianHandicap { line: quote.line, team: info.team_home.clone() },
+                    quote.home,
+                    home.win_given_no_push(),
+                    home.push_probability(),
+                ),
+                (
+                    BetType::AsianHandicap { line: (-quote.line).normalize(), team: info.team_away.clone() },
+                    quote.away,
+                    away.win_given_no_push(),
+                    away.push_probability(),
+                ),
+            ]
+        })
+        .collect()
+}
+
+fn push_bounded<T>(history: &mut VecDeque<T>, item: T, capacity: usize) {
+    history.push_back(item);
+    while history.len() > capacity {
+        history.pop_front();
+    }
+}
+
+/// Profit or loss of the bet the portfolio settled last
+fn last_settled_profit_loss(portfolio: &Portfolio) -> Decimal {
+    portfolio.historical_bets.last()
+        .and_then(|bet| bet.base_settled_profit_loss())
+        .unwrap_or(Decimal::ZERO)
+}
+
+fn promotion_id(bet: &BettingDecision) -> Option<Uuid> {
+    bet.metadata.get("promotion")?.as_str()?.parse().ok()
+}
+
+impl RiskManager {
+    /// Thresholds that equity's drawdown fell through going from `before` to `after`
+    fn drawdown_crossed(&self, before: f64, after: f64) -> Vec<f64> {
+        self.drawdown_thresholds.iter()
+            .copied()
+            .filter(|threshold| before < *threshold && after >= *threshold)
+            .collect()
+    }
+
+    /// Caps a proposed stake so that neither the match's league nor either
+    /// team ends up with more than its exposure limit of the bankroll at risk.
+    pub fn size_for_correlation(
+        &self,
+        proposed_stake: Decimal,
+        match_id: &str,
+        bankroll: Decimal,
+        active_bets: &[BettingDecision],
+        match_info: &HashMap<String, MatchInfo>,
+    ) -> Decimal {
+        let Some(info) = match_info.get(match_id) else {
+            return proposed_stake;
+        };
+
+        let group_exposure = group_exposure(active_bets, match_info);
+        info.exposure_groups()
+            .iter()
+            .map(|group| {
+                let exposure = group_exposure.get(group).copied().unwrap_or(Decimal::ZERO);
+                (self.group_limit(group, bankroll) - exposure).max(Decimal::ZERO)
+            })
+            .fold(proposed_stake, Decimal::min)
+    }
+
+    /// Turn away accumulators whose legs move together: two legs on one
+    /// match, or a team playing in two of its matches. They are priced as
+    /// independent, which would overstate what such a slip is worth.
+    pub fn check_accumulator(&self, legs: &[AccumulatorLeg], match_info: &HashMap<String, MatchInfo>) -> Result<()> {
+        let mut matches = HashSet::new();
+        let mut teams: HashMap<&str, &str> = HashMap::new();
+        for leg in legs {
+            if !matches.insert(leg.match_id.as_str()) {
+                return Err(QuantsError::InvalidAccumulator(format!("more than one leg on {}", leg.match_id)));
+            }
+            let Some(info) = match_info.get(&leg.match_id) else {
+                continue;
+            };
+            for team in [&info.team_home, &info.team_away] {
+                if let Some(other) = teams.insert(team, &leg.match_id) {
+                    return Err(QuantsError::InvalidAccumulator(
+                        format!("{} plays in both {} and {}", team, other, leg.match_id)
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Open stake on every league and team with a bet on it, against its limit
+    pub fn exposure_report(
+        &self,
+        bankroll: Decimal,
+        active_bets: &[BettingDecision],
+        match_info: &HashMap<String, MatchInfo>,
+    ) -> ExposureReport {
+        let mut report = ExposureReport::default();
+        for (group, exposure) in group_exposure(active_bets, match_info) {
+            let limit = self.group_limit(&group, bankroll);
+            let (name, groups) = match group {
+                ExposureGroup::League(name) => (name, &mut report.leagues),
+                ExposureGroup::Team(name) => (name, &mut report.teams),
+            };
+            let utilization = if limit > Decimal::ZERO {
+                (exposure / limit).to_f64().unwrap_or(0.0)
+            } else {
+                1.0
+            };
+            groups.push(GroupExposure { name, exposure, limit, utilization });
+        }
+        for groups in [&mut report.leagues, &mut report.teams] {
+            groups.sort_by(|a, b| b.exposure.cmp(&a.exposure).then_with(|| a.name.cmp(&b.name)));
+        }
+        report
+    }
+
+    fn group_limit(&self, group: &ExposureGroup, bankroll: Decimal) -> Decimal {
+        let fraction = self.exposure_limits.limit_for(group, self.max_correlated_exposure);
+        bankroll * Decimal::from_f64(fraction).unwrap_or(Decimal::ZERO)
+    }
+
+    /// Herfindahl index of open exposure by league, team and bet type. A
+    /// match's stake is split evenly between its two teams, and an
+    /// accumulator's between its legs; bets on matches without league/team
+    /// context only count towards bet type.
+    pub fn concentration(
+        &self,
+        active_bets: &[BettingDecision],
+        match_info: &HashMap<String, MatchInfo>,
+    ) -> PortfolioConcentration {
+        let mut leagues: HashMap<String, f64> = HashMap::new();
+        let mut teams: HashMap<String, f64> = HashMap::new();
+        let mut bet_types: HashMap<String, f64> = HashMap::new();
+
+        for bet in active_bets {
+            let stake = bet.base_cash_stake().to_f64().unwrap_or(0.0);
+            *bet_types.entry(describe_bet_type(&bet.bet_type)).or_default() += stake;
+            let matches = bet_matches(bet);
+            let stake = stake / matches.len() as f64;
+            for info in matches.iter().filter_map(|match_id| match_info.get(*match_id)) {
+                *leagues.entry(info.league.clone()).or_default() += stake;
+                *teams.entry(info.team_home.clone()).or_default() += stake / 2.0;
+                *teams.entry(info.team_away.clone()).or_default() += stake / 2.0;
+            }
+        }
+
+        let dimensions = [("league", &leagues), ("team", &teams), ("bet type", &bet_types)];
+        let alerts = if active_bets.len() >= MIN_BETS_FOR_CONCENTRATION {
+            dimensions.iter()
+                .filter_map(|(dimension, exposure)| {
+                    let hhi = herfindahl(exposure);
+                    let (largest_group, largest_share) = largest_share(exposure)?;
+                    (hhi > self.max_concentration).then(|| ConcentrationAlert {
+                        dimension: dimension.to_string(),
+                        hhi,
+                        threshold: self.max_concentration,
+                        largest_group,
+                        largest_share,
+                    })
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let league_hhi = herfindahl(&leagues);
+        let team_hhi = herfindahl(&teams);
+        let bet_type_hhi = herfindahl(&bet_types);
+
+        PortfolioConcentration {
+            league_hhi,
+            team_hhi,
+            bet_type_hhi,
+            diversification_score: 1.0 - league_hhi.max(team_hhi).max(bet_type_hhi),
+            alerts,
+        }
+    }
+}
+
+/// Open stake per league and team. Each team carries the full stake of its
+/// matches, and an accumulator's full stake counts once in every group any
+/// leg is in; bets on matches without league/team context are left out.
+fn group_exposure(
+    active_bets: &[BettingDecision],
+    match_info: &HashMap<String, MatchInfo>,
+) -> HashMap<ExposureGroup, Decimal> {
+    let mut exposure: HashMap<ExposureGroup, Decimal> = HashMap::new();
+    for bet in active_bets {
+        let groups: HashSet<_> = bet_matches(bet).into_iter()
+            .filter_map(|match_id| match_info.get(match_id))
+            .flat_map(MatchInfo::exposure_groups)
+            .collect();
+        for group in groups {
+            *exposure.entry(group).or_insert(Decimal::ZERO) += bet.base_cash_stake();
+        }
+    }
+    exposure
+}
+
+/// The matches a bet rides on: its own, or each of an accumulator's legs'
+fn bet_matches(bet: &BettingDecision) -> Vec<&str> {
+    match bet.bet_type.legs() {
+        [] => vec![bet.match_id.as_str()],
+        legs => legs.iter().map(|leg| leg.match_id.as_str()).collect(),
+    }
+}
+
+/// Sum of squared shares: 1.0 when everything sits in one group, 1/n when
+/// spread evenly over n groups, 0.0 with no exposure
+fn herfindahl(exposure: &HashMap<String, f64>) -> f64 {
+    let total: f64 = exposure.values().sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+    exposure.values().map(|stake| (stake / total).powi(2)).sum()
+}
+
+fn largest_share(exposure: &HashMap<String, f64>) -> Option<(String, f64)> {
+    let total: f64 = exposure.values().sum();
+    if total <= 0.0 {
+        return None;
+    }
+    exposure.iter()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(group, stake)| (group.clone(), stake / total))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PortfolioConcentration {
+    pub league_hhi: f64,
+    pub team_hhi: f64,
+    pub bet_type_hhi: f64,
+    /// 1.0 minus the most concentrated dimension's index
+    pub diversification_score: f64,
+    pub alerts: Vec<ConcentrationAlert>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConcentrationAlert {
+    pub dimension: String,
+    pub hhi: f64,
+    pub threshold: f64,
+    pub largest_group: String,
+    pub largest_share: f64,
+}
+
+#[derive(Debug, Clone)]
 pub struct PortfolioSummary {
+    /// The base currency every total is in
+    pub currency: Currency,
     pub total_bankroll: Decimal,
     pub available_bankroll: Decimal,
     pub total_exposure: Decimal,
     pub active_bets_count: usize,
     pub total_trades: u64,
+    /// Profit and loss over total stakes
     pub roi: f64,
+    /// Profit and loss over net contributions
+    pub return_on_capital: f64,
     pub win_rate: f64,
     pub profit_loss: Decimal,
+    pub max_drawdown: f64,
+    pub sharpe_ratio: f64,
+    pub concentration: PortfolioConcentration,
+    pub exposure: ExposureReport,
+    /// Open stake in each currency bets were placed in, unconverted
+    pub exposure_by_currency: BTreeMap<Currency, Decimal>,
+    /// Each strategy book's own results, by preset name
+    pub strategies: BTreeMap<String, PortfolioSummary>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BetOutcome {
-    HomeWin,
-    Draw,
-    AwayWin,
+    Result(Outcome1X2),
+    /// Postponed or cancelled: there is no result and every stake is returned
+    Void,
+}
+
+impl From<Outcome1X2> for BetOutcome {
+    fn from(outcome: Outcome1X2) -> Self {
+        Self::Result(outcome)
+    }
 }
 
 impl Default for RiskAssessment {
@@ -483,6 +3089,8 @@ impl Default for RiskAssessment {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::blotter::BlotterEvent;
+    use quant_models::{AsianHandicapQuote, CorrectScoreQuote};
     use rust_decimal_macros::dec;
 
     #[tokio::test]
@@ -495,6 +3103,52 @@ mod tests {
         assert_eq!(summary.active_bets_count, 0);
     }
 
+    #[tokio::test]
+    async fn test_simulate_trade_reports_binding_constraints() {
+        let engine = TradingEngine::new(dec!(1000.0));
+        engine.update_market_odds("m1".to_string(), SimpleMarketOdds::new(dec!(2.2), dec!(3.6), dec!(4.5))).await;
+        let mut prediction = Prediction::new(
+            "m1".to_string(), "poisson".to_string(), "1.0".to_string(), 0.6, 0.15, Utc::now(),
+        ).unwrap();
+        prediction.confidence = 0.9;
+
+        let trades = engine.simulate_trade(&prediction, &TradeSimulationParams::default()).await.unwrap();
+        assert_eq!(trades.len(), 3);
+        let home = &trades[0];
+        assert_eq!((&home.bet_type, home.odds, home.strategy.as_str()), (&BetType::HomeWin, dec!(2.2), "Moderate Growth"));
+        assert!(home.edge > 0.03 && home.expected_value > 0.0 && home.kelly_fraction > 0.0);
+        // Half Kelly asks for more than the strategy's 5% of bankroll
+        assert!(home.kelly_stake > dec!(50));
+        assert_eq!(home.stake, dec!(50));
+        assert_eq!(home.binding_constraints, vec![StakeConstraint::MaxStakePercent { max_stake_percent: 0.05 }]);
+        let away = trades.iter().find(|trade| trade.bet_type == BetType::AwayWin).unwrap();
+        assert_eq!(away.stake, Decimal::ZERO);
+        assert_eq!(away.binding_constraints, vec![StakeConstraint::StrategyFilter { strategy: "Moderate Growth".to_string() }]);
+        // Nothing was placed or recorded
+        assert!(engine.get_active_bets().await.is_empty());
+        assert!(engine.get_trading_signals(&HistoryFilter::default()).await.is_empty());
+
+        // At a given price, for one outcome and another strategy
+        let params = TradeSimulationParams {
+            strategy: Some("aggressive".to_string()),
+            bet_type: Some(BetType::HomeWin),
+            odds: Some(dec!(3.0)),
+        };
+        let trades = engine.simulate_trade(&prediction, &params).await.unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!((trades[0].bookmaker.as_str(), trades[0].odds), ("what_if", dec!(3.0)));
+        assert!(trades[0].stake > Decimal::ZERO);
+        assert!(trades[0].stake <= dec!(100));
+
+        let unknown = TradeSimulationParams { strategy: Some("reckless".to_string()), ..Default::default() };
+        assert!(engine.simulate_trade(&prediction, &unknown).await.is_err());
+        prediction.match_id = "unpriced".to_string();
+        assert!(matches!(
+            engine.simulate_trade(&prediction, &TradeSimulationParams::default()).await,
+            Err(QuantsError::MatchNotFound { .. })
+        ));
+    }
+
     #[tokio::test]
     async fn test_risk_constraints() {
         let engine = TradingEngine::new(dec!(1000.0));
@@ -504,9 +3158,1051 @@ mod tests {
         let constrained_stake = engine.apply_risk_constraints(
             dec!(2000.0), // More than bankroll
             "test_match",
+            None,
             &portfolio,
+            true,
         ).await;
         
         assert!(constrained_stake < dec!(1000.0));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_stakes_fitted_to_venue_limits() {
+        let limits = StakeLimits { min_stake: Some(dec!(5)), max_stake: Some(dec!(40)), increment: Some(dec!(2.5)), ..StakeLimits::default() };
+        let engine = TradingEngine::new(dec!(1000.0))
+            .with_venue_stake_limits(HashMap::from([("Paper".to_string(), limits)]));
+        let portfolio = Portfolio::new(dec!(1000.0));
+
+        assert_eq!(engine.apply_risk_constraints(dec!(23.9), "m1", None, &portfolio, true).await, dec!(22.5));
+        assert_eq!(engine.apply_risk_constraints(dec!(100), "m1", None, &portfolio, true).await, dec!(40));
+        assert_eq!(engine.apply_risk_constraints(dec!(4.9), "m1", None, &portfolio, true).await, Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_daily_loss_limit_halts_trading() {
+        // 5% of bankroll
+        let engine = TradingEngine::new(dec!(1000.0));
+        let signal = |match_id: &str| {
+            let bet = BettingDecision::new(
+                match_id.to_string(), BetType::HomeWin, dec!(60), dec!(2.0), 0.6, "test".to_string(),
+            ).unwrap();
+            TradingSignal::new(match_id.to_string(), 0.5, Some(bet), RiskAssessment::default(), String::new())
+        };
+
+        assert!(engine.execute_trade(&signal("m1")).await.unwrap());
+        assert_eq!(engine.settle_match("m1", 0, 1).await.unwrap(), 1);
+        // Settling again finds nothing open and counts nothing twice
+        assert_eq!(engine.settle_match("m1", 0, 1).await.unwrap(), 0);
+
+        let status = engine.get_daily_loss_status().await;
+        assert_eq!(status.realized_loss, dec!(60));
+        assert!(status.halted);
+        assert!(!engine.execute_trade(&signal("m2")).await.unwrap());
+        assert_eq!(engine.get_portfolio_summary().await.active_bets_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_bets_and_breaches_published_on_event_bus() {
+        let events = DomainEventBus::new();
+        let mut feed = events.subscribe();
+        let engine = TradingEngine::new(dec!(1000.0))
+            .with_event_bus(events)
+            .with_drawdown_thresholds(vec![0.1, 0.05, 0.0]);
+        let bet = BettingDecision::new(
+            "m1".to_string(), BetType::HomeWin, dec!(60), dec!(2.0), 0.6, "test".to_string(),
+        ).unwrap();
+        let signal = TradingSignal::new("m1".to_string(), 0.5, Some(bet), RiskAssessment::default(), String::new());
+
+        assert!(engine.execute_trade(&signal).await.unwrap());
+        assert_eq!(engine.settle_match("m1", 0, 1).await.unwrap(), 1);
+
+        let mut published = Vec::new();
+        while let Ok(message) = feed.try_recv() {
+            published.push(message.event);
+        }
+        assert_eq!(published.iter().map(DomainEvent::name).collect::<Vec<_>>(),
+                   ["bet_placed", "bet_settled", "risk_limit_breached", "drawdown_threshold_crossed"]);
+        assert!(matches!(published[1], DomainEvent::BetSettled { profit_loss, .. } if profit_loss == dec!(-60)));
+        assert!(matches!(published[2], DomainEvent::RiskLimitBreached { limit: RiskLimit::DailyLoss { .. }, .. }));
+        assert!(matches!(published[3], DomainEvent::DrawdownThresholdCrossed { threshold, .. } if threshold == 0.05));
+    }
+
+    #[tokio::test]
+    async fn test_blotter_follows_each_order_in_sequence() {
+        let engine = TradingEngine::new(dec!(1000.0));
+        let mut blotter = engine.subscribe_blotter();
+        for match_id in ["m1", "m2"] {
+            let bet = BettingDecision::new(
+                match_id.to_string(), BetType::HomeWin, dec!(50), dec!(2.0), 0.6, "test".to_string(),
+            ).unwrap();
+            let signal = TradingSignal::new(match_id.to_string(), 0.5, Some(bet), RiskAssessment::default(), String::new());
+            assert!(engine.execute_trade(&signal).await.unwrap());
+        }
+        assert_eq!(engine.settle_match("m1", 2, 0).await.unwrap(), 1);
+        assert_eq!(engine.void_match("m2").await.unwrap(), 1);
+
+        let mut messages = Vec::new();
+        while let Ok(message) = blotter.try_recv() {
+            messages.push(message);
+        }
+        assert!(messages.windows(2).all(|pair| pair[1].sequence == pair[0].sequence + 1));
+        let steps: Vec<_> = messages
+            .iter()
+            .map(|message| (message.match_id.as_str(), serde_json::to_value(message).unwrap()["type"].clone()))
+            .collect();
+        assert_eq!(steps, [
+            ("m1", serde_json::json!("risk_checked")),
+            ("m1", serde_json::json!("placed")),
+            ("m2", serde_json::json!("risk_checked")),
+            ("m2", serde_json::json!("placed")),
+            ("m1", serde_json::json!("settled")),
+            ("m2", serde_json::json!("voided")),
+        ]);
+        assert!(matches!(messages[4].event, BlotterEvent::Settled { profit_loss, .. } if profit_loss == dec!(50)));
+    }
+
+    /// Fills every order after a pause, counting what it was sent
+    #[derive(Default)]
+    struct SlowVenue {
+        orders: std::sync::atomic::AtomicUsize,
+        cancels: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl ExecutionVenue for SlowVenue {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        async fn place_order(&self, bet: &BettingDecision) -> anyhow::Result<OrderReceipt> {
+            self.orders.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            PaperVenue::new().place_order(bet).await
+        }
+
+        async fn cancel_order(&self, _receipt: &OrderReceipt) -> anyhow::Result<()> {
+            self.cancels.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    /// Fills part of every order at a better price
+    struct PartFillVenue;
+
+    #[async_trait::async_trait]
+    impl ExecutionVenue for PartFillVenue {
+        fn name(&self) -> &str {
+            "part-fill"
+        }
+
+        async fn place_order(&self, bet: &BettingDecision) -> anyhow::Result<OrderReceipt> {
+            Ok(OrderReceipt {
+                venue: self.name().to_string(),
+                order_id: "1".to_string(),
+                market_id: None,
+                size_matched: bet.stake - dec!(10),
+                average_price: bet.odds + dec!(0.1),
+            })
+        }
+
+        async fn cancel_order(&self, _receipt: &OrderReceipt) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bets_booked_at_the_venues_fill() {
+        let engine = TradingEngine::new(dec!(1000.0)).with_execution_venue(Arc::new(PartFillVenue));
+        let bet = BettingDecision::new(
+            "m1".to_string(), BetType::HomeWin, dec!(50), dec!(2.0), 0.6, "test".to_string(),
+        ).unwrap();
+        let signal = TradingSignal::new("m1".to_string(), 0.5, Some(bet.clone()), RiskAssessment::default(), String::new());
+        assert!(engine.execute_trade(&signal).await.unwrap());
+
+        assert_eq!(engine.get_bet(bet.id).await.map(|booked| booked.stake), Some(dec!(40)));
+        let portfolio = engine.portfolio.read().await;
+        let booked = &portfolio.active_bets[0];
+        assert_eq!((booked.stake, booked.odds), (dec!(40), dec!(2.1)));
+        assert!((booked.true_probability() - 0.6).abs() < 1e-9);
+        assert!(booked.expected_value > bet.expected_value);
+        assert_eq!(portfolio.available_bankroll, dec!(960));
+    }
+
+    #[tokio::test]
+    async fn test_orders_in_flight_hold_their_slot_and_stake() {
+        let venue = Arc::new(SlowVenue::default());
+        let mut engine = TradingEngine::new(dec!(1000.0)).with_execution_venue(venue.clone());
+        engine.risk_manager.max_concurrent_bets = 1;
+        let signal = |match_id: &str| {
+            let bet = BettingDecision::new(
+                match_id.to_string(), BetType::HomeWin, dec!(50), dec!(2.0), 0.6, "test".to_string(),
+            ).unwrap();
+            TradingSignal::new(match_id.to_string(), 0.5, Some(bet), RiskAssessment::default(), String::new())
+        };
+
+        // The second order is checked while the first is still at the venue
+        let (m1, m2) = (signal("m1"), signal("m2"));
+        let (first, second) = tokio::join!(engine.execute_trade(&m1), engine.execute_trade(&m2));
+        assert!(first.unwrap());
+        assert!(!second.unwrap());
+        assert_eq!(venue.orders.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(venue.cancels.load(std::sync::atomic::Ordering::SeqCst), 0);
+        assert_eq!(engine.get_portfolio_summary().await.active_bets_count, 1);
+
+        // Nothing stays held once the order is booked
+        assert_eq!(engine.reservations.lock().unwrap().bets, 0);
+        assert!(engine.adjust_bankroll(dec!(-950), None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_trades_called_off_when_the_price_moves() {
+        let tolerance = OddsTolerance { max_ticks: Some(5), recheck_edge: true };
+        let engine = TradingEngine::new(dec!(1000.0)).with_odds_tolerance("moderate", tolerance);
+        let signal = |probability| {
+            let bet = BettingDecision::new(
+                "m1".to_string(), BetType::HomeWin, dec!(50), dec!(2.0), probability, "Moderate Growth".to_string(),
+            ).unwrap();
+            TradingSignal::new("m1".to_string(), 0.5, Some(bet), RiskAssessment::default(), String::new())
+        };
+
+        // Ten ticks in from 2.0
+        engine.update_market_odds("m1".to_string(), SimpleMarketOdds::new(dec!(2.2), dec!(3.4), dec!(3.6))).await;
+        assert!(!engine.execute_trade(&signal(0.6)).await.unwrap());
+
+        // Four ticks is close enough, but 0.53 no longer has a 3% edge at 1.96
+        engine.update_market_odds("m1".to_string(), SimpleMarketOdds::new(dec!(1.96), dec!(3.4), dec!(3.6))).await;
+        assert!(!engine.execute_trade(&signal(0.53)).await.unwrap());
+        assert!(engine.execute_trade(&signal(0.6)).await.unwrap());
+        assert_eq!(engine.get_portfolio_summary().await.active_bets_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_correlated_exposure_limits_stake() {
+        let engine = TradingEngine::new(dec!(1000.0)).with_max_correlated_exposure(0.1);
+        for (match_id, home, away) in [("m1", "Arsenal", "Chelsea"), ("m2", "Arsenal", "Everton")] {
+            let event = MatchEvent::new(
+                match_id.to_string(),
+                quant_models::EventType::MatchStart,
+                home.to_string(),
+                away.to_string(),
+                "Premier League".to_string(),
+                "2024-25".to_string(),
+            );
+            engine.register_match(&event).await;
+        }
+
+        let mut portfolio = Portfolio::new(dec!(1000.0));
+        let existing = BettingDecision::new(
+            "m1".to_string(), BetType::HomeWin, dec!(80), dec!(2.0), 0.6, "test".to_string(),
+        ).unwrap();
+        portfolio.place_bet(existing).unwrap();
+
+        // Arsenal/Premier League groups already carry 80 of a 100 limit
+        let stake = engine.apply_risk_constraints(dec!(50.0), "m2", None, &portfolio, true).await;
+        assert_eq!(stake, dec!(20.0));
+
+        // Unregistered matches are not grouped
+        let stake = engine.apply_risk_constraints(dec!(50.0), "unknown", None, &portfolio, true).await;
+        assert_eq!(stake, dec!(50.0));
+    }
+
+    #[tokio::test]
+    async fn test_league_and_team_exposure_limits() {
+        let engine = TradingEngine::new(dec!(1000.0)).with_exposure_limits(ExposureLimits {
+            max_league: Some(0.12),
+            max_team: Some(0.1),
+            teams: HashMap::from([("Everton".to_string(), 0.01)]),
+            ..Default::default()
+        });
+        for (match_id, home, away) in [
+            ("m1", "Arsenal", "Chelsea"), ("m2", "Arsenal", "Everton"), ("m3", "Spurs", "Villa"),
+        ] {
+            engine.register_match(&MatchEvent::new(
+                match_id.to_string(),
+                quant_models::EventType::MatchStart,
+                home.to_string(),
+                away.to_string(),
+                "Premier League".to_string(),
+                "2024-25".to_string(),
+            )).await;
+        }
+
+        let existing = BettingDecision::new(
+            "m1".to_string(), BetType::HomeWin, dec!(80), dec!(2.0), 0.6, "test".to_string(),
+        ).unwrap();
+        let signal = TradingSignal::new("m1".to_string(), 0.5, Some(existing), RiskAssessment::default(), String::new());
+        assert!(engine.execute_trade(&signal).await.unwrap());
+        let portfolio = engine.portfolio.read().await.clone();
+
+        // Everton's own cap is tighter than Arsenal's remaining 20
+        let stake = engine.apply_risk_constraints(dec!(45.0), "m2", None, &portfolio, true).await;
+        assert_eq!(stake, dec!(10.0));
+        // Fresh teams, but the league only has 40 of its 120 left
+        let stake = engine.apply_risk_constraints(dec!(45.0), "m3", None, &portfolio, true).await;
+        assert_eq!(stake, dec!(40.0));
+
+        let exposure = engine.get_portfolio_summary().await.exposure;
+        assert_eq!(exposure.leagues.len(), 1);
+        assert_eq!((exposure.leagues[0].exposure, exposure.leagues[0].limit), (dec!(80), dec!(120)));
+        let teams: Vec<_> = exposure.teams.iter().map(|team| (team.name.as_str(), team.utilization)).collect();
+        assert_eq!(teams, [("Arsenal", 0.8), ("Chelsea", 0.8)]);
+    }
+
+    #[tokio::test]
+    async fn test_concentration_alerts_on_single_league() {
+        let engine = TradingEngine::new(dec!(1000.0)).with_max_concentration(0.5);
+        let fixtures = [
+            ("m1", "Arsenal", "Chelsea", "Premier League"),
+            ("m2", "Everton", "Fulham", "Premier League"),
+            ("m3", "Leeds United", "Burnley", "Championship"),
+        ];
+        for (match_id, home, away, league) in fixtures {
+            let event = MatchEvent::new(
+                match_id.to_string(),
+                quant_models::EventType::MatchStart,
+                home.to_string(),
+                away.to_string(),
+                league.to_string(),
+                "2024-25".to_string(),
+            );
+            engine.register_match(&event).await;
+        }
+
+        let bets: Vec<_> = [("m1", BetType::HomeWin), ("m2", BetType::Draw), ("m3", BetType::AwayWin)]
+            .into_iter()
+            .map(|(match_id, bet_type)| BettingDecision::new(
+                match_id.to_string(), bet_type, dec!(40), dec!(2.0), 0.6, "test".to_string(),
+            ).unwrap())
+            .collect();
+        let match_info = engine.match_info.read().await;
+
+        // Two of three equal bets in one league: (2/3)^2 + (1/3)^2 = 5/9
+        let concentration = engine.risk_manager.concentration(&bets, &match_info);
+        assert!((concentration.league_hhi - 5.0 / 9.0).abs() < 1e-9);
+        assert!((concentration.team_hhi - 1.0 / 6.0).abs() < 1e-9);
+        assert!((concentration.diversification_score - 4.0 / 9.0).abs() < 1e-9);
+        assert_eq!(concentration.alerts.len(), 1);
+        assert_eq!(concentration.alerts[0].dimension, "league");
+        assert_eq!(concentration.alerts[0].largest_group, "Premier League");
+
+        // Too few bets to judge
+        assert!(engine.risk_manager.concentration(&bets[..2], &match_info).alerts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_finished_matches_forgotten_past_history_capacity() {
+        let engine = TradingEngine::new(dec!(1000.0)).with_history_capacity(1);
+        for match_id in ["m1", "m2", "m3"] {
+            engine.register_match(&MatchEvent::new(
+                match_id.to_string(),
+                quant_models::EventType::MatchStart,
+                "Arsenal".to_string(),
+                "Chelsea".to_string(),
+                "Premier League".to_string(),
+                "2024-25".to_string(),
+            )).await;
+        }
+        // Settling on the result alone leaves a handicap bet open
+        let handicap = BettingDecision::new(
+            "m3".to_string(), BetType::AsianHandicap { line: dec!(-0.25), team: "Arsenal".to_string() },
+            dec!(50), dec!(2.0), 0.6, "test".to_string(),
+        ).unwrap();
+        let signal = TradingSignal::new("m3".to_string(), 0.5, Some(handicap), RiskAssessment::default(), String::new());
+        assert!(engine.execute_trade(&signal).await.unwrap());
+
+        engine.settle_bet("m1", Outcome1X2::HomeWin).await.unwrap();
+        engine.settle_bet("m3", Outcome1X2::HomeWin).await.unwrap();
+        assert!(engine.get_match_info("m1").await.unwrap().finished_at.is_some());
+        engine.settle_bet("m2", Outcome1X2::Draw).await.unwrap();
+
+        assert!(engine.get_match_info("m1").await.is_none());
+        assert!(engine.get_match_info("m2").await.is_some());
+        // Still needed to settle the open bet
+        assert!(engine.get_match_info("m3").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_postponed_match_bets_voided_and_refunded() {
+        let engine = TradingEngine::new(dec!(1000.0));
+        for (match_id, bet_type) in [("m1", BetType::HomeWin), ("m2", BetType::Draw), ("m2", BetType::AwayWin)] {
+            let bet = BettingDecision::new(
+                match_id.to_string(), bet_type, dec!(50), dec!(2.0), 0.6, "test".to_string(),
+            ).unwrap();
+            let signal = TradingSignal::new(
+                match_id.to_string(), 1.0, Some(bet), RiskAssessment::default(), String::new(),
+            );
+            assert!(engine.execute_trade(&signal).await.unwrap());
+        }
+        engine.settle_bet("m1", Outcome1X2::HomeWin).await.unwrap();
+
+        assert_eq!(engine.void_match("m2").await.unwrap(), 2);
+        assert_eq!(engine.void_match("m2").await.unwrap(), 0);
+        let summary = engine.get_portfolio_summary().await;
+        assert_eq!(summary.available_bankroll, dec!(1050));
+        assert_eq!(summary.active_bets_count, 0);
+        assert_eq!(summary.profit_loss, dec!(50));
+        // Refunded bets neither won nor lost
+        assert_eq!(summary.win_rate, 1.0);
+        assert!(engine.get_match_bets("m2").await.iter().all(|bet| bet.status == BetStatus::Void));
+    }
+
+    #[tokio::test]
+    async fn test_journal_replay_restores_portfolio() {
+        let path = std::env::temp_dir().join(format!("trade-journal-{}.log", uuid::Uuid::new_v4()));
+
+        let before = {
+            let engine = TradingEngine::new(dec!(1000.0)).with_journal(&path).unwrap();
+            for match_id in ["m1", "m2"] {
+                let bet = BettingDecision::new(
+                    match_id.to_string(), BetType::HomeWin, dec!(50), dec!(2.0), 0.6, "test".to_string(),
+                ).unwrap();
+                let signal = TradingSignal::new(
+                    match_id.to_string(), 1.0, Some(bet), RiskAssessment::default(), String::new(),
+                );
+                assert!(engine.execute_trade(&signal).await.unwrap());
+            }
+            engine.settle_bet("m1", Outcome1X2::HomeWin).await.unwrap();
+            engine.adjust_bankroll(dec!(200), Some("top-up".to_string())).await.unwrap();
+            assert!(engine.adjust_bankroll(dec!(-5000), None).await.is_err());
+            engine.get_portfolio_summary().await
+        };
+
+        // An entry the portfolio refuses is skipped rather than failing startup
+        {
+            let (journal, _) = TradeJournal::open(&path).unwrap();
+            let oversized = BettingDecision::new(
+                "m3".to_string(), BetType::HomeWin, dec!(5000), dec!(2.0), 0.6, "test".to_string(),
+            ).unwrap();
+            journal.append(JournalRecord::BetPlaced { bet: Box::new(oversized) }).unwrap();
+        }
+
+        // A fresh engine on the same journal recovers the same state
+        let engine = TradingEngine::new(dec!(1000.0)).with_journal(&path).unwrap();
+        let after = engine.get_portfolio_summary().await;
+        assert_eq!(after.available_bankroll, before.available_bankroll);
+        assert_eq!(after.available_bankroll, dec!(1000.0) - dec!(50) - dec!(50) + dec!(100) + dec!(200));
+        assert_eq!(after.total_bankroll, dec!(1200.0));
+        assert_eq!(engine.get_bankroll_adjustments().await.len(), 1);
+        assert_eq!(after.active_bets_count, 1);
+        assert_eq!(after.total_trades, 2);
+        assert_eq!(after.profit_loss, dec!(50));
+        assert_eq!(engine.get_recent_trades(&HistoryFilter::default()).await.len(), 2);
+
+        // The settlement's equity point is restored as well
+        let curve = engine.get_equity_curve().await;
+        assert_eq!(curve.points().len(), 1);
+        assert_eq!(curve.points()[0].equity, dec!(1050));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_blending_toward_market_removes_edge() {
+        let prediction = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.65, 0.15, Utc::now(),
+        ).unwrap().with_draw_prob(0.20).unwrap().with_confidence(0.8).unwrap();
+        let feed = SimpleMarketOdds::new(dec!(1.80), dec!(3.50), dec!(4.50));
+
+        let engine = TradingEngine::new(dec!(1000.0));
+        engine.update_market_odds("m1".to_string(), feed.clone()).await;
+        assert!(engine.process_prediction(&prediction).await.unwrap().recommended_bet.is_some());
+
+        // Trusting only the market leaves nothing to bet on
+        let engine = TradingEngine::new(dec!(1000.0)).with_blender(
+            ProbabilityBlender::new().with_method(crate::BlendMethod::Linear).with_model_weight(0.0),
+        );
+        engine.update_market_odds("m1".to_string(), feed).await;
+        assert!(engine.process_prediction(&prediction).await.unwrap().recommended_bet.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_strategy_ensemble_votes_recorded_on_bet() {
+        let prediction = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.65, 0.15, Utc::now(),
+        ).unwrap().with_draw_prob(0.20).unwrap().with_confidence(0.8).unwrap();
+        let ensemble = StrategyEnsemble::new(vec![
+            BettingStrategy::conservative(), BettingStrategy::moderate(), BettingStrategy::aggressive(),
+        ]);
+
+        let engine = TradingEngine::new(dec!(1000.0)).with_strategy_ensemble(ensemble);
+        engine.update_market_odds("m1".to_string(), SimpleMarketOdds::new(dec!(1.80), dec!(3.50), dec!(4.50))).await;
+        let bet = engine.process_prediction(&prediction).await.unwrap().recommended_bet.unwrap();
+
+        assert_eq!(bet.strategy, ENSEMBLE_STRATEGY);
+        let votes = strategy_ensemble::bet_votes(&bet);
+        assert_eq!(votes.len(), 3);
+        let approved: Vec<_> = votes.iter().filter(|vote| vote.approved).collect();
+        assert!(approved.len() >= 2);
+        let mean = approved.iter().map(|vote| vote.stake).sum::<Decimal>() / Decimal::from(approved.len());
+        // The averaged stake can still be cut back by the risk constraints
+        assert!(bet.stake > Decimal::ZERO && bet.stake <= mean.round_dp(2));
+    }
+
+    #[tokio::test]
+    async fn test_strategy_books_trade_their_own_bankrolls() {
+        let prediction = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.65, 0.15, Utc::now(),
+        ).unwrap().with_draw_prob(0.20).unwrap().with_confidence(0.8).unwrap();
+        let presets = ["aggressive", "conservative", "reckless"].map(String::from);
+        let engine = TradingEngine::new(dec!(1000.0)).with_strategy_books(&presets, dec!(500.0));
+        engine.update_market_odds("m1".to_string(), SimpleMarketOdds::new(dec!(1.80), dec!(3.50), dec!(4.50))).await;
+        engine.process_prediction(&prediction).await.unwrap();
+
+        let books = engine.get_strategy_book_summaries().await;
+        assert_eq!(books.keys().collect::<Vec<_>>(), ["aggressive", "conservative"]);
+        let aggressive = &books["aggressive"];
+        assert_eq!(aggressive.active_bets_count, 1);
+        assert!(aggressive.available_bankroll < dec!(500.0));
+        let conservative_stake = dec!(500.0) - books["conservative"].available_bankroll;
+        assert!(dec!(500.0) - aggressive.available_bankroll > conservative_stake);
+
+        engine.settle_match("m1", 2, 0).await.unwrap();
+        let summary = engine.get_portfolio_summary().await;
+        // Only paper trades on the books; the live bankroll is untouched
+        assert_eq!((summary.available_bankroll, summary.active_bets_count), (dec!(1000.0), 0));
+        let aggressive = &summary.strategies["aggressive"];
+        assert_eq!((aggressive.active_bets_count, aggressive.total_trades), (0, 1));
+        assert!(aggressive.profit_loss > Decimal::ZERO);
+        assert_eq!(aggressive.win_rate, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_market_filter_rejections_logged_on_signal() {
+        let prediction = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.65, 0.15, Utc::now(),
+        ).unwrap().with_draw_prob(0.20).unwrap().with_confidence(0.8).unwrap();
+        // About 6.3% overround
+        let odds = SimpleMarketOdds::new(dec!(1.80), dec!(3.50), dec!(4.50));
+
+        let tight = MarketFilter { max_overround: Some(0.05), ..Default::default() };
+        let engine = TradingEngine::new(dec!(1000.0)).with_market_filter("moderate", tight);
+        engine.update_market_odds("m1".to_string(), odds.clone()).await;
+        let signal = engine.process_prediction(&prediction).await.unwrap();
+        assert!(signal.recommended_bet.is_none());
+        assert!(matches!(
+            &signal.rejections[0],
+            MarketRejection::Overround { market, .. } if market == "Home Win"
+        ));
+        // Rejected signals stay in the audit log
+        assert_eq!(engine.get_trading_signals(&HistoryFilter::default()).await.len(), 1);
+
+        let deep = MarketFilter { max_overround: Some(0.08), min_liquidity: Some(dec!(5000)) };
+        let engine = TradingEngine::new(dec!(1000.0)).with_market_filter("moderate", deep);
+        engine.update_market_odds("m1".to_string(), odds).await;
+        engine.update_market_liquidity("m1".to_string(), dec!(4000)).await;
+        let signal = engine.process_prediction(&prediction).await.unwrap();
+        assert!(signal.recommended_bet.is_none());
+        assert_eq!(signal.rejections, vec![MarketRejection::Liquidity {
+            strategy: "Moderate Growth".to_string(),
+            liquidity: Some(dec!(4000)),
+            min_liquidity: dec!(5000),
+        }]);
+
+        engine.update_market_liquidity("m1".to_string(), dec!(10000)).await;
+        let signal = engine.process_prediction(&prediction).await.unwrap();
+        assert!(signal.recommended_bet.is_some() && signal.rejections.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_staking_plan_sizes_the_preset() {
+        let prediction = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.65, 0.15, Utc::now(),
+        ).unwrap().with_draw_prob(0.20).unwrap().with_confidence(0.8).unwrap();
+        let engine = TradingEngine::new(dec!(1000.0))
+            .with_staking_plan("moderate", Arc::new(quant_models::FlatStake { amount: dec!(12) }));
+        engine.update_market_odds("m1".to_string(), SimpleMarketOdds::new(dec!(1.80), dec!(3.50), dec!(4.50))).await;
+
+        let signal = engine.process_prediction(&prediction).await.unwrap();
+        let bet = signal.recommended_bet.unwrap();
+        assert_eq!((bet.bet_type, bet.stake), (BetType::HomeWin, dec!(12)));
+        assert_eq!(engine.get_active_strategy().await.staking_plan().name(), "flat");
+    }
+
+    #[tokio::test]
+    async fn test_trade_and_signal_history_filters() {
+        let engine = TradingEngine::new(dec!(1000.0)).with_history_capacity(2);
+        for (match_id, strategy) in [("m1", "value"), ("m2", "arbitrage"), ("m3", "value")] {
+            let bet = BettingDecision::new(
+                match_id.to_string(), BetType::HomeWin, dec!(10), dec!(2.0), 0.6, strategy.to_string(),
+            ).unwrap();
+            let signal = TradingSignal::new(
+                match_id.to_string(), 1.0, Some(bet), RiskAssessment::default(), String::new(),
+            );
+            assert!(engine.execute_trade(&signal).await.unwrap());
+        }
+
+        // Capacity drops the oldest; newest come first
+        let trades = engine.get_recent_trades(&HistoryFilter::default()).await;
+        let ids: Vec<_> = trades.iter().map(|bet| bet.match_id.as_str()).collect();
+        assert_eq!(ids, ["m3", "m2"]);
+
+        let value = HistoryFilter { strategy: Some("value".to_string()), ..Default::default() };
+        assert_eq!(engine.get_recent_trades(&value).await.len(), 1);
+        let later = HistoryFilter { from: Some(Utc::now() + chrono::Duration::seconds(1)), ..Default::default() };
+        assert!(engine.get_recent_trades(&later).await.is_empty());
+
+        // Signals without a recommendation are not logged
+        let prediction = Prediction::new(
+            "m4".to_string(), "test".to_string(), "1.0".to_string(), 0.45, 0.25, Utc::now(),
+        ).unwrap().with_draw_prob(0.30).unwrap();
+        engine.process_prediction(&prediction).await.unwrap();
+        assert!(engine.get_trading_signals(&HistoryFilter::default()).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_best_bookmaker_price_used_for_edge() {
+        let engine = TradingEngine::new(dec!(1000.0));
+        engine.update_market_odds(
+            "m1".to_string(),
+            SimpleMarketOdds::new(dec!(1.80), dec!(3.50), dec!(4.50)),
+        ).await;
+        engine.update_bookmaker_odds(&MarketOdds {
+            id: uuid::Uuid::new_v4(),
+            match_id: "m1".to_string(),
+            market_type: quant_models::MarketType::MatchWinner,
+            bookmaker: "Bet365".to_string(),
+            odds: quant_models::OddsFormat::Decimal {
+                home: dec!(2.20),
+                draw: Some(dec!(3.40)),
+                away: dec!(4.20),
+            },
+            timestamp: Utc::now(),
+            is_active: true,
+        }).await.unwrap();
+
+        let prediction = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.55, 0.20, Utc::now(),
+        ).unwrap().with_draw_prob(0.25).unwrap().with_confidence(0.7).unwrap();
+
+        // Only Bet365's home price clears the minimum edge
+        let signal = engine.process_prediction(&prediction).await.unwrap();
+        let bet = signal.recommended_bet.expect("expected a home win bet");
+        assert_eq!(bet.bet_type, BetType::HomeWin);
+        assert_eq!(bet.odds, dec!(2.20));
+        assert_eq!(bet.metadata["bookmaker"], "Bet365");
+    }
+
+    #[tokio::test]
+    async fn test_bets_staked_in_bookmaker_currency_and_booked_in_base() {
+        let rates = FixedRates::new(Currency::Usd).with_rate(Currency::Gbp, dec!(1.25));
+        let engines = [
+            TradingEngine::new(dec!(1000.0)),
+            TradingEngine::new(dec!(1000.0))
+                .with_currency(Currency::Usd, Arc::new(rates))
+                .with_bookmaker_currencies(HashMap::from([("bet365".to_string(), Currency::Gbp)])),
+        ];
+        let mut bets = Vec::new();
+        for engine in &engines {
+            engine.update_market_odds(
+                "m1".to_string(),
+                SimpleMarketOdds::new(dec!(1.80), dec!(3.50), dec!(4.50)),
+            ).await;
+            engine.update_bookmaker_odds(&MarketOdds {
+                id: uuid::Uuid::new_v4(),
+                match_id: "m1".to_string(),
+                market_type: quant_models::MarketType::MatchWinner,
+                bookmaker: "Bet365".to_string(),
+                odds: quant_models::OddsFormat::Decimal { home: dec!(2.20), draw: Some(dec!(3.40)), away: dec!(4.20) },
+                timestamp: Utc::now(),
+                is_active: true,
+            }).await.unwrap();
+            let prediction = Prediction::new(
+                "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.55, 0.20, Utc::now(),
+            ).unwrap().with_draw_prob(0.25).unwrap().with_confidence(0.7).unwrap();
+            let signal = engine.process_prediction(&prediction).await.unwrap();
+            assert!(engine.execute_trade(&signal).await.unwrap());
+            bets.push(signal.recommended_bet.unwrap());
+        }
+
+        // The same dollar stake, placed in pounds
+        let (usd, gbp) = (&bets[0], &bets[1]);
+        assert_eq!((usd.currency, gbp.currency), (Currency::Usd, Currency::Gbp));
+        assert_eq!(gbp.stake, (usd.stake / dec!(1.25)).round_dp(2));
+
+        let summary = engines[1].get_portfolio_summary().await;
+        assert_eq!(summary.total_exposure, gbp.stake * dec!(1.25));
+        assert_eq!(summary.available_bankroll, dec!(1000.0) - gbp.stake * dec!(1.25));
+        assert_eq!(summary.exposure_by_currency[&Currency::Gbp], gbp.stake);
+
+        // Winnings come back at the rate the bet was priced at
+        engines[1].settle_match("m1", 2, 0).await.unwrap();
+        let summary = engines[1].get_portfolio_summary().await;
+        assert_eq!(summary.profit_loss, gbp.stake * dec!(1.20) * dec!(1.25));
+    }
+
+    #[tokio::test]
+    async fn test_exchange_commission_priced_into_bet() {
+        let engine = TradingEngine::new(dec!(1000.0))
+            .with_venue_commissions(HashMap::from([("Exchange".to_string(), dec!(0.20))]));
+        engine.update_market_odds(
+            "m1".to_string(),
+            SimpleMarketOdds::new(dec!(1.80), dec!(3.50), dec!(4.50)),
+        ).await;
+        engine.update_bookmaker_odds(&MarketOdds {
+            id: uuid::Uuid::new_v4(),
+            match_id: "m1".to_string(),
+            market_type: quant_models::MarketType::MatchWinner,
+            bookmaker: "exchange".to_string(),
+            odds: quant_models::OddsFormat::Decimal {
+                home: dec!(2.20),
+                draw: Some(dec!(3.40)),
+                away: dec!(4.20),
+            },
+            timestamp: Utc::now(),
+            is_active: true,
+        }).await.unwrap();
+
+        let prediction = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.55, 0.20, Utc::now(),
+        ).unwrap().with_draw_prob(0.25).unwrap().with_confidence(0.7).unwrap();
+
+        // 2.20 less 20% commission on winnings prices like 1.96
+        let signal = engine.process_prediction(&prediction).await.unwrap();
+        let bet = signal.recommended_bet.expect("expected a home win bet");
+        assert_eq!(bet.odds, dec!(2.20));
+        assert_eq!(bet.commission, dec!(0.20));
+        assert!((bet.expected_value - (0.55 * 1.96 - 1.0)).abs() < 1e-9);
+        assert!((bet.kelly_fraction - (0.96 * 0.55 - 0.45) / 0.96).abs() < 1e-9);
+    }
+
+    fn bet365_quote(home: Decimal, draw: Decimal, away: Decimal) -> MarketOdds {
+        MarketOdds {
+            id: uuid::Uuid::new_v4(),
+            match_id: "m1".to_string(),
+            market_type: quant_models::MarketType::MatchWinner,
+            bookmaker: "Bet365".to_string(),
+            odds: quant_models::OddsFormat::Decimal { home, draw: Some(draw), away },
+            timestamp: Utc::now(),
+            is_active: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_value_bets_found_in_every_book() {
+        let engine = TradingEngine::new(dec!(1000.0))
+            .with_venue_commissions(HashMap::from([("Bet365".to_string(), dec!(0.05))]));
+        engine.update_market_odds(
+            "m1".to_string(),
+            SimpleMarketOdds::new(dec!(1.80), dec!(3.50), dec!(4.50)),
+        ).await;
+        engine.update_bookmaker_odds(&bet365_quote(dec!(2.10), dec!(3.40), dec!(4.20))).await.unwrap();
+
+        let stale = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.20, 0.55, Utc::now() - chrono::Duration::minutes(5),
+        ).unwrap().with_draw_prob(0.25).unwrap();
+        let latest = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.55, 0.20, Utc::now(),
+        ).unwrap().with_draw_prob(0.25).unwrap();
+
+        let value_bets = engine.find_value_bets(&[latest.clone(), stale], &ValueBetFilter::default()).await;
+        // Only the home win at Bet365 beats its implied probability, by 0.074
+        assert_eq!(value_bets.len(), 1);
+        let bet = &value_bets[0];
+        assert_eq!((bet.bookmaker.as_str(), &bet.bet_type), ("Bet365", &BetType::HomeWin));
+        assert!((bet.expected_value - (0.55 * 2.10 - 1.0)).abs() < 1e-9);
+        assert!((bet.kelly_fraction - kelly_fraction(0.55, net_odds(dec!(2.10), dec!(0.05)))).abs() < 1e-12);
+        assert!(bet.recommended_stake > Decimal::ZERO);
+
+        let strict = ValueBetFilter { min_edge: 0.1, league: None };
+        assert!(engine.find_value_bets(&[latest], &strict).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_odds_boost_attached_to_opportunity() {
+        let engine = TradingEngine::new(dec!(1000.0));
+        engine.update_market_odds(
+            "m1".to_string(),
+            SimpleMarketOdds::new(dec!(1.80), dec!(3.50), dec!(4.50)),
+        ).await;
+        engine.update_bookmaker_odds(&bet365_quote(dec!(1.85), dec!(3.40), dec!(4.20))).await.unwrap();
+
+        let boost = Promotion::new(
+            "Bet365".to_string(),
+            quant_models::PromotionKind::OddsBoost { boost_percent: 0.25, max_stake: Some(dec!(5)) },
+            Utc::now() + chrono::Duration::hours(1),
+        ).unwrap().for_match("m1".to_string()).for_bet_type(BetType::HomeWin);
+        let boost_id = engine.register_promotion(boost).await.unwrap();
+
+        let prediction = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.55, 0.20, Utc::now(),
+        ).unwrap().with_draw_prob(0.25).unwrap().with_confidence(0.7).unwrap();
+
+        // 1.85 boosted by 25% pays 2.06, capped at the boost's 5 unit stake
+        let signal = engine.process_prediction(&prediction).await.unwrap();
+        let bet = signal.recommended_bet.expect("expected a boosted home win bet");
+        assert_eq!(bet.odds, dec!(2.06));
+        assert_eq!(bet.stake, dec!(5));
+        assert_eq!(promotion_id(&bet), Some(boost_id));
+    }
+
+    #[tokio::test]
+    async fn test_free_bet_used_once_on_best_snr_outcome() {
+        let engine = TradingEngine::new(dec!(1000.0));
+        engine.update_market_odds(
+            "m1".to_string(),
+            SimpleMarketOdds::new(dec!(2.00), dec!(3.20), dec!(3.80)),
+        ).await;
+        engine.update_bookmaker_odds(&bet365_quote(dec!(2.00), dec!(3.20), dec!(3.80))).await.unwrap();
+
+        let free_bet = Promotion::new(
+            "Bet365".to_string(),
+            quant_models::PromotionKind::FreeBet { stake: dec!(10) },
+            Utc::now() + chrono::Duration::hours(1),
+        ).unwrap();
+        engine.register_promotion(free_bet).await.unwrap();
+
+        let prediction = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.45, 0.25, Utc::now(),
+        ).unwrap().with_draw_prob(0.30).unwrap().with_confidence(0.7).unwrap();
+
+        // No cash edge anywhere; the free bet goes on the away win, where
+        // p * (odds - 1) = 0.25 * 2.8 beats the draw's 0.30 * 2.2
+        let signal = engine.process_prediction(&prediction).await.unwrap();
+        let bet = signal.recommended_bet.clone().expect("expected a free bet");
+        assert!(bet.free_bet);
+        assert_eq!(bet.bet_type, BetType::AwayWin);
+
+        assert!(engine.execute_trade(&signal).await.unwrap());
+        assert!(!engine.execute_trade(&signal).await.unwrap());
+        assert!(engine.get_promotions().await.is_empty());
+
+        let summary = engine.get_portfolio_summary().await;
+        assert_eq!(summary.available_bankroll, dec!(1000.0));
+        assert_eq!(summary.total_exposure, Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_derived_markets_evaluated_and_settled() {
+        let engine = TradingEngine::new(dec!(1000.0));
+        let feed = SimpleMarketOdds::new(dec!(2.35), dec!(3.60), dec!(3.00));
+        engine.update_market_odds("m1".to_string(), feed.clone()).await;
+        engine.update_derived_odds("m1".to_string(), DerivedMarketOdds::from_match_odds(&feed)).await;
+
+        let prediction = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.45, 0.25, Utc::now(),
+        ).unwrap().with_draw_prob(0.30).unwrap().with_confidence(0.7).unwrap();
+
+        // Home and draw are each just short of the 3% minimum edge, but the
+        // overpriced away win leaves value in draw no bet home at 1.70
+        let signal = engine.process_prediction(&prediction).await.unwrap();
+        let bet = signal.recommended_bet.clone().expect("expected a derived market bet");
+        assert_eq!(bet.bet_type, BetType::DrawNoBet { home: true });
+        assert_eq!(bet.odds, dec!(1.70));
+        assert!((bet.expected_value - (0.45 / 0.70 * 1.70 - 1.0) * 0.70).abs() < 1e-9);
+
+        // A draw refunds the stake
+        assert!(engine.execute_trade(&signal).await.unwrap());
+        engine.settle_bet("m1", Outcome1X2::Draw).await.unwrap();
+        let summary = engine.get_portfolio_summary().await;
+        assert_eq!(summary.available_bankroll, dec!(1000.0));
+        assert_eq!(summary.active_bets_count, 0);
+
+        // Double chance 12 loses only on a draw
+        let double_chance = BettingDecision::new(
+            "m2".to_string(),
+            BetType::DoubleChance { selection: DoubleChanceSelection::HomeOrAway },
+            dec!(10), dec!(1.30), 0.8, "test".to_string(),
+        ).unwrap();
+        let signal = TradingSignal::new(
+            "m2".to_string(), 0.5, Some(double_chance), RiskAssessment::default(), String::new(),
+        );
+        assert!(engine.execute_trade(&signal).await.unwrap());
+        engine.settle_bet("m2", Outcome1X2::AwayWin).await.unwrap();
+        assert_eq!(engine.get_portfolio_summary().await.available_bankroll, dec!(1003.0));
+    }
+
+    #[tokio::test]
+    async fn test_asian_handicap_evaluated_and_settled() {
+        let engine = TradingEngine::new(dec!(1000.0));
+        engine.register_match(&MatchEvent::new(
+            "m1".to_string(),
+            quant_models::EventType::MatchStart,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        )).await;
+        // Match winner prices leave no value anywhere
+        engine.update_market_odds("m1".to_string(), SimpleMarketOdds::new(dec!(1.40), dec!(4.0), dec!(6.0))).await;
+        engine.update_asian_handicap_odds("m1".to_string(), AsianHandicapOdds {
+            main_line: dec!(-1),
+            quotes: vec![AsianHandicapQuote { line: dec!(-1), home: dec!(2.30), away: dec!(1.60) }],
+        }).await;
+
+        let prediction = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.65, 0.15, Utc::now(),
+        ).unwrap().with_draw_prob(0.20).unwrap().with_confidence(0.8).unwrap();
+
+        let signal = engine.process_prediction(&prediction).await.unwrap();
+        let bet = signal.recommended_bet.clone().expect("expected an Asian handicap bet");
+        assert_eq!(bet.bet_type, BetType::AsianHandicap { line: dec!(-1), team: "Arsenal".to_string() });
+        assert_eq!(bet.odds, dec!(2.30));
+
+        // Settling on the result alone cannot decide a handicap
+        assert!(engine.execute_trade(&signal).await.unwrap());
+        engine.settle_bet("m1", Outcome1X2::HomeWin).await.unwrap();
+        assert_eq!(engine.get_portfolio_summary().await.active_bets_count, 1);
+
+        // Winning by exactly one goal pushes a -1 line
+        engine.settle_match("m1", 2, 1).await.unwrap();
+        let summary = engine.get_portfolio_summary().await;
+        assert_eq!(summary.active_bets_count, 0);
+        assert_eq!(summary.available_bankroll, dec!(1000.0));
+
+        // Chelsea +0.75 half loses on a one-goal defeat: +0.5 loses, +1 pushes
+        let bet = BettingDecision::new(
+            "m1".to_string(),
+            BetType::AsianHandicap { line: dec!(0.75), team: "Chelsea".to_string() },
+            dec!(20), dec!(2.0), 0.5, "test".to_string(),
+        ).unwrap();
+        let signal = TradingSignal::new(
+            "m1".to_string(), 0.5, Some(bet), RiskAssessment::default(), String::new(),
+        );
+        assert!(engine.execute_trade(&signal).await.unwrap());
+        engine.settle_match("m1", 2, 1).await.unwrap();
+        assert_eq!(engine.get_portfolio_summary().await.available_bankroll, dec!(990.0));
+    }
+
+    #[tokio::test]
+    async fn test_correct_score_bet_capped_and_settled() {
+        let engine = TradingEngine::new(dec!(1000.0)).with_max_correct_score_stake(0.002);
+        engine.update_market_odds("m1".to_string(), SimpleMarketOdds::new(dec!(1.40), dec!(4.0), dec!(6.0))).await;
+        let quote = |home_goals, away_goals, price| CorrectScoreQuote { home_goals, away_goals, price };
+        engine.update_correct_score_odds("m1".to_string(), CorrectScoreOdds {
+            quotes: vec![quote(1, 0, dec!(9.0)), quote(2, 0, dec!(12.0)), quote(4, 0, dec!(60.0))],
+        }).await;
+
+        // 2-0 at 12.0 against a model that gives it 12% is the best value; 4-0
+        // is beyond the odds ceiling however generous
+        let matrix = ScoreMatrix::from_fn(4, |home, away| match (home, away) {
+            (1, 0) => 0.10,
+            (2, 0) => 0.12,
+            (4, 0) => 0.05,
+            _ => 0.03,
+        });
+        let prediction = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.65, 0.15, Utc::now(),
+        ).unwrap().with_draw_prob(0.20).unwrap().with_confidence(0.8).unwrap().with_score_matrix(matrix);
+
+        let signal = engine.process_prediction(&prediction).await.unwrap();
+        let bet = signal.recommended_bet.clone().expect("expected a correct score bet");
+        assert_eq!(bet.bet_type, BetType::CorrectScore { home_goals: 2, away_goals: 0 });
+        assert_eq!(bet.stake, dec!(2.00));
+
+        assert!(engine.execute_trade(&signal).await.unwrap());
+        engine.settle_bet("m1", Outcome1X2::HomeWin).await.unwrap();
+        assert_eq!(engine.get_portfolio_summary().await.active_bets_count, 1);
+        engine.settle_match("m1", 2, 0).await.unwrap();
+        assert_eq!(engine.get_portfolio_summary().await.available_bankroll, dec!(1022.0));
+    }
+
+    #[tokio::test]
+    async fn test_half_result_bets_settled_by_half() {
+        let engine = TradingEngine::new(dec!(1000.0));
+        let event = MatchEvent::new(
+            "m1".to_string(),
+            quant_models::EventType::MatchStart,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        );
+        engine.register_match(&event).await;
+        engine.update_market_odds("m1".to_string(), SimpleMarketOdds::new(dec!(1.40), dec!(4.0), dec!(6.0))).await;
+        engine.update_half_result_odds("m1".to_string(), HalfResultOdds {
+            first_half: Some(SimpleMarketOdds::new(dec!(3.0), dec!(2.2), dec!(5.0))),
+            second_half: SimpleMarketOdds::new(dec!(2.6), dec!(2.5), dec!(4.5)),
+        }).await;
+        let prediction = Prediction::new(
+            "m1".to_string(), "test".to_string(), "1.0".to_string(), 0.65, 0.15, Utc::now(),
+        ).unwrap().with_draw_prob(0.20).unwrap().with_confidence(0.8).unwrap();
+
+        // The match winner is fairly priced, but the home side is long to
+        // win the first half
+        let signal = engine.process_prediction(&prediction).await.unwrap();
+        let first_half = BetType::HalfResult { half: MatchHalf::First, outcome: Outcome1X2::HomeWin };
+        let bet = signal.recommended_bet.clone().expect("expected a first half bet");
+        assert_eq!(bet.bet_type, first_half);
+        let first_stake = bet.stake;
+        assert!(engine.execute_trade(&signal).await.unwrap());
+
+        // A full match bet doesn't eat into either half's exposure allowance
+        let mut portfolio = Portfolio::new(dec!(1000.0));
+        let full_match = BettingDecision::new(
+            "m1".to_string(), BetType::HomeWin, dec!(100), dec!(1.40), 0.65, "test".to_string(),
+        ).unwrap();
+        portfolio.place_bet(full_match).unwrap();
+        assert_eq!(engine.apply_risk_constraints(dec!(10), "m1", None, &portfolio, true).await, Decimal::ZERO);
+        assert_eq!(engine.apply_risk_constraints(dec!(10), "m1", Some(MatchHalf::Second), &portfolio, true).await, dec!(10));
+
+        // 1-0 at half time wins the first half bet, and the first half is no
+        // longer traded
+        assert_eq!(engine.settle_first_half("m1", 1, 0).await.unwrap(), 1);
+        assert_eq!(engine.get_portfolio_summary().await.available_bankroll, dec!(1000.0) + first_stake * dec!(2.0));
+        let signal = engine.process_prediction(&prediction).await.unwrap();
+        let bet = signal.recommended_bet.clone().expect("expected a second half bet");
+        assert_eq!(bet.bet_type, BetType::HalfResult { half: MatchHalf::Second, outcome: Outcome1X2::HomeWin });
+        let second_stake = bet.stake;
+        assert!(engine.execute_trade(&signal).await.unwrap());
+
+        // 1-1 at full time: the second half went 0-1
+        engine.settle_bet("m1", Outcome1X2::Draw).await.unwrap();
+        assert_eq!(engine.get_portfolio_summary().await.active_bets_count, 1);
+        engine.settle_match("m1", 1, 1).await.unwrap();
+        let summary = engine.get_portfolio_summary().await;
+        assert_eq!(summary.active_bets_count, 0);
+        assert_eq!(summary.available_bankroll, dec!(1000.0) + first_stake * dec!(2.0) - second_stake);
+    }
+
+    #[tokio::test]
+    async fn test_accumulator_checked_placed_and_settled_by_leg() {
+        let path = std::env::temp_dir().join(format!("trade-journal-{}.log", uuid::Uuid::new_v4()));
+        let engine = TradingEngine::new(dec!(1000.0)).with_journal(&path).unwrap();
+        for (match_id, home, away) in [("m1", "Arsenal", "Chelsea"), ("m2", "Liverpool", "Everton"), ("m3", "Spurs", "Arsenal")] {
+            let event = MatchEvent::new(
+                match_id.to_string(),
+                quant_models::EventType::MatchStart,
+                home.to_string(),
+                away.to_string(),
+                "Premier League".to_string(),
+                "2024-25".to_string(),
+            );
+            engine.register_match(&event).await;
+        }
+
+        // Legs that move together are turned away
+        let same_match = BetSlip::new(dec!(20))
+            .with_leg("m1", BetType::HomeWin, dec!(2.0), 0.55)
+            .with_leg("m1", BetType::DrawNoBet { home: true }, dec!(1.5), 0.7);
+        let same_team = BetSlip::new(dec!(20))
+            .with_leg("m1", BetType::HomeWin, dec!(2.0), 0.55)
+            .with_leg("m3", BetType::AwayWin, dec!(3.0), 0.35);
+        for slip in [same_match, same_team] {
+            assert!(matches!(engine.place_accumulator(slip).await, Err(QuantsError::InvalidAccumulator(_))));
+        }
+
+        let slip = BetSlip::new(dec!(20))
+            .with_leg("m1", BetType::HomeWin, dec!(2.0), 0.55)
+            .with_leg("m2", BetType::Draw, dec!(3.4), 0.3);
+        let bet = engine.place_accumulator(slip).await.unwrap().expect("expected the accumulator to be booked");
+        assert_eq!((bet.odds, bet.match_id.as_str()), (dec!(6.80), "m1+m2"));
+        let summary = engine.get_portfolio_summary().await;
+        assert_eq!(summary.total_exposure, dec!(20));
+        // Both legs are Premier League matches; the stake counts once
+        let league = &summary.exposure.leagues[0];
+        assert_eq!((league.name.as_str(), league.exposure), ("Premier League", dec!(20)));
+        assert_eq!(engine.get_match_bets("m2").await.len(), 1);
+
+        // The first leg wins and the accumulator stays open, which a
+        // restart picks up from the journal
+        assert_eq!(engine.settle_match("m1", 1, 0).await.unwrap(), 0);
+        let restarted = TradingEngine::new(dec!(1000.0)).with_journal(&path).unwrap();
+        let open = restarted.get_active_bets().await;
+        assert_eq!(open[0].bet_type.legs()[0].status, BetStatus::Won);
+
+        assert_eq!(engine.settle_match("m2", 1, 1).await.unwrap(), 1);
+        let summary = engine.get_portfolio_summary().await;
+        assert_eq!((summary.active_bets_count, summary.available_bankroll), (0, dec!(1116.0)));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,16 +1,180 @@
 // Event bus for internal message passing
 
-use quant_models::MatchEvent;
-use tokio::sync::mpsc;
+use crate::message::DomainEventMessage;
+use quant_models::DomainEvent;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
 
+/// Messages a slow subscriber can fall behind by before it misses some
+const DOMAIN_EVENT_CAPACITY: usize = 1_024;
+const TOPIC_CAPACITY: usize = 1_024;
+
+/// Typed publish/subscribe: one topic per message type, e.g. `MatchEvent`,
+/// `Prediction`, `TradingSignal` or `SettlementResult`, each fanned out to
+/// every subscriber. Clones publish onto the same topics.
+#[derive(Clone, Default)]
 pub struct EventBus {
-    sender: mpsc::UnboundedSender<MatchEvent>,
-    receiver: mpsc::UnboundedReceiver<MatchEvent>,
+    topics: Arc<Mutex<HashMap<TypeId, Box<dyn Any + Send>>>>,
 }
 
 impl EventBus {
     pub fn new() -> Self {
-        let (sender, receiver) = mpsc::unbounded_channel();
-        Self { sender, receiver }
+        Self::default()
+    }
+
+    /// The topic carrying `T`, opened on first use. Publishers on a hot path
+    /// can hold on to it rather than look it up per message.
+    pub fn topic<T: Clone + Send + 'static>(&self) -> Topic<T> {
+        let mut topics = self.topics.lock().unwrap_or_else(|e| e.into_inner());
+        topics
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(Topic::<T>::new()))
+            .downcast_ref::<Topic<T>>()
+            .expect("topics are keyed by their message type")
+            .clone()
+    }
+
+    /// Returns how many subscribers the message reached
+    pub fn publish<T: Clone + Send + 'static>(&self, message: T) -> usize {
+        self.topic::<T>().publish(message)
+    }
+
+    pub fn subscribe<T: Clone + Send + 'static>(&self) -> broadcast::Receiver<T> {
+        self.topic::<T>().subscribe()
+    }
+}
+
+/// One message type's feed on the `EventBus`
+pub struct Topic<T> {
+    sender: broadcast::Sender<T>,
+}
+
+impl<T: Clone> Topic<T> {
+    fn new() -> Self {
+        Self { sender: broadcast::channel(TOPIC_CAPACITY).0 }
+    }
+
+    /// Returns how many subscribers the message reached. With none it is
+    /// dropped without being sent.
+    pub fn publish(&self, message: T) -> usize {
+        if self.sender.receiver_count() == 0 {
+            return 0;
+        }
+        self.sender.send(message).unwrap_or(0)
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<T> {
+        self.sender.subscribe()
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl<T> Clone for Topic<T> {
+    fn clone(&self) -> Self {
+        Self { sender: self.sender.clone() }
+    }
+}
+
+/// Fans domain events out to every subscriber. Clones publish onto the same
+/// feed.
+#[derive(Clone)]
+pub struct DomainEventBus {
+    sender: broadcast::Sender<DomainEventMessage>,
+    /// Held while sending, so subscribers see sequence numbers in order
+    next_sequence: Arc<Mutex<u64>>,
+}
+
+impl DomainEventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(DOMAIN_EVENT_CAPACITY);
+        Self { sender, next_sequence: Arc::new(Mutex::new(1)) }
+    }
+
+    pub fn publish(&self, event: DomainEvent) -> DomainEventMessage {
+        let mut next_sequence = self.next_sequence.lock().unwrap_or_else(|e| e.into_inner());
+        let message = DomainEventMessage {
+            sequence: *next_sequence,
+            timestamp: chrono::Utc::now(),
+            event,
+        };
+        *next_sequence += 1;
+        // No subscribers is fine
+        let _ = self.sender.send(message.clone());
+        message
     }
-}
\ No newline at end of file
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DomainEventMessage> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for DomainEventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_models::{EventType, MatchEvent};
+
+    #[tokio::test]
+    async fn test_each_type_has_its_own_topic() {
+        let bus = EventBus::new();
+        assert_eq!(bus.publish(1_u32), 0);
+
+        let mut first = bus.subscribe::<MatchEvent>();
+        let mut second = bus.clone().subscribe::<MatchEvent>();
+        let mut numbers = bus.subscribe::<u32>();
+        let kick_off = MatchEvent::new(
+            "m1".to_string(),
+            EventType::MatchStart,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        );
+        let topic = bus.topic::<MatchEvent>();
+        assert_eq!(topic.subscriber_count(), 2);
+        assert_eq!(topic.publish(kick_off), 2);
+        bus.publish(7_u32);
+
+        for receiver in [&mut first, &mut second] {
+            assert_eq!(receiver.recv().await.unwrap().match_id, "m1");
+            assert!(receiver.try_recv().is_err());
+        }
+        assert_eq!(numbers.recv().await.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_share_one_numbered_feed() {
+        let bus = DomainEventBus::new();
+        let mut monitor = bus.subscribe();
+        let mut websocket = bus.clone().subscribe();
+
+        bus.publish(DomainEvent::DrawdownThresholdCrossed { threshold: 0.1, drawdown: 0.12 });
+        bus.clone().publish(DomainEvent::ModelPromoted {
+            name: "logistic".to_string(),
+            version: "2".to_string(),
+            validation_log_loss: 0.98,
+        });
+
+        for receiver in [&mut monitor, &mut websocket] {
+            assert_eq!(receiver.recv().await.unwrap().sequence, 1);
+            let promoted = receiver.recv().await.unwrap();
+            assert_eq!((promoted.sequence, promoted.event.name()), (2, "model_promoted"));
+        }
+
+        let json = serde_json::to_value(bus.publish(DomainEvent::DrawdownThresholdCrossed {
+            threshold: 0.2, drawdown: 0.2,
+        })).unwrap();
+        assert_eq!(json["type"], "drawdown_threshold_crossed");
+        assert_eq!(json["sequence"], 3);
+    }
+}
@@ -1,7 +1,13 @@
 pub mod redis_stream;
 pub mod event_bus;
 pub mod message;
+pub mod ordering;
+pub mod sharding;
+pub mod queue;
 
 pub use redis_stream::*;
 pub use event_bus::*;
-pub use message::*;
\ No newline at end of file
+pub use message::*;
+pub use ordering::*;
+pub use sharding::*;
+pub use queue::*;
\ No newline at end of file
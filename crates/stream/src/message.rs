@@ -1,11 +1,174 @@
 // Message serialization and deserialization
 
 use serde::{Serialize, Deserialize};
-use quant_models::MatchEvent;
+use quant_models::{BettingDecision, DomainEvent, MatchEvent};
+use thiserror::Error;
 
+/// Envelope version this build writes. Bump it when the envelope or the
+/// event changes shape, and keep `StreamMessage::decode` reading the one
+/// before so producers and consumers can be upgraded one at a time.
+pub const STREAM_SCHEMA_VERSION: u32 = 2;
+
+/// A match event on the Redis event stream
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamMessage {
+    pub schema_version: u32,
+    /// Process that published the message, e.g. `quant-rs/0.1.0`
+    pub producer: String,
     pub id: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub event: MatchEvent,
-}
\ No newline at end of file
+    /// CRC-32 of the event's JSON, as 8 hex digits
+    pub checksum: String,
+}
+
+/// Version 1 messages: the bare event, with no envelope fields
+#[derive(Deserialize)]
+struct StreamMessageV1 {
+    id: String,
+    timestamp: chrono::DateTime<chrono::Utc>,
+    event: MatchEvent,
+}
+
+#[derive(Debug, Error)]
+pub enum StreamDecodeError {
+    #[error("malformed stream message: {0}")]
+    Malformed(#[from] serde_json::Error),
+    #[error("stream schema version {0} is newer than this build reads ({STREAM_SCHEMA_VERSION}) and not compatible with it")]
+    UnsupportedVersion(u32),
+    #[error("stream message {id} fails its checksum: {expected} expected, {actual} computed")]
+    ChecksumMismatch { id: String, expected: String, actual: String },
+}
+
+impl StreamMessage {
+    pub fn new(producer: impl Into<String>, event: MatchEvent) -> Self {
+        let checksum = event_checksum(&serde_json::to_value(&event).unwrap_or_default());
+        Self {
+            schema_version: STREAM_SCHEMA_VERSION,
+            producer: producer.into(),
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: chrono::Utc::now(),
+            event,
+            checksum,
+        }
+    }
+
+    pub fn encode(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Read a message written by this build, the version before it, or a
+    /// newer build whose changes were only additions. Version 1 messages
+    /// come back with an empty producer and a freshly computed checksum.
+    pub fn decode(payload: &str) -> Result<Self, StreamDecodeError> {
+        let value: serde_json::Value = serde_json::from_str(payload)?;
+        let version = value.get("schema_version")
+            .and_then(|version| version.as_u64())
+            .map_or(1, |version| version.min(u32::MAX as u64) as u32);
+        let checksum = value.get("event").map(event_checksum).unwrap_or_default();
+
+        if version == 1 {
+            let message: StreamMessageV1 = serde_json::from_value(value)?;
+            return Ok(Self {
+                schema_version: 1,
+                producer: String::new(),
+                id: message.id,
+                timestamp: message.timestamp,
+                event: message.event,
+                checksum,
+            });
+        }
+
+        let message: Self = match serde_json::from_value(value) {
+            Ok(message) => message,
+            Err(_) if version > STREAM_SCHEMA_VERSION => return Err(StreamDecodeError::UnsupportedVersion(version)),
+            Err(e) => return Err(e.into()),
+        };
+        if message.checksum != checksum {
+            return Err(StreamDecodeError::ChecksumMismatch {
+                id: message.id,
+                expected: message.checksum,
+                actual: checksum,
+            });
+        }
+        Ok(message)
+    }
+}
+
+/// Taken over the event as a JSON value, whose object keys serialize in a
+/// fixed order, so producer and consumer hash the same bytes
+fn event_checksum(event: &serde_json::Value) -> String {
+    format!("{:08x}", crc32(event.to_string().as_bytes()))
+}
+
+/// CRC-32 as gzip and zlib compute it
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+/// Trading activity published to downstream consumers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TradeMessage {
+    BetExecuted { bet: BettingDecision },
+}
+
+/// A domain event as delivered by the bus, numbered in publish order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainEventMessage {
+    pub sequence: u64,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten)]
+    pub event: DomainEvent,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_models::EventType;
+
+    #[test]
+    fn test_stream_message_reads_current_and_older_versions() {
+        let event = MatchEvent::new(
+            "m1".to_string(),
+            EventType::MatchStart,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        );
+        let message = StreamMessage::new("quant-rs/0.1.0", event.clone());
+        let decoded = StreamMessage::decode(&message.encode().unwrap()).unwrap();
+        assert_eq!((decoded.schema_version, decoded.producer.as_str()), (STREAM_SCHEMA_VERSION, "quant-rs/0.1.0"));
+        assert_eq!((decoded.event.id, &decoded.checksum), (event.id, &message.checksum));
+
+        // Written by a build from before the envelope
+        let v1 = serde_json::json!({ "id": "old", "timestamp": message.timestamp, "event": event }).to_string();
+        let decoded = StreamMessage::decode(&v1).unwrap();
+        assert_eq!((decoded.schema_version, decoded.id.as_str(), decoded.producer.as_str()), (1, "old", ""));
+        assert_eq!(decoded.checksum, message.checksum);
+
+        // A newer build that only added fields is still readable; one that
+        // changed the shape is reported as such
+        let mut newer = serde_json::to_value(&message).unwrap();
+        newer["schema_version"] = 3.into();
+        newer["partition"] = 7.into();
+        assert_eq!(StreamMessage::decode(&newer.to_string()).unwrap().schema_version, 3);
+        newer["event"] = serde_json::json!({ "kind": "match_start" });
+        assert!(matches!(StreamMessage::decode(&newer.to_string()), Err(StreamDecodeError::UnsupportedVersion(3))));
+
+        let mut tampered = serde_json::to_value(&message).unwrap();
+        tampered["event"]["team_home"] = "Spurs".into();
+        assert!(matches!(
+            StreamMessage::decode(&tampered.to_string()),
+            Err(StreamDecodeError::ChecksumMismatch { .. })
+        ));
+        assert!(matches!(StreamMessage::decode("{"), Err(StreamDecodeError::Malformed(_))));
+    }
+}
@@ -0,0 +1,287 @@
+// Deduplication and per-match ordering of incoming events
+
+use quant_models::{EventType, MatchEvent};
+use crate::queue::EventQueueReceiver;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct OrderingConfig {
+    /// How long an early event waits for the ones before it
+    pub reorder_window: Duration,
+    /// Event ids remembered for dropping redeliveries
+    pub dedup_capacity: usize,
+    /// Early events held per match before the gap is given up on
+    pub max_buffered_per_match: usize,
+}
+
+impl Default for OrderingConfig {
+    fn default() -> Self {
+        Self {
+            reorder_window: Duration::from_millis(500),
+            dedup_capacity: 10_000,
+            max_buffered_per_match: 256,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OrderingStats {
+    pub released: u64,
+    /// Redeliveries of an event id, or of a sequence number, already seen
+    pub duplicates: u64,
+    /// Events arriving after later ones had been released
+    pub late_dropped: u64,
+    /// Missing sequence numbers given up on once the window passed
+    pub gaps_skipped: u64,
+    pub buffered: usize,
+}
+
+#[derive(Debug, Default)]
+struct MatchOrder {
+    next_sequence: u64,
+    /// Early events by sequence number, with when they arrived
+    pending: BTreeMap<u64, (MatchEvent, Instant)>,
+}
+
+/// Releases each match's events in sequence order, exactly once. Events out
+/// of order are held for up to the reorder window; if the missing ones still
+/// haven't arrived, the gap is skipped and anything from before it that turns
+/// up later is dropped, so match state never goes backwards. Events without
+/// a sequence number are only deduplicated.
+pub struct EventSequencer {
+    config: OrderingConfig,
+    matches: HashMap<String, MatchOrder>,
+    seen: HashSet<Uuid>,
+    seen_order: VecDeque<Uuid>,
+    stats: OrderingStats,
+}
+
+impl EventSequencer {
+    pub fn new(config: OrderingConfig) -> Self {
+        Self {
+            config,
+            matches: HashMap::new(),
+            seen: HashSet::new(),
+            seen_order: VecDeque::new(),
+            stats: OrderingStats::default(),
+        }
+    }
+
+    /// Accept an event; returns whatever can now be released, in order
+    pub fn push(&mut self, event: MatchEvent, now: Instant) -> Vec<MatchEvent> {
+        if !self.remember(event.id) {
+            self.stats.duplicates += 1;
+            return Vec::new();
+        }
+        let Some(sequence) = event.sequence else {
+            return self.release(vec![event]);
+        };
+
+        // The first event seen for a match sets where its sequence starts
+        let order = self.matches.entry(event.match_id.clone()).or_insert_with(|| MatchOrder {
+            next_sequence: sequence,
+            pending: BTreeMap::new(),
+        });
+        if sequence < order.next_sequence {
+            self.stats.late_dropped += 1;
+            return Vec::new();
+        }
+        if order.pending.contains_key(&sequence) {
+            self.stats.duplicates += 1;
+            return Vec::new();
+        }
+
+        let match_id = event.match_id.clone();
+        order.pending.insert(sequence, (event, now));
+        let mut ready = Self::drain_ready(order);
+        if order.pending.len() > self.config.max_buffered_per_match {
+            ready.extend(Self::skip_gap(order, &mut self.stats));
+        }
+        self.finish_match_if_ended(&match_id, &ready);
+        self.release(ready)
+    }
+
+    /// Release events whose wait for a missing predecessor has run out.
+    /// Call periodically so a gap doesn't stall a match that has gone quiet.
+    pub fn flush_expired(&mut self, now: Instant) -> Vec<MatchEvent> {
+        let window = self.config.reorder_window;
+        let mut ready = Vec::new();
+        let mut ended = Vec::new();
+        for (match_id, order) in self.matches.iter_mut() {
+            let expired = order.pending.values().next()
+                .is_some_and(|(_, arrived)| now.duration_since(*arrived) >= window);
+            if expired {
+                let released = Self::skip_gap(order, &mut self.stats);
+                if released.iter().any(|event| event.event_type == EventType::MatchEnd) {
+                    ended.push(match_id.clone());
+                }
+                ready.extend(released);
+            }
+        }
+        for match_id in ended {
+            self.forget_match(&match_id);
+        }
+        self.release(ready)
+    }
+
+    /// Everything still held, in order, e.g. when the input closes
+    pub fn drain(&mut self) -> Vec<MatchEvent> {
+        let mut ready = Vec::new();
+        for (_, order) in self.matches.drain() {
+            ready.extend(order.pending.into_values().map(|(event, _)| event));
+        }
+        self.release(ready)
+    }
+
+    pub fn stats(&self) -> OrderingStats {
+        OrderingStats {
+            buffered: self.matches.values().map(|order| order.pending.len()).sum(),
+            ..self.stats.clone()
+        }
+    }
+
+    fn drain_ready(order: &mut MatchOrder) -> Vec<MatchEvent> {
+        let mut ready = Vec::new();
+        while let Some((event, _)) = order.pending.remove(&order.next_sequence) {
+            order.next_sequence += 1;
+            ready.push(event);
+        }
+        ready
+    }
+
+    /// Jump to the earliest held event and release from there
+    fn skip_gap(order: &mut MatchOrder, stats: &mut OrderingStats) -> Vec<MatchEvent> {
+        if let Some(&first) = order.pending.keys().next() {
+            stats.gaps_skipped += first - order.next_sequence;
+            order.next_sequence = first;
+        }
+        Self::drain_ready(order)
+    }
+
+    /// A finished match's ordering state is dropped; its event ids stay in
+    /// the dedup cache
+    fn finish_match_if_ended(&mut self, match_id: &str, released: &[MatchEvent]) {
+        if released.iter().any(|event| event.event_type == EventType::MatchEnd) {
+            self.forget_match(match_id);
+        }
+    }
+
+    fn forget_match(&mut self, match_id: &str) {
+        if self.matches.get(match_id).is_some_and(|order| order.pending.is_empty()) {
+            self.matches.remove(match_id);
+        }
+    }
+
+    /// False if the id was already seen
+    fn remember(&mut self, id: Uuid) -> bool {
+        if !self.seen.insert(id) {
+            return false;
+        }
+        self.seen_order.push_back(id);
+        while self.seen_order.len() > self.config.dedup_capacity {
+            if let Some(oldest) = self.seen_order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    fn release(&mut self, events: Vec<MatchEvent>) -> Vec<MatchEvent> {
+        self.stats.released += events.len() as u64;
+        events
+    }
+}
+
+/// Put a sequencer between the ingress queue and its consumer. The returned
+/// channel is bounded like the queue, so a slow consumer holds the sequencer
+/// back and the queue's overflow policy takes over. It closes once the input
+/// has closed and everything held has been released.
+pub fn spawn_ordering(
+    mut input: EventQueueReceiver,
+    config: OrderingConfig,
+) -> mpsc::Receiver<MatchEvent> {
+    let (sender, output) = mpsc::channel(input.capacity());
+    let tick = (config.reorder_window / 2).max(Duration::from_millis(10));
+
+    tokio::spawn(async move {
+        let mut sequencer = EventSequencer::new(config);
+        let mut interval = tokio::time::interval(tick);
+        loop {
+            let ready = tokio::select! {
+                event = input.recv() => match event {
+                    Some(event) => sequencer.push(event, Instant::now()),
+                    None => break,
+                },
+                _ = interval.tick() => sequencer.flush_expired(Instant::now()),
+            };
+            for event in ready {
+                if sender.send(event).await.is_err() {
+                    return;
+                }
+            }
+        }
+
+        for event in sequencer.drain() {
+            let _ = sender.send(event).await;
+        }
+        let stats = sequencer.stats();
+        tracing::info!("🔀 Event ordering stopped: {} released, {} duplicates, {} late, {} gaps skipped",
+                       stats.released, stats.duplicates, stats.late_dropped, stats.gaps_skipped);
+    });
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(match_id: &str, sequence: u64, event_type: EventType) -> MatchEvent {
+        MatchEvent::new(
+            match_id.to_string(),
+            event_type,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        ).with_sequence(sequence)
+    }
+
+    fn sequences(events: &[MatchEvent]) -> Vec<u64> {
+        events.iter().filter_map(|event| event.sequence).collect()
+    }
+
+    #[test]
+    fn test_events_deduplicated_and_reordered() {
+        let config = OrderingConfig { reorder_window: Duration::from_millis(100), ..Default::default() };
+        let mut sequencer = EventSequencer::new(config);
+        let start = Instant::now();
+
+        let first = event("m1", 1, EventType::MatchStart);
+        assert_eq!(sequences(&sequencer.push(first.clone(), start)), [1]);
+        assert!(sequencer.push(first, start).is_empty());
+
+        // 3 waits for 2, then both go out in order
+        assert!(sequencer.push(event("m1", 3, EventType::HalfTime), start).is_empty());
+        assert_eq!(sequences(&sequencer.push(event("m1", 2, EventType::OddsUpdate), start)), [2, 3]);
+
+        // 4 never arrives; 5 is released once the window passes and 4 is then too late
+        assert!(sequencer.push(event("m1", 5, EventType::FullTime), start).is_empty());
+        assert!(sequencer.flush_expired(start + Duration::from_millis(50)).is_empty());
+        assert_eq!(sequences(&sequencer.flush_expired(start + Duration::from_millis(100))), [5]);
+        assert!(sequencer.push(event("m1", 4, EventType::OddsUpdate), start).is_empty());
+
+        // Events without a sequence number pass straight through
+        let mut unsequenced = event("m2", 0, EventType::MatchStart);
+        unsequenced.sequence = None;
+        assert_eq!(sequencer.push(unsequenced, start).len(), 1);
+
+        let stats = sequencer.stats();
+        assert_eq!((stats.released, stats.duplicates, stats.late_dropped, stats.gaps_skipped), (5, 1, 1, 1));
+        assert_eq!(stats.buffered, 0);
+    }
+}
@@ -0,0 +1,397 @@
+// Bounded ingress queue between the data feed and event processing, with a
+// choice of what happens when the processor falls behind
+
+use anyhow::Result;
+use quant_models::MatchEvent;
+use redis::aio::MultiplexedConnection;
+use redis::{AsyncCommands, Client};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Notify, OnceCell};
+
+/// What a send does when the queue is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// Wait for room, slowing the producer down
+    #[default]
+    Block,
+    /// Discard the oldest queued event to make room
+    DropOldest,
+    /// Push the overflow to a Redis list and read it back once the queue has
+    /// drained
+    Spill,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueueConfig {
+    /// Events held in memory
+    pub capacity: usize,
+    pub overflow: OverflowPolicy,
+    /// Redis list the `spill` policy writes to
+    pub spill_key: String,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            overflow: OverflowPolicy::Block,
+            spill_key: "quant:event_spill".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueueStats {
+    /// Events waiting in memory
+    pub depth: usize,
+    pub capacity: usize,
+    /// Deepest the in-memory queue has been
+    pub high_water: usize,
+    /// Events waiting in the Redis spill
+    pub spilled_depth: usize,
+    pub enqueued: u64,
+    /// Events discarded under `drop_oldest`, or because spilling failed
+    pub dropped: u64,
+    pub spilled: u64,
+    /// Sends that had to wait for room under `block`
+    pub blocked: u64,
+}
+
+/// Overflow storage in a Redis list. The list is cleared on connect: events
+/// left over from an earlier run are stale by now.
+pub struct RedisSpill {
+    client: Client,
+    connection: OnceCell<MultiplexedConnection>,
+    key: String,
+}
+
+impl RedisSpill {
+    pub async fn connect(redis_url: &str, key: &str) -> Result<Self> {
+        let spill = Self {
+            client: Client::open(redis_url)?,
+            connection: OnceCell::new(),
+            key: key.to_string(),
+        };
+        let mut connection = spill.connection().await?;
+        let _: () = connection.del(&spill.key).await?;
+        Ok(spill)
+    }
+
+    async fn connection(&self) -> Result<MultiplexedConnection> {
+        Ok(self.connection
+            .get_or_try_init(|| self.client.get_multiplexed_tokio_connection())
+            .await?
+            .clone())
+    }
+
+    async fn push(&self, event: &MatchEvent) -> Result<()> {
+        let payload = serde_json::to_string(event)?;
+        let _: () = self.connection().await?.rpush(&self.key, payload).await?;
+        Ok(())
+    }
+
+    async fn pop(&self) -> Result<Option<MatchEvent>> {
+        let payload: Option<String> = self.connection().await?.lpop(&self.key, None).await?;
+        Ok(payload.map(|payload| serde_json::from_str(&payload)).transpose()?)
+    }
+}
+
+struct QueueState {
+    events: VecDeque<MatchEvent>,
+    /// Events pushed to the spill and not yet read back; while any are, new
+    /// events are spilled too so they keep their order
+    spilled_depth: usize,
+    senders: usize,
+    receiver_alive: bool,
+    stats: QueueStats,
+}
+
+struct Shared {
+    state: Mutex<QueueState>,
+    policy: OverflowPolicy,
+    spill: Option<RedisSpill>,
+    item_ready: Notify,
+    space_ready: Notify,
+}
+
+impl Shared {
+    fn stats(&self) -> QueueStats {
+        let state = self.state.lock().unwrap();
+        QueueStats {
+            depth: state.events.len(),
+            spilled_depth: state.spilled_depth,
+            ..state.stats.clone()
+        }
+    }
+}
+
+/// Create a queue. `spill` is needed for the `spill` policy; without it
+/// overflow falls back to waiting for room.
+pub fn event_queue(config: &QueueConfig, spill: Option<RedisSpill>) -> (EventQueueSender, EventQueueReceiver) {
+    let capacity = config.capacity.max(1);
+    let policy = match (config.overflow, &spill) {
+        (OverflowPolicy::Spill, None) => {
+            tracing::warn!("📥 Event queue spill has no Redis connection; blocking on overflow instead");
+            OverflowPolicy::Block
+        }
+        (policy, _) => policy,
+    };
+    let shared = Arc::new(Shared {
+        state: Mutex::new(QueueState {
+            events: VecDeque::with_capacity(capacity.min(1024)),
+            spilled_depth: 0,
+            senders: 1,
+            receiver_alive: true,
+            stats: QueueStats { capacity, ..Default::default() },
+        }),
+        policy,
+        spill,
+        item_ready: Notify::new(),
+        space_ready: Notify::new(),
+    });
+    (EventQueueSender { shared: shared.clone() }, EventQueueReceiver { shared })
+}
+
+pub struct EventQueueSender {
+    shared: Arc<Shared>,
+}
+
+impl EventQueueSender {
+    /// Queue an event, applying the overflow policy if the queue is full.
+    /// Fails only once the receiver is gone.
+    pub async fn send(&self, event: MatchEvent) -> Result<()> {
+        let mut waited = false;
+        loop {
+            let wait = {
+                let mut state = self.shared.state.lock().unwrap();
+                if !state.receiver_alive {
+                    // Pass the wake-up on to any other sender still waiting
+                    self.shared.space_ready.notify_one();
+                    anyhow::bail!("event queue closed");
+                }
+                let full = state.spilled_depth > 0 || state.events.len() >= state.stats.capacity;
+                if !full || self.shared.policy == OverflowPolicy::DropOldest {
+                    if full && state.events.pop_front().is_some() {
+                        state.stats.dropped += 1;
+                    }
+                    state.events.push_back(event);
+                    state.stats.enqueued += 1;
+                    state.stats.high_water = state.stats.high_water.max(state.events.len());
+                    drop(state);
+                    self.shared.item_ready.notify_one();
+                    return Ok(());
+                }
+                if self.shared.policy == OverflowPolicy::Spill {
+                    state.spilled_depth += 1;
+                    false
+                } else {
+                    if !waited {
+                        state.stats.blocked += 1;
+                        waited = true;
+                    }
+                    true
+                }
+            };
+
+            if wait {
+                // A recv before this point leaves a permit, so it isn't missed
+                self.shared.space_ready.notified().await;
+                continue;
+            }
+            return self.spill(event).await;
+        }
+    }
+
+    async fn spill(&self, event: MatchEvent) -> Result<()> {
+        let spill = self.shared.spill.as_ref().expect("spill policy without a spill");
+        let pushed = spill.push(&event).await;
+        {
+            let mut state = self.shared.state.lock().unwrap();
+            match &pushed {
+                Ok(()) => {
+                    state.stats.enqueued += 1;
+                    state.stats.spilled += 1;
+                }
+                Err(e) => {
+                    state.spilled_depth -= 1;
+                    state.stats.dropped += 1;
+                    tracing::warn!("📥 Could not spill event {} to Redis, dropping it: {}", event.id, e);
+                }
+            }
+        }
+        self.shared.item_ready.notify_one();
+        Ok(())
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        self.shared.stats()
+    }
+}
+
+impl Clone for EventQueueSender {
+    fn clone(&self) -> Self {
+        self.shared.state.lock().unwrap().senders += 1;
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl Drop for EventQueueSender {
+    fn drop(&mut self) {
+        let last = {
+            let mut state = self.shared.state.lock().unwrap();
+            state.senders -= 1;
+            state.senders == 0
+        };
+        if last {
+            self.shared.item_ready.notify_one();
+        }
+    }
+}
+
+pub struct EventQueueReceiver {
+    shared: Arc<Shared>,
+}
+
+impl EventQueueReceiver {
+    /// The next event, in the order they were sent; `None` once every
+    /// sender has gone and the queue and spill are empty
+    pub async fn recv(&mut self) -> Option<MatchEvent> {
+        loop {
+            let spilled = {
+                let mut state = self.shared.state.lock().unwrap();
+                if let Some(event) = state.events.pop_front() {
+                    drop(state);
+                    self.shared.space_ready.notify_one();
+                    return Some(event);
+                }
+                if state.spilled_depth == 0 && state.senders == 0 {
+                    return None;
+                }
+                state.spilled_depth > 0
+            };
+
+            if spilled {
+                if let Some(spill) = &self.shared.spill {
+                    match spill.pop().await {
+                        Ok(Some(event)) => {
+                            self.shared.state.lock().unwrap().spilled_depth -= 1;
+                            return Some(event);
+                        }
+                        // The push is still in flight; its sender will wake us
+                        Ok(None) => {}
+                        Err(e) => {
+                            tracing::warn!("📥 Could not read spilled events from Redis: {}", e);
+                            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                            continue;
+                        }
+                    }
+                }
+            }
+            self.shared.item_ready.notified().await;
+        }
+    }
+
+    /// The next event held in memory, without waiting
+    pub fn try_recv(&mut self) -> Option<MatchEvent> {
+        let event = self.shared.state.lock().unwrap().events.pop_front();
+        if event.is_some() {
+            self.shared.space_ready.notify_one();
+        }
+        event
+    }
+
+    /// Room in memory for this many events
+    pub fn capacity(&self) -> usize {
+        self.shared.state.lock().unwrap().stats.capacity
+    }
+
+    /// A handle for reading queue stats that doesn't keep the queue open
+    pub fn monitor(&self) -> QueueMonitor {
+        QueueMonitor { shared: self.shared.clone() }
+    }
+}
+
+impl Drop for EventQueueReceiver {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().receiver_alive = false;
+        self.shared.space_ready.notify_one();
+    }
+}
+
+#[derive(Clone)]
+pub struct QueueMonitor {
+    shared: Arc<Shared>,
+}
+
+impl QueueMonitor {
+    pub fn stats(&self) -> QueueStats {
+        self.shared.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_models::EventType;
+    use std::time::Duration;
+
+    fn event(sequence: u64) -> MatchEvent {
+        MatchEvent::new(
+            "m1".to_string(),
+            EventType::MatchStart,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        ).with_sequence(sequence)
+    }
+
+    #[tokio::test]
+    async fn test_overflow_policies() {
+        // Dropping the oldest keeps the newest `capacity` events
+        let config = QueueConfig { capacity: 3, overflow: OverflowPolicy::DropOldest, ..Default::default() };
+        let (sender, mut receiver) = event_queue(&config, None);
+        for sequence in 0..5 {
+            sender.send(event(sequence)).await.unwrap();
+        }
+        let stats = receiver.monitor().stats();
+        assert_eq!((stats.depth, stats.high_water, stats.dropped, stats.enqueued), (3, 3, 2, 5));
+        drop(sender);
+        let mut sequences = Vec::new();
+        while let Some(event) = receiver.recv().await {
+            sequences.push(event.sequence.unwrap());
+        }
+        assert_eq!(sequences, vec![2, 3, 4]);
+
+        // Blocking holds the producer until the consumer makes room
+        let config = QueueConfig { capacity: 2, overflow: OverflowPolicy::Block, ..Default::default() };
+        let (sender, mut receiver) = event_queue(&config, None);
+        let producer = tokio::spawn(async move {
+            for sequence in 0..4 {
+                sender.send(event(sequence)).await.unwrap();
+            }
+            sender.stats()
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!producer.is_finished());
+        assert_eq!(receiver.monitor().stats().depth, 2);
+        for expected in 0..4 {
+            assert_eq!(receiver.recv().await.unwrap().sequence, Some(expected));
+        }
+        let stats = producer.await.unwrap();
+        assert_eq!((stats.dropped, stats.enqueued), (0, 4));
+        assert!(stats.blocked >= 1);
+        assert!(receiver.recv().await.is_none());
+
+        // Without a Redis connection, spilling falls back to blocking
+        let config = QueueConfig { capacity: 1, overflow: OverflowPolicy::Spill, ..Default::default() };
+        let (sender, receiver) = event_queue(&config, None);
+        sender.send(event(0)).await.unwrap();
+        drop(receiver);
+        assert!(sender.send(event(1)).await.is_err());
+    }
+}
@@ -1,15 +1,72 @@
 // Redis streaming implementation
 
-use redis::Client;
+use redis::aio::MultiplexedConnection;
+use redis::{AsyncCommands, Client};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use quant_models::QueryLog;
+use crate::message::StreamMessage;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
 
 pub struct RedisStream {
     client: Client,
+    connection: OnceCell<MultiplexedConnection>,
+    query_log: Arc<QueryLog>,
 }
 
 impl RedisStream {
     pub async fn new(redis_url: &str) -> Result<Self> {
         let client = Client::open(redis_url)?;
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            connection: OnceCell::new(),
+            query_log: Arc::new(QueryLog::default()),
+        })
     }
-}
\ No newline at end of file
+
+    /// Record command timings, and slow commands, in a shared log
+    pub fn with_query_log(mut self, query_log: Arc<QueryLog>) -> Self {
+        self.query_log = query_log;
+        self
+    }
+
+    /// Append a message to a stream, tagged with its id so consumers can
+    /// drop redeliveries. Returns the entry id Redis assigned.
+    pub async fn publish(&self, stream_key: &str, message_id: &str, payload: &str) -> Result<String> {
+        let mut connection = self.connection
+            .get_or_try_init(|| self.client.get_multiplexed_tokio_connection())
+            .await?
+            .clone();
+
+        let entry_id: String = self.query_log.time(
+            "redis",
+            "xadd",
+            connection.xadd(stream_key, "*", &[("message_id", message_id), ("payload", payload)]),
+        ).await?;
+
+        Ok(entry_id)
+    }
+
+    /// Append a match event in its versioned envelope
+    pub async fn publish_message(&self, stream_key: &str, message: &StreamMessage) -> Result<String> {
+        self.publish(stream_key, &message.id, &message.encode()?).await
+    }
+
+    /// Drop entries added before `cutoff`, going by the time Redis stamped
+    /// into their ids. Returns how many were removed.
+    pub async fn trim_before(&self, stream_key: &str, cutoff: DateTime<Utc>) -> Result<u64> {
+        let mut connection = self.connection
+            .get_or_try_init(|| self.client.get_multiplexed_tokio_connection())
+            .await?
+            .clone();
+
+        let mut trim = redis::cmd("XTRIM");
+        trim.arg(stream_key)
+            .arg("MINID")
+            .arg(format!("{}-0", cutoff.timestamp_millis().max(0)));
+        let removed: u64 = self.query_log.time("redis", "xtrim", trim.query_async(&mut connection)).await?;
+
+        Ok(removed)
+    }
+}
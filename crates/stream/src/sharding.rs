@@ -0,0 +1,83 @@
+// Spreads events over parallel workers, keeping each match on one worker
+
+use quant_models::MatchEvent;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use tokio::sync::mpsc;
+
+/// Which of `shards` workers handles `match_id`; the same match always
+/// lands on the same worker
+pub fn shard_for(match_id: &str, shards: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    match_id.hash(&mut hasher);
+    (hasher.finish() % shards.max(1) as u64) as usize
+}
+
+/// Split one event channel into `shards` channels by match id, each holding
+/// up to `capacity` events. Events of a match come out of its channel in the
+/// order they went in, while different matches can be processed
+/// concurrently; a worker that falls behind holds up the split. The returned
+/// channels close once the input has closed.
+pub fn spawn_sharding(
+    mut input: mpsc::Receiver<MatchEvent>,
+    shards: usize,
+    capacity: usize,
+) -> Vec<mpsc::Receiver<MatchEvent>> {
+    let (senders, receivers): (Vec<_>, Vec<_>) = (0..shards.max(1)).map(|_| mpsc::channel(capacity.max(1))).unzip();
+
+    tokio::spawn(async move {
+        while let Some(event) = input.recv().await {
+            let shard = shard_for(&event.match_id, senders.len());
+            if senders[shard].send(event).await.is_err() {
+                tracing::warn!("🔀 Event worker {} stopped; its events are dropped", shard);
+            }
+        }
+    });
+
+    receivers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_models::EventType;
+
+    fn event(match_id: &str, sequence: u64) -> MatchEvent {
+        MatchEvent::new(
+            match_id.to_string(),
+            EventType::MatchStart,
+            "Arsenal".to_string(),
+            "Chelsea".to_string(),
+            "Premier League".to_string(),
+            "2024-25".to_string(),
+        ).with_sequence(sequence)
+    }
+
+    #[tokio::test]
+    async fn test_matches_stay_on_one_worker_in_order() {
+        let (sender, input) = mpsc::channel(100);
+        let mut workers = spawn_sharding(input, 4, 100);
+        assert_eq!(workers.len(), 4);
+
+        let matches: Vec<String> = (0..20).map(|i| format!("m{}", i)).collect();
+        for sequence in 0..5 {
+            for match_id in &matches {
+                sender.send(event(match_id, sequence)).await.unwrap();
+            }
+        }
+        drop(sender);
+
+        let mut received = 0;
+        for (shard, worker) in workers.iter_mut().enumerate() {
+            let mut last_sequence = std::collections::HashMap::new();
+            while let Some(event) = worker.recv().await {
+                assert_eq!(shard_for(&event.match_id, 4), shard);
+                let previous = last_sequence.insert(event.match_id.clone(), event.sequence.unwrap());
+                assert!(previous < event.sequence);
+                received += 1;
+            }
+        }
+        assert_eq!(received, 100);
+        assert_eq!(spawn_sharding(mpsc::channel(1).1, 0, 0).len(), 1);
+    }
+}
@@ -57,7 +57,7 @@ api_call "/api/v1/status" "System Status"
 api_call "/api/v1/events/live" "Live Events (Last 10)"
 
 # Recent Events with Pagination
-api_call "/api/v1/events?page=1&limit=5" "Recent Events (Paginated)"
+api_call "/api/v1/events?limit=5&event_type=goal" "Recent Goals (Paginated)"
 
 # Recent Predictions
 api_call "/api/v1/predictions?page=1&limit=3" "Recent Predictions"
@@ -0,0 +1,17 @@
+-- Transactional outbox for messages that must be published to Redis streams.
+-- Rows are written in the same transaction as the state change they describe
+-- and removed from the pending set once the relay has published them.
+
+CREATE TABLE outbox (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    stream_key VARCHAR(255) NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    published_at TIMESTAMPTZ,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT
+);
+
+CREATE INDEX idx_outbox_pending ON outbox(created_at) WHERE published_at IS NULL;
+
+COMMENT ON TABLE outbox IS 'Messages awaiting publication to Redis, written atomically with their source rows';
@@ -0,0 +1,19 @@
+-- Feature snapshots captured at prediction time, labeled with the full-time
+-- result once the match finishes. Used by the offline model trainer.
+
+CREATE TABLE training_examples (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    match_id VARCHAR(255) NOT NULL,
+    features JSONB NOT NULL,
+    outcome VARCHAR(20),
+    feature_timestamp TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT valid_training_outcome CHECK (outcome IS NULL OR outcome IN ('home_win', 'draw', 'away_win'))
+);
+
+CREATE INDEX idx_training_examples_match_id ON training_examples(match_id);
+CREATE INDEX idx_training_examples_labeled ON training_examples(feature_timestamp DESC) WHERE outcome IS NOT NULL;
+
+COMMENT ON TABLE training_examples IS 'Feature vectors with their eventual match result, for offline training';
+COMMENT ON COLUMN training_examples.features IS 'Feature name to value map as produced by the feature engineer';
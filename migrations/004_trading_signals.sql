@@ -0,0 +1,22 @@
+-- Every trading signal the engine generated, whether or not it was executed,
+-- so strategy decisions can be audited after the fact.
+
+CREATE TABLE trading_signals (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    match_id VARCHAR(255) NOT NULL,
+    signal_strength DOUBLE PRECISION NOT NULL,
+    bet_type VARCHAR(50),
+    stake DECIMAL(12,2),
+    odds DECIMAL(8,2),
+    strategy VARCHAR(100),
+    risk_score DOUBLE PRECISION NOT NULL,
+    warnings TEXT[] NOT NULL DEFAULT '{}',
+    reasoning TEXT NOT NULL,
+    generated_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_trading_signals_match_id ON trading_signals(match_id);
+CREATE INDEX idx_trading_signals_generated_at ON trading_signals(generated_at DESC);
+
+COMMENT ON TABLE trading_signals IS 'Signals produced by the trading engine, with the bet they recommended if any';
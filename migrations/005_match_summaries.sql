@@ -0,0 +1,15 @@
+-- Post-mortem summary of every finished match: result, events, the model's
+-- kick-off prediction, closing odds and the bets placed on it.
+
+CREATE TABLE match_summaries (
+    match_id VARCHAR(255) PRIMARY KEY,
+    home_score INTEGER NOT NULL,
+    away_score INTEGER NOT NULL,
+    profit_loss DECIMAL(12,2) NOT NULL DEFAULT 0,
+    summary JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_match_summaries_created_at ON match_summaries(created_at DESC);
+
+COMMENT ON TABLE match_summaries IS 'End-of-match summaries; the full record is kept as JSON in summary';
@@ -0,0 +1,18 @@
+-- The exact feature vector each prediction was made from, so predictions can
+-- be debugged and models re-evaluated on the inputs they actually saw.
+
+CREATE TABLE prediction_features (
+    prediction_id UUID PRIMARY KEY,
+    match_id VARCHAR(255) NOT NULL,
+    model_name VARCHAR(255) NOT NULL,
+    model_version VARCHAR(50) NOT NULL,
+    features JSONB NOT NULL,
+    feature_timestamp TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_prediction_features_match_id ON prediction_features(match_id);
+CREATE INDEX idx_prediction_features_model ON prediction_features(model_name, model_version, feature_timestamp DESC);
+
+COMMENT ON TABLE prediction_features IS 'Feature store: inputs behind each prediction, keyed by prediction id';
+COMMENT ON COLUMN prediction_features.features IS 'Feature name to value map as produced by the feature engineer';
@@ -0,0 +1,8 @@
+-- The full event as received, so derived state (match contexts, team stats,
+-- match timelines) can be rebuilt by replaying the event log.
+
+ALTER TABLE events ADD COLUMN payload JSONB;
+
+CREATE INDEX idx_events_replay_order ON events(timestamp, created_at, id) WHERE payload IS NOT NULL;
+
+COMMENT ON COLUMN events.payload IS 'Serialized MatchEvent; rows without one predate the event log and are not replayed';
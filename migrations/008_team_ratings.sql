@@ -0,0 +1,12 @@
+-- Elo rating per team, updated as results come in, so ratings carry over
+-- between runs instead of starting from scratch.
+
+CREATE TABLE team_ratings (
+    team VARCHAR(255) PRIMARY KEY,
+    rating DOUBLE PRECISION NOT NULL,
+    matches_played INTEGER NOT NULL DEFAULT 0,
+    season VARCHAR(50),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON COLUMN team_ratings.season IS 'Season of the latest rated match; a match from a later season regresses the rating toward the mean first';
@@ -0,0 +1,29 @@
+-- Every signal, risk decision and execution the trader makes, written once
+-- and never changed. Execution attempts carry an idempotency key; the
+-- unique index means a bet retried after a crash is refused instead of
+-- being placed twice.
+
+CREATE TABLE trade_audit_log (
+    id UUID PRIMARY KEY,
+    kind VARCHAR(50) NOT NULL,
+    match_id VARCHAR(255) NOT NULL,
+    signal_id UUID,
+    bet_id UUID,
+    idempotency_key VARCHAR(255),
+    entry JSONB NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_trade_audit_log_match ON trade_audit_log (match_id, recorded_at);
+CREATE UNIQUE INDEX idx_trade_audit_log_execution_key ON trade_audit_log (idempotency_key)
+    WHERE kind = 'execution_attempt';
+
+CREATE FUNCTION trade_audit_log_append_only() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'trade_audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trade_audit_log_no_changes
+    BEFORE UPDATE OR DELETE ON trade_audit_log
+    FOR EACH ROW EXECUTE FUNCTION trade_audit_log_append_only();
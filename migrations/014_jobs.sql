@@ -0,0 +1,20 @@
+-- Background jobs, such as settlement retries and retraining, kept so they
+-- are retried on failure and picked up again after a restart.
+
+CREATE TABLE jobs (
+    id UUID PRIMARY KEY,
+    kind VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL,
+    run_at TIMESTAMPTZ NOT NULL,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_jobs_unfinished ON jobs(run_at) WHERE status IN ('pending', 'running');
+CREATE INDEX idx_jobs_created ON jobs(created_at DESC);
+
+COMMENT ON COLUMN jobs.status IS 'pending, running, succeeded or failed; a failed job has used up its attempts';
@@ -0,0 +1,8 @@
+-- The 1X2 prices on offer when each prediction was made, so models can be
+-- evaluated for return as well as accuracy from the stored predictions.
+
+ALTER TABLE predictions ADD COLUMN home_odds DECIMAL(8,2);
+ALTER TABLE predictions ADD COLUMN draw_odds DECIMAL(8,2);
+ALTER TABLE predictions ADD COLUMN away_odds DECIMAL(8,2);
+
+CREATE INDEX idx_predictions_model_timestamp ON predictions(model_name, prediction_timestamp);
@@ -0,0 +1,167 @@
+-- Initial database schema for the sports betting prediction system, for
+-- SQLite. Mirrors ../001_initial_schema.sql: UUIDs are 16-byte blobs,
+-- timestamps RFC 3339 text, JSON and decimals text, booleans 0/1.
+
+-- Matches table
+CREATE TABLE matches (
+    id BLOB PRIMARY KEY,
+    match_id TEXT UNIQUE NOT NULL,
+    team_home TEXT NOT NULL,
+    team_away TEXT NOT NULL,
+    league TEXT NOT NULL,
+    season TEXT NOT NULL,
+    match_date TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'scheduled',
+    home_score INTEGER,
+    away_score INTEGER,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+);
+
+-- Events table for match events
+CREATE TABLE events (
+    id BLOB PRIMARY KEY,
+    match_id TEXT NOT NULL REFERENCES matches(match_id),
+    event_type TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    minute INTEGER,
+    team TEXT,
+    player TEXT,
+    metadata TEXT DEFAULT '{}',
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+);
+
+-- Predictions table
+CREATE TABLE predictions (
+    id BLOB PRIMARY KEY,
+    match_id TEXT NOT NULL REFERENCES matches(match_id),
+    model_name TEXT NOT NULL,
+    model_version TEXT NOT NULL,
+    home_win_prob REAL NOT NULL CHECK (home_win_prob >= 0 AND home_win_prob <= 1),
+    draw_prob REAL CHECK (draw_prob >= 0 AND draw_prob <= 1),
+    away_win_prob REAL NOT NULL CHECK (away_win_prob >= 0 AND away_win_prob <= 1),
+    confidence REAL NOT NULL CHECK (confidence >= 0 AND confidence <= 1),
+    expected_goals_home REAL,
+    expected_goals_away REAL,
+    -- JSON array of feature names
+    features_used TEXT NOT NULL DEFAULT '[]',
+    prediction_timestamp TEXT NOT NULL,
+    match_timestamp TEXT NOT NULL,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    CONSTRAINT valid_probabilities CHECK (
+        home_win_prob + away_win_prob + COALESCE(draw_prob, 0) <= 1.001
+    )
+);
+
+-- Bets table
+CREATE TABLE bets (
+    id BLOB PRIMARY KEY,
+    match_id TEXT NOT NULL REFERENCES matches(match_id),
+    bet_type TEXT NOT NULL,
+    stake TEXT NOT NULL CHECK (CAST(stake AS REAL) > 0),
+    odds TEXT NOT NULL CHECK (CAST(odds AS REAL) > 1.0),
+    expected_value REAL NOT NULL,
+    kelly_fraction REAL NOT NULL,
+    confidence REAL NOT NULL,
+    strategy TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    placed_at TEXT NOT NULL,
+    settled_at TEXT,
+    payout TEXT,
+    profit_loss TEXT,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+);
+
+-- Odds table for market data
+CREATE TABLE odds (
+    id BLOB PRIMARY KEY,
+    match_id TEXT NOT NULL REFERENCES matches(match_id),
+    bookmaker TEXT NOT NULL,
+    market_type TEXT NOT NULL,
+    home_odds TEXT,
+    draw_odds TEXT,
+    away_odds TEXT,
+    timestamp TEXT NOT NULL,
+    is_active BOOLEAN DEFAULT TRUE,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+);
+
+-- Model performance tracking
+CREATE TABLE model_performance (
+    id BLOB PRIMARY KEY,
+    model_name TEXT NOT NULL,
+    model_version TEXT NOT NULL,
+    total_predictions INTEGER NOT NULL DEFAULT 0,
+    correct_predictions INTEGER NOT NULL DEFAULT 0,
+    accuracy REAL NOT NULL DEFAULT 0,
+    log_loss REAL NOT NULL DEFAULT 0,
+    brier_score REAL NOT NULL DEFAULT 0,
+    roi REAL NOT NULL DEFAULT 0,
+    sharpe_ratio REAL NOT NULL DEFAULT 0,
+    max_drawdown REAL NOT NULL DEFAULT 0,
+    calibration_slope REAL NOT NULL DEFAULT 1.0,
+    calibration_intercept REAL NOT NULL DEFAULT 0.0,
+    evaluation_period_start TEXT NOT NULL,
+    evaluation_period_end TEXT NOT NULL,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    UNIQUE(model_name, model_version, evaluation_period_start)
+);
+
+-- Indexes for performance
+CREATE INDEX idx_matches_date ON matches(match_date);
+CREATE INDEX idx_matches_league_season ON matches(league, season);
+CREATE INDEX idx_matches_teams ON matches(team_home, team_away);
+
+CREATE INDEX idx_events_match_id ON events(match_id);
+CREATE INDEX idx_events_timestamp ON events(timestamp);
+CREATE INDEX idx_events_type ON events(event_type);
+
+CREATE INDEX idx_predictions_match_id ON predictions(match_id);
+CREATE INDEX idx_predictions_model ON predictions(model_name, model_version);
+CREATE INDEX idx_predictions_timestamp ON predictions(prediction_timestamp);
+
+CREATE INDEX idx_bets_match_id ON bets(match_id);
+CREATE INDEX idx_bets_status ON bets(status);
+CREATE INDEX idx_bets_strategy ON bets(strategy);
+CREATE INDEX idx_bets_placed_at ON bets(placed_at);
+
+CREATE INDEX idx_odds_match_id ON odds(match_id);
+CREATE INDEX idx_odds_bookmaker ON odds(bookmaker);
+CREATE INDEX idx_odds_timestamp ON odds(timestamp);
+CREATE INDEX idx_odds_active ON odds(is_active) WHERE is_active = TRUE;
+
+CREATE INDEX idx_model_performance_model ON model_performance(model_name, model_version);
+CREATE INDEX idx_model_performance_period ON model_performance(evaluation_period_start, evaluation_period_end);
+
+-- Views for common queries
+CREATE VIEW active_matches AS
+SELECT * FROM matches
+WHERE status IN ('scheduled', 'live', 'halftime')
+ORDER BY match_date;
+
+CREATE VIEW recent_predictions AS
+SELECT p.*, m.team_home, m.team_away, m.league, m.match_date
+FROM predictions p
+JOIN matches m ON p.match_id = m.match_id
+WHERE p.prediction_timestamp >= strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now', '-7 days')
+ORDER BY p.prediction_timestamp DESC;
+
+CREATE VIEW betting_summary AS
+SELECT
+    strategy,
+    COUNT(*) as total_bets,
+    SUM(CAST(stake AS REAL)) as total_staked,
+    SUM(CASE WHEN status = 'won' THEN 1 ELSE 0 END) as won_bets,
+    SUM(CASE WHEN status = 'won' THEN CAST(payout AS REAL) ELSE 0 END) as total_payouts,
+    SUM(CAST(COALESCE(profit_loss, 0) AS REAL)) as total_profit_loss,
+    AVG(CASE WHEN status IN ('won', 'lost') THEN
+        CASE WHEN status = 'won' THEN 1.0 ELSE 0.0 END
+    END) as win_rate,
+    CASE WHEN SUM(CAST(stake AS REAL)) > 0 THEN
+        SUM(CAST(COALESCE(profit_loss, 0) AS REAL)) / SUM(CAST(stake AS REAL))
+    ELSE 0 END as roi
+FROM bets
+WHERE status IN ('won', 'lost')
+GROUP BY strategy;
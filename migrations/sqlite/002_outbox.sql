@@ -0,0 +1,15 @@
+-- Transactional outbox for messages that must be published to Redis streams.
+-- Rows are written in the same transaction as the state change they describe
+-- and removed from the pending set once the relay has published them.
+
+CREATE TABLE outbox (
+    id BLOB PRIMARY KEY,
+    stream_key TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    published_at TEXT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT
+);
+
+CREATE INDEX idx_outbox_pending ON outbox(created_at) WHERE published_at IS NULL;
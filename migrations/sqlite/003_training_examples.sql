@@ -0,0 +1,16 @@
+-- Feature snapshots captured at prediction time, labeled with the full-time
+-- result once the match finishes. Used by the offline model trainer.
+
+CREATE TABLE training_examples (
+    id BLOB PRIMARY KEY,
+    match_id TEXT NOT NULL,
+    features TEXT NOT NULL,
+    outcome TEXT,
+    feature_timestamp TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+
+    CONSTRAINT valid_training_outcome CHECK (outcome IS NULL OR outcome IN ('home_win', 'draw', 'away_win'))
+);
+
+CREATE INDEX idx_training_examples_match_id ON training_examples(match_id);
+CREATE INDEX idx_training_examples_labeled ON training_examples(feature_timestamp DESC) WHERE outcome IS NOT NULL;
@@ -0,0 +1,21 @@
+-- Every trading signal the engine generated, whether or not it was executed,
+-- so strategy decisions can be audited after the fact.
+
+CREATE TABLE trading_signals (
+    id BLOB PRIMARY KEY,
+    match_id TEXT NOT NULL,
+    signal_strength REAL NOT NULL,
+    bet_type TEXT,
+    stake TEXT,
+    odds TEXT,
+    strategy TEXT,
+    risk_score REAL NOT NULL,
+    -- JSON array of warnings
+    warnings TEXT NOT NULL DEFAULT '[]',
+    reasoning TEXT NOT NULL,
+    generated_at TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+);
+
+CREATE INDEX idx_trading_signals_match_id ON trading_signals(match_id);
+CREATE INDEX idx_trading_signals_generated_at ON trading_signals(generated_at DESC);
@@ -0,0 +1,13 @@
+-- Post-mortem summary of every finished match: result, events, the model's
+-- kick-off prediction, closing odds and the bets placed on it.
+
+CREATE TABLE match_summaries (
+    match_id TEXT PRIMARY KEY,
+    home_score INTEGER NOT NULL,
+    away_score INTEGER NOT NULL,
+    profit_loss TEXT NOT NULL DEFAULT '0',
+    summary TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+);
+
+CREATE INDEX idx_match_summaries_created_at ON match_summaries(created_at DESC);
@@ -0,0 +1,15 @@
+-- The exact feature vector each prediction was made from, so predictions can
+-- be debugged and models re-evaluated on the inputs they actually saw.
+
+CREATE TABLE prediction_features (
+    prediction_id BLOB PRIMARY KEY,
+    match_id TEXT NOT NULL,
+    model_name TEXT NOT NULL,
+    model_version TEXT NOT NULL,
+    features TEXT NOT NULL,
+    feature_timestamp TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+);
+
+CREATE INDEX idx_prediction_features_match_id ON prediction_features(match_id);
+CREATE INDEX idx_prediction_features_model ON prediction_features(model_name, model_version, feature_timestamp DESC);
@@ -0,0 +1,10 @@
+-- Elo rating per team, updated as results come in, so ratings carry over
+-- between runs instead of starting from scratch.
+
+CREATE TABLE team_ratings (
+    team TEXT PRIMARY KEY,
+    rating REAL NOT NULL,
+    matches_played INTEGER NOT NULL DEFAULT 0,
+    season TEXT,
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+);
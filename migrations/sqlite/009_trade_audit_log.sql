@@ -0,0 +1,31 @@
+-- Every signal, risk decision and execution the trader makes, written once
+-- and never changed. Execution attempts carry an idempotency key; the
+-- unique index means a bet retried after a crash is refused instead of
+-- being placed twice.
+
+CREATE TABLE trade_audit_log (
+    id BLOB PRIMARY KEY,
+    kind TEXT NOT NULL,
+    match_id TEXT NOT NULL,
+    signal_id BLOB,
+    bet_id BLOB,
+    idempotency_key TEXT,
+    entry TEXT NOT NULL,
+    recorded_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_trade_audit_log_match ON trade_audit_log (match_id, recorded_at);
+CREATE UNIQUE INDEX idx_trade_audit_log_execution_key ON trade_audit_log (idempotency_key)
+    WHERE kind = 'execution_attempt';
+
+CREATE TRIGGER trade_audit_log_no_updates
+    BEFORE UPDATE ON trade_audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'trade_audit_log is append-only');
+END;
+
+CREATE TRIGGER trade_audit_log_no_deletes
+    BEFORE DELETE ON trade_audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'trade_audit_log is append-only');
+END;
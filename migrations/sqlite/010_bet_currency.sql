@@ -0,0 +1,6 @@
+-- Bets can be placed at bookmakers quoting different currencies. Stakes and
+-- payouts stay in the bet's own currency; fx_rate is what one unit of it was
+-- worth in the portfolio's base currency when the bet was priced.
+
+ALTER TABLE bets ADD COLUMN currency TEXT NOT NULL DEFAULT 'USD';
+ALTER TABLE bets ADD COLUMN fx_rate TEXT NOT NULL DEFAULT '1' CHECK (CAST(fx_rate AS REAL) > 0);
@@ -0,0 +1,8 @@
+-- Browsing the event log newest first, one page after another: pages are
+-- keyed on (timestamp, id) rather than offsets, and league and team
+-- filters match names case-insensitively.
+
+CREATE INDEX idx_events_listing ON events(timestamp DESC, id DESC) WHERE payload IS NOT NULL;
+CREATE INDEX idx_matches_league_lower ON matches(LOWER(league));
+CREATE INDEX idx_matches_team_home_lower ON matches(LOWER(team_home));
+CREATE INDEX idx_matches_team_away_lower ON matches(LOWER(team_away));
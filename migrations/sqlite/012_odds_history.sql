@@ -0,0 +1,4 @@
+-- Every quote the system sees is kept as a live tick, so a match's line
+-- movement can be read back per bookmaker in time order.
+
+CREATE INDEX idx_odds_history ON odds(match_id, bookmaker, timestamp);
@@ -0,0 +1,4 @@
+-- Fixtures are matches stored ahead of kick-off as 'scheduled', listed by
+-- kick-off time.
+
+CREATE INDEX idx_matches_fixtures ON matches(status, match_date);
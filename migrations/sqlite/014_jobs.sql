@@ -0,0 +1,18 @@
+-- Background jobs, such as settlement retries and retraining, kept so they
+-- are retried on failure and picked up again after a restart.
+
+CREATE TABLE jobs (
+    id BLOB PRIMARY KEY,
+    kind TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL,
+    run_at TEXT NOT NULL,
+    last_error TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+);
+
+CREATE INDEX idx_jobs_unfinished ON jobs(run_at) WHERE status IN ('pending', 'running');
+CREATE INDEX idx_jobs_created ON jobs(created_at DESC);
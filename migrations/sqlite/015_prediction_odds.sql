@@ -0,0 +1,8 @@
+-- The 1X2 prices on offer when each prediction was made, so models can be
+-- evaluated for return as well as accuracy from the stored predictions.
+
+ALTER TABLE predictions ADD COLUMN home_odds TEXT;
+ALTER TABLE predictions ADD COLUMN draw_odds TEXT;
+ALTER TABLE predictions ADD COLUMN away_odds TEXT;
+
+CREATE INDEX idx_predictions_model_timestamp ON predictions(model_name, prediction_timestamp);
@@ -1,6 +1,16 @@
 use config::{Config, ConfigError, Environment, File};
+use quant_api::RateLimitConfig;
+use quant_api_grpc::GrpcConfig;
+use quant_ml::{DixonColesConfig, DriftThresholds, EloConfig, StandingsConfig};
+use quant_models::{Currency, MarketFilter, OddsTolerance, StakingConfig};
+use quant_stream::QueueConfig;
+use quant_services::{
+    AlertRules, BlendMethod, BookmakerProfile, ChannelConfig, ExposureLimits, FixturesConfig, JobsConfig, LatencyBudgetConfig, LeagueTier, NotificationsConfig,
+    OddsRefreshConfig, SnapshotConfig, StakeLimits, StakeSizing,
+};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,8 +20,30 @@ pub struct AppConfig {
     pub server: ServerConfig,
     pub ml: MlConfig,
     pub trading: TradingConfig,
+    pub simulator: SimulatorConfig,
     pub monitoring: MonitoringConfig,
+    pub memory: MemoryConfig,
+    pub retention: RetentionConfig,
+    pub ordering: OrderingConfig,
+    /// Periodic snapshots of the engine state for `--restore`, off unless `snapshot.enabled = true`
+    #[serde(default)]
+    pub snapshot: SnapshotConfig,
+    /// Strong signals and settlements posted to Telegram or Discord, off unless `notifications.enabled = true`
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    /// Time limits on the simulator, prediction and trading stages, off unless `latency_budget.enabled = true`
+    #[serde(default)]
+    pub latency_budget: LatencyBudgetConfig,
+    /// When upcoming matches are priced and predicted ahead of kick-off
+    #[serde(default)]
+    pub fixtures: FixturesConfig,
+    /// How often queued settlement, retraining and odds backfill jobs run and are retried
+    #[serde(default)]
+    pub jobs: JobsConfig,
     pub external_apis: ExternalApiConfig,
+    /// Files the configuration was read from, then `environment`
+    #[serde(skip)]
+    pub sources: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,18 +56,67 @@ pub struct DatabaseConfig {
 pub struct RedisConfig {
     pub url: String,
     pub stream_key: String,
+    pub trade_stream_key: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// Per-client request limits, e.g. `server.rate_limit.requests_per_second = 5`
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// Keys accepted in `X-API-Key`; a client sending one is rate limited on
+    /// its own, any other key counts as its address
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+    /// gRPC endpoints, off unless `server.grpc.enabled = true`
+    #[serde(default)]
+    pub grpc: GrpcConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MlConfig {
+    /// How often the model is retrained from the database and promoted if it
+    /// scores better on a holdout; 0 turns retraining off
     pub model_update_interval_hours: u64,
     pub prediction_confidence_threshold: f64,
+    /// Where `--train` writes model artifacts
+    pub model_dir: String,
+    /// Artifact to load at startup instead of the untrained default weights
+    #[serde(default)]
+    pub model_artifact: Option<String>,
+    /// Artifact run in shadow against the live model, predicting but never traded
+    #[serde(default)]
+    pub challenger_artifact: Option<String>,
+    /// Team rating updates
+    #[serde(default)]
+    pub elo: EloConfig,
+    /// Season length and drop zone the table-pressure features are read against
+    #[serde(default)]
+    pub standings: StandingsConfig,
+    /// Fitting the Poisson model's team strengths at startup
+    #[serde(default)]
+    pub dixon_coles: DixonColesConfig,
+    /// Stored results per team that seed its form and strengths at startup;
+    /// 0 starts every team empty
+    #[serde(default = "default_warm_start_results")]
+    pub warm_start_results: usize,
+    /// Predict matches under way from their score, minute and red cards
+    #[serde(default = "default_in_play_model")]
+    pub in_play_model: bool,
+    /// When live features and predictions count as drifted from the
+    /// installed artifact's training set
+    #[serde(default)]
+    pub drift: DriftThresholds,
+}
+
+fn default_in_play_model() -> bool {
+    true
+}
+
+fn default_warm_start_results() -> usize {
+    10
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,12 +126,201 @@ pub struct TradingConfig {
     pub kelly_multiplier: f64,
     pub min_odds: Decimal,
     pub max_odds: Decimal,
+    pub max_correlated_exposure: f64,
+    /// Per-league and per-team caps on open stake; unset ones use `max_correlated_exposure`
+    #[serde(default)]
+    pub exposure_limits: ExposureLimits,
+    /// Herfindahl index of open exposure (by league, team or bet type) that triggers a concentration alert
+    pub max_concentration: f64,
+    /// Max stake on an exact score, as a fraction of available bankroll
+    pub max_correct_score_stake: f64,
+    pub journal_path: String,
+    /// When the daily loss limit resets, `HH:MM` at `daily_reset_utc_offset`
+    pub daily_reset_time: String,
+    /// e.g. `+01:00`; fixed, so not adjusted for daylight saving
+    pub daily_reset_utc_offset: String,
+    /// When, `HH:MM` UTC, open bets on finished matches are settled each night
+    pub reconciliation_time: String,
+    pub blending: BlendingConfig,
+    #[serde(default)]
+    pub ensemble: EnsembleConfig,
+    /// Presets paper traded side by side, each on a virtual bankroll of its own
+    #[serde(default)]
+    pub strategy_books: StrategyBooksConfig,
+    /// Markets each strategy preset stays out of, e.g. `trading.filters.moderate.max_overround = 0.08`
+    #[serde(default)]
+    pub filters: HashMap<String, MarketFilter>,
+    /// How far prices may move between signal and execution for each
+    /// strategy preset, e.g. `trading.odds_tolerance.moderate.max_ticks = 3`
+    #[serde(default)]
+    pub odds_tolerance: HashMap<String, OddsTolerance>,
+    /// How each strategy preset sizes its bets, fractional Kelly unless set,
+    /// e.g. `trading.staking.moderate = { plan = "flat", amount = "25" }`
+    #[serde(default)]
+    pub staking: HashMap<String, StakingConfig>,
+    /// Place executed trades on Betfair rather than only paper trading them
+    pub live_execution: bool,
+    #[serde(default)]
+    pub betfair: BetfairConfig,
+    /// Commission on net winnings per venue, e.g. `trading.venue_commissions.betfairexchange = "0.05"`
+    #[serde(default)]
+    pub venue_commissions: HashMap<String, Decimal>,
+    /// Stakes each venue accepts, in the base currency, e.g. `trading.venue_stake_limits.betfair.increment = "0.5"`
+    #[serde(default)]
+    pub venue_stake_limits: HashMap<String, StakeLimits>,
+    /// Currency the bankroll is kept and reported in
+    #[serde(default)]
+    pub base_currency: Currency,
+    /// Value of one unit of each other currency in the base currency, e.g. `trading.fx_rates.eur = "1.08"`
+    #[serde(default)]
+    pub fx_rates: HashMap<Currency, Decimal>,
+    /// Bookmakers quoting in another currency than the base, e.g. `trading.bookmaker_currencies.bet365 = "GBP"`
+    #[serde(default)]
+    pub bookmaker_currencies: HashMap<String, Currency>,
+}
+
+/// Shrinking model probabilities toward the market before edges are computed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlendingConfig {
+    /// `none`, `linear` or `logarithmic`
+    pub method: BlendMethod,
+    /// Weight on the model in leagues that have not been calibrated yet
+    pub model_weight: f64,
+    /// Settled predictions a league needs before its weight is calibrated
+    pub min_samples: usize,
+}
+
+/// Several strategies voting on each bet instead of the active one deciding
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnsembleConfig {
+    /// Preset names (`conservative`, `moderate`, `aggressive`); empty turns the ensemble off
+    #[serde(default)]
+    pub strategies: Vec<String>,
+    /// Approvals a bet needs; 0 means a simple majority
+    #[serde(default)]
+    pub min_approvals: usize,
+    /// `mean`, `median` or `min` of the approving strategies' stakes
+    #[serde(default)]
+    pub sizing: StakeSizing,
+}
+
+/// Strategies run concurrently on virtual sub-bankrolls to compare them live
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyBooksConfig {
+    /// Preset names (`conservative`, `moderate`, `aggressive`); empty turns the books off
+    pub strategies: Vec<String>,
+    /// Each book's starting bankroll; `trading.initial_bankroll` when unset
+    pub bankroll: Option<Decimal>,
+}
+
+/// Betfair Exchange account used when `trading.live_execution` is on
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BetfairConfig {
+    pub app_key: String,
+    pub username: String,
+    pub password: String,
+    /// TOML file mapping match ids to Betfair match odds markets
+    pub markets_path: Option<String>,
+    pub keep_alive_minutes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatorConfig {
+    pub margins: MarginConfig,
+    /// Seeds match and price simulation so a run can be repeated; random when unset
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Schedule spec (TOML or JSON) to generate the simulated matches from,
+    /// instead of the three sample fixtures
+    #[serde(default)]
+    pub schedule: Option<String>,
+    /// Line moves between feed events, e.g. `simulator.odds_refresh.interval_seconds = 10`
+    #[serde(default)]
+    pub odds_refresh: OddsRefreshConfig,
+    /// Books quoting the match winner market, each with its own margin,
+    /// latency and bias; the four built-in ones when empty
+    #[serde(default)]
+    pub bookmakers: Vec<BookmakerProfile>,
+}
+
+/// Overround of the simulated books
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarginConfig {
+    /// Match winner margin at kick-off per league tier
+    pub top: f64,
+    pub second: f64,
+    pub lower: f64,
+    /// Extra margin, relative to the kick-off margin, on prices `tightening_hours` or more before kick-off
+    pub early_premium: f64,
+    pub tightening_hours: f64,
+    /// Tier per league, on top of the built-in ones, e.g. `simulator.margins.league_tiers.eredivisie = "top"`
+    #[serde(default)]
+    pub league_tiers: HashMap<String, LeagueTier>,
+    /// Margin relative to match winner per market, e.g. `simulator.margins.market_multipliers.asian_handicap = 0.6`
+    #[serde(default)]
+    pub market_multipliers: HashMap<String, f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MonitoringConfig {
     pub metrics_port: u16,
     pub health_check_interval_seconds: u64,
+    /// Database and Redis calls taking at least this long are logged as slow queries
+    pub slow_query_ms: u64,
+    /// When to alert; checked every `health_check_interval_seconds`
+    #[serde(default)]
+    pub alerts: AlertRules,
+    /// Where alerts are sent, besides the `alerts` log target
+    #[serde(default)]
+    pub channels: Vec<ChannelConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryConfig {
+    pub events_budget_mb: f64,
+    pub predictions_budget_mb: f64,
+    pub odds_budget_mb: f64,
+    pub contexts_budget_mb: f64,
+    pub latency_budget_mb: f64,
+}
+
+/// Pruning of raw data once it is past its retention window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    /// Rows deleted per transaction
+    pub batch_size: i64,
+    /// Days to keep per table, e.g. `retention.tables.odds_ticks = 30`; 0 or absent keeps rows forever
+    #[serde(default)]
+    pub tables: HashMap<String, u32>,
+    /// Days to keep per Redis stream, keyed by stream key
+    #[serde(default)]
+    pub streams: HashMap<String, u32>,
+    /// Write pruned rows here as JSON Lines before deleting them
+    #[serde(default)]
+    pub archive_dir: Option<String>,
+    pub compress_archives: bool,
+    /// Where `--archive-season` writes completed seasons
+    pub season_archive_dir: String,
+}
+
+/// Deduplication and reordering of feed events before they are processed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderingConfig {
+    /// How long an out-of-order event waits for the ones before it
+    pub reorder_window_ms: u64,
+    /// Event ids remembered for dropping redeliveries
+    pub dedup_cache_size: usize,
+    pub max_buffered_per_match: usize,
+    /// Workers processing events in parallel; each match stays on one worker
+    pub event_workers: usize,
+    /// Size and overflow policy of the queue between the feed and the
+    /// workers, e.g. `ordering.queue.overflow = "drop_oldest"`
+    #[serde(default)]
+    pub queue: QueueConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,36 +333,80 @@ impl AppConfig {
     pub fn new() -> Result<Self, ConfigError> {
         let run_mode = env::var("RUN_MODE").unwrap_or_else(|_| "development".into());
 
+        let files = ["config/default".to_string(), format!("config/{}", run_mode), "config/local".to_string()];
+
         let config = Config::builder()
             // Start with default values
             .set_default("database.url", "postgresql://localhost:5432/qtdev")?
             .set_default("database.max_connections", 20)?
             .set_default("redis.url", "redis://localhost:6379")?
             .set_default("redis.stream_key", "sports_events")?
+            .set_default("redis.trade_stream_key", "trade_events")?
             .set_default("server.host", "0.0.0.0")?
             .set_default("server.port", 8080)?
             .set_default("ml.model_update_interval_hours", 24)?
             .set_default("ml.prediction_confidence_threshold", 0.7)?
+            .set_default("ml.model_dir", "data/models")?
             .set_default("trading.initial_bankroll", "10000.00")?
             .set_default("trading.max_stake_percent", 0.05)?
             .set_default("trading.kelly_multiplier", 0.5)?
             .set_default("trading.min_odds", "1.20")?
             .set_default("trading.max_odds", "10.00")?
+            .set_default("trading.max_correlated_exposure", 0.15)?
+            .set_default("trading.max_concentration", 0.5)?
+            .set_default("trading.max_correct_score_stake", 0.005)?
+            .set_default("trading.journal_path", "data/trade_journal.log")?
+            .set_default("trading.daily_reset_time", "00:00")?
+            .set_default("trading.daily_reset_utc_offset", "+00:00")?
+            .set_default("trading.reconciliation_time", "03:00")?
+            .set_default("trading.blending.method", "none")?
+            .set_default("trading.blending.model_weight", 0.5)?
+            .set_default("trading.blending.min_samples", 50)?
+            .set_default("trading.venue_commissions.betfairexchange", "0.05")?
+            .set_default("trading.live_execution", false)?
+            .set_default("trading.betfair.keep_alive_minutes", 15)?
+            .set_default("simulator.margins.top", 0.04)?
+            .set_default("simulator.margins.second", 0.06)?
+            .set_default("simulator.margins.lower", 0.08)?
+            .set_default("simulator.margins.early_premium", 0.5)?
+            .set_default("simulator.margins.tightening_hours", 48.0)?
             .set_default("monitoring.metrics_port", 9090)?
             .set_default("monitoring.health_check_interval_seconds", 30)?
+            .set_default("monitoring.slow_query_ms", 100)?
+            .set_default("memory.events_budget_mb", 8.0)?
+            .set_default("memory.predictions_budget_mb", 4.0)?
+            .set_default("memory.odds_budget_mb", 2.0)?
+            .set_default("memory.contexts_budget_mb", 2.0)?
+            .set_default("memory.latency_budget_mb", 1.0)?
+            .set_default("retention.enabled", true)?
+            .set_default("retention.interval_minutes", 60)?
+            .set_default("retention.batch_size", 5000)?
+            .set_default("retention.tables.events", 90)?
+            .set_default("retention.tables.odds_ticks", 30)?
+            .set_default("retention.tables.published_outbox", 7)?
+            .set_default("retention.streams.trade_events", 90)?
+            .set_default("retention.compress_archives", true)?
+            .set_default("retention.season_archive_dir", "data/archive/seasons")?
+            .set_default("ordering.reorder_window_ms", 500)?
+            .set_default("ordering.dedup_cache_size", 10000)?
+            .set_default("ordering.max_buffered_per_match", 256)?
+            .set_default("ordering.event_workers", 4)?
             .set_default(
                 "external_apis.sports_api_base_url",
                 "https://api.sportsdataapi.com",
             )?
             // Add in settings from configuration file
-            .add_source(File::with_name("config/default").required(false))
-            .add_source(File::with_name(&format!("config/{}", run_mode)).required(false))
-            .add_source(File::with_name("config/local").required(false))
+            .add_source(File::with_name(&files[0]).required(false))
+            .add_source(File::with_name(&files[1]).required(false))
+            .add_source(File::with_name(&files[2]).required(false))
             // Add in settings from environment variables
             .add_source(Environment::new().separator("_"))
             .build()?;
 
-        config.try_deserialize()
+        let mut config: Self = config.try_deserialize()?;
+        config.sources = files.iter().filter_map(|name| found_config_file(name)).collect();
+        config.sources.push("environment".to_string());
+        Ok(config)
     }
 
     pub fn database_url(&self) -> &str {
@@ -106,4 +420,15 @@ impl AppConfig {
     pub fn server_addr(&self) -> String {
         format!("{}:{}", self.server.host, self.server.port)
     }
+
+    pub fn grpc_addr(&self) -> String {
+        format!("{}:{}", self.server.host, self.server.grpc.port)
+    }
+}
+
+/// The file `File::with_name(name)` reads, if there is one
+fn found_config_file(name: &str) -> Option<String> {
+    ["toml", "json", "yaml", "yml", "ini", "ron", "json5"].iter()
+        .map(|extension| format!("{}.{}", name, extension))
+        .find(|path| std::path::Path::new(path).is_file())
 }
@@ -1,18 +1,44 @@
 mod config;
+mod profiler;
 
 use anyhow::Result;
+use chrono::{Duration, Utc};
 use config::AppConfig;
-use quant_models::MatchEvent;
-use quant_services::{DataFeedService, DataFeedConfig, PredictorService, TradingEngine, MarketSimulator, MetricsCollector};
-use quant_api::{create_routes, AppState};
+use quant_models::{BettingStrategy, EventType, FixedRates, MarketFilter, MatchEvent, MatchStatus, Outcome1X2, Prediction, QueryLog, RateProvider, StakingConfig};
+use quant_services::{
+    DataFeedService, DataFeedConfig, PredictorService, OddsRefresher, TradingEngine, MarketSimulator, MetricsCollector,
+    MemoryBudgets, CacheKind, OutboxRelay, PredictionLedger, persist_executed_bet, persist_settled_bets, persist_trading_signal, HistoricalImporter,
+    ModelTrainingJob, ModelRetrainer, OddsHistory, PredictionHistory, ProbabilityBlender, MatchSummaryTracker, MarginModel, LeagueTier, MARKET_FEED_BOOKMAKER, MIN_EXECUTION_STRENGTH,
+    FeatureStore, SearchIndex, RetentionJob, RetentionPolicy, SeasonArchiver, Scenario, ScheduleSpec, MatchStateMachine,
+    MatchCorrection, StateRebuilder, EventReplayer, EventLog, StrategyEnsemble, BetfairExchange, BetfairCredentials, ExecutionVenue, load_betfair_markets,
+    DailyResetSchedule, SettlementReconciler, EngineSnapshot, StateSnapshotter, MonitorService, Notifier, LatencyBudget, FixtureService, Fixture, JobQueue, SettleMatchHandler, SettleMatchTask, BackfillOddsHandler,
+    SETTLE_MATCH_JOB, RETRAIN_MODEL_JOB, BACKFILL_ODDS_JOB, PipelineStage, RepositoryAuditLog, ModelProvenance, Provenance, git_commit, sha256_hex,
+    Backtester, ParameterGrid, SweepRanking, WalkForwardConfig, SettlementResult, TradingSignal,
+};
+use quant_stream::{event_queue, spawn_ordering, spawn_sharding, DomainEventBus, EventBus, OrderingConfig, OverflowPolicy, RedisSpill, RedisStream};
+use quant_api::{authenticate, create_routes, rate_limit, AppState, AuthMiddleware, RateLimiter};
+use quant_api_grpc::{serve_grpc, QuantGrpc};
 use rust_decimal_macros::dec;
 use tower_http::cors::CorsLayer;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::sync::RwLock;
-use tokio::sync::mpsc;
-use tracing::{info, warn, error};
+use tokio_util::sync::CancellationToken;
+use quant_db::{
+    BetRecord, BetRepository, EventRecord, EventRepository, FeatureStoreRepository, MatchRecord, MatchRepository,
+    MatchSummaryRecord, MatchSummaryRepository, PredictionFeaturesRecord, Repository, RetentionTarget,
+    TeamRatingRecord, TeamRatingRepository, TrainingDataRepository, TrainingExampleRecord,
+};
+use quant_ml::{DixonColesFitter, EloRatings, FeatureEngineer, LeagueTables, ModelArtifact, ModelFeedback};
+use tracing::{debug, info, warn, error};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+const SHUTDOWN_DRAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+const DATABASE_CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+/// Settled predictions older than this no longer count toward blend weights
+const BLEND_CALIBRATION_DAYS: i64 = 180;
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Load environment variables
@@ -27,6 +53,11 @@ async fn main() -> Result<()> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    // Profiling mode: run synthetic events through the hot path and exit
+    if let Some(event_count) = profiler::profile_run_arg() {
+        return profiler::run(event_count).await;
+    }
+
     info!("🚀 Starting Quant-RS Sports Betting Prediction System");
 
     // Load configuration
@@ -36,8 +67,66 @@ async fn main() -> Result<()> {
     info!("🔄 Redis: {}", config.redis_url());
     info!("🌐 Server will bind to: {}", config.server_addr());
 
-    // Create event channel for internal communication
-    let (event_sender, mut event_receiver) = mpsc::unbounded_channel::<MatchEvent>();
+    // Import mode: load historical results into the database and exit
+    if let Some(files) = import_files_arg() {
+        return run_import(&config, &files).await;
+    }
+
+    // Archive mode: move a finished season to cold storage and exit
+    if let Some(season) = flag_value("--archive-season") {
+        return run_season_archive(&config, &season).await;
+    }
+
+    // Restore mode: load an archived season back into the database and exit
+    if let Some(dir) = flag_value("--restore-season") {
+        return run_season_restore(&config, &dir).await;
+    }
+
+    // Transfer mode: train on some leagues, score on others and exit
+    if std::env::args().any(|arg| arg == "--evaluate-transfer") {
+        return run_transfer_evaluation(&config).await;
+    }
+
+    // Sweep mode: backtest a grid of strategy parameters on held-out matches and exit
+    if std::env::args().any(|arg| arg == "--backtest-sweep") {
+        return run_backtest_sweep(&config).await;
+    }
+
+    // Walk-forward mode: retrain and score window by window, trading each out of sample, and exit
+    if std::env::args().any(|arg| arg == "--walk-forward") {
+        return run_walk_forward(&config).await;
+    }
+
+    // Training mode: fit the model on labeled examples, write the artifact and exit
+    if std::env::args().any(|arg| arg == "--train") {
+        return run_training(&config).await;
+    }
+
+    // Cancelled on Ctrl+C; every long-running task watches it and winds down
+    let shutdown = CancellationToken::new();
+
+    // Bounded queue between the feed and processing, so a slow processor
+    // can't grow memory without limit
+    let spill = if config.ordering.queue.overflow == OverflowPolicy::Spill {
+        match RedisSpill::connect(config.redis_url(), &config.ordering.queue.spill_key).await {
+            Ok(spill) => Some(spill),
+            Err(e) => {
+                warn!("⚠️ Event queue can't spill to Redis: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let (event_sender, raw_events) = event_queue(&config.ordering.queue, spill);
+    let event_queue_monitor = raw_events.monitor();
+    // Duplicate or out-of-order events would corrupt match state, so they
+    // are dropped or put back in sequence before anything sees them
+    let event_receiver = spawn_ordering(raw_events, OrderingConfig {
+        reorder_window: std::time::Duration::from_millis(config.ordering.reorder_window_ms),
+        dedup_capacity: config.ordering.dedup_cache_size,
+        max_buffered_per_match: config.ordering.max_buffered_per_match,
+    });
     
     // Initialize data feed service
     let feed_config = DataFeedConfig {
@@ -47,11 +136,29 @@ async fn main() -> Result<()> {
         simulation_speed_multiplier: 1.0,
     };
     
-    let data_feed = DataFeedService::new(event_sender, Some(feed_config));
+    // One seed drives match and price simulation, so a logged run can be replayed
+    let simulation_seed = config.simulator.seed.unwrap_or_else(random_seed);
+    
+    // The feed and the odds refresher hold the only senders, so the channel
+    // closes once both stop
+    let odds_refresh_events = config.simulator.odds_refresh.enabled.then(|| event_sender.clone());
+    let data_feed = DataFeedService::new(event_sender, Some(feed_config))
+        .with_seed(simulation_seed)
+        .with_shutdown(shutdown.child_token());
+    let feed_control = data_feed.control();
+    // Scenario mode: play a scripted set of matches instead of the sample fixtures.
+    // Those with a kick-off ahead are listed as fixtures until then.
+    let mut simulated_fixtures = Vec::new();
+    if let Some(path) = flag_value("--scenario") {
+        let scenario = Scenario::load(&path)?;
+        feed_control.load_scenario(&scenario)?;
+        simulated_fixtures = scenario.matches;
+    } else if let Some(path) = flag_value("--schedule").or_else(|| config.simulator.schedule.clone()) {
+        simulated_fixtures = feed_control.load_schedule(&ScheduleSpec::load(&path)?, simulation_seed)?;
+    }
     
     // Start data feed service in background
     let feed_handle = {
-        let data_feed = data_feed.clone();
         tokio::spawn(async move {
             if let Err(e) = data_feed.start().await {
                 error!("❌ Data feed service error: {}", e);
@@ -59,24 +166,449 @@ async fn main() -> Result<()> {
         })
     };
     
+    // Bets, settlements, risk breaches and model promotions, published once
+    // for the audit log and websocket subscribers alike
+    let domain_events = DomainEventBus::new();
+    // Processed events, predictions, signals and settlements for whichever modules subscribe
+    let event_bus = EventBus::new();
+    // Subscribed now so nothing is missed before the monitor and notifier start
+    let monitor_feed = domain_events.subscribe();
+    let notifications = &config.notifications;
+    let notifier_feed = (notifications.enabled && !notifications.targets.is_empty())
+        .then(|| domain_events.subscribe());
+
+    // Resume from the last engine snapshot when started with `--restore [FILE]`
+    let restored = if std::env::args().any(|arg| arg == "--restore") {
+        let path = flag_value("--restore").unwrap_or_else(|| config.snapshot.path.clone());
+        let snapshot = EngineSnapshot::load(&path)?;
+        info!("💾 Restoring engine state saved at {} from {}", snapshot.taken_at, path);
+        Some(snapshot)
+    } else {
+        None
+    };
+    let (restored_trading, restored_features, restored_model) = match restored {
+        Some(snapshot) => (Some(snapshot.trading), Some(snapshot.features), Some(snapshot.model)),
+        None => (None, None, None),
+    };
+
     // Initialize prediction service
-    let predictor = Arc::new(PredictorService::new());
+    let elo_ratings = Arc::new(EloRatings::with_config(config.ml.elo.clone()));
+    let feature_engineer = FeatureEngineer::new()
+        .with_elo_ratings(elo_ratings.clone())
+        .with_standings(Arc::new(LeagueTables::with_config(config.ml.standings.clone())));
+    let mut predictor = PredictorService::new()
+        .with_feature_engineer(feature_engineer)
+        .with_event_bus(domain_events.clone())
+        .with_in_play(config.ml.in_play_model)
+        .with_drift_thresholds(config.ml.drift.clone());
+    #[cfg(feature = "batched-inference")]
+    {
+        predictor = predictor.with_batched_inference(quant_ml::BatchInferenceConfig::default())?;
+    }
+    // Shadow mode: the challenger predicts alongside but is never traded
+    if let Some(path) = &config.ml.challenger_artifact {
+        let artifact = ModelArtifact::load(path)?;
+        info!("🥊 Shadowing {} {} as challenger", artifact.name, artifact.version);
+        predictor = predictor.with_challenger_artifact(&artifact)?;
+    }
+    let predictor = Arc::new(predictor);
+    let mut artifact_sha256 = None;
+    if let Some(path) = &config.ml.model_artifact {
+        predictor.install_artifact(&ModelArtifact::load(path)?).await?;
+        artifact_sha256 = Some(sha256_hex(&std::fs::read(path)?));
+    }
     
-    // Initialize trading engine with $10,000 starting bankroll
-    let trading_engine = Arc::new(TradingEngine::new(dec!(10000.0)));
+    // Initialize metrics collector
+    let metrics_collector = MetricsCollector::new()
+        .with_memory_budgets(MemoryBudgets::from_megabytes(
+            config.memory.events_budget_mb,
+            config.memory.predictions_budget_mb,
+            config.memory.odds_budget_mb,
+            config.memory.contexts_budget_mb,
+            config.memory.latency_budget_mb,
+        ))
+        .with_slow_query_threshold(std::time::Duration::from_millis(config.monitoring.slow_query_ms))
+        .with_event_queue(event_queue_monitor)
+        .with_prediction_cache(predictor.prediction_cache());
+    #[cfg(feature = "batched-inference")]
+    let metrics_collector = metrics_collector.with_inference(predictor.clone());
+    let metrics_collector = Arc::new(metrics_collector);
+    let memory = metrics_collector.memory_accountant();
     
-    // Initialize market simulator
-    let market_simulator = Arc::new(MarketSimulator::new());
+    // Optional persistence: without a database the system runs purely in memory
+    let repository = connect_repository(&config, metrics_collector.query_log()).await;
+    // Subscribed before trading starts, so every settlement reaches the bets table
+    let settlement_feed = repository.as_ref().map(|_| domain_events.subscribe());
     
-    // Initialize metrics collector
-    let metrics_collector = Arc::new(MetricsCollector::new());
+    // Pick team ratings up where the last run left them
+    if let Some(repository) = &repository {
+        match repository.get_team_ratings().await {
+            Ok(records) => {
+                elo_ratings.load(records.iter().map(TeamRatingRecord::to_team_rating));
+                info!("📈 Loaded Elo ratings for {} teams", elo_ratings.len());
+            }
+            Err(e) => warn!("📈 Failed to load Elo ratings: {}", e),
+        }
+        if config.ml.warm_start_results > 0 {
+            match ModelTrainingJob::new().recent_results(repository).await {
+                Ok(results) => predictor.warm_start(&results, config.ml.warm_start_results),
+                Err(e) => warn!("📈 Not warming up team form: {}", e),
+            }
+        }
+
+        let fitter = DixonColesFitter::with_config(config.ml.dixon_coles.clone());
+        match ModelTrainingJob::new().with_fitter(fitter).fit_team_strengths(repository).await {
+            Ok(fit) => predictor.install_dixon_coles(fit).await,
+            Err(e) => warn!("🧠 Not fitting team strengths: {}", e),
+        }
+    }
+
+    // The snapshot is newer than anything stored, so it wins over the database
+    if let Some(features) = restored_features {
+        info!("💾 Restored {} team stats and {} live match contexts",
+              features.team_stats.len(), features.match_contexts.len());
+        predictor.get_feature_engineer().restore_state(features);
+    }
+    if let Some(model) = &restored_model {
+        if let Err(e) = predictor.restore_model_state(model).await {
+            warn!("💾 Not restoring learned model weights: {}", e);
+        }
+    }
+    
+    // Bets at bookmakers quoting other currencies are converted into the base currency
+    let trading = &config.trading;
+    let fx_rates = trading.fx_rates.iter()
+        .fold(FixedRates::new(trading.base_currency), |rates, (currency, rate)| rates.with_rate(*currency, *rate));
+    for (bookmaker, currency) in &trading.bookmaker_currencies {
+        if fx_rates.rate(*currency, trading.base_currency).is_none() {
+            anyhow::bail!("bookmaker '{}' quotes in {} but trading.fx_rates has no {} rate", bookmaker, currency, currency);
+        }
+    }
+
+    // Initialize trading engine with a 10,000 starting bankroll, replaying the
+    // trade journal on top of any restored state
+    let daily_reset = DailyResetSchedule::parse(&config.trading.daily_reset_time, &config.trading.daily_reset_utc_offset)?;
+    let mut trading_engine = TradingEngine::new(dec!(10000.0))
+        .with_currency(trading.base_currency, Arc::new(fx_rates))
+        .with_bookmaker_currencies(trading.bookmaker_currencies.clone())
+        .with_event_bus(domain_events.clone())
+        .with_daily_reset(daily_reset);
+    if let Some(state) = restored_trading {
+        trading_engine = trading_engine.with_restored_state(state)?;
+    }
+    let mut trading_engine = trading_engine
+        .with_journal(&config.trading.journal_path)?
+        .with_max_correlated_exposure(config.trading.max_correlated_exposure)
+        .with_exposure_limits(config.trading.exposure_limits.clone())
+        .with_max_concentration(config.trading.max_concentration)
+        .with_max_correct_score_stake(config.trading.max_correct_score_stake)
+        .with_venue_commissions(config.trading.venue_commissions.clone())
+        .with_venue_stake_limits(config.trading.venue_stake_limits.clone())
+        .with_blender(
+            ProbabilityBlender::new()
+                .with_method(config.trading.blending.method)
+                .with_model_weight(config.trading.blending.model_weight)
+                .with_min_samples(config.trading.blending.min_samples)
+        );
+    for (preset, filter) in &config.trading.filters {
+        if BettingStrategy::preset(preset).is_none() {
+            anyhow::bail!("unknown strategy '{}' in trading.filters", preset);
+        }
+        trading_engine = trading_engine.with_market_filter(preset, filter.clone());
+    }
+    for (preset, tolerance) in &config.trading.odds_tolerance {
+        if BettingStrategy::preset(preset).is_none() {
+            anyhow::bail!("unknown strategy '{}' in trading.odds_tolerance", preset);
+        }
+        trading_engine = trading_engine.with_odds_tolerance(preset, *tolerance);
+    }
+    for (preset, staking) in &config.trading.staking {
+        let Some(strategy) = BettingStrategy::preset(preset) else {
+            anyhow::bail!("unknown strategy '{}' in trading.staking", preset);
+        };
+        let plan = staking.plan(strategy.kelly_multiplier);
+        info!("💰 {} stakes with the {} plan", preset, plan.name());
+        trading_engine = trading_engine.with_staking_plan(preset, plan);
+    }
+    if let Some(ensemble) = strategy_ensemble(&config.trading.ensemble, &config.trading.filters, &config.trading.staking)? {
+        info!("🗳️ {} strategies vote on each bet, {} approvals needed",
+              ensemble.strategies().len(), ensemble.min_approvals());
+        trading_engine = trading_engine.with_strategy_ensemble(ensemble);
+    }
+    let books = &config.trading.strategy_books;
+    if let Some(unknown) = books.strategies.iter().find(|preset| BettingStrategy::preset(preset).is_none()) {
+        anyhow::bail!("unknown strategy '{}' in trading.strategy_books.strategies", unknown);
+    }
+    if !books.strategies.is_empty() {
+        let bankroll = books.bankroll.unwrap_or(config.trading.initial_bankroll);
+        info!("📒 Paper trading {} side by side, {} each", books.strategies.join(", "), bankroll);
+        trading_engine = trading_engine.with_strategy_books(&books.strategies, bankroll);
+    }
+    if config.trading.live_execution {
+        let betfair = Arc::new(betfair_exchange(&config.trading.betfair)?);
+        betfair.login().await?;
+        warn!("🏦 Live execution: trades are placed on Betfair with real money");
+
+        let keep_alive = betfair.clone();
+        let every = config.trading.betfair.keep_alive_minutes.max(1) * 60;
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(every));
+            interval.tick().await;
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = interval.tick() => {
+                        if let Err(e) = keep_alive.keep_alive().await {
+                            error!("🏦 Betfair session could not be kept alive: {}", e);
+                        }
+                    }
+                }
+            }
+        });
+        trading_engine = trading_engine.with_execution_venue(betfair);
+    }
+    if let Some(repository) = &repository {
+        trading_engine = trading_engine.with_audit_log(Arc::new(RepositoryAuditLog::new(repository.clone())));
+    }
+    let trading_engine = Arc::new(trading_engine);
+
+    // Save the engine state on an interval for a later `--restore`
+    let snapshotter = config.snapshot.enabled.then(|| {
+        Arc::new(StateSnapshotter::new(trading_engine.clone(), predictor.clone(), &config.snapshot.path)
+            .with_interval(std::time::Duration::from_secs(config.snapshot.interval_seconds)))
+    });
+    if let Some(snapshotter) = snapshotter.clone() {
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move { snapshotter.run(shutdown).await });
+    }
+
+    // Start each trading day with a fresh daily loss limit
+    {
+        let trading_engine = trading_engine.clone();
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            loop {
+                let until_reset = (daily_reset.next_reset(Utc::now()) - Utc::now())
+                    .to_std()
+                    .unwrap_or_default();
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = tokio::time::sleep(until_reset) => trading_engine.reset_daily_loss().await,
+                }
+            }
+        });
+    }
+    
+    // Initialize market simulator, with margins by league tier, market and time to kick-off
+    let margins = &config.simulator.margins;
+    let margin_model = MarginModel::new()
+        .with_tier_margin(LeagueTier::Top, margins.top)
+        .with_tier_margin(LeagueTier::Second, margins.second)
+        .with_tier_margin(LeagueTier::Lower, margins.lower)
+        .with_kickoff_tightening(margins.early_premium, margins.tightening_hours);
+    let margin_model = margins.league_tiers.iter()
+        .fold(margin_model, |model, (league, tier)| model.with_league_tier(league, *tier));
+    let margin_model = margins.market_multipliers.iter()
+        .fold(margin_model, |model, (market, multiplier)| model.with_market_multiplier(market, *multiplier));
+    let mut market_simulator = MarketSimulator::new().with_seed(simulation_seed).with_margin_model(margin_model);
+    if !config.simulator.bookmakers.is_empty() {
+        market_simulator = market_simulator.with_bookmakers(config.simulator.bookmakers.clone());
+    }
+    let market_simulator = Arc::new(market_simulator);
+
+    // Drift open markets toward fair value between feed events; the moves
+    // come through the pipeline as odds updates
+    if let Some(events) = odds_refresh_events {
+        let refresher = OddsRefresher::new(market_simulator.clone(), events)
+            .with_interval(std::time::Duration::from_secs(config.simulator.odds_refresh.interval_seconds));
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move { refresher.run(shutdown).await });
+    }
+
+    // Upcoming matches, stored and from the feed, priced and predicted before kick-off
+    let mut fixture_service = FixtureService::new(predictor.clone(), market_simulator.clone())
+        .with_pre_match_window(chrono::Duration::hours(config.fixtures.pre_match_hours as i64));
+    if let Some(repository) = &repository {
+        fixture_service = fixture_service.with_repository(repository.clone());
+    }
+    let fixture_service = Arc::new(fixture_service);
+    match fixture_service.load_stored(chrono::Utc::now()).await {
+        Ok(0) => {}
+        Ok(stored) => info!("🗓️ Loaded {} stored fixtures", stored),
+        Err(e) => warn!("🗓️ Failed to load stored fixtures: {}", e),
+    }
+    fixture_service.add(simulated_fixtures.iter().filter_map(Fixture::from_simulated).collect()).await;
+    {
+        let fixture_service = fixture_service.clone();
+        let interval = std::time::Duration::from_secs(config.fixtures.check_interval_seconds);
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move { fixture_service.run(interval, shutdown).await });
+    }
+    
+    // Keep keyed caches within their memory budgets
+    {
+        let memory = memory.clone();
+        let market_simulator = market_simulator.clone();
+        let feature_engineer = predictor.get_feature_engineer();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(10));
+            loop {
+                interval.tick().await;
+                market_simulator.enforce_memory_budget(&memory).await;
+                memory.enforce_context_budget(&feature_engineer);
+            }
+        });
+    }
     
     // Start metrics collection
     metrics_collector.start_periodic_collection().await;
     
+    // Audit domain events and alert on error spikes, drawdowns, a silent feed or a degrading model
+    {
+        let monitor = config.monitoring.channels.iter()
+            .fold(MonitorService::new("monitor".to_string()), |monitor, channel| monitor.with_channel(channel.build()))
+            .with_alert_rules(config.monitoring.alerts.clone())
+            .with_health_checks(
+                metrics_collector.clone(),
+                predictor.clone(),
+                std::time::Duration::from_secs(config.monitoring.health_check_interval_seconds),
+            );
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move { monitor.run(monitor_feed, shutdown).await });
+    }
+    
+    // Relay committed outbox messages to Redis; stopped only after the final bet flush
+    let relay_shutdown = CancellationToken::new();
+    let relay_handle = match &repository {
+        Some(repository) => match RedisStream::new(config.redis_url()).await {
+            Ok(stream) => {
+                let stream = stream.with_query_log(metrics_collector.query_log());
+                let relay = OutboxRelay::new(repository.clone(), Arc::new(stream));
+                let relay_shutdown = relay_shutdown.clone();
+                Some(tokio::spawn(async move { relay.run(relay_shutdown).await }))
+            }
+            Err(e) => {
+                warn!("📤 Redis unavailable, outbox messages will wait in the database: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    // Settled, half-settled and voided bets are written back to their rows
+    let settlement_handle = match (&repository, settlement_feed) {
+        (Some(repository), Some(feed)) => {
+            Some(tokio::spawn(persist_settled_bets(repository.clone(), feed, relay_shutdown.clone())))
+        }
+        _ => None,
+    };
+    
+    // Nightly, settle bets whose settlement event never arrived
+    if let Some(repository) = &repository {
+        let run_at = chrono::NaiveTime::parse_from_str(&config.trading.reconciliation_time, "%H:%M")
+            .map_err(|_| anyhow::anyhow!("invalid trading.reconciliation_time '{}', expected HH:MM", config.trading.reconciliation_time))?;
+        let reconciler = SettlementReconciler::new(repository.clone(), trading_engine.clone())
+            .with_metrics(metrics_collector.clone())
+            .with_event_bus(event_bus.clone())
+            .with_run_at(run_at);
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move { reconciler.run(shutdown).await });
+    }
+
+    // Settlement retries, retraining and odds backfill, retried until they succeed
+    let mut job_queue = JobQueue::new(config.jobs.clone());
+    if let Some(repository) = &repository {
+        job_queue = job_queue.with_repository(repository.clone());
+    }
+    let job_queue = Arc::new(job_queue);
+    job_queue.register(SETTLE_MATCH_JOB, Arc::new(SettleMatchHandler::new(trading_engine.clone())));
+    if let Some(repository) = &repository {
+        job_queue.register(BACKFILL_ODDS_JOB, Arc::new(BackfillOddsHandler::new(repository.clone())));
+    }
+
+    // Retrain on settled matches and promote the new model only if it beats the live one
+    if let Some(repository) = repository.as_ref().filter(|_| config.ml.model_update_interval_hours > 0) {
+        let retrainer = Arc::new(
+            ModelRetrainer::new(repository.clone(), predictor.clone(), &config.ml.model_dir)
+                .with_interval(std::time::Duration::from_secs(config.ml.model_update_interval_hours * 3600))
+                .with_jobs(job_queue.clone())
+        );
+        job_queue.register(RETRAIN_MODEL_JOB, retrainer.clone());
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move { retrainer.run(shutdown).await });
+    }
+
+    match job_queue.load_stored().await {
+        Ok(0) => {}
+        Ok(stored) => info!("🛠️ Resuming {} unfinished jobs", stored),
+        Err(e) => warn!("🛠️ Failed to load stored jobs: {}", e),
+    }
+    {
+        let job_queue = job_queue.clone();
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move { job_queue.run(shutdown).await });
+    }
+
+    // Prune raw data past its retention window until shutdown
+    if let Some(repository) = repository.as_ref().filter(|_| config.retention.enabled) {
+        let job = retention_job(&config, repository.clone(), metrics_collector.clone()).await;
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move { job.run(shutdown).await });
+    }
+    
     // Storage for API endpoints
     let recent_events = Arc::new(RwLock::new(Vec::<MatchEvent>::new()));
     let recent_predictions = Arc::new(RwLock::new(Vec::new()));
+    let prediction_history = Arc::new(PredictionHistory::new());
+    let prediction_ledger = Arc::new(match &repository {
+        Some(repository) => PredictionLedger::new().with_repository(repository.clone()),
+        None => PredictionLedger::new(),
+    });
+    let odds_history = Arc::new(match &repository {
+        Some(repository) => OddsHistory::new().with_repository(repository.clone()).with_jobs(job_queue.clone()),
+        None => OddsHistory::new(),
+    });
+    let match_summaries = Arc::new(MatchSummaryTracker::new());
+    let feature_store = Arc::new(FeatureStore::new());
+    let search_index = Arc::new(SearchIndex::new());
+    let match_states = Arc::new(MatchStateMachine::new());
+
+    // Rebuilds derived state from the stored event log on request
+    let mut rebuilder = StateRebuilder::new(
+        predictor.get_feature_engineer(),
+        match_states.clone(),
+        match_summaries.clone(),
+    ).with_feed_control(feed_control.clone());
+    let mut replayer = EventReplayer::new(predictor.clone(), odds_history.clone());
+    let mut event_log = EventLog::new();
+    if let Some(repository) = &repository {
+        rebuilder = rebuilder.with_repository(repository.clone());
+        replayer = replayer.with_repository(repository.clone());
+        event_log = event_log.with_repository(repository.clone());
+    }
+    
+    // What this run was started from, logged once and served with the system status
+    let (model_name, model_version) = predictor.active_model().await;
+    let migration_level = match &repository {
+        Some(repository) => repository.migration_level().await.unwrap_or_else(|e| {
+            warn!("⚠️ Could not read the migration level: {}", e);
+            None
+        }),
+        None => None,
+    };
+    let provenance = Provenance::new(env!("CARGO_PKG_VERSION"))
+        .with_git_commit(std::env::var("GIT_COMMIT").ok().or_else(|| git_commit(std::path::Path::new("."))))
+        .with_config_sources(config.sources.clone())
+        .with_model(ModelProvenance {
+            name: model_name,
+            version: model_version,
+            artifact: config.ml.model_artifact.clone(),
+            sha256: artifact_sha256,
+        })
+        .with_feature_schema(predictor.get_feature_engineer().pipeline().schema().id())
+        .with_migration_level(migration_level)
+        .with_simulation_seed(simulation_seed);
+    provenance.log();
     
     // Create API state
     let api_state = AppState {
@@ -85,43 +617,150 @@ async fn main() -> Result<()> {
         predictor: predictor.clone(),
         recent_events: recent_events.clone(),
         recent_predictions: recent_predictions.clone(),
+        prediction_history: prediction_history.clone(),
+        prediction_ledger: prediction_ledger.clone(),
+        odds_history: odds_history.clone(),
+        match_summaries: match_summaries.clone(),
+        feature_store: feature_store.clone(),
+        search_index: search_index.clone(),
+        metrics: metrics_collector.clone(),
+        feed_control,
+        rebuilder: Arc::new(rebuilder),
+        replayer: Arc::new(replayer),
+        event_log: Arc::new(event_log),
+        domain_events,
+        provenance: Arc::new(provenance),
+        fixtures: fixture_service.clone(),
+        jobs: job_queue.clone(),
     };
     
     // Start API server
     let api_handle = {
+        let rate_limiter = Arc::new(
+            RateLimiter::new(config.server.rate_limit.clone()).with_metrics(metrics_collector.clone())
+        );
+        let auth = Arc::new(AuthMiddleware::new().with_keys(config.server.api_keys.clone()));
+        // Layers run outermost first: keys are verified before limiting
         let router = create_routes()
             .with_state(api_state)
+            .layer(axum::middleware::from_fn_with_state(rate_limiter, rate_limit))
+            .layer(axum::middleware::from_fn_with_state(auth, authenticate))
             .layer(CorsLayer::permissive());
         let config_clone = config.clone();
+        let shutdown = shutdown.clone();
         
         tokio::spawn(async move {
             let listener = tokio::net::TcpListener::bind(&config_clone.server_addr()).await.unwrap();
             info!("🌐 API server starting on {}", config_clone.server_addr());
-            axum::serve(listener, router).await.unwrap();
+            axum::serve(listener, router.into_make_service_with_connect_info::<std::net::SocketAddr>())
+                .with_graceful_shutdown(shutdown.cancelled_owned())
+                .await
+                .unwrap();
         })
     };
     
-    // Start event processor in background
-    let processor_handle = {
+    // gRPC streams and notifications are fed from the event processors below. Open
+    // streams would hold up a graceful stop, so the server is left to exit with the process.
+    if config.server.grpc.enabled {
+        let service = QuantGrpc::new(trading_engine.clone(), market_simulator.clone(), event_bus.clone());
+        let shutdown = shutdown.clone();
+        match config.grpc_addr().parse() {
+            Ok(addr) => {
+                tokio::spawn(async move {
+                    info!("🛰️ gRPC server starting on {}", addr);
+                    if let Err(e) = serve_grpc(addr, service, shutdown.cancelled_owned()).await {
+                        error!("🛰️ gRPC server failed: {}", e);
+                    }
+                });
+            }
+            Err(e) => error!("🛰️ Invalid gRPC address {}: {}", config.grpc_addr(), e),
+        }
+    }
+
+    // Post strong signals and settled bets to Telegram or Discord
+    if let Some(events) = notifier_feed {
+        let notifier = Notifier::new(config.notifications.clone());
+        tokio::spawn(notifier.run(event_bus.subscribe(), events, shutdown.clone()));
+    }
+    
+    // Start event processors in background, one per shard of matches. The
+    // simulator, prediction and trading decision run within their latency
+    // budgets; execution is never cut off part way.
+    let event_count = Arc::new(AtomicU64::new(0));
+    let latency_budget = Arc::new(LatencyBudget::new(config.latency_budget.clone()));
+    let mut processor_handles = Vec::new();
+    for mut event_receiver in spawn_sharding(event_receiver, config.ordering.event_workers, config.ordering.queue.capacity) {
         let metrics = metrics_collector.clone();
         let events_storage = recent_events.clone();
         let predictions_storage = recent_predictions.clone();
+        let prediction_history = prediction_history.clone();
+        let memory = memory.clone();
+        let trading_engine = trading_engine.clone();
+        let repository = repository.clone();
+        let prediction_ledger = prediction_ledger.clone();
+        let odds_history = odds_history.clone();
+        let match_summaries = match_summaries.clone();
+        let feature_store = feature_store.clone();
+        let search_index = search_index.clone();
+        let trade_stream_key = config.redis.trade_stream_key.clone();
+        let match_states = match_states.clone();
+        let predictor = predictor.clone();
+        let market_simulator = market_simulator.clone();
+        let event_count = event_count.clone();
+        let match_event_topic = event_bus.topic::<MatchEvent>();
+        let prediction_topic = event_bus.topic::<Prediction>();
+        let signal_topic = event_bus.topic::<TradingSignal>();
+        let settlement_topic = event_bus.topic::<SettlementResult>();
+        let latency_budget = latency_budget.clone();
+        let fixture_service = fixture_service.clone();
+        let job_queue = job_queue.clone();
         
-        tokio::spawn(async move {
-            let mut event_count = 0;
+        processor_handles.push(tokio::spawn(async move {
             while let Some(event) = event_receiver.recv().await {
-                event_count += 1;
+                let event_count = event_count.fetch_add(1, Ordering::Relaxed) + 1;
                 
                 // Track metrics
                 metrics.increment_events_processed().await;
+
+                // Drop events that can't have happened, e.g. a goal after full time
+                match match_states.apply(&event) {
+                    Ok(corrections) => {
+                        for correction in corrections {
+                            warn!("🚩 {}: {:?}", event.match_id, correction);
+                            if let MatchCorrection::Score { home, away, .. } = correction {
+                                predictor.get_feature_engineer().correct_score(&event.match_id, home, away);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        metrics.increment_errors().await;
+                        warn!("🚫 Rejected {:?} for {}: {}", event.event_type, event.match_id, e);
+                        continue;
+                    }
+                }
+
+                // Append to the event log, so derived state can be rebuilt from it
+                if let Some(repository) = &repository {
+                    let mut match_record = MatchRecord::new(
+                        event.match_id.clone(),
+                        event.team_home.clone(),
+                        event.team_away.clone(),
+                        event.league.clone(),
+                        event.season.clone(),
+                    );
+                    // Pre-match odds moves leave a stored fixture scheduled
+                    if matches!(event.match_status, MatchStatus::Scheduled) {
+                        match_record.status = "scheduled".to_string();
+                    }
+                    if let Err(e) = repository.record_event(&match_record, &EventRecord::from(&event)).await {
+                        warn!("💾 Failed to store event for {}: {}", event.match_id, e);
+                    }
+                }
                 
                 // Store event for API
                 {
                     let mut events = events_storage.write().await;
-                    events.push(event.clone());
-                    if events.len() > 1000 {
-                        events.remove(0); // Keep only last 1000 events
-                    }
+                    memory.push_vec(CacheKind::Events, &mut events, event.clone());
                 }
                 
                 info!("🏈 Event #{}: {} - {:?} ({} vs {})", 
@@ -132,86 +771,319 @@ async fn main() -> Result<()> {
                       event.team_away
                 );
             
+                match_event_topic.publish(event.clone());
+                
+                if matches!(event.event_type, EventType::MatchStart) {
+                    fixture_service.kicked_off(&event.match_id).await;
+                }
+
+                // Track league/team context for correlated exposure limits
+                trading_engine.register_match(&event).await;
+                match_summaries.record_event(&event).await;
+                search_index.index_event(&event).await;
+
+                // The first half's result bets are decided at half time
+                if matches!(event.event_type, EventType::HalfTime) {
+                    if let Some(context) = predictor.get_feature_engineer().get_match_context(&event.match_id) {
+                        if let Err(e) = trading_engine.settle_first_half(&event.match_id, context.home_score, context.away_score).await {
+                            metrics.increment_errors().await;
+                            error!("🏁 Failed to settle first half bets for {}: {}", event.match_id, e);
+                        }
+                    }
+                }
+                
+                // Record final results so predictions can be evaluated later
+                if matches!(event.event_type, EventType::FullTime) {
+                    if let Some(context) = predictor.get_feature_engineer().get_match_context(&event.match_id) {
+                        let outcome = Outcome1X2::from_score(context.home_score, context.away_score);
+                        if let Some(repository) = &repository {
+                            if let Err(e) = repository.label_training_examples(&event.match_id, &outcome).await {
+                                warn!("🧠 Failed to label training examples for {}: {}", event.match_id, e);
+                            }
+                            // Kept so reconciliation can settle bets if settling below fails
+                            let (home, away) = (i32::from(context.home_score), i32::from(context.away_score));
+                            if let Err(e) = repository.record_result(&event.match_id, home, away).await {
+                                warn!("💾 Failed to store the result of {}: {}", event.match_id, e);
+                            }
+                        }
+                        prediction_ledger.record_result(&event.match_id, outcome).await;
+                        if let Some(challenger) = predictor.challenger() {
+                            challenger.record_result(&event.match_id, outcome).await;
+                        }
+
+                        predictor.record_result(&event, context.home_score, context.away_score).await;
+                        if let Some(repository) = &repository {
+                            let elo_ratings = predictor.get_feature_engineer().elo_ratings();
+                            let ratings: Vec<TeamRatingRecord> = [&event.team_home, &event.team_away].into_iter()
+                                .filter_map(|team| elo_ratings.get(team))
+                                .map(|rating| TeamRatingRecord::from(&rating))
+                                .collect();
+                            if let Err(e) = repository.save_team_ratings(&ratings).await {
+                                warn!("💾 Failed to store Elo ratings after {}: {}", event.match_id, e);
+                            }
+                        }
+
+                        // Online learning, and the ensemble reweights its components on the result
+                        for prediction in prediction_ledger.match_predictions(&event.match_id).await {
+                            let correct = prediction.most_likely_outcome() == outcome;
+                            let feedback = ModelFeedback {
+                                prediction_id: prediction.id,
+                                actual_outcome: correct,
+                                reward: if correct { 1.0 } else { -1.0 },
+                                outcome: Some(outcome),
+                            };
+                            if let Err(e) = predictor.apply_feedback(&feedback).await {
+                                warn!("🧠 Failed to apply feedback for {}: {}", prediction.id, e);
+                            }
+                        }
+                        if let Some(weights) = predictor.ensemble_weights().await {
+                            debug!("🧠 Ensemble weights: logistic {:.2}, poisson {:.2}",
+                                   weights.logistic_weight, weights.poisson_weight);
+                        }
+
+                        match trading_engine.settle_match(&event.match_id, context.home_score, context.away_score).await {
+                            Ok(settled) => {
+                                settlement_topic.publish(SettlementResult::new(
+                                    event.match_id.clone(), context.home_score, context.away_score, settled,
+                                ));
+                            }
+                            Err(e) => {
+                                metrics.increment_errors().await;
+                                error!("🏁 Failed to settle bets for {}, queued for retry: {}", event.match_id, e);
+                                let task = SettleMatchTask {
+                                    match_id: event.match_id.clone(),
+                                    home_goals: context.home_score,
+                                    away_goals: context.away_score,
+                                };
+                                match serde_json::to_value(&task) {
+                                    Ok(payload) => { job_queue.enqueue(SETTLE_MATCH_JOB, payload).await; }
+                                    Err(e) => warn!("🛠️ Failed to queue settlement of {}: {}", event.match_id, e),
+                                }
+                            }
+                        }
+
+                        // Each result refines how far the model is trusted over the market
+                        let model_name = predictions_storage.read().await.last().map(|p| p.model_name.clone());
+                        if let Some(model_name) = model_name {
+                            let now = Utc::now();
+                            let samples = prediction_ledger
+                                .samples(&model_name, now - Duration::days(BLEND_CALIBRATION_DAYS), now)
+                                .await;
+                            trading_engine.calibrate_blending(&samples).await;
+                        }
+                    }
+                }
+
+                // Postponed or cancelled: no result is coming, so stakes are returned
+                if matches!(event.event_type, EventType::MatchEnd)
+                    && matches!(event.match_status, MatchStatus::Postponed | MatchStatus::Cancelled)
+                {
+                    match trading_engine.void_match(&event.match_id).await {
+                        Ok(0) => {}
+                        Ok(voided) => info!("↩️ Voided {} bets on {} ({:?})", voided, event.match_id, event.match_status),
+                        Err(e) => {
+                            metrics.increment_errors().await;
+                            error!("↩️ Failed to void bets for {}: {}", event.match_id, e);
+                        }
+                    }
+                    continue;
+                }
+
+                // Post-mortem, once the result is in and bets are settled
+                if matches!(event.event_type, EventType::MatchEnd) {
+                    latency_budget.forget(&event.match_id);
+                    let closing_odds = match match_summaries.kickoff(&event.match_id).await {
+                        Some(kickoff) => odds_history.snapshot_at(&event.match_id, MARKET_FEED_BOOKMAKER, kickoff).await,
+                        None => None,
+                    };
+                    let bets = trading_engine.get_match_bets(&event.match_id).await;
+                    if let Some(summary) = match_summaries.finish(&event.match_id, closing_odds, bets).await {
+                        info!("📋 {} {}-{} {}: {} bets, P&L {}",
+                              summary.team_home, summary.final_score.home, summary.final_score.away,
+                              summary.team_away, summary.bets.len(), summary.profit_loss);
+                        if let Some(repository) = &repository {
+                            if let Err(e) = repository.save_match_summary(&MatchSummaryRecord::from(&summary)).await {
+                                warn!("💾 Failed to persist summary for {}: {}", event.match_id, e);
+                            }
+                        }
+                    }
+                    continue;
+                }
+
+                // Bets are settled at full time; nothing is left to price or trade
+                if matches!(event.event_type, EventType::FullTime) {
+                    continue;
+                }
+                
                 // Generate market odds for this event
-                let market_odds = match market_simulator.generate_market_odds(&event).await {
-                    Ok(odds) => {
+                let market_odds = match latency_budget.run(PipelineStage::Simulator, market_simulator.generate_market_odds(&event)).await {
+                    Ok(Ok(odds)) => {
                         trading_engine.update_market_odds(event.match_id.clone(), odds.clone()).await;
+                        odds_history.record(&event.match_id, MARKET_FEED_BOOKMAKER, event.timestamp, &odds).await;
+                        if let Some(derived) = market_simulator.get_derived_odds(&event.match_id).await {
+                            trading_engine.update_derived_odds(event.match_id.clone(), derived).await;
+                        }
+                        if let Some(handicap) = market_simulator.get_asian_handicap_odds(&event.match_id).await {
+                            trading_engine.update_asian_handicap_odds(event.match_id.clone(), handicap).await;
+                        }
+                        if let Some(correct_score) = market_simulator.get_correct_score_odds(&event.match_id).await {
+                            trading_engine.update_correct_score_odds(event.match_id.clone(), correct_score).await;
+                        }
+                        if let Some(halves) = market_simulator.get_half_result_odds(&event.match_id).await {
+                            trading_engine.update_half_result_odds(event.match_id.clone(), halves).await;
+                        }
+                        if let Some(liquidity) = market_simulator.get_liquidity(&event.match_id).await {
+                            trading_engine.update_market_liquidity(event.match_id.clone(), liquidity).await;
+                        }
+                        for book_odds in market_simulator.generate_bookmaker_odds(&event).await {
+                            odds_history.record_market_odds(&book_odds).await;
+                            if let Err(e) = trading_engine.update_bookmaker_odds(&book_odds).await {
+                                warn!("📊 Rejected {} odds for {}: {}", book_odds.bookmaker, event.match_id, e);
+                            }
+                        }
                         Some(odds)
                     }
-                    Err(e) => {
+                    Ok(Err(e)) => {
                         metrics.increment_errors().await;
                         warn!("📊 Failed to generate market odds for {}: {}", event.match_id, e);
                         None
                     }
+                    Err(timeout) => {
+                        metrics.record_stage_timeout(timeout.stage.name()).await;
+                        warn!("⏱️ {} for {}, odds left as they were", timeout, event.match_id);
+                        None
+                    }
                 };
                 
                 // Process event through prediction engine with latency tracking
                 let prediction_tracker = metrics.start_latency_tracking("prediction".to_string());
-                match predictor.predict(&event).await {
-                    Ok(prediction) => {
+                let prediction = match latency_budget.run(PipelineStage::Prediction, predictor.predict_with_features(&event)).await {
+                    Ok(Ok((prediction, features))) => {
                         prediction_tracker.finish(&metrics);
+                        let stored_features = feature_store.record(&prediction, features.clone()).await;
+                        if let Some(repository) = &repository {
+                            if let Err(e) = repository.record_training_example(&TrainingExampleRecord::new(&features)).await {
+                                warn!("🧠 Failed to store training example for {}: {}", event.match_id, e);
+                            }
+                            if let Err(e) = repository.save_prediction_features(&PredictionFeaturesRecord::from(&stored_features)).await {
+                                warn!("🧠 Failed to store features for prediction {}: {}", prediction.id, e);
+                            }
+                        }
                         metrics.increment_predictions_generated().await;
+                        prediction_ledger.record_prediction(prediction.clone(), market_odds.clone()).await;
+                        if let Err(e) = predictor.predict_challenger(&event, &features, market_odds.clone()).await {
+                            warn!("🥊 Challenger failed to predict {}: {}", event.match_id, e);
+                        }
+                        match_summaries.record_prediction(&prediction).await;
+                        let minute = predictor.get_feature_engineer().get_match_context(&event.match_id)
+                            .map(|context| context.minute);
+                        prediction_history.record(&prediction, minute).await;
                         
                         // Store prediction for API
                         {
                             let mut predictions = predictions_storage.write().await;
-                            predictions.push(prediction.clone());
-                            if predictions.len() > 500 {
-                                predictions.remove(0); // Keep only last 500 predictions
-                            }
+                            memory.push_vec(CacheKind::Predictions, &mut predictions, prediction.clone());
                         }
+                        prediction_topic.publish(prediction.clone());
                         
                         info!("🎯 Generated prediction - Most likely: {:?}", 
                               prediction.most_likely_outcome());
+                        latency_budget.remember(&prediction);
+                        prediction
+                    }
+                    Ok(Err(e)) => {
+                        prediction_tracker.finish(&metrics);
+                        metrics.increment_errors().await;
+                        error!("❌ Prediction failed for {}: {}", event.match_id, e);
+                        continue;
+                    }
+                    Err(timeout) => {
+                        prediction_tracker.finish(&metrics);
+                        metrics.record_stage_timeout(timeout.stage.name()).await;
+                        match latency_budget.fallback(&event.match_id) {
+                            Some(prediction) => {
+                                warn!("⏱️ {} for {}, trading on the last prediction", timeout, event.match_id);
+                                prediction
+                            }
+                            None => {
+                                warn!("⏱️ {} for {}, event skipped", timeout, event.match_id);
+                                continue;
+                            }
+                        }
+                    }
+                };
+
+                // Send prediction to trading engine with latency tracking
+                let trading_tracker = metrics.start_latency_tracking("trading_decision".to_string());
+                match latency_budget.run(PipelineStage::Trading, trading_engine.process_prediction(&prediction)).await {
+                    Ok(Ok(signal)) => {
+                        trading_tracker.finish(&metrics);
+                        signal_topic.publish(signal.clone());
                         
-                        // Send prediction to trading engine with latency tracking
-                        let trading_tracker = metrics.start_latency_tracking("trading_decision".to_string());
-                        match trading_engine.process_prediction(&prediction).await {
-                            Ok(signal) => {
-                                trading_tracker.finish(&metrics);
-                                
-                                if signal.signal_strength > 0.0 {
-                                    info!("💡 Trading signal: {:.1}% strength - {}", 
-                                          signal.signal_strength * 100.0,
-                                          signal.reasoning);
-                                    
-                                    // Execute trade if signal is strong enough
-                                    if signal.signal_strength > 0.3 { // 30% threshold
-                                        match trading_engine.execute_trade(&signal).await {
-                                            Ok(executed) => {
-                                                if executed {
-                                                    metrics.increment_trades_executed().await;
-                                                    let summary = trading_engine.get_portfolio_summary().await;
-                                                    info!("💼 Portfolio: ${} available, {} active bets, ROI: {:.1}%",
-                                                          summary.available_bankroll,
-                                                          summary.active_bets_count,
-                                                          summary.roi * 100.0);
-                                                }
+                        if signal.signal_strength > 0.0 {
+                            info!("💡 Trading signal: {:.1}% strength - {}", 
+                                  signal.signal_strength * 100.0,
+                                  signal.reasoning);
+
+                            if let Some(repository) = &repository {
+                                if let Err(e) = persist_trading_signal(repository, &signal).await {
+                                    warn!("💾 Failed to persist trading signal {}: {}", signal.id, e);
+                                }
+                            }
+                            
+                            // Execute trade if signal is strong enough
+                            if signal.signal_strength > MIN_EXECUTION_STRENGTH {
+                                match trading_engine.execute_trade(&signal).await {
+                                    Ok(executed) => {
+                                        if executed {
+                                            metrics.increment_trades_executed().await;
+                                            // Stored as booked, at the stake and odds the venue filled
+                                            let booked = match &signal.recommended_bet {
+                                                Some(bet) => trading_engine.get_bet(bet.id).await,
+                                                None => None,
+                                            };
+                                            if let Some(bet) = &booked {
+                                                search_index.index_bet(bet).await;
                                             }
-                                            Err(e) => {
-                                                metrics.increment_errors().await;
-                                                error!("❌ Trade execution failed: {}", e);
+                                            if let (Some(repository), Some(bet)) = (&repository, &booked) {
+                                                let match_info = trading_engine.get_match_info(&bet.match_id).await;
+                                                if let Err(e) = persist_executed_bet(
+                                                    repository, &trade_stream_key, bet, match_info.as_ref(),
+                                                ).await {
+                                                    metrics.increment_errors().await;
+                                                    error!("💾 Failed to persist bet {}: {}", bet.id, e);
+                                                }
                                             }
+                                            let summary = trading_engine.get_portfolio_summary().await;
+                                            info!("💼 Portfolio: {} {} available, {} active bets, ROI: {:.1}%",
+                                                  summary.available_bankroll,
+                                                  summary.currency,
+                                                  summary.active_bets_count,
+                                                  summary.roi * 100.0);
                                         }
                                     }
+                                    Err(e) => {
+                                        metrics.increment_errors().await;
+                                        error!("❌ Trade execution failed: {}", e);
+                                    }
                                 }
                             }
-                            Err(e) => {
-                                trading_tracker.finish(&metrics);
-                                metrics.increment_errors().await;
-                                error!("❌ Trading signal generation failed: {}", e);
-                            }
                         }
                     }
-                    Err(e) => {
-                        prediction_tracker.finish(&metrics);
+                    Ok(Err(e)) => {
+                        trading_tracker.finish(&metrics);
                         metrics.increment_errors().await;
-                        error!("❌ Prediction failed for {}: {}", event.match_id, e);
+                        error!("❌ Trading signal generation failed: {}", e);
+                    }
+                    Err(timeout) => {
+                        trading_tracker.finish(&metrics);
+                        metrics.record_stage_timeout(timeout.stage.name()).await;
+                        warn!("⏱️ {} for {}, no trade", timeout, event.match_id);
                     }
                 }
             }
-        })
-    };
+        }));
+    }
     
     info!("✅ All services started successfully");
     info!("🎮 Running in simulation mode - generating live match events");
@@ -238,14 +1110,391 @@ async fn main() -> Result<()> {
     // Keep the application running
     tokio::signal::ctrl_c().await?;
     info!("👋 Shutting down gracefully");
+    shutdown.cancel();
+    
+    // Feed stops producing, then the processor drains whatever is still queued
+    if let Err(e) = feed_handle.await {
+        error!("❌ Data feed task failed during shutdown: {}", e);
+    }
+    let drain = async {
+        for processor_handle in processor_handles {
+            if let Err(e) = processor_handle.await {
+                error!("❌ Event processor task failed during shutdown: {}", e);
+            }
+        }
+    };
+    match tokio::time::timeout(SHUTDOWN_DRAIN_TIMEOUT, drain).await {
+        Ok(()) => info!("✅ Event processors drained"),
+        Err(_) => warn!("⏱️ Event processors did not drain within {:?}", SHUTDOWN_DRAIN_TIMEOUT),
+    }
+    
+    if let Some(repository) = &repository {
+        flush_open_bets(repository, &trading_engine).await;
+    }
+
+    // Snapshot once the processors have settled, so a restart resumes from here
+    if let Some(snapshotter) = &snapshotter {
+        match snapshotter.save().await {
+            Ok(snapshot) => info!("💾 Saved engine snapshot: {} active bets, {} live matches",
+                                  snapshot.trading.portfolio.active_bets.len(), snapshot.features.match_contexts.len()),
+            Err(e) => error!("❌ Could not save the engine snapshot: {}", e),
+        }
+    }
+    
+    // Publish whatever the processor and flush committed, then stop the relay
+    relay_shutdown.cancel();
+    if let Some(relay_handle) = relay_handle {
+        if tokio::time::timeout(SHUTDOWN_DRAIN_TIMEOUT, relay_handle).await.is_err() {
+            warn!("⏱️ Outbox relay did not finish within {:?}", SHUTDOWN_DRAIN_TIMEOUT);
+        }
+    }
+    if let Some(settlement_handle) = settlement_handle {
+        if tokio::time::timeout(SHUTDOWN_DRAIN_TIMEOUT, settlement_handle).await.is_err() {
+            warn!("⏱️ Settled bets were not all stored within {:?}", SHUTDOWN_DRAIN_TIMEOUT);
+        }
+    }
+    
+    // API finishes in-flight requests once the token is cancelled
+    match tokio::time::timeout(SHUTDOWN_DRAIN_TIMEOUT, api_handle).await {
+        Ok(Ok(())) => info!("✅ API server stopped"),
+        Ok(Err(e)) => error!("❌ API server task failed during shutdown: {}", e),
+        Err(_) => warn!("⏱️ API server did not finish in-flight requests within {:?}", SHUTDOWN_DRAIN_TIMEOUT),
+    }
     
     // Final performance summary
     metrics_collector.log_performance_summary().await;
-    
-    // Clean shutdown
-    feed_handle.abort();
-    processor_handle.abort();
-    api_handle.abort();
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Parse `--import FILE...` from the command line arguments
+fn import_files_arg() -> Option<Vec<String>> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == "--import")?;
+    Some(args[index + 1..].iter().take_while(|arg| !arg.starts_with("--")).cloned().collect())
+}
+
+async fn run_import(config: &AppConfig, files: &[String]) -> Result<()> {
+    if files.is_empty() {
+        anyhow::bail!("--import needs at least one CSV file");
+    }
+
+    let repository = Repository::connect(config.database_url()).await?;
+    let importer = HistoricalImporter::new();
+
+    let mut imported = 0;
+    for file in files {
+        imported += importer.import_file(&repository, file).await?.imported;
+    }
+
+    info!("📥 Historical import finished: {} matches from {} files", imported, files.len());
+    Ok(())
+}
+
+/// A fresh seed for runs that don't configure one
+fn random_seed() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+    std::collections::hash_map::RandomState::new().build_hasher().finish()
+}
+
+/// The value following `flag` on the command line, if present
+fn flag_value(flag: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == flag)?;
+    args.get(index + 1).filter(|value| !value.starts_with("--")).cloned()
+}
+
+/// `--archive-season SEASON [--league NAME] [--keep-live]`
+async fn run_season_archive(config: &AppConfig, season: &str) -> Result<()> {
+    let repository = Repository::connect(config.database_url()).await?;
+    let league = flag_value("--league");
+    let purge = !std::env::args().any(|arg| arg == "--keep-live");
+
+    let report = SeasonArchiver::new(&config.retention.season_archive_dir)
+        .archive(&repository, season, league.as_deref(), purge)
+        .await?;
+    info!("🗄️ Season archive finished: {} rows in {}, {} removed from the live tables",
+          report.manifest.total_rows(), report.path.display(), report.rows_purged);
+    info!("🗄️ Restore it with --restore-season {}", report.path.display());
+    Ok(())
+}
+
+async fn run_season_restore(config: &AppConfig, dir: &str) -> Result<()> {
+    let repository = Repository::connect(config.database_url()).await?;
+
+    let report = SeasonArchiver::restore(&repository, dir).await?;
+    info!("🗄️ Season restore finished: {} of {} archived rows inserted", report.rows_restored, report.rows_archived);
+    Ok(())
+}
+
+async fn run_training(config: &AppConfig) -> Result<()> {
+    let repository = Repository::connect(config.database_url()).await?;
+
+    let run = ModelTrainingJob::new().run(&repository, &config.ml.model_dir).await?;
+    info!("🧠 Training finished: {} {} written to {}", run.artifact.name, run.artifact.version, run.path.display());
+    info!("🧠 Set ml.model_artifact = \"{}\" to use it", run.path.display());
+    Ok(())
+}
+
+/// `--evaluate-transfer --train-leagues A,B --test-leagues C,D`
+async fn run_transfer_evaluation(config: &AppConfig) -> Result<()> {
+    let leagues = |flag: &str| -> Result<Vec<String>> {
+        let leagues: Vec<String> = flag_value(flag).unwrap_or_default()
+            .split(',')
+            .map(|league| league.trim().to_string())
+            .filter(|league| !league.is_empty())
+            .collect();
+        if leagues.is_empty() {
+            anyhow::bail!("--evaluate-transfer needs {} with a comma-separated list of leagues", flag);
+        }
+        Ok(leagues)
+    };
+    let (train_leagues, test_leagues) = (leagues("--train-leagues")?, leagues("--test-leagues")?);
+
+    let repository = Repository::connect(config.database_url()).await?;
+    let report = ModelTrainingJob::new().evaluate_transfer(&repository, &train_leagues, &test_leagues).await?;
+
+    info!("🧠 Trained on {} examples from {}", report.train_examples, report.train_leagues.join(", "));
+    let line = |label: &str, r: &quant_ml::EvaluationReport| info!(
+        "🧠 {:<24} {:>6} examples  accuracy {:>5.1}%  log loss {:.4}  Brier {:.4}  ROI {:>6.1}%",
+        label, r.sample_count, r.accuracy * 100.0, r.log_loss, r.brier_score, r.roi_if_followed * 100.0,
+    );
+    line("in-league holdout", &report.in_league);
+    for (league, league_report) in &report.per_league {
+        line(league, league_report);
+    }
+    line("all test leagues", &report.transfer);
+    line("base rate", &report.base_rate);
+    if report.beats_base_rate() {
+        info!("🧠 Transfer costs {:+.4} log loss per match and still beats the base rate", report.log_loss_increase());
+    } else {
+        warn!("🧠 The model does no better than the base rate outside its training leagues; don't trade a new league on it");
+    }
+    Ok(())
+}
+
+/// `--backtest-sweep [--strategy NAME] [--min-edge A,B] [--kelly-multiplier A,B]
+/// [--min-confidence A,B] [--rank-by roi|profit_loss|sharpe]`
+async fn run_backtest_sweep(config: &AppConfig) -> Result<()> {
+    let values = |flag: &str| -> Result<Vec<f64>> {
+        flag_value(flag).unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.parse().map_err(|_| anyhow::anyhow!("{} needs comma-separated numbers, got '{}'", flag, value)))
+            .collect()
+    };
+    let grid = ParameterGrid {
+        min_edge: values("--min-edge")?,
+        kelly_multiplier: values("--kelly-multiplier")?,
+        min_confidence: values("--min-confidence")?,
+    };
+    let ranking = match flag_value("--rank-by") {
+        Some(rank_by) => serde_json::from_value::<SweepRanking>(serde_json::Value::String(rank_by.clone()))
+            .map_err(|_| anyhow::anyhow!("--rank-by is roi, profit_loss or sharpe, not '{}'", rank_by))?,
+        None => SweepRanking::default(),
+    };
+    let (preset, strategy) = backtest_strategy(config)?;
+
+    let repository = Repository::connect(config.database_url()).await?;
+    let samples = ModelTrainingJob::new().holdout_samples(&repository).await?;
+    let results = Backtester::new(config.trading.initial_bankroll)
+        .sweep(&strategy, &grid, Arc::new(samples), ranking)
+        .await?;
+
+    info!("📈 {} combinations of {} backtested, best first by {:?}", results.len(), preset, ranking);
+    for result in &results {
+        let (parameters, report) = (&result.parameters, &result.report);
+        info!(
+            "📈 {:>3}. edge {:>5.3}  kelly {:>4.2}  confidence {:>4.2}  {:>5} bets  staked {:>10}  P/L {:>10}  ROI {:>6.1}%  drawdown {:>5.1}%  Sharpe {:>5.2}",
+            result.rank, parameters.min_edge, parameters.kelly_multiplier, parameters.min_confidence, report.bets,
+            report.staked.round_dp(2), report.profit_loss.round_dp(2), report.roi * 100.0, report.max_drawdown * 100.0, report.sharpe_ratio,
+        );
+    }
+    Ok(())
+}
+
+/// `--walk-forward --train-size N --test-size M [--expanding] [--strategy NAME]`
+async fn run_walk_forward(config: &AppConfig) -> Result<()> {
+    let size = |flag: &str| -> Result<usize> {
+        flag_value(flag)
+            .and_then(|value| value.parse().ok())
+            .filter(|size| *size > 0)
+            .ok_or_else(|| anyhow::anyhow!("--walk-forward needs {} with a number of examples", flag))
+    };
+    let walk_forward = WalkForwardConfig {
+        train_size: size("--train-size")?,
+        test_size: size("--test-size")?,
+        expanding: std::env::args().any(|arg| arg == "--expanding"),
+    };
+    let (preset, strategy) = backtest_strategy(config)?;
+
+    let repository = Repository::connect(config.database_url()).await?;
+    let windows = ModelTrainingJob::new().walk_forward(&repository, &walk_forward).await?;
+    let report = Backtester::new(config.trading.initial_bankroll).walk_forward(&strategy, windows)?;
+
+    for result in &report.windows {
+        let (window, backtest) = (&result.window, &result.backtest);
+        info!(
+            "📈 Window {:>3}  {} to {}  trained on {:>6}  log loss {:.4}  Brier {:.4}  {:>4} bets  P/L {:>10}  ROI {:>6.1}%",
+            window.index, window.test_from.date_naive(), window.test_to.date_naive(), window.train_examples,
+            window.evaluation.log_loss, window.evaluation.brier_score, backtest.bets, backtest.profit_loss.round_dp(2), backtest.roi * 100.0,
+        );
+    }
+    info!("📈 {} across {} windows: {} bets, P/L {}, ROI {:.1}%, max drawdown {:.1}%",
+          preset, report.windows.len(), report.combined.bets, report.combined.profit_loss.round_dp(2),
+          report.combined.roi * 100.0, report.combined.max_drawdown * 100.0);
+    info!("📈 Log loss {:.4} ± {:.4}, ROI {:.1}% ± {:.1}% from window to window",
+          report.log_loss_mean, report.log_loss_std_dev, report.roi_mean * 100.0, report.roi_std_dev * 100.0);
+    Ok(())
+}
+
+/// The `--strategy` preset (`moderate` by default) with its configured staking plan
+fn backtest_strategy(config: &AppConfig) -> Result<(String, BettingStrategy)> {
+    let preset = flag_value("--strategy").unwrap_or_else(|| "moderate".to_string());
+    let strategy = BettingStrategy::preset(&preset)
+        .ok_or_else(|| anyhow::anyhow!("unknown strategy '{}' for --strategy", preset))?;
+    let strategy = match config.trading.staking.get(&preset) {
+        Some(staking) => {
+            let plan = staking.plan(strategy.kelly_multiplier);
+            strategy.with_staking_plan(plan)
+        }
+        None => strategy,
+    };
+    Ok((preset, strategy))
+}
+
+fn strategy_ensemble(
+    config: &config::EnsembleConfig,
+    filters: &HashMap<String, MarketFilter>,
+    staking: &HashMap<String, StakingConfig>,
+) -> Result<Option<StrategyEnsemble>> {
+    if config.strategies.is_empty() {
+        return Ok(None);
+    }
+    let strategies = config.strategies.iter()
+        .map(|name| {
+            let strategy = BettingStrategy::preset(name)
+                .ok_or_else(|| anyhow::anyhow!("unknown strategy '{}' in trading.ensemble.strategies", name))?;
+            let strategy = match filters.get(name) {
+                Some(filter) => strategy.with_market_filter(filter.clone()),
+                None => strategy,
+            };
+            Ok(match staking.get(name) {
+                Some(staking) => {
+                    let plan = staking.plan(strategy.kelly_multiplier);
+                    strategy.with_staking_plan(plan)
+                }
+                None => strategy,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut ensemble = StrategyEnsemble::new(strategies).with_sizing(config.sizing);
+    if config.min_approvals > 0 {
+        ensemble = ensemble.with_min_approvals(config.min_approvals);
+    }
+    Ok(Some(ensemble))
+}
+
+fn betfair_exchange(config: &config::BetfairConfig) -> Result<BetfairExchange> {
+    if config.app_key.is_empty() || config.username.is_empty() || config.password.is_empty() {
+        anyhow::bail!("trading.live_execution needs trading.betfair.app_key, username and password");
+    }
+    let markets = match &config.markets_path {
+        Some(path) => load_betfair_markets(path)?,
+        None => Default::default(),
+    };
+    if markets.is_empty() {
+        warn!("🏦 No Betfair markets mapped; no trade can be placed until they are");
+    }
+    Ok(BetfairExchange::new(BetfairCredentials {
+        app_key: config.app_key.clone(),
+        username: config.username.clone(),
+        password: config.password.clone(),
+    }).with_markets(markets))
+}
+
+async fn retention_job(config: &AppConfig, repository: Arc<Repository>, metrics: Arc<MetricsCollector>) -> RetentionJob {
+    let retention = &config.retention;
+    let mut policy = RetentionPolicy::new().with_batch_size(retention.batch_size);
+    for target in RetentionTarget::ALL {
+        let days = retention.tables.get(target.as_str()).copied().filter(|days| *days > 0);
+        policy = policy.with_window(target, days);
+    }
+    for table in retention.tables.keys() {
+        if table.parse::<RetentionTarget>().is_err() {
+            warn!("🧹 Ignoring retention window for unknown table '{}'", table);
+        }
+    }
+    for (stream_key, days) in retention.streams.iter().filter(|(_, days)| **days > 0) {
+        policy = policy.with_stream_window(stream_key, *days);
+    }
+    if let Some(dir) = &retention.archive_dir {
+        policy = policy.with_archive(dir, retention.compress_archives);
+    }
+
+    let query_log = metrics.query_log();
+    let mut job = RetentionJob::new(repository, policy)
+        .with_metrics(metrics)
+        .with_interval(std::time::Duration::from_secs(retention.interval_minutes.max(1) * 60));
+    if !retention.streams.is_empty() {
+        match RedisStream::new(config.redis_url()).await {
+            Ok(stream) => job = job.with_stream(Arc::new(stream.with_query_log(query_log))),
+            Err(e) => warn!("🧹 Redis unavailable, streams will not be trimmed: {}", e),
+        }
+    }
+    job
+}
+
+async fn connect_repository(config: &AppConfig, query_log: Arc<QueryLog>) -> Option<Arc<Repository>> {
+    match tokio::time::timeout(
+        DATABASE_CONNECT_TIMEOUT,
+        Repository::connect(config.database_url()),
+    ).await {
+        Ok(Ok(repository)) => {
+            info!("💾 Connected to {} database, bets will be persisted", repository.backend());
+            Some(Arc::new(repository.with_query_log(query_log)))
+        }
+        Ok(Err(e)) => {
+            warn!("💾 Database unavailable, running without persistence: {}", e);
+            None
+        }
+        Err(_) => {
+            warn!("💾 Database connection timed out, running without persistence");
+            None
+        }
+    }
+}
+
+/// Persist still-open bets so a restart can pick them up. Failures are logged
+/// rather than returned so they never block the rest of the shutdown.
+async fn flush_open_bets(repository: &Repository, trading_engine: &TradingEngine) {
+    let open_bets = trading_engine.get_active_bets().await;
+    if open_bets.is_empty() {
+        return;
+    }
+
+    let mut flushed = 0;
+    for bet in &open_bets {
+        if let Some(info) = trading_engine.get_match_info(&bet.match_id).await {
+            let match_record = MatchRecord::new(
+                bet.match_id.clone(),
+                info.team_home,
+                info.team_away,
+                info.league,
+                info.season,
+            );
+            if let Err(e) = repository.create_match(&match_record).await {
+                warn!("💾 Failed to store match {} for open bet: {}", bet.match_id, e);
+                continue;
+            }
+        }
+
+        match repository.create_bet(&BetRecord::from(bet)).await {
+            Ok(_) => flushed += 1,
+            Err(e) => warn!("💾 Failed to flush bet {}: {}", bet.id, e),
+        }
+    }
+
+    info!("💾 Flushed {}/{} open bets to the database", flushed, open_bets.len());
+}
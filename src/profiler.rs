@@ -0,0 +1,157 @@
+use anyhow::Result;
+use quant_models::{CardType, EventType, MatchEvent, MatchStatus};
+use quant_services::{MarketSimulator, PredictorService, TradingEngine};
+use rust_decimal_macros::dec;
+use std::time::{Duration, Instant};
+
+const STAGES: [&str; 5] = ["features", "prediction", "market_odds", "trading", "persistence"];
+const BAR_WIDTH: usize = 40;
+
+/// Parse `--profile-run N` from the command line arguments
+pub fn profile_run_arg() -> Option<usize> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|arg| arg == "--profile-run")
+        .and_then(|index| args.get(index + 1))
+        .and_then(|count| count.parse().ok())
+}
+
+struct StageTimings {
+    name: &'static str,
+    samples: Vec<Duration>,
+}
+
+impl StageTimings {
+    fn total(&self) -> Duration {
+        self.samples.iter().sum()
+    }
+
+    fn percentile(&self, p: f64) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        let mut sorted = self.samples.clone();
+        sorted.sort();
+        let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+        sorted[index]
+    }
+}
+
+/// Push N synthetic events through the pipeline stages with per-stage timing
+/// and print a flame-style breakdown of where the time goes.
+pub async fn run(event_count: usize) -> Result<()> {
+    let predictor = PredictorService::new();
+    let feature_engineer = predictor.get_feature_engineer();
+    let market_simulator = MarketSimulator::new();
+    let trading_engine = TradingEngine::new(dec!(10000.0));
+
+    let mut timings: Vec<StageTimings> = STAGES
+        .iter()
+        .map(|name| StageTimings { name, samples: Vec::with_capacity(event_count) })
+        .collect();
+
+    println!("🔬 Profiling {} synthetic events...", event_count);
+    let run_start = Instant::now();
+    let mut trading_errors = 0;
+
+    for index in 0..event_count {
+        let event = synthetic_event(index);
+
+        let start = Instant::now();
+        let features = feature_engineer.extract_features(&event).await?;
+        timings[0].samples.push(start.elapsed());
+
+        let start = Instant::now();
+        let prediction = predictor.predict_from_features(&features).await?;
+        timings[1].samples.push(start.elapsed());
+
+        let start = Instant::now();
+        let odds = market_simulator.generate_market_odds(&event).await?;
+        trading_engine.update_market_odds(event.match_id.clone(), odds).await;
+        timings[2].samples.push(start.elapsed());
+
+        // Trading errors are expected occasionally (e.g. rejected stakes) and
+        // are counted rather than aborting the run, matching the live pipeline
+        let start = Instant::now();
+        match trading_engine.process_prediction(&prediction).await {
+            Ok(signal) => {
+                if trading_engine.execute_trade(&signal).await.is_err() {
+                    trading_errors += 1;
+                }
+            }
+            Err(_) => trading_errors += 1,
+        }
+        timings[3].samples.push(start.elapsed());
+
+        // No database in profiling mode: serialization is the cost we control
+        let start = Instant::now();
+        let _ = serde_json::to_vec(&event)?;
+        let _ = serde_json::to_vec(&prediction)?;
+        timings[4].samples.push(start.elapsed());
+    }
+
+    print_report(&timings, event_count, run_start.elapsed());
+    if trading_errors > 0 {
+        println!("⚠️  {} trading decisions returned errors", trading_errors);
+    }
+    Ok(())
+}
+
+fn synthetic_event(index: usize) -> MatchEvent {
+    let match_number = index % 20;
+    let minute = ((index / 20) % 90) as u8;
+    let home = format!("Home Team {}", match_number);
+    let away = format!("Away Team {}", match_number);
+
+    let event_type = match index % 7 {
+        0 => EventType::Goal { team: home.clone(), player: None, minute },
+        3 => EventType::Card {
+            team: away.clone(),
+            player: format!("Player{}", index % 23),
+            card_type: CardType::Yellow,
+            minute,
+        },
+        5 => EventType::OddsUpdate,
+        _ => EventType::MatchStart,
+    };
+
+    MatchEvent::new(
+        format!("profile_match_{:03}", match_number),
+        event_type,
+        home,
+        away,
+        "Premier League".to_string(),
+        "2024-25".to_string(),
+    )
+    .with_status(MatchStatus::Live)
+}
+
+fn print_report(timings: &[StageTimings], event_count: usize, wall_time: Duration) {
+    let grand_total: Duration = timings.iter().map(StageTimings::total).sum();
+    let grand_total_us = grand_total.as_micros().max(1) as f64;
+
+    println!();
+    println!("📊 Hot path breakdown ({} events, {:.1}ms wall time)", event_count, wall_time.as_secs_f64() * 1000.0);
+    println!("{:<12} {:>10} {:>10} {:>10} {:>7}", "stage", "total ms", "p50 µs", "p99 µs", "share");
+
+    for stage in timings {
+        let total_us = stage.total().as_micros() as f64;
+        let share = total_us / grand_total_us;
+        let bar = "█".repeat((share * BAR_WIDTH as f64).round() as usize);
+
+        println!(
+            "{:<12} {:>10.2} {:>10} {:>10} {:>6.1}%  {}",
+            stage.name,
+            total_us / 1000.0,
+            stage.percentile(0.5).as_micros(),
+            stage.percentile(0.99).as_micros(),
+            share * 100.0,
+            bar
+        );
+    }
+
+    if let Some(hottest) = timings.iter().max_by_key(|stage| stage.total()) {
+        println!();
+        println!("🔥 Dominant stage: {}", hottest.name);
+    }
+}
@@ -8,9 +8,10 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use rust_decimal_macros::dec;
 
-use quant_api::{create_routes, AppState};
-use quant_services::{TradingEngine, MarketSimulator, PredictorService};
-use quant_models::{MatchEvent, Prediction, EventType, BettingOutcome, Sport};
+use quant_api::{authenticate, create_routes, rate_limit, AppState, AuthMiddleware, RateLimitConfig, RateLimiter};
+use quant_services::{TradingEngine, MarketSimulator, PredictorService, PredictionLedger, OddsHistory, MatchSummaryTracker, FeedControl, FeatureStore, SearchIndex, MetricsCollector, MatchStateMachine, StateRebuilder, EventReplayer, EventLog, PredictionHistory, Provenance, FixtureService, Fixture, JobQueue, JobsConfig};
+use quant_stream::DomainEventBus;
+use quant_models::{MatchEvent, Prediction, EventType, MatchStatus, Score, SimpleMarketOdds};
 
 #[tokio::test]
 async fn test_health_endpoint() {
@@ -24,7 +25,7 @@ async fn test_health_endpoint() {
 
     assert_eq!(response.status(), StatusCode::OK);
     
-    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
     let health_response: Value = serde_json::from_slice(&body).unwrap();
     
     assert_eq!(health_response["status"], "healthy");
@@ -49,7 +50,7 @@ async fn test_system_status_endpoint() {
 
     assert_eq!(response.status(), StatusCode::OK);
     
-    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
     let status_response: Value = serde_json::from_slice(&body).unwrap();
     
     assert_eq!(status_response["success"], true);
@@ -71,7 +72,7 @@ async fn test_recent_events_endpoint() {
     let response = app
         .oneshot(
             Request::builder()
-                .uri("/api/v1/events?page=1&limit=10")
+                .uri("/api/v1/events?limit=10&league=test%20league&event_type=goal")
                 .body(Body::empty())
                 .unwrap(),
         )
@@ -80,14 +81,12 @@ async fn test_recent_events_endpoint() {
 
     assert_eq!(response.status(), StatusCode::OK);
     
-    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
     let events_response: Value = serde_json::from_slice(&body).unwrap();
     
     assert_eq!(events_response["success"], true);
-    assert!(events_response["data"].is_array());
-    assert!(events_response["pagination"].is_object());
-    assert_eq!(events_response["pagination"]["page"], 1);
-    assert_eq!(events_response["pagination"]["limit"], 10);
+    assert_eq!(events_response["data"]["events"].as_array().unwrap().len(), 1);
+    assert!(events_response["data"]["next_cursor"].is_null());
 }
 
 #[tokio::test]
@@ -115,7 +114,7 @@ async fn test_live_events_endpoint() {
 
     assert_eq!(response.status(), StatusCode::OK);
     
-    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
     let events_response: Value = serde_json::from_slice(&body).unwrap();
     
     assert_eq!(events_response["success"], true);
@@ -145,7 +144,7 @@ async fn test_recent_predictions_endpoint() {
 
     assert_eq!(response.status(), StatusCode::OK);
     
-    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
     let predictions_response: Value = serde_json::from_slice(&body).unwrap();
     
     assert_eq!(predictions_response["success"], true);
@@ -178,7 +177,7 @@ async fn test_prediction_by_match_endpoint() {
 
     assert_eq!(response.status(), StatusCode::OK);
     
-    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
     let prediction_response: Value = serde_json::from_slice(&body).unwrap();
     
     assert_eq!(prediction_response["success"], true);
@@ -215,7 +214,7 @@ async fn test_portfolio_endpoint() {
 
     assert_eq!(response.status(), StatusCode::OK);
     
-    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
     let portfolio_response: Value = serde_json::from_slice(&body).unwrap();
     
     assert_eq!(portfolio_response["success"], true);
@@ -254,7 +253,7 @@ async fn test_markets_endpoint() {
 
     assert_eq!(response.status(), StatusCode::OK);
     
-    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
     let markets_response: Value = serde_json::from_slice(&body).unwrap();
     
     assert_eq!(markets_response["success"], true);
@@ -280,7 +279,7 @@ async fn test_pagination_limits() {
         .clone()
         .oneshot(
             Request::builder()
-                .uri("/api/v1/events?page=1&limit=200") // Exceeds max of 100
+                .uri("/api/v1/events?limit=200") // Exceeds max of 100
                 .body(Body::empty())
                 .unwrap(),
         )
@@ -289,16 +288,19 @@ async fn test_pagination_limits() {
 
     assert_eq!(response.status(), StatusCode::OK);
     
-    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
     let events_response: Value = serde_json::from_slice(&body).unwrap();
     
-    assert_eq!(events_response["pagination"]["limit"], 100); // Should be capped at 100
+    // Should be capped at 100
+    assert_eq!(events_response["data"]["events"].as_array().unwrap().len(), 100);
+    let cursor = events_response["data"]["next_cursor"].as_str().unwrap().to_string();
 
-    // Test multiple pages
+    // The next page picks up after the cursor
     let response = app
+        .clone()
         .oneshot(
             Request::builder()
-                .uri("/api/v1/events?page=2&limit=50")
+                .uri(format!("/api/v1/events?limit=100&cursor={}", cursor))
                 .body(Body::empty())
                 .unwrap(),
         )
@@ -307,12 +309,17 @@ async fn test_pagination_limits() {
 
     assert_eq!(response.status(), StatusCode::OK);
     
-    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
     let events_response: Value = serde_json::from_slice(&body).unwrap();
     
-    assert_eq!(events_response["pagination"]["page"], 2);
-    assert_eq!(events_response["pagination"]["limit"], 50);
-    assert!(events_response["pagination"]["total"].as_u64().unwrap() >= 150);
+    assert_eq!(events_response["data"]["events"].as_array().unwrap().len(), 50);
+    assert!(events_response["data"]["next_cursor"].is_null());
+
+    let response = app
+        .oneshot(Request::builder().uri("/api/v1/events?cursor=bogus").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 }
 
 #[tokio::test]
@@ -337,22 +344,22 @@ async fn test_error_handling() {
 #[tokio::test]
 async fn test_concurrent_requests() {
     let app_state = create_test_app_state().await;
-    let app = Arc::new(create_routes().with_state(app_state));
 
     // Add some test data
     for i in 0..10 {
         let mut event = create_test_match_event();
         event.match_id = format!("concurrent_test_{}", i);
-        app.clone().layer().recent_events.write().await.push(event);
+        app_state.recent_events.write().await.push(event);
     }
 
+    let app = create_routes().with_state(app_state);
+
     // Make multiple concurrent requests
     let mut handles = vec![];
     for _ in 0..10 {
         let app_clone = app.clone();
         let handle = tokio::spawn(async move {
             let response = app_clone
-                .clone()
                 .oneshot(
                     Request::builder()
                         .uri("/health")
@@ -372,6 +379,238 @@ async fn test_concurrent_requests() {
     }
 }
 
+#[tokio::test]
+async fn test_clients_over_their_rate_limit_get_429_with_retry_after() {
+    let app_state = create_test_app_state().await;
+    let metrics = app_state.metrics.clone();
+    let config = RateLimitConfig { enabled: true, requests_per_second: 0.5, burst: 2 };
+    let limiter = Arc::new(RateLimiter::new(config).with_metrics(metrics.clone()));
+    let auth = Arc::new(AuthMiddleware::new().with_keys(["bot".to_string(), "dashboard".to_string()]));
+    let app = create_routes()
+        .with_state(app_state)
+        .layer(axum::middleware::from_fn_with_state(limiter, rate_limit))
+        .layer(axum::middleware::from_fn_with_state(auth, authenticate));
+
+    let request = |key: &str| {
+        Request::builder().uri("/api/v1/status").header("x-api-key", key).body(Body::empty()).unwrap()
+    };
+    for _ in 0..2 {
+        assert_eq!(app.clone().oneshot(request("bot")).await.unwrap().status(), StatusCode::OK);
+    }
+    let throttled = app.clone().oneshot(request("bot")).await.unwrap();
+    assert_eq!(throttled.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(throttled.headers()["retry-after"], "2");
+
+    // Other clients and health checks are unaffected
+    assert_eq!(app.clone().oneshot(request("dashboard")).await.unwrap().status(), StatusCode::OK);
+    let health = Request::builder().uri("/health").header("x-api-key", "bot").body(Body::empty()).unwrap();
+    assert_eq!(app.clone().oneshot(health).await.unwrap().status(), StatusCode::OK);
+
+    // Unknown keys share their address's budget, however many are tried
+    for key in ["forged-1", "forged-2"] {
+        assert_eq!(app.clone().oneshot(request(key)).await.unwrap().status(), StatusCode::OK);
+    }
+    assert_eq!(app.oneshot(request("forged-3")).await.unwrap().status(), StatusCode::TOO_MANY_REQUESTS);
+
+    assert_eq!(metrics.get_current_metrics().await.throttled_requests, 2);
+}
+
+#[tokio::test]
+async fn test_replay_endpoint_leaves_live_state_alone() {
+    let app_state = create_test_app_state().await;
+    let kickoff = MatchEvent::new(
+        "test_match_123".to_string(),
+        EventType::MatchStart,
+        "Test Team A".to_string(),
+        "Test Team B".to_string(),
+        "Test League".to_string(),
+        "2024".to_string(),
+    );
+    app_state.recent_events.write().await.extend([kickoff, create_test_match_event()]);
+    let predictor = app_state.predictor.clone();
+    let trading_engine = app_state.trading_engine.clone();
+
+    let app = create_routes().with_state(app_state);
+    let replay = |match_id: &str| {
+        Request::builder()
+            .method("POST")
+            .uri("/api/v1/replay")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({ "match_id": match_id }).to_string()))
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(replay("test_match_123")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let report: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(report["data"]["events_replayed"], 2);
+    assert!(report["data"]["steps"][1]["prediction"].is_object());
+    assert!(report["data"]["steps"][1]["signal"].is_object());
+
+    assert_eq!(predictor.get_prediction_count().await, 0);
+    assert_eq!(trading_engine.get_portfolio_summary().await.total_trades, 0);
+
+    let response = app.oneshot(replay("unknown_match")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_value_bets_endpoint_ranks_and_filters() {
+    let app_state = create_test_app_state().await;
+    app_state.trading_engine.register_match(&create_test_match_event()).await;
+    // Implied 0.333 / 0.278 / 0.3125 against predicted 0.4 / 0.3 / 0.3
+    app_state.trading_engine.update_market_odds(
+        "test_match_123".to_string(),
+        SimpleMarketOdds::new(dec!(3.0), dec!(3.6), dec!(3.2)),
+    ).await;
+    app_state.recent_predictions.write().await.push(create_test_prediction());
+
+    let app = create_routes().with_state(app_state);
+    let value_bets = |query: &str| {
+        let app = app.clone();
+        let uri = format!("/api/v1/value-bets{}", query);
+        async move {
+            let response = app.oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<Value>(&body).unwrap()["data"].as_array().unwrap().clone()
+        }
+    };
+
+    let all = value_bets("").await;
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0]["bet_type"], "HomeWin");
+    assert!(all[0]["expected_value"].as_f64().unwrap() > all[1]["expected_value"].as_f64().unwrap());
+    assert!(all[0]["kelly_fraction"].as_f64().unwrap() > 0.0);
+
+    let edged = value_bets("?min_edge=0.05&league=test%20league").await;
+    assert_eq!(edged.len(), 1);
+    assert!((edged[0]["edge"].as_f64().unwrap() - (0.4 - 1.0 / 3.0)).abs() < 1e-9);
+    assert!(value_bets("?league=Other").await.is_empty());
+}
+
+#[tokio::test]
+async fn test_odds_convert_endpoint() {
+    let app = create_routes().with_state(create_test_app_state().await);
+    let convert = |query: &str| {
+        let app = app.clone();
+        let request = Request::builder().uri(format!("/api/v1/tools/odds/convert?{}", query)).body(Body::empty()).unwrap();
+        async move { app.oneshot(request).await.unwrap() }
+    };
+
+    let response = convert("odds=%2B150&from=american").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let conversion: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(conversion["data"]["decimal"], 2.5);
+    assert_eq!(conversion["data"]["fractional"], "3/2");
+    assert_eq!(conversion["data"]["implied_probability"], 0.4);
+
+    assert_eq!(convert("odds=abc").await.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(convert("odds=1/0&from=fractional").await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_simulate_trade_endpoint() {
+    let app_state = create_test_app_state().await;
+    app_state.trading_engine
+        .update_market_odds("test_match_123".to_string(), SimpleMarketOdds::new(dec!(2.2), dec!(3.6), dec!(4.5)))
+        .await;
+    let trading_engine = app_state.trading_engine.clone();
+    let app = create_routes().with_state(app_state);
+    let simulate = |body: Value| {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/simulate/trade")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let app = app.clone();
+        async move { app.oneshot(request).await.unwrap() }
+    };
+
+    let response = simulate(json!({
+        "match_id": "test_match_123",
+        "prediction": { "home_win_prob": 0.6, "away_win_prob": 0.15, "confidence": 0.9 },
+    })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let simulation: Value = serde_json::from_slice(&body).unwrap();
+    let home = &simulation["data"][0];
+    assert_eq!(home["bet_type"], "HomeWin");
+    assert_eq!(home["stake"], 500.0);
+    assert_eq!(home["binding_constraints"][0]["constraint"], "max_stake_percent");
+    assert!(home["kelly_fraction"].as_f64().unwrap() > 0.0);
+    assert!(simulation["message"].as_str().unwrap().ends_with("on Home Win at 2.2"));
+    assert_eq!(trading_engine.get_portfolio_summary().await.total_trades, 0);
+
+    // No prediction for the match yet, and one that can't be
+    assert_eq!(simulate(json!({ "match_id": "test_match_123" })).await.status(), StatusCode::NOT_FOUND);
+    let impossible = json!({ "match_id": "test_match_123", "prediction": { "home_win_prob": 0.8, "away_win_prob": 0.4 } });
+    assert_eq!(simulate(impossible).await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_fixtures_endpoint() {
+    let state = create_test_app_state().await;
+    let kickoff = "2030-05-18T15:00:00Z".parse().unwrap();
+    state.fixtures.add(vec![Fixture::new(
+        "final".to_string(), "Arsenal".to_string(), "Chelsea".to_string(), "Premier League".to_string(), kickoff,
+    )]).await;
+    let app = create_routes().with_state(state);
+    let fixtures = |query: &str| {
+        let app = app.clone();
+        let request = Request::builder().uri(format!("/api/v1/fixtures{}", query)).body(Body::empty()).unwrap();
+        async move { app.oneshot(request).await.unwrap() }
+    };
+
+    let response = fixtures("?date=2030-05-18").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let day: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(day["data"].as_array().unwrap().len(), 1);
+    assert_eq!(day["data"][0]["match_id"], "final");
+    assert_eq!(day["data"][0]["kickoff"], "2030-05-18T15:00:00Z");
+    assert!(day["data"][0]["prediction"].is_null());
+
+    let body = axum::body::to_bytes(fixtures("?date=2030-05-19").await.into_body(), usize::MAX).await.unwrap();
+    let next_day: Value = serde_json::from_slice(&body).unwrap();
+    assert!(next_day["data"].as_array().unwrap().is_empty());
+
+    assert_eq!(fixtures("?date=tomorrow").await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_jobs_endpoint() {
+    let state = create_test_app_state().await;
+    let unknown = state.jobs.enqueue("no_such_job", Value::Null).await;
+    // Without a handler it fails, and is retried later
+    state.jobs.run_due(unknown.created_at).await;
+    let queued = state.jobs.enqueue("settle_match", json!({ "match_id": "m1", "home_goals": 2, "away_goals": 1 })).await;
+    let app = create_routes().with_state(state);
+    let jobs = |query: &str| {
+        let app = app.clone();
+        let request = Request::builder().uri(format!("/api/v1/jobs{}", query)).body(Body::empty()).unwrap();
+        async move { app.oneshot(request).await.unwrap() }
+    };
+
+    let body = axum::body::to_bytes(jobs("").await.into_body(), usize::MAX).await.unwrap();
+    let all: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(all["data"].as_array().unwrap().len(), 2);
+
+    let body = axum::body::to_bytes(jobs("?status=pending").await.into_body(), usize::MAX).await.unwrap();
+    let pending: Value = serde_json::from_slice(&body).unwrap();
+    let pending = pending["data"].as_array().unwrap();
+    assert_eq!(pending.len(), 2);
+    let retried = pending.iter().find(|job| job["kind"] == "no_such_job").unwrap();
+    assert_eq!(retried["attempts"], 1);
+    assert_eq!(retried["last_error"], "no handler for no_such_job jobs");
+    assert!(pending.iter().any(|job| job["id"] == queued.id.to_string() && job["attempts"] == 0));
+
+    assert_eq!(jobs("?status=done").await.status(), StatusCode::BAD_REQUEST);
+}
+
 // Helper functions
 async fn create_test_app_state() -> AppState {
     let trading_engine = Arc::new(TradingEngine::new(dec!(10000.0)));
@@ -382,49 +621,58 @@ async fn create_test_app_state() -> AppState {
 
     AppState {
         trading_engine,
-        market_simulator,
-        predictor,
+        market_simulator: market_simulator.clone(),
+        predictor: predictor.clone(),
         recent_events,
         recent_predictions,
+        prediction_history: Arc::new(PredictionHistory::new()),
+        prediction_ledger: Arc::new(PredictionLedger::new()),
+        odds_history: Arc::new(OddsHistory::new()),
+        match_summaries: Arc::new(MatchSummaryTracker::new()),
+        feature_store: Arc::new(FeatureStore::new()),
+        search_index: Arc::new(SearchIndex::new()),
+        metrics: Arc::new(MetricsCollector::new()),
+        feed_control: FeedControl::default(),
+        rebuilder: Arc::new(StateRebuilder::new(
+            predictor.get_feature_engineer(),
+            Arc::new(MatchStateMachine::new()),
+            Arc::new(MatchSummaryTracker::new()),
+        )),
+        replayer: Arc::new(EventReplayer::new(predictor.clone(), Arc::new(OddsHistory::new()))),
+        event_log: Arc::new(EventLog::new()),
+        domain_events: DomainEventBus::new(),
+        provenance: Arc::new(Provenance::new("test")),
+        fixtures: Arc::new(FixtureService::new(predictor, market_simulator)),
+        jobs: Arc::new(JobQueue::new(JobsConfig::default())),
     }
 }
 
 fn create_test_match_event() -> MatchEvent {
-    MatchEvent {
-        id: uuid::Uuid::new_v4(),
-        match_id: "test_match_123".to_string(),
-        event_type: EventType::Goal,
-        timestamp: chrono::Utc::now(),
-        sport: Sport::Football,
-        league: "Test League".to_string(),
-        team_home: "Test Team A".to_string(),
-        team_away: "Test Team B".to_string(),
-        score_home: Some(1),
-        score_away: Some(0),
-        minute: Some(45),
-        player: Some("Test Player".to_string()),
-        metadata: std::collections::HashMap::new(),
-    }
+    MatchEvent::new(
+        "test_match_123".to_string(),
+        EventType::Goal {
+            team: "Test Team A".to_string(),
+            player: Some("Test Player".to_string()),
+            minute: 45,
+        },
+        "Test Team A".to_string(),
+        "Test Team B".to_string(),
+        "Test League".to_string(),
+        "2024-25".to_string(),
+    )
+    .with_score(Score { home: 1, away: 0, half_time_home: None, half_time_away: None })
+    .with_status(MatchStatus::Live)
 }
 
 fn create_test_prediction() -> Prediction {
-    use std::collections::HashMap;
-    
-    let mut probabilities = HashMap::new();
-    probabilities.insert(BettingOutcome::HomeWin, 0.4);
-    probabilities.insert(BettingOutcome::Draw, 0.3);
-    probabilities.insert(BettingOutcome::AwayWin, 0.3);
-
-    Prediction {
-        id: uuid::Uuid::new_v4(),
-        match_id: "test_match_123".to_string(),
-        model_version: "test_v1.0".to_string(),
-        timestamp: chrono::Utc::now(),
-        probabilities,
-        confidence: 0.75,
-        expected_value: 0.15,
-        recommended_bet: Some(BettingOutcome::HomeWin),
-        stake_percentage: Some(0.02),
-        metadata: HashMap::new(),
-    }
-}
\ No newline at end of file
+    Prediction::new(
+        "test_match_123".to_string(),
+        "test_model".to_string(),
+        "test_v1.0".to_string(),
+        0.4, // home_win_prob
+        0.3, // away_win_prob
+        chrono::Utc::now(),
+    ).unwrap()
+    .with_draw_prob(0.3).unwrap()
+    .with_confidence(0.75).unwrap()
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use quant_ml::models::{LogisticRegressionModel, PoissonModel, EnsembleModel, Model, ModelFeedback};
-use quant_models::{FeatureVector, BettingOutcome};
+use quant_ml::schema::FeatureSchema;
+use quant_models::FeatureVector;
 use chrono::Utc;
 use uuid::Uuid;
 
@@ -21,7 +22,7 @@ async fn test_logistic_regression_prediction() {
     // Test basic prediction properties
     assert_eq!(prediction.match_id, "test_match_123");
     assert_eq!(prediction.model_version, "v1.0");
-    assert!(prediction.timestamp <= Utc::now());
+    assert!(prediction.prediction_timestamp <= Utc::now());
     
     // Test probability constraints
     assert!(prediction.home_win_prob >= 0.01 && prediction.home_win_prob <= 0.98);
@@ -40,7 +41,7 @@ async fn test_logistic_regression_prediction() {
 
 #[tokio::test]
 async fn test_logistic_regression_different_features() {
-    let model = LogisticRegressionModel::new();
+    let model = strength_model();
     
     // Test with strong home advantage features
     let mut strong_home_features = create_test_feature_vector();
@@ -67,7 +68,7 @@ async fn test_logistic_regression_different_features() {
 async fn test_poisson_model_creation() {
     let model = PoissonModel::new();
     assert_eq!(model.model_name(), "PoissonGoals");
-    assert_eq!(model.model_version(), "v1.0");
+    assert_eq!(model.model_version(), "v2.0");
 }
 
 #[tokio::test]
@@ -79,7 +80,7 @@ async fn test_poisson_model_prediction() {
     
     // Test basic prediction properties
     assert_eq!(prediction.match_id, "test_match_123");
-    assert_eq!(prediction.model_version, "v1.0");
+    assert_eq!(prediction.model_version, "v2.0");
     
     // Test probability constraints
     assert!(prediction.home_win_prob >= 0.01 && prediction.home_win_prob <= 0.98);
@@ -100,12 +101,13 @@ async fn test_poisson_model_prediction() {
 async fn test_poisson_model_attack_defense_features() {
     let model = PoissonModel::new();
     
-    // Test with strong attacking features
+    // Test with strong attacking features. Defense scales the goals a side
+    // concedes, so leaky defenses are the high values.
     let mut high_scoring_features = create_test_feature_vector();
     high_scoring_features.features.insert("home_attack".to_string(), 2.0);
     high_scoring_features.features.insert("away_attack".to_string(), 2.0);
-    high_scoring_features.features.insert("home_defense".to_string(), 0.5);
-    high_scoring_features.features.insert("away_defense".to_string(), 0.5);
+    high_scoring_features.features.insert("home_defense".to_string(), 2.0);
+    high_scoring_features.features.insert("away_defense".to_string(), 2.0);
     
     let high_scoring_pred = model.predict(&high_scoring_features).await.unwrap();
     
@@ -113,8 +115,8 @@ async fn test_poisson_model_attack_defense_features() {
     let mut low_scoring_features = create_test_feature_vector();
     low_scoring_features.features.insert("home_attack".to_string(), 0.5);
     low_scoring_features.features.insert("away_attack".to_string(), 0.5);
-    low_scoring_features.features.insert("home_defense".to_string(), 2.0);
-    low_scoring_features.features.insert("away_defense".to_string(), 2.0);
+    low_scoring_features.features.insert("home_defense".to_string(), 0.5);
+    low_scoring_features.features.insert("away_defense".to_string(), 0.5);
     
     let low_scoring_pred = model.predict(&low_scoring_features).await.unwrap();
     
@@ -158,8 +160,8 @@ async fn test_ensemble_model_prediction() {
 
 #[tokio::test]
 async fn test_ensemble_combines_models() {
-    let ensemble = EnsembleModel::new();
-    let logistic = LogisticRegressionModel::new();
+    let ensemble = EnsembleModel::new().with_logistic_model(LogisticRegressionModel::new().with_seed(42));
+    let logistic = LogisticRegressionModel::new().with_seed(42);
     let poisson = PoissonModel::new();
     
     let features = create_test_feature_vector();
@@ -180,17 +182,18 @@ async fn test_ensemble_combines_models() {
 
 #[tokio::test]
 async fn test_model_feedback_updates() {
-    let mut model = LogisticRegressionModel::new();
+    let mut model = strength_model();
     
     // Get initial weights by making a prediction
     let features = create_test_feature_vector();
     let initial_pred = model.predict(&features).await.unwrap();
     
-    // Create positive feedback
+    // Create positive feedback on that prediction
     let positive_feedback = ModelFeedback {
-        prediction_id: Uuid::new_v4(),
+        prediction_id: initial_pred.id,
         actual_outcome: true,
         reward: 1.0,
+        outcome: None,
     };
     
     // Update model with positive feedback
@@ -201,6 +204,7 @@ async fn test_model_feedback_updates() {
         prediction_id: Uuid::new_v4(),
         actual_outcome: false,
         reward: -0.5,
+        outcome: None,
     };
     
     // Update model with negative feedback
@@ -209,6 +213,8 @@ async fn test_model_feedback_updates() {
     // The model should still produce valid predictions after updates
     let updated_pred = model.predict(&features).await.unwrap();
     
+    // Only the feedback on a stored prediction moves the weights
+    assert_ne!(updated_pred.home_win_prob, initial_pred.home_win_prob);
     assert!(updated_pred.home_win_prob >= 0.01 && updated_pred.home_win_prob <= 0.98);
     assert!(updated_pred.away_win_prob >= 0.01 && updated_pred.away_win_prob <= 0.98);
     assert!(updated_pred.draw_prob.unwrap() >= 0.01 && updated_pred.draw_prob.unwrap() <= 0.98);
@@ -232,12 +238,16 @@ async fn test_poisson_model_weight_updates() {
             prediction_id: Uuid::new_v4(),
             actual_outcome: true,
             reward: 0.8,
+            outcome: None,
         };
         model.update_weights(&feedback).await.unwrap();
     }
     
     let updated_pred = model.predict(&features).await.unwrap();
     
+    // Raising the home goal rate more than the away one favours the home side
+    assert!(updated_pred.home_win_prob > initial_pred.home_win_prob);
+    
     // Model should still produce valid predictions
     assert!(updated_pred.home_win_prob >= 0.01 && updated_pred.home_win_prob <= 0.98);
     assert!(updated_pred.away_win_prob >= 0.01 && updated_pred.away_win_prob <= 0.98);
@@ -338,6 +348,7 @@ async fn test_missing_features() {
     let feature_vector = FeatureVector {
         match_id: "sparse_test_123".to_string(),
         timestamp: Utc::now(),
+        schema: None,
         features: sparse_features,
     };
     
@@ -350,6 +361,15 @@ async fn test_missing_features() {
 }
 
 // Helper function to create test feature vector
+/// A logistic model over a few small-valued features, so its untrained
+/// softmax isn't saturated the way raw ratings such as Elo saturate it
+fn strength_model() -> LogisticRegressionModel {
+    let features = ["home_advantage", "elo_difference", "form_difference"];
+    LogisticRegressionModel::new()
+        .with_schema(FeatureSchema::new("strength", 1, features.iter().map(ToString::to_string).collect()))
+        .with_seed(42)
+}
+
 fn create_test_feature_vector() -> FeatureVector {
     let mut features = HashMap::new();
     
@@ -377,9 +397,9 @@ fn create_test_feature_vector() -> FeatureVector {
     features.insert("away_attack".to_string(), 1.0);
     features.insert("away_defense".to_string(), 0.9);
     
-    // Expected goals
-    features.insert("home_expected_goals".to_string(), 1.3);
-    features.insert("away_expected_goals".to_string(), 1.1);
+    // Expected goals from shots so far
+    features.insert("home_xg".to_string(), 1.3);
+    features.insert("away_xg".to_string(), 1.1);
     
     // Form and discipline
     features.insert("home_form".to_string(), 0.7);
@@ -404,6 +424,7 @@ fn create_test_feature_vector() -> FeatureVector {
     FeatureVector {
         match_id: "test_match_123".to_string(),
         timestamp: Utc::now(),
+        schema: None,
         features,
     }
 }
\ No newline at end of file
@@ -7,28 +7,26 @@ pub mod performance_tests;
 
 // Common test utilities and helpers
 use chrono::Utc;
-use uuid::Uuid;
 use rust_decimal_macros::dec;
 
-use quant_models::{MatchEvent, EventType, Sport, Prediction, SimpleMarketOdds};
+use quant_models::{MatchEvent, EventType, MatchStatus, Prediction, Score, SimpleMarketOdds};
 
 /// Create a standard test match event for consistent testing
 pub fn create_standard_test_event() -> MatchEvent {
-    MatchEvent {
-        id: Uuid::new_v4(),
-        match_id: "standard_test_match".to_string(),
-        event_type: EventType::Goal,
-        timestamp: Utc::now(),
-        sport: Sport::Football,
-        league: "Test League".to_string(),
-        team_home: "Home Team".to_string(),
-        team_away: "Away Team".to_string(),
-        score_home: Some(1),
-        score_away: Some(0),
-        minute: Some(45),
-        player: Some("Test Player".to_string()),
-        metadata: serde_json::Value::Null,
-    }
+    MatchEvent::new(
+        "standard_test_match".to_string(),
+        EventType::Goal {
+            team: "Home Team".to_string(),
+            player: Some("Test Player".to_string()),
+            minute: 45,
+        },
+        "Home Team".to_string(),
+        "Away Team".to_string(),
+        "Test League".to_string(),
+        "2024-25".to_string(),
+    )
+    .with_score(Score { home: 1, away: 0, half_time_home: None, half_time_away: None })
+    .with_status(MatchStatus::Live)
 }
 
 /// Create a standard test prediction for consistent testing
@@ -62,7 +60,7 @@ mod test_helpers {
     fn test_standard_helpers() {
         let event = create_standard_test_event();
         assert_eq!(event.match_id, "standard_test_match");
-        assert_eq!(event.sport, Sport::Football);
+        assert_eq!(event.minute(), Some(45));
         
         let prediction = create_standard_test_prediction();  
         assert_eq!(prediction.match_id, "standard_test_match");
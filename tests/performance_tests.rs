@@ -1,17 +1,16 @@
-use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tokio::time::sleep;
+use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use chrono::Utc;
-use uuid::Uuid;
 
 use quant_services::{
     DataFeedService, DataFeedConfig, PredictorService, TradingEngine, 
     MarketSimulator, MetricsCollector
 };
-use quant_models::{MatchEvent, EventType, Sport, BettingOutcome, Prediction, FeatureVector};
+use quant_models::{MatchEvent, EventType, MatchStatus, Prediction, Score, SimpleMarketOdds};
 
 #[tokio::test]
 async fn test_prediction_latency() {
@@ -218,7 +217,7 @@ async fn test_metrics_collection_performance() {
 
 #[tokio::test]
 async fn test_data_feed_simulation_performance() {
-    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let (sender, mut receiver) = quant_stream::event_queue(&quant_stream::QueueConfig::default(), None);
     
     let config = DataFeedConfig {
         feed_interval_ms: 10, // Very fast for testing
@@ -345,13 +344,13 @@ async fn test_end_to_end_pipeline_performance() {
 
 #[tokio::test]
 async fn test_stress_test_trading_engine() {
-    let engine = TradingEngine::new(dec!(1000000.0)); // Large bankroll
+    let engine = Arc::new(TradingEngine::new(dec!(1000000.0))); // Large bankroll
     
     // Create many predictions simultaneously
     let mut handles = vec![];
     
     for batch in 0..10 {
-        let engine_clone = Arc::new(engine.clone());
+        let engine_clone = engine.clone();
         
         let handle = tokio::spawn(async move {
             let mut successful_trades = 0;
@@ -401,21 +400,20 @@ async fn test_stress_test_trading_engine() {
 
 // Helper functions
 fn create_test_match_event() -> MatchEvent {
-    MatchEvent {
-        id: Uuid::new_v4(),
-        match_id: "perf_test_match".to_string(),
-        event_type: EventType::Goal,
-        timestamp: Utc::now(),
-        sport: Sport::Football,
-        league: "Performance Test League".to_string(),
-        team_home: "Test Team A".to_string(),
-        team_away: "Test Team B".to_string(),
-        score_home: Some(1),
-        score_away: Some(0),
-        minute: Some(45),
-        player: Some("Test Player".to_string()),
-        metadata: HashMap::new(),
-    }
+    MatchEvent::new(
+        "perf_test_match".to_string(),
+        EventType::Goal {
+            team: "Test Team A".to_string(),
+            player: Some("Test Player".to_string()),
+            minute: 45,
+        },
+        "Test Team A".to_string(),
+        "Test Team B".to_string(),
+        "Performance Test League".to_string(),
+        "2024-25".to_string(),
+    )
+    .with_score(Score { home: 1, away: 0, half_time_home: None, half_time_away: None })
+    .with_status(MatchStatus::Live)
 }
 
 fn create_test_events(count: usize) -> Vec<MatchEvent> {
@@ -433,33 +431,27 @@ fn create_test_predictions(count: usize) -> Vec<Prediction> {
 }
 
 fn create_test_prediction_for_match(match_id: &str) -> Prediction {
-    let mut probabilities = HashMap::new();
-    probabilities.insert(BettingOutcome::HomeWin, 0.4 + (rand::random::<f64>() * 0.2));
-    probabilities.insert(BettingOutcome::Draw, 0.25 + (rand::random::<f64>() * 0.1));
-    probabilities.insert(BettingOutcome::AwayWin, 0.35 + (rand::random::<f64>() * 0.15));
+    let home_win = 0.4 + (rand::random::<f64>() * 0.2);
+    let draw = 0.25 + (rand::random::<f64>() * 0.1);
 
-    Prediction {
-        id: Uuid::new_v4(),
-        match_id: match_id.to_string(),
-        model_version: "perf_test_v1.0".to_string(),
-        timestamp: Utc::now(),
-        probabilities,
-        confidence: 0.6 + (rand::random::<f64>() * 0.3),
-        expected_value: rand::random::<f64>() * 0.2,
-        recommended_bet: Some(BettingOutcome::HomeWin),
-        stake_percentage: Some(0.01 + (rand::random::<f64>() * 0.04)),
-        metadata: HashMap::new(),
-    }
+    Prediction::new(
+        match_id.to_string(),
+        "perf_test".to_string(),
+        "perf_test_v1.0".to_string(),
+        home_win,
+        1.0 - home_win - draw,
+        Utc::now(),
+    ).unwrap()
+    .with_draw_prob(draw).unwrap()
+    .with_confidence(0.6 + (rand::random::<f64>() * 0.3)).unwrap()
 }
 
-fn create_test_market_odds(match_id: &str) -> quant_models::SimpleMarketOdds {
-    use quant_models::SimpleMarketOdds;
-    
-    SimpleMarketOdds {
-        match_id: match_id.to_string(),
-        home_win: dec!(1.8) + (dec!(rand::random::<f64>() * 2.0)),
-        draw: dec!(3.0) + (dec!(rand::random::<f64>() * 1.5)),
-        away_win: dec!(2.2) + (dec!(rand::random::<f64>() * 1.8)),
-        last_updated: Utc::now(),
-    }
-}
\ No newline at end of file
+fn create_test_market_odds(_match_id: &str) -> SimpleMarketOdds {
+    let jitter = |range: f64| Decimal::try_from(rand::random::<f64>() * range).unwrap().round_dp(2);
+
+    SimpleMarketOdds::new(
+        dec!(1.8) + jitter(2.0),
+        dec!(3.0) + jitter(1.5),
+        dec!(2.2) + jitter(1.8),
+    )
+}